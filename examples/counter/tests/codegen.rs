@@ -0,0 +1,27 @@
+// Copyright (c) Zefchain Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Exports the Counter application's ABI.
+//!
+//! Run `linera project codegen` to generate typed client bindings from it.
+
+#![cfg(not(target_arch = "wasm32"))]
+
+use linera_sdk::test::TestValidator;
+
+/// Describes the counter's operations, responses and GraphQL schema.
+#[tokio::test(flavor = "multi_thread")]
+async fn export_abi() {
+    let (validator, module_id) =
+        TestValidator::with_current_module::<counter::CounterAbi, (), u64>().await;
+    let mut chain = validator.new_chain().await;
+
+    let application_id = chain
+        .create_application(module_id, (), 0_u64, vec![])
+        .await;
+
+    let description = chain.export_abi(application_id, "counter").await;
+    assert_eq!(description.name, "counter");
+    assert!(description.formats.is_empty());
+    assert!(description.graphql["__schema"]["queryType"].is_object());
+}
@@ -0,0 +1,165 @@
+// Copyright (c) Zefchain Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Integration tests for the Counter application on a network of several validators.
+
+#![cfg(not(target_arch = "wasm32"))]
+
+use linera_sdk::{
+    linera_base_types::{ApplicationId, Round, TimeDelta, TimeoutConfig},
+    test::{ActiveChain, TestValidator, TryProposeBlockError, ValidatorBehavior},
+};
+
+/// Creates a network of four validators with two shards each, and a chain with a counter
+/// initialized to `initial_value`.
+async fn setup(
+    initial_value: u64,
+) -> (
+    TestValidator,
+    ActiveChain,
+    ApplicationId<counter::CounterAbi>,
+) {
+    let validator = TestValidator::with_validators(4, 2).await;
+    let publisher = validator.new_chain().await;
+    let module_id = publisher
+        .publish_current_module::<counter::CounterAbi, (), u64>()
+        .await;
+    let mut chain = validator.new_chain().await;
+    let application_id = chain
+        .create_application(module_id, (), initial_value, vec![])
+        .await;
+    (validator, chain, application_id)
+}
+
+/// Reads the counter's value on the `chain`.
+async fn counter_value(
+    chain: &ActiveChain,
+    application_id: ApplicationId<counter::CounterAbi>,
+) -> u64 {
+    let response = chain
+        .graphql_query(application_id, "query { value }")
+        .await
+        .response;
+    response["value"].as_u64().expect("Failed to get the u64")
+}
+
+/// Tests that a block is confirmed as long as a quorum of the validators votes for it, and that
+/// a validator that was offline catches up once it is back.
+#[tokio::test(flavor = "multi_thread")]
+async fn block_is_confirmed_despite_one_faulty_validator() {
+    let (validator, chain, application_id) = setup(42).await;
+
+    validator
+        .set_validator_behavior(0, ValidatorBehavior::WithholdVotes)
+        .await;
+    chain
+        .propose_block(|block| {
+            block.with_operation(application_id, 15);
+        })
+        .await;
+    assert_eq!(counter_value(&chain, application_id).await, 57);
+
+    validator
+        .set_validator_behavior(0, ValidatorBehavior::Honest)
+        .await;
+    validator
+        .set_validator_behavior(3, ValidatorBehavior::Offline)
+        .await;
+    chain
+        .propose_block(|block| {
+            block.with_operation(application_id, 3);
+        })
+        .await;
+    assert_eq!(counter_value(&chain, application_id).await, 60);
+
+    // The next block needs the vote of the validator that was offline.
+    validator
+        .set_validator_behavior(3, ValidatorBehavior::Honest)
+        .await;
+    validator
+        .set_validator_behavior(1, ValidatorBehavior::Offline)
+        .await;
+    chain
+        .propose_block(|block| {
+            block.with_operation(application_id, 1);
+        })
+        .await;
+    assert_eq!(counter_value(&chain, application_id).await, 61);
+}
+
+/// Tests that no block is confirmed if too many validators withhold their votes.
+#[tokio::test(flavor = "multi_thread")]
+async fn block_is_not_confirmed_without_quorum() {
+    let (validator, chain, application_id) = setup(42).await;
+
+    validator
+        .set_validator_behavior(1, ValidatorBehavior::WithholdVotes)
+        .await;
+    validator
+        .set_validator_behavior(2, ValidatorBehavior::Offline)
+        .await;
+
+    let result = chain
+        .try_propose_block(|block| {
+            block.with_operation(application_id, 15);
+        })
+        .await;
+    assert!(matches!(
+        result,
+        Err(TryProposeBlockError::NoQuorum { round: Round::Fast })
+    ));
+    assert_eq!(counter_value(&chain, application_id).await, 42);
+}
+
+/// Tests that a multi-owner chain's round times out according to the validators' clock.
+#[tokio::test(flavor = "multi_thread")]
+async fn round_times_out() {
+    let (validator, chain, application_id) = setup(42).await;
+    let owner = chain.public_key().into();
+    let base_timeout = TimeDelta::from_secs(10);
+
+    chain
+        .propose_block(|block| {
+            block.with_owner_change(
+                vec![],
+                vec![(owner, 100)],
+                1,
+                false,
+                TimeoutConfig {
+                    base_timeout,
+                    ..TimeoutConfig::default()
+                },
+            );
+        })
+        .await;
+    assert_eq!(chain.current_round().await, Round::MultiLeader(0));
+
+    chain
+        .propose_block(|block| {
+            block.with_operation(application_id, 15);
+        })
+        .await;
+    assert_eq!(counter_value(&chain, application_id).await, 57);
+
+    // The validators don't vote for a timeout before the round's deadline.
+    assert!(matches!(
+        chain.request_timeout().await,
+        Err(TryProposeBlockError::NoQuorum {
+            round: Round::MultiLeader(0)
+        })
+    ));
+
+    validator.clock().add(base_timeout);
+    chain
+        .request_timeout()
+        .await
+        .expect("Failed to time out the round");
+    assert_eq!(chain.current_round().await, Round::SingleLeader(0));
+
+    chain
+        .propose_block(|block| {
+            block.with_operation(application_id, 3);
+        })
+        .await;
+    assert_eq!(counter_value(&chain, application_id).await, 60);
+}
@@ -0,0 +1,35 @@
+// Copyright (c) Zefchain Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Snapshot tests for the Counter application's GraphQL service.
+//!
+//! Run `linera project test --update-snapshots` to record the snapshots again after changing the
+//! service.
+
+#![cfg(not(target_arch = "wasm32"))]
+
+use linera_sdk::test::TestValidator;
+
+/// Checks the counter's GraphQL schema and a query response against their snapshots.
+#[tokio::test(flavor = "multi_thread")]
+async fn service_snapshots() {
+    let (validator, module_id) =
+        TestValidator::with_current_module::<counter::CounterAbi, (), u64>().await;
+    let mut chain = validator.new_chain().await;
+
+    let application_id = chain
+        .create_application(module_id, (), 42_u64, vec![])
+        .await;
+    chain
+        .add_block(|block| {
+            block.with_operation(application_id, 15_u64);
+        })
+        .await;
+
+    chain
+        .assert_graphql_schema_snapshot(application_id, "counter_schema")
+        .await;
+    chain
+        .assert_graphql_query_snapshot(application_id, "counter_value", "query { value }")
+        .await;
+}
@@ -10,7 +10,7 @@ use fungible::{
 };
 use linera_sdk::{
     linera_base_types::{AccountOwner, Amount},
-    test::{MessageAction, TestValidator},
+    test::{MessageAction, TestValidator, ValidatorBehavior},
 };
 
 /// Test transferring tokens across microchains.
@@ -134,3 +134,64 @@ async fn test_bouncing_tokens() {
         Some(initial_amount),
     );
 }
+
+/// Test transferring tokens across microchains while the validators delay their cross-chain
+/// requests.
+///
+/// Runs four validators with three shards each. The transferred tokens only become available on
+/// the `receiver_chain` once the validators deliver the delayed cross-chain requests.
+#[tokio::test(flavor = "multi_thread")]
+async fn test_delayed_cross_chain_transfer() {
+    let initial_amount = Amount::from_tokens(20);
+    let transfer_amount = Amount::from_tokens(15);
+
+    let validator = TestValidator::with_validators(4, 3).await;
+    let publisher_chain = validator.new_chain().await;
+    let module_id = publisher_chain
+        .publish_current_module::<FungibleTokenAbi, Parameters, InitialState>()
+        .await;
+    let mut sender_chain = validator.new_chain().await;
+    let sender_account = AccountOwner::from(sender_chain.public_key());
+
+    let initial_state = InitialStateBuilder::default().with_account(sender_account, initial_amount);
+    let params = Parameters::new("DLY");
+    let application_id = sender_chain
+        .create_application(module_id, params, initial_state.build(), vec![])
+        .await;
+
+    let receiver_chain = validator.new_chain().await;
+    let receiver_account = AccountOwner::from(receiver_chain.public_key());
+
+    for index in 0..validator.validator_count() {
+        validator
+            .set_validator_behavior(index, ValidatorBehavior::DelayCrossChainRequests)
+            .await;
+    }
+
+    sender_chain
+        .add_block(|block| {
+            block.with_operation(
+                application_id,
+                Operation::Transfer {
+                    owner: sender_account,
+                    amount: transfer_amount,
+                    target_account: Account {
+                        chain_id: receiver_chain.id(),
+                        owner: receiver_account,
+                    },
+                },
+            );
+        })
+        .await;
+
+    // The transfer hasn't reached the receiver's inbox yet, so there is nothing to receive.
+    receiver_chain.handle_received_messages().await;
+
+    validator.deliver_delayed_cross_chain_requests().await;
+    receiver_chain.handle_received_messages().await;
+
+    assert_eq!(
+        fungible::query_account(application_id, &receiver_chain, receiver_account).await,
+        Some(transfer_amount),
+    );
+}
@@ -0,0 +1,92 @@
+// Copyright (c) Zefchain Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Property-based tests for the Fungible Token application.
+
+#![cfg(not(target_arch = "wasm32"))]
+
+use fungible::{Account, FungibleTokenAbi, InitialState, Operation, Parameters};
+use linera_sdk::{
+    fuzz::{proptest::prelude::*, ContractFuzzer, FuzzEnvironment},
+    linera_base_types::{AccountOwner, Amount},
+};
+
+/// Tests that random transfers, including rejected and therefore bouncing ones, never create
+/// tokens.
+#[test]
+fn fuzz_transfers_never_create_tokens() {
+    transfers_fuzzer().with_cases(16).run();
+}
+
+/// Tests that the fuzzer can also run inside an asynchronous test.
+#[tokio::test]
+async fn fuzz_transfers_in_async_test() {
+    transfers_fuzzer().with_cases(2).run_async().await;
+}
+
+/// Returns a [`ContractFuzzer`] generating random transfers, and checking that they never create
+/// tokens.
+fn transfers_fuzzer() -> ContractFuzzer<FungibleTokenAbi> {
+    let initial_amount = Amount::from_tokens(100);
+    let total_supply = Amount::from_tokens(300);
+
+    ContractFuzzer::<FungibleTokenAbi>::new(move |validator| async move {
+        let publisher = validator.new_chain().await;
+        let module_id = publisher
+            .publish_current_module::<FungibleTokenAbi, Parameters, InitialState>()
+            .await;
+        let (application_id, accounts) =
+            fungible::create_with_accounts(&validator, module_id, [initial_amount; 3]).await;
+        let chains = accounts.into_iter().map(|(chain, _, _)| chain).collect();
+        FuzzEnvironment::new(validator, application_id, chains)
+    })
+    .with_operations(|environment| {
+        let accounts = accounts(environment);
+        let amounts = (0_u128..=150).prop_map(Amount::from_tokens);
+        (
+            prop::sample::select(accounts.clone()),
+            prop::sample::select(accounts),
+            amounts,
+        )
+            .prop_map(|(source, target_account, amount)| Operation::Transfer {
+                owner: source.owner,
+                amount,
+                target_account,
+            })
+            .boxed()
+    })
+    .with_invariant("tokens are never created", move |environment| {
+        Box::pin(async move {
+            let mut total = Amount::ZERO;
+            for chain in &environment.chains {
+                for account in accounts(environment) {
+                    let balance =
+                        fungible::query_account(environment.application_id, chain, account.owner)
+                            .await
+                            .unwrap_or_default();
+                    total.saturating_add_assign(balance);
+                }
+            }
+            if total <= total_supply {
+                Ok(())
+            } else {
+                Err(format!("{total} tokens exist, more than {total_supply}"))
+            }
+        })
+    })
+    // The contract rejects transfers from unauthenticated owners and transfers exceeding the
+    // balance by panicking.
+    .with_panics_as_rejections()
+}
+
+/// Returns the accounts of the owners of the chains.
+fn accounts(environment: &FuzzEnvironment<FungibleTokenAbi>) -> Vec<Account> {
+    environment
+        .chains
+        .iter()
+        .map(|chain| Account {
+            chain_id: chain.id(),
+            owner: AccountOwner::from(chain.public_key()),
+        })
+        .collect()
+}
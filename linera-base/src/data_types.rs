@@ -22,6 +22,7 @@ use custom_debug_derive::Debug;
 use linera_witty::{WitLoad, WitStore, WitType};
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use thiserror::Error;
+use url::Url;
 
 #[cfg(with_metrics)]
 use crate::prometheus_util::MeasureLatency as _;
@@ -918,6 +919,16 @@ pub struct ApplicationPermissions {
     #[graphql(default)]
     #[debug(skip_if = Option::is_none)]
     pub make_http_requests: Option<Vec<ApplicationId>>,
+    /// Restrictions on the HTTP requests of individual applications. Applications without a
+    /// policy are only subject to the committee's limits.
+    ///
+    /// Permissions written in self-describing formats (e.g. JSON) before this field existed
+    /// deserialize with no policies. The BCS encoding, and therefore the IDs of chains
+    /// created with these permissions, includes the field.
+    #[graphql(default)]
+    #[serde(default)]
+    #[debug(skip_if = Vec::is_empty)]
+    pub http_policies: Vec<HttpPolicy>,
 }
 
 impl ApplicationPermissions {
@@ -931,6 +942,7 @@ impl ApplicationPermissions {
             change_application_permissions: vec![app_id],
             call_service_as_oracle: Some(vec![app_id]),
             make_http_requests: Some(vec![app_id]),
+            http_policies: Vec::new(),
         }
     }
 
@@ -944,6 +956,7 @@ impl ApplicationPermissions {
             change_application_permissions: app_ids.clone(),
             call_service_as_oracle: Some(app_ids.clone()),
             make_http_requests: Some(app_ids),
+            http_policies: Vec::new(),
         }
     }
 
@@ -982,6 +995,103 @@ impl ApplicationPermissions {
             .map(|app_ids| app_ids.contains(app_id))
             .unwrap_or(true)
     }
+
+    /// Returns the HTTP policy of the given application, if there is one.
+    pub fn http_policy(&self, app_id: &ApplicationId) -> Option<&HttpPolicy> {
+        self.http_policies
+            .iter()
+            .find(|policy| policy.application_id == *app_id)
+    }
+}
+
+/// Restrictions on the HTTP requests an application can make on a chain, in addition to the
+/// limits of the committee's resource control policy.
+#[derive(
+    Debug,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    Clone,
+    Serialize,
+    Deserialize,
+    WitType,
+    WitLoad,
+    WitStore,
+    InputObject,
+)]
+pub struct HttpPolicy {
+    /// The application this policy applies to.
+    pub application_id: ApplicationId,
+    /// If non-empty, requested URLs must match one of these prefixes: they must have the same
+    /// scheme, host and port, and their normalized path must start with the path segments of
+    /// the prefix. E.g. `https://api.example.com/v1` allows `https://api.example.com/v1/price`
+    /// but not `https://api.example.com/v10` or `https://api.example.com/v1/../admin`.
+    #[graphql(default)]
+    #[debug(skip_if = Vec::is_empty)]
+    pub url_prefixes: Vec<String>,
+    /// If non-empty, only these HTTP methods are allowed.
+    #[graphql(default)]
+    #[debug(skip_if = Vec::is_empty)]
+    pub methods: Vec<http::Method>,
+    /// The maximum size of a request, including its URL, headers and body.
+    #[debug(skip_if = Option::is_none)]
+    pub maximum_request_bytes: Option<u64>,
+    /// The maximum size of a response, including its headers and body.
+    #[debug(skip_if = Option::is_none)]
+    pub maximum_response_bytes: Option<u64>,
+    /// The maximum number of HTTP requests the application can make in a single block.
+    #[debug(skip_if = Option::is_none)]
+    pub maximum_requests_per_block: Option<u32>,
+}
+
+impl HttpPolicy {
+    /// Returns whether the policy allows the URL and method of the given request.
+    pub fn allows(&self, request: &http::Request) -> bool {
+        (self.url_prefixes.is_empty() || self.allows_url(&request.url))
+            && (self.methods.is_empty() || self.methods.contains(&request.method))
+    }
+
+    /// Returns whether the URL matches one of the prefixes, comparing them as parsed URLs, so
+    /// that the check applies to the URL that is actually requested.
+    fn allows_url(&self, url: &str) -> bool {
+        let Ok(url) = Url::parse(url) else {
+            return false;
+        };
+        self.url_prefixes.iter().any(|prefix| {
+            Url::parse(prefix).is_ok_and(|prefix| Self::url_has_prefix(&url, &prefix))
+        })
+    }
+
+    /// Returns whether `url` has the same origin and credentials as `prefix`, and a path
+    /// starting with the segments of the prefix's path.
+    fn url_has_prefix(url: &Url, prefix: &Url) -> bool {
+        if url.scheme() != prefix.scheme()
+            || url.host() != prefix.host()
+            || url.port_or_known_default() != prefix.port_or_known_default()
+            || url.username() != prefix.username()
+            || url.password() != prefix.password()
+            || prefix
+                .query()
+                .is_some_and(|query| url.query() != Some(query))
+        {
+            return false;
+        }
+        let (Some(segments), Some(prefix_segments)) = (url.path_segments(), prefix.path_segments())
+        else {
+            return false;
+        };
+        // A trailing slash in the prefix only marks the end of the last segment.
+        let mut prefix_segments = prefix_segments.collect::<Vec<_>>();
+        if prefix_segments.last() == Some(&"") {
+            prefix_segments.pop();
+        }
+        let mut segments = segments;
+        prefix_segments
+            .into_iter()
+            .all(|prefix_segment| segments.next() == Some(prefix_segment))
+    }
 }
 
 /// A record of a single oracle response.
@@ -1003,6 +1113,10 @@ pub enum OracleResponse {
     Round(Option<u32>),
     /// An event was read.
     Event(EventId, Vec<u8>),
+    /// Random bytes derived from the block's randomness beacon.
+    Random(#[debug(with = "hex_debug")] [u8; 32]),
+    /// The response to a query to an application on another chain, at the given height.
+    RemoteQuery(ChainId, BlockHeight, Vec<u8>),
 }
 
 impl BcsHashable<'_> for OracleResponse {}
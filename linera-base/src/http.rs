@@ -3,14 +3,23 @@
 
 //! Types used when performing HTTP requests.
 
+use std::borrow::Cow;
+
 use custom_debug_derive::Debug;
 use linera_witty::{WitLoad, WitStore, WitType};
 use serde::{Deserialize, Serialize};
 
-use crate::hex_debug;
+use crate::{
+    crypto::{
+        BcsSignable, CryptoError, ValidatorPublicKey, ValidatorSecretKey, ValidatorSignature,
+    },
+    data_types::BlockHeight,
+    hex_debug,
+    identifiers::ChainId,
+};
 
 /// An HTTP request.
-#[derive(Clone, Debug, Eq, PartialEq, WitLoad, WitStore, WitType)]
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize, WitLoad, WitStore, WitType)]
 #[witty(name = "http-request")]
 pub struct Request {
     /// The [`Method`] used for the HTTP request.
@@ -24,6 +33,7 @@ pub struct Request {
 
     /// The body of the request.
     #[debug(with = "hex_debug")]
+    #[serde(with = "serde_bytes")]
     pub body: Vec<u8>,
 }
 
@@ -67,10 +77,36 @@ impl Request {
         self.headers.push(Header::new(name, value));
         self
     }
+
+    /// Returns the size of this [`Request`] in bytes, counting its URL, headers and body.
+    pub fn size(&self) -> u64 {
+        let header_size = self
+            .headers
+            .iter()
+            .map(|header| header.name.len() + header.value.len())
+            .sum::<usize>();
+        (self.url.len() + header_size + self.body.len()) as u64
+    }
 }
 
 /// The method used in an HTTP request.
-#[derive(Clone, Copy, Debug, Eq, PartialEq, WitLoad, WitStore, WitType)]
+#[derive(
+    Clone,
+    Copy,
+    Debug,
+    Deserialize,
+    Eq,
+    Hash,
+    Ord,
+    PartialEq,
+    PartialOrd,
+    Serialize,
+    WitLoad,
+    WitStore,
+    WitType,
+    async_graphql::Enum,
+)]
+#[graphql(name = "HttpMethod")]
 #[witty(name = "http-method")]
 pub enum Method {
     /// A GET request.
@@ -167,6 +203,16 @@ impl Response {
         self.headers.push(Header::new(name, value));
         self
     }
+
+    /// Returns the size of this [`Response`] in bytes, counting its headers and body.
+    pub fn size(&self) -> u64 {
+        let header_size = self
+            .headers
+            .iter()
+            .map(|header| header.name.len() + header.value.len())
+            .sum::<usize>();
+        (header_size + self.body.len()) as u64
+    }
 }
 
 /// A header for an HTTP request or response.
@@ -191,3 +237,216 @@ impl Header {
         }
     }
 }
+
+/// How the responses of several validators to the same HTTP request are reduced to a
+/// single [`Response`].
+///
+/// Before reducing, the headers of every response are canonicalized: only the headers
+/// returned identically by all validators are kept, sorted by name and value. Headers
+/// that vary between validators, such as `Date`, are therefore dropped instead of making
+/// the responses disagree.
+#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize, WitLoad, WitStore, WitType)]
+#[witty(name = "http-reducer")]
+pub enum Reducer {
+    /// All the responses must be identical.
+    ExactMatch,
+
+    /// The responses must have JSON bodies, and the numbers found at each of the given
+    /// [JSON pointers](https://datatracker.ietf.org/doc/html/rfc6901) are replaced with
+    /// their median, weighted by the validators' votes. The rest of the responses must be
+    /// identical.
+    Median(Vec<String>),
+
+    /// The response returned by validators with more than half of the votes.
+    Majority,
+}
+
+impl Reducer {
+    /// Reduces the given responses, each with the voting weight of the validator that
+    /// returned it. Returns `None` if the responses cannot be reconciled.
+    pub fn reduce(&self, responses: &[(&Response, u64)]) -> Option<Response> {
+        let headers = common_headers(responses);
+        let responses = responses
+            .iter()
+            .map(|(response, weight)| {
+                let response = Response {
+                    status: response.status,
+                    headers: headers.clone(),
+                    body: response.body.clone(),
+                };
+                (response, *weight)
+            })
+            .collect::<Vec<_>>();
+        let (first, _) = responses.first()?;
+        let total_weight = responses.iter().map(|(_, weight)| weight).sum::<u64>();
+        match self {
+            Reducer::ExactMatch => responses
+                .iter()
+                .all(|(response, _)| response == first)
+                .then(|| first.clone()),
+            Reducer::Majority => responses
+                .iter()
+                .map(|(candidate, _)| {
+                    let weight = responses
+                        .iter()
+                        .filter(|(response, _)| response == candidate)
+                        .map(|(_, weight)| weight)
+                        .sum::<u64>();
+                    (candidate, weight)
+                })
+                .find(|(_, weight)| weight.saturating_mul(2) > total_weight)
+                .map(|(response, _)| response.clone()),
+            Reducer::Median(pointers) => {
+                if responses
+                    .iter()
+                    .any(|(response, _)| response.status != first.status)
+                {
+                    return None;
+                }
+                let documents = responses
+                    .iter()
+                    .map(|(response, weight)| {
+                        let document = serde_json::from_slice::<serde_json::Value>(&response.body);
+                        Some((document.ok()?, *weight))
+                    })
+                    .collect::<Option<Vec<_>>>()?;
+                // Apart from the reduced numbers, all documents must agree, so that no
+                // single validator decides the rest of the response.
+                let mut reduced = without_pointers(&documents[0].0, pointers)?;
+                for (document, _) in &documents[1..] {
+                    if without_pointers(document, pointers)? != reduced {
+                        return None;
+                    }
+                }
+                for pointer in pointers {
+                    let mut values = documents
+                        .iter()
+                        .map(|(document, weight)| {
+                            let value = document.pointer(pointer)?;
+                            Some((value.as_f64()?, value, *weight))
+                        })
+                        .collect::<Option<Vec<_>>>()?;
+                    values.sort_by(|(a, _, _), (b, _, _)| a.total_cmp(b));
+                    let mut cumulative_weight = 0u64;
+                    let (_, median, _) = values.into_iter().find(|(_, _, weight)| {
+                        cumulative_weight = cumulative_weight.saturating_add(*weight);
+                        cumulative_weight.saturating_mul(2) >= total_weight
+                    })?;
+                    *reduced.pointer_mut(pointer)? = median.clone();
+                }
+                Some(Response {
+                    status: first.status,
+                    headers,
+                    body: serde_json::to_vec(&reduced).ok()?,
+                })
+            }
+        }
+    }
+}
+
+/// Returns the headers present with the same value in all `responses`, sorted by name and
+/// value. Header names are compared case-insensitively.
+fn common_headers(responses: &[(&Response, u64)]) -> Vec<Header> {
+    let normalized = |response: &Response| {
+        response
+            .headers
+            .iter()
+            .map(|header| Header::new(header.name.to_ascii_lowercase(), header.value.clone()))
+            .collect::<Vec<_>>()
+    };
+    let Some((first, _)) = responses.first() else {
+        return Vec::new();
+    };
+    let others = responses[1..]
+        .iter()
+        .map(|(response, _)| normalized(response))
+        .collect::<Vec<_>>();
+    let mut headers = normalized(first)
+        .into_iter()
+        .filter(|header| others.iter().all(|headers| headers.contains(header)))
+        .collect::<Vec<_>>();
+    headers.sort_by(|a, b| (&a.name, &a.value).cmp(&(&b.name, &b.value)));
+    headers.dedup();
+    headers
+}
+
+/// Returns a copy of `document` where the values at the given `pointers` are replaced with
+/// `null`, or `None` if one of them is missing.
+fn without_pointers(
+    document: &serde_json::Value,
+    pointers: &[String],
+) -> Option<serde_json::Value> {
+    let mut document = document.clone();
+    for pointer in pointers {
+        *document.pointer_mut(pointer)? = serde_json::Value::Null;
+    }
+    Some(document)
+}
+
+/// A [`Response`] to an HTTP request, signed by a validator for a given block height of a
+/// chain.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct SignedResponse {
+    /// The validator that performed the request.
+    pub validator: ValidatorPublicKey,
+
+    /// The response received by the validator.
+    pub response: Response,
+
+    /// The validator's signature over the chain, the block height, the request and the
+    /// response.
+    pub signature: ValidatorSignature,
+}
+
+/// The value signed by a validator in a [`SignedResponse`].
+#[derive(Debug, Deserialize, Serialize)]
+struct ResponseVote<'a> {
+    chain_id: ChainId,
+    height: BlockHeight,
+    request: Cow<'a, Request>,
+    response: Cow<'a, Response>,
+}
+
+impl BcsSignable<'_> for ResponseVote<'_> {}
+
+impl SignedResponse {
+    /// Signs the `response` received for the `request` made on behalf of the block at
+    /// `height` of the chain `chain_id`.
+    pub fn new(
+        chain_id: ChainId,
+        height: BlockHeight,
+        request: &Request,
+        response: Response,
+        secret: &ValidatorSecretKey,
+    ) -> Self {
+        let vote = ResponseVote {
+            chain_id,
+            height,
+            request: Cow::Borrowed(request),
+            response: Cow::Borrowed(&response),
+        };
+        let signature = ValidatorSignature::new(&vote, secret);
+        SignedResponse {
+            validator: secret.public(),
+            response,
+            signature,
+        }
+    }
+
+    /// Checks that the validator signed this response for the given chain, block height
+    /// and request.
+    pub fn check(
+        &self,
+        chain_id: ChainId,
+        height: BlockHeight,
+        request: &Request,
+    ) -> Result<(), CryptoError> {
+        let vote = ResponseVote {
+            chain_id,
+            height,
+            request: Cow::Borrowed(request),
+            response: Cow::Borrowed(&self.response),
+        };
+        self.signature.check(&vote, &self.validator)
+    }
+}
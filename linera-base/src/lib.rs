@@ -33,6 +33,7 @@ pub mod ownership;
 pub mod port;
 #[cfg(with_metrics)]
 pub mod prometheus_util;
+pub mod randomness;
 #[cfg(not(chain))]
 pub mod task;
 pub mod vm;
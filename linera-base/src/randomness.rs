@@ -0,0 +1,582 @@
+// Copyright (c) Zefchain Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Types used by the randomness beacon, which provides unbiased random bytes to
+//! applications.
+//!
+//! The beacon is a threshold BLS signature scheme over the BLS12-381 curve. When a
+//! committee is created, a dealer splits a secret key into shares, one per vote of each
+//! validator, such that any set of shares whose number reaches the committee's quorum
+//! threshold can combine their signatures into the signature of the secret key. BLS
+//! signatures are unique, so the combined signature, and therefore the seed derived from
+//! it, does not depend on which validators contributed their shares: a block proposer
+//! cannot choose among several seeds by selecting the shares.
+//!
+//! The signed message is the chain ID, the block height and the round in which the block is
+//! proposed, so a proposer only obtains a single seed per round.
+
+use std::{
+    collections::BTreeMap,
+    fmt,
+    hash::{Hash, Hasher},
+};
+
+use ark_bls12_381::{Bls12_381, Fr, G1Affine, G1Projective, G2Affine, G2Projective};
+use ark_ec::{
+    hashing::{
+        curve_maps::wb::WBMap, map_to_curve_hasher::MapToCurveBasedHasher, HashToCurve as _,
+    },
+    pairing::Pairing as _,
+    AffineRepr as _, CurveGroup as _,
+};
+use ark_ff::{field_hashers::DefaultFieldHasher, Field as _, PrimeField as _, Zero as _};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::{
+    crypto::{BcsHashable, CryptoHash, ValidatorPublicKey},
+    data_types::{BlockHeight, Round},
+    identifiers::{ApplicationId, ChainId},
+};
+
+/// The domain separation tag for hashing the signed messages to the curve.
+const HASH_TO_CURVE_DOMAIN: &[u8] =
+    b"LINERA-RANDOMNESS-V01-CS01-with-BLS12381G1_XMD:SHA3-256_SSWU_RO_";
+
+/// Hashes messages to points of the signature group.
+type MessageHasher = MapToCurveBasedHasher<
+    G1Projective,
+    DefaultFieldHasher<sha3::Sha3_256, 128>,
+    WBMap<ark_bls12_381::g1::Config>,
+>;
+
+/// An error in producing or combining randomness shares.
+#[derive(Debug, Error)]
+pub enum RandomnessError {
+    #[error("Several randomness shares from validator {0:?}")]
+    DuplicateShare(ValidatorPublicKey),
+    #[error("Invalid randomness share by {0:?}")]
+    InvalidShare(ValidatorPublicKey),
+    #[error("Only {count} randomness key shares, below the threshold of {threshold}")]
+    SharesBelowThreshold { count: u64, threshold: u64 },
+    #[error("The combined randomness signature is invalid")]
+    InvalidSignature,
+    #[error("Failed to hash the randomness message to the curve")]
+    HashToCurve,
+    #[error("Invalid encoding of a randomness key or signature")]
+    InvalidEncoding,
+}
+
+/// A public key of the randomness beacon: either the committee's key or the public key of a
+/// key share.
+#[derive(Clone, Copy, Eq, PartialEq)]
+pub struct RandomnessPublicKey(G2Affine);
+
+/// A signature of the randomness beacon: either the committee's signature or a signature
+/// by a key share.
+#[derive(Clone, Copy, Eq, PartialEq)]
+pub struct RandomnessSignature(G1Affine);
+
+/// A validator's shares of the committee's randomness secret key, one per vote.
+#[derive(Clone, Eq, PartialEq)]
+pub struct RandomnessSecretKey(Vec<Fr>);
+
+/// The public keys of a committee's randomness beacon.
+#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub struct RandomnessPublicKeys {
+    /// The committee's public key, which verifies the combined signatures.
+    pub public_key: RandomnessPublicKey,
+    /// The public keys of each validator's key shares. The shares are numbered from 1, in
+    /// the order of the validators.
+    pub shares: BTreeMap<ValidatorPublicKey, Vec<RandomnessPublicKey>>,
+    /// The number of key shares whose signatures are combined into the committee's
+    /// signature.
+    pub threshold: u64,
+}
+
+/// A validator's share of the randomness for a block of a chain in a given round.
+///
+/// The share contains a signature of each of the validator's key shares over the chain ID,
+/// the block height and the round.
+#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub struct RandomnessShare {
+    /// The validator that produced this share.
+    pub validator: ValidatorPublicKey,
+
+    /// The signatures of the validator's key shares, in order.
+    pub signatures: Vec<RandomnessSignature>,
+}
+
+/// The message signed by the validators' key shares.
+#[derive(Debug, Serialize)]
+struct RandomnessMessage {
+    chain_id: ChainId,
+    height: BlockHeight,
+    round: Round,
+}
+
+impl RandomnessMessage {
+    /// Returns the point of the signature group that is signed.
+    fn hash_to_curve(&self) -> Result<G1Affine, RandomnessError> {
+        let bytes = bcs::to_bytes(self).expect("serialization should not fail");
+        MessageHasher::new(HASH_TO_CURVE_DOMAIN)
+            .and_then(|hasher| hasher.hash(&bytes))
+            .map_err(|_| RandomnessError::HashToCurve)
+    }
+}
+
+impl RandomnessPublicKeys {
+    /// Creates a new secret key for a committee with the given validators and votes, and
+    /// splits it into one share per vote, such that the signatures of any `threshold`
+    /// shares can be combined.
+    ///
+    /// Returns the public keys and each validator's secret key shares. The dealer must
+    /// forget the secret key shares once they are handed over to the validators.
+    pub fn deal<R: rand::RngCore + rand::CryptoRng + ?Sized>(
+        validators: impl IntoIterator<Item = (ValidatorPublicKey, u64)>,
+        threshold: u64,
+        rng: &mut R,
+    ) -> (Self, BTreeMap<ValidatorPublicKey, RandomnessSecretKey>) {
+        assert!(threshold > 0, "the threshold must be positive");
+        let coefficients = (0..threshold)
+            .map(|_| {
+                let mut bytes = [0u8; 64];
+                rng.fill_bytes(&mut bytes);
+                Fr::from_le_bytes_mod_order(&bytes)
+            })
+            .collect::<Vec<_>>();
+        let evaluate = |index: u64| {
+            let x = Fr::from(index);
+            coefficients
+                .iter()
+                .rev()
+                .fold(Fr::zero(), |value, coefficient| value * x + coefficient)
+        };
+        let generator = G2Affine::generator();
+        let mut shares = BTreeMap::new();
+        let mut secret_keys = BTreeMap::new();
+        // The shares are numbered in the order of the validators' public keys.
+        let validators = validators.into_iter().collect::<BTreeMap<_, _>>();
+        let mut index = 0;
+        for (validator, votes) in validators {
+            let secrets = (0..votes)
+                .map(|_| {
+                    index += 1;
+                    evaluate(index)
+                })
+                .collect::<Vec<_>>();
+            let public_keys = secrets
+                .iter()
+                .map(|secret| RandomnessPublicKey((generator * secret).into_affine()))
+                .collect();
+            shares.insert(validator, public_keys);
+            secret_keys.insert(validator, RandomnessSecretKey(secrets));
+        }
+        assert!(
+            index >= threshold,
+            "the threshold must not exceed the number of votes"
+        );
+        let public_key = RandomnessPublicKey((generator * coefficients[0]).into_affine());
+        let keys = RandomnessPublicKeys {
+            public_key,
+            shares,
+            threshold,
+        };
+        (keys, secret_keys)
+    }
+
+    /// Checks that the `validator` produced this `share` for the given chain, block height
+    /// and round.
+    pub fn check_share(
+        &self,
+        share: &RandomnessShare,
+        chain_id: ChainId,
+        height: BlockHeight,
+        round: Round,
+    ) -> Result<(), RandomnessError> {
+        let validator = share.validator;
+        let public_keys = self
+            .shares
+            .get(&validator)
+            .ok_or(RandomnessError::InvalidShare(validator))?;
+        if public_keys.len() != share.signatures.len() {
+            return Err(RandomnessError::InvalidShare(validator));
+        }
+        let message = RandomnessMessage {
+            chain_id,
+            height,
+            round,
+        }
+        .hash_to_curve()?;
+        // The signatures are checked together, each weighted with a coefficient that
+        // depends on all of them, so that invalid signatures cannot cancel each other out.
+        let mut signature = G1Projective::zero();
+        let mut public_key = G2Projective::zero();
+        for (index, (share_signature, share_public_key)) in
+            share.signatures.iter().zip(public_keys).enumerate()
+        {
+            let coefficient = batch_coefficient(share, index as u64);
+            signature += share_signature.0 * coefficient;
+            public_key += share_public_key.0 * coefficient;
+        }
+        if Bls12_381::pairing(signature, G2Affine::generator())
+            != Bls12_381::pairing(message, public_key)
+        {
+            return Err(RandomnessError::InvalidShare(validator));
+        }
+        Ok(())
+    }
+
+    /// Combines the `shares` produced for the given chain, block height and round into the
+    /// committee's signature, and returns the seed derived from it.
+    ///
+    /// The shares are not checked individually; instead, the combined signature is checked
+    /// against the committee's public key. Since the signature is unique, the seed does not
+    /// depend on which shares are given, as long as they reach the threshold.
+    pub fn combine_shares(
+        &self,
+        shares: &[RandomnessShare],
+        chain_id: ChainId,
+        height: BlockHeight,
+        round: Round,
+    ) -> Result<CryptoHash, RandomnessError> {
+        let first_indices = self.first_indices();
+        let mut signatures = BTreeMap::new();
+        for share in shares {
+            let validator = share.validator;
+            let (first_index, public_keys) = first_indices
+                .get(&validator)
+                .ok_or(RandomnessError::InvalidShare(validator))?;
+            if public_keys.len() != share.signatures.len() {
+                return Err(RandomnessError::InvalidShare(validator));
+            }
+            for (offset, signature) in share.signatures.iter().enumerate() {
+                if signatures
+                    .insert(first_index + offset as u64, *signature)
+                    .is_some()
+                {
+                    return Err(RandomnessError::DuplicateShare(validator));
+                }
+            }
+        }
+        let count = signatures.len() as u64;
+        if count < self.threshold {
+            return Err(RandomnessError::SharesBelowThreshold {
+                count,
+                threshold: self.threshold,
+            });
+        }
+        let signatures = signatures
+            .into_iter()
+            .take(self.threshold as usize)
+            .collect::<Vec<_>>();
+        let indices = signatures
+            .iter()
+            .map(|(index, _)| Fr::from(*index))
+            .collect::<Vec<_>>();
+        let mut combined = G1Projective::zero();
+        for (i, (_, signature)) in signatures.iter().enumerate() {
+            // The Lagrange coefficient of the i-th share, to interpolate the secret at zero.
+            let mut numerator = Fr::from(1u64);
+            let mut denominator = Fr::from(1u64);
+            for (j, index) in indices.iter().enumerate() {
+                if i != j {
+                    numerator *= index;
+                    denominator *= *index - indices[i];
+                }
+            }
+            let inverse = denominator
+                .inverse()
+                .expect("share indices are distinct and nonzero");
+            combined += signature.0 * (numerator * inverse);
+        }
+        let combined = combined.into_affine();
+        let message = RandomnessMessage {
+            chain_id,
+            height,
+            round,
+        }
+        .hash_to_curve()?;
+        if Bls12_381::pairing(combined, G2Affine::generator())
+            != Bls12_381::pairing(message, self.public_key.0)
+        {
+            return Err(RandomnessError::InvalidSignature);
+        }
+        Ok(CryptoHash::new(&RandomnessSeed(RandomnessSignature(
+            combined,
+        ))))
+    }
+
+    /// Returns the index of the first key share of each validator, and the public keys of
+    /// its shares.
+    fn first_indices(&self) -> BTreeMap<ValidatorPublicKey, (u64, &Vec<RandomnessPublicKey>)> {
+        let mut first_index = 1;
+        self.shares
+            .iter()
+            .map(|(validator, public_keys)| {
+                let entry = (*validator, (first_index, public_keys));
+                first_index += public_keys.len() as u64;
+                entry
+            })
+            .collect()
+    }
+}
+
+/// Returns the coefficient of the signature with the given `index` when checking a share.
+fn batch_coefficient(share: &RandomnessShare, index: u64) -> Fr {
+    use sha3::Digest as _;
+
+    let mut hasher = sha3::Sha3_256::new();
+    hasher.update(b"LINERA-RANDOMNESS-BATCH-COEFFICIENT");
+    hasher.update(bcs::to_bytes(share).expect("serialization should not fail"));
+    hasher.update(index.to_le_bytes());
+    Fr::from_le_bytes_mod_order(&hasher.finalize())
+}
+
+/// The committee's signature from which a block's randomness seed is derived.
+#[derive(Debug, Deserialize, Serialize)]
+struct RandomnessSeed(RandomnessSignature);
+
+impl BcsHashable<'_> for RandomnessSeed {}
+
+impl RandomnessShare {
+    /// Creates the share of the validator with the given secret key shares for the block at
+    /// `height` of the chain `chain_id`, proposed in `round`.
+    pub fn new(
+        validator: ValidatorPublicKey,
+        secret_key: &RandomnessSecretKey,
+        chain_id: ChainId,
+        height: BlockHeight,
+        round: Round,
+    ) -> Result<Self, RandomnessError> {
+        let message = RandomnessMessage {
+            chain_id,
+            height,
+            round,
+        }
+        .hash_to_curve()?;
+        let signatures = secret_key
+            .0
+            .iter()
+            .map(|secret| RandomnessSignature((message * secret).into_affine()))
+            .collect();
+        Ok(RandomnessShare {
+            validator,
+            signatures,
+        })
+    }
+}
+
+impl RandomnessPublicKey {
+    /// Returns the compressed byte representation of this key.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        self.0
+            .serialize_compressed(&mut bytes)
+            .expect("serialization to bytes should not fail");
+        bytes
+    }
+
+    /// Parses a key from its compressed byte representation.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, RandomnessError> {
+        G2Affine::deserialize_compressed(bytes)
+            .map(RandomnessPublicKey)
+            .map_err(|_| RandomnessError::InvalidEncoding)
+    }
+}
+
+impl RandomnessSignature {
+    /// Returns the compressed byte representation of this signature.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        self.0
+            .serialize_compressed(&mut bytes)
+            .expect("serialization to bytes should not fail");
+        bytes
+    }
+
+    /// Parses a signature from its compressed byte representation.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, RandomnessError> {
+        G1Affine::deserialize_compressed(bytes)
+            .map(RandomnessSignature)
+            .map_err(|_| RandomnessError::InvalidEncoding)
+    }
+}
+
+impl Serialize for RandomnessPublicKey {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::ser::Serializer,
+    {
+        if serializer.is_human_readable() {
+            serializer.serialize_str(&hex::encode(self.to_bytes()))
+        } else {
+            serializer.serialize_newtype_struct(
+                "RandomnessPublicKey",
+                serde_bytes::Bytes::new(&self.to_bytes()),
+            )
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for RandomnessPublicKey {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::de::Deserializer<'de>,
+    {
+        let bytes = if deserializer.is_human_readable() {
+            let s = String::deserialize(deserializer)?;
+            hex::decode(s).map_err(serde::de::Error::custom)?
+        } else {
+            #[derive(Deserialize)]
+            #[serde(rename = "RandomnessPublicKey")]
+            struct PublicKey(#[serde(with = "serde_bytes")] Vec<u8>);
+
+            PublicKey::deserialize(deserializer)?.0
+        };
+        Self::from_bytes(&bytes).map_err(serde::de::Error::custom)
+    }
+}
+
+impl Serialize for RandomnessSignature {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::ser::Serializer,
+    {
+        if serializer.is_human_readable() {
+            serializer.serialize_str(&hex::encode(self.to_bytes()))
+        } else {
+            serializer.serialize_newtype_struct(
+                "RandomnessSignature",
+                serde_bytes::Bytes::new(&self.to_bytes()),
+            )
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for RandomnessSignature {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::de::Deserializer<'de>,
+    {
+        let bytes = if deserializer.is_human_readable() {
+            let s = String::deserialize(deserializer)?;
+            hex::decode(s).map_err(serde::de::Error::custom)?
+        } else {
+            #[derive(Deserialize)]
+            #[serde(rename = "RandomnessSignature")]
+            struct Signature(#[serde(with = "serde_bytes")] Vec<u8>);
+
+            Signature::deserialize(deserializer)?.0
+        };
+        Self::from_bytes(&bytes).map_err(serde::de::Error::custom)
+    }
+}
+
+impl Hash for RandomnessPublicKey {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.to_bytes().hash(state)
+    }
+}
+
+impl Hash for RandomnessSignature {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.to_bytes().hash(state)
+    }
+}
+
+impl fmt::Debug for RandomnessPublicKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}..", hex::encode(&self.to_bytes()[0..9]))
+    }
+}
+
+impl fmt::Debug for RandomnessSignature {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}..", hex::encode(&self.to_bytes()[0..9]))
+    }
+}
+
+impl fmt::Debug for RandomnessSecretKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "<redacted for randomness secret key>")
+    }
+}
+
+impl Serialize for RandomnessSecretKey {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::ser::Serializer,
+    {
+        // This is only used for JSON configuration.
+        if !serializer.is_human_readable() {
+            return Err(serde::ser::Error::custom(
+                "randomness secret keys can only be serialized in human-readable formats",
+            ));
+        }
+        let shares = self
+            .0
+            .iter()
+            .map(|secret| {
+                let mut bytes = Vec::new();
+                secret
+                    .serialize_compressed(&mut bytes)
+                    .expect("serialization to bytes should not fail");
+                hex::encode(bytes)
+            })
+            .collect::<Vec<_>>();
+        shares.serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for RandomnessSecretKey {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::de::Deserializer<'de>,
+    {
+        // This is only used for JSON configuration.
+        if !deserializer.is_human_readable() {
+            return Err(serde::de::Error::custom(
+                "randomness secret keys can only be deserialized from human-readable formats",
+            ));
+        }
+        let shares = Vec::<String>::deserialize(deserializer)?;
+        let secrets = shares
+            .into_iter()
+            .map(|share| {
+                let bytes = hex::decode(share).map_err(serde::de::Error::custom)?;
+                Fr::deserialize_compressed(bytes.as_slice()).map_err(serde::de::Error::custom)
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(RandomnessSecretKey(secrets))
+    }
+}
+
+/// The input from which the random bytes returned to an application are derived.
+#[derive(Debug, Deserialize, Serialize)]
+struct RandomBytesInput {
+    seed: CryptoHash,
+    application_id: ApplicationId,
+    transaction_index: u32,
+    index: u32,
+}
+
+impl BcsHashable<'_> for RandomBytesInput {}
+
+/// Derives the random bytes for the `index`-th request of an application in a
+/// transaction, from the `seed` of the block.
+pub fn derive_random_bytes(
+    seed: CryptoHash,
+    application_id: ApplicationId,
+    transaction_index: u32,
+    index: u32,
+) -> [u8; 32] {
+    CryptoHash::new(&RandomBytesInput {
+        seed,
+        application_id,
+        transaction_index,
+        index,
+    })
+    .into()
+}
@@ -5,6 +5,7 @@ use std::collections::{BTreeMap, BTreeSet};
 
 use custom_debug_derive::Debug;
 use linera_base::{
+    crypto::CryptoHash,
     data_types::{Blob, Event, OracleResponse, Timestamp},
     identifiers::{AccountOwner, BlobId, ChainId},
 };
@@ -49,6 +50,9 @@ pub struct BlockExecutionTracker<'resources, 'blobs> {
 
     // We expect the number of outcomes to be equal to the number of transactions in the block.
     expected_outcomes_count: usize,
+
+    // The randomness seed provided by a previous transaction in the block, if any.
+    randomness_seed: Option<CryptoHash>,
 }
 
 impl<'resources, 'blobs> BlockExecutionTracker<'resources, 'blobs> {
@@ -82,6 +86,7 @@ impl<'resources, 'blobs> BlockExecutionTracker<'resources, 'blobs> {
             transaction_index: 0,
             published_blobs,
             expected_outcomes_count: proposal.incoming_bundles.len() + proposal.operations.len(),
+            randomness_seed: None,
         })
     }
 
@@ -94,7 +99,8 @@ impl<'resources, 'blobs> BlockExecutionTracker<'resources, 'blobs> {
             self.next_application_index,
             self.next_chain_index,
             self.oracle_responses()?,
-        ))
+        )
+        .with_randomness_seed(self.randomness_seed))
     }
 
     /// Returns oracle responses for the current transaction.
@@ -127,6 +133,7 @@ impl<'resources, 'blobs> BlockExecutionTracker<'resources, 'blobs> {
         self.next_message_index = txn_outcome.next_message_index;
         self.next_application_index = txn_outcome.next_application_index;
         self.next_chain_index = txn_outcome.next_chain_index;
+        self.randomness_seed = txn_outcome.randomness_seed;
         self.oracle_responses
             .push(txn_outcome.oracle_responses.clone());
         self.events.push(txn_outcome.events.clone());
@@ -25,6 +25,7 @@ use linera_execution::{
 use linera_views::{
     bucket_queue_view::BucketQueueView,
     context::Context,
+    key_value_store_view::HistoryWindow,
     log_view::LogView,
     map_view::MapView,
     reentrant_collection_view::ReentrantCollectionView,
@@ -277,6 +278,11 @@ where
     /// Number of outgoing messages in flight for each block height.
     /// We use a `RegisterView` to prioritize speed for small maps.
     pub outbox_counters: RegisterView<C, BTreeMap<BlockHeight, u32>>,
+    /// The height below which blocks may have been pruned from this node's storage.
+    pub pruned_height: RegisterView<C, BlockHeight>,
+    /// The height of the first block from which the prior values of the application states
+    /// are kept, if this node keeps them.
+    pub state_history_start: RegisterView<C, Option<BlockHeight>>,
 }
 
 /// Block-chaining state.
@@ -373,17 +379,40 @@ where
         query: Query,
         service_runtime_endpoint: Option<&mut ServiceRuntimeEndpoint>,
     ) -> Result<QueryOutcome, ChainError> {
+        let mut next_block_height = self.tip_state.get().next_block_height;
+        if let Some(height) = query.height() {
+            next_block_height = height.try_add_one()?;
+        }
         let context = QueryContext {
             chain_id: self.chain_id(),
-            next_block_height: self.tip_state.get().next_block_height,
+            next_block_height,
             local_time,
         };
+        let state_history = self.state_history_window();
         self.execution_state
-            .query_application(context, query, service_runtime_endpoint)
+            .query_application(context, query, service_runtime_endpoint, state_history)
             .await
             .with_execution_context(ChainExecutionContext::Query)
     }
 
+    /// Returns the heights at which the application states can be read, if this node keeps
+    /// their prior values: within the configured retention, and not before the first block
+    /// executed with the history enabled.
+    pub fn state_history_window(&self) -> Option<HistoryWindow> {
+        let retention = self
+            .context()
+            .extra()
+            .execution_runtime_config()
+            .state_history_retention?;
+        let next_block_height = self.tip_state.get().next_block_height;
+        let start = self.state_history_start.get().unwrap_or(next_block_height);
+        Some(HistoryWindow {
+            start: start.0,
+            next_version: next_block_height.0,
+            retention,
+        })
+    }
+
     pub async fn describe_application(
         &mut self,
         application_id: ApplicationId,
@@ -477,6 +506,77 @@ where
         Ok(())
     }
 
+    /// Checks a state loaded from a snapshot taken after the given block, and resets what the
+    /// block does not certify. Returns whether the state matches the block.
+    ///
+    /// The execution state, the tip and the end of the confirmed log must match the block, and
+    /// the inboxes must be consistent and contain the bundles the block received. The consensus
+    /// state, the pending blobs, the outboxes, the received log and the history of application
+    /// states are specific to the node that took the snapshot: they are reset, so that this node
+    /// neither votes nor delivers messages based on them.
+    pub async fn adopt_snapshot(
+        &mut self,
+        block_hash: CryptoHash,
+        block: &Block,
+        local_time: Timestamp,
+    ) -> Result<bool, ChainError> {
+        let height = block.header.height;
+        let next_height = height.try_add_one()?;
+        let tip_state = self.tip_state.get();
+        if tip_state.block_hash != Some(block_hash)
+            || tip_state.next_block_height != next_height
+            || *self.execution_state_hash.get() != Some(block.header.state_hash)
+            || self.execution_state.crypto_hash().await? != block.header.state_hash
+        {
+            return Ok(false);
+        }
+        if self.confirmed_log.count() != usize::try_from(next_height)?
+            || self.confirmed_log.get(height.try_into()?).await? != Some(block_hash)
+        {
+            return Ok(false);
+        }
+        if let Ok(previous_height) = height.try_sub_one() {
+            let previous_hash = self.confirmed_log.get(previous_height.try_into()?).await?;
+            if previous_hash != block.header.previous_block_hash {
+                return Ok(false);
+            }
+        }
+        for recipient in block.recipients() {
+            if self.previous_message_blocks.get(&recipient).await? != Some(height) {
+                return Ok(false);
+            }
+        }
+        let inboxes = self
+            .inboxes
+            .try_load_all_entries()
+            .await?
+            .into_iter()
+            .collect::<HashMap<_, _>>();
+        for inbox in inboxes.values() {
+            if !inbox.is_consistent().await? {
+                return Ok(false);
+            }
+        }
+        for incoming_bundle in &block.body.incoming_bundles {
+            let Some(inbox) = inboxes.get(&incoming_bundle.origin) else {
+                return Ok(false);
+            };
+            if *inbox.next_cursor_to_remove.get() <= Cursor::from(&incoming_bundle.bundle) {
+                return Ok(false);
+            }
+        }
+        drop(inboxes);
+
+        self.outboxes.clear();
+        self.outbox_counters.set(BTreeMap::new());
+        self.received_log.clear();
+        self.received_certificate_trackers.set(HashMap::new());
+        self.state_history_start.set(None);
+        self.pruned_height.set(height);
+        self.reset_chain_manager(next_height, local_time)?;
+        Ok(true)
+    }
+
     /// Verifies that this chain is up-to-date and all the messages executed ahead of time
     /// have been properly received by now.
     pub async fn validate_incoming_bundles(&self) -> Result<(), ChainError> {
@@ -932,6 +1032,19 @@ where
         // Last, reset the consensus state based on the current ownership.
         self.reset_chain_manager(block.header.height.try_add_one()?, local_time)?;
 
+        // Track from which block the prior values of the application states are kept.
+        let keeps_state_history = self
+            .context()
+            .extra()
+            .execution_runtime_config()
+            .state_history_retention
+            .is_some();
+        match (keeps_state_history, *self.state_history_start.get()) {
+            (true, None) => self.state_history_start.set(Some(block.header.height)),
+            (false, Some(_)) => self.state_history_start.set(None),
+            _ => {}
+        }
+
         // Advance to next block height.
         let tip = self.tip_state.get_mut();
         tip.block_hash = Some(hash);
@@ -191,6 +191,25 @@ where
         }
     }
 
+    /// Returns whether the queued bundles are in order, and within the range the cursors allow.
+    pub(crate) async fn is_consistent(&self) -> Result<bool, ViewError> {
+        let next_cursor_to_add = *self.next_cursor_to_add.get();
+        let next_cursor_to_remove = *self.next_cursor_to_remove.get();
+        let added_bundles = self.added_bundles.elements().await?;
+        let removed_bundles = self.removed_bundles.elements().await?;
+        let is_in_range = |bundles: &[MessageBundle], start: Cursor, end: Cursor| {
+            bundles
+                .iter()
+                .all(|bundle| (start..end).contains(&Cursor::from(bundle)))
+                && bundles
+                    .windows(2)
+                    .all(|pair| Cursor::from(&pair[0]) < Cursor::from(&pair[1]))
+        };
+        Ok((added_bundles.is_empty() || removed_bundles.is_empty())
+            && is_in_range(&added_bundles, next_cursor_to_remove, next_cursor_to_add)
+            && is_in_range(&removed_bundles, next_cursor_to_add, next_cursor_to_remove))
+    }
+
     /// Consumes a bundle from the inbox.
     ///
     /// Returns `true` if the bundle was already known, i.e. it was present in `added_bundles`.
@@ -272,10 +272,10 @@ async fn test_application_permissions() -> anyhow::Result<()> {
     let extra = &chain.context().extra();
     extra
         .user_contracts()
-        .insert(application_id, application.clone().into());
+        .insert(app_description.module_id, application.clone().into());
     extra
         .user_contracts()
-        .insert(another_app_id, application.clone().into());
+        .insert(another_app.module_id, application.clone().into());
 
     extra.add_blobs(env.description_blobs()).await?;
     extra
@@ -731,10 +731,10 @@ async fn prepare_test_with_dummy_mock_application(
     let extra = &chain.context().extra();
     extra
         .user_contracts()
-        .insert(application_id, application.clone().into());
+        .insert(app_description.module_id, application.clone().into());
     extra
         .user_services()
-        .insert(application_id, application.clone().into());
+        .insert(app_description.module_id, application.clone().into());
     extra
         .add_blobs([
             contract_blob,
@@ -55,6 +55,12 @@ pub struct ChainListenerConfig {
         env = "LINERA_LISTENER_DELAY_AFTER"
     )]
     pub delay_after_ms: u64,
+
+    /// The maximal number of chains to listen to. The chains of the wallet are always
+    /// listened to; further chains, e.g. the ones publishing events that our chains
+    /// subscribe to, are ignored.
+    #[arg(long = "listener-max-chains", env = "LINERA_LISTENER_MAX_CHAINS")]
+    pub max_chains: Option<usize>,
 }
 
 type ContextChainClient<C> = ChainClient<<C as ClientContext>::Environment>;
@@ -309,6 +315,14 @@ impl<C: ClientContext> ChainListener<C> {
         if self.listening.contains_key(&chain_id) {
             return Ok(BTreeSet::new());
         }
+        if let Some(max_chains) = self.config.max_chains {
+            if self.listening.len() >= max_chains
+                && self.context.lock().await.wallet().get(chain_id).is_none()
+            {
+                warn!("Not listening to {chain_id}: already listening to {max_chains} chains");
+                return Ok(BTreeSet::new());
+            }
+        }
         let client = self.context.lock().await.make_chain_client(chain_id);
         let (listener, abort_handle, notification_stream) = client.listen().await?;
         if client.is_tracked() {
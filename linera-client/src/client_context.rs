@@ -160,7 +160,8 @@ where
             options.max_loaded_chains,
             options.grace_period,
             options.blob_download_timeout,
-        );
+        )
+        .with_archive_nodes(options.archive_nodes.clone());
 
         ClientContext {
             client: Arc::new(client),
@@ -4,7 +4,8 @@
 use std::{collections::HashSet, fmt, iter, num::NonZeroUsize, path::PathBuf};
 
 use linera_base::{
-    data_types::{ApplicationPermissions, TimeDelta},
+    crypto::ValidatorPublicKey,
+    data_types::{ApplicationPermissions, HttpPolicy, TimeDelta},
     identifiers::{AccountOwner, ApplicationId, ChainId},
     ownership::{ChainOwnership, TimeoutConfig},
     time::Duration,
@@ -110,6 +111,11 @@ pub struct ClientContextOptions {
         value_parser = util::parse_millis
     )]
     pub blob_download_timeout: Duration,
+
+    /// A node that keeps the full history of the chains, given as `<PUBLIC_KEY>@<ADDRESS>`,
+    /// to download blocks from that the validators have already pruned. Can be repeated.
+    #[arg(long = "archive-node", value_parser = util::parse_archive_node)]
+    pub archive_nodes: Vec<(ValidatorPublicKey, String)>,
 }
 
 #[derive(Debug, Clone, clap::Args)]
@@ -239,6 +245,11 @@ pub struct ApplicationPermissionsConfig {
     /// API.
     #[arg(long)]
     pub make_http_requests: Option<Vec<ApplicationId>>,
+    /// Restrictions on the HTTP requests of an application, as a JSON object with the field
+    /// `application_id` and optionally `url_prefixes`, `methods`, `maximum_request_bytes`,
+    /// `maximum_response_bytes` and `maximum_requests_per_block`. Can be repeated.
+    #[arg(long = "http-policy", value_parser = util::parse_json::<HttpPolicy>)]
+    pub http_policies: Vec<HttpPolicy>,
 }
 
 impl From<ApplicationPermissionsConfig> for ApplicationPermissions {
@@ -252,6 +263,7 @@ impl From<ApplicationPermissionsConfig> for ApplicationPermissions {
                 .unwrap_or_default(),
             call_service_as_oracle: config.call_service_as_oracle,
             make_http_requests: config.make_http_requests,
+            http_policies: config.http_policies,
         }
     }
 }
@@ -12,6 +12,7 @@ use linera_base::{
     },
     identifiers::ChainId,
     ownership::ChainOwnership,
+    randomness::{RandomnessPublicKeys, RandomnessSecretKey},
 };
 use linera_execution::{
     committee::{Committee, ValidatorState},
@@ -63,12 +64,19 @@ pub struct ValidatorServerConfig {
     pub validator: ValidatorConfig,
     pub validator_secret: ValidatorSecretKey,
     pub internal_network: ValidatorInternalNetworkConfig,
+    /// The validator's shares of the committee's randomness key, if any.
+    #[serde(default)]
+    pub randomness_secret: Option<RandomnessSecretKey>,
 }
 
 /// The (public) configuration for all validators.
 #[derive(Debug, Default, Clone, Deserialize, Serialize)]
 pub struct CommitteeConfig {
     pub validators: Vec<ValidatorConfig>,
+    /// The public keys of the randomness beacon, if the validators were given shares of a
+    /// randomness key.
+    #[serde(default)]
+    pub randomness_keys: Option<RandomnessPublicKeys>,
 }
 
 impl CommitteeConfig {
@@ -87,7 +95,13 @@ impl CommitteeConfig {
                 )
             })
             .collect();
-        Committee::new(validators, policy)
+        let committee = Committee::new(validators, policy);
+        match self.randomness_keys {
+            Some(randomness_keys) => committee
+                .with_randomness_keys(randomness_keys)
+                .expect("the randomness keys should match the committee"),
+            None => committee,
+        }
     }
 }
 
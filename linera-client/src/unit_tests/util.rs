@@ -31,7 +31,10 @@ pub fn make_genesis_config(builder: &TestBuilder<MemoryStorageBuilder>) -> Genes
         .next()
         .expect("should have at least one chain");
     let mut genesis_config = GenesisConfig::new(
-        CommitteeConfig { validators },
+        CommitteeConfig {
+            validators,
+            randomness_keys: builder.initial_committee.randomness_keys().cloned(),
+        },
         Timestamp::from(0),
         builder.initial_committee.policy().clone(),
         "test network".to_string(),
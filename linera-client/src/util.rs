@@ -5,7 +5,7 @@ use std::{collections::HashSet, num::ParseIntError, str::FromStr};
 
 use futures::future;
 use linera_base::{
-    crypto::CryptoError,
+    crypto::{CryptoError, ValidatorPublicKey},
     data_types::{TimeDelta, Timestamp},
     identifiers::ChainId,
     time::Duration,
@@ -25,6 +25,10 @@ pub fn parse_millis_delta(s: &str) -> Result<TimeDelta, ParseIntError> {
     Ok(TimeDelta::from_millis(s.parse()?))
 }
 
+pub fn parse_secs_delta(s: &str) -> Result<TimeDelta, ParseIntError> {
+    Ok(TimeDelta::from_secs(s.parse()?))
+}
+
 pub fn parse_chain_set(s: &str) -> Result<HashSet<ChainId>, CryptoError> {
     match s.trim() {
         "" => Ok(HashSet::new()),
@@ -32,6 +36,19 @@ pub fn parse_chain_set(s: &str) -> Result<HashSet<ChainId>, CryptoError> {
     }
 }
 
+/// Parses an archive node given as `<PUBLIC_KEY>@<ADDRESS>`.
+pub fn parse_archive_node(s: &str) -> Result<(ValidatorPublicKey, String), String> {
+    let (public_key, address) = s
+        .split_once('@')
+        .ok_or_else(|| format!("Expecting <PUBLIC_KEY>@<ADDRESS>, got {s}"))?;
+    let public_key = public_key.parse().map_err(|error| format!("{error}"))?;
+    Ok((public_key, address.to_string()))
+}
+
+pub fn parse_json<T: serde::de::DeserializeOwned>(s: &str) -> Result<T, serde_json::Error> {
+    serde_json::from_str(s)
+}
+
 pub fn parse_ascii_alphanumeric_string(s: &str) -> Result<String, &'static str> {
     if s.chars().all(|x| x.is_ascii_alphanumeric()) {
         Ok(s.to_string())
@@ -31,7 +31,7 @@ use tracing::{debug, instrument, trace, warn, Instrument as _};
 
 use super::{config::ChainWorkerConfig, state::ChainWorkerState, DeliveryNotifier};
 use crate::{
-    data_types::{ChainInfoQuery, ChainInfoResponse},
+    data_types::{ChainInfoQuery, ChainInfoResponse, ChainSnapshotEntries},
     value_cache::ValueCache,
     worker::{NetworkActions, WorkerError},
 };
@@ -163,6 +163,15 @@ where
         new_trackers: BTreeMap<ValidatorPublicKey, u64>,
         callback: oneshot::Sender<Result<(), WorkerError>>,
     },
+
+    /// Replace the chain state with a snapshot taken after a confirmed block.
+    LoadChainSnapshot {
+        certificate: ConfirmedBlockCertificate,
+        #[debug(skip)]
+        chunks: Vec<ChainSnapshotEntries>,
+        #[debug(skip)]
+        callback: oneshot::Sender<Result<(), WorkerError>>,
+    },
 }
 
 /// The actor worker type.
@@ -433,6 +442,13 @@ where
                         .await,
                 )
                 .is_ok(),
+            ChainWorkerRequest::LoadChainSnapshot {
+                certificate,
+                chunks,
+                callback,
+            } => callback
+                .send(self.worker.load_chain_snapshot(certificate, chunks).await)
+                .is_ok(),
         };
 
         if !responded {
@@ -498,6 +514,9 @@ where
             ChainWorkerRequest::UpdateReceivedCertificateTrackers { callback, .. } => {
                 callback.send(Err(error)).is_ok()
             }
+            ChainWorkerRequest::LoadChainSnapshot { callback, .. } => {
+                callback.send(Err(error)).is_ok()
+            }
         };
 
         if !responded {
@@ -5,7 +5,9 @@
 
 use std::sync::Arc;
 
-use linera_base::{crypto::ValidatorSecretKey, time::Duration};
+use linera_base::{crypto::ValidatorSecretKey, randomness::RandomnessSecretKey, time::Duration};
+
+use super::{HistoryArchive, RetentionPolicy};
 
 /// Configuration parameters for the [`ChainWorkerState`][`super::state::ChainWorkerState`].
 #[derive(Clone, Default)]
@@ -13,6 +15,8 @@ pub struct ChainWorkerConfig {
     /// The signature key pair of the validator. The key may be missing for replicas
     /// without voting rights (possibly with a partial view of chains).
     pub key_pair: Option<Arc<ValidatorSecretKey>>,
+    /// The validator's shares of the committee's randomness key, if any.
+    pub randomness_key: Option<Arc<RandomnessSecretKey>>,
     /// Whether inactive chains are allowed in storage.
     pub allow_inactive_chains: bool,
     /// Whether new messages from deprecated epochs are allowed.
@@ -22,6 +26,10 @@ pub struct ChainWorkerConfig {
     /// Blocks with a timestamp this far in the future will still be accepted, but the validator
     /// will wait until that timestamp before voting.
     pub grace_period: Duration,
+    /// Which historical blocks of each chain are kept in storage.
+    pub retention_policy: RetentionPolicy,
+    /// Where the pruned blocks are archived before being deleted, if anywhere.
+    pub history_archive: Option<Arc<dyn HistoryArchive>>,
 }
 
 impl ChainWorkerConfig {
@@ -42,4 +50,9 @@ impl ChainWorkerConfig {
     pub fn key_pair(&self) -> Option<&ValidatorSecretKey> {
         self.key_pair.as_ref().map(Arc::as_ref)
     }
+
+    /// Gets a reference to the [`RandomnessSecretKey`], if available.
+    pub fn randomness_key(&self) -> Option<&RandomnessSecretKey> {
+        self.randomness_key.as_ref().map(Arc::as_ref)
+    }
 }
@@ -6,6 +6,7 @@
 mod actor;
 mod config;
 mod delivery_notifier;
+mod retention;
 mod state;
 
 pub(super) use self::delivery_notifier::DeliveryNotifier;
@@ -14,5 +15,6 @@ pub(crate) use self::state::CrossChainUpdateHelper;
 pub use self::{
     actor::{ChainWorkerActor, ChainWorkerRequest},
     config::ChainWorkerConfig,
+    retention::{HistoryArchive, RetentionPolicy},
     state::ChainWorkerState,
 };
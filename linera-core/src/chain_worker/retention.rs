@@ -0,0 +1,44 @@
+// Copyright (c) Zefchain Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! How long the historical blocks of chains are kept in storage.
+
+use std::io;
+
+use async_trait::async_trait;
+use linera_base::{data_types::Blob, time::Duration};
+use linera_chain::types::ConfirmedBlockCertificate;
+
+/// Which historical blocks of a chain are kept in storage.
+///
+/// A block is only pruned once it is outside of every configured limit, so without any limit
+/// the full history is kept. The latest block and the blocks with outgoing messages still in
+/// flight are never pruned.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct RetentionPolicy {
+    /// The number of most recent block heights to keep.
+    pub keep_heights: Option<u64>,
+    /// How long to keep blocks, measured from their timestamp.
+    pub keep_duration: Option<Duration>,
+}
+
+impl RetentionPolicy {
+    /// Returns whether this policy keeps the full history of every chain.
+    pub fn keeps_everything(&self) -> bool {
+        self.keep_heights.is_none() && self.keep_duration.is_none()
+    }
+}
+
+/// A destination for the blocks that are pruned from storage.
+#[async_trait]
+pub trait HistoryArchive: Send + Sync {
+    /// Archives the certificates of pruned blocks of a chain, ordered by height, together with
+    /// the data blobs they use.
+    ///
+    /// Nothing is deleted from storage unless this succeeds.
+    async fn archive(
+        &self,
+        certificates: &[ConfirmedBlockCertificate],
+        blobs: &[Blob],
+    ) -> Result<(), io::Error>;
+}
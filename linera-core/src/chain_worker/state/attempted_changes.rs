@@ -3,14 +3,17 @@
 
 //! Operations that persist changes to the chain state when they are successful.
 
-use std::{borrow::Cow, collections::BTreeMap};
+use std::{
+    borrow::Cow,
+    collections::{BTreeMap, BTreeSet},
+};
 
 use futures::future::Either;
 use linera_base::{
     crypto::ValidatorPublicKey,
-    data_types::{Blob, BlockHeight, Epoch, Timestamp},
+    data_types::{Blob, BlockHeight, Epoch, TimeDelta, Timestamp},
     ensure,
-    identifiers::{AccountOwner, ChainId},
+    identifiers::{AccountOwner, BlobType, ChainId},
 };
 use linera_chain::{
     data_types::{
@@ -35,6 +38,9 @@ use crate::{
     worker::{NetworkActions, Notification, Reason, WorkerError},
 };
 
+/// The maximum number of blocks pruned from storage after each new block.
+const MAX_PRUNED_BLOCKS_PER_STEP: u64 = 100;
+
 /// Wrapper type that tracks if the changes to the `chain` state should be rolled back when
 /// dropped.
 pub struct ChainWorkerStateWithAttemptedChanges<'state, StorageClient>
@@ -617,6 +623,90 @@ where
         ))
     }
 
+    /// Prunes the oldest blocks that are outside of the retention policy from storage, after
+    /// archiving them if a history archive is configured.
+    ///
+    /// Blobs are never deleted here, since blocks of other chains may still use them. Once no
+    /// block or chain state in storage references them, the blob collection deletes them.
+    pub(super) async fn prune_history(&mut self) -> Result<(), WorkerError> {
+        let policy = self.state.config.retention_policy;
+        let chain = &self.state.chain;
+        let start = *chain.pruned_height.get();
+        let next_block_height = chain.tip_state.get().next_block_height;
+        // The latest block, and the blocks with outgoing messages in flight, are always kept.
+        let mut end = next_block_height.0.saturating_sub(1);
+        if let Some((height, _)) = chain.outbox_counters.get().first_key_value() {
+            end = end.min(height.0);
+        }
+        if let Some(keep_heights) = policy.keep_heights {
+            end = end.min(next_block_height.0.saturating_sub(keep_heights));
+        }
+        end = end.min(start.0.saturating_add(MAX_PRUNED_BLOCKS_PER_STEP));
+        if end <= start.0 {
+            return Ok(());
+        }
+        let hashes = chain
+            .confirmed_log
+            .read(usize::try_from(start)?..usize::try_from(BlockHeight(end))?)
+            .await?;
+        let mut certificates = self.state.storage.read_certificates(hashes).await?;
+        if let Some(keep_duration) = policy.keep_duration {
+            let cutoff = self
+                .state
+                .storage
+                .clock()
+                .current_time()
+                .saturating_sub(TimeDelta::from_duration(keep_duration));
+            let expired = certificates
+                .iter()
+                .take_while(|certificate| certificate.block().header.timestamp < cutoff)
+                .count();
+            certificates.truncate(expired);
+        }
+        let Some(last_certificate) = certificates.last() else {
+            return Ok(());
+        };
+        let pruned_height = last_certificate.block().header.height.try_add_one()?;
+
+        let hashes = certificates
+            .iter()
+            .map(|certificate| certificate.hash())
+            .collect::<Vec<_>>();
+
+        if let Some(archive) = &self.state.config.history_archive {
+            let blob_ids = certificates
+                .iter()
+                .flat_map(|certificate| certificate.block().required_blob_ids())
+                .filter(|blob_id| blob_id.blob_type == BlobType::Data)
+                .collect::<BTreeSet<_>>()
+                .into_iter()
+                .collect::<Vec<_>>();
+            let blobs = self
+                .state
+                .storage
+                .read_blobs(&blob_ids)
+                .await?
+                .into_iter()
+                .flatten()
+                .collect::<Vec<_>>();
+            archive
+                .archive(&certificates, &blobs)
+                .await
+                .map_err(WorkerError::HistoryArchive)?;
+        }
+
+        // Record the new pruned height first, so that it is never lower than the actually
+        // pruned blocks.
+        self.state.chain.pruned_height.set(pruned_height);
+        self.save().await?;
+        self.state.storage.delete_certificates(&hashes).await?;
+        debug!(
+            "Pruned blocks {start} to {pruned_height} (excluded) of chain {:.8}",
+            self.state.chain.chain_id()
+        );
+        Ok(())
+    }
+
     /// Stores the chain state in persistent storage.
     ///
     /// Waits until the [`ChainStateView`] is no longer shared before persisting the changes.
@@ -27,7 +27,13 @@ use linera_chain::{
 };
 use linera_execution::{ExecutionStateView, Query, QueryOutcome, ServiceRuntimeEndpoint};
 use linera_storage::{Clock as _, Storage};
-use linera_views::{views::ClonableView, ViewError};
+use linera_views::{
+    batch::Batch,
+    context::Context,
+    store::{KeyValueIterable, ReadableKeyValueStore, WritableKeyValueStore},
+    views::{ClonableView, RootView},
+    ViewError,
+};
 use tokio::sync::{oneshot, OwnedRwLockReadGuard, RwLock};
 
 #[cfg(test)]
@@ -38,7 +44,7 @@ use self::{
 };
 use super::{ChainWorkerConfig, DeliveryNotifier};
 use crate::{
-    data_types::{ChainInfoQuery, ChainInfoResponse, CrossChainRequest},
+    data_types::{ChainInfoQuery, ChainInfoResponse, ChainSnapshotEntries, CrossChainRequest},
     value_cache::ValueCache,
     worker::{NetworkActions, WorkerError},
 };
@@ -255,10 +261,24 @@ where
         certificate: ConfirmedBlockCertificate,
         notify_when_messages_are_delivered: Option<oneshot::Sender<()>>,
     ) -> Result<(ChainInfoResponse, NetworkActions), WorkerError> {
-        ChainWorkerStateWithAttemptedChanges::new(self)
+        let response = ChainWorkerStateWithAttemptedChanges::new(self)
             .await
             .process_confirmed_block(certificate, notify_when_messages_are_delivered)
-            .await
+            .await?;
+        if !self.config.retention_policy.keeps_everything() {
+            if let Err(error) = ChainWorkerStateWithAttemptedChanges::new(self)
+                .await
+                .prune_history()
+                .await
+            {
+                tracing::warn!(
+                    chain_id = %self.chain_id(),
+                    %error,
+                    "Failed to prune the chain's history"
+                );
+            }
+        }
+        Ok(response)
     }
 
     /// Updates the chain's inboxes, receiving messages from a cross-chain update.
@@ -537,6 +557,101 @@ where
             .update_received_certificate_trackers(new_trackers)
             .await
     }
+
+    /// Replaces the state of the chain with a snapshot taken after the block of `certificate`.
+    ///
+    /// The snapshot is written under a separate root key, and only replaces the chain's state
+    /// once it is checked against the block: see [`ChainStateView::adopt_snapshot`]. The blocks
+    /// before it are then considered pruned. Nothing happens if the chain is already at or past
+    /// that block.
+    pub(super) async fn load_chain_snapshot(
+        &mut self,
+        certificate: ConfirmedBlockCertificate,
+        chunks: Vec<ChainSnapshotEntries>,
+    ) -> Result<(), WorkerError> {
+        let block_hash = certificate.hash();
+        let block = certificate.block();
+        let chain_id = self.chain_id();
+        if self.chain.tip_state.get().next_block_height > block.header.height {
+            return Ok(());
+        }
+        let staged_store = self
+            .storage
+            .load_staged_chain(chain_id)
+            .await?
+            .context()
+            .store()
+            .clone();
+        let mut batch = Batch::new();
+        batch.delete_key_prefix(Vec::new());
+        for (key, value) in chunks.into_iter().flat_map(|chunk| chunk.0) {
+            batch.put_key_value_bytes(key, value);
+        }
+        staged_store
+            .write_batch(batch)
+            .await
+            .map_err(ViewError::from)?;
+        let result = self.adopt_staged_chain(&certificate).await;
+        let mut batch = Batch::new();
+        batch.delete_key_prefix(Vec::new());
+        staged_store
+            .write_batch(batch)
+            .await
+            .map_err(ViewError::from)?;
+        let key_values = result?.ok_or(WorkerError::InvalidChainSnapshot(block_hash))?;
+
+        self.storage
+            .write_blobs_and_certificate(&[], &certificate)
+            .await?;
+        // Wait until the chain state is no longer shared before replacing it, as in `save`.
+        let maybe_shared_chain_view = self.shared_chain_view.take();
+        let _maybe_write_guard = match &maybe_shared_chain_view {
+            Some(shared_chain_view) => Some(shared_chain_view.write().await),
+            None => None,
+        };
+        let mut batch = Batch::new();
+        batch.delete_key_prefix(Vec::new());
+        for (key, value) in key_values {
+            batch.put_key_value_bytes(key, value);
+        }
+        self.chain
+            .context()
+            .store()
+            .write_batch(batch)
+            .await
+            .map_err(ViewError::from)?;
+        self.chain = self.storage.load_chain(chain_id).await?;
+        self.knows_chain_is_active = false;
+        Ok(())
+    }
+
+    /// Checks the staged state of the chain against the certificate's block, and returns the
+    /// keys and values of the adopted state, or `None` if it does not match.
+    async fn adopt_staged_chain(
+        &self,
+        certificate: &ConfirmedBlockCertificate,
+    ) -> Result<Option<Vec<(Vec<u8>, Vec<u8>)>>, WorkerError> {
+        let mut chain = self.storage.load_staged_chain(self.chain_id()).await?;
+        let local_time = self.storage.clock().current_time();
+        if !chain
+            .adopt_snapshot(certificate.hash(), certificate.block(), local_time)
+            .await?
+        {
+            return Ok(None);
+        }
+        chain.save().await?;
+        let key_values = chain
+            .context()
+            .store()
+            .find_key_values_by_prefix(&[])
+            .await
+            .map_err(ViewError::from)?;
+        let key_values = key_values
+            .into_iterator_owned()
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(ViewError::from)?;
+        Ok(Some(key_values))
+    }
 }
 
 /// Returns the keys whose value is `None`.
@@ -16,7 +16,7 @@ use linera_chain::{
     manager,
     types::Block,
 };
-use linera_execution::{Query, QueryOutcome};
+use linera_execution::{Operation, Query, QueryOutcome};
 use linera_storage::{Clock as _, Storage};
 use linera_views::views::{ClonableView, View};
 #[cfg(with_testing)]
@@ -178,6 +178,26 @@ where
             chain.manager.verify_owner(proposal),
             WorkerError::InvalidOwner
         );
+        // The randomness must be provided for the round in which the block is first
+        // proposed, so that the proposer obtains a single seed per round.
+        let randomness_round = match original_proposal {
+            None => Some(content.round),
+            Some(OriginalProposal::Fast { .. }) => Some(Round::Fast),
+            // The block was validated in an earlier round, with that round's randomness.
+            Some(OriginalProposal::Regular { .. }) => None,
+        };
+        if let Some(expected) = randomness_round {
+            for provided in block
+                .operations
+                .iter()
+                .filter_map(Operation::randomness_round)
+            {
+                ensure!(
+                    provided == expected,
+                    WorkerError::RandomnessRoundMismatch { expected, provided }
+                );
+            }
+        }
         match original_proposal {
             None => {
                 if let Some(signer) = block.authenticated_signer {
@@ -341,6 +361,16 @@ where
         if query.request_manager_values {
             info.manager.add_values(&chain.manager);
         }
+        if query.request_storage_usage {
+            info.requested_storage_usage = chain
+                .execution_state
+                .system
+                .storage_usage
+                .index_values()
+                .await?
+                .into_iter()
+                .collect();
+        }
         Ok(ChainInfoResponse::new(info, self.0.config.key_pair()))
     }
 
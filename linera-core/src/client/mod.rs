@@ -5,7 +5,7 @@
 use std::{
     collections::{hash_map, BTreeMap, BTreeSet, HashMap, HashSet},
     convert::Infallible,
-    iter,
+    iter, mem,
     num::NonZeroUsize,
     ops::{Deref, DerefMut},
     sync::{Arc, RwLock},
@@ -28,10 +28,11 @@ use linera_base::{
     abi::Abi,
     crypto::{AccountPublicKey, CryptoHash, Signer, ValidatorPublicKey},
     data_types::{
-        Amount, ApplicationPermissions, ArithmeticError, Blob, BlobContent, BlockHeight, Epoch,
-        Round, Timestamp,
+        Amount, ApplicationDescription, ApplicationPermissions, ArithmeticError, Blob, BlobContent,
+        BlockHeight, Epoch, Round, Timestamp,
     },
     ensure,
+    http::{self, SignedResponse},
     identifiers::{
         Account, AccountOwner, ApplicationId, BlobId, BlobType, ChainId, EventId, IndexAndEvent,
         ModuleId, StreamId,
@@ -54,8 +55,8 @@ use linera_chain::{
 use linera_execution::{
     committee::Committee,
     system::{
-        AdminOperation, OpenChainConfig, Recipient, SystemOperation, EPOCH_STREAM_NAME,
-        REMOVED_EPOCH_STREAM_NAME,
+        application_upgrade_stream_id, AdminOperation, OpenChainConfig, Recipient, SystemOperation,
+        EPOCH_STREAM_NAME, REMOVED_EPOCH_STREAM_NAME,
     },
     ExecutionError, Operation, Query, QueryOutcome, QueryResponse, SystemQuery, SystemResponse,
 };
@@ -70,7 +71,8 @@ use tracing::{debug, error, info, instrument, warn, Instrument as _};
 
 use crate::{
     data_types::{
-        BlockHeightRange, ChainInfo, ChainInfoQuery, ChainInfoResponse, ClientOutcome, RoundTimeout,
+        BlockHeightRange, ChainInfo, ChainInfoQuery, ChainInfoResponse, ChainSnapshotRequest,
+        ClientOutcome, HttpOracleRequest, RandomnessRequest, RoundTimeout,
     },
     environment::Environment,
     local_node::{LocalChainInfoExt as _, LocalNodeClient, LocalNodeError},
@@ -171,6 +173,9 @@ pub struct Client<Env: Environment> {
     max_loaded_chains: NonZeroUsize,
     /// The delay when downloading a blob, after which we try a second validator.
     blob_download_timeout: Duration,
+    /// Nodes that keep the full history of the chains, to download blocks from that the
+    /// validators have already pruned.
+    archive_nodes: Vec<(ValidatorPublicKey, String)>,
 }
 
 impl<Env: Environment> Client<Env> {
@@ -214,9 +219,16 @@ impl<Env: Environment> Client<Env> {
             notifier: Arc::new(ChannelNotifier::default()),
             max_loaded_chains,
             blob_download_timeout,
+            archive_nodes: Vec::new(),
         }
     }
 
+    /// Sets the nodes to download blocks from that the validators have already pruned.
+    pub fn with_archive_nodes(mut self, archive_nodes: Vec<(ValidatorPublicKey, String)>) -> Self {
+        self.archive_nodes = archive_nodes;
+        self
+    }
+
     /// Returns the storage client used by this client's local node.
     pub fn storage_client(&self) -> &Env::Storage {
         self.environment.storage()
@@ -326,7 +338,17 @@ impl<Env: Environment> Client<Env> {
             )
             .await?;
         }
-        let info = self.fetch_chain_info(chain_id, validators).await?;
+        let mut info = self.fetch_chain_info(chain_id, validators).await?;
+        if info.next_block_height < target_next_block_height {
+            // The validators may have pruned the blocks we are missing.
+            self.download_from_archive_nodes(
+                chain_id,
+                info.next_block_height,
+                target_next_block_height,
+            )
+            .await?;
+            info = self.local_node.chain_info(chain_id).await?;
+        }
         if target_next_block_height <= info.next_block_height {
             Ok(info)
         } else {
@@ -371,6 +393,46 @@ impl<Env: Environment> Client<Env> {
         Ok(())
     }
 
+    /// Downloads and processes the certificates from `start` up to (excluding) `stop` from the
+    /// archive nodes, and returns the next block height we have for the chain afterwards.
+    #[instrument(level = "trace", skip(self))]
+    async fn download_from_archive_nodes(
+        &self,
+        chain_id: ChainId,
+        start: BlockHeight,
+        stop: BlockHeight,
+    ) -> Result<BlockHeight, ChainClientError> {
+        let mut next_block_height = start;
+        if self.archive_nodes.is_empty() {
+            return Ok(next_block_height);
+        }
+        let archive_nodes = self
+            .validator_node_provider()
+            .make_nodes_from_list(self.archive_nodes.iter().cloned())?
+            .map(|(public_key, node)| RemoteNode { public_key, node })
+            .collect::<Vec<_>>();
+        for remote_node in &archive_nodes {
+            if next_block_height >= stop {
+                break;
+            }
+            if let Err(error) = self
+                .try_download_certificates_from(remote_node, chain_id, next_block_height, stop)
+                .await
+            {
+                warn!(
+                    "Failed to download chain {chain_id:.8} from archive node {}: {error}",
+                    remote_node.public_key
+                );
+            }
+            next_block_height = self
+                .local_node
+                .chain_info(chain_id)
+                .await?
+                .next_block_height;
+        }
+        Ok(next_block_height)
+    }
+
     /// Tries to process all the certificates, requesting any missing blobs from the given node.
     /// Returns the chain info of the last successfully processed certificate.
     #[instrument(level = "trace", skip_all)]
@@ -942,6 +1004,107 @@ impl<Env: Environment> Client<Env> {
             .map_err(Into::into)
     }
 
+    /// Synchronizes a chain by loading a snapshot of its state after its latest block from one
+    /// of the validators, instead of executing all of its earlier blocks, and then processes
+    /// any blocks after the snapshot.
+    ///
+    /// If no validator provides a valid snapshot, all missing blocks are processed instead.
+    #[instrument(level = "trace", skip(self))]
+    async fn synchronize_chain_state_from_snapshot(
+        &self,
+        chain_id: ChainId,
+    ) -> Result<Box<ChainInfo>, ChainClientError> {
+        let mut validators = self.validator_nodes().await?;
+        validators.shuffle(&mut rand::thread_rng());
+        for remote_node in &validators {
+            match self
+                .try_load_chain_snapshot_from(remote_node, chain_id)
+                .await
+            {
+                Ok(()) => break,
+                Err(error) => warn!(
+                    "Failed to load a snapshot of chain {chain_id:.8} from validator {}: {error}",
+                    remote_node.public_key
+                ),
+            }
+        }
+        self.synchronize_chain_state(chain_id).await
+    }
+
+    /// Downloads a snapshot of a chain's state after its latest block from the specified
+    /// validator, checks it against the block's certificate, and loads it into the local node.
+    #[instrument(level = "trace", skip(self, remote_node))]
+    async fn try_load_chain_snapshot_from(
+        &self,
+        remote_node: &RemoteNode<Env::ValidatorNode>,
+        chain_id: ChainId,
+    ) -> Result<(), ChainClientError> {
+        let request = ChainSnapshotRequest {
+            chain_id,
+            block_hash: None,
+            index: 0,
+        };
+        let first_chunk = remote_node
+            .node
+            .download_chain_snapshot_chunk(request)
+            .await?;
+        let manifest = first_chunk
+            .manifest
+            .ok_or(NodeError::InvalidChainSnapshotChunk)?;
+        ensure!(
+            manifest.chain_id == chain_id
+                && manifest.chunk_hashes.first() == Some(&first_chunk.entries.hash()),
+            NodeError::InvalidChainSnapshotChunk
+        );
+        let certificate = remote_node
+            .download_certificates(vec![manifest.block_hash])
+            .await?
+            .pop()
+            .filter(|certificate| certificate.hash() == manifest.block_hash)
+            .ok_or_else(|| NodeError::MissingCertificates(vec![manifest.block_hash]))?;
+        let header = &certificate.block().header;
+        ensure!(
+            header.chain_id == chain_id,
+            NodeError::UnexpectedCertificateValue
+        );
+        let local_info = self.local_node.chain_info(chain_id).await?;
+        if local_info.next_block_height > header.height {
+            return Ok(());
+        }
+        let (max_epoch, committees) = self.admin_committees().await?;
+        match Self::check_certificate(max_epoch, &committees, &certificate)? {
+            CheckCertificateResult::FutureEpoch => {
+                return Err(ChainClientError::CommitteeSynchronizationError)
+            }
+            CheckCertificateResult::OldEpoch => {
+                return Err(ChainClientError::CommitteeDeprecationError)
+            }
+            CheckCertificateResult::New => {}
+        }
+
+        let mut chunks = vec![first_chunk.entries];
+        for (index, chunk_hash) in (1..).zip(&manifest.chunk_hashes[1..]) {
+            let request = ChainSnapshotRequest {
+                chain_id,
+                block_hash: Some(manifest.block_hash),
+                index,
+            };
+            let chunk = remote_node
+                .node
+                .download_chain_snapshot_chunk(request)
+                .await?;
+            ensure!(
+                chunk.entries.hash() == *chunk_hash,
+                NodeError::InvalidChainSnapshotChunk
+            );
+            chunks.push(chunk.entries);
+        }
+        self.local_node
+            .load_chain_snapshot(certificate, chunks)
+            .await?;
+        Ok(())
+    }
+
     /// Downloads any certificates from the specified validator that we are missing for the given
     /// chain, and processes them.
     #[instrument(level = "trace", skip(self, remote_node, chain_id))]
@@ -950,18 +1113,40 @@ impl<Env: Environment> Client<Env> {
         remote_node: &RemoteNode<Env::ValidatorNode>,
         chain_id: ChainId,
     ) -> Result<(), ChainClientError> {
-        let local_info = self.local_node.chain_info(chain_id).await?;
-        let range = BlockHeightRange {
-            start: local_info.next_block_height,
-            limit: None,
+        let mut local_info = self.local_node.chain_info(chain_id).await?;
+        let info = loop {
+            let range = BlockHeightRange {
+                start: local_info.next_block_height,
+                limit: None,
+            };
+            let query = ChainInfoQuery::new(chain_id)
+                .with_sent_certificate_hashes_in_range(range)
+                .with_manager_values();
+            let info = remote_node.handle_chain_info_query(query).await?;
+            if info.next_block_height < local_info.next_block_height {
+                return Ok(());
+            }
+            if info.pruned_height <= local_info.next_block_height {
+                break info;
+            }
+            // The missing blocks have to be downloaded from a node that still has them.
+            let next_block_height = self
+                .download_from_archive_nodes(
+                    chain_id,
+                    local_info.next_block_height,
+                    info.pruned_height,
+                )
+                .await?;
+            if next_block_height < info.pruned_height {
+                info!(
+                    "Validator {} pruned chain {chain_id:.8} below height {}, and no archive \
+                    node has the missing blocks",
+                    remote_node.public_key, info.pruned_height
+                );
+                return Ok(());
+            }
+            local_info = self.local_node.chain_info(chain_id).await?;
         };
-        let query = ChainInfoQuery::new(chain_id)
-            .with_sent_certificate_hashes_in_range(range)
-            .with_manager_values();
-        let info = remote_node.handle_chain_info_query(query).await?;
-        if info.next_block_height < local_info.next_block_height {
-            return Ok(());
-        }
 
         let certificates: Vec<ConfirmedBlockCertificate> = remote_node
             .download_certificates(info.requested_sent_certificate_hashes)
@@ -1362,6 +1547,12 @@ pub enum ChainClientError {
     #[error("Internal error within chain client: {0}")]
     InternalError(&'static str),
 
+    #[error("The current committee has no randomness keys")]
+    RandomnessUnavailable,
+
+    #[error("Invalid new committee: {0}")]
+    InvalidCommittee(String),
+
     #[error(
         "Cannot accept a certificate from an unknown committee in the future. \
          Please synchronize the local view of the admin chain"
@@ -2180,6 +2371,15 @@ impl<Env: Environment> ChainClient<Env> {
         self.client.synchronize_chain_state(chain_id).await
     }
 
+    /// Synchronizes this chain from a snapshot of its state certified by the validators,
+    /// without executing the blocks before the snapshot.
+    #[instrument(level = "trace")]
+    pub async fn synchronize_from_snapshot(&self) -> Result<Box<ChainInfo>, ChainClientError> {
+        self.client
+            .synchronize_chain_state_from_snapshot(self.chain_id)
+            .await
+    }
+
     /// Executes a list of operations.
     #[instrument(level = "trace", skip(operations, blobs))]
     pub async fn execute_operations(
@@ -2337,6 +2537,76 @@ impl<Env: Environment> ChainClient<Env> {
             .max(block_time)
     }
 
+    /// Asks the validators to perform an HTTP request on behalf of the next block of this
+    /// chain, and returns the responses signed by a quorum of them.
+    ///
+    /// The responses can be included in an operation, for the application to verify them
+    /// and reduce them to a single response during execution. The request is signed by
+    /// this client's owner of the chain.
+    #[instrument(level = "trace", skip(request))]
+    pub async fn collect_http_responses(
+        &self,
+        request: http::Request,
+    ) -> Result<Vec<SignedResponse>, ChainClientError> {
+        let owner = self.identity().await?;
+        let committee = self.local_committee().await?;
+        let height = self.chain_info().await?.next_block_height;
+        let nodes = self.client.make_nodes(&committee)?;
+        let request = HttpOracleRequest::new(self.chain_id, height, request, owner, self.signer())
+            .await
+            .map_err(ChainClientError::signer_failure)?;
+        let ((), responses) = communicate_with_quorum(
+            &nodes,
+            &committee,
+            |_| (),
+            |remote_node| {
+                let request = request.clone();
+                Box::pin(async move { Ok(remote_node.handle_http_oracle_request(request).await?) })
+            },
+            self.options.grace_period,
+        )
+        .await?;
+        Ok(responses)
+    }
+
+    /// Asks a quorum of validators for their randomness shares for the next block of this
+    /// chain, and returns the operation that provides the randomness to the block.
+    ///
+    /// The operation must come before any operation whose application calls
+    /// `random_bytes`. The block must be proposed in the current round of the chain, as
+    /// validators only sign randomness shares for that round.
+    #[instrument(level = "trace")]
+    pub async fn randomness_operation(&self) -> Result<Operation, ChainClientError> {
+        let info = self.chain_info_with_manager_values().await?;
+        let round = info.manager.current_round;
+        let committee = self.local_committee().await?;
+        let randomness_keys = committee
+            .randomness_keys()
+            .ok_or(ChainClientError::RandomnessUnavailable)?;
+        let nodes = self.client.make_nodes(&committee)?;
+        let request = RandomnessRequest {
+            chain_id: self.chain_id,
+            height: info.next_block_height,
+            round,
+        };
+        let ((), shares) = communicate_with_quorum(
+            &nodes,
+            &committee,
+            |_| (),
+            |remote_node| {
+                let request = request.clone();
+                Box::pin(async move {
+                    Ok(remote_node
+                        .handle_randomness_request(request, randomness_keys)
+                        .await?)
+                })
+            },
+            self.options.grace_period,
+        )
+        .await?;
+        Ok(SystemOperation::ProvideRandomness { round, shares }.into())
+    }
+
     /// Queries an application.
     #[instrument(level = "trace", skip(query))]
     pub async fn query_application(&self, query: Query) -> Result<QueryOutcome, ChainClientError> {
@@ -3134,12 +3404,43 @@ impl<Env: Environment> ChainClient<Env> {
         parameters: Vec<u8>,
         instantiation_argument: Vec<u8>,
         required_application_ids: Vec<ApplicationId>,
+    ) -> Result<ClientOutcome<(ApplicationId, ConfirmedBlockCertificate)>, ChainClientError> {
+        self.create_upgradeable_application_untyped(
+            module_id,
+            parameters,
+            instantiation_argument,
+            required_application_ids,
+            None,
+        )
+        .await
+    }
+
+    /// Creates an application by instantiating some bytecode, which the `upgrade_authority`
+    /// can later upgrade to a different module.
+    #[instrument(
+        level = "trace",
+        skip(
+            self,
+            module_id,
+            parameters,
+            instantiation_argument,
+            required_application_ids
+        )
+    )]
+    pub async fn create_upgradeable_application_untyped(
+        &self,
+        module_id: ModuleId,
+        parameters: Vec<u8>,
+        instantiation_argument: Vec<u8>,
+        required_application_ids: Vec<ApplicationId>,
+        upgrade_authority: Option<AccountOwner>,
     ) -> Result<ClientOutcome<(ApplicationId, ConfirmedBlockCertificate)>, ChainClientError> {
         self.execute_operation(SystemOperation::CreateApplication {
             module_id,
             parameters,
             instantiation_argument,
             required_application_ids,
+            upgrade_authority,
         })
         .await?
         .try_map(|certificate| {
@@ -3163,6 +3464,23 @@ impl<Env: Environment> ChainClient<Env> {
         })
     }
 
+    /// Upgrades the application `application_id` to the module `module_id`.
+    ///
+    /// This must be the application's creator chain, and the signer must be the application's
+    /// upgrade authority. Other chains follow the upgrade when processing their inboxes.
+    #[instrument(level = "trace", skip(self))]
+    pub async fn upgrade_application(
+        &self,
+        application_id: ApplicationId,
+        module_id: ModuleId,
+    ) -> Result<ClientOutcome<ConfirmedBlockCertificate>, ChainClientError> {
+        self.execute_operation(SystemOperation::UpgradeApplication {
+            application_id,
+            module_id,
+        })
+        .await
+    }
+
     /// Creates a new committee and starts using it (admin chains only).
     #[instrument(level = "trace", skip(committee))]
     pub async fn stage_new_committee(
@@ -3217,6 +3535,7 @@ impl<Env: Environment> ChainClient<Env> {
         let _latency = metrics::PROCESS_INBOX_WITHOUT_PREPARE_LATENCY.measure_latency();
 
         let mut epoch_change_ops = self.collect_epoch_changes().await?.into_iter();
+        let mut application_upgrade_ops = self.collect_application_upgrades().await?;
 
         let mut certificates = Vec::new();
         loop {
@@ -3225,6 +3544,7 @@ impl<Env: Environment> ChainClient<Env> {
             let block_operations = stream_updates
                 .into_iter()
                 .chain(epoch_change_ops.next())
+                .chain(mem::take(&mut application_upgrade_ops))
                 .collect::<Vec<_>>();
             if incoming_bundles.is_empty() && block_operations.is_empty() {
                 return Ok((certificates, None));
@@ -3270,6 +3590,63 @@ impl<Env: Environment> ChainClient<Env> {
         Ok(epoch_change_ops)
     }
 
+    /// Returns operations to follow the upgrades of the applications on this chain that were
+    /// created on other chains, in order.
+    ///
+    /// Only the upgrades of creator chains known to the local node are found.
+    async fn collect_application_upgrades(&self) -> Result<Vec<Operation>, ChainClientError> {
+        let applications = {
+            let chain = self.chain_state_view().await?;
+            let mut applications = Vec::new();
+            for application_id in chain.execution_state.users.indices().await? {
+                let processed = chain
+                    .execution_state
+                    .system
+                    .application_upgrades
+                    .get(&application_id)
+                    .await?
+                    .map_or(0, |modules| modules.len());
+                applications.push((application_id, processed));
+            }
+            applications
+        };
+        let mut operations = Vec::new();
+        for (application_id, processed) in applications {
+            let storage = self.client.storage_client();
+            let Some(blob) = storage
+                .read_blob(application_id.description_blob_id())
+                .await?
+            else {
+                continue;
+            };
+            let description: ApplicationDescription = bcs::from_bytes(blob.bytes())?;
+            if description.creator_chain_id == self.chain_id {
+                continue;
+            }
+            let creator_chain = self
+                .client
+                .local_node
+                .chain_state_view(description.creator_chain_id)
+                .await?;
+            let upgrades = creator_chain
+                .execution_state
+                .stream_event_counts
+                .get(&application_upgrade_stream_id(application_id))
+                .await?
+                .unwrap_or_default();
+            let pending = usize::try_from(upgrades)
+                .unwrap_or(usize::MAX)
+                .saturating_sub(processed);
+            operations.extend(
+                iter::repeat_with(|| {
+                    Operation::system(SystemOperation::ProcessApplicationUpgrade(application_id))
+                })
+                .take(pending),
+            );
+        }
+        Ok(operations)
+    }
+
     /// Returns whether the system event on the admin chain with the given stream name and key
     /// exists in storage.
     async fn has_admin_event(
@@ -7,11 +7,12 @@ use std::{collections::BTreeMap, ops::Not};
 use custom_debug_derive::Debug;
 use linera_base::{
     crypto::{
-        BcsSignable, CryptoError, CryptoHash, ValidatorPublicKey, ValidatorSecretKey,
-        ValidatorSignature,
+        AccountPublicKey, AccountSignature, BcsHashable, BcsSignable, CryptoError, CryptoHash,
+        Signer, ValidatorPublicKey, ValidatorSecretKey, ValidatorSignature,
     },
     data_types::{Amount, BlockHeight, ChainDescription, Epoch, Round, Timestamp},
-    identifiers::{AccountOwner, ChainId},
+    http,
+    identifiers::{AccountOwner, ApplicationId, ChainId},
 };
 use linera_chain::{
     data_types::{ChainAndHeight, IncomingBundle, MessageBundle},
@@ -90,6 +91,9 @@ pub struct ChainInfoQuery {
     /// Include a vote to switch to fallback mode, if appropriate.
     #[debug(skip_if = Not::not)]
     pub request_fallback: bool,
+    /// Query the number of stored bytes each application on the chain paid storage rent for.
+    #[debug(skip_if = Not::not)]
+    pub request_storage_usage: bool,
 }
 
 impl ChainInfoQuery {
@@ -105,6 +109,7 @@ impl ChainInfoQuery {
             request_manager_values: false,
             request_leader_timeout: false,
             request_fallback: false,
+            request_storage_usage: false,
         }
     }
 
@@ -152,6 +157,11 @@ impl ChainInfoQuery {
         self.request_fallback = true;
         self
     }
+
+    pub fn with_storage_usage(mut self) -> Self {
+        self.request_storage_usage = true;
+        self
+    }
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -195,6 +205,13 @@ pub struct ChainInfo {
     /// The response to `request_received_certificates_excluding_first_n`
     #[debug(skip_if = Vec::is_empty)]
     pub requested_received_log: Vec<ChainAndHeight>,
+    /// The number of stored bytes each application on the chain paid storage rent for (if
+    /// requested).
+    #[debug(skip_if = BTreeMap::is_empty)]
+    pub requested_storage_usage: BTreeMap<ApplicationId, u64>,
+    /// The height below which this validator may have pruned the chain's blocks. Earlier
+    /// blocks have to be downloaded from a node that keeps the full history.
+    pub pruned_height: BlockHeight,
 }
 
 impl ChainInfo {
@@ -208,6 +225,12 @@ impl ChainInfo {
             next_block_height: self.next_block_height,
         })
     }
+
+    /// Returns the total number of stored bytes the chain's applications paid storage rent for,
+    /// if the storage usage was requested.
+    pub fn total_storage_usage(&self) -> u64 {
+        self.requested_storage_usage.values().sum()
+    }
 }
 
 /// The response to an `ChainInfoQuery`
@@ -218,6 +241,142 @@ pub struct ChainInfoResponse {
     pub signature: Option<ValidatorSignature>,
 }
 
+/// A request for a validator to perform an HTTP request on behalf of the next block of a
+/// chain, and to sign the response.
+///
+/// The request must be signed by an owner of the chain.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[cfg_attr(with_testing, derive(Eq, PartialEq))]
+pub struct HttpOracleRequest {
+    /// The chain proposing the block.
+    pub chain_id: ChainId,
+    /// The height of the block.
+    pub height: BlockHeight,
+    /// The HTTP request to perform.
+    pub request: http::Request,
+    /// The public key of the chain owner who signed the request.
+    pub public_key: AccountPublicKey,
+    /// The owner's signature over the chain, the height and the HTTP request.
+    pub signature: AccountSignature,
+}
+
+/// The value signed by a chain owner in an [`HttpOracleRequest`].
+#[derive(Debug, Serialize, Deserialize)]
+struct HttpOracleRequestValue {
+    chain_id: ChainId,
+    height: BlockHeight,
+    request: http::Request,
+}
+
+impl BcsSignable<'_> for HttpOracleRequestValue {}
+
+impl HttpOracleRequest {
+    /// Creates a request for the block at `height` of the chain `chain_id`, signed by the
+    /// chain `owner`.
+    pub async fn new<S: Signer + ?Sized>(
+        chain_id: ChainId,
+        height: BlockHeight,
+        request: http::Request,
+        owner: AccountOwner,
+        signer: &S,
+    ) -> Result<Self, S::Error> {
+        let value = HttpOracleRequestValue {
+            chain_id,
+            height,
+            request,
+        };
+        let signature = signer.sign(&owner, &CryptoHash::new(&value)).await?;
+        let public_key = signer.get_public_key(&owner).await?;
+        Ok(HttpOracleRequest {
+            chain_id,
+            height,
+            request: value.request,
+            public_key,
+            signature,
+        })
+    }
+
+    /// Returns the chain owner who signed this request.
+    pub fn owner(&self) -> AccountOwner {
+        self.public_key.into()
+    }
+
+    /// Checks the owner's signature.
+    pub fn check_signature(&self) -> Result<(), CryptoError> {
+        let value = HttpOracleRequestValue {
+            chain_id: self.chain_id,
+            height: self.height,
+            request: self.request.clone(),
+        };
+        self.signature.verify(&value, self.public_key)
+    }
+}
+
+/// A request for a validator's share of the randomness for the next block of a chain.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[cfg_attr(with_testing, derive(Eq, PartialEq))]
+pub struct RandomnessRequest {
+    /// The chain proposing the block.
+    pub chain_id: ChainId,
+    /// The height of the block.
+    pub height: BlockHeight,
+    /// The round in which the block will be proposed.
+    pub round: Round,
+}
+
+/// A request for a chunk of a snapshot of a chain's state.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[cfg_attr(with_testing, derive(Eq, PartialEq))]
+pub struct ChainSnapshotRequest {
+    /// The chain whose state is requested.
+    pub chain_id: ChainId,
+    /// The latest block included in the snapshot, or `None` to take a new snapshot after the
+    /// chain's latest block.
+    #[debug(skip_if = Option::is_none)]
+    pub block_hash: Option<CryptoHash>,
+    /// The index of the requested chunk.
+    pub index: u32,
+}
+
+/// The description of a snapshot of a chain's state after one of its blocks.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[cfg_attr(with_testing, derive(Eq, PartialEq))]
+pub struct ChainSnapshotManifest {
+    /// The chain whose state this is.
+    pub chain_id: ChainId,
+    /// The latest block included in the snapshot. Its certificate attests the snapshot's
+    /// execution state.
+    pub block_hash: CryptoHash,
+    /// The hashes of the snapshot's chunks, in order.
+    pub chunk_hashes: Vec<CryptoHash>,
+}
+
+/// A chunk of a snapshot of a chain's state.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[cfg_attr(with_testing, derive(Eq, PartialEq))]
+pub struct ChainSnapshotChunk {
+    /// The manifest of the snapshot, if a new snapshot was requested.
+    #[debug(skip_if = Option::is_none)]
+    pub manifest: Option<ChainSnapshotManifest>,
+    /// The keys and values of the chain's storage in this chunk.
+    #[debug(skip)]
+    pub entries: ChainSnapshotEntries,
+}
+
+/// Keys and values of a chain's storage, ordered by key.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[cfg_attr(with_testing, derive(Eq, PartialEq))]
+pub struct ChainSnapshotEntries(pub Vec<(Vec<u8>, Vec<u8>)>);
+
+impl BcsHashable<'_> for ChainSnapshotEntries {}
+
+impl ChainSnapshotEntries {
+    /// Returns the hash that the manifest of a snapshot lists for this chunk.
+    pub fn hash(&self) -> CryptoHash {
+        CryptoHash::new(self)
+    }
+}
+
 /// An internal request between chains within a validator.
 #[derive(Clone, Debug, Serialize, Deserialize)]
 #[cfg_attr(with_testing, derive(Eq, PartialEq))]
@@ -283,6 +442,8 @@ where
             requested_sent_certificate_hashes: Vec::new(),
             count_received_log: view.received_log.count(),
             requested_received_log: Vec::new(),
+            requested_storage_usage: BTreeMap::new(),
+            pruned_height: *view.pruned_height.get(),
         }
     }
 }
@@ -15,7 +15,7 @@ use linera_base::{
 };
 use linera_chain::{
     data_types::{BlockProposal, ProposedBlock},
-    types::{Block, GenericCertificate, LiteCertificate},
+    types::{Block, ConfirmedBlockCertificate, GenericCertificate, LiteCertificate},
     ChainStateView,
 };
 use linera_execution::{committee::Committee, BlobState, Query, QueryOutcome};
@@ -26,7 +26,7 @@ use tokio::sync::OwnedRwLockReadGuard;
 use tracing::{instrument, warn};
 
 use crate::{
-    data_types::{ChainInfo, ChainInfoQuery, ChainInfoResponse},
+    data_types::{ChainInfo, ChainInfoQuery, ChainInfoResponse, ChainSnapshotEntries},
     notifier::Notifier,
     worker::{ProcessableCertificate, WorkerError, WorkerState},
 };
@@ -244,6 +244,20 @@ where
         Ok(self.node.state.chain_state_view(chain_id).await?)
     }
 
+    /// Replaces the state of a chain with a snapshot taken after the block of `certificate`.
+    #[instrument(level = "trace", skip_all)]
+    pub async fn load_chain_snapshot(
+        &self,
+        certificate: ConfirmedBlockCertificate,
+        chunks: Vec<ChainSnapshotEntries>,
+    ) -> Result<(), LocalNodeError> {
+        Ok(self
+            .node
+            .state
+            .load_chain_snapshot(certificate, chunks)
+            .await?)
+    }
+
     #[instrument(level = "trace", skip(self))]
     pub(crate) async fn chain_info(
         &self,
@@ -10,7 +10,9 @@ use futures::stream::Stream;
 use linera_base::{
     crypto::{CryptoError, CryptoHash, ValidatorPublicKey},
     data_types::{ArithmeticError, BlobContent, BlockHeight, NetworkDescription},
+    http::SignedResponse,
     identifiers::{BlobId, ChainId},
+    randomness::RandomnessShare,
 };
 use linera_chain::{
     data_types::BlockProposal,
@@ -27,7 +29,10 @@ use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
 use crate::{
-    data_types::{ChainInfoQuery, ChainInfoResponse},
+    data_types::{
+        ChainInfoQuery, ChainInfoResponse, ChainSnapshotChunk, ChainSnapshotRequest,
+        HttpOracleRequest, RandomnessRequest,
+    },
     worker::{Notification, WorkerError},
 };
 
@@ -135,6 +140,25 @@ pub trait ValidatorNode {
 
     /// Returns the missing `Blob`s by their IDs.
     async fn missing_blob_ids(&self, blob_ids: Vec<BlobId>) -> Result<Vec<BlobId>, NodeError>;
+
+    /// Performs an HTTP request on behalf of the next block of a chain, and returns the
+    /// response signed by the validator.
+    async fn handle_http_oracle_request(
+        &self,
+        request: HttpOracleRequest,
+    ) -> Result<SignedResponse, NodeError>;
+
+    /// Returns the validator's share of the randomness for the next block of a chain.
+    async fn handle_randomness_request(
+        &self,
+        request: RandomnessRequest,
+    ) -> Result<RandomnessShare, NodeError>;
+
+    /// Downloads a chunk of a snapshot of a chain's state.
+    async fn download_chain_snapshot_chunk(
+        &self,
+        request: ChainSnapshotRequest,
+    ) -> Result<ChainSnapshotChunk, NodeError>;
 }
 
 /// Turn an address into a validator node.
@@ -254,6 +278,12 @@ pub enum NodeError {
     EmptyBlobsNotFound,
     #[error("Local error handling validator response")]
     ResponseHandlingError { error: String },
+    #[error("The signed HTTP response is invalid")]
+    InvalidHttpOracleResponse,
+    #[error("The randomness share is invalid")]
+    InvalidRandomnessShare,
+    #[error("The chain snapshot chunk is invalid")]
+    InvalidChainSnapshotChunk,
 }
 
 impl From<tonic::Status> for NodeError {
@@ -9,7 +9,9 @@ use linera_base::{
     crypto::{CryptoHash, ValidatorPublicKey},
     data_types::{Blob, BlockHeight},
     ensure,
+    http::SignedResponse,
     identifiers::{BlobId, ChainId},
+    randomness::{RandomnessPublicKeys, RandomnessShare},
 };
 use linera_chain::{
     data_types::BlockProposal,
@@ -22,7 +24,10 @@ use rand::seq::SliceRandom as _;
 use tracing::{instrument, warn};
 
 use crate::{
-    data_types::{BlockHeightRange, ChainInfo, ChainInfoQuery, ChainInfoResponse},
+    data_types::{
+        BlockHeightRange, ChainInfo, ChainInfoQuery, ChainInfoResponse, HttpOracleRequest,
+        RandomnessRequest,
+    },
     node::{CrossChainMessageDelivery, NodeError, ValidatorNode},
 };
 
@@ -175,6 +180,15 @@ impl<N: ValidatorNode> RemoteNode<N> {
         };
         let query = ChainInfoQuery::new(chain_id).with_sent_certificate_hashes_in_range(range);
         match self.handle_chain_info_query(query).await {
+            Ok(info) if info.pruned_height > start => {
+                // Let the caller fall back to a node that still has these blocks.
+                tracing::info!(
+                    name = ?self.public_key,
+                    pruned_height = %info.pruned_height,
+                    "Validator pruned the requested certificates"
+                );
+                Ok(None)
+            }
             Ok(info) => {
                 let certificates = self
                     .node
@@ -302,6 +316,52 @@ impl<N: ValidatorNode> RemoteNode<N> {
         Ok(hashes)
     }
 
+    /// Requests the validator to perform an HTTP request for the next block of a chain, and
+    /// checks its signature on the response.
+    #[instrument(level = "trace")]
+    pub(crate) async fn handle_http_oracle_request(
+        &self,
+        request: HttpOracleRequest,
+    ) -> Result<SignedResponse, NodeError> {
+        let HttpOracleRequest {
+            chain_id,
+            height,
+            request: http_request,
+            ..
+        } = request.clone();
+        let response = self.node.handle_http_oracle_request(request).await?;
+        ensure!(
+            response.validator == self.public_key
+                && response.check(chain_id, height, &http_request).is_ok(),
+            NodeError::InvalidHttpOracleResponse
+        );
+        Ok(response)
+    }
+
+    /// Requests the validator's share of the randomness for the next block of a chain, and
+    /// checks it against the committee's randomness keys.
+    #[instrument(level = "trace", skip(randomness_keys))]
+    pub(crate) async fn handle_randomness_request(
+        &self,
+        request: RandomnessRequest,
+        randomness_keys: &RandomnessPublicKeys,
+    ) -> Result<RandomnessShare, NodeError> {
+        let RandomnessRequest {
+            chain_id,
+            height,
+            round,
+        } = request;
+        let share = self.node.handle_randomness_request(request).await?;
+        ensure!(
+            share.validator == self.public_key
+                && randomness_keys
+                    .check_share(&share, chain_id, height, round)
+                    .is_ok(),
+            NodeError::InvalidRandomnessShare
+        );
+        Ok(share)
+    }
+
     #[instrument(level = "trace")]
     pub async fn download_certificates(
         &self,
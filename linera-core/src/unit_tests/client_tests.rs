@@ -2641,3 +2641,236 @@ where
     assert_eq!(client.local_balance().await.unwrap(), expected_balance);
     Ok(())
 }
+
+/// Serves HTTP requests with a JSON price that increases with every request, and a `Date`
+/// header that differs between responses. Returns the URL of the server.
+async fn serve_prices() -> String {
+    use tokio::io::{AsyncReadExt as _, AsyncWriteExt as _};
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let address = listener.local_addr().unwrap();
+    tokio::spawn(async move {
+        for count in 0.. {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let mut buffer = [0; 4096];
+            let _ = stream.read(&mut buffer).await.unwrap();
+            let body = format!(r#"{{"price":{},"unit":"USD"}}"#, 10 + count);
+            let response = format!(
+                "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\n\
+                 Date: Mon, 19 Oct 2026 10:00:{:02} GMT\r\nContent-Length: {}\r\n\
+                 Connection: close\r\n\r\n{body}",
+                count % 60,
+                body.len(),
+            );
+            stream.write_all(response.as_bytes()).await.unwrap();
+        }
+    });
+    format!("http://{address}/price")
+}
+
+#[test_case(MemoryStorageBuilder::default(); "memory")]
+#[test_log::test(tokio::test)]
+async fn test_http_oracle<B>(storage_builder: B) -> anyhow::Result<()>
+where
+    B: StorageBuilder,
+{
+    use linera_base::{crypto::Signer as _, http};
+    use linera_execution::http_oracle;
+
+    use crate::{data_types::HttpOracleRequest, worker::MAX_HTTP_ORACLE_REQUESTS_PER_BLOCK};
+
+    let policy = ResourceControlPolicy {
+        http_request_allow_list: BTreeSet::from(["127.0.0.1".to_owned()]),
+        ..ResourceControlPolicy::default()
+    };
+    let signer = InMemorySigner::new(None);
+    let mut builder = TestBuilder::new(storage_builder, 4, 0, signer)
+        .await?
+        .with_policy(policy);
+    let client = builder.add_root_chain(1, Amount::ONE).await?;
+    let chain_id = client.chain_id();
+    let committee = builder.initial_committee.clone();
+    let request = http::Request::get(serve_prices().await);
+
+    // The validators' responses differ in their price and `Date` header, but can be
+    // verified and reduced to their median.
+    let responses = client.collect_http_responses(request.clone()).await?;
+    assert!(responses.len() >= 3);
+    let reducer = http::Reducer::Median(vec!["/price".to_owned()]);
+    let height = BlockHeight::ZERO;
+    let response = http_oracle::verify_http_responses(
+        &committee, None, chain_id, height, &request, &reducer, &responses,
+    )?;
+    assert_eq!(response.status, 200);
+    assert!(response.headers.iter().all(|header| header.name != "date"));
+    let body: serde_json::Value = serde_json::from_slice(&response.body)?;
+    assert_eq!(body["unit"], "USD");
+    let price = body["price"].as_u64().unwrap();
+    let prices = responses
+        .iter()
+        .map(|response| serde_json::from_slice::<serde_json::Value>(&response.response.body))
+        .collect::<Result<Vec<_>, _>>()?;
+    assert!(prices.iter().any(|body| body["price"] == price));
+    assert_matches!(
+        http_oracle::verify_http_responses(
+            &committee,
+            None,
+            chain_id,
+            height,
+            &request,
+            &http::Reducer::ExactMatch,
+            &responses
+        ),
+        Err(ExecutionError::IrreducibleHttpResponses)
+    );
+    assert_matches!(
+        http_oracle::verify_http_responses(
+            &committee,
+            None,
+            chain_id,
+            height.try_add_one()?,
+            &request,
+            &reducer,
+            &responses
+        ),
+        Err(ExecutionError::InvalidHttpResponse(_))
+    );
+    assert_matches!(
+        http_oracle::verify_http_responses(
+            &committee,
+            None,
+            chain_id,
+            height,
+            &request,
+            &reducer,
+            &responses[..1]
+        ),
+        Err(ExecutionError::HttpResponsesBelowQuorum { .. })
+    );
+
+    // Validators only perform requests signed by an owner of the chain.
+    let mut other_signer = InMemorySigner::new(None);
+    let other_owner: AccountOwner = other_signer.generate_new().into();
+    let other_request = HttpOracleRequest::new(
+        chain_id,
+        height,
+        request.clone(),
+        other_owner,
+        &other_signer,
+    )
+    .await?;
+    let result = builder
+        .node(0)
+        .handle_http_oracle_request(other_request.clone())
+        .await;
+    assert_matches!(
+        result,
+        Err(NodeError::WorkerError { error }) if error.contains("owner of the chain")
+    );
+    let mut forged_request = other_request;
+    forged_request.public_key = client
+        .signer()
+        .get_public_key(&client.identity().await?)
+        .await?;
+    let result = builder
+        .node(0)
+        .handle_http_oracle_request(forged_request)
+        .await;
+    assert_matches!(result, Err(NodeError::WorkerError { .. }));
+
+    // They only perform a limited number of requests per block.
+    let owner = client.identity().await?;
+    let owner_request =
+        HttpOracleRequest::new(chain_id, height, request, owner, client.signer()).await?;
+    let node = builder.node(0);
+    let mut performed = 0;
+    let error = loop {
+        match node.handle_http_oracle_request(owner_request.clone()).await {
+            Ok(_) => performed += 1,
+            Err(error) => break error,
+        }
+    };
+    assert!(performed < MAX_HTTP_ORACLE_REQUESTS_PER_BLOCK);
+    assert_matches!(
+        error,
+        NodeError::WorkerError { error } if error.contains("HTTP oracle requests")
+    );
+    Ok(())
+}
+
+#[test_case(MemoryStorageBuilder::default(); "memory")]
+#[test_log::test(tokio::test)]
+async fn test_randomness<B>(storage_builder: B) -> anyhow::Result<()>
+where
+    B: StorageBuilder,
+{
+    use linera_execution::randomness::verify_randomness_shares;
+
+    use crate::data_types::RandomnessRequest;
+
+    let signer = InMemorySigner::new(None);
+    let mut builder = TestBuilder::new(storage_builder, 4, 0, signer)
+        .await?
+        .with_randomness()
+        .await;
+    let client = builder.add_root_chain(1, Amount::ONE).await?;
+    let chain_id = client.chain_id();
+    let committee = builder.initial_committee.clone();
+    let height = BlockHeight::ZERO;
+
+    // The chain has a single super owner, so blocks are proposed in the fast round.
+    let operation = client.randomness_operation().await?;
+    let Operation::System(system_operation) = &operation else {
+        panic!("unexpected operation {operation:?}");
+    };
+    let SystemOperation::ProvideRandomness { round, shares } = system_operation.as_ref() else {
+        panic!("unexpected operation {system_operation:?}");
+    };
+    let (round, shares) = (*round, shares.clone());
+    assert_eq!(round, Round::Fast);
+    let seed = verify_randomness_shares(&committee, chain_id, height, round, &shares)?;
+
+    // Any quorum of shares yields the same seed.
+    let request = RandomnessRequest {
+        chain_id,
+        height,
+        round,
+    };
+    let mut all_shares = Vec::new();
+    for i in 0..4 {
+        all_shares.push(
+            builder
+                .node(i)
+                .handle_randomness_request(request.clone())
+                .await?,
+        );
+    }
+    for subset in [&all_shares[..3], &all_shares[1..], &all_shares[..]] {
+        assert_eq!(
+            verify_randomness_shares(&committee, chain_id, height, round, subset)?,
+            seed
+        );
+    }
+    assert_matches!(
+        verify_randomness_shares(&committee, chain_id, height, round, &all_shares[..2]),
+        Err(ExecutionError::RandomnessSharesBelowQuorum { .. })
+    );
+
+    // Validators only sign shares for the current round, and the shares are bound to it.
+    let other_request = RandomnessRequest {
+        round: Round::SingleLeader(0),
+        ..request
+    };
+    assert_matches!(
+        builder.node(0).handle_randomness_request(other_request).await,
+        Err(NodeError::WorkerError { error }) if error.contains("current round")
+    );
+    let other_round = SystemOperation::ProvideRandomness {
+        round: Round::SingleLeader(0),
+        shares,
+    };
+    assert!(client.execute_operation(other_round).await.is_err());
+
+    client.execute_operation(operation).await?.unwrap();
+    Ok(())
+}
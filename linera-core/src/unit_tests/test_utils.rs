@@ -18,8 +18,10 @@ use futures::{
 use linera_base::{
     crypto::{AccountPublicKey, CryptoHash, InMemorySigner, ValidatorKeypair, ValidatorPublicKey},
     data_types::*,
+    http::SignedResponse,
     identifiers::{AccountOwner, BlobId, ChainId},
     ownership::ChainOwnership,
+    randomness::{RandomnessPublicKeys, RandomnessSecretKey, RandomnessShare},
 };
 use linera_chain::{
     data_types::BlockProposal,
@@ -257,6 +259,36 @@ where
         })
         .await
     }
+
+    async fn handle_http_oracle_request(
+        &self,
+        request: HttpOracleRequest,
+    ) -> Result<SignedResponse, NodeError> {
+        self.spawn_and_receive(move |validator, sender| {
+            validator.do_handle_http_oracle_request(request, sender)
+        })
+        .await
+    }
+
+    async fn handle_randomness_request(
+        &self,
+        request: RandomnessRequest,
+    ) -> Result<RandomnessShare, NodeError> {
+        self.spawn_and_receive(move |validator, sender| {
+            validator.do_handle_randomness_request(request, sender)
+        })
+        .await
+    }
+
+    async fn download_chain_snapshot_chunk(
+        &self,
+        request: ChainSnapshotRequest,
+    ) -> Result<ChainSnapshotChunk, NodeError> {
+        self.spawn_and_receive(move |validator, sender| {
+            validator.do_download_chain_snapshot_chunk(request, sender)
+        })
+        .await
+    }
 }
 
 impl<S> LocalValidatorClient<S>
@@ -283,6 +315,14 @@ where
         self.client.lock().await.fault_type = fault_type;
     }
 
+    async fn set_randomness_key(&self, randomness_key: RandomnessSecretKey) {
+        let mut validator = self.client.lock().await;
+        validator.state = validator
+            .state
+            .clone()
+            .with_randomness_key(Some(randomness_key));
+    }
+
     async fn fault_type(&self) -> FaultType {
         self.client.lock().await.fault_type
     }
@@ -619,6 +659,48 @@ where
             .map_err(Into::into);
         sender.send(missing_blob_ids)
     }
+
+    async fn do_handle_http_oracle_request(
+        self,
+        request: HttpOracleRequest,
+        sender: oneshot::Sender<Result<SignedResponse, NodeError>>,
+    ) -> Result<(), Result<SignedResponse, NodeError>> {
+        let validator = self.client.lock().await;
+        let result = validator
+            .state
+            .handle_http_oracle_request(request)
+            .await
+            .map_err(Into::into);
+        sender.send(result)
+    }
+
+    async fn do_handle_randomness_request(
+        self,
+        request: RandomnessRequest,
+        sender: oneshot::Sender<Result<RandomnessShare, NodeError>>,
+    ) -> Result<(), Result<RandomnessShare, NodeError>> {
+        let validator = self.client.lock().await;
+        let result = validator
+            .state
+            .handle_randomness_request(request)
+            .await
+            .map_err(Into::into);
+        sender.send(result)
+    }
+
+    async fn do_download_chain_snapshot_chunk(
+        self,
+        request: ChainSnapshotRequest,
+        sender: oneshot::Sender<Result<ChainSnapshotChunk, NodeError>>,
+    ) -> Result<(), Result<ChainSnapshotChunk, NodeError>> {
+        let validator = self.client.lock().await;
+        let result = validator
+            .state
+            .handle_chain_snapshot_request(request)
+            .await
+            .map_err(Into::into);
+        sender.send(result)
+    }
 }
 
 #[derive(Clone)]
@@ -800,7 +882,34 @@ where
 
     pub fn with_policy(mut self, policy: ResourceControlPolicy) -> Self {
         let validators = self.initial_committee.validators().clone();
+        let randomness_keys = self.initial_committee.randomness_keys().cloned();
         self.initial_committee = Committee::new(validators, policy);
+        if let Some(randomness_keys) = randomness_keys {
+            self.initial_committee = self
+                .initial_committee
+                .with_randomness_keys(randomness_keys)
+                .expect("the votes are unchanged");
+        }
+        self
+    }
+
+    /// Gives the validators shares of a randomness key, so that they can provide
+    /// randomness to blocks.
+    pub async fn with_randomness(mut self) -> Self {
+        let (randomness_keys, randomness_secrets) = RandomnessPublicKeys::deal(
+            self.initial_committee.keys_and_weights(),
+            self.initial_committee.quorum_threshold(),
+            &mut rand::rngs::OsRng,
+        );
+        self.initial_committee = self
+            .initial_committee
+            .with_randomness_keys(randomness_keys)
+            .expect("the keys were dealt for this committee");
+        for validator in &self.validator_clients {
+            validator
+                .set_randomness_key(randomness_secrets[&validator.public_key].clone())
+                .await;
+        }
         self
     }
 
@@ -183,6 +183,7 @@ where
         parameters: parameters_bytes.clone(),
         instantiation_argument: initial_value_bytes.clone(),
         required_application_ids: vec![],
+        upgrade_authority: None,
     };
     let application_description = ApplicationDescription {
         module_id,
@@ -16,6 +16,7 @@ use std::{
 };
 
 use assert_matches::assert_matches;
+use async_trait::async_trait;
 use linera_base::{
     crypto::{AccountPublicKey, AccountSecretKey, CryptoHash, InMemorySigner, ValidatorKeypair},
     data_types::*,
@@ -65,7 +66,7 @@ use crate::test_utils::RocksDbStorageBuilder;
 #[cfg(feature = "scylladb")]
 use crate::test_utils::ScyllaDbStorageBuilder;
 use crate::{
-    chain_worker::CrossChainUpdateHelper,
+    chain_worker::{CrossChainUpdateHelper, HistoryArchive, RetentionPolicy},
     data_types::*,
     test_utils::{MemoryStorageBuilder, StorageBuilder},
     worker::{
@@ -3741,6 +3742,7 @@ where
     let query = Query::User {
         application_id,
         bytes: vec![],
+        at_height: None,
     };
     for query_time in query_times {
         clock.set(query_time);
@@ -3813,6 +3815,7 @@ where
     let query = Query::User {
         application_id,
         bytes: vec![],
+        at_height: None,
     };
 
     let query_contexts_before_new_block =
@@ -3934,3 +3937,229 @@ where
 
     Ok(())
 }
+
+/// Records the heights of the blocks it archives.
+#[derive(Default)]
+struct RecordingArchive(Mutex<Vec<BlockHeight>>);
+
+#[async_trait]
+impl HistoryArchive for RecordingArchive {
+    async fn archive(
+        &self,
+        certificates: &[ConfirmedBlockCertificate],
+        _blobs: &[Blob],
+    ) -> Result<(), std::io::Error> {
+        let mut heights = self.0.lock().unwrap();
+        heights.extend(
+            certificates
+                .iter()
+                .map(|certificate| certificate.block().header.height),
+        );
+        Ok(())
+    }
+}
+
+/// Tests that blocks outside of the retention policy are archived and pruned from storage.
+#[test_case(MemoryStorageBuilder::default(); "memory")]
+#[cfg_attr(feature = "rocksdb", test_case(RocksDbStorageBuilder::new().await; "rocks_db"))]
+#[cfg_attr(feature = "dynamodb", test_case(DynamoDbStorageBuilder::default(); "dynamo_db"))]
+#[cfg_attr(feature = "scylladb", test_case(ScyllaDbStorageBuilder::default(); "scylla_db"))]
+#[test_log::test(tokio::test)]
+async fn test_prune_history<B>(mut storage_builder: B) -> anyhow::Result<()>
+where
+    B: StorageBuilder,
+{
+    let storage = storage_builder.build().await?;
+    let mut env = TestEnvironment::new(storage.clone(), false, false).await;
+    let archive = Arc::new(RecordingArchive::default());
+    env.worker.chain_worker_config.retention_policy = RetentionPolicy {
+        keep_heights: Some(1),
+        keep_duration: None,
+    };
+    env.worker.chain_worker_config.history_archive = Some(archive.clone());
+    let owner_key = AccountSecretKey::generate().public();
+    let balance = Amount::from_tokens(5);
+    let chain_description = env.add_root_chain(1, owner_key.into(), balance).await;
+    let chain_id = chain_description.id();
+
+    let mut certificates: Vec<ConfirmedBlockCertificate> = Vec::new();
+    for tokens_left in (2..5).rev() {
+        let certificate = env
+            .make_transfer_certificate(
+                chain_description.clone(),
+                owner_key,
+                owner_key.into(),
+                AccountOwner::CHAIN,
+                Recipient::Burn,
+                Amount::ONE,
+                Vec::new(),
+                Amount::from_tokens(tokens_left),
+                BTreeMap::new(),
+                certificates.last(),
+            )
+            .await;
+        env.worker()
+            .fully_handle_certificate_with_notifications(certificate.clone(), &())
+            .await?;
+        certificates.push(certificate);
+    }
+
+    let info = env
+        .worker()
+        .handle_chain_info_query(ChainInfoQuery::new(chain_id))
+        .await?
+        .0
+        .info;
+    assert_eq!(info.next_block_height, BlockHeight(3));
+    assert_eq!(info.pruned_height, BlockHeight(2));
+    assert_eq!(
+        *archive.0.lock().unwrap(),
+        vec![BlockHeight(0), BlockHeight(1)]
+    );
+    assert!(!storage.contains_certificate(certificates[0].hash()).await?);
+    assert!(!storage.contains_certificate(certificates[1].hash()).await?);
+    assert!(storage.contains_certificate(certificates[2].hash()).await?);
+    Ok(())
+}
+
+/// Returns the chunks of a new snapshot of the chain, with its manifest.
+async fn take_chain_snapshot<S>(
+    worker: &WorkerState<S>,
+    chain_id: ChainId,
+) -> anyhow::Result<(ChainSnapshotManifest, Vec<ChainSnapshotEntries>)>
+where
+    S: Storage + Clone + Send + Sync + 'static,
+{
+    let request = ChainSnapshotRequest {
+        chain_id,
+        block_hash: None,
+        index: 0,
+    };
+    let first_chunk = worker.handle_chain_snapshot_request(request).await?;
+    let manifest = first_chunk.manifest.expect("a new snapshot has a manifest");
+    assert_eq!(manifest.chunk_hashes[0], first_chunk.entries.hash());
+    let mut chunks = vec![first_chunk.entries];
+    for index in 1..manifest.chunk_hashes.len() as u32 {
+        let request = ChainSnapshotRequest {
+            chain_id,
+            block_hash: Some(manifest.block_hash),
+            index,
+        };
+        let chunk = worker.handle_chain_snapshot_request(request).await?;
+        assert!(chunk.manifest.is_none());
+        assert_eq!(manifest.chunk_hashes[index as usize], chunk.entries.hash());
+        chunks.push(chunk.entries);
+    }
+    Ok((manifest, chunks))
+}
+
+/// Tests that a chain's state can be copied from a snapshot, that a snapshot is only loaded
+/// with the certificate of the block it was taken after, and that a rejected snapshot leaves
+/// the chain's state unchanged.
+#[test_case(MemoryStorageBuilder::default(); "memory")]
+#[cfg_attr(feature = "rocksdb", test_case(RocksDbStorageBuilder::new().await; "rocks_db"))]
+#[cfg_attr(feature = "dynamodb", test_case(DynamoDbStorageBuilder::default(); "dynamo_db"))]
+#[cfg_attr(feature = "scylladb", test_case(ScyllaDbStorageBuilder::default(); "scylla_db"))]
+#[test_log::test(tokio::test)]
+async fn test_chain_snapshot<B>(mut storage_builder: B) -> anyhow::Result<()>
+where
+    B: StorageBuilder,
+{
+    let mut env = TestEnvironment::new(storage_builder.build().await?, false, false).await;
+    let owner_key = AccountSecretKey::generate().public();
+    let balance = Amount::from_tokens(5);
+    let chain_description = env.add_root_chain(1, owner_key.into(), balance).await;
+    let chain_id = chain_description.id();
+
+    let mut certificates: Vec<ConfirmedBlockCertificate> = Vec::new();
+    let mut snapshots = Vec::new();
+    for tokens_left in (3..5).rev() {
+        let certificate = env
+            .make_transfer_certificate(
+                chain_description.clone(),
+                owner_key,
+                owner_key.into(),
+                AccountOwner::CHAIN,
+                Recipient::Burn,
+                Amount::ONE,
+                Vec::new(),
+                Amount::from_tokens(tokens_left),
+                BTreeMap::new(),
+                certificates.last(),
+            )
+            .await;
+        env.worker()
+            .fully_handle_certificate_with_notifications(certificate.clone(), &())
+            .await?;
+        let (manifest, chunks) = take_chain_snapshot(env.worker(), chain_id).await?;
+        assert_eq!(manifest.block_hash, certificate.hash());
+        certificates.push(certificate);
+        snapshots.push(chunks);
+    }
+
+    // Only the latest snapshot of a chain is kept.
+    let request = ChainSnapshotRequest {
+        chain_id,
+        block_hash: Some(certificates[0].hash()),
+        index: 0,
+    };
+    assert_matches!(
+        env.worker().handle_chain_snapshot_request(request).await,
+        Err(WorkerError::ChainSnapshotExpired(hash)) if hash == certificates[0].hash()
+    );
+
+    let receiver = TestEnvironment::new(storage_builder.build().await?, true, false).await;
+    let storage = receiver.worker().storage_client().clone();
+    assert_matches!(
+        receiver
+            .worker()
+            .load_chain_snapshot(certificates[1].clone(), snapshots[0].clone())
+            .await,
+        Err(WorkerError::InvalidChainSnapshot(hash)) if hash == certificates[1].hash()
+    );
+    let chain = storage.load_chain(chain_id).await?;
+    assert_eq!(chain.tip_state.get().next_block_height, BlockHeight(0));
+    assert!(chain.execution_state.system.description.get().is_none());
+
+    receiver
+        .worker()
+        .load_chain_snapshot(certificates[0].clone(), snapshots[0].clone())
+        .await?;
+    assert_matches!(
+        receiver
+            .worker()
+            .load_chain_snapshot(certificates[1].clone(), snapshots[0].clone())
+            .await,
+        Err(WorkerError::InvalidChainSnapshot(hash)) if hash == certificates[1].hash()
+    );
+    let chain = storage.load_chain(chain_id).await?;
+    assert_eq!(chain.tip_state.get().next_block_height, BlockHeight(1));
+    assert_eq!(
+        *chain.execution_state.system.balance.get(),
+        Amount::from_tokens(4)
+    );
+    let staged_chain = storage.load_staged_chain(chain_id).await?;
+    assert_eq!(
+        staged_chain.tip_state.get().next_block_height,
+        BlockHeight(0)
+    );
+
+    receiver
+        .worker()
+        .load_chain_snapshot(certificates[1].clone(), snapshots[1].clone())
+        .await?;
+    let info = receiver
+        .worker()
+        .handle_chain_info_query(ChainInfoQuery::new(chain_id))
+        .await?
+        .0
+        .info;
+    assert_eq!(info.next_block_height, BlockHeight(2));
+    assert_eq!(info.pruned_height, BlockHeight(1));
+    assert_eq!(info.chain_balance, Amount::from_tokens(3));
+    assert!(storage.contains_certificate(certificates[1].hash()).await?);
+    let chain = storage.load_chain(chain_id).await?;
+    assert_eq!(chain.received_log.count(), 0);
+    assert!(chain.outboxes.indices().await?.is_empty());
+    Ok(())
+}
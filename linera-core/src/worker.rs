@@ -5,6 +5,7 @@
 use std::{
     collections::{BTreeMap, HashMap, HashSet, VecDeque},
     num::NonZeroUsize,
+    ops::Bound,
     sync::{Arc, Mutex, RwLock},
     time::Duration,
 };
@@ -16,9 +17,11 @@ use linera_base::{
         ApplicationDescription, ArithmeticError, Blob, BlockHeight, DecompressionError, Epoch,
         Round,
     },
-    doc_scalar,
+    doc_scalar, ensure,
     hashed::Hashed,
+    http::SignedResponse,
     identifiers::{AccountOwner, ApplicationId, BlobId, ChainId},
+    randomness::{RandomnessSecretKey, RandomnessShare},
     time::timer::{sleep, timeout},
 };
 #[cfg(with_testing)]
@@ -31,18 +34,30 @@ use linera_chain::{
     },
     ChainError, ChainStateView,
 };
-use linera_execution::{ExecutionError, ExecutionStateView, Query, QueryOutcome};
+use linera_execution::{http_oracle, ExecutionError, ExecutionStateView, Query, QueryOutcome};
 use linera_storage::Storage;
-use linera_views::ViewError;
+use linera_views::{
+    batch::Batch,
+    context::Context,
+    store::{ReadableKeyValueStore, WritableKeyValueStore},
+    ViewError,
+};
 use lru::LruCache;
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
-use tokio::sync::{mpsc, oneshot, OwnedRwLockReadGuard};
+use tokio::sync::{mpsc, oneshot, OwnedRwLockReadGuard, Semaphore};
 use tracing::{error, instrument, trace, warn};
 
 use crate::{
-    chain_worker::{ChainWorkerActor, ChainWorkerConfig, ChainWorkerRequest, DeliveryNotifier},
-    data_types::{ChainInfoQuery, ChainInfoResponse, CrossChainRequest},
+    chain_worker::{
+        ChainWorkerActor, ChainWorkerConfig, ChainWorkerRequest, DeliveryNotifier, HistoryArchive,
+        RetentionPolicy,
+    },
+    data_types::{
+        ChainInfoQuery, ChainInfoResponse, ChainSnapshotChunk, ChainSnapshotEntries,
+        ChainSnapshotManifest, ChainSnapshotRequest, CrossChainRequest, HttpOracleRequest,
+        RandomnessRequest,
+    },
     join_set_ext::{JoinSet, JoinSetExt},
     notifier::Notifier,
     value_cache::ValueCache,
@@ -215,6 +230,32 @@ pub enum WorkerError {
     TooManyPublishedBlobs(u64),
     #[error(transparent)]
     Decompression(#[from] DecompressionError),
+    #[error("This worker does not sign HTTP oracle responses")]
+    HttpOracleUnavailable,
+    #[error("HTTP oracle request failed: {0}")]
+    HttpOracleRequestFailed(Box<ExecutionError>),
+    #[error("HTTP oracle requests must be signed by an owner of the chain")]
+    HttpOracleRequestUnauthorized,
+    #[error("At most {0} HTTP oracle requests are performed per block")]
+    TooManyHttpOracleRequests(u32),
+    #[error("This worker does not sign randomness shares")]
+    RandomnessBeaconUnavailable,
+    #[error("Randomness shares can only be requested for the current round {current_round}")]
+    InvalidRandomnessRound { current_round: Round },
+    #[error("The block provides randomness for round {provided}, but is proposed in {expected}")]
+    RandomnessRoundMismatch { expected: Round, provided: Round },
+    #[error("Failed to archive pruned blocks: {0}")]
+    HistoryArchive(#[source] std::io::Error),
+    #[error("Chain {0} has no blocks to take a snapshot after")]
+    ChainSnapshotUnavailable(ChainId),
+    #[error("The snapshot after block {0} is no longer available")]
+    ChainSnapshotExpired(CryptoHash),
+    #[error("The snapshot has no chunk {0}")]
+    InvalidChainSnapshotChunk(u32),
+    #[error("The snapshot does not match the state certified after block {0}")]
+    InvalidChainSnapshot(CryptoHash),
+    #[error("Too many chain snapshots are being taken; try again later")]
+    ChainSnapshotsBusy,
 }
 
 impl From<ChainError> for WorkerError {
@@ -280,6 +321,95 @@ where
     chain_worker_tasks: Arc<Mutex<JoinSet>>,
     /// The cache of running [`ChainWorkerActor`]s.
     chain_workers: Arc<Mutex<LruCache<ChainId, ChainActorEndpoint<StorageClient>>>>,
+    /// The snapshots of recently requested chains, and the chains whose snapshot is being
+    /// written.
+    chain_snapshots: Arc<Mutex<ChainSnapshots>>,
+    /// The permits to take a new chain snapshot.
+    chain_snapshot_permits: Arc<Semaphore>,
+    /// The number of HTTP oracle requests performed for recent blocks.
+    http_oracle_requests: Arc<Mutex<LruCache<(ChainId, BlockHeight), u32>>>,
+}
+
+/// The number of chain snapshots kept in storage while they are being downloaded.
+const CHAIN_SNAPSHOT_CACHE_SIZE: NonZeroUsize = NonZeroUsize::new(16).unwrap();
+
+/// The maximum number of chain snapshots that are taken at the same time.
+const MAX_CONCURRENT_CHAIN_SNAPSHOTS: usize = 2;
+
+/// The number of blocks for which the HTTP oracle requests are counted.
+const HTTP_ORACLE_REQUEST_CACHE_SIZE: NonZeroUsize = NonZeroUsize::new(1000).unwrap();
+
+/// The maximum number of HTTP requests a validator performs for a single block.
+pub const MAX_HTTP_ORACLE_REQUESTS_PER_BLOCK: u32 = 16;
+
+/// The approximate number of bytes of keys and values in a chunk of a chain snapshot.
+const CHAIN_SNAPSHOT_CHUNK_SIZE: usize = 1 << 20;
+
+/// The number of keys read at a time while a chain's state is copied to its snapshot.
+const CHAIN_SNAPSHOT_PAGE_SIZE: usize = 1000;
+
+/// A snapshot of a chain's state, written to [`Storage::chain_snapshot_store`] and split into
+/// chunks.
+struct ChainSnapshot {
+    manifest: ChainSnapshotManifest,
+    /// The first key of each chunk: a chunk holds the keys up to the start of the next one.
+    chunk_starts: Vec<Vec<u8>>,
+}
+
+/// The chain snapshots kept in storage.
+struct ChainSnapshots {
+    /// The most recent snapshot of each chain, for recently requested chains.
+    cache: LruCache<ChainId, Arc<ChainSnapshot>>,
+    /// The chains whose snapshot store is being written or cleared.
+    busy: HashSet<ChainId>,
+}
+
+impl ChainSnapshots {
+    fn new() -> Self {
+        ChainSnapshots {
+            cache: LruCache::new(CHAIN_SNAPSHOT_CACHE_SIZE),
+            busy: HashSet::new(),
+        }
+    }
+}
+
+/// Marks the snapshot store of a chain as busy until it is dropped.
+struct BusyChainSnapshot {
+    chain_snapshots: Arc<Mutex<ChainSnapshots>>,
+    chain_id: ChainId,
+}
+
+impl Drop for BusyChainSnapshot {
+    fn drop(&mut self) {
+        self.chain_snapshots
+            .lock()
+            .unwrap()
+            .busy
+            .remove(&self.chain_id);
+    }
+}
+
+/// Writes a chunk of a chain snapshot to the snapshot's store, after the rest of `batch`, and
+/// returns the chunk's first key and its hash.
+async fn write_chain_snapshot_chunk<S>(
+    store: &S,
+    mut batch: Batch,
+    entries: ChainSnapshotEntries,
+) -> Result<(Vec<u8>, CryptoHash), ViewError>
+where
+    S: WritableKeyValueStore,
+    ViewError: From<S::Error>,
+{
+    let hash = entries.hash();
+    let start = entries
+        .0
+        .first()
+        .map_or_else(Vec::new, |(key, _)| key.clone());
+    for (key, value) in entries.0 {
+        batch.put_key_value_bytes(key, value);
+    }
+    store.write_batch(batch).await?;
+    Ok((start, hash))
 }
 
 impl<StorageClient> Clone for WorkerState<StorageClient>
@@ -297,6 +427,9 @@ where
             delivery_notifiers: self.delivery_notifiers.clone(),
             chain_worker_tasks: self.chain_worker_tasks.clone(),
             chain_workers: self.chain_workers.clone(),
+            chain_snapshots: self.chain_snapshots.clone(),
+            chain_snapshot_permits: self.chain_snapshot_permits.clone(),
+            http_oracle_requests: self.http_oracle_requests.clone(),
         }
     }
 }
@@ -330,6 +463,11 @@ where
             delivery_notifiers: Arc::default(),
             chain_worker_tasks: Arc::default(),
             chain_workers: Arc::new(Mutex::new(LruCache::new(chain_worker_limit))),
+            chain_snapshots: Arc::new(Mutex::new(ChainSnapshots::new())),
+            chain_snapshot_permits: Arc::new(Semaphore::new(MAX_CONCURRENT_CHAIN_SNAPSHOTS)),
+            http_oracle_requests: Arc::new(Mutex::new(LruCache::new(
+                HTTP_ORACLE_REQUEST_CACHE_SIZE,
+            ))),
         }
     }
 
@@ -350,6 +488,11 @@ where
             delivery_notifiers: Arc::default(),
             chain_worker_tasks: Arc::default(),
             chain_workers: Arc::new(Mutex::new(LruCache::new(chain_worker_limit))),
+            chain_snapshots: Arc::new(Mutex::new(ChainSnapshots::new())),
+            chain_snapshot_permits: Arc::new(Semaphore::new(MAX_CONCURRENT_CHAIN_SNAPSHOTS)),
+            http_oracle_requests: Arc::new(Mutex::new(LruCache::new(
+                HTTP_ORACLE_REQUEST_CACHE_SIZE,
+            ))),
         }
     }
 
@@ -382,6 +525,27 @@ where
         self
     }
 
+    /// Returns an instance that prunes the blocks outside of the `retention_policy` from
+    /// storage, archiving them first into `history_archive` if provided.
+    #[instrument(level = "trace", skip(self, history_archive))]
+    pub fn with_retention_policy(
+        mut self,
+        retention_policy: RetentionPolicy,
+        history_archive: Option<Arc<dyn HistoryArchive>>,
+    ) -> Self {
+        self.chain_worker_config.retention_policy = retention_policy;
+        self.chain_worker_config.history_archive = history_archive;
+        self
+    }
+
+    /// Returns an instance that signs randomness shares with the given shares of the
+    /// committee's randomness key.
+    #[instrument(level = "trace", skip(self, randomness_key))]
+    pub fn with_randomness_key(mut self, randomness_key: Option<RandomnessSecretKey>) -> Self {
+        self.chain_worker_config.randomness_key = randomness_key.map(Arc::new);
+        self
+    }
+
     #[instrument(level = "trace", skip(self))]
     pub fn nickname(&self) -> &str {
         &self.nickname
@@ -994,6 +1158,326 @@ where
         result
     }
 
+    /// Performs an HTTP request on behalf of the next block of a chain, and signs the
+    /// response.
+    ///
+    /// The request must be signed by an owner of the chain, and at most
+    /// [`MAX_HTTP_ORACLE_REQUESTS_PER_BLOCK`] requests are performed per block.
+    #[instrument(skip_all, fields(
+        nick = self.nickname,
+        chain_id = format!("{:.8}", request.chain_id)
+    ))]
+    pub async fn handle_http_oracle_request(
+        &self,
+        request: HttpOracleRequest,
+    ) -> Result<SignedResponse, WorkerError> {
+        trace!("{} <-- {:?}", self.nickname, request);
+        let key_pair = self
+            .chain_worker_config
+            .key_pair()
+            .ok_or(WorkerError::HttpOracleUnavailable)?;
+        request.check_signature()?;
+        let owner = request.owner();
+        let HttpOracleRequest {
+            chain_id,
+            height,
+            request,
+            ..
+        } = request;
+        let query = ChainInfoQuery::new(chain_id).with_committees();
+        let (response, _actions) = self.handle_chain_info_query(query).await?;
+        let info = response.info;
+        ensure!(
+            info.next_block_height == height,
+            WorkerError::UnexpectedBlockHeight {
+                expected_block_height: info.next_block_height,
+                found_block_height: height,
+            }
+        );
+        let ownership = &info.manager.ownership;
+        ensure!(
+            ownership.verify_owner(&owner) || ownership.open_multi_leader_rounds,
+            WorkerError::HttpOracleRequestUnauthorized
+        );
+        let committee = info
+            .requested_committees
+            .and_then(|mut committees| committees.remove(&info.epoch))
+            .ok_or(WorkerError::HttpOracleUnavailable)?;
+        {
+            let mut requests = self.http_oracle_requests.lock().unwrap();
+            let count = requests.get(&(chain_id, height)).copied().unwrap_or(0);
+            ensure!(
+                count < MAX_HTTP_ORACLE_REQUESTS_PER_BLOCK,
+                WorkerError::TooManyHttpOracleRequests(MAX_HTTP_ORACLE_REQUESTS_PER_BLOCK)
+            );
+            requests.put((chain_id, height), count + 1);
+        }
+        let response = http_oracle::perform_http_request(request.clone(), &committee, None, true)
+            .await
+            .map_err(|error| WorkerError::HttpOracleRequestFailed(Box::new(error)))?;
+        let result = SignedResponse::new(chain_id, height, &request, response, key_pair);
+        trace!("{} --> {:?}", self.nickname, result);
+        Ok(result)
+    }
+
+    /// Returns this validator's share of the randomness for the next block of a chain, in
+    /// the chain's current round.
+    #[instrument(skip_all, fields(
+        nick = self.nickname,
+        chain_id = format!("{:.8}", request.chain_id)
+    ))]
+    pub async fn handle_randomness_request(
+        &self,
+        request: RandomnessRequest,
+    ) -> Result<RandomnessShare, WorkerError> {
+        trace!("{} <-- {:?}", self.nickname, request);
+        let RandomnessRequest {
+            chain_id,
+            height,
+            round,
+        } = request;
+        let (Some(key_pair), Some(randomness_key)) = (
+            self.chain_worker_config.key_pair(),
+            self.chain_worker_config.randomness_key(),
+        ) else {
+            return Err(WorkerError::RandomnessBeaconUnavailable);
+        };
+        let query = ChainInfoQuery::new(chain_id);
+        let (response, _actions) = self.handle_chain_info_query(query).await?;
+        let info = response.info;
+        ensure!(
+            info.next_block_height == height,
+            WorkerError::UnexpectedBlockHeight {
+                expected_block_height: info.next_block_height,
+                found_block_height: height,
+            }
+        );
+        // Only signing for the current round prevents the proposer from choosing among the
+        // seeds of several rounds.
+        let current_round = info.manager.current_round;
+        ensure!(
+            current_round == round,
+            WorkerError::InvalidRandomnessRound { current_round }
+        );
+        let result =
+            RandomnessShare::new(key_pair.public(), randomness_key, chain_id, height, round)
+                .map_err(|_| WorkerError::RandomnessBeaconUnavailable)?;
+        trace!("{} --> {:?}", self.nickname, result);
+        Ok(result)
+    }
+
+    /// Returns a chunk of a snapshot of a chain's state.
+    ///
+    /// Without a block hash, a new snapshot is taken after the chain's latest block, and the
+    /// chunk comes with the snapshot's manifest.
+    #[instrument(skip_all, fields(
+        nick = self.nickname,
+        chain_id = format!("{:.8}", request.chain_id)
+    ))]
+    pub async fn handle_chain_snapshot_request(
+        &self,
+        request: ChainSnapshotRequest,
+    ) -> Result<ChainSnapshotChunk, WorkerError> {
+        trace!("{} <-- {:?}", self.nickname, request);
+        let ChainSnapshotRequest {
+            chain_id,
+            block_hash,
+            index,
+        } = request;
+        let (snapshot, manifest) = match block_hash {
+            None => {
+                let snapshot = self.take_chain_snapshot(chain_id).await?;
+                let manifest = snapshot.manifest.clone();
+                (snapshot, Some(manifest))
+            }
+            Some(block_hash) => {
+                let snapshot = self
+                    .chain_snapshots
+                    .lock()
+                    .unwrap()
+                    .cache
+                    .get(&chain_id)
+                    .filter(|snapshot| snapshot.manifest.block_hash == block_hash)
+                    .cloned()
+                    .ok_or(WorkerError::ChainSnapshotExpired(block_hash))?;
+                (snapshot, None)
+            }
+        };
+        let index = index as usize;
+        let start = snapshot
+            .chunk_starts
+            .get(index)
+            .cloned()
+            .ok_or(WorkerError::InvalidChainSnapshotChunk(index as u32))?;
+        let end = match snapshot.chunk_starts.get(index + 1) {
+            Some(end) => Bound::Excluded(end.clone()),
+            None => Bound::Unbounded,
+        };
+        let entries = self
+            .storage
+            .chain_snapshot_store(chain_id)?
+            .find_key_values_by_prefix_in_range(&[], (Bound::Included(start), end), false, None)
+            .await
+            .map_err(ViewError::from)?;
+        let result = ChainSnapshotChunk {
+            manifest,
+            entries: ChainSnapshotEntries(entries),
+        };
+        trace!("{} --> {:?}", self.nickname, result);
+        Ok(result)
+    }
+
+    /// Returns a snapshot of a chain's state after its latest block, reusing a cached one if
+    /// there was no new block since.
+    ///
+    /// The state is copied page by page to the chain's snapshot store, so that chunks are
+    /// served from storage. Only a few snapshots are taken at the same time, and only the
+    /// latest one is kept for each of the recently requested chains.
+    async fn take_chain_snapshot(
+        &self,
+        chain_id: ChainId,
+    ) -> Result<Arc<ChainSnapshot>, WorkerError> {
+        // The read guard keeps the chain worker from changing the state while it is copied.
+        let chain = self.chain_state_view(chain_id).await?;
+        let block_hash = chain
+            .tip_state
+            .get()
+            .block_hash
+            .ok_or(WorkerError::ChainSnapshotUnavailable(chain_id))?;
+        let _busy = {
+            let mut chain_snapshots = self.chain_snapshots.lock().unwrap();
+            if let Some(snapshot) = chain_snapshots.cache.get(&chain_id) {
+                if snapshot.manifest.block_hash == block_hash {
+                    return Ok(snapshot.clone());
+                }
+            }
+            ensure!(
+                chain_snapshots.busy.insert(chain_id),
+                WorkerError::ChainSnapshotsBusy
+            );
+            BusyChainSnapshot {
+                chain_snapshots: self.chain_snapshots.clone(),
+                chain_id,
+            }
+        };
+        let _permit = self
+            .chain_snapshot_permits
+            .try_acquire()
+            .map_err(|_| WorkerError::ChainSnapshotsBusy)?;
+        // The previous snapshot of the chain is overwritten.
+        self.chain_snapshots.lock().unwrap().cache.pop(&chain_id);
+
+        let source = chain.context().store();
+        let target = self.storage.chain_snapshot_store(chain_id)?;
+        let mut chunk_starts = Vec::new();
+        let mut chunk_hashes = Vec::new();
+        let mut chunk = Vec::new();
+        let mut chunk_size = 0;
+        let mut batch = Batch::new();
+        batch.delete_key_prefix(Vec::new());
+        let mut lower_bound = Bound::Unbounded;
+        loop {
+            let key_values = source
+                .find_key_values_by_prefix_in_range(
+                    &[],
+                    (lower_bound, Bound::Unbounded),
+                    false,
+                    Some(CHAIN_SNAPSHOT_PAGE_SIZE),
+                )
+                .await
+                .map_err(ViewError::from)?;
+            let is_last = key_values.len() < CHAIN_SNAPSHOT_PAGE_SIZE;
+            lower_bound = match key_values.last() {
+                Some((key, _)) => Bound::Excluded(key.clone()),
+                None => Bound::Unbounded,
+            };
+            for (key, value) in key_values {
+                chunk_size += key.len() + value.len();
+                chunk.push((key, value));
+                if chunk_size >= CHAIN_SNAPSHOT_CHUNK_SIZE {
+                    let entries = ChainSnapshotEntries(std::mem::take(&mut chunk));
+                    chunk_size = 0;
+                    let (start, hash) =
+                        write_chain_snapshot_chunk(&target, std::mem::take(&mut batch), entries)
+                            .await?;
+                    chunk_starts.push(start);
+                    chunk_hashes.push(hash);
+                }
+            }
+            if is_last {
+                break;
+            }
+        }
+        if !chunk.is_empty() || chunk_hashes.is_empty() {
+            let entries = ChainSnapshotEntries(chunk);
+            let (start, hash) = write_chain_snapshot_chunk(&target, batch, entries).await?;
+            chunk_starts.push(start);
+            chunk_hashes.push(hash);
+        }
+        drop(chain);
+
+        let manifest = ChainSnapshotManifest {
+            chain_id,
+            block_hash,
+            chunk_hashes,
+        };
+        let snapshot = Arc::new(ChainSnapshot {
+            manifest,
+            chunk_starts,
+        });
+        let evicted = {
+            let mut chain_snapshots = self.chain_snapshots.lock().unwrap();
+            let evicted = chain_snapshots
+                .cache
+                .push(chain_id, snapshot.clone())
+                .map(|(evicted, _)| evicted)
+                .filter(|evicted| chain_snapshots.busy.insert(*evicted));
+            evicted.map(|evicted| BusyChainSnapshot {
+                chain_snapshots: self.chain_snapshots.clone(),
+                chain_id: evicted,
+            })
+        };
+        // The snapshot of the evicted chain is deleted, unless it is being overwritten.
+        if let Some(evicted) = evicted {
+            let mut batch = Batch::new();
+            batch.delete_key_prefix(Vec::new());
+            self.storage
+                .chain_snapshot_store(evicted.chain_id)?
+                .write_batch(batch)
+                .await
+                .map_err(ViewError::from)?;
+        }
+        Ok(snapshot)
+    }
+
+    /// Replaces the state of a chain with a snapshot taken after the block of `certificate`.
+    ///
+    /// The snapshot is loaded by the chain's worker, so that no other request reads or changes
+    /// the chain's state in the meantime. It is written under a separate root key, and only
+    /// replaces the chain's state once it is checked against the block: see
+    /// [`ChainStateView::adopt_snapshot`]. Nothing happens if the chain is already at or past
+    /// that block.
+    #[instrument(skip_all, fields(
+        nick = self.nickname,
+        chain_id = format!("{:.8}", certificate.block().header.chain_id),
+        height = %certificate.block().header.height,
+    ))]
+    pub async fn load_chain_snapshot(
+        &self,
+        certificate: ConfirmedBlockCertificate,
+        chunks: Vec<ChainSnapshotEntries>,
+    ) -> Result<(), WorkerError> {
+        let chain_id = certificate.block().header.chain_id;
+        self.query_chain_worker(chain_id, move |callback| {
+            ChainWorkerRequest::LoadChainSnapshot {
+                certificate,
+                chunks,
+                callback,
+            }
+        })
+        .await
+    }
+
     #[instrument(skip_all, fields(
         nick = self.nickname,
         chain_id = format!("{:.8}", request.target_chain_id())
@@ -121,6 +121,17 @@ library Linera {
         return output2;
     }
 
+    function random_bytes() internal returns (bytes32) {
+        address precompile = address(0x0b);
+        bytes memory input1 = new bytes(2);
+        input1[0] = bytes1(uint8(1));
+        input1[1] = bytes1(uint8(5));
+        (bool success, bytes memory output1) = precompile.call(input1);
+        require(success);
+        require(output1.length == 32);
+        return bytes32(output1);
+    }
+
     function try_query_application(bytes32 universal_address, bytes memory argument) internal returns (bytes memory) {
         address precompile = address(0x0b);
         bytes memory input2 = abi.encodePacked(uint8(2), uint8(0), universal_address, argument);
@@ -5,7 +5,10 @@
 use std::{borrow::Cow, collections::BTreeMap, str::FromStr};
 
 use async_graphql::InputObject;
-use linera_base::crypto::{AccountPublicKey, CryptoError, ValidatorPublicKey};
+use linera_base::{
+    crypto::{AccountPublicKey, CryptoError, ValidatorPublicKey},
+    randomness::RandomnessPublicKeys,
+};
 use serde::{Deserialize, Serialize};
 
 use crate::policy::ResourceControlPolicy;
@@ -71,6 +74,10 @@ pub struct Committee {
     validity_threshold: u64,
     /// The policy agreed on for this epoch.
     policy: ResourceControlPolicy,
+    /// The public keys of the randomness beacon, if the validators hold shares of a
+    /// randomness key.
+    #[graphql(skip)]
+    randomness_keys: Option<RandomnessPublicKeys>,
 }
 
 impl Serialize for Committee {
@@ -96,7 +103,7 @@ impl<'de> Deserialize<'de> for Committee {
             Committee::try_from(committee_full).map_err(serde::de::Error::custom)
         } else {
             let committee_minimal = CommitteeMinimal::deserialize(deserializer)?;
-            Ok(Committee::from(committee_minimal))
+            Committee::try_from(committee_minimal).map_err(serde::de::Error::custom)
         }
     }
 }
@@ -109,6 +116,8 @@ struct CommitteeFull<'a> {
     quorum_threshold: u64,
     validity_threshold: u64,
     policy: Cow<'a, ResourceControlPolicy>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    randomness_keys: Option<Cow<'a, RandomnessPublicKeys>>,
 }
 
 #[derive(Serialize, Deserialize)]
@@ -116,6 +125,7 @@ struct CommitteeFull<'a> {
 struct CommitteeMinimal<'a> {
     validators: Cow<'a, BTreeMap<ValidatorPublicKey, ValidatorState>>,
     policy: Cow<'a, ResourceControlPolicy>,
+    randomness_keys: Option<Cow<'a, RandomnessPublicKeys>>,
 }
 
 impl TryFrom<CommitteeFull<'static>> for Committee {
@@ -128,8 +138,12 @@ impl TryFrom<CommitteeFull<'static>> for Committee {
             quorum_threshold,
             validity_threshold,
             policy,
+            randomness_keys,
         } = committee_full;
-        let committee = Committee::new(validators.into_owned(), policy.into_owned());
+        let mut committee = Committee::new(validators.into_owned(), policy.into_owned());
+        if let Some(randomness_keys) = randomness_keys {
+            committee = committee.with_randomness_keys(randomness_keys.into_owned())?;
+        }
         if total_votes != committee.total_votes {
             Err(format!(
                 "invalid committee: total_votes is {}; should be {}",
@@ -159,6 +173,7 @@ impl<'a> From<&'a Committee> for CommitteeFull<'a> {
             quorum_threshold,
             validity_threshold,
             policy,
+            randomness_keys,
         } = committee;
         CommitteeFull {
             validators: Cow::Borrowed(validators),
@@ -166,14 +181,25 @@ impl<'a> From<&'a Committee> for CommitteeFull<'a> {
             quorum_threshold: *quorum_threshold,
             validity_threshold: *validity_threshold,
             policy: Cow::Borrowed(policy),
+            randomness_keys: randomness_keys.as_ref().map(Cow::Borrowed),
         }
     }
 }
 
-impl From<CommitteeMinimal<'static>> for Committee {
-    fn from(committee_min: CommitteeMinimal) -> Committee {
-        let CommitteeMinimal { validators, policy } = committee_min;
-        Committee::new(validators.into_owned(), policy.into_owned())
+impl TryFrom<CommitteeMinimal<'static>> for Committee {
+    type Error = String;
+
+    fn try_from(committee_min: CommitteeMinimal) -> Result<Committee, Self::Error> {
+        let CommitteeMinimal {
+            validators,
+            policy,
+            randomness_keys,
+        } = committee_min;
+        let committee = Committee::new(validators.into_owned(), policy.into_owned());
+        match randomness_keys {
+            Some(randomness_keys) => committee.with_randomness_keys(randomness_keys.into_owned()),
+            None => Ok(committee),
+        }
     }
 }
 
@@ -185,10 +211,12 @@ impl<'a> From<&'a Committee> for CommitteeMinimal<'a> {
             quorum_threshold: _,
             validity_threshold: _,
             policy,
+            randomness_keys,
         } = committee;
         CommitteeMinimal {
             validators: Cow::Borrowed(validators),
             policy: Cow::Borrowed(policy),
+            randomness_keys: randomness_keys.as_ref().map(Cow::Borrowed),
         }
     }
 }
@@ -232,9 +260,38 @@ impl Committee {
             quorum_threshold,
             validity_threshold,
             policy,
+            randomness_keys: None,
         }
     }
 
+    /// Sets the public keys of the randomness beacon, after checking that each validator
+    /// has one key share per vote and that a quorum of votes is needed to combine them.
+    pub fn with_randomness_keys(
+        mut self,
+        randomness_keys: RandomnessPublicKeys,
+    ) -> Result<Self, String> {
+        let shares_match_votes = randomness_keys.shares.len() == self.validators.len()
+            && self.validators.iter().all(|(validator, state)| {
+                randomness_keys
+                    .shares
+                    .get(validator)
+                    .is_some_and(|shares| shares.len() as u64 == state.votes)
+            });
+        if !shares_match_votes {
+            return Err(
+                "invalid committee: the randomness key shares do not match the votes".into(),
+            );
+        }
+        if randomness_keys.threshold != self.quorum_threshold {
+            return Err(format!(
+                "invalid committee: the randomness threshold is {}; should be {}",
+                randomness_keys.threshold, self.quorum_threshold,
+            ));
+        }
+        self.randomness_keys = Some(randomness_keys);
+        Ok(self)
+    }
+
     #[cfg(with_testing)]
     pub fn make_simple(keys: Vec<(ValidatorPublicKey, AccountPublicKey)>) -> Self {
         let map = keys
@@ -304,6 +361,11 @@ impl Committee {
         &self.policy
     }
 
+    /// Returns the public keys of the randomness beacon, if any.
+    pub fn randomness_keys(&self) -> Option<&RandomnessPublicKeys> {
+        self.randomness_keys.as_ref()
+    }
+
     /// Returns a mutable reference to this committee's [`ResourceControlPolicy`].
     pub fn policy_mut(&mut self) -> &mut ResourceControlPolicy {
         &mut self.policy
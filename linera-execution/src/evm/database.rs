@@ -398,6 +398,21 @@ impl<Runtime> DatabaseRuntime<Runtime>
 where
     Runtime: ContractRuntime,
 {
+    /// Replaces the deployed code of the contract, keeping its storage.
+    pub fn replace_contract_code(&mut self, code: Bytecode) -> Result<(), ExecutionError> {
+        let contract_address = Address::ZERO.create(0);
+        let mut account_info = self.basic_ref(contract_address)?.unwrap_or_default();
+        account_info.code_hash = code.hash_slow();
+        account_info.code = Some(code);
+        let val = KeyTag::ContractAddress as u8;
+        let key_info = vec![val, KeyCategory::AccountInfo as u8];
+        let mut batch = Batch::new();
+        batch.put_key_value(key_info, &account_info)?;
+        let mut runtime = self.runtime.lock().expect("The lock should be possible");
+        runtime.write_batch(batch)?;
+        Ok(())
+    }
+
     pub fn get_contract_block_env(&self) -> Result<BlockEnv, ExecutionError> {
         let mut block_env = self.get_block_env()?;
         let mut runtime = self.runtime.lock().expect("The lock should be possible");
@@ -12,7 +12,7 @@ use linera_base::{
     crypto::CryptoHash,
     data_types::{Bytecode, Resources, SendMessageRequest, StreamUpdate},
     ensure,
-    identifiers::{ApplicationId, ChainId, StreamName},
+    identifiers::{ApplicationId, ChainId, ModuleId, StreamName},
     vm::{EvmQuery, VmRuntime},
 };
 use revm::{primitives::Bytes, InspectCommitEvm, InspectEvm, Inspector};
@@ -46,6 +46,10 @@ const EXECUTE_MESSAGE_SELECTOR: &[u8] = &[173, 125, 234, 205];
 /// only when creating a new instance of a shared contract
 const INSTANTIATE_SELECTOR: &[u8] = &[156, 163, 60, 158];
 
+/// This is the selector of the `migrate` that should be called
+/// only when the application is upgraded to a new module
+const MIGRATE_SELECTOR: &[u8] = &[143, 211, 171, 128];
+
 fn forbid_execute_operation_origin(vec: &[u8]) -> Result<(), ExecutionError> {
     ensure!(
         vec != EXECUTE_MESSAGE_SELECTOR,
@@ -55,6 +59,10 @@ fn forbid_execute_operation_origin(vec: &[u8]) -> Result<(), ExecutionError> {
         vec != INSTANTIATE_SELECTOR,
         ExecutionError::EvmError(EvmExecutionError::OperationCallInstantiate)
     );
+    ensure!(
+        vec != MIGRATE_SELECTOR,
+        ExecutionError::EvmError(EvmExecutionError::OperationCallMigrate)
+    );
     Ok(())
 }
 
@@ -74,7 +82,7 @@ const INTERPRETER_RESULT_SELECTOR: &[u8] = &[1, 2, 3, 4];
 mod tests {
     use revm_primitives::keccak256;
 
-    use crate::evm::revm::{EXECUTE_MESSAGE_SELECTOR, INSTANTIATE_SELECTOR};
+    use crate::evm::revm::{EXECUTE_MESSAGE_SELECTOR, INSTANTIATE_SELECTOR, MIGRATE_SELECTOR};
 
     // The function keccak256 is not const so we cannot build the execute_message
     // selector directly.
@@ -89,6 +97,12 @@ mod tests {
         let selector = &keccak256("instantiate(bytes)".as_bytes())[..4];
         assert_eq!(selector, INSTANTIATE_SELECTOR);
     }
+
+    #[test]
+    fn check_migrate_selector() {
+        let selector = &keccak256("migrate()".as_bytes())[..4];
+        assert_eq!(selector, MIGRATE_SELECTOR);
+    }
 }
 
 #[cfg(with_metrics)]
@@ -307,6 +321,8 @@ enum ContractPrecompileTag {
     MessageId,
     /// Key prefix for `message_is_bouncing`
     MessageIsBouncing,
+    /// Key prefix for `random_bytes`
+    RandomBytes,
 }
 
 /// Some functionalities from the ServiceRuntime not in BaseRuntime
@@ -513,6 +529,13 @@ impl<'a> ContractPrecompile {
                 };
                 Ok(vec![value])
             }
+            ContractPrecompileTag::RandomBytes => {
+                ensure!(vec.is_empty(), format!("vec should be empty"));
+                let bytes = runtime
+                    .random_bytes()
+                    .map_err(|error| format!("RandomBytes error {error}"))?;
+                Ok(bytes.to_vec())
+            }
         }
     }
 
@@ -875,6 +898,16 @@ where
         todo!("Streams are not implemented for Ethereum smart contracts yet.")
     }
 
+    fn migrate(&mut self, _previous_module: ModuleId) -> Result<(), ExecutionError> {
+        self.replace_deployed_code()?;
+        let result = self.transact_commit(Choice::Call, MIGRATE_SELECTOR)?;
+        let (gas_final, output, logs) = result.output_and_logs();
+        self.consume_fuel(gas_final)?;
+        self.write_logs(logs, "migrate")?;
+        assert_eq!(output.len(), 0);
+        Ok(())
+    }
+
     fn finalize(&mut self) -> Result<(), ExecutionError> {
         Ok(())
     }
@@ -946,6 +979,68 @@ where
         self.write_logs(result.logs, "deploy")
     }
 
+    /// Replaces the contract's deployed code with the code created by the constructor of the
+    /// contract's module, keeping the contract's storage. The constructor is executed without
+    /// committing its changes.
+    fn replace_deployed_code(&mut self) -> Result<(), ExecutionError> {
+        let mut vec_init = self.module.clone();
+        let constructor_argument = self.db.constructor_argument()?;
+        vec_init.extend_from_slice(&constructor_argument);
+        let inspector = CallInterceptorContract {
+            db: self.db.clone(),
+        };
+        let block_env = self.db.get_contract_block_env()?;
+        let gas_limit = {
+            let mut runtime = self.db.runtime.lock().expect("The lock should be possible");
+            runtime.remaining_fuel(VmRuntime::Evm)?
+        };
+        let nonce = self.db.get_nonce(&ZERO_ADDRESS)?;
+        let result_state = {
+            let ctx: revm_context::Context<
+                BlockEnv,
+                _,
+                _,
+                _,
+                Journal<WrapDatabaseRef<&mut DatabaseRuntime<Runtime>>>,
+                (),
+            > = revm_context::Context::<BlockEnv, _, _, _, _, _>::new(
+                WrapDatabaseRef(&mut self.db),
+                SpecId::PRAGUE,
+            )
+            .with_block(block_env);
+            let instructions = EthInstructions::new_mainnet();
+            let mut evm = Evm::new_with_inspector(
+                ctx,
+                inspector.clone(),
+                instructions,
+                ContractPrecompile::default(),
+            );
+            evm.inspect(
+                TxEnv {
+                    kind: TxKind::Create,
+                    data: Bytes::copy_from_slice(&vec_init),
+                    nonce,
+                    gas_limit,
+                    ..TxEnv::default()
+                },
+                inspector,
+            )
+            .map_err(|error| {
+                let error = format!("{:?}", error);
+                let error = EvmExecutionError::TransactError(error);
+                ExecutionError::EvmError(error)
+            })
+        }?;
+        let storage_stats = self.db.take_storage_stats();
+        let result = process_execution_result(storage_stats, result_state.result)?;
+        self.consume_fuel(result.gas_final)?;
+        let Output::Create(code, _) = result.output else {
+            unreachable!("It is impossible for a Choice::Create to lead to an Output::Call");
+        };
+        self.db
+            .replace_contract_code(revm_state::Bytecode::new_raw(code))
+    }
+
     fn transact_commit(
         &mut self,
         ch: Choice,
@@ -9,11 +9,13 @@ use std::{
 use futures::{FutureExt, StreamExt};
 use linera_base::{
     data_types::{Amount, BlockHeight, StreamUpdate},
-    identifiers::{Account, AccountOwner, StreamId},
+    ensure,
+    identifiers::{Account, AccountOwner, ModuleId, StreamId},
+    time::Instant,
 };
 use linera_views::{
     context::Context,
-    key_value_store_view::KeyValueStoreView,
+    key_value_store_view::{HistoryWindow, KeyValueStoreView},
     map_view::MapView,
     reentrant_collection_view::HashedReentrantCollectionView,
     views::{ClonableView, View},
@@ -31,11 +33,12 @@ use {
 
 use super::{runtime::ServiceRuntimeRequest, ExecutionRequest};
 use crate::{
-    resources::ResourceController, system::SystemExecutionStateView, ApplicationDescription,
-    ApplicationId, ContractSyncRuntime, ExecutionError, ExecutionRuntimeConfig,
-    ExecutionRuntimeContext, Message, MessageContext, MessageKind, Operation, OperationContext,
-    OutgoingMessage, ProcessStreamsContext, Query, QueryContext, QueryOutcome, ServiceSyncRuntime,
-    SystemMessage, Timestamp, TransactionTracker,
+    resources::ResourceController, system::SystemExecutionStateView,
+    transaction_tracker::ApplicationUpgrade, ApplicationDescription, ApplicationId,
+    ContractSyncRuntime, ExecutionError, ExecutionRuntimeConfig, ExecutionRuntimeContext, Message,
+    MessageContext, MessageKind, Operation, OperationContext, OutgoingMessage,
+    ProcessStreamsContext, Query, QueryContext, QueryOutcome, ServiceSyncRuntime, SystemMessage,
+    Timestamp, TransactionTracker,
 };
 
 /// A view accessing the execution state of a chain.
@@ -98,7 +101,7 @@ where
         self.context()
             .extra()
             .user_contracts()
-            .insert(application_id, contract);
+            .insert(application_description.module_id, contract);
 
         self.context()
             .extra()
@@ -140,6 +143,7 @@ pub enum UserAction {
     Operation(OperationContext, Vec<u8>),
     Message(MessageContext, Vec<u8>),
     ProcessStreams(ProcessStreamsContext, Vec<StreamUpdate>),
+    Migrate(ProcessStreamsContext, ModuleId),
 }
 
 impl UserAction {
@@ -148,6 +152,7 @@ impl UserAction {
             UserAction::Instantiate(context, _) => context.authenticated_signer,
             UserAction::Operation(context, _) => context.authenticated_signer,
             UserAction::ProcessStreams(_, _) => None,
+            UserAction::Migrate(_, _) => None,
             UserAction::Message(context, _) => context.authenticated_signer,
         }
     }
@@ -157,6 +162,7 @@ impl UserAction {
             UserAction::Instantiate(context, _) => context.height,
             UserAction::Operation(context, _) => context.height,
             UserAction::ProcessStreams(context, _) => context.height,
+            UserAction::Migrate(context, _) => context.height,
             UserAction::Message(context, _) => context.height,
         }
     }
@@ -166,6 +172,7 @@ impl UserAction {
             UserAction::Instantiate(context, _) => context.round,
             UserAction::Operation(context, _) => context.round,
             UserAction::ProcessStreams(context, _) => context.round,
+            UserAction::Migrate(context, _) => context.round,
             UserAction::Message(context, _) => context.round,
         }
     }
@@ -175,6 +182,7 @@ impl UserAction {
             UserAction::Instantiate(context, _) => context.timestamp,
             UserAction::Operation(context, _) => context.timestamp,
             UserAction::ProcessStreams(context, _) => context.timestamp,
+            UserAction::Migrate(context, _) => context.timestamp,
             UserAction::Message(context, _) => context.timestamp,
         }
     }
@@ -194,7 +202,9 @@ where
         txn_tracker: &mut TransactionTracker,
         resource_controller: &mut ResourceController<Option<AccountOwner>>,
     ) -> Result<(), ExecutionError> {
-        let ExecutionRuntimeConfig {} = self.context().extra().execution_runtime_config();
+        let ExecutionRuntimeConfig {
+            state_history_retention: _,
+        } = self.context().extra().execution_runtime_config();
         self.run_user_action_with_runtime(
             application_id,
             action,
@@ -223,7 +233,7 @@ where
             .balance()?;
         let controller = ResourceController::new(
             resource_controller.policy().clone(),
-            resource_controller.tracker,
+            resource_controller.tracker.clone(),
             initial_balance,
         );
         let (execution_state_sender, mut execution_state_receiver) =
@@ -312,6 +322,8 @@ where
         }
         self.process_subscriptions(txn_tracker, resource_controller, context.into())
             .await?;
+        self.process_application_upgrades(txn_tracker, resource_controller, context.into())
+            .await?;
         Ok(())
     }
 
@@ -346,6 +358,8 @@ where
         }
         self.process_subscriptions(txn_tracker, resource_controller, context.into())
             .await?;
+        self.process_application_upgrades(txn_tracker, resource_controller, context.into())
+            .await?;
         Ok(())
     }
 
@@ -394,11 +408,14 @@ where
         Ok(())
     }
 
+    /// Queries an application. User queries at a past height read the application states
+    /// from the given state history window.
     pub async fn query_application(
         &mut self,
         context: QueryContext,
         query: Query,
         endpoint: Option<&mut ServiceRuntimeEndpoint>,
+        state_history: Option<HistoryWindow>,
     ) -> Result<QueryOutcome, ExecutionError> {
         assert_eq!(context.chain_id, self.context().extra().chain_id());
         match query {
@@ -409,22 +426,30 @@ where
             Query::User {
                 application_id,
                 bytes,
+                at_height,
             } => {
-                let ExecutionRuntimeConfig {} = self.context().extra().execution_runtime_config();
+                let at_height = Self::state_history_read(at_height, state_history)?;
                 let outcome = match endpoint {
                     Some(endpoint) => {
                         self.query_user_application_with_long_lived_service(
                             application_id,
                             context,
                             bytes,
+                            at_height,
                             &mut endpoint.incoming_execution_requests,
                             &mut endpoint.runtime_request_sender,
                         )
                         .await?
                     }
                     None => {
-                        self.query_user_application(application_id, context, bytes)
-                            .await?
+                        self.query_user_application_at(
+                            application_id,
+                            context,
+                            bytes,
+                            at_height,
+                            None,
+                        )
+                        .await?
                     }
                 };
                 Ok(outcome.into())
@@ -432,11 +457,52 @@ where
         }
     }
 
-    async fn query_user_application(
+    /// Queries a user application, without a long-lived service runtime. If `at_height` is
+    /// provided, the application state is read as it was after the block at that height,
+    /// which must be within the `state_history` window. The query fails once the `deadline`
+    /// is reached.
+    pub async fn query_user_application(
         &mut self,
         application_id: ApplicationId,
         context: QueryContext,
         query: Vec<u8>,
+        at_height: Option<BlockHeight>,
+        state_history: Option<HistoryWindow>,
+        deadline: Option<Instant>,
+    ) -> Result<QueryOutcome<Vec<u8>>, ExecutionError> {
+        let at_height = Self::state_history_read(at_height, state_history)?;
+        self.query_user_application_at(application_id, context, query, at_height, deadline)
+            .await
+    }
+
+    /// Returns the height and the state history window to read the application states at
+    /// for a query at `at_height`, or `None` if the query reads the current states.
+    fn state_history_read(
+        at_height: Option<BlockHeight>,
+        state_history: Option<HistoryWindow>,
+    ) -> Result<Option<(BlockHeight, HistoryWindow)>, ExecutionError> {
+        let Some(height) = at_height else {
+            return Ok(None);
+        };
+        let window = state_history.ok_or(ExecutionError::StateHistoryDisabled)?;
+        ensure!(
+            window.contains(height.0),
+            ExecutionError::StateHistoryUnavailable(height)
+        );
+        if height.0.saturating_add(1) == window.next_version {
+            // The current states are the ones after the last block.
+            return Ok(None);
+        }
+        Ok(Some((height, window)))
+    }
+
+    async fn query_user_application_at(
+        &mut self,
+        application_id: ApplicationId,
+        context: QueryContext,
+        query: Vec<u8>,
+        at_height: Option<(BlockHeight, HistoryWindow)>,
+        deadline: Option<Instant>,
     ) -> Result<QueryOutcome<Vec<u8>>, ExecutionError> {
         let (execution_state_sender, mut execution_state_receiver) =
             futures::channel::mpsc::unbounded();
@@ -445,7 +511,8 @@ where
             .await?;
 
         let service_runtime_task = linera_base::task::Blocking::spawn(move |mut codes| {
-            let mut runtime = ServiceSyncRuntime::new(execution_state_sender, context);
+            let mut runtime =
+                ServiceSyncRuntime::new_with_deadline(execution_state_sender, context, deadline);
 
             async move {
                 let code = codes.next().await.expect("we send this immediately below");
@@ -458,8 +525,7 @@ where
         service_runtime_task.send(code)?;
 
         while let Some(request) = execution_state_receiver.next().await {
-            self.handle_request(request, &mut ResourceController::default())
-                .await?;
+            self.handle_query_request(request, at_height).await?;
         }
 
         service_runtime_task.join().await
@@ -470,6 +536,7 @@ where
         application_id: ApplicationId,
         context: QueryContext,
         query: Vec<u8>,
+        at_height: Option<(BlockHeight, HistoryWindow)>,
         incoming_execution_requests: &mut futures::channel::mpsc::UnboundedReceiver<
             ExecutionRequest,
         >,
@@ -491,7 +558,7 @@ where
             futures::select! {
                 maybe_request = incoming_execution_requests.next() => {
                     if let Some(request) = maybe_request {
-                        self.handle_request(request, &mut ResourceController::default()).await?;
+                        self.handle_query_request(request, at_height).await?;
                     }
                 }
                 outcome = &mut outcome_receiver => {
@@ -501,6 +568,22 @@ where
         }
     }
 
+    /// Handles a request from a service runtime, reading the application states at the
+    /// given height if any.
+    async fn handle_query_request(
+        &mut self,
+        request: ExecutionRequest,
+        at_height: Option<(BlockHeight, HistoryWindow)>,
+    ) -> Result<(), ExecutionError> {
+        match at_height {
+            Some((height, window)) => self.handle_request_at_height(request, height, window).await,
+            None => {
+                self.handle_request(request, &mut ResourceController::default())
+                    .await
+            }
+        }
+    }
+
     pub async fn list_applications(
         &self,
     ) -> Result<Vec<(ApplicationId, ApplicationDescription)>, ExecutionError> {
@@ -565,4 +648,52 @@ where
             }
         }
     }
+
+    /// Applies the application upgrades requested during the transaction, and calls `migrate`
+    /// on the upgraded applications' new contracts.
+    async fn process_application_upgrades(
+        &mut self,
+        txn_tracker: &mut TransactionTracker,
+        resource_controller: &mut ResourceController<Option<AccountOwner>>,
+        context: ProcessStreamsContext,
+    ) -> Result<(), ExecutionError> {
+        loop {
+            for ApplicationUpgrade {
+                authority,
+                application_id,
+                module_id,
+            } in txn_tracker.take_application_upgrades()
+            {
+                let previous_module = self
+                    .system
+                    .upgrade_application(
+                        context.chain_id,
+                        authority,
+                        application_id,
+                        module_id,
+                        txn_tracker,
+                    )
+                    .await?;
+                txn_tracker.add_application_migration(application_id, previous_module);
+            }
+            let migrations = txn_tracker.take_application_migrations();
+            if migrations.is_empty() {
+                return Ok(());
+            }
+            for (application_id, previous_module) in migrations {
+                self.run_user_action(
+                    application_id,
+                    UserAction::Migrate(context, previous_module),
+                    None,
+                    None,
+                    txn_tracker,
+                    resource_controller,
+                )
+                .await?;
+            }
+            // Migrations can subscribe to streams or request further upgrades.
+            self.process_subscriptions(txn_tracker, resource_controller, context)
+                .await?;
+        }
+    }
 }
@@ -3,26 +3,32 @@
 
 //! Handle requests from the synchronous execution thread of user applications.
 
-#[cfg(not(web))]
-use std::time::Duration;
+use std::ops::Bound;
 
 use custom_debug_derive::Debug;
-use futures::{channel::mpsc, StreamExt as _};
+use futures::channel::mpsc;
 #[cfg(with_metrics)]
 use linera_base::prometheus_util::MeasureLatency as _;
 use linera_base::{
     data_types::{
         Amount, ApplicationPermissions, ArithmeticError, BlobContent, BlockHeight, Timestamp,
     },
-    ensure, hex_debug, hex_vec_debug, http,
+    hex_debug, hex_vec_debug, http,
     identifiers::{Account, AccountOwner, BlobId, BlobType, ChainId, EventId, StreamId},
     ownership::ChainOwnership,
+    time::Instant,
+};
+use linera_views::{
+    batch::Batch,
+    context::Context,
+    key_value_store_view::{HistoryWindow, VersioningConfig},
+    views::View,
 };
-use linera_views::{batch::Batch, context::Context, views::View};
 use oneshot::Sender;
-use reqwest::{header::HeaderMap, Client, Url};
+use reqwest::Url;
 
 use crate::{
+    http_oracle,
     system::{CreateApplicationResult, OpenChainConfig, Recipient},
     util::RespondExt,
     ApplicationDescription, ApplicationId, ExecutionError, ExecutionRuntimeContext,
@@ -80,10 +86,16 @@ where
             }
             None => self.system.describe_application(id, txn_tracker).await?,
         };
+        // The code is loaded from the module the application was last upgraded to, while the
+        // runtime keeps the description the application was created with.
+        let module_id = self.system.current_module_id(id, &description).await?;
         let code = self
             .context()
             .extra()
-            .get_user_contract(&description)
+            .get_user_contract(&ApplicationDescription {
+                module_id,
+                ..description.clone()
+            })
             .await?;
         Ok((code, description))
     }
@@ -103,10 +115,16 @@ where
             }
             None => self.system.describe_application(id, txn_tracker).await?,
         };
+        // The code is loaded from the module the application was last upgraded to, while the
+        // runtime keeps the description the application was created with.
+        let module_id = self.system.current_module_id(id, &description).await?;
         let code = self
             .context()
             .extra()
-            .get_user_service(&description)
+            .get_user_service(&ApplicationDescription {
+                module_id,
+                ..description.clone()
+            })
             .await?;
         Ok((code, description))
     }
@@ -269,14 +287,88 @@ where
                 callback.respond(result);
             }
 
+            FindKeysByPrefixInRange {
+                id,
+                key_prefix,
+                range,
+                reverse,
+                limit,
+                callback,
+            } => {
+                let view = self.users.try_load_entry(&id).await?;
+                let result = match view {
+                    Some(view) => {
+                        view.find_keys_by_prefix_in_range(&key_prefix, range, reverse, limit)
+                            .await?
+                    }
+                    None => Vec::new(),
+                };
+                callback.respond(result);
+            }
+
+            FindKeyValuesByPrefixInRange {
+                id,
+                key_prefix,
+                range,
+                reverse,
+                limit,
+                callback,
+            } => {
+                let view = self.users.try_load_entry(&id).await?;
+                let result = match view {
+                    Some(view) => {
+                        view.find_key_values_by_prefix_in_range(&key_prefix, range, reverse, limit)
+                            .await?
+                    }
+                    None => Vec::new(),
+                };
+                callback.respond(result);
+            }
+
             WriteBatch {
                 id,
                 batch,
+                height,
                 callback,
             } => {
+                let retention = self
+                    .context()
+                    .extra()
+                    .execution_runtime_config()
+                    .state_history_retention;
                 let mut view = self.users.try_load_entry_mut(&id).await?;
-                view.write_batch(batch).await?;
-                callback.respond(());
+                let old_size = view.total_size().sum();
+                match retention {
+                    Some(retention) => {
+                        let config = VersioningConfig {
+                            version: height.0,
+                            retention,
+                        };
+                        view.write_batch_versioned(batch, config).await?;
+                    }
+                    None => view.write_batch(batch).await?,
+                }
+                let new_size = view.total_size().sum();
+                // Respond with the number of bytes to charge storage rent for, or, if negative, to
+                // refund. Only the bytes the application paid rent for can be refunded.
+                let paid = self
+                    .system
+                    .storage_usage
+                    .get(&id)
+                    .await?
+                    .unwrap_or_default();
+                let delta = (i64::from(new_size) - i64::from(old_size))
+                    .max(-i64::try_from(paid).map_err(|_| ArithmeticError::Overflow)?);
+                let paid = paid
+                    .checked_add_signed(delta)
+                    .ok_or(ArithmeticError::Overflow)?;
+                if paid == 0 {
+                    self.system.storage_usage.remove(&id)?;
+                } else {
+                    self.system.storage_usage.insert(&id, paid)?;
+                }
+                let delta = i32::try_from(delta).map_err(|_| ArithmeticError::Overflow)?;
+                callback.respond(delta);
             }
 
             OpenChain {
@@ -347,6 +439,7 @@ where
                         module_id,
                         parameters,
                         required_application_ids,
+                        None,
                         txn_tracker,
                     )
                     .await?;
@@ -354,57 +447,58 @@ where
             }
 
             PerformHttpRequest {
+                application_id,
                 request,
                 http_responses_are_oracle_responses,
                 callback,
             } => {
-                let headers = request
-                    .headers
-                    .into_iter()
-                    .map(|http::Header { name, value }| Ok((name.parse()?, value.try_into()?)))
-                    .collect::<Result<HeaderMap, ExecutionError>>()?;
-
                 let url = Url::parse(&request.url)?;
-                let host = url
-                    .host_str()
-                    .ok_or_else(|| ExecutionError::UnauthorizedHttpRequest(url.clone()))?;
-
                 let (_epoch, committee) = self
                     .system
                     .current_committee()
-                    .ok_or_else(|| ExecutionError::UnauthorizedHttpRequest(url.clone()))?;
-                let allowed_hosts = &committee.policy().http_request_allow_list;
-
-                ensure!(
-                    allowed_hosts.contains(host),
-                    ExecutionError::UnauthorizedHttpRequest(url)
-                );
-
-                #[cfg_attr(web, allow(unused_mut))]
-                let mut request = Client::new()
-                    .request(request.method.into(), url)
-                    .body(request.body)
-                    .headers(headers);
-                #[cfg(not(web))]
-                {
-                    request = request.timeout(Duration::from_millis(
-                        committee.policy().http_request_timeout_ms,
-                    ));
-                }
-
-                let response = request.send().await?;
-
-                let mut response_size_limit = committee.policy().maximum_http_response_bytes;
-
-                if http_responses_are_oracle_responses {
-                    response_size_limit =
-                        response_size_limit.min(committee.policy().maximum_oracle_response_bytes);
-                }
+                    .ok_or(ExecutionError::UnauthorizedHttpRequest(url))?;
+                let application_policy = self
+                    .system
+                    .application_permissions
+                    .get()
+                    .http_policy(&application_id);
+                let response = http_oracle::perform_http_request(
+                    request,
+                    committee,
+                    application_policy,
+                    http_responses_are_oracle_responses,
+                )
+                .await?;
+                callback.respond(response);
+            }
 
-                callback.respond(
-                    self.receive_http_response(response, response_size_limit)
-                        .await?,
-                );
+            VerifyHttpResponses {
+                application_id,
+                chain_id,
+                height,
+                request,
+                reducer,
+                responses,
+                callback,
+            } => {
+                let (_epoch, committee) = self
+                    .system
+                    .current_committee()
+                    .ok_or(ExecutionError::InvalidCommittees)?;
+                let application_policy = self
+                    .system
+                    .application_permissions
+                    .get()
+                    .http_policy(&application_id);
+                callback.respond(http_oracle::verify_http_responses(
+                    committee,
+                    application_policy,
+                    chain_id,
+                    height,
+                    &request,
+                    &reducer,
+                    &responses,
+                ));
             }
 
             ReadBlobContent { blob_id, callback } => {
@@ -451,6 +545,30 @@ where
                 callback.respond(index)
             }
 
+            QueryRemoteApplication {
+                chain_id,
+                height,
+                application_id,
+                query,
+                deadline,
+                callback,
+            } => {
+                let local_time = *self.system.timestamp.get();
+                let response = self
+                    .context()
+                    .extra()
+                    .query_remote_application(
+                        chain_id,
+                        height,
+                        application_id,
+                        query,
+                        local_time,
+                        deadline,
+                    )
+                    .await;
+                callback.respond(response);
+            }
+
             ReadEvent { event_id, callback } => {
                 let event = self.context().extra().get_event(event_id.clone()).await?;
                 let event = event.ok_or(ExecutionError::EventsNotFound(vec![event_id]))?;
@@ -503,70 +621,156 @@ where
 
         Ok(())
     }
-}
 
-impl<C> ExecutionStateView<C>
-where
-    C: Context + Clone + Send + Sync + 'static,
-    C::Extra: ExecutionRuntimeContext,
-{
-    /// Receives an HTTP response, returning the prepared [`http::Response`] instance.
-    ///
-    /// Ensures that the response does not exceed the provided `size_limit`.
-    async fn receive_http_response(
+    /// Handles a request from a service runtime that reads the application state as it was
+    /// after the block at the given height, which must be within the state history
+    /// `window`. The system state is only available at the tip, so requests reading it are
+    /// rejected. Other requests are handled as usual.
+    pub(crate) async fn handle_request_at_height(
         &mut self,
-        response: reqwest::Response,
-        size_limit: u64,
-    ) -> Result<http::Response, ExecutionError> {
-        let status = response.status().as_u16();
-        let maybe_content_length = response.content_length();
-
-        let headers = response
-            .headers()
-            .iter()
-            .map(|(name, value)| http::Header::new(name.to_string(), value.as_bytes()))
-            .collect::<Vec<_>>();
-
-        let total_header_size = headers
-            .iter()
-            .map(|header| (header.name.len() + header.value.len()) as u64)
-            .sum();
-
-        let mut remaining_bytes = size_limit.checked_sub(total_header_size).ok_or(
-            ExecutionError::HttpResponseSizeLimitExceeded {
-                limit: size_limit,
-                size: total_header_size,
-            },
-        )?;
-
-        if let Some(content_length) = maybe_content_length {
-            if content_length > remaining_bytes {
-                return Err(ExecutionError::HttpResponseSizeLimitExceeded {
-                    limit: size_limit,
-                    size: content_length + total_header_size,
-                });
+        request: ExecutionRequest,
+        height: BlockHeight,
+        window: HistoryWindow,
+    ) -> Result<(), ExecutionError> {
+        use ExecutionRequest::*;
+        let version = height.0;
+        match request {
+            ChainBalance { .. }
+            | OwnerBalance { .. }
+            | OwnerBalances { .. }
+            | BalanceOwners { .. }
+            | SystemTimestamp { .. }
+            | ChainOwnership { .. }
+            | NextEventIndex { .. } => {
+                return Err(ExecutionError::SystemStateUnavailable(height));
             }
-        }
 
-        let mut body = Vec::with_capacity(maybe_content_length.unwrap_or(0) as usize);
-        let mut body_stream = response.bytes_stream();
+            ContainsKey { id, key, callback } => {
+                let view = self.users.try_load_entry(&id).await?;
+                let result = match view {
+                    Some(view) => view.contains_key_at_version(&key, version, window).await?,
+                    None => false,
+                };
+                callback.respond(result);
+            }
 
-        while let Some(bytes) = body_stream.next().await.transpose()? {
-            remaining_bytes = remaining_bytes.checked_sub(bytes.len() as u64).ok_or(
-                ExecutionError::HttpResponseSizeLimitExceeded {
-                    limit: size_limit,
-                    size: bytes.len() as u64 + (size_limit - remaining_bytes),
-                },
-            )?;
+            ContainsKeys { id, keys, callback } => {
+                let view = self.users.try_load_entry(&id).await?;
+                let result = match view {
+                    Some(view) => view.contains_keys_at_version(keys, version, window).await?,
+                    None => vec![false; keys.len()],
+                };
+                callback.respond(result);
+            }
 
-            body.extend(&bytes);
-        }
+            ReadMultiValuesBytes { id, keys, callback } => {
+                let view = self.users.try_load_entry(&id).await?;
+                let values = match view {
+                    Some(view) => view.multi_get_at_version(keys, version, window).await?,
+                    None => vec![None; keys.len()],
+                };
+                callback.respond(values);
+            }
+
+            ReadValueBytes { id, key, callback } => {
+                let view = self.users.try_load_entry(&id).await?;
+                let result = match view {
+                    Some(view) => view.get_at_version(&key, version, window).await?,
+                    None => None,
+                };
+                callback.respond(result);
+            }
+
+            FindKeysByPrefix {
+                id,
+                key_prefix,
+                callback,
+            } => {
+                let view = self.users.try_load_entry(&id).await?;
+                let result = match view {
+                    Some(view) => {
+                        view.find_keys_by_prefix_at_version(&key_prefix, version, window)
+                            .await?
+                    }
+                    None => Vec::new(),
+                };
+                callback.respond(result);
+            }
+
+            FindKeyValuesByPrefix {
+                id,
+                key_prefix,
+                callback,
+            } => {
+                let view = self.users.try_load_entry(&id).await?;
+                let result = match view {
+                    Some(view) => {
+                        view.find_key_values_by_prefix_at_version(&key_prefix, version, window)
+                            .await?
+                    }
+                    None => Vec::new(),
+                };
+                callback.respond(result);
+            }
+
+            FindKeysByPrefixInRange {
+                id,
+                key_prefix,
+                range,
+                reverse,
+                limit,
+                callback,
+            } => {
+                let view = self.users.try_load_entry(&id).await?;
+                let result = match view {
+                    Some(view) => {
+                        view.find_keys_by_prefix_in_range_at_version(
+                            &key_prefix,
+                            range,
+                            reverse,
+                            limit,
+                            version,
+                            window,
+                        )
+                        .await?
+                    }
+                    None => Vec::new(),
+                };
+                callback.respond(result);
+            }
 
-        Ok(http::Response {
-            status,
-            headers,
-            body,
-        })
+            FindKeyValuesByPrefixInRange {
+                id,
+                key_prefix,
+                range,
+                reverse,
+                limit,
+                callback,
+            } => {
+                let view = self.users.try_load_entry(&id).await?;
+                let result = match view {
+                    Some(view) => {
+                        view.find_key_values_by_prefix_in_range_at_version(
+                            &key_prefix,
+                            range,
+                            reverse,
+                            limit,
+                            version,
+                            window,
+                        )
+                        .await?
+                    }
+                    None => Vec::new(),
+                };
+                callback.respond(result);
+            }
+
+            request => {
+                self.handle_request(request, &mut ResourceController::default())
+                    .await?
+            }
+        }
+        Ok(())
     }
 }
 
@@ -690,11 +894,34 @@ pub enum ExecutionRequest {
         callback: Sender<Vec<(Vec<u8>, Vec<u8>)>>,
     },
 
+    FindKeysByPrefixInRange {
+        id: ApplicationId,
+        #[debug(with = hex_debug)]
+        key_prefix: Vec<u8>,
+        range: (Bound<Vec<u8>>, Bound<Vec<u8>>),
+        reverse: bool,
+        limit: Option<usize>,
+        #[debug(skip)]
+        callback: Sender<Vec<Vec<u8>>>,
+    },
+
+    FindKeyValuesByPrefixInRange {
+        id: ApplicationId,
+        #[debug(with = hex_debug)]
+        key_prefix: Vec<u8>,
+        range: (Bound<Vec<u8>>, Bound<Vec<u8>>),
+        reverse: bool,
+        limit: Option<usize>,
+        #[debug(skip)]
+        callback: Sender<Vec<(Vec<u8>, Vec<u8>)>>,
+    },
+
     WriteBatch {
         id: ApplicationId,
         batch: Batch,
+        height: BlockHeight,
         #[debug(skip)]
-        callback: Sender<()>,
+        callback: Sender<i32>,
     },
 
     OpenChain {
@@ -737,12 +964,24 @@ pub enum ExecutionRequest {
     },
 
     PerformHttpRequest {
+        application_id: ApplicationId,
         request: http::Request,
         http_responses_are_oracle_responses: bool,
         #[debug(skip)]
         callback: Sender<http::Response>,
     },
 
+    VerifyHttpResponses {
+        application_id: ApplicationId,
+        chain_id: ChainId,
+        height: BlockHeight,
+        request: http::Request,
+        reducer: http::Reducer,
+        responses: Vec<http::SignedResponse>,
+        #[debug(skip)]
+        callback: Sender<Result<http::Response, ExecutionError>>,
+    },
+
     ReadBlobContent {
         blob_id: BlobId,
         #[debug(skip)]
@@ -761,6 +1000,17 @@ pub enum ExecutionRequest {
         callback: Sender<u32>,
     },
 
+    QueryRemoteApplication {
+        chain_id: ChainId,
+        height: BlockHeight,
+        application_id: ApplicationId,
+        #[debug(with = hex_debug)]
+        query: Vec<u8>,
+        deadline: Option<Instant>,
+        #[debug(skip)]
+        callback: Sender<Result<Vec<u8>, ExecutionError>>,
+    },
+
     ReadEvent {
         event_id: EventId,
         callback: oneshot::Sender<Vec<u8>>,
@@ -0,0 +1,225 @@
+// Copyright (c) Zefchain Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Performing HTTP requests on behalf of applications, and verifying the responses signed
+//! by a quorum of validators.
+
+use std::collections::BTreeSet;
+#[cfg(not(web))]
+use std::time::Duration;
+
+use futures::StreamExt as _;
+use linera_base::{
+    data_types::{BlockHeight, HttpPolicy},
+    ensure,
+    http::{self, SignedResponse},
+    identifiers::ChainId,
+};
+use reqwest::{header::HeaderMap, Client, Url};
+
+use crate::{committee::Committee, ExecutionError, ResourceControlPolicy};
+
+/// Performs an HTTP request to one of the hosts allowed by the committee's policy, and by
+/// the HTTP policy of the requesting application, if any.
+pub async fn perform_http_request(
+    request: http::Request,
+    committee: &Committee,
+    application_policy: Option<&HttpPolicy>,
+    http_responses_are_oracle_responses: bool,
+) -> Result<http::Response, ExecutionError> {
+    if let Some(application_policy) = application_policy {
+        check_http_request(application_policy, &request)?;
+    }
+
+    let headers = request
+        .headers
+        .into_iter()
+        .map(|http::Header { name, value }| Ok((name.parse()?, value.try_into()?)))
+        .collect::<Result<HeaderMap, ExecutionError>>()?;
+
+    let url = Url::parse(&request.url)?;
+    let host = url
+        .host_str()
+        .ok_or_else(|| ExecutionError::UnauthorizedHttpRequest(url.clone()))?;
+
+    let policy = committee.policy();
+    ensure!(
+        policy.http_request_allow_list.contains(host),
+        ExecutionError::UnauthorizedHttpRequest(url)
+    );
+
+    #[cfg_attr(web, allow(unused_mut))]
+    let mut request = Client::new()
+        .request(request.method.into(), url)
+        .body(request.body)
+        .headers(headers);
+    #[cfg(not(web))]
+    {
+        request = request.timeout(Duration::from_millis(policy.http_request_timeout_ms));
+    }
+
+    let response = request.send().await?;
+
+    let response_size_limit = http_response_size_limit(
+        policy,
+        application_policy,
+        http_responses_are_oracle_responses,
+    );
+
+    receive_http_response(response, response_size_limit).await
+}
+
+/// Returns the maximum size of an HTTP response allowed by the committee's `policy` and by
+/// the HTTP policy of the requesting application, if any.
+pub fn http_response_size_limit(
+    policy: &ResourceControlPolicy,
+    application_policy: Option<&HttpPolicy>,
+    http_responses_are_oracle_responses: bool,
+) -> u64 {
+    let mut response_size_limit = policy.maximum_http_response_bytes;
+
+    if http_responses_are_oracle_responses {
+        response_size_limit = response_size_limit.min(policy.maximum_oracle_response_bytes);
+    }
+
+    if let Some(limit) = application_policy.and_then(|policy| policy.maximum_response_bytes) {
+        response_size_limit = response_size_limit.min(limit);
+    }
+
+    response_size_limit
+}
+
+/// Checks that the application's HTTP `policy` allows the `request`.
+pub fn check_http_request(
+    policy: &HttpPolicy,
+    request: &http::Request,
+) -> Result<(), ExecutionError> {
+    ensure!(
+        policy.allows(request),
+        ExecutionError::HttpRequestNotAllowedByPolicy {
+            application_id: policy.application_id,
+            method: request.method,
+            url: request.url.clone(),
+        }
+    );
+    if let Some(limit) = policy.maximum_request_bytes {
+        let size = request.size();
+        ensure!(
+            size <= limit,
+            ExecutionError::HttpRequestSizeLimitExceeded { limit, size }
+        );
+    }
+    Ok(())
+}
+
+/// Receives an HTTP response, returning the prepared [`http::Response`] instance.
+///
+/// Ensures that the response does not exceed the provided `size_limit`.
+async fn receive_http_response(
+    response: reqwest::Response,
+    size_limit: u64,
+) -> Result<http::Response, ExecutionError> {
+    let status = response.status().as_u16();
+    let maybe_content_length = response.content_length();
+
+    let headers = response
+        .headers()
+        .iter()
+        .map(|(name, value)| http::Header::new(name.to_string(), value.as_bytes()))
+        .collect::<Vec<_>>();
+
+    let total_header_size = headers
+        .iter()
+        .map(|header| (header.name.len() + header.value.len()) as u64)
+        .sum();
+
+    let mut remaining_bytes = size_limit.checked_sub(total_header_size).ok_or(
+        ExecutionError::HttpResponseSizeLimitExceeded {
+            limit: size_limit,
+            size: total_header_size,
+        },
+    )?;
+
+    if let Some(content_length) = maybe_content_length {
+        if content_length > remaining_bytes {
+            return Err(ExecutionError::HttpResponseSizeLimitExceeded {
+                limit: size_limit,
+                size: content_length + total_header_size,
+            });
+        }
+    }
+
+    let mut body = Vec::with_capacity(maybe_content_length.unwrap_or(0) as usize);
+    let mut body_stream = response.bytes_stream();
+
+    while let Some(bytes) = body_stream.next().await.transpose()? {
+        remaining_bytes = remaining_bytes.checked_sub(bytes.len() as u64).ok_or(
+            ExecutionError::HttpResponseSizeLimitExceeded {
+                limit: size_limit,
+                size: bytes.len() as u64 + (size_limit - remaining_bytes),
+            },
+        )?;
+
+        body.extend(&bytes);
+    }
+
+    Ok(http::Response {
+        status,
+        headers,
+        body,
+    })
+}
+
+/// Checks that the `responses` to the `request` were signed for the given block by
+/// validators of the committee with a quorum of votes, and reduces them to a single
+/// response. The request and the reduced response must satisfy the HTTP policy of the
+/// requesting application, if any.
+pub fn verify_http_responses(
+    committee: &Committee,
+    application_policy: Option<&HttpPolicy>,
+    chain_id: ChainId,
+    height: BlockHeight,
+    request: &http::Request,
+    reducer: &http::Reducer,
+    responses: &[SignedResponse],
+) -> Result<http::Response, ExecutionError> {
+    if let Some(application_policy) = application_policy {
+        check_http_request(application_policy, request)?;
+    }
+    let mut validators = BTreeSet::new();
+    let mut weighted_responses = Vec::with_capacity(responses.len());
+    let mut total_weight = 0u64;
+    for signed_response in responses {
+        let validator = signed_response.validator;
+        ensure!(
+            validators.insert(validator),
+            ExecutionError::DuplicateHttpResponse(validator)
+        );
+        let weight = committee.weight(&validator);
+        ensure!(weight > 0, ExecutionError::InvalidHttpResponse(validator));
+        signed_response
+            .check(chain_id, height, request)
+            .map_err(|_| ExecutionError::InvalidHttpResponse(validator))?;
+        total_weight = total_weight.saturating_add(weight);
+        weighted_responses.push((&signed_response.response, weight));
+    }
+    let threshold = committee.quorum_threshold();
+    ensure!(
+        total_weight >= threshold,
+        ExecutionError::HttpResponsesBelowQuorum {
+            weight: total_weight,
+            threshold,
+        }
+    );
+    let response = reducer
+        .reduce(&weighted_responses)
+        .ok_or(ExecutionError::IrreducibleHttpResponses)?;
+    if let Some(limit) = application_policy.and_then(|policy| policy.maximum_response_bytes) {
+        let size = response.size();
+        ensure!(
+            size <= limit,
+            ExecutionError::HttpResponseSizeLimitExceeded { limit, size }
+        );
+    }
+    Ok(response)
+}
@@ -12,7 +12,9 @@ mod execution;
 mod execution_state_actor;
 #[cfg(with_graphql)]
 mod graphql;
+pub mod http_oracle;
 mod policy;
+pub mod randomness;
 mod resources;
 mod runtime;
 pub mod system;
@@ -22,7 +24,7 @@ mod transaction_tracker;
 mod util;
 mod wasm;
 
-use std::{any::Any, fmt, str::FromStr, sync::Arc};
+use std::{any::Any, fmt, ops::Bound, str::FromStr, sync::Arc};
 
 use async_graphql::SimpleObject;
 use async_trait::async_trait;
@@ -33,10 +35,11 @@ use derive_more::Display;
 use js_sys::wasm_bindgen::JsValue;
 use linera_base::{
     abi::Abi,
-    crypto::{BcsHashable, CryptoHash},
+    crypto::{BcsHashable, CryptoHash, ValidatorPublicKey},
     data_types::{
         Amount, ApplicationDescription, ApplicationPermissions, ArithmeticError, Blob, BlockHeight,
-        DecompressionError, Epoch, NetworkDescription, SendMessageRequest, StreamUpdate, Timestamp,
+        DecompressionError, Epoch, NetworkDescription, Round, SendMessageRequest, StreamUpdate,
+        Timestamp,
     },
     doc_scalar, hex_debug, http,
     identifiers::{
@@ -44,7 +47,9 @@ use linera_base::{
         GenericApplicationId, MessageId, ModuleId, StreamName,
     },
     ownership::ChainOwnership,
+    randomness::RandomnessError,
     task,
+    time::Instant,
     vm::VmRuntime,
 };
 use linera_views::{batch::Batch, ViewError};
@@ -277,6 +282,59 @@ pub enum ExecutionError {
     UnauthorizedHttpRequest(reqwest::Url),
     #[error("Attempt to perform an HTTP request to an invalid URL")]
     InvalidUrlForHttpRequest(#[from] url::ParseError),
+    #[error("Several HTTP responses signed by validator {0:?}")]
+    DuplicateHttpResponse(ValidatorPublicKey),
+    #[error("Invalid HTTP response signature by {0:?}")]
+    InvalidHttpResponse(ValidatorPublicKey),
+    #[error(
+        "HTTP responses signed by validators with {weight} votes, below the quorum of {threshold}"
+    )]
+    HttpResponsesBelowQuorum { weight: u64, threshold: u64 },
+    #[error("HTTP responses of the validators cannot be reduced to a single response")]
+    IrreducibleHttpResponses,
+    #[error(
+        "The HTTP policy of application {application_id} does not allow {method:?} requests \
+        to {url}"
+    )]
+    HttpRequestNotAllowedByPolicy {
+        application_id: ApplicationId,
+        method: http::Method,
+        url: String,
+    },
+    #[error("HTTP request exceeds the size limit of {limit} bytes, having {size} bytes")]
+    HttpRequestSizeLimitExceeded { limit: u64, size: u64 },
+    #[error("Application {application_id} exceeded its quota of {limit} HTTP requests per block")]
+    HttpRequestQuotaExceeded {
+        application_id: ApplicationId,
+        limit: u32,
+    },
+    #[error(
+        "No randomness is available in this block; it must be provided by a \
+        `ProvideRandomness` system operation first"
+    )]
+    RandomnessUnavailable,
+    #[error("Randomness was already provided in this block")]
+    RandomnessAlreadyProvided,
+    #[error("Several randomness shares from validator {0:?}")]
+    DuplicateRandomnessShare(ValidatorPublicKey),
+    #[error("Invalid randomness share by {0:?}")]
+    InvalidRandomnessShare(ValidatorPublicKey),
+    #[error(
+        "Randomness shares produced by validators with {weight} votes, below the quorum of \
+        {threshold}"
+    )]
+    RandomnessSharesBelowQuorum { weight: u64, threshold: u64 },
+    #[error("The current committee has no randomness keys")]
+    RandomnessKeysMissing,
+    #[error("Randomness was provided for round {0}, not for the round of this block")]
+    InvalidRandomnessRound(Round),
+    #[error("Failed to combine the randomness shares: {0}")]
+    RandomnessCombination(#[from] RandomnessError),
+    #[error("The state of chain {chain_id} after block {height} is not available")]
+    RemoteChainStateUnavailable {
+        chain_id: ChainId,
+        height: BlockHeight,
+    },
     #[error("Failed to send contract code to worker thread: {0:?}")]
     ContractModuleSend(#[from] linera_base::task::SendError<UserContractCode>),
     #[error("Failed to send service code to worker thread: {0:?}")]
@@ -332,16 +390,32 @@ pub enum ExecutionError {
     TicksOutOfOrder,
     #[error("Application {0:?} is not registered by the chain")]
     UnknownApplicationId(Box<ApplicationId>),
+    #[error("Application {0:?} can only be upgraded by its upgrade authority")]
+    UnauthorizedApplicationUpgrade(Box<ApplicationId>),
+    #[error("Application {0:?} cannot be upgraded to a module for a different virtual machine")]
+    ApplicationUpgradeVmRuntimeMismatch(Box<ApplicationId>),
+    #[error("Application {0:?} can only be upgraded on its creator chain")]
+    ApplicationUpgradeOnNonCreatorChain(Box<ApplicationId>),
+    #[error("Upgrades of application {0:?} happen on this chain and cannot be processed")]
+    ApplicationUpgradeOnCreatorChain(Box<ApplicationId>),
     #[error("No recorded response for oracle query")]
     MissingOracleResponse,
     #[error("process_streams was not called for all stream updates")]
     UnprocessedStreams,
+    #[error("Some application upgrades were not applied")]
+    UnprocessedApplicationUpgrades,
     #[error("Internal error: {0}")]
     InternalError(&'static str),
     #[error("UpdateStreams contains an unknown event")]
     EventNotFound(EventId),
     #[error("UpdateStreams is outdated")]
     OutdatedUpdateStreams,
+    #[error("Application state history is not kept, so queries at past heights are unavailable")]
+    StateHistoryDisabled,
+    #[error("The application states at height {0} are not available on this node")]
+    StateHistoryUnavailable(BlockHeight),
+    #[error("The system state at height {0} is not available; only the current one is kept")]
+    SystemStateUnavailable(BlockHeight),
 }
 
 /// The public entry points provided by the contract part of an application.
@@ -358,6 +432,10 @@ pub trait UserContract {
     /// Reacts to new events on streams this application subscribes to.
     fn process_streams(&mut self, updates: Vec<StreamUpdate>) -> Result<(), ExecutionError>;
 
+    /// Migrates the application state after the application was upgraded from the
+    /// `previous_module` to this contract.
+    fn migrate(&mut self, previous_module: ModuleId) -> Result<(), ExecutionError>;
+
     /// Finishes execution of the current transaction.
     fn finalize(&mut self) -> Result<(), ExecutionError>;
 }
@@ -370,7 +448,11 @@ pub trait UserService {
 
 /// Configuration options for the execution runtime available to applications.
 #[derive(Clone, Copy, Default)]
-pub struct ExecutionRuntimeConfig {}
+pub struct ExecutionRuntimeConfig {
+    /// The number of block heights for which the prior values of the application states
+    /// are kept, so that services can be queried at past heights. `None` disables this.
+    pub state_history_retention: Option<u64>,
+}
 
 /// Requirements for the `extra` field in our state views (and notably the
 /// [`ExecutionStateView`]).
@@ -381,9 +463,9 @@ pub trait ExecutionRuntimeContext {
 
     fn execution_runtime_config(&self) -> ExecutionRuntimeConfig;
 
-    fn user_contracts(&self) -> &Arc<DashMap<ApplicationId, UserContractCode>>;
+    fn user_contracts(&self) -> &Arc<DashMap<ModuleId, UserContractCode>>;
 
-    fn user_services(&self) -> &Arc<DashMap<ApplicationId, UserServiceCode>>;
+    fn user_services(&self) -> &Arc<DashMap<ModuleId, UserServiceCode>>;
 
     async fn get_user_contract(
         &self,
@@ -405,6 +487,28 @@ pub trait ExecutionRuntimeContext {
 
     async fn contains_event(&self, event_id: EventId) -> Result<bool, ViewError>;
 
+    /// Queries an application on the chain `chain_id`, in the state after the block at
+    /// `height`. If the chain has advanced further in this node's storage, the state is read
+    /// from the state history; this fails if the history is disabled, or if the chain has
+    /// not reached `height` yet. The service fails once the `deadline` is reached.
+    ///
+    /// The state is the one last saved to this node's storage, read without going through
+    /// the chain's worker. Whether the query succeeds therefore depends on how far this node
+    /// has synchronized the chain and on its `state_history_retention`, which is node
+    /// configuration, not part of the protocol: validators with different retentions can
+    /// disagree on a block querying an old height. Such a block only becomes final if a
+    /// quorum of validators can answer the query, and it is then replayed from the recorded
+    /// oracle responses.
+    async fn query_remote_application(
+        &self,
+        chain_id: ChainId,
+        height: BlockHeight,
+        application_id: ApplicationId,
+        query: Vec<u8>,
+        local_time: Timestamp,
+        deadline: Option<Instant>,
+    ) -> Result<Vec<u8>, ExecutionError>;
+
     #[cfg(with_testing)]
     async fn add_blobs(
         &self,
@@ -644,6 +748,17 @@ pub trait BaseRuntime {
         promise: &Self::FindKeysByPrefix,
     ) -> Result<Vec<Vec<u8>>, ExecutionError>;
 
+    /// Creates the promise to access at most `limit` keys having a specific prefix and
+    /// whose remainder lies in `range`, in reverse order if `reverse` is set. The promise
+    /// is resolved with `find_keys_by_prefix_wait`.
+    fn find_keys_by_prefix_in_range_new(
+        &mut self,
+        key_prefix: Vec<u8>,
+        range: (Bound<Vec<u8>>, Bound<Vec<u8>>),
+        reverse: bool,
+        limit: Option<usize>,
+    ) -> Result<Self::FindKeysByPrefix, ExecutionError>;
+
     /// Reads the data from the key/values having a specific prefix.
     #[cfg(feature = "test")]
     #[expect(clippy::type_complexity)]
@@ -668,6 +783,17 @@ pub trait BaseRuntime {
         promise: &Self::FindKeyValuesByPrefix,
     ) -> Result<Vec<(Vec<u8>, Vec<u8>)>, ExecutionError>;
 
+    /// Creates the promise to access at most `limit` key/values having a specific prefix
+    /// and whose key remainder lies in `range`, in reverse order if `reverse` is set. The
+    /// promise is resolved with `find_key_values_by_prefix_wait`.
+    fn find_key_values_by_prefix_in_range_new(
+        &mut self,
+        key_prefix: Vec<u8>,
+        range: (Bound<Vec<u8>>, Bound<Vec<u8>>),
+        reverse: bool,
+        limit: Option<usize>,
+    ) -> Result<Self::FindKeyValuesByPrefix, ExecutionError>;
+
     /// Makes an HTTP request to the given URL and returns the answer, if any.
     fn perform_http_request(
         &mut self,
@@ -791,6 +917,15 @@ pub trait ContractRuntime: BaseRuntime {
         query: Vec<u8>,
     ) -> Result<Vec<u8>, ExecutionError>;
 
+    /// Queries a service on another chain, in the state after the block at `height`.
+    fn query_remote_application(
+        &mut self,
+        chain_id: ChainId,
+        height: BlockHeight,
+        application_id: ApplicationId,
+        query: Vec<u8>,
+    ) -> Result<Vec<u8>, ExecutionError>;
+
     /// Opens a new chain.
     fn open_chain(
         &mut self,
@@ -817,9 +952,34 @@ pub trait ContractRuntime: BaseRuntime {
         required_application_ids: Vec<ApplicationId>,
     ) -> Result<ApplicationId, ExecutionError>;
 
+    /// Requests an upgrade of the application `application_id` to the module `module_id`, with
+    /// the current application as the upgrade authority. The current chain must be the
+    /// application's creator chain.
+    ///
+    /// The upgrade is applied, and the application's `migrate` entry point called, after the
+    /// current transaction.
+    fn upgrade_application(
+        &mut self,
+        application_id: ApplicationId,
+        module_id: ModuleId,
+    ) -> Result<(), ExecutionError>;
+
     /// Returns the round in which this block was validated.
     fn validation_round(&mut self) -> Result<Option<u32>, ExecutionError>;
 
+    /// Checks that the `responses` to an HTTP `request` were signed for the current block
+    /// by a quorum of validators, and reduces them to a single response.
+    fn verify_http_responses(
+        &mut self,
+        request: http::Request,
+        reducer: http::Reducer,
+        responses: Vec<http::SignedResponse>,
+    ) -> Result<http::Response, ExecutionError>;
+
+    /// Returns 32 random bytes, derived from the randomness beacon of the current block.
+    /// Each call returns different bytes.
+    fn random_bytes(&mut self) -> Result<[u8; 32], ExecutionError>;
+
     /// Writes a batch of changes.
     fn write_batch(&mut self, batch: Batch) -> Result<(), ExecutionError>;
 }
@@ -865,6 +1025,12 @@ pub enum Query {
         #[serde(with = "serde_bytes")]
         #[debug(with = "hex_debug")]
         bytes: Vec<u8>,
+        /// The block height after which the application state should be queried, or
+        /// `None` for the current state. Only the application states are read at that
+        /// height: reading the system state, e.g. balances or ownership, fails unless it is
+        /// the latest block.
+        #[debug(skip_if = Option::is_none)]
+        at_height: Option<BlockHeight>,
     },
 }
 
@@ -995,8 +1161,8 @@ impl OperationContext {
 pub struct TestExecutionRuntimeContext {
     chain_id: ChainId,
     execution_runtime_config: ExecutionRuntimeConfig,
-    user_contracts: Arc<DashMap<ApplicationId, UserContractCode>>,
-    user_services: Arc<DashMap<ApplicationId, UserServiceCode>>,
+    user_contracts: Arc<DashMap<ModuleId, UserContractCode>>,
+    user_services: Arc<DashMap<ModuleId, UserServiceCode>>,
     blobs: Arc<DashMap<BlobId, Blob>>,
     events: Arc<DashMap<EventId, Vec<u8>>>,
 }
@@ -1027,11 +1193,11 @@ impl ExecutionRuntimeContext for TestExecutionRuntimeContext {
         self.execution_runtime_config
     }
 
-    fn user_contracts(&self) -> &Arc<DashMap<ApplicationId, UserContractCode>> {
+    fn user_contracts(&self) -> &Arc<DashMap<ModuleId, UserContractCode>> {
         &self.user_contracts
     }
 
-    fn user_services(&self) -> &Arc<DashMap<ApplicationId, UserServiceCode>> {
+    fn user_services(&self) -> &Arc<DashMap<ModuleId, UserServiceCode>> {
         &self.user_services
     }
 
@@ -1039,10 +1205,9 @@ impl ExecutionRuntimeContext for TestExecutionRuntimeContext {
         &self,
         description: &ApplicationDescription,
     ) -> Result<UserContractCode, ExecutionError> {
-        let application_id = description.into();
         Ok(self
             .user_contracts()
-            .get(&application_id)
+            .get(&description.module_id)
             .ok_or_else(|| {
                 ExecutionError::ApplicationBytecodeNotFound(Box::new(description.clone()))
             })?
@@ -1053,10 +1218,9 @@ impl ExecutionRuntimeContext for TestExecutionRuntimeContext {
         &self,
         description: &ApplicationDescription,
     ) -> Result<UserServiceCode, ExecutionError> {
-        let application_id = description.into();
         Ok(self
             .user_services()
-            .get(&application_id)
+            .get(&description.module_id)
             .ok_or_else(|| {
                 ExecutionError::ApplicationBytecodeNotFound(Box::new(description.clone()))
             })?
@@ -1094,6 +1258,18 @@ impl ExecutionRuntimeContext for TestExecutionRuntimeContext {
         Ok(self.events.contains_key(&event_id))
     }
 
+    async fn query_remote_application(
+        &self,
+        chain_id: ChainId,
+        height: BlockHeight,
+        _application_id: ApplicationId,
+        _query: Vec<u8>,
+        _local_time: Timestamp,
+        _deadline: Option<Instant>,
+    ) -> Result<Vec<u8>, ExecutionError> {
+        Err(ExecutionError::RemoteChainStateUnavailable { chain_id, height })
+    }
+
     #[cfg(with_testing)]
     async fn add_blobs(
         &self,
@@ -1192,8 +1368,17 @@ impl Operation {
             SystemOperation::ProcessNewEpoch(_)
                 | SystemOperation::ProcessRemovedEpoch(_)
                 | SystemOperation::UpdateStreams(_)
+                | SystemOperation::ProvideRandomness { .. }
         )
     }
+
+    /// Returns the round for which this operation provides randomness, if any.
+    pub fn randomness_round(&self) -> Option<Round> {
+        match self.as_system_operation() {
+            Some(SystemOperation::ProvideRandomness { round, .. }) => Some(*round),
+            _ => None,
+        }
+    }
 }
 
 impl From<SystemMessage> for Message {
@@ -1257,9 +1442,27 @@ impl Query {
         Ok(Query::User {
             application_id,
             bytes: serde_json::to_vec(&query)?,
+            at_height: None,
         })
     }
 
+    /// Makes this query target the application state after the block at the given height.
+    /// This has no effect on system queries, which always read the current state.
+    pub fn at_height(mut self, height: BlockHeight) -> Self {
+        if let Query::User { at_height, .. } = &mut self {
+            *at_height = Some(height);
+        }
+        self
+    }
+
+    /// Returns the block height targeted by this query, if it is not the current state.
+    pub fn height(&self) -> Option<BlockHeight> {
+        match self {
+            Query::System(_) => None,
+            Query::User { at_height, .. } => *at_height,
+        }
+    }
+
     pub fn application_id(&self) -> GenericApplicationId {
         match self {
             Self::System(_) => GenericApplicationId::System,
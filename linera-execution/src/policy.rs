@@ -45,9 +45,11 @@ pub struct ResourceControlPolicy {
     pub blob_byte_read: Amount,
     /// The price to publish a blob, per byte.
     pub blob_byte_published: Amount,
-    /// The price of increasing storage by a byte.
-    // TODO(#1536): This is not fully supported.
+    /// The price of increasing the storage used by applications by a byte.
     pub byte_stored: Amount,
+    /// The amount refunded for each byte of storage that applications free. Refunds never
+    /// exceed `byte_stored` per byte.
+    pub byte_stored_refund: Amount,
     /// The base price of adding an operation to a block.
     pub operation: Amount,
     /// The additional price for each byte in the argument of a user operation.
@@ -108,6 +110,7 @@ impl fmt::Display for ResourceControlPolicy {
             blob_byte_read,
             blob_byte_published,
             byte_stored,
+            byte_stored_refund,
             operation,
             operation_byte,
             message,
@@ -143,6 +146,7 @@ impl fmt::Display for ResourceControlPolicy {
             {blob_byte_read:.2} cost of reading blobs, per byte\n\
             {blob_byte_published:.2} cost of publishing blobs, per byte\n\
             {byte_stored:.2} cost per byte stored\n\
+            {byte_stored_refund:.2} refund per byte freed from storage\n\
             {operation:.2} per operation\n\
             {operation_byte:.2} per byte in the argument of an operation\n\
             {service_as_oracle_query:.2} per query to a service as an oracle\n\
@@ -192,6 +196,7 @@ impl ResourceControlPolicy {
             blob_byte_read: Amount::ZERO,
             blob_byte_published: Amount::ZERO,
             byte_stored: Amount::ZERO,
+            byte_stored_refund: Amount::ZERO,
             operation: Amount::ZERO,
             operation_byte: Amount::ZERO,
             message: Amount::ZERO,
@@ -270,6 +275,7 @@ impl ResourceControlPolicy {
             read_operation: Amount::from_micros(10),
             write_operation: Amount::from_micros(20),
             byte_stored: Amount::from_nanos(10),
+            byte_stored_refund: Amount::ZERO,
             message_byte: Amount::from_nanos(100),
             operation_byte: Amount::from_nanos(10),
             operation: Amount::from_micros(10),
@@ -360,12 +366,17 @@ impl ResourceControlPolicy {
             .try_add(self.blob_published)
     }
 
-    // TODO(#1536): This is not fully implemented.
-    #[allow(dead_code)]
     pub(crate) fn bytes_stored_price(&self, count: u64) -> Result<Amount, ArithmeticError> {
         self.byte_stored.try_mul(count as u128)
     }
 
+    /// Returns the amount refunded when applications free `count` bytes of storage.
+    pub(crate) fn bytes_freed_refund(&self, count: u64) -> Result<Amount, ArithmeticError> {
+        self.byte_stored_refund
+            .min(self.byte_stored)
+            .try_mul(count as u128)
+    }
+
     /// Returns how much it would cost to perform `count` queries to services running as oracles.
     pub(crate) fn service_as_oracle_queries_price(
         &self,
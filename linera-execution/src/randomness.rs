@@ -0,0 +1,61 @@
+// Copyright (c) Zefchain Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Verifying the randomness shares provided by a quorum of validators for a block.
+
+use std::collections::BTreeSet;
+
+use linera_base::{
+    crypto::CryptoHash,
+    data_types::{BlockHeight, Round},
+    ensure,
+    identifiers::ChainId,
+    randomness::RandomnessShare,
+};
+
+use crate::{committee::Committee, ExecutionError};
+
+/// Checks that the `shares` were produced for the given block and round by validators of
+/// the committee with a quorum of votes, and combines them into the block's randomness
+/// seed.
+///
+/// The seed is the committee's threshold signature, so it is the same for any quorum of
+/// shares.
+pub fn verify_randomness_shares(
+    committee: &Committee,
+    chain_id: ChainId,
+    height: BlockHeight,
+    round: Round,
+    shares: &[RandomnessShare],
+) -> Result<CryptoHash, ExecutionError> {
+    let randomness_keys = committee
+        .randomness_keys()
+        .ok_or(ExecutionError::RandomnessKeysMissing)?;
+    let mut validators = BTreeSet::new();
+    let mut total_weight = 0u64;
+    for share in shares {
+        let validator = share.validator;
+        ensure!(
+            validators.insert(validator),
+            ExecutionError::DuplicateRandomnessShare(validator)
+        );
+        let weight = committee.weight(&validator);
+        ensure!(
+            weight > 0,
+            ExecutionError::InvalidRandomnessShare(validator)
+        );
+        randomness_keys
+            .check_share(share, chain_id, height, round)
+            .map_err(|_| ExecutionError::InvalidRandomnessShare(validator))?;
+        total_weight = total_weight.saturating_add(weight);
+    }
+    let threshold = committee.quorum_threshold();
+    ensure!(
+        total_weight >= threshold,
+        ExecutionError::RandomnessSharesBelowQuorum {
+            weight: total_weight,
+            threshold,
+        }
+    );
+    Ok(randomness_keys.combine_shares(shares, chain_id, height, round)?)
+}
@@ -3,13 +3,13 @@
 
 //! This module tracks the resources used during the execution of a transaction.
 
-use std::{sync::Arc, time::Duration};
+use std::{collections::BTreeMap, sync::Arc, time::Duration};
 
 use custom_debug_derive::Debug;
 use linera_base::{
     data_types::{Amount, ArithmeticError, Blob},
     ensure,
-    identifiers::AccountOwner,
+    identifiers::{AccountOwner, ApplicationId},
     vm::VmRuntime,
 };
 use linera_views::{context::Context, ViewError};
@@ -53,7 +53,7 @@ impl<Account, Tracker> ResourceController<Account, Tracker> {
 /// a specific execution flow. This could be the execution of a block,
 /// the processing of a single message, or a specific phase within these
 /// broader operations.
-#[derive(Copy, Debug, Clone, Default)]
+#[derive(Debug, Clone, Default)]
 pub struct ResourceTracker {
     /// The total size of the block so far.
     pub block_size: u64,
@@ -89,6 +89,8 @@ pub struct ResourceTracker {
     pub message_bytes: u64,
     /// The number of HTTP requests performed.
     pub http_requests: u32,
+    /// The number of HTTP requests performed by each application.
+    pub http_requests_per_application: BTreeMap<ApplicationId, u32>,
     /// The number of calls to services as oracles.
     pub service_oracle_queries: u32,
     /// The time spent executing services as oracles.
@@ -233,6 +235,32 @@ where
         self.update_balance(self.policy.http_request)
     }
 
+    /// Tracks an HTTP request performed by the given application, ensuring that it stays
+    /// within the application's quota, if any.
+    pub(crate) fn track_application_http_request(
+        &mut self,
+        application_id: ApplicationId,
+        maximum_requests_per_block: Option<u32>,
+    ) -> Result<(), ExecutionError> {
+        let requests = self
+            .tracker
+            .as_mut()
+            .http_requests_per_application
+            .entry(application_id)
+            .or_default();
+        *requests = requests.checked_add(1).ok_or(ArithmeticError::Overflow)?;
+        if let Some(limit) = maximum_requests_per_block {
+            ensure!(
+                *requests <= limit,
+                ExecutionError::HttpRequestQuotaExceeded {
+                    application_id,
+                    limit,
+                }
+            );
+        }
+        Ok(())
+    }
+
     /// Tracks a number of fuel units used.
     pub(crate) fn track_fuel(
         &mut self,
@@ -359,9 +387,10 @@ where
         Ok(())
     }
 
-    /// Tracks a change in the number of bytes stored.
-    // TODO(#1536): This is not fully implemented.
-    #[allow(dead_code)]
+    /// Tracks a change in the number of bytes stored by an application.
+    ///
+    /// Storage growth is charged, and freed storage is refunded according to the policy. The
+    /// negative `delta`s must not exceed the storage the application paid for.
     pub(crate) fn track_stored_bytes(&mut self, delta: i32) -> Result<(), ExecutionError> {
         self.tracker.as_mut().bytes_stored = self
             .tracker
@@ -369,6 +398,13 @@ where
             .bytes_stored
             .checked_add(delta)
             .ok_or(ArithmeticError::Overflow)?;
+        let count = u64::from(delta.unsigned_abs());
+        if delta > 0 {
+            self.update_balance(self.policy.bytes_stored_price(count)?)?;
+        } else if delta < 0 {
+            self.account
+                .try_add_assign(self.policy.bytes_freed_refund(count)?)?;
+        }
         Ok(())
     }
 
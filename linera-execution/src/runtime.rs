@@ -4,7 +4,7 @@
 use std::{
     collections::{hash_map, BTreeMap, HashMap, HashSet},
     mem,
-    ops::{Deref, DerefMut},
+    ops::{Bound, Deref, DerefMut},
     sync::{Arc, Mutex},
     time::Instant,
 };
@@ -22,6 +22,7 @@ use linera_base::{
         StreamId, StreamName,
     },
     ownership::ChainOwnership,
+    randomness,
     vm::VmRuntime,
 };
 use linera_views::batch::Batch;
@@ -30,6 +31,7 @@ use oneshot::Receiver;
 use crate::{
     execution::UserAction,
     execution_state_actor::{ExecutionRequest, ExecutionStateSender},
+    http_oracle,
     resources::ResourceController,
     system::CreateApplicationResult,
     util::{ReceiverExt, UnboundedSenderExt},
@@ -828,6 +830,30 @@ where
         Ok(keys)
     }
 
+    fn find_keys_by_prefix_in_range_new(
+        &mut self,
+        key_prefix: Vec<u8>,
+        range: (Bound<Vec<u8>>, Bound<Vec<u8>>),
+        reverse: bool,
+        limit: Option<usize>,
+    ) -> Result<Self::FindKeysByPrefix, ExecutionError> {
+        let mut this = self.inner();
+        let id = this.current_application().id;
+        this.resource_controller.track_read_operation()?;
+        let receiver = this.execution_state_sender.send_request(move |callback| {
+            ExecutionRequest::FindKeysByPrefixInRange {
+                id,
+                key_prefix,
+                range,
+                reverse,
+                limit,
+                callback,
+            }
+        })?;
+        let state = this.view_user_states.entry(id).or_default();
+        state.find_keys_queries.register(receiver)
+    }
+
     fn find_key_values_by_prefix_new(
         &mut self,
         key_prefix: Vec<u8>,
@@ -846,6 +872,30 @@ where
         state.find_key_values_queries.register(receiver)
     }
 
+    fn find_key_values_by_prefix_in_range_new(
+        &mut self,
+        key_prefix: Vec<u8>,
+        range: (Bound<Vec<u8>>, Bound<Vec<u8>>),
+        reverse: bool,
+        limit: Option<usize>,
+    ) -> Result<Self::FindKeyValuesByPrefix, ExecutionError> {
+        let mut this = self.inner();
+        let id = this.current_application().id;
+        this.resource_controller.track_read_operation()?;
+        let receiver = this.execution_state_sender.send_request(move |callback| {
+            ExecutionRequest::FindKeyValuesByPrefixInRange {
+                id,
+                key_prefix,
+                range,
+                reverse,
+                limit,
+                callback,
+            }
+        })?;
+        let state = this.view_user_states.entry(id).or_default();
+        state.find_key_values_queries.register(receiver)
+    }
+
     fn find_key_values_by_prefix_wait(
         &mut self,
         promise: &Self::FindKeyValuesByPrefix,
@@ -879,17 +929,39 @@ where
             ExecutionError::UnauthorizedApplication(app_id)
         );
 
+        // The application's policy is checked before replaying, so that a recorded
+        // response cannot smuggle in a request that the policy forbids.
+        let application_policy = app_permissions.http_policy(&app_id);
+        if let Some(application_policy) = application_policy {
+            http_oracle::check_http_request(application_policy, &request)?;
+        }
+
         this.resource_controller.track_http_request()?;
+        this.resource_controller.track_application_http_request(
+            app_id,
+            application_policy.and_then(|policy| policy.maximum_requests_per_block),
+        )?;
 
         let response =
             if let Some(response) = this.transaction_tracker.next_replayed_oracle_response()? {
-                match response {
-                    OracleResponse::Http(response) => response,
-                    _ => return Err(ExecutionError::OracleResponseMismatch),
-                }
+                let OracleResponse::Http(response) = response else {
+                    return Err(ExecutionError::OracleResponseMismatch);
+                };
+                let limit = http_oracle::http_response_size_limit(
+                    this.resource_controller.policy(),
+                    application_policy,
+                    Self::LIMIT_HTTP_RESPONSE_SIZE_TO_ORACLE_RESPONSE_SIZE,
+                );
+                let size = response.size();
+                ensure!(
+                    size <= limit,
+                    ExecutionError::HttpResponseSizeLimitExceeded { limit, size }
+                );
+                response
             } else {
                 this.execution_state_sender
                     .send_request(|callback| ExecutionRequest::PerformHttpRequest {
+                        application_id: app_id,
                         request,
                         http_responses_are_oracle_responses:
                             Self::LIMIT_HTTP_RESPONSE_SIZE_TO_ORACLE_RESPONSE_SIZE,
@@ -1083,6 +1155,9 @@ impl ContractSyncRuntimeHandle {
             UserAction::ProcessStreams(_context, updates) => {
                 code.process_streams(updates).map(|()| None)
             }
+            UserAction::Migrate(_context, previous_module) => {
+                code.migrate(previous_module).map(|()| None)
+            }
         };
 
         let result = self.execute(application_id, signer, closure)?;
@@ -1472,6 +1547,73 @@ impl ContractRuntime for ContractSyncRuntimeHandle {
         Ok(response)
     }
 
+    fn query_remote_application(
+        &mut self,
+        chain_id: ChainId,
+        height: BlockHeight,
+        application_id: ApplicationId,
+        query: Vec<u8>,
+    ) -> Result<Vec<u8>, ExecutionError> {
+        let mut this = self.inner();
+
+        let app_permissions = this
+            .execution_state_sender
+            .send_request(|callback| ExecutionRequest::GetApplicationPermissions { callback })?
+            .recv_response()?;
+
+        let app_id = this.current_application().id;
+        ensure!(
+            app_permissions.can_call_services(&app_id),
+            ExecutionError::UnauthorizedApplication(app_id)
+        );
+
+        this.resource_controller.track_service_oracle_call()?;
+        let response =
+            if let Some(response) = this.transaction_tracker.next_replayed_oracle_response()? {
+                match response {
+                    OracleResponse::RemoteQuery(recorded_chain_id, recorded_height, bytes)
+                        if recorded_chain_id == chain_id && recorded_height == height =>
+                    {
+                        bytes
+                    }
+                    _ => return Err(ExecutionError::OracleResponseMismatch),
+                }
+            } else {
+                let timeout = this
+                    .resource_controller
+                    .remaining_service_oracle_execution_time()?;
+                let execution_start = Instant::now();
+                let deadline = Some(execution_start + timeout);
+                let response = this
+                    .execution_state_sender
+                    .send_request(|callback| ExecutionRequest::QueryRemoteApplication {
+                        chain_id,
+                        height,
+                        application_id,
+                        query,
+                        deadline,
+                        callback,
+                    })?
+                    .recv_response()?;
+                // Always track the execution time, irrespective to whether the service ran
+                // successfully or timed out
+                this.resource_controller
+                    .track_service_oracle_execution(execution_start.elapsed())?;
+                response?
+            };
+
+        this.resource_controller
+            .track_service_oracle_response(response.len())?;
+        this.transaction_tracker
+            .add_oracle_response(OracleResponse::RemoteQuery(
+                chain_id,
+                height,
+                response.clone(),
+            ));
+
+        Ok(response)
+    }
+
     fn open_chain(
         &mut self,
         ownership: ChainOwnership,
@@ -1573,6 +1715,21 @@ impl ContractRuntime for ContractSyncRuntimeHandle {
         Ok(app_id)
     }
 
+    fn upgrade_application(
+        &mut self,
+        application_id: ApplicationId,
+        module_id: ModuleId,
+    ) -> Result<(), ExecutionError> {
+        let mut this = self.inner();
+        let authority = AccountOwner::from(this.current_application().id);
+        this.transaction_tracker.add_application_upgrade(
+            Some(authority),
+            application_id,
+            module_id,
+        );
+        Ok(())
+    }
+
     fn validation_round(&mut self) -> Result<Option<u32>, ExecutionError> {
         let mut this = self.inner();
         let round =
@@ -1589,6 +1746,62 @@ impl ContractRuntime for ContractSyncRuntimeHandle {
         Ok(round)
     }
 
+    fn verify_http_responses(
+        &mut self,
+        request: http::Request,
+        reducer: http::Reducer,
+        responses: Vec<http::SignedResponse>,
+    ) -> Result<http::Response, ExecutionError> {
+        let mut this = self.inner();
+        let app_permissions = this
+            .execution_state_sender
+            .send_request(|callback| ExecutionRequest::GetApplicationPermissions { callback })?
+            .recv_response()?;
+
+        let app_id = this.current_application().id;
+        ensure!(
+            app_permissions.can_make_http_requests(&app_id),
+            ExecutionError::UnauthorizedApplication(app_id)
+        );
+
+        this.resource_controller.track_http_request()?;
+        this.resource_controller.track_application_http_request(
+            app_id,
+            app_permissions
+                .http_policy(&app_id)
+                .and_then(|policy| policy.maximum_requests_per_block),
+        )?;
+
+        let chain_id = this.chain_id;
+        let height = this.height;
+        this.execution_state_sender
+            .send_request(|callback| ExecutionRequest::VerifyHttpResponses {
+                application_id: app_id,
+                chain_id,
+                height,
+                request,
+                reducer,
+                responses,
+                callback,
+            })?
+            .recv_response()?
+    }
+
+    fn random_bytes(&mut self) -> Result<[u8; 32], ExecutionError> {
+        let mut this = self.inner();
+        let seed = this
+            .transaction_tracker
+            .randomness_seed()
+            .ok_or(ExecutionError::RandomnessUnavailable)?;
+        let application_id = this.current_application().id;
+        let transaction_index = this.transaction_tracker.transaction_index();
+        let index = this.transaction_tracker.next_random_index()?;
+        let bytes = randomness::derive_random_bytes(seed, application_id, transaction_index, index);
+        this.transaction_tracker
+            .replay_oracle_response(OracleResponse::Random(bytes))?;
+        Ok(bytes)
+    }
+
     fn write_batch(&mut self, batch: Batch) -> Result<(), ExecutionError> {
         let mut this = self.inner();
         let id = this.current_application().id;
@@ -1602,13 +1815,18 @@ impl ContractRuntime for ContractSyncRuntimeHandle {
         )?;
         this.resource_controller
             .track_bytes_written(batch.size() as u64)?;
-        this.execution_state_sender
+        let height = this.height;
+        let stored_bytes_delta = this
+            .execution_state_sender
             .send_request(|callback| ExecutionRequest::WriteBatch {
                 id,
                 batch,
+                height,
                 callback,
             })?
             .recv_response()?;
+        this.resource_controller
+            .track_stored_bytes(stored_bytes_delta)?;
         Ok(())
     }
 }
@@ -1616,9 +1834,19 @@ impl ContractRuntime for ContractSyncRuntimeHandle {
 impl ServiceSyncRuntime {
     /// Creates a new [`ServiceSyncRuntime`] ready to execute using a provided [`QueryContext`].
     pub fn new(execution_state_sender: ExecutionStateSender, context: QueryContext) -> Self {
+        Self::new_with_deadline(execution_state_sender, context, None)
+    }
+
+    /// Creates a new [`ServiceSyncRuntime`] ready to execute using a provided [`QueryContext`],
+    /// whose queries fail once the `deadline` is reached.
+    pub fn new_with_deadline(
+        execution_state_sender: ExecutionStateSender,
+        context: QueryContext,
+        deadline: Option<Instant>,
+    ) -> Self {
         let mut txn_tracker = TransactionTracker::default();
         txn_tracker.set_local_time(context.local_time);
-        Self::new_with_txn_tracker(execution_state_sender, context, None, txn_tracker)
+        Self::new_with_txn_tracker(execution_state_sender, context, deadline, txn_tracker)
     }
 
     /// Creates a new [`ServiceSyncRuntime`] ready to execute using a provided [`QueryContext`].
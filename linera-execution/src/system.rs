@@ -16,11 +16,12 @@ use linera_base::{
     crypto::CryptoHash,
     data_types::{
         Amount, ApplicationPermissions, ArithmeticError, Blob, BlobContent, BlockHeight,
-        ChainDescription, ChainOrigin, Epoch, InitialChainConfig, OracleResponse, Timestamp,
+        ChainDescription, ChainOrigin, Epoch, InitialChainConfig, OracleResponse, Round, Timestamp,
     },
     ensure, hex_debug,
     identifiers::{Account, AccountOwner, BlobId, BlobType, ChainId, EventId, ModuleId, StreamId},
     ownership::{ChainOwnership, TimeoutConfig},
+    randomness::RandomnessShare,
 };
 use linera_views::{
     context::Context,
@@ -34,7 +35,7 @@ use serde::{Deserialize, Serialize};
 #[cfg(test)]
 use crate::test_utils::SystemExecutionState;
 use crate::{
-    committee::Committee, ApplicationDescription, ApplicationId, ExecutionError,
+    committee::Committee, randomness, ApplicationDescription, ApplicationId, ExecutionError,
     ExecutionRuntimeContext, MessageContext, MessageKind, OperationContext, OutgoingMessage,
     QueryContext, QueryOutcome, ResourceController, TransactionTracker,
 };
@@ -43,6 +44,17 @@ use crate::{
 pub static EPOCH_STREAM_NAME: &[u8] = &[0];
 /// The event stream name for removed epochs.
 pub static REMOVED_EPOCH_STREAM_NAME: &[u8] = &[1];
+/// The prefix of the event stream names for application upgrades. The rest of the name is the
+/// application's description hash.
+pub static APPLICATION_UPGRADE_STREAM_PREFIX: &[u8] = &[2];
+
+/// Returns the system event stream on an application's creator chain that lists the modules
+/// the application was upgraded to, in order.
+pub fn application_upgrade_stream_id(application_id: ApplicationId) -> StreamId {
+    let mut stream_name = APPLICATION_UPGRADE_STREAM_PREFIX.to_vec();
+    stream_name.extend_from_slice(application_id.application_description_hash.as_bytes());
+    StreamId::system(stream_name)
+}
 
 /// The number of times the [`SystemOperation::OpenChain`] was executed.
 #[cfg(with_metrics)]
@@ -91,6 +103,15 @@ pub struct SystemExecutionStateView<C> {
     pub used_blobs: HashedSetView<C, BlobId>,
     /// The event stream subscriptions of applications on this chain.
     pub event_subscriptions: MapView<C, (ChainId, StreamId), EventSubscriptions>,
+    /// The modules that applications were upgraded to, oldest first. On an application's
+    /// creator chain, this is where upgrades happen. Other chains follow them in the same order
+    /// by executing [`SystemOperation::ProcessApplicationUpgrade`].
+    pub application_upgrades: HashedMapView<C, ApplicationId, Vec<ModuleId>>,
+    /// The owners allowed to upgrade the applications created on this chain.
+    pub upgrade_authorities: HashedMapView<C, ApplicationId, AccountOwner>,
+    /// The number of stored bytes each application on this chain paid storage rent for.
+    /// Freed storage is only refunded up to this amount.
+    pub storage_usage: HashedMapView<C, ApplicationId, u64>,
 }
 
 /// The applications subscribing to a particular stream, and the next event index.
@@ -193,6 +214,9 @@ pub enum SystemOperation {
         instantiation_argument: Vec<u8>,
         #[debug(skip_if = Vec::is_empty)]
         required_application_ids: Vec<ApplicationId>,
+        /// The owner allowed to upgrade the application, if any.
+        #[debug(skip_if = Option::is_none)]
+        upgrade_authority: Option<AccountOwner>,
     },
     /// Operations that are only allowed on the admin chain.
     Admin(AdminOperation),
@@ -202,6 +226,24 @@ pub enum SystemOperation {
     ProcessRemovedEpoch(Epoch),
     /// Updates the event stream trackers.
     UpdateStreams(Vec<(ChainId, StreamId, u32)>),
+    /// Provides the randomness shares of a quorum of validators for this block, in the round
+    /// in which it is first proposed. Applications can then obtain random bytes in the
+    /// following transactions of the block.
+    ProvideRandomness {
+        round: Round,
+        shares: Vec<RandomnessShare>,
+    },
+    /// Upgrades an application to a new module, and calls the new contract's `migrate` entry
+    /// point. The operation must be executed on the application's creator chain, and signed by
+    /// the application's upgrade authority. Other chains can then follow the upgrade by
+    /// executing [`SystemOperation::ProcessApplicationUpgrade`].
+    UpgradeApplication {
+        application_id: ApplicationId,
+        module_id: ModuleId,
+    },
+    /// Processes the next upgrade of an application on its creator chain, and calls the new
+    /// contract's `migrate` entry point on this chain.
+    ProcessApplicationUpgrade(ApplicationId),
 }
 
 /// Operations that are only allowed on the admin chain.
@@ -479,6 +521,7 @@ where
                 parameters,
                 instantiation_argument,
                 required_application_ids,
+                upgrade_authority,
             } => {
                 let txn_tracker_moved = mem::take(txn_tracker);
                 let CreateApplicationResult {
@@ -491,12 +534,29 @@ where
                         module_id,
                         parameters,
                         required_application_ids,
+                        upgrade_authority,
                         txn_tracker_moved,
                     )
                     .await?;
                 *txn_tracker = txn_tracker_moved;
                 new_application = Some((app_id, instantiation_argument));
             }
+            UpgradeApplication {
+                application_id,
+                module_id,
+            } => {
+                txn_tracker.add_application_upgrade(
+                    context.authenticated_signer,
+                    application_id,
+                    module_id,
+                );
+            }
+            ProcessApplicationUpgrade(application_id) => {
+                let previous_module = self
+                    .process_application_upgrade(context.chain_id, application_id, txn_tracker)
+                    .await?;
+                txn_tracker.add_application_migration(application_id, previous_module);
+            }
             PublishDataBlob { blob_hash } => {
                 self.blob_published(&BlobId::new(blob_hash, BlobType::Data), txn_tracker)?;
             }
@@ -599,6 +659,25 @@ where
                     );
                 }
             }
+            ProvideRandomness { round, shares } => {
+                let (_epoch, committee) = self
+                    .current_committee()
+                    .ok_or(ExecutionError::InvalidCommittees)?;
+                if let Some(multi_leader_round) = context.round {
+                    ensure!(
+                        round == Round::MultiLeader(multi_leader_round),
+                        ExecutionError::InvalidRandomnessRound(round)
+                    );
+                }
+                let seed = randomness::verify_randomness_shares(
+                    committee,
+                    context.chain_id,
+                    context.height,
+                    round,
+                    &shares,
+                )?;
+                txn_tracker.set_randomness_seed(seed)?;
+            }
         }
 
         Ok(new_application)
@@ -865,6 +944,7 @@ where
         module_id: ModuleId,
         parameters: Vec<u8>,
         required_application_ids: Vec<ApplicationId>,
+        upgrade_authority: Option<AccountOwner>,
         mut txn_tracker: TransactionTracker,
     ) -> Result<CreateApplicationResult, ExecutionError> {
         let application_index = txn_tracker.next_application_index();
@@ -891,8 +971,14 @@ where
         self.used_blobs.insert(&blob.id())?;
         txn_tracker.add_created_blob(blob);
 
+        let app_id = ApplicationId::from(&application_description);
+        if let Some(upgrade_authority) = upgrade_authority {
+            self.upgrade_authorities
+                .insert(&app_id, upgrade_authority)?;
+        }
+
         Ok(CreateApplicationResult {
-            app_id: ApplicationId::from(&application_description),
+            app_id,
             txn_tracker,
         })
     }
@@ -936,6 +1022,118 @@ where
         Ok(description)
     }
 
+    /// Returns the module that the application `id` with the `description` currently uses on
+    /// this chain.
+    pub async fn current_module_id(
+        &self,
+        id: ApplicationId,
+        description: &ApplicationDescription,
+    ) -> Result<ModuleId, ExecutionError> {
+        let upgrades = self.application_upgrades.get(&id).await?;
+        Ok(upgrades
+            .and_then(|modules| modules.last().copied())
+            .unwrap_or(description.module_id))
+    }
+
+    /// Upgrades the application `id` to the module `module_id`, if this is the application's
+    /// creator chain and the `authority` is the application's upgrade authority.
+    ///
+    /// The upgrade is published as an event, so that other chains can follow it. Returns the
+    /// module the application used before.
+    pub async fn upgrade_application(
+        &mut self,
+        chain_id: ChainId,
+        authority: Option<AccountOwner>,
+        id: ApplicationId,
+        module_id: ModuleId,
+        txn_tracker: &mut TransactionTracker,
+    ) -> Result<ModuleId, ExecutionError> {
+        let description = self.describe_application(id, txn_tracker).await?;
+        ensure!(
+            description.creator_chain_id == chain_id,
+            ExecutionError::ApplicationUpgradeOnNonCreatorChain(Box::new(id))
+        );
+        let upgrade_authority = self.upgrade_authorities.get(&id).await?;
+        ensure!(
+            authority.is_some() && upgrade_authority == authority,
+            ExecutionError::UnauthorizedApplicationUpgrade(Box::new(id))
+        );
+        let (previous_module, index) = self.apply_upgrade(id, &description, module_id).await?;
+        let blob_ids = self.check_bytecode_blobs(&module_id).await?;
+        for blob_id in blob_ids {
+            self.blob_used(txn_tracker, blob_id).await?;
+        }
+        txn_tracker.add_event(
+            application_upgrade_stream_id(id),
+            index,
+            bcs::to_bytes(&module_id)?,
+        );
+        Ok(previous_module)
+    }
+
+    /// Applies the next upgrade of the application `id` that happened on its creator chain.
+    ///
+    /// Returns the module the application used before.
+    pub async fn process_application_upgrade(
+        &mut self,
+        chain_id: ChainId,
+        id: ApplicationId,
+        txn_tracker: &mut TransactionTracker,
+    ) -> Result<ModuleId, ExecutionError> {
+        let description = self.describe_application(id, txn_tracker).await?;
+        ensure!(
+            description.creator_chain_id != chain_id,
+            ExecutionError::ApplicationUpgradeOnCreatorChain(Box::new(id))
+        );
+        let index = self
+            .application_upgrades
+            .get(&id)
+            .await?
+            .map_or(0, |modules| modules.len());
+        let event_id = EventId {
+            chain_id: description.creator_chain_id,
+            stream_id: application_upgrade_stream_id(id),
+            index: u32::try_from(index).map_err(|_| ArithmeticError::Overflow)?,
+        };
+        let bytes = match txn_tracker.next_replayed_oracle_response()? {
+            None => self.get_event(event_id.clone()).await?,
+            Some(OracleResponse::Event(recorded_event_id, bytes))
+                if recorded_event_id == event_id =>
+            {
+                bytes
+            }
+            Some(_) => return Err(ExecutionError::OracleResponseMismatch),
+        };
+        let module_id = bcs::from_bytes(&bytes)?;
+        txn_tracker.add_oracle_response(OracleResponse::Event(event_id, bytes));
+        let (previous_module, _) = self.apply_upgrade(id, &description, module_id).await?;
+        let blob_ids = self.check_bytecode_blobs(&module_id).await?;
+        for blob_id in blob_ids {
+            self.blob_used(txn_tracker, blob_id).await?;
+        }
+        Ok(previous_module)
+    }
+
+    /// Records that the application `id` now uses the module `module_id`.
+    ///
+    /// Returns the module the application used before, and the index of the upgrade.
+    async fn apply_upgrade(
+        &mut self,
+        id: ApplicationId,
+        description: &ApplicationDescription,
+        module_id: ModuleId,
+    ) -> Result<(ModuleId, u32), ExecutionError> {
+        ensure!(
+            module_id.vm_runtime == description.module_id.vm_runtime,
+            ExecutionError::ApplicationUpgradeVmRuntimeMismatch(Box::new(id))
+        );
+        let modules = self.application_upgrades.get_mut_or_default(&id).await?;
+        let previous_module = modules.last().copied().unwrap_or(description.module_id);
+        let index = u32::try_from(modules.len()).map_err(|_| ArithmeticError::Overflow)?;
+        modules.push(module_id);
+        Ok((previous_module, index))
+    }
+
     /// Retrieves the recursive dependencies of applications and applies a topological sort.
     pub async fn find_dependencies(
         &mut self,
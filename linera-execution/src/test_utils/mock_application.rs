@@ -19,7 +19,7 @@ use std::{
 use js_sys::wasm_bindgen;
 use linera_base::{
     data_types::StreamUpdate,
-    identifiers::{ChainId, StreamId},
+    identifiers::{ChainId, ModuleId, StreamId},
 };
 
 use crate::{
@@ -138,6 +138,11 @@ type ProcessStreamHandler = Box<
         + Send
         + Sync,
 >;
+type MigrateHandler = Box<
+    dyn FnOnce(&mut ContractSyncRuntimeHandle, ModuleId) -> Result<(), ExecutionError>
+        + Send
+        + Sync,
+>;
 type FinalizeHandler =
     Box<dyn FnOnce(&mut ContractSyncRuntimeHandle) -> Result<(), ExecutionError> + Send + Sync>;
 type HandleQueryHandler = Box<
@@ -157,6 +162,8 @@ pub enum ExpectedCall {
     ExecuteMessage(#[debug(skip)] ExecuteMessageHandler),
     /// An expected call to [`UserContract::process_streams`].
     ProcessStreams(#[debug(skip)] ProcessStreamHandler),
+    /// An expected call to [`UserContract::migrate`].
+    Migrate(#[debug(skip)] MigrateHandler),
     /// An expected call to [`UserContract::finalize`].
     Finalize(#[debug(skip)] FinalizeHandler),
     /// An expected call to [`UserService::handle_query`].
@@ -170,6 +177,7 @@ impl Display for ExpectedCall {
             ExpectedCall::ExecuteOperation(_) => "execute_operation",
             ExpectedCall::ExecuteMessage(_) => "execute_message",
             ExpectedCall::ProcessStreams(_) => "process_streams",
+            ExpectedCall::Migrate(_) => "migrate",
             ExpectedCall::Finalize(_) => "finalize",
             ExpectedCall::HandleQuery(_) => "handle_query",
         };
@@ -226,6 +234,17 @@ impl ExpectedCall {
         ExpectedCall::ProcessStreams(Box::new(handler))
     }
 
+    /// Creates an [`ExpectedCall`] to the [`MockApplicationInstance`]'s [`UserContract::migrate`]
+    /// implementation, which is handled by the provided `handler`.
+    pub fn migrate(
+        handler: impl FnOnce(&mut ContractSyncRuntimeHandle, ModuleId) -> Result<(), ExecutionError>
+            + Send
+            + Sync
+            + 'static,
+    ) -> Self {
+        ExpectedCall::Migrate(Box::new(handler))
+    }
+
     /// Creates an [`ExpectedCall`] to the [`MockApplicationInstance`]'s [`UserContract::finalize`]
     /// implementation, which is handled by the provided `handler`.
     pub fn finalize(
@@ -324,6 +343,16 @@ impl UserContract for MockApplicationInstance<ContractSyncRuntimeHandle> {
         }
     }
 
+    fn migrate(&mut self, previous_module: ModuleId) -> Result<(), ExecutionError> {
+        match self.next_expected_call() {
+            Some(ExpectedCall::Migrate(handler)) => handler(&mut self.runtime, previous_module),
+            Some(unexpected_call) => {
+                panic!("Expected a call to `migrate`, got a call to `{unexpected_call}` instead.")
+            }
+            None => panic!("Unexpected call to `migrate`"),
+        }
+    }
+
     fn finalize(&mut self) -> Result<(), ExecutionError> {
         match self.next_expected_call() {
             Some(ExpectedCall::Finalize(handler)) => handler(&mut self.runtime),
@@ -231,10 +231,10 @@ where
 
         extra
             .user_contracts()
-            .insert(id, mock_application.clone().into());
+            .insert(description.module_id, mock_application.clone().into());
         extra
             .user_services()
-            .insert(id, mock_application.clone().into());
+            .insert(description.module_id, mock_application.clone().into());
         extra
             .add_blobs([
                 contract,
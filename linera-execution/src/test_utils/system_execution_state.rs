@@ -11,7 +11,7 @@ use custom_debug_derive::Debug;
 use linera_base::{
     crypto::CryptoHash,
     data_types::{Amount, ApplicationPermissions, Blob, ChainDescription, Epoch, Timestamp},
-    identifiers::{AccountOwner, ApplicationId, BlobId, ChainId},
+    identifiers::{AccountOwner, ApplicationId, BlobId, ChainId, ModuleId},
     ownership::ChainOwnership,
 };
 use linera_views::{
@@ -48,7 +48,7 @@ pub struct SystemExecutionState {
     #[debug(skip_if = Vec::is_empty)]
     pub extra_blobs: Vec<Blob>,
     #[debug(skip_if = BTreeMap::is_empty)]
-    pub mock_applications: BTreeMap<ApplicationId, MockApplication>,
+    pub mock_applications: BTreeMap<ModuleId, MockApplication>,
 }
 
 impl SystemExecutionState {
@@ -130,11 +130,13 @@ impl SystemExecutionState {
             .add_blobs(extra_blobs)
             .await
             .expect("Adding blobs to the `TestExecutionRuntimeContext` should not fail");
-        for (id, mock_application) in mock_applications {
+        for (module_id, mock_application) in mock_applications {
             extra
                 .user_contracts()
-                .insert(id, mock_application.clone().into());
-            extra.user_services().insert(id, mock_application.into());
+                .insert(module_id, mock_application.clone().into());
+            extra
+                .user_services()
+                .insert(module_id, mock_application.into());
         }
 
         let context = MemoryContext::new_for_testing(extra);
@@ -189,7 +191,8 @@ impl RegisterMockApplication for SystemExecutionState {
             service,
             Blob::new_application_description(&description),
         ]);
-        self.mock_applications.insert(id, application.clone());
+        self.mock_applications
+            .insert(description.module_id, application.clone());
 
         Ok((id, application))
     }
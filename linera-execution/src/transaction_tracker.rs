@@ -8,9 +8,10 @@ use std::{
 
 use custom_debug_derive::Debug;
 use linera_base::{
+    crypto::CryptoHash,
     data_types::{ArithmeticError, Blob, Event, OracleResponse, StreamUpdate, Timestamp},
     ensure,
-    identifiers::{ApplicationId, BlobId, ChainId, StreamId},
+    identifiers::{AccountOwner, ApplicationId, BlobId, ChainId, ModuleId, StreamId},
 };
 
 use crate::{ExecutionError, OutgoingMessage};
@@ -48,6 +49,29 @@ pub struct TransactionTracker {
     streams_to_process: BTreeMap<ApplicationId, AppStreamUpdates>,
     /// Published blobs this transaction refers to by [`BlobId`].
     blobs_published: BTreeSet<BlobId>,
+    /// The randomness seed of the block, if it was provided by a previous transaction or by
+    /// this one.
+    randomness_seed: Option<CryptoHash>,
+    /// The number of times random bytes were requested in this transaction.
+    next_random_index: u32,
+    /// Application upgrades requested during this transaction, applied once it has finished.
+    #[debug(skip_if = Vec::is_empty)]
+    application_upgrades: Vec<ApplicationUpgrade>,
+    /// Applications upgraded during this transaction whose new contract's `migrate` entry point
+    /// still needs to be called, with the modules they used before.
+    #[debug(skip_if = Vec::is_empty)]
+    application_migrations: Vec<(ApplicationId, ModuleId)>,
+}
+
+/// A request to upgrade an application to a new module.
+#[derive(Clone, Debug)]
+pub struct ApplicationUpgrade {
+    /// The owner requesting the upgrade, which must be the application's upgrade authority.
+    pub authority: Option<AccountOwner>,
+    /// The application to upgrade.
+    pub application_id: ApplicationId,
+    /// The module to upgrade the application to.
+    pub module_id: ModuleId,
 }
 
 /// The [`TransactionTracker`] contents after a transaction has finished.
@@ -68,6 +92,8 @@ pub struct TransactionOutcome {
     pub operation_result: Vec<u8>,
     /// Blobs published by this transaction.
     pub blobs_published: BTreeSet<BlobId>,
+    /// The randomness seed of the block, if it has been provided so far.
+    pub randomness_seed: Option<CryptoHash>,
 }
 
 impl TransactionTracker {
@@ -95,6 +121,33 @@ impl TransactionTracker {
         self
     }
 
+    pub fn with_randomness_seed(mut self, randomness_seed: Option<CryptoHash>) -> Self {
+        self.randomness_seed = randomness_seed;
+        self
+    }
+
+    pub fn randomness_seed(&self) -> Option<CryptoHash> {
+        self.randomness_seed
+    }
+
+    pub fn set_randomness_seed(
+        &mut self,
+        randomness_seed: CryptoHash,
+    ) -> Result<(), ExecutionError> {
+        ensure!(
+            self.randomness_seed.is_none(),
+            ExecutionError::RandomnessAlreadyProvided
+        );
+        self.randomness_seed = Some(randomness_seed);
+        Ok(())
+    }
+
+    pub fn next_random_index(&mut self) -> Result<u32, ArithmeticError> {
+        let index = self.next_random_index;
+        self.next_random_index = index.checked_add(1).ok_or(ArithmeticError::Overflow)?;
+        Ok(index)
+    }
+
     pub fn local_time(&self) -> Timestamp {
         self.local_time
     }
@@ -229,6 +282,36 @@ impl TransactionTracker {
             .collect()
     }
 
+    pub fn add_application_upgrade(
+        &mut self,
+        authority: Option<AccountOwner>,
+        application_id: ApplicationId,
+        module_id: ModuleId,
+    ) {
+        self.application_upgrades.push(ApplicationUpgrade {
+            authority,
+            application_id,
+            module_id,
+        });
+    }
+
+    pub fn take_application_upgrades(&mut self) -> Vec<ApplicationUpgrade> {
+        mem::take(&mut self.application_upgrades)
+    }
+
+    pub fn add_application_migration(
+        &mut self,
+        application_id: ApplicationId,
+        previous_module: ModuleId,
+    ) {
+        self.application_migrations
+            .push((application_id, previous_module));
+    }
+
+    pub fn take_application_migrations(&mut self) -> Vec<(ApplicationId, ModuleId)> {
+        mem::take(&mut self.application_migrations)
+    }
+
     /// Adds the oracle response to the record.
     /// If replaying, it also checks that it matches the next replayed one and returns `true`.
     pub fn replay_oracle_response(
@@ -282,11 +365,19 @@ impl TransactionTracker {
             operation_result,
             streams_to_process,
             blobs_published,
+            randomness_seed,
+            next_random_index: _,
+            application_upgrades,
+            application_migrations,
         } = self;
         ensure!(
             streams_to_process.is_empty(),
             ExecutionError::UnprocessedStreams
         );
+        ensure!(
+            application_upgrades.is_empty() && application_migrations.is_empty(),
+            ExecutionError::UnprocessedApplicationUpgrades
+        );
         if let Some(mut responses) = replaying_oracle_responses {
             ensure!(
                 responses.next().is_none(),
@@ -303,6 +394,7 @@ impl TransactionTracker {
             blobs: blobs.into_values().collect(),
             operation_result: operation_result.unwrap_or_default(),
             blobs_published,
+            randomness_seed,
         })
     }
 }
@@ -8,9 +8,7 @@ use linera_base::{
     vm::VmRuntime,
 };
 
-use super::{
-    ApplicationRegistry, ApplicationRegistryView, ApplicationDescription, ApplicationId,
-};
+use super::{ApplicationDescription, ApplicationId, ApplicationRegistry, ApplicationRegistryView};
 
 fn message_id(index: u32) -> MessageId {
     MessageId {
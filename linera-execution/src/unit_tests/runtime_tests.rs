@@ -11,7 +11,11 @@ use std::{
 };
 
 use futures::{channel::mpsc, StreamExt};
-use linera_base::{crypto::CryptoHash, data_types::BlockHeight, identifiers::ApplicationId};
+use linera_base::{
+    crypto::CryptoHash,
+    data_types::{Amount, BlockHeight},
+    identifiers::ApplicationId,
+};
 use linera_views::batch::Batch;
 
 use super::{ApplicationStatus, SyncRuntimeHandle, SyncRuntimeInternal, WithContext};
@@ -19,7 +23,8 @@ use crate::{
     execution_state_actor::ExecutionRequest,
     runtime::{LoadedApplication, ResourceController, SyncRuntime},
     test_utils::{create_dummy_user_application_description, dummy_chain_description},
-    ContractRuntime, TransactionTracker, UserContractInstance,
+    ContractRuntime, ResourceControlPolicy, ResourceTracker, TransactionTracker,
+    UserContractInstance,
 };
 
 /// Test if dropping [`SyncRuntime`] does not leak memory.
@@ -100,6 +105,8 @@ async fn test_write_batch() {
 
     let expected_bytes_count =
         write_key.len() + write_data.len() + delete_key.len() + delete_key_prefix.len();
+    // The deleted keys were not stored, so only the new entry grows the application's storage.
+    let expected_stored_bytes_delta = (write_key.len() + write_data.len()) as i32;
 
     batch.put_key_value_bytes(write_key, write_data);
     batch.delete_key(delete_key);
@@ -119,6 +126,7 @@ async fn test_write_batch() {
             id,
             batch,
             callback,
+            ..
         } = request
         else {
             panic!("Expected a `ExecutionRequest::WriteBatch` but got {request:?} instead");
@@ -128,7 +136,7 @@ async fn test_write_batch() {
         assert_eq!(batch, expected_batch);
 
         callback
-            .send(())
+            .send(expected_stored_bytes_delta)
             .expect("Failed to notify that writing the batch finished");
     });
 
@@ -144,6 +152,61 @@ async fn test_write_batch() {
         runtime.inner().resource_controller.tracker.bytes_written,
         expected_bytes_count as u64
     );
+    assert_eq!(
+        runtime.inner().resource_controller.tracker.bytes_stored,
+        expected_stored_bytes_delta
+    );
+}
+
+/// Test that storage growth is charged and that freed storage is refunded.
+#[test_log::test(tokio::test(flavor = "multi_thread"))]
+async fn test_stored_bytes_are_charged_and_refunded() {
+    let (mut runtime, mut execution_state_receiver) = create_contract_runtime();
+    let policy = ResourceControlPolicy {
+        byte_stored: Amount::from_tokens(2),
+        byte_stored_refund: Amount::from_tokens(1),
+        ..ResourceControlPolicy::no_fees()
+    };
+    runtime.resource_controller = ResourceController::new(
+        Arc::new(policy),
+        ResourceTracker::default(),
+        Amount::from_tokens(100),
+    );
+    let mut runtime = SyncRuntimeHandle::from(runtime);
+
+    tokio::spawn(async move {
+        for stored_bytes_delta in [10, -4] {
+            let request = execution_state_receiver
+                .next()
+                .await
+                .expect("Missing expected request to write a batch");
+            let ExecutionRequest::WriteBatch { callback, .. } = request else {
+                panic!("Expected a `ExecutionRequest::WriteBatch` but got {request:?} instead");
+            };
+            callback
+                .send(stored_bytes_delta)
+                .expect("Failed to notify that writing the batch finished");
+        }
+    });
+
+    let mut batch = Batch::new();
+    batch.put_key_value_bytes(vec![1], vec![2]);
+    runtime
+        .write_batch(batch.clone())
+        .expect("Failed to write the first batch");
+    assert_eq!(
+        runtime.inner().resource_controller.account,
+        Amount::from_tokens(80)
+    );
+
+    runtime
+        .write_batch(batch)
+        .expect("Failed to write the second batch");
+    assert_eq!(
+        runtime.inner().resource_controller.account,
+        Amount::from_tokens(84)
+    );
+    assert_eq!(runtime.inner().resource_controller.tracker.bytes_stored, 6);
 }
 
 /// Creates a [`SyncRuntimeInternal`] instance for contracts, and returns it and the receiver
@@ -1,6 +1,8 @@
 // Copyright (c) Zefchain Labs, Inc.
 // SPDX-License-Identifier: Apache-2.0
 
+#[cfg(with_testing)]
+use assert_matches::assert_matches;
 use linera_base::data_types::{Blob, BlockHeight, Bytecode};
 #[cfg(with_testing)]
 use linera_base::vm::VmRuntime;
@@ -68,6 +70,7 @@ async fn application_message_index() -> anyhow::Result<()> {
         parameters: vec![],
         instantiation_argument: vec![],
         required_application_ids: vec![],
+        upgrade_authority: None,
     };
     let mut txn_tracker = TransactionTracker::default();
     view.context()
@@ -89,6 +92,150 @@ async fn application_message_index() -> anyhow::Result<()> {
     Ok(())
 }
 
+/// Tests that only the upgrade authority can upgrade an application on its creator chain,
+/// without changing its description or ID, and that other chains follow the upgrade through the
+/// event it emits.
+#[cfg(with_testing)]
+#[tokio::test]
+async fn upgrade_application() -> anyhow::Result<()> {
+    let (mut view, context) = new_view_and_context().await;
+    let mut blobs = Vec::new();
+    let mut module_ids = Vec::new();
+    for name in ["v1", "v2"] {
+        let contract = Bytecode::new(format!("{name} contract").into_bytes());
+        let service = Bytecode::new(format!("{name} service").into_bytes());
+        let contract_blob = Blob::new_contract_bytecode(contract.compress());
+        let service_blob = Blob::new_service_bytecode(service.compress());
+        module_ids.push(ModuleId::new(
+            contract_blob.id().hash,
+            service_blob.id().hash,
+            VmRuntime::Wasm,
+        ));
+        blobs.extend([contract_blob, service_blob]);
+    }
+    view.context().extra().add_blobs(blobs.clone()).await?;
+    let authority = AccountOwner::from(CryptoHash::test_hash("upgrade authority"));
+
+    let operation = SystemOperation::CreateApplication {
+        module_id: module_ids[0],
+        parameters: vec![],
+        instantiation_argument: vec![],
+        required_application_ids: vec![],
+        upgrade_authority: Some(authority),
+    };
+    let mut txn_tracker = TransactionTracker::default();
+    let (id, _) = view
+        .system
+        .execute_operation(
+            context,
+            operation,
+            &mut txn_tracker,
+            &mut ResourceController::default(),
+        )
+        .await?
+        .expect("The application should have been created");
+
+    for wrong_authority in [None, Some(AccountOwner::CHAIN)] {
+        let result = view
+            .system
+            .upgrade_application(
+                context.chain_id,
+                wrong_authority,
+                id,
+                module_ids[1],
+                &mut txn_tracker,
+            )
+            .await;
+        assert_matches!(
+            result,
+            Err(ExecutionError::UnauthorizedApplicationUpgrade(app_id)) if *app_id == id
+        );
+    }
+
+    let previous_module = view
+        .system
+        .upgrade_application(
+            context.chain_id,
+            Some(authority),
+            id,
+            module_ids[1],
+            &mut txn_tracker,
+        )
+        .await?;
+    assert_eq!(previous_module, module_ids[0]);
+    let description = view
+        .system
+        .describe_application(id, &mut txn_tracker)
+        .await?;
+    assert_eq!(description.module_id, module_ids[0]);
+    assert_eq!(ApplicationId::from(&description), id);
+    assert_eq!(
+        view.system.current_module_id(id, &description).await?,
+        module_ids[1]
+    );
+    let description_blob = Blob::new_application_description(&description);
+    let events = txn_tracker.into_outcome()?.events;
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].stream_id, application_upgrade_stream_id(id));
+    assert_eq!(events[0].index, 0);
+
+    // Another chain can't upgrade the application, but follows the upgrade.
+    let other_description = dummy_chain_description(6);
+    let other_chain_id = other_description.id();
+    let mut other_view = SystemExecutionState::new(other_description)
+        .into_view()
+        .await;
+    let extra = other_view.context().extra().clone();
+    extra.add_blobs(blobs).await?;
+    extra.add_blobs([description_blob]).await?;
+    let mut txn_tracker = TransactionTracker::default();
+    let result = other_view
+        .system
+        .upgrade_application(
+            other_chain_id,
+            Some(authority),
+            id,
+            module_ids[1],
+            &mut txn_tracker,
+        )
+        .await;
+    assert_matches!(
+        result,
+        Err(ExecutionError::ApplicationUpgradeOnNonCreatorChain(app_id)) if *app_id == id
+    );
+    let result = other_view
+        .system
+        .process_application_upgrade(other_chain_id, id, &mut txn_tracker)
+        .await;
+    assert_matches!(result, Err(ExecutionError::EventsNotFound(_)));
+
+    extra
+        .add_events(events.into_iter().map(|event| {
+            let event_id = EventId {
+                chain_id: context.chain_id,
+                stream_id: event.stream_id,
+                index: event.index,
+            };
+            (event_id, event.value)
+        }))
+        .await?;
+    let mut txn_tracker = TransactionTracker::default();
+    let previous_module = other_view
+        .system
+        .process_application_upgrade(other_chain_id, id, &mut txn_tracker)
+        .await?;
+    assert_eq!(previous_module, module_ids[0]);
+    assert_eq!(
+        other_view
+            .system
+            .current_module_id(id, &description)
+            .await?,
+        module_ids[1]
+    );
+
+    Ok(())
+}
+
 #[tokio::test]
 async fn open_chain_message_index() {
     let (mut view, context) = new_view_and_context().await;
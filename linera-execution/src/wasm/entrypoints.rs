@@ -3,7 +3,7 @@
 
 //! Wasm entrypoints for contracts and services.
 
-use linera_base::data_types::StreamUpdate;
+use linera_base::{data_types::StreamUpdate, identifiers::ModuleId};
 use linera_witty::wit_import;
 
 /// WIT entrypoints for application contracts.
@@ -13,6 +13,7 @@ pub trait ContractEntrypoints {
     fn execute_operation(operation: Vec<u8>) -> Vec<u8>;
     fn execute_message(message: Vec<u8>);
     fn process_streams(streams: Vec<StreamUpdate>);
+    fn migrate(previous_module: ModuleId);
     fn finalize();
 }
 
@@ -1,7 +1,7 @@
 // Copyright (c) Zefchain Labs, Inc.
 // SPDX-License-Identifier: Apache-2.0
 
-use std::{any::Any, collections::HashMap, marker::PhantomData};
+use std::{any::Any, collections::HashMap, marker::PhantomData, ops::Bound};
 
 use linera_base::{
     crypto::CryptoHash,
@@ -377,6 +377,60 @@ where
             .find_key_values_by_prefix_wait(&promise)
             .map_err(|error| RuntimeError::Custom(error.into()))
     }
+
+    /// Creates a new promise to search for keys that start with the `key_prefix` and whose
+    /// remainder lies between `lower` and `upper`. The bounds are given as the key and a flag
+    /// telling if it is included, with `None` meaning unbounded. The promise is resolved with
+    /// `find_keys_wait`.
+    fn find_keys_in_range_new(
+        caller: &mut Caller,
+        key_prefix: Vec<u8>,
+        lower: Option<(Vec<u8>, bool)>,
+        upper: Option<(Vec<u8>, bool)>,
+        reverse: bool,
+        limit: Option<u32>,
+    ) -> Result<u32, RuntimeError> {
+        let mut data = caller.user_data_mut();
+        let range = (bound_from_wit(lower), bound_from_wit(upper));
+        let limit = limit.map(|limit| limit as usize);
+        let promise = data
+            .runtime
+            .find_keys_by_prefix_in_range_new(key_prefix, range, reverse, limit)
+            .map_err(|error| RuntimeError::Custom(error.into()))?;
+
+        data.register_promise(promise)
+    }
+
+    /// Creates a new promise to search for entries whose keys start with the `key_prefix`
+    /// and whose remainder lies between `lower` and `upper`, as in `find_keys_in_range_new`.
+    /// The promise is resolved with `find_key_values_wait`.
+    fn find_key_values_in_range_new(
+        caller: &mut Caller,
+        key_prefix: Vec<u8>,
+        lower: Option<(Vec<u8>, bool)>,
+        upper: Option<(Vec<u8>, bool)>,
+        reverse: bool,
+        limit: Option<u32>,
+    ) -> Result<u32, RuntimeError> {
+        let mut data = caller.user_data_mut();
+        let range = (bound_from_wit(lower), bound_from_wit(upper));
+        let limit = limit.map(|limit| limit as usize);
+        let promise = data
+            .runtime
+            .find_key_values_by_prefix_in_range_new(key_prefix, range, reverse, limit)
+            .map_err(|error| RuntimeError::Custom(error.into()))?;
+
+        data.register_promise(promise)
+    }
+}
+
+/// Converts a range bound received from an application into a [`Bound`].
+fn bound_from_wit(bound: Option<(Vec<u8>, bool)>) -> Bound<Vec<u8>> {
+    match bound {
+        None => Bound::Unbounded,
+        Some((key, true)) => Bound::Included(key),
+        Some((key, false)) => Bound::Excluded(key),
+    }
 }
 
 /// An implementation of the system API made available to contracts.
@@ -531,6 +585,20 @@ where
             .map_err(|error| RuntimeError::Custom(error.into()))
     }
 
+    /// Upgrades an application on the current chain to a new module, once the current
+    /// transaction is complete.
+    fn upgrade_application(
+        caller: &mut Caller,
+        application_id: ApplicationId,
+        module_id: ModuleId,
+    ) -> Result<(), RuntimeError> {
+        caller
+            .user_data_mut()
+            .runtime
+            .upgrade_application(application_id, module_id)
+            .map_err(|error| RuntimeError::Custom(error.into()))
+    }
+
     /// Calls another application.
     fn try_call_application(
         caller: &mut Caller,
@@ -611,6 +679,22 @@ where
             .map_err(|error| RuntimeError::Custom(error.into()))
     }
 
+    /// Queries a service on another chain, in the state after the block at `height`, and
+    /// returns the response.
+    fn query_remote_application(
+        caller: &mut Caller,
+        chain_id: ChainId,
+        height: BlockHeight,
+        application_id: ApplicationId,
+        query: Vec<u8>,
+    ) -> Result<Vec<u8>, RuntimeError> {
+        caller
+            .user_data_mut()
+            .runtime
+            .query_remote_application(chain_id, height, application_id, query)
+            .map_err(|error| RuntimeError::Custom(error.into()))
+    }
+
     /// Consume some fuel.
     ///
     /// This is intended for the metering instrumentation, but if the user wants to donate
@@ -632,6 +716,34 @@ where
             .map_err(|error| RuntimeError::Custom(error.into()))
     }
 
+    /// Checks that the BCS-serialized signed `responses` to an HTTP `request` were signed
+    /// for the current block by a quorum of validators, and reduces them to a single
+    /// response.
+    fn verify_http_responses(
+        caller: &mut Caller,
+        request: http::Request,
+        reducer: http::Reducer,
+        responses: Vec<u8>,
+    ) -> Result<http::Response, RuntimeError> {
+        let responses =
+            bcs::from_bytes(&responses).map_err(|error| RuntimeError::Custom(error.into()))?;
+        caller
+            .user_data_mut()
+            .runtime_mut()
+            .verify_http_responses(request, reducer, responses)
+            .map_err(|error| RuntimeError::Custom(error.into()))
+    }
+
+    /// Returns 32 random bytes, derived from the randomness beacon of the current block.
+    fn random_bytes(caller: &mut Caller) -> Result<Vec<u8>, RuntimeError> {
+        caller
+            .user_data_mut()
+            .runtime_mut()
+            .random_bytes()
+            .map(Vec::from)
+            .map_err(|error| RuntimeError::Custom(error.into()))
+    }
+
     /// Writes a batch of `operations` to storage.
     fn write_batch(
         caller: &mut Caller,
@@ -5,7 +5,10 @@
 
 use std::{marker::Unpin, sync::LazyLock};
 
-use linera_base::data_types::{Bytecode, StreamUpdate};
+use linera_base::{
+    data_types::{Bytecode, StreamUpdate},
+    identifiers::ModuleId,
+};
 use linera_witty::{
     wasmer::{EntrypointInstance, InstanceBuilder},
     ExportTo,
@@ -154,6 +157,13 @@ where
         Ok(())
     }
 
+    fn migrate(&mut self, previous_module: ModuleId) -> Result<(), ExecutionError> {
+        ContractEntrypoints::new(&mut self.instance)
+            .migrate(previous_module)
+            .map_err(WasmExecutionError::from)?;
+        Ok(())
+    }
+
     fn finalize(&mut self) -> Result<(), ExecutionError> {
         ContractEntrypoints::new(&mut self.instance)
             .finalize()
@@ -5,7 +5,10 @@
 
 use std::sync::LazyLock;
 
-use linera_base::data_types::{Bytecode, StreamUpdate};
+use linera_base::{
+    data_types::{Bytecode, StreamUpdate},
+    identifiers::ModuleId,
+};
 use linera_witty::{wasmtime::EntrypointInstance, ExportTo};
 use tokio::sync::Mutex;
 use wasmtime::{Config, Engine, Linker, Module, Store};
@@ -159,6 +162,13 @@ where
         Ok(())
     }
 
+    fn migrate(&mut self, previous_module: ModuleId) -> Result<(), ExecutionError> {
+        ContractEntrypoints::new(&mut self.instance)
+            .migrate(previous_module)
+            .map_err(WasmExecutionError::from)?;
+        Ok(())
+    }
+
     fn finalize(&mut self) -> Result<(), ExecutionError> {
         ContractEntrypoints::new(&mut self.instance)
             .finalize()
@@ -13,11 +13,12 @@ use linera_base::{
     crypto::CryptoHash,
     data_types::{
         Amount, ApplicationDescription, ApplicationPermissions, Blob, BlockHeight,
-        CompressedBytecode, OracleResponse,
+        CompressedBytecode, HttpPolicy, OracleResponse,
     },
     http,
     identifiers::{Account, AccountOwner, ApplicationId, ModuleId},
     ownership::ChainOwnership,
+    randomness,
     vm::VmRuntime,
 };
 use linera_execution::{
@@ -950,3 +951,302 @@ async fn test_perform_http_request(authorized_apps: Option<Vec<()>>) -> Result<(
 
     Ok(())
 }
+
+/// Tests that an application's HTTP policy limits the number of requests per block.
+#[test_case(1 => matches Ok(_); "within quota")]
+#[test_case(2 => matches Ok(_); "at quota")]
+#[test_case(3 => matches Err(ExecutionError::HttpRequestQuotaExceeded { limit: 2, .. }); "above quota")]
+#[test_log::test(tokio::test)]
+async fn test_http_request_quota(requests: usize) -> Result<(), ExecutionError> {
+    let description = dummy_chain_description(0);
+    let chain_id = description.id();
+    let mut view = SystemExecutionState {
+        ownership: ChainOwnership::default(),
+        balance: Amount::ONE,
+        balances: BTreeMap::new(),
+        ..SystemExecutionState::new(description)
+    }
+    .into_view()
+    .await;
+
+    let contract_blob = TransferTestEndpoint::sender_application_contract_blob();
+    let service_blob = TransferTestEndpoint::sender_application_service_blob();
+    let contract_blob_id = contract_blob.id();
+    let service_blob_id = service_blob.id();
+
+    let application_description = TransferTestEndpoint::sender_application_description();
+    let application_description_blob = Blob::new_application_description(&application_description);
+    let app_desc_blob_id = application_description_blob.id();
+
+    let (application_id, application) = view
+        .register_mock_application_with(application_description, contract_blob, service_blob)
+        .await
+        .expect("should register mock application");
+
+    view.system
+        .application_permissions
+        .set(ApplicationPermissions {
+            http_policies: vec![HttpPolicy {
+                application_id,
+                url_prefixes: vec!["http://localhost/".to_owned()],
+                methods: vec![http::Method::Get],
+                maximum_request_bytes: None,
+                maximum_response_bytes: None,
+                maximum_requests_per_block: Some(2),
+            }],
+            ..ApplicationPermissions::new_single(application_id)
+        });
+
+    application.expect_call(ExpectedCall::execute_operation(
+        move |runtime, _operation| {
+            for _ in 0..requests {
+                runtime.perform_http_request(http::Request::get("http://localhost/"))?;
+            }
+            Ok(vec![])
+        },
+    ));
+    application.expect_call(ExpectedCall::default_finalize());
+
+    let context = create_dummy_operation_context(chain_id);
+    let mut controller = ResourceController::default();
+    let operation = Operation::User {
+        application_id,
+        bytes: vec![],
+    };
+
+    let mut oracle_responses = vec![
+        OracleResponse::Blob(app_desc_blob_id),
+        OracleResponse::Blob(contract_blob_id),
+        OracleResponse::Blob(service_blob_id),
+    ];
+    oracle_responses
+        .extend((0..requests).map(|_| OracleResponse::Http(http::Response::ok(vec![]))));
+
+    view.execute_operation(
+        context,
+        operation,
+        &mut TransactionTracker::new_replaying(oracle_responses),
+        &mut controller,
+    )
+    .await?;
+
+    assert_eq!(
+        controller.tracker.http_requests_per_application[&application_id],
+        requests as u32
+    );
+
+    Ok(())
+}
+
+/// Tests that an application's HTTP policy is enforced when the responses are replayed.
+#[test_case("http://localhost/", 4 => matches Ok(_); "allowed")]
+#[test_case("http://example.com/", 4 => matches Err(ExecutionError::HttpRequestNotAllowedByPolicy { .. }); "forbidden url")]
+#[test_case("http://localhost/", 9 => matches Err(ExecutionError::HttpResponseSizeLimitExceeded { limit: 8, size: 9 }); "oversized response")]
+#[test_log::test(tokio::test)]
+async fn test_http_policy_when_replaying(
+    url: &'static str,
+    response_size: usize,
+) -> Result<(), ExecutionError> {
+    let description = dummy_chain_description(0);
+    let chain_id = description.id();
+    let mut view = SystemExecutionState {
+        ownership: ChainOwnership::default(),
+        balance: Amount::ONE,
+        balances: BTreeMap::new(),
+        ..SystemExecutionState::new(description)
+    }
+    .into_view()
+    .await;
+
+    let contract_blob = TransferTestEndpoint::sender_application_contract_blob();
+    let service_blob = TransferTestEndpoint::sender_application_service_blob();
+    let contract_blob_id = contract_blob.id();
+    let service_blob_id = service_blob.id();
+
+    let application_description = TransferTestEndpoint::sender_application_description();
+    let application_description_blob = Blob::new_application_description(&application_description);
+    let app_desc_blob_id = application_description_blob.id();
+
+    let (application_id, application) = view
+        .register_mock_application_with(application_description, contract_blob, service_blob)
+        .await
+        .expect("should register mock application");
+
+    view.system
+        .application_permissions
+        .set(ApplicationPermissions {
+            http_policies: vec![HttpPolicy {
+                application_id,
+                url_prefixes: vec!["http://localhost/".to_owned()],
+                methods: vec![http::Method::Get],
+                maximum_request_bytes: None,
+                maximum_response_bytes: Some(8),
+                maximum_requests_per_block: None,
+            }],
+            ..ApplicationPermissions::new_single(application_id)
+        });
+
+    application.expect_call(ExpectedCall::execute_operation(
+        move |runtime, _operation| {
+            runtime.perform_http_request(http::Request::get(url))?;
+            Ok(vec![])
+        },
+    ));
+    application.expect_call(ExpectedCall::default_finalize());
+
+    let context = create_dummy_operation_context(chain_id);
+    let mut controller = ResourceController::default();
+    let operation = Operation::User {
+        application_id,
+        bytes: vec![],
+    };
+
+    view.execute_operation(
+        context,
+        operation,
+        &mut TransactionTracker::new_replaying(vec![
+            OracleResponse::Blob(app_desc_blob_id),
+            OracleResponse::Blob(contract_blob_id),
+            OracleResponse::Blob(service_blob_id),
+            OracleResponse::Http(http::Response::ok(vec![0; response_size])),
+        ]),
+        &mut controller,
+    )
+    .await?;
+
+    Ok(())
+}
+
+/// Tests that applications can only obtain random bytes once the block's randomness seed was
+/// provided, and that the bytes are derived from the seed.
+#[test_case(None => matches Err(ExecutionError::RandomnessUnavailable); "without seed")]
+#[test_case(Some(CryptoHash::test_hash("seed")) => matches Ok(_); "with seed")]
+#[test_log::test(tokio::test)]
+async fn test_random_bytes(seed: Option<CryptoHash>) -> Result<(), ExecutionError> {
+    let description = dummy_chain_description(0);
+    let chain_id = description.id();
+    let mut view = SystemExecutionState::new(description).into_view().await;
+
+    let contract_blob = TransferTestEndpoint::sender_application_contract_blob();
+    let service_blob = TransferTestEndpoint::sender_application_service_blob();
+    let contract_blob_id = contract_blob.id();
+    let service_blob_id = service_blob.id();
+
+    let application_description = TransferTestEndpoint::sender_application_description();
+    let application_description_blob = Blob::new_application_description(&application_description);
+    let app_desc_blob_id = application_description_blob.id();
+
+    let (application_id, application) = view
+        .register_mock_application_with(application_description, contract_blob, service_blob)
+        .await
+        .expect("should register mock application");
+
+    let expected_bytes = seed.map(|seed| {
+        [0, 1].map(|index| randomness::derive_random_bytes(seed, application_id, 0, index))
+    });
+
+    application.expect_call(ExpectedCall::execute_operation(
+        move |runtime, _operation| {
+            let first = runtime.random_bytes()?;
+            let second = runtime.random_bytes()?;
+            assert_eq!(Some([first, second]), expected_bytes);
+            assert_ne!(first, second);
+            Ok(vec![])
+        },
+    ));
+    application.expect_call(ExpectedCall::default_finalize());
+
+    let context = create_dummy_operation_context(chain_id);
+    let mut controller = ResourceController::default();
+    let operation = Operation::User {
+        application_id,
+        bytes: vec![],
+    };
+
+    let mut oracle_responses = vec![
+        OracleResponse::Blob(app_desc_blob_id),
+        OracleResponse::Blob(contract_blob_id),
+        OracleResponse::Blob(service_blob_id),
+    ];
+    oracle_responses.extend(
+        expected_bytes
+            .into_iter()
+            .flatten()
+            .map(OracleResponse::Random),
+    );
+
+    view.execute_operation(
+        context,
+        operation,
+        &mut TransactionTracker::new_replaying(oracle_responses).with_randomness_seed(seed),
+        &mut controller,
+    )
+    .await?;
+
+    Ok(())
+}
+
+/// Tests that a contract's queries to applications on other chains are answered from the
+/// oracle responses when replaying, and checked against the requested chain and height.
+#[test_case(None => matches Err(ExecutionError::RemoteChainStateUnavailable { .. }); "not replaying")]
+#[test_case(Some(BlockHeight(5)) => matches Ok(_); "replaying")]
+#[test_case(Some(BlockHeight(4)) => matches Err(ExecutionError::OracleResponseMismatch); "replaying other height")]
+#[test_log::test(tokio::test)]
+async fn test_query_remote_application(
+    recorded_height: Option<BlockHeight>,
+) -> Result<(), ExecutionError> {
+    let description = dummy_chain_description(0);
+    let chain_id = description.id();
+    let remote_chain_id = dummy_chain_description(1).id();
+    let mut view = SystemExecutionState::new(description).into_view().await;
+
+    let contract_blob = TransferTestEndpoint::sender_application_contract_blob();
+    let service_blob = TransferTestEndpoint::sender_application_service_blob();
+    let contract_blob_id = contract_blob.id();
+    let service_blob_id = service_blob.id();
+
+    let application_description = TransferTestEndpoint::sender_application_description();
+    let application_description_blob = Blob::new_application_description(&application_description);
+    let app_desc_blob_id = application_description_blob.id();
+
+    let (application_id, application) = view
+        .register_mock_application_with(application_description, contract_blob, service_blob)
+        .await
+        .expect("should register mock application");
+
+    application.expect_call(ExpectedCall::execute_operation(
+        move |runtime, _operation| {
+            let response = runtime.query_remote_application(
+                remote_chain_id,
+                BlockHeight(5),
+                application_id,
+                b"query".to_vec(),
+            )?;
+            assert_eq!(response, b"response");
+            Ok(vec![])
+        },
+    ));
+    application.expect_call(ExpectedCall::default_finalize());
+
+    let context = create_dummy_operation_context(chain_id);
+    let mut controller = ResourceController::default();
+    let operation = Operation::User {
+        application_id,
+        bytes: vec![],
+    };
+
+    let mut txn_tracker = match recorded_height {
+        None => TransactionTracker::default(),
+        Some(height) => TransactionTracker::new_replaying(vec![
+            OracleResponse::Blob(app_desc_blob_id),
+            OracleResponse::Blob(contract_blob_id),
+            OracleResponse::Blob(service_blob_id),
+            OracleResponse::RemoteQuery(remote_chain_id, height, b"response".to_vec()),
+        ]),
+    };
+
+    view.execute_operation(context, operation, &mut txn_tracker, &mut controller)
+        .await?;
+
+    Ok(())
+}
@@ -16,12 +16,13 @@ use linera_base::{
 };
 use linera_execution::{
     test_utils::{
-        blob_oracle_responses, dummy_chain_description, ExpectedCall, RegisterMockApplication,
-        SystemExecutionState,
+        blob_oracle_responses, create_dummy_operation_context, dummy_chain_description,
+        ExpectedCall, RegisterMockApplication, SystemExecutionState,
     },
-    ContractRuntime, ExecutionError, Message, MessageContext, ResourceControlPolicy,
+    ContractRuntime, ExecutionError, Message, MessageContext, Operation, ResourceControlPolicy,
     ResourceController, ResourceTracker, TransactionTracker,
 };
+use linera_views::batch::Batch;
 use test_case::test_case;
 
 /// Tests if the chain balance is updated based on the fees spent for consuming resources.
@@ -236,6 +237,7 @@ async fn test_fee_consumption(
         blob_published: Amount::from_tokens(97),
         blob_byte_read: Amount::from_tokens(101),
         blob_byte_published: Amount::from_tokens(103),
+        byte_stored_refund: Amount::from_tokens(107),
         http_request_allow_list: BTreeSet::new(),
     };
 
@@ -356,6 +358,77 @@ async fn test_fee_consumption(
     Ok(())
 }
 
+/// Tests that storage growth is charged, and that freeing storage is only refunded for the bytes
+/// the application paid storage rent for.
+#[tokio::test]
+async fn test_storage_rent_refunds_are_capped() -> anyhow::Result<()> {
+    let chain_description = dummy_chain_description(0);
+    let chain_id = chain_description.id();
+    let mut state = SystemExecutionState {
+        description: Some(chain_description),
+        ..SystemExecutionState::default()
+    };
+    let (application_id, application, blobs) = state.register_mock_application(0).await?;
+    let mut view = state.into_view().await;
+    view.system.balance.set(Amount::from_tokens(1_000));
+
+    // 101 bytes the application didn't pay storage rent for.
+    let mut batch = Batch::new();
+    batch.put_key_value_bytes(vec![0], vec![0; 100]);
+    view.users
+        .try_load_entry_mut(&application_id)
+        .await?
+        .write_batch(batch)
+        .await?;
+
+    let policy = ResourceControlPolicy {
+        byte_stored: Amount::from_tokens(2),
+        byte_stored_refund: Amount::from_tokens(1),
+        ..ResourceControlPolicy::no_fees()
+    };
+    let mut controller =
+        ResourceController::new(Arc::new(policy), ResourceTracker::default(), None);
+
+    application.expect_call(ExpectedCall::execute_operation(|runtime, _operation| {
+        // Storing 11 bytes costs 22 tokens.
+        let mut batch = Batch::new();
+        batch.put_key_value_bytes(vec![1], vec![1; 10]);
+        runtime.write_batch(batch)?;
+        // Freeing all 112 bytes only refunds the 11 that were paid for.
+        let mut batch = Batch::new();
+        batch.delete_key_prefix(vec![]);
+        runtime.write_batch(batch)?;
+        Ok(vec![])
+    }));
+    application.expect_call(ExpectedCall::default_finalize());
+
+    let mut txn_tracker = TransactionTracker::new_replaying(blob_oracle_responses(blobs.iter()));
+    view.execute_operation(
+        create_dummy_operation_context(chain_id),
+        Operation::User {
+            application_id,
+            bytes: vec![],
+        },
+        &mut txn_tracker,
+        &mut controller,
+    )
+    .await?;
+
+    assert_eq!(*view.system.balance.get(), Amount::from_tokens(989));
+    assert_eq!(controller.tracker.bytes_stored, 0);
+    assert_eq!(view.system.storage_usage.get(&application_id).await?, None);
+    assert_eq!(
+        view.users
+            .try_load_entry(&application_id)
+            .await?
+            .expect("The application's storage should exist")
+            .total_size()
+            .sum(),
+        0
+    );
+    Ok(())
+}
+
 /// A runtime operation that costs some amount of fees.
 pub enum FeeSpend {
     /// Consume some execution fuel.
@@ -63,13 +63,13 @@ async fn test_fuel_for_counter_revm_application() -> anyhow::Result<()> {
     view.context()
         .extra()
         .user_contracts()
-        .insert(app_id, contract.clone().into());
+        .insert(app_desc.module_id, contract.clone().into());
 
     let service = EvmServiceModule::Revm { module };
     view.context()
         .extra()
         .user_services()
-        .insert(app_id, service.into());
+        .insert(app_desc.module_id, service.into());
 
     view.simulate_instantiation(
         contract.into(),
@@ -134,9 +134,12 @@ async fn test_fuel_for_counter_revm_application() -> anyhow::Result<()> {
         let query = Query::User {
             application_id: app_id,
             bytes,
+            at_height: None,
         };
 
-        let result = view.query_application(query_context, query, None).await?;
+        let result = view
+            .query_application(query_context, query, None, None)
+            .await?;
 
         let QueryResponse::User(result) = result.response else {
             anyhow::bail!("Wrong QueryResponse result");
@@ -187,13 +190,13 @@ async fn test_terminate_execute_operation_by_lack_of_fuel() -> anyhow::Result<()
     view.context()
         .extra()
         .user_contracts()
-        .insert(app_id, contract.clone().into());
+        .insert(app_desc.module_id, contract.clone().into());
 
     let service = EvmServiceModule::Revm { module };
     view.context()
         .extra()
         .user_services()
-        .insert(app_id, service.into());
+        .insert(app_desc.module_id, service.into());
 
     view.simulate_instantiation(
         contract.into(),
@@ -285,13 +288,13 @@ async fn test_terminate_query_by_lack_of_fuel() -> anyhow::Result<()> {
     view.context()
         .extra()
         .user_contracts()
-        .insert(app_id, contract.clone().into());
+        .insert(app_desc.module_id, contract.clone().into());
 
     let service = EvmServiceModule::Revm { module };
     view.context()
         .extra()
         .user_services()
-        .insert(app_id, service.into());
+        .insert(app_desc.module_id, service.into());
 
     view.simulate_instantiation(
         contract.into(),
@@ -321,9 +324,12 @@ async fn test_terminate_query_by_lack_of_fuel() -> anyhow::Result<()> {
     let query = Query::User {
         application_id: app_id,
         bytes,
+        at_height: None,
     };
 
-    let result = view.query_application(query_context, query, None).await;
+    let result = view
+        .query_application(query_context, query, None, None)
+        .await;
     assert!(result.is_err());
 
     Ok(())
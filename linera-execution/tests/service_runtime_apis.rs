@@ -5,14 +5,19 @@
 
 use std::{collections::BTreeMap, vec};
 
-use linera_base::{data_types::Amount, identifiers::AccountOwner};
+use assert_matches::assert_matches;
+use linera_base::{
+    data_types::{Amount, BlockHeight},
+    identifiers::AccountOwner,
+};
 use linera_execution::{
     test_utils::{
         create_dummy_query_context, dummy_chain_description, test_accounts_strategy, ExpectedCall,
         RegisterMockApplication, SystemExecutionState,
     },
-    BaseRuntime, Query,
+    BaseRuntime, ExecutionError, Query,
 };
+use linera_views::key_value_store_view::HistoryWindow;
 use test_strategy::proptest;
 
 /// Tests the contract system API to read the chain balance.
@@ -37,9 +42,12 @@ async fn test_read_chain_balance_system_api(chain_balance: Amount) {
     let query = Query::User {
         application_id,
         bytes: vec![],
+        at_height: None,
     };
 
-    view.query_application(context, query, None).await.unwrap();
+    view.query_application(context, query, None, None)
+        .await
+        .unwrap();
 }
 
 /// Tests the contract system API to read a single account balance.
@@ -68,9 +76,12 @@ async fn test_read_owner_balance_system_api(
     let query = Query::User {
         application_id,
         bytes: vec![],
+        at_height: None,
     };
 
-    view.query_application(context, query, None).await.unwrap();
+    view.query_application(context, query, None, None)
+        .await
+        .unwrap();
 }
 
 /// Tests if reading the balance of a missing account returns zero.
@@ -95,9 +106,12 @@ async fn test_read_owner_balance_returns_zero_for_missing_accounts(missing_accou
     let query = Query::User {
         application_id,
         bytes: vec![],
+        at_height: None,
     };
 
-    view.query_application(context, query, None).await.unwrap();
+    view.query_application(context, query, None, None)
+        .await
+        .unwrap();
 }
 
 /// Tests the contract system API to read all account balances.
@@ -127,9 +141,12 @@ async fn test_read_owner_balances_system_api(
     let query = Query::User {
         application_id,
         bytes: vec![],
+        at_height: None,
     };
 
-    view.query_application(context, query, None).await.unwrap();
+    view.query_application(context, query, None, None)
+        .await
+        .unwrap();
 }
 
 /// Tests the contract system API to read all account owners.
@@ -159,7 +176,64 @@ async fn test_read_balance_owners_system_api(
     let query = Query::User {
         application_id,
         bytes: vec![],
+        at_height: None,
     };
 
-    view.query_application(context, query, None).await.unwrap();
+    view.query_application(context, query, None, None)
+        .await
+        .unwrap();
+}
+
+/// Tests that queries at a past height are limited to the state history window, and cannot
+/// read the system state.
+#[tokio::test]
+async fn test_system_api_at_past_height() {
+    let mut view = SystemExecutionState::new(dummy_chain_description(0))
+        .into_view()
+        .await;
+
+    let (application_id, application, _) = view.register_mock_application(0).await.unwrap();
+
+    application.expect_call(ExpectedCall::handle_query(move |runtime, _query| {
+        runtime.read_chain_balance()?;
+        Ok(vec![])
+    }));
+
+    let context = create_dummy_query_context();
+    let query = Query::User {
+        application_id,
+        bytes: vec![],
+        at_height: Some(BlockHeight(3)),
+    };
+    let window = HistoryWindow {
+        start: 2,
+        next_version: 6,
+        retention: 3,
+    };
+
+    assert_matches!(
+        view.query_application(context, query.clone(), None, None)
+            .await,
+        Err(ExecutionError::StateHistoryDisabled)
+    );
+    let late_window = HistoryWindow { start: 5, ..window };
+    assert_matches!(
+        view.query_application(context, query.clone(), None, Some(late_window))
+            .await,
+        Err(ExecutionError::StateHistoryUnavailable(BlockHeight(3)))
+    );
+    let short_window = HistoryWindow {
+        retention: 2,
+        ..window
+    };
+    assert_matches!(
+        view.query_application(context, query.clone(), None, Some(short_window))
+            .await,
+        Err(ExecutionError::StateHistoryUnavailable(BlockHeight(3)))
+    );
+    assert_matches!(
+        view.query_application(context, query, None, Some(window))
+            .await,
+        Err(ExecutionError::SystemStateUnavailable(BlockHeight(3)))
+    );
 }
@@ -179,9 +179,11 @@ async fn test_simple_user_operation() -> anyhow::Result<()> {
             context,
             Query::User {
                 application_id: caller_id,
-                bytes: vec![]
+                bytes: vec![],
+                at_height: None
             },
             Some(&mut service_runtime_endpoint),
+            None,
         )
         .await
         .unwrap(),
@@ -196,9 +198,11 @@ async fn test_simple_user_operation() -> anyhow::Result<()> {
             context,
             Query::User {
                 application_id: caller_id,
-                bytes: vec![]
+                bytes: vec![],
+                at_height: None
             },
             Some(&mut service_runtime_endpoint),
+            None,
         )
         .await
         .unwrap(),
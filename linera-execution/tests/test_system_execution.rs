@@ -128,7 +128,7 @@ async fn test_simple_system_query() -> anyhow::Result<()> {
         response,
         operations,
     } = view
-        .query_application(context, Query::System(SystemQuery), None)
+        .query_application(context, Query::System(SystemQuery), None, None)
         .await
         .unwrap();
     assert_eq!(
@@ -49,14 +49,14 @@ async fn test_fuel_for_counter_wasm_application(
     view.context()
         .extra()
         .user_contracts()
-        .insert(app_id, contract.into());
+        .insert(app_desc.module_id, contract.into());
 
     let service =
         WasmServiceModule::from_file("tests/fixtures/counter_service.wasm", wasm_runtime).await?;
     view.context()
         .extra()
         .user_services()
-        .insert(app_id, service.into());
+        .insert(app_desc.module_id, service.into());
 
     view.context()
         .extra()
@@ -129,6 +129,7 @@ async fn test_fuel_for_counter_wasm_application(
             context,
             Query::user_without_abi(app_id, &request).unwrap(),
             Some(&mut service_runtime_endpoint),
+            None,
         )
         .await?;
     let QueryOutcome {
@@ -4,7 +4,9 @@
 //! An example of an indexer with the operations plugin.
 
 use linera_indexer::{common::IndexerError, plugin::Plugin, rocks_db::RocksDbRunner};
-use linera_indexer_plugins::operations::OperationsPlugin;
+use linera_indexer_plugins::{
+    abi::ApplicationAbi, applications::ApplicationsPlugin, operations::OperationsPlugin,
+};
 
 #[tokio::main]
 async fn main() -> Result<(), IndexerError> {
@@ -20,5 +22,22 @@ async fn main() -> Result<(), IndexerError> {
     runner
         .add_plugin(OperationsPlugin::load(runner.store.clone()).await?)
         .await?;
+    let abis = runner
+        .config
+        .abis
+        .iter()
+        .map(|path| ApplicationAbi::read(path))
+        .collect::<Result<Vec<_>, _>>()?;
+    runner
+        .add_plugin(
+            ApplicationsPlugin::load(runner.store.clone())
+                .await?
+                .with_abis(abis),
+        )
+        .await?;
+    #[cfg(feature = "sql")]
+    if let Some(plugin) = linera_indexer::sql::SqlPlugin::from_config(&runner.config.sql).await? {
+        runner.add_plugin(plugin).await?;
+    }
     runner.run().await
 }
@@ -0,0 +1,83 @@
+-- The initial schema of the SQL sink of the indexer. Statements are kept compatible with
+-- both PostgreSQL and SQLite. Hashes, identifiers and byte strings are stored as
+-- hexadecimal text. Amounts are stored twice: as decimal text in tokens, e.g. `1.5`, and
+-- in attos as `NUMERIC(39,0)` in the `_attos` columns, which can be summed and compared.
+-- PostgreSQL stores the latter exactly, but SQLite stores the ones above 2^63 as
+-- floating-point numbers, so the decimal text is the exact value there.
+
+CREATE TABLE blocks (
+    chain_id TEXT NOT NULL,
+    height BIGINT NOT NULL,
+    hash TEXT NOT NULL UNIQUE,
+    previous_block_hash TEXT,
+    epoch BIGINT NOT NULL,
+    timestamp BIGINT NOT NULL,
+    authenticated_signer TEXT,
+    state_hash TEXT NOT NULL,
+    PRIMARY KEY (chain_id, height)
+);
+
+CREATE TABLE operations (
+    chain_id TEXT NOT NULL,
+    height BIGINT NOT NULL,
+    operation_index BIGINT NOT NULL,
+    -- `NULL` for system operations.
+    application_id TEXT,
+    -- JSON for system operations, the serialized bytes for user operations.
+    content TEXT NOT NULL,
+    PRIMARY KEY (chain_id, height, operation_index),
+    FOREIGN KEY (chain_id, height) REFERENCES blocks (chain_id, height)
+);
+
+CREATE TABLE messages (
+    chain_id TEXT NOT NULL,
+    height BIGINT NOT NULL,
+    transaction_index BIGINT NOT NULL,
+    message_index BIGINT NOT NULL,
+    destination TEXT NOT NULL,
+    authenticated_signer TEXT,
+    grant_amount TEXT NOT NULL,
+    grant_attos NUMERIC(39,0) NOT NULL,
+    refund_grant_to TEXT,
+    kind TEXT NOT NULL,
+    -- `NULL` for system messages.
+    application_id TEXT,
+    -- JSON for system messages, the serialized bytes for user messages.
+    content TEXT NOT NULL,
+    PRIMARY KEY (chain_id, height, transaction_index, message_index),
+    FOREIGN KEY (chain_id, height) REFERENCES blocks (chain_id, height)
+);
+
+CREATE INDEX messages_destination ON messages (destination);
+
+CREATE TABLE events (
+    chain_id TEXT NOT NULL,
+    height BIGINT NOT NULL,
+    transaction_index BIGINT NOT NULL,
+    event_index BIGINT NOT NULL,
+    stream_application_id TEXT NOT NULL,
+    stream_name TEXT NOT NULL,
+    stream_index BIGINT NOT NULL,
+    value TEXT NOT NULL,
+    PRIMARY KEY (chain_id, height, transaction_index, event_index),
+    FOREIGN KEY (chain_id, height) REFERENCES blocks (chain_id, height)
+);
+
+CREATE INDEX events_stream ON events (stream_application_id, stream_name, stream_index);
+
+-- The changes of the native token balances caused by transfers, withdrawals and credits.
+-- Fees are not included.
+CREATE TABLE balance_changes (
+    chain_id TEXT NOT NULL,
+    height BIGINT NOT NULL,
+    change_index BIGINT NOT NULL,
+    owner TEXT NOT NULL,
+    -- Either `credit` or `debit`.
+    direction TEXT NOT NULL,
+    amount TEXT NOT NULL,
+    amount_attos NUMERIC(39,0) NOT NULL,
+    PRIMARY KEY (chain_id, height, change_index),
+    FOREIGN KEY (chain_id, height) REFERENCES blocks (chain_id, height)
+);
+
+CREATE INDEX balance_changes_owner ON balance_changes (chain_id, owner);
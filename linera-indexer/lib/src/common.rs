@@ -8,7 +8,11 @@ use axum::{
     http::Uri,
     response::{self, IntoResponse},
 };
-use linera_base::crypto::CryptoHash;
+use linera_base::{
+    crypto::CryptoHash,
+    data_types::{ArithmeticError, BlockHeight},
+    identifiers::ChainId,
+};
 use reqwest::header::InvalidHeaderValue;
 use thiserror::Error;
 
@@ -48,6 +52,15 @@ pub enum IndexerError {
     InvalidCertificateValue(CryptoHash),
     #[error("Clone with root key error")]
     CloneWithRootKeyError,
+    #[error(transparent)]
+    ArithmeticError(#[from] ArithmeticError),
+    #[error("The SQL plugin requires a database URL")]
+    MissingDatabaseUrl,
+    #[error("Block {height} of {chain_id:?} does not extend the last indexed block")]
+    ChainDivergence {
+        chain_id: ChainId,
+        height: BlockHeight,
+    },
 
     #[cfg(feature = "rocksdb")]
     #[error(transparent)]
@@ -55,6 +68,15 @@ pub enum IndexerError {
     #[cfg(feature = "scylladb")]
     #[error(transparent)]
     ScyllaDbError(#[from] Box<linera_views::scylla_db::ScyllaDbStoreError>),
+    #[cfg(feature = "sql")]
+    #[error(transparent)]
+    SqlError(#[from] sqlx::Error),
+    #[cfg(feature = "sql")]
+    #[error(transparent)]
+    MigrateError(#[from] sqlx::migrate::MigrateError),
+    #[cfg(feature = "sql")]
+    #[error(transparent)]
+    JsonError(#[from] serde_json::Error),
 }
 
 impl From<async_tungstenite::tungstenite::Error> for IndexerError {
@@ -3,11 +3,15 @@
 
 //! This module defines the base component of linera-indexer.
 
-use std::{collections::BTreeMap, sync::Arc};
+use std::{
+    collections::{BTreeMap, BTreeSet, VecDeque},
+    sync::Arc,
+};
 
 use async_graphql::{EmptyMutation, EmptySubscription, Schema, SimpleObject};
 use async_graphql_axum::{GraphQLRequest, GraphQLResponse};
 use axum::{extract::Extension, routing::get, Router};
+use futures::stream::{FuturesUnordered, StreamExt as _};
 use linera_base::{crypto::CryptoHash, data_types::BlockHeight, identifiers::ChainId};
 use linera_chain::types::{CertificateValue as _, ConfirmedBlock};
 use linera_views::{
@@ -20,19 +24,28 @@ use linera_views::{
 };
 use tokio::sync::Mutex;
 use tower_http::cors::CorsLayer;
-use tracing::info;
+use tracing::{info, warn};
 
 use crate::{
     common::{graphiql, IndexerError},
     plugin::Plugin,
-    service::Listener,
+    service::BlockSource,
 };
 
+/// The number of block hashes requested at once when catching up with a chain.
+const BACKFILL_PAGE_SIZE: u64 = 100;
+
 #[derive(RootView)]
 pub struct StateView<C> {
     chains: MapView<C, ChainId, (CryptoHash, BlockHeight)>,
     plugins: SetView<C, String>,
     initiated: RegisterView<C, bool>,
+    /// The highest block height registered by each plugin, for each chain.
+    plugin_checkpoints: MapView<C, (String, ChainId), BlockHeight>,
+    /// The highest block height seen on the node service, for each chain.
+    tips: MapView<C, ChainId, BlockHeight>,
+    /// The height at which the indexing of each chain started.
+    starts: MapView<C, ChainId, BlockHeight>,
 }
 
 #[derive(Clone)]
@@ -50,12 +63,6 @@ pub enum IndexerCommand {
     Schema,
 }
 
-#[derive(Debug)]
-enum LatestBlock {
-    LatestHash(CryptoHash),
-    StartHeight(BlockHeight),
-}
-
 impl<S> Indexer<S>
 where
     S: KeyValueStore + Clone + Send + Sync + 'static,
@@ -77,84 +84,261 @@ where
         })
     }
 
-    /// Processes one block: registers the block in all the plugins and saves the state of
-    /// the indexer.
+    /// Processes one block: registers the block in all the plugins that have not seen it
+    /// yet and saves the state of the indexer.
     pub async fn process_value(
         &self,
         state: &mut StateView<ViewContext<(), S>>,
         value: &ConfirmedBlock,
     ) -> Result<(), IndexerError> {
-        for plugin in self.plugins.values() {
-            plugin.register(value).await?
-        }
         let chain_id = value.chain_id();
         let hash = value.hash();
         let height = value.height();
-        info!("save {:?}: {:?} ({})", chain_id, hash, height);
-        state
-            .chains
-            .insert(&chain_id, (value.hash(), value.height()))?;
+        for (name, plugin) in &self.plugins {
+            let key = (name.clone(), chain_id);
+            let checkpoint = state.plugin_checkpoints.get(&key).await?;
+            if checkpoint.is_some_and(|checkpoint| checkpoint >= height) {
+                continue;
+            }
+            plugin.register(value).await?;
+            state.plugin_checkpoints.insert(&key, height)?;
+        }
+        let latest = state.chains.get(&chain_id).await?;
+        if latest.is_none_or(|(_, latest_height)| latest_height < height) {
+            info!("save {:?}: {:?} ({})", chain_id, hash, height);
+            state.chains.insert(&chain_id, (hash, height))?;
+        }
+        Self::record_tip(state, chain_id, height, None).await?;
         state.save().await.map_err(IndexerError::ViewError)
     }
 
-    /// Processes a `NewBlock` notification: processes all blocks from the latest
-    /// registered to the one in the notification in the corresponding chain.
+    /// Records that the node service knows about blocks up to `height` in a chain, and
+    /// the height at which the indexing of the chain starts, if known.
+    async fn record_tip(
+        state: &mut StateView<ViewContext<(), S>>,
+        chain_id: ChainId,
+        height: BlockHeight,
+        start: Option<BlockHeight>,
+    ) -> Result<(), IndexerError> {
+        let tip = state.tips.get(&chain_id).await?;
+        if tip.is_none_or(|tip| tip < height) {
+            state.tips.insert(&chain_id, height)?;
+        }
+        if let Some(start) = start {
+            state.starts.insert(&chain_id, start)?;
+        }
+        Ok(())
+    }
+
+    /// Returns the lowest block height that the indexer or one of its plugins still has to
+    /// process for a chain.
+    async fn next_height(
+        &self,
+        state: &StateView<ViewContext<(), S>>,
+        chain_id: ChainId,
+        start: BlockHeight,
+    ) -> Result<BlockHeight, IndexerError> {
+        let mut next_height = match state.chains.get(&chain_id).await? {
+            Some((_, height)) => height.try_add_one()?,
+            None => start,
+        };
+        for name in self.plugins.keys() {
+            let key = (name.clone(), chain_id);
+            let plugin_height = match state.plugin_checkpoints.get(&key).await? {
+                Some(height) => height.try_add_one()?,
+                None => start,
+            };
+            next_height = next_height.min(plugin_height);
+        }
+        Ok(next_height.max(start))
+    }
+
+    /// Checks that a block whose predecessor is `previous_hash` extends the block
+    /// registered by the indexer for this chain, if it directly follows it.
+    fn check_continuity(
+        latest: Option<(CryptoHash, BlockHeight)>,
+        chain_id: ChainId,
+        height: BlockHeight,
+        previous_hash: Option<CryptoHash>,
+    ) -> Result<(), IndexerError> {
+        match latest {
+            Some((latest_hash, latest_height))
+                if latest_height.try_add_one()? == height && previous_hash != Some(latest_hash) =>
+            {
+                Err(IndexerError::ChainDivergence { chain_id, height })
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Processes a `NewBlock` notification: processes all blocks from the lowest one
+    /// still missing in the indexer or one of its plugins to the one in the notification
+    /// in the corresponding chain.
     pub async fn process(
         &self,
-        listener: &Listener,
+        source: &impl BlockSource,
         value: &ConfirmedBlock,
     ) -> Result<(), IndexerError> {
         let chain_id = value.chain_id();
         let hash = value.hash();
         let height = value.height();
         let state = &mut self.state.0.lock().await;
-        if height < listener.start {
+        let next_height = self.next_height(state, chain_id, source.start()).await?;
+        if height < next_height {
             return Ok(());
         };
-        let latest_block = match state.chains.get(&chain_id).await? {
-            None => LatestBlock::StartHeight(listener.start),
-            Some((last_hash, last_height)) => {
-                if last_hash == hash || last_height >= height {
-                    return Ok(());
-                }
-                LatestBlock::LatestHash(last_hash)
-            }
-        };
+        let latest = state.chains.get(&chain_id).await?;
         info!("process {:?}: {:?} ({})", chain_id, hash, height);
 
         let mut values = Vec::new();
         let mut value = value.clone();
         loop {
             let header = &value.block().header;
+            Self::check_continuity(latest, chain_id, header.height, header.previous_block_hash)?;
             values.push(value.clone());
-            if let Some(hash) = header.previous_block_hash {
-                match latest_block {
-                    LatestBlock::LatestHash(latest_hash) if latest_hash != hash => {
-                        value = listener.service.get_value(chain_id, Some(hash)).await?;
-                        continue;
-                    }
-                    LatestBlock::StartHeight(start) if header.height > start => {
-                        value = listener.service.get_value(chain_id, Some(hash)).await?;
-                        continue;
-                    }
-                    _ => break,
+            match header.previous_block_hash {
+                Some(hash) if header.height > next_height => {
+                    value = source.get_value(chain_id, Some(hash)).await?;
                 }
+                _ => break,
             }
-            break;
         }
 
+        state.starts.insert(&chain_id, source.start())?;
         while let Some(value) = values.pop() {
             self.process_value(state, &value).await?
         }
         Ok(())
     }
 
-    pub async fn init(&self, listener: &Listener, chain_id: ChainId) -> Result<(), IndexerError> {
-        match listener.service.get_value(chain_id, None).await {
-            Ok(value) => self.process(listener, &value).await,
-            Err(IndexerError::NotFound(_)) => Ok(()),
-            Err(e) => Err(e),
+    /// Catches up with the history of one chain, from the lowest block still missing in
+    /// the indexer or one of its plugins to the latest block known by the node service.
+    ///
+    /// Unlike [`Indexer::process`], the state is only locked while processing each block,
+    /// and the blocks are fetched once each, in increasing height order, using the hashes of
+    /// the confirmed log of the chain. Returns the other chains that the processed blocks
+    /// exchanged messages with.
+    pub async fn backfill_chain(
+        &self,
+        source: &impl BlockSource,
+        chain_id: ChainId,
+    ) -> Result<BTreeSet<ChainId>, IndexerError> {
+        let tip = match source.get_value(chain_id, None).await {
+            Ok(value) => value,
+            Err(IndexerError::NotFound(_)) => return Ok(BTreeSet::new()),
+            Err(e) => return Err(e),
+        };
+        let (mut next_height, mut latest) = {
+            let mut state = self.state.0.lock().await;
+            Self::record_tip(&mut state, chain_id, tip.height(), Some(source.start())).await?;
+            state.save().await?;
+            (
+                self.next_height(&state, chain_id, source.start()).await?,
+                state.chains.get(&chain_id).await?,
+            )
+        };
+        if tip.height() < next_height {
+            return Ok(BTreeSet::new());
+        }
+        info!(
+            "backfill {:?} from {} to {}",
+            chain_id,
+            next_height,
+            tip.height()
+        );
+
+        let end = tip.height().try_add_one()?;
+        let mut neighbors = BTreeSet::new();
+        while next_height < end {
+            let page_end = BlockHeight(next_height.0.saturating_add(BACKFILL_PAGE_SIZE)).min(end);
+            let hashes = source.get_hashes(chain_id, next_height, page_end).await?;
+            if hashes.is_empty() {
+                return Err(IndexerError::NotFound(None));
+            }
+            for hash in hashes {
+                let value = if hash == tip.hash() {
+                    tip.clone()
+                } else {
+                    source.get_value(chain_id, Some(hash)).await?
+                };
+                let header = &value.block().header;
+                if header.height != next_height {
+                    return Err(IndexerError::NotFound(Some(hash)));
+                }
+                Self::check_continuity(
+                    latest,
+                    chain_id,
+                    header.height,
+                    header.previous_block_hash,
+                )?;
+                let body = &value.block().body;
+                neighbors.extend(body.incoming_bundles.iter().map(|bundle| bundle.origin));
+                neighbors.extend(
+                    body.messages
+                        .iter()
+                        .flatten()
+                        .map(|message| message.destination),
+                );
+                let mut state = self.state.0.lock().await;
+                self.process_value(&mut state, &value).await?;
+                latest = state.chains.get(&chain_id).await?;
+                next_height.try_add_assign_one()?;
+                if next_height == end {
+                    break;
+                }
+            }
+        }
+        neighbors.remove(&chain_id);
+        Ok(neighbors)
+    }
+
+    /// Catches up with the history of many chains, processing at most `concurrency` chains
+    /// at the same time. If `discover` is set, the chains that exchanged messages with the
+    /// processed ones are backfilled as well.
+    pub async fn backfill(
+        &self,
+        source: &impl BlockSource,
+        chains: impl IntoIterator<Item = ChainId>,
+        concurrency: usize,
+        discover: bool,
+    ) -> Result<(), IndexerError> {
+        let mut pending = chains.into_iter().collect::<VecDeque<_>>();
+        let mut seen = pending.iter().copied().collect::<BTreeSet<_>>();
+        let mut tasks = FuturesUnordered::new();
+        loop {
+            while tasks.len() < concurrency.max(1) {
+                let Some(chain_id) = pending.pop_front() else {
+                    break;
+                };
+                tasks.push(self.backfill_chain(source, chain_id));
+            }
+            let Some(result) = tasks.next().await else {
+                break;
+            };
+            let neighbors = result?;
+            if discover {
+                for chain_id in neighbors {
+                    if seen.insert(chain_id) {
+                        pending.push_back(chain_id);
+                    }
+                }
+            }
+        }
+        let lags = self.state.clone().lag().await?;
+        for lag in lags.iter().filter(|lag| lag.lag > 0) {
+            warn!("{:?} is lagging {} blocks behind", lag.chain, lag.lag);
         }
+        info!("backfilled {} chains", seen.len());
+        Ok(())
+    }
+
+    pub async fn init(
+        &self,
+        source: &impl BlockSource,
+        chain_id: ChainId,
+    ) -> Result<(), IndexerError> {
+        self.backfill_chain(source, chain_id).await?;
+        Ok(())
     }
 
     /// Produces the GraphQL schema for the indexer or for a certain plugin
@@ -202,6 +386,39 @@ pub struct HighestBlock {
     height: Option<BlockHeight>,
 }
 
+#[derive(SimpleObject)]
+pub struct PluginCheckpoint {
+    pub plugin: String,
+    pub height: Option<BlockHeight>,
+}
+
+#[derive(SimpleObject)]
+pub struct ChainLag {
+    pub chain: ChainId,
+    /// The highest block height seen on the node service
+    pub tip: Option<BlockHeight>,
+    /// The highest block height registered by each plugin
+    pub plugins: Vec<PluginCheckpoint>,
+    /// The number of blocks that the indexer or some plugin still has to register
+    pub lag: u64,
+}
+
+/// Returns the number of blocks from `start` to `tip` that are above `processed`.
+fn missing_blocks(
+    tip: Option<BlockHeight>,
+    processed: Option<BlockHeight>,
+    start: BlockHeight,
+) -> u64 {
+    let Some(tip) = tip else {
+        return 0;
+    };
+    let next = match processed {
+        Some(height) => height.0.saturating_add(1).max(start.0),
+        None => start.0,
+    };
+    tip.0.saturating_add(1).saturating_sub(next)
+}
+
 #[async_graphql::Object(cache_control(no_cache))]
 impl<C> State<C>
 where
@@ -228,6 +445,42 @@ where
         }
         Ok(result)
     }
+
+    /// Gets how far behind the node service each plugin is, for each chain
+    pub async fn lag(&self) -> Result<Vec<ChainLag>, IndexerError> {
+        let state = self.0.lock().await;
+        let plugins = state.plugins.indices().await?;
+        let mut chains = state.chains.indices().await?;
+        chains.extend(state.tips.indices().await?);
+        chains.sort();
+        chains.dedup();
+        let mut result = Vec::new();
+        for chain in chains {
+            let tip = state.tips.get(&chain).await?;
+            let start = state.starts.get(&chain).await?.unwrap_or_default();
+            let processed = state.chains.get(&chain).await?.map(|(_, height)| height);
+            let mut checkpoints = Vec::new();
+            let mut lag = missing_blocks(tip, processed, start);
+            for plugin in &plugins {
+                let height = state
+                    .plugin_checkpoints
+                    .get(&(plugin.clone(), chain))
+                    .await?;
+                lag = lag.max(missing_blocks(tip, height, start));
+                checkpoints.push(PluginCheckpoint {
+                    plugin: plugin.clone(),
+                    height,
+                });
+            }
+            result.push(ChainLag {
+                chain,
+                tip,
+                plugins: checkpoints,
+                lag,
+            });
+        }
+        Ok(result)
+    }
 }
 
 impl<C> State<C>
@@ -6,6 +6,7 @@
 //! - the block processing (indexer.rs)
 //! - the generic plugin trait (plugin.rs)
 //! - the runner struct (runner.rs)
+//! - an optional plugin writing blocks to a SQL database (sql.rs)
 
 pub mod common;
 pub mod indexer;
@@ -17,3 +18,5 @@ pub mod service;
 pub mod rocks_db;
 #[cfg(feature = "scylladb")]
 pub mod scylla_db;
+#[cfg(feature = "sql")]
+pub mod sql;
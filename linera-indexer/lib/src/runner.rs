@@ -3,6 +3,8 @@
 
 //! This module defines the trait for indexer runners.
 
+use std::path::PathBuf;
+
 use linera_base::identifiers::ChainId;
 use linera_views::store::KeyValueStore;
 use tokio::select;
@@ -22,6 +24,21 @@ pub enum IndexerCommand {
         /// The port of the indexer server
         #[arg(long, default_value = "8081")]
         port: u16,
+        /// The maximal number of chains to catch up with at the same time
+        #[arg(long, default_value = "10")]
+        concurrency: usize,
+        /// Chains to index (default: the ones on the service wallet)
+        chains: Vec<ChainId>,
+    },
+    Backfill {
+        #[command(flatten)]
+        listener: Listener,
+        /// The maximal number of chains to catch up with at the same time
+        #[arg(long, default_value = "10")]
+        concurrency: usize,
+        /// Also backfill the chains that exchanged messages with the indexed ones
+        #[arg(long)]
+        discover: bool,
         /// Chains to index (default: the ones on the service wallet)
         chains: Vec<ChainId>,
     },
@@ -31,6 +48,12 @@ pub enum IndexerCommand {
 pub struct IndexerConfig<Config: clap::Args> {
     #[command(flatten)]
     pub client: Config,
+    #[cfg(feature = "sql")]
+    #[command(flatten)]
+    pub sql: crate::sql::SqlConfig,
+    /// The JSON files describing the ABIs of the applications whose entries are decoded
+    #[arg(long = "abi", env = "LINERA_INDEXER_ABIS", value_delimiter = ',')]
+    pub abis: Vec<PathBuf>,
     #[command(subcommand)]
     pub command: IndexerCommand,
 }
@@ -91,10 +114,27 @@ where
                 println!("{}", self.indexer.sdl(plugin)?);
                 Ok(())
             }
+            IndexerCommand::Backfill {
+                chains,
+                listener,
+                concurrency,
+                discover,
+            } => {
+                info!("config: {:?}", config);
+                let chains = if chains.is_empty() {
+                    listener.service.get_chains().await?
+                } else {
+                    chains
+                };
+                self.indexer
+                    .backfill(&listener, chains, concurrency, discover)
+                    .await
+            }
             IndexerCommand::Run {
                 chains,
                 listener,
                 port,
+                concurrency,
             } => {
                 info!("config: {:?}", config);
                 let chains = if chains.is_empty() {
@@ -102,10 +142,9 @@ where
                 } else {
                     chains
                 };
-                let initialize_chains = chains
-                    .iter()
-                    .map(|chain_id| self.indexer.init(&listener, *chain_id));
-                futures::future::try_join_all(initialize_chains).await?;
+                self.indexer
+                    .backfill(&listener, chains.iter().copied(), concurrency, false)
+                    .await?;
                 let connections = {
                     chains
                         .into_iter()
@@ -14,11 +14,16 @@ use futures::{
 use graphql_client::reqwest::post_graphql;
 use graphql_ws_client::{graphql::StreamingOperation, GraphQLClientClientBuilder};
 use linera_base::{
-    crypto::CryptoHash, data_types::BlockHeight, identifiers::ChainId, time::Duration,
+    crypto::CryptoHash,
+    data_types::{ArithmeticError, BlockHeight},
+    identifiers::ChainId,
+    time::Duration,
 };
 use linera_chain::types::ConfirmedBlock;
 use linera_core::worker::Reason;
-use linera_service_graphql_client::{block, chains, notifications, Block, Chains, Notifications};
+use linera_service_graphql_client::{
+    block, chains, confirmed_log, notifications, Block, Chains, ConfirmedLog, Notifications,
+};
 use linera_views::store::KeyValueStore;
 use tokio::runtime::Handle;
 use tracing::error;
@@ -99,6 +104,28 @@ impl Service {
             .map_err(IndexerError::ConversionError)
     }
 
+    /// Gets the hashes of the blocks of a chain from height `start` to `end` (excluded)
+    pub async fn get_hashes(
+        &self,
+        chain_id: ChainId,
+        start: BlockHeight,
+        end: BlockHeight,
+    ) -> Result<Vec<CryptoHash>, IndexerError> {
+        let client = reqwest_client();
+        let variables = confirmed_log::Variables {
+            chain_id,
+            start: Some(i64::try_from(start.0).map_err(|_| ArithmeticError::Overflow)?),
+            end: Some(i64::try_from(end.0).map_err(|_| ArithmeticError::Overflow)?),
+        };
+        let response = post_graphql::<ConfirmedLog, _>(&client, &self.http(), variables).await?;
+        Ok(response
+            .data
+            .ok_or_else(|| IndexerError::NullData(response.errors))?
+            .chain
+            .confirmed_log
+            .entries)
+    }
+
     /// Gets chains
     pub async fn get_chains(&self) -> Result<Vec<ChainId>, IndexerError> {
         let client = reqwest_client();
@@ -112,6 +139,28 @@ impl Service {
     }
 }
 
+/// Where the indexer gets the confirmed blocks from.
+#[async_trait::async_trait]
+pub trait BlockSource: Send + Sync {
+    /// The height at which the indexer should start
+    fn start(&self) -> BlockHeight;
+
+    /// Gets one block of a chain, or the latest one if no hash is given
+    async fn get_value(
+        &self,
+        chain_id: ChainId,
+        hash: Option<CryptoHash>,
+    ) -> Result<ConfirmedBlock, IndexerError>;
+
+    /// Gets the hashes of the blocks of a chain from height `start` to `end` (excluded)
+    async fn get_hashes(
+        &self,
+        chain_id: ChainId,
+        start: BlockHeight,
+        end: BlockHeight,
+    ) -> Result<Vec<CryptoHash>, IndexerError>;
+}
+
 #[derive(clap::Parser, Debug, Clone)]
 pub struct Listener {
     #[command(flatten)]
@@ -121,6 +170,30 @@ pub struct Listener {
     pub start: BlockHeight,
 }
 
+#[async_trait::async_trait]
+impl BlockSource for Listener {
+    fn start(&self) -> BlockHeight {
+        self.start
+    }
+
+    async fn get_value(
+        &self,
+        chain_id: ChainId,
+        hash: Option<CryptoHash>,
+    ) -> Result<ConfirmedBlock, IndexerError> {
+        self.service.get_value(chain_id, hash).await
+    }
+
+    async fn get_hashes(
+        &self,
+        chain_id: ChainId,
+        start: BlockHeight,
+        end: BlockHeight,
+    ) -> Result<Vec<CryptoHash>, IndexerError> {
+        self.service.get_hashes(chain_id, start, end).await
+    }
+}
+
 impl Listener {
     /// Connects to the WebSocket of the service node for a particular chain
     pub async fn listen<S>(
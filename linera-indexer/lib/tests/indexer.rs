@@ -0,0 +1,250 @@
+// Copyright (c) Zefchain Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use std::{
+    collections::BTreeMap,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+};
+
+use axum::Router;
+use linera_base::{
+    crypto::CryptoHash,
+    data_types::{Amount, BlockHeight},
+    identifiers::ChainId,
+};
+use linera_chain::{
+    data_types::BlockExecutionOutcome,
+    test::{make_child_block, make_first_block, BlockTestExt},
+    types::{CertificateValue as _, ConfirmedBlock},
+};
+use linera_indexer::{
+    common::IndexerError, indexer::Indexer, plugin::Plugin, service::BlockSource,
+};
+use linera_views::memory::MemoryStore;
+
+/// A node service serving a fixed set of chains.
+#[derive(Default)]
+struct TestSource {
+    start: BlockHeight,
+    chains: BTreeMap<ChainId, Vec<ConfirmedBlock>>,
+    hide_confirmed_log: bool,
+    fetched_blocks: AtomicUsize,
+}
+
+#[async_trait::async_trait]
+impl BlockSource for TestSource {
+    fn start(&self) -> BlockHeight {
+        self.start
+    }
+
+    async fn get_value(
+        &self,
+        chain_id: ChainId,
+        hash: Option<CryptoHash>,
+    ) -> Result<ConfirmedBlock, IndexerError> {
+        self.fetched_blocks.fetch_add(1, Ordering::Relaxed);
+        let blocks = self
+            .chains
+            .get(&chain_id)
+            .ok_or(IndexerError::NotFound(hash))?;
+        let block = match hash {
+            None => blocks.last(),
+            Some(hash) => blocks.iter().find(|block| block.hash() == hash),
+        };
+        block.cloned().ok_or(IndexerError::NotFound(hash))
+    }
+
+    async fn get_hashes(
+        &self,
+        chain_id: ChainId,
+        start: BlockHeight,
+        end: BlockHeight,
+    ) -> Result<Vec<CryptoHash>, IndexerError> {
+        if self.hide_confirmed_log {
+            return Ok(Vec::new());
+        }
+        let blocks = &self.chains[&chain_id];
+        let end = (end.0 as usize).min(blocks.len());
+        let start = (start.0 as usize).min(end);
+        Ok(blocks[start..end]
+            .iter()
+            .map(|block| block.hash())
+            .collect())
+    }
+}
+
+/// A plugin recording the heights of the blocks it registers.
+#[derive(Clone)]
+struct RecordingPlugin {
+    name: &'static str,
+    heights: Arc<Mutex<Vec<BlockHeight>>>,
+}
+
+impl RecordingPlugin {
+    fn new(name: &'static str) -> Self {
+        RecordingPlugin {
+            name,
+            heights: Arc::default(),
+        }
+    }
+
+    fn heights(&self) -> Vec<u64> {
+        let heights = self.heights.lock().unwrap();
+        heights.iter().map(|height| height.0).collect()
+    }
+}
+
+#[async_trait::async_trait]
+impl Plugin<MemoryStore> for RecordingPlugin {
+    fn name(&self) -> String {
+        self.name.to_string()
+    }
+
+    async fn load(_store: MemoryStore) -> Result<Self, IndexerError> {
+        Ok(RecordingPlugin::new("recording"))
+    }
+
+    async fn register(&self, value: &ConfirmedBlock) -> Result<(), IndexerError> {
+        self.heights.lock().unwrap().push(value.height());
+        Ok(())
+    }
+
+    fn sdl(&self) -> String {
+        String::new()
+    }
+
+    fn route(&self, app: Router) -> Router {
+        app
+    }
+}
+
+fn make_chain(chain_id: ChainId, length: usize) -> Vec<ConfirmedBlock> {
+    let recipient = ChainId(CryptoHash::test_hash("recipient"));
+    let block = make_first_block(chain_id).with_simple_transfer(recipient, Amount::ONE);
+    let mut blocks = vec![ConfirmedBlock::new(
+        BlockExecutionOutcome::default().with(block),
+    )];
+    while blocks.len() < length {
+        let block = make_child_block(blocks.last().unwrap()).with_burn(Amount::ONE);
+        blocks.push(ConfirmedBlock::new(
+            BlockExecutionOutcome::default().with(block),
+        ));
+    }
+    blocks
+}
+
+async fn make_indexer(namespace: &str) -> Indexer<MemoryStore> {
+    let store = MemoryStore::new(10, namespace).unwrap();
+    Indexer::load(store).await.unwrap()
+}
+
+#[tokio::test]
+async fn test_backfill_fetches_each_block_once() {
+    let chain_id = ChainId(CryptoHash::test_hash("chain"));
+    let mut source = TestSource::default();
+    source.chains.insert(chain_id, make_chain(chain_id, 5));
+    let mut indexer = make_indexer("indexer_fetch_once").await;
+    let plugin = RecordingPlugin::new("plugin");
+    indexer.add_plugin(plugin.clone()).await.unwrap();
+
+    indexer
+        .backfill(&source, [chain_id], 1, false)
+        .await
+        .unwrap();
+    assert_eq!(plugin.heights(), [0, 1, 2, 3, 4]);
+    assert_eq!(source.fetched_blocks.load(Ordering::Relaxed), 5);
+    let lags = indexer.state.lag().await.unwrap();
+    assert_eq!(lags.len(), 1);
+    assert_eq!(lags[0].tip, Some(BlockHeight(4)));
+    assert_eq!(lags[0].lag, 0);
+}
+
+#[tokio::test]
+async fn test_backfill_resumes_from_plugin_checkpoints() {
+    let chain_id = ChainId(CryptoHash::test_hash("chain"));
+    let blocks = make_chain(chain_id, 5);
+    let mut source = TestSource::default();
+    source.chains.insert(chain_id, blocks[..3].to_vec());
+    let mut indexer = make_indexer("indexer_resume").await;
+    let old_plugin = RecordingPlugin::new("old");
+    indexer.add_plugin(old_plugin.clone()).await.unwrap();
+    indexer
+        .backfill(&source, [chain_id], 1, false)
+        .await
+        .unwrap();
+    assert_eq!(old_plugin.heights(), [0, 1, 2]);
+
+    // The chain grows and a new plugin is added: the new plugin starts from the beginning
+    // and the old one only registers the new blocks.
+    source.chains.insert(chain_id, blocks);
+    let new_plugin = RecordingPlugin::new("new");
+    indexer.add_plugin(new_plugin.clone()).await.unwrap();
+    let lags = indexer.state.lag().await.unwrap();
+    assert_eq!(lags[0].lag, 3);
+    indexer
+        .backfill(&source, [chain_id], 1, false)
+        .await
+        .unwrap();
+    assert_eq!(old_plugin.heights(), [0, 1, 2, 3, 4]);
+    assert_eq!(new_plugin.heights(), [0, 1, 2, 3, 4]);
+    let lags = indexer.state.lag().await.unwrap();
+    assert_eq!(lags[0].lag, 0);
+}
+
+#[tokio::test]
+async fn test_backfill_rejects_diverging_chain() {
+    let chain_id = ChainId(CryptoHash::test_hash("chain"));
+    let blocks = make_chain(chain_id, 3);
+    let mut source = TestSource::default();
+    source.chains.insert(chain_id, blocks.clone());
+    let indexer = make_indexer("indexer_diverging").await;
+    indexer
+        .backfill(&source, [chain_id], 1, false)
+        .await
+        .unwrap();
+
+    // The node service now serves a different block at height 2, and a child of it.
+    let other_block = make_child_block(&blocks[1]).with_simple_transfer(chain_id, Amount::ONE);
+    let other_block = ConfirmedBlock::new(BlockExecutionOutcome::default().with(other_block));
+    let child = make_child_block(&other_block).with_burn(Amount::ONE);
+    let child = ConfirmedBlock::new(BlockExecutionOutcome::default().with(child));
+    source.chains.insert(
+        chain_id,
+        vec![blocks[0].clone(), blocks[1].clone(), other_block, child],
+    );
+    let result = indexer.backfill(&source, [chain_id], 1, false).await;
+    assert!(matches!(
+        result,
+        Err(IndexerError::ChainDivergence { height, .. }) if height == BlockHeight(3)
+    ));
+}
+
+#[tokio::test]
+async fn test_lag_counts_unprocessed_chains_from_start() {
+    let chain_id = ChainId(CryptoHash::test_hash("chain"));
+    let mut source = TestSource {
+        start: BlockHeight(2),
+        hide_confirmed_log: true,
+        ..TestSource::default()
+    };
+    source.chains.insert(chain_id, make_chain(chain_id, 5));
+    let mut indexer = make_indexer("indexer_lag").await;
+    let plugin = RecordingPlugin::new("plugin");
+    indexer.add_plugin(plugin.clone()).await.unwrap();
+
+    // The tip is recorded, but no block can be fetched.
+    assert!(indexer.backfill_chain(&source, chain_id).await.is_err());
+    let lags = indexer.state.lag().await.unwrap();
+    assert_eq!(lags.len(), 1);
+    assert_eq!(lags[0].tip, Some(BlockHeight(4)));
+    assert_eq!(lags[0].lag, 3);
+
+    source.hide_confirmed_log = false;
+    indexer.backfill_chain(&source, chain_id).await.unwrap();
+    assert_eq!(plugin.heights(), [2, 3, 4]);
+    let lags = indexer.state.lag().await.unwrap();
+    assert_eq!(lags[0].lag, 0);
+}
@@ -0,0 +1,34 @@
+// Copyright (c) Zefchain Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+#![cfg(feature = "sql")]
+
+use linera_base::{crypto::CryptoHash, data_types::Amount, identifiers::ChainId};
+use linera_chain::{
+    data_types::BlockExecutionOutcome,
+    test::{make_child_block, make_first_block, BlockTestExt},
+    types::ConfirmedBlock,
+};
+use linera_indexer::sql::SqlPlugin;
+
+#[tokio::test]
+async fn test_sql_plugin_writes_blocks_once() {
+    let chain_id = ChainId(CryptoHash::test_hash("chain"));
+    let recipient = ChainId(CryptoHash::test_hash("recipient"));
+    // A single connection, since each connection to `sqlite::memory:` opens a new database.
+    let plugin = SqlPlugin::connect("sqlite::memory:", 1).await.unwrap();
+
+    let block = make_first_block(chain_id).with_simple_transfer(recipient, Amount::ONE);
+    let value = ConfirmedBlock::new(BlockExecutionOutcome::default().with(block));
+    plugin.write_block(&value).await.unwrap();
+    plugin.write_block(&value).await.unwrap();
+    let block = make_child_block(&value).with_burn(Amount::ONE);
+    let value = ConfirmedBlock::new(BlockExecutionOutcome::default().with(block));
+    plugin.write_block(&value).await.unwrap();
+
+    assert_eq!(plugin.blocks(chain_id).await.unwrap(), 2);
+    assert_eq!(plugin.operations(chain_id).await.unwrap(), 2);
+    assert_eq!(plugin.balance_changes(chain_id).await.unwrap(), 2);
+    assert_eq!(plugin.messages(chain_id).await.unwrap(), 0);
+    assert_eq!(plugin.blocks(recipient).await.unwrap(), 0);
+}
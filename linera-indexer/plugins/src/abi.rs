@@ -0,0 +1,453 @@
+// Copyright (c) Zefchain Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Descriptions of application types and a BCS decoder driven by them.
+//!
+//! The formats are those of `serde-reflection`, so that the registry traced from an
+//! application's `Operation`, `Message` and event types can be written to JSON and loaded
+//! by the indexer without depending on the application crate.
+
+use std::path::Path;
+
+use async_graphql::Enum;
+use linera_base::identifiers::ApplicationId;
+use linera_indexer::common::IndexerError;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use serde_reflection::{ContainerFormat, Format, Named, Registry, VariantFormat};
+use thiserror::Error;
+
+/// The maximal nesting of containers accepted by the decoder, as in BCS.
+const MAX_CONTAINER_DEPTH: usize = 500;
+
+/// The kind of user data described by an ABI.
+#[derive(
+    Deserialize, Serialize, Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Enum,
+)]
+pub enum EntryKind {
+    Operation,
+    Message,
+    Event,
+}
+
+/// The description of the types used by an application.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ApplicationAbi {
+    /// The application whose operations, messages and events are decoded.
+    pub application_id: ApplicationId,
+    /// The formats of the containers used by the application, by name.
+    pub formats: Registry,
+    /// The name of the operation type, if operations should be decoded.
+    #[serde(default)]
+    pub operation: Option<String>,
+    /// The name of the message type, if messages should be decoded.
+    #[serde(default)]
+    pub message: Option<String>,
+    /// The name of the event type, if events should be decoded.
+    #[serde(default)]
+    pub event: Option<String>,
+}
+
+impl ApplicationAbi {
+    /// Reads the description of an application from a JSON file.
+    pub fn read(path: &Path) -> Result<Self, IndexerError> {
+        let file = std::fs::File::open(path)?;
+        serde_json::from_reader(std::io::BufReader::new(file))
+            .map_err(|error| std::io::Error::from(error).into())
+    }
+
+    /// Returns the name of the type used for entries of the given kind, if any.
+    pub fn type_name(&self, kind: EntryKind) -> Option<&str> {
+        match kind {
+            EntryKind::Operation => self.operation.as_deref(),
+            EntryKind::Message => self.message.as_deref(),
+            EntryKind::Event => self.event.as_deref(),
+        }
+    }
+
+    /// Returns the name of the variant of a decoded `value` of the container `type_name`,
+    /// if it is an enum.
+    pub fn variant<'v>(&self, type_name: &str, value: &'v Value) -> Option<&'v str> {
+        let Some(ContainerFormat::Enum(_)) = self.formats.get(type_name) else {
+            return None;
+        };
+        match value {
+            Value::String(name) => Some(name),
+            Value::Object(variant) => variant.keys().next().map(String::as_str),
+            _ => None,
+        }
+    }
+
+    /// Decodes the BCS `bytes` of a value of the container `type_name` into JSON.
+    pub fn decode(&self, type_name: &str, bytes: &[u8]) -> Result<Value, AbiError> {
+        let mut decoder = Decoder {
+            formats: &self.formats,
+            input: bytes,
+            depth: 0,
+        };
+        let value = decoder.container(type_name)?;
+        if !decoder.input.is_empty() {
+            return Err(AbiError::TrailingBytes(decoder.input.len()));
+        }
+        Ok(value)
+    }
+}
+
+/// Returns the scalar leaves of a decoded value, with their paths made of the field
+/// names, variant names and sequence indices leading to them, e.g. `Transfer.owner`. The
+/// path of a scalar value is empty.
+pub fn fields(value: &Value) -> Vec<(String, Value)> {
+    fn visit(path: String, value: &Value, fields: &mut Vec<(String, Value)>) {
+        let child = |name: &str| {
+            if path.is_empty() {
+                name.to_owned()
+            } else {
+                format!("{path}.{name}")
+            }
+        };
+        match value {
+            Value::Object(entries) => {
+                for (name, value) in entries {
+                    visit(child(name), value, fields);
+                }
+            }
+            Value::Array(values) => {
+                for (index, value) in values.iter().enumerate() {
+                    visit(child(&index.to_string()), value, fields);
+                }
+            }
+            value => fields.push((path, value.clone())),
+        }
+    }
+
+    let mut result = Vec::new();
+    visit(String::new(), value, &mut result);
+    result
+}
+
+#[derive(Error, Debug, PartialEq, Eq)]
+pub enum AbiError {
+    #[error("Unknown type: {0}")]
+    UnknownType(String),
+    #[error("Unknown variant {index} of {type_name}")]
+    UnknownVariant { type_name: String, index: u32 },
+    #[error("Unexpected end of input")]
+    UnexpectedEnd,
+    #[error("{0} trailing bytes after the value")]
+    TrailingBytes(usize),
+    #[error("Format {0} is not supported by BCS")]
+    UnsupportedFormat(&'static str),
+    #[error("Invalid boolean: {0}")]
+    InvalidBool(u8),
+    #[error("Invalid option tag: {0}")]
+    InvalidOptionTag(u8),
+    #[error("Invalid length")]
+    InvalidLength,
+    #[error("Invalid UTF-8 string")]
+    InvalidUtf8,
+    #[error("Exceeded the maximal container depth")]
+    ExceededContainerDepth,
+}
+
+struct Decoder<'a> {
+    formats: &'a Registry,
+    input: &'a [u8],
+    depth: usize,
+}
+
+macro_rules! read_number {
+    ($decoder:expr, $number:ty) => {{
+        let bytes = $decoder.take(std::mem::size_of::<$number>())?;
+        <$number>::from_le_bytes(bytes.try_into().expect("size was checked"))
+    }};
+}
+
+impl<'a> Decoder<'a> {
+    fn take(&mut self, length: usize) -> Result<&'a [u8], AbiError> {
+        if self.input.len() < length {
+            return Err(AbiError::UnexpectedEnd);
+        }
+        let (bytes, rest) = self.input.split_at(length);
+        self.input = rest;
+        Ok(bytes)
+    }
+
+    fn byte(&mut self) -> Result<u8, AbiError> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn uleb128(&mut self) -> Result<u32, AbiError> {
+        let mut value = 0u64;
+        for shift in (0..32).step_by(7) {
+            let byte = self.byte()?;
+            value |= u64::from(byte & 0x7f) << shift;
+            if byte & 0x80 == 0 {
+                return u32::try_from(value).map_err(|_| AbiError::InvalidLength);
+            }
+        }
+        Err(AbiError::InvalidLength)
+    }
+
+    fn length(&mut self) -> Result<usize, AbiError> {
+        let length = self.uleb128()?;
+        usize::try_from(length).map_err(|_| AbiError::InvalidLength)
+    }
+
+    fn container(&mut self, type_name: &str) -> Result<Value, AbiError> {
+        let format = self
+            .formats
+            .get(type_name)
+            .ok_or_else(|| AbiError::UnknownType(type_name.to_owned()))?;
+        self.depth += 1;
+        if self.depth > MAX_CONTAINER_DEPTH {
+            return Err(AbiError::ExceededContainerDepth);
+        }
+        let value = match format {
+            ContainerFormat::UnitStruct => Value::Null,
+            ContainerFormat::NewTypeStruct(format) => self.value(format)?,
+            ContainerFormat::TupleStruct(formats) => self.tuple(formats)?,
+            ContainerFormat::Struct(fields) => self.fields(fields)?,
+            ContainerFormat::Enum(variants) => {
+                let index = self.uleb128()?;
+                let Named { name, value } =
+                    variants
+                        .get(&index)
+                        .ok_or_else(|| AbiError::UnknownVariant {
+                            type_name: type_name.to_owned(),
+                            index,
+                        })?;
+                let value = match value {
+                    VariantFormat::Variable(_) => {
+                        return Err(AbiError::UnsupportedFormat("VARIABLE"))
+                    }
+                    VariantFormat::Unit => return self.leave(Value::String(name.clone())),
+                    VariantFormat::NewType(format) => self.value(format)?,
+                    VariantFormat::Tuple(formats) => self.tuple(formats)?,
+                    VariantFormat::Struct(fields) => self.fields(fields)?,
+                };
+                Value::Object([(name.clone(), value)].into_iter().collect())
+            }
+        };
+        self.leave(value)
+    }
+
+    fn leave(&mut self, value: Value) -> Result<Value, AbiError> {
+        self.depth -= 1;
+        Ok(value)
+    }
+
+    fn tuple(&mut self, formats: &[Format]) -> Result<Value, AbiError> {
+        let values = formats
+            .iter()
+            .map(|format| self.value(format))
+            .collect::<Result<_, _>>()?;
+        Ok(Value::Array(values))
+    }
+
+    fn fields(&mut self, fields: &[Named<Format>]) -> Result<Value, AbiError> {
+        let fields = fields
+            .iter()
+            .map(|field| Ok::<_, AbiError>((field.name.clone(), self.value(&field.value)?)))
+            .collect::<Result<_, _>>()?;
+        Ok(Value::Object(fields))
+    }
+
+    fn value(&mut self, format: &Format) -> Result<Value, AbiError> {
+        let value = match format {
+            Format::Variable(_) => return Err(AbiError::UnsupportedFormat("VARIABLE")),
+            Format::TypeName(name) => self.container(name)?,
+            Format::Unit => Value::Null,
+            Format::Bool => match self.byte()? {
+                0 => Value::Bool(false),
+                1 => Value::Bool(true),
+                byte => return Err(AbiError::InvalidBool(byte)),
+            },
+            Format::I8 => read_number!(self, i8).into(),
+            Format::I16 => read_number!(self, i16).into(),
+            Format::I32 => read_number!(self, i32).into(),
+            Format::I64 => read_number!(self, i64).into(),
+            Format::I128 => read_number!(self, i128).to_string().into(),
+            Format::U8 => read_number!(self, u8).into(),
+            Format::U16 => read_number!(self, u16).into(),
+            Format::U32 => read_number!(self, u32).into(),
+            Format::U64 => read_number!(self, u64).into(),
+            Format::U128 => read_number!(self, u128).to_string().into(),
+            Format::F32 => return Err(AbiError::UnsupportedFormat("F32")),
+            Format::F64 => return Err(AbiError::UnsupportedFormat("F64")),
+            Format::Char => return Err(AbiError::UnsupportedFormat("CHAR")),
+            Format::Str => {
+                let length = self.length()?;
+                let bytes = self.take(length)?;
+                let string = std::str::from_utf8(bytes).map_err(|_| AbiError::InvalidUtf8)?;
+                Value::String(string.to_owned())
+            }
+            Format::Bytes => {
+                let length = self.length()?;
+                Value::Array(
+                    self.take(length)?
+                        .iter()
+                        .map(|byte| (*byte).into())
+                        .collect(),
+                )
+            }
+            Format::Option(format) => match self.byte()? {
+                0 => Value::Null,
+                1 => self.value(format)?,
+                tag => return Err(AbiError::InvalidOptionTag(tag)),
+            },
+            Format::Seq(format) => {
+                let length = self.length()?;
+                let values = (0..length)
+                    .map(|_| self.value(format))
+                    .collect::<Result<_, _>>()?;
+                Value::Array(values)
+            }
+            Format::Map { key, value } => {
+                let length = self.length()?;
+                let mut entries = serde_json::Map::new();
+                for _ in 0..length {
+                    let key = match self.value(key)? {
+                        Value::String(key) => key,
+                        key => key.to_string(),
+                    };
+                    entries.insert(key, self.value(value)?);
+                }
+                Value::Object(entries)
+            }
+            Format::Tuple(formats) => self.tuple(formats)?,
+            Format::TupleArray { content, size } => {
+                let values = (0..*size)
+                    .map(|_| self.value(content))
+                    .collect::<Result<_, _>>()?;
+                Value::Array(values)
+            }
+        };
+        Ok(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use linera_base::crypto::CryptoHash;
+    use serde_json::json;
+    use serde_reflection::{Tracer, TracerConfig};
+
+    use super::*;
+
+    #[derive(Serialize, Deserialize)]
+    enum Operation {
+        Transfer {
+            owner: String,
+            amount: u128,
+            target: Option<(u32, u64)>,
+        },
+        Claim(Vec<u8>),
+        Burn,
+    }
+
+    fn abi() -> ApplicationAbi {
+        let formats = json!({
+            "Operation": {
+                "ENUM": {
+                    "0": {
+                        "Transfer": {
+                            "STRUCT": [
+                                { "owner": "STR" },
+                                { "amount": "U128" },
+                                { "target": { "OPTION": { "TUPLE": ["U32", "U64"] } } }
+                            ]
+                        }
+                    },
+                    "1": { "Claim": { "NEWTYPE": { "SEQ": "U8" } } },
+                    "2": { "Burn": "UNIT" }
+                }
+            }
+        });
+        ApplicationAbi {
+            application_id: ApplicationId::new(CryptoHash::from([0; 4])),
+            formats: serde_json::from_value(formats).unwrap(),
+            operation: Some("Operation".to_owned()),
+            message: None,
+            event: None,
+        }
+    }
+
+    #[test]
+    fn test_decode_operations() {
+        let abi = abi();
+        let operation = Operation::Transfer {
+            owner: "alice".to_owned(),
+            amount: 1_000,
+            target: Some((7, 42)),
+        };
+        let bytes = bcs::to_bytes(&operation).unwrap();
+        assert_eq!(
+            abi.decode("Operation", &bytes).unwrap(),
+            json!({ "Transfer": { "owner": "alice", "amount": "1000", "target": [7, 42] } })
+        );
+
+        let bytes = bcs::to_bytes(&Operation::Claim(vec![1, 2])).unwrap();
+        assert_eq!(
+            abi.decode("Operation", &bytes).unwrap(),
+            json!({ "Claim": [1, 2] })
+        );
+
+        let bytes = bcs::to_bytes(&Operation::Burn).unwrap();
+        assert_eq!(abi.decode("Operation", &bytes).unwrap(), json!("Burn"));
+    }
+
+    #[test]
+    fn test_traced_registry() {
+        let mut tracer = Tracer::new(TracerConfig::default());
+        tracer.trace_simple_type::<Operation>().unwrap();
+        assert_eq!(tracer.registry().unwrap(), abi().formats);
+    }
+
+    #[test]
+    fn test_variants_and_fields() {
+        let abi = abi();
+        let transfer =
+            json!({ "Transfer": { "owner": "alice", "amount": "1000", "target": [7, 42] } });
+        assert_eq!(abi.variant("Operation", &transfer), Some("Transfer"));
+        assert_eq!(abi.variant("Operation", &json!("Burn")), Some("Burn"));
+        assert_eq!(abi.variant("Message", &transfer), None);
+        let mut transfer_fields = fields(&transfer);
+        transfer_fields.sort_by(|(path1, _), (path2, _)| path1.cmp(path2));
+        assert_eq!(
+            transfer_fields,
+            vec![
+                ("Transfer.amount".to_owned(), json!("1000")),
+                ("Transfer.owner".to_owned(), json!("alice")),
+                ("Transfer.target.0".to_owned(), json!(7)),
+                ("Transfer.target.1".to_owned(), json!(42)),
+            ]
+        );
+        assert_eq!(fields(&json!("Burn")), vec![(String::new(), json!("Burn"))]);
+    }
+
+    #[test]
+    fn test_decode_errors() {
+        let abi = abi();
+        let mut bytes = bcs::to_bytes(&Operation::Burn).unwrap();
+        bytes.push(0);
+        assert_eq!(
+            abi.decode("Operation", &bytes),
+            Err(AbiError::TrailingBytes(1))
+        );
+        assert_eq!(
+            abi.decode("Operation", &[1, 3]),
+            Err(AbiError::UnexpectedEnd)
+        );
+        assert_eq!(
+            abi.decode("Operation", &[3]),
+            Err(AbiError::UnknownVariant {
+                type_name: "Operation".to_owned(),
+                index: 3
+            })
+        );
+        assert_eq!(
+            abi.decode("Message", &[]),
+            Err(AbiError::UnknownType("Message".to_owned()))
+        );
+    }
+}
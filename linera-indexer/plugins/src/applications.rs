@@ -0,0 +1,531 @@
+// Copyright (c) Zefchain Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use std::{collections::BTreeMap, sync::Arc};
+
+use async_graphql::{ComplexObject, InputObject, Json, SimpleObject};
+use axum::Router;
+use linera_base::{
+    crypto::CryptoHash,
+    data_types::BlockHeight,
+    identifiers::{ApplicationId, ChainId, GenericApplicationId, StreamName},
+};
+use linera_chain::types::{CertificateValue as _, ConfirmedBlock};
+use linera_execution::{Message, Operation};
+use linera_indexer::{
+    common::IndexerError,
+    plugin::{load, route, sdl, Plugin},
+};
+use linera_views::{
+    context::{Context, ViewContext},
+    map_view::MapView,
+    store::KeyValueStore,
+    views::RootView,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tokio::sync::Mutex;
+use tracing::{info, warn};
+
+use crate::abi::{self, ApplicationAbi, EntryKind};
+
+/// The maximal number of entries examined by a query before it returns a cursor to
+/// continue from.
+const MAX_SCANNED_ENTRIES: u64 = 10_000;
+
+#[derive(Deserialize, Serialize, Clone, SimpleObject, Debug)]
+#[graphql(complex)]
+pub struct ApplicationEntry {
+    application_id: ApplicationId,
+    kind: EntryKind,
+    /// The index of the entry among the ones of the same kind for this application
+    index: u64,
+    chain_id: ChainId,
+    height: BlockHeight,
+    block: CryptoHash,
+    /// The stream of the entry, for events
+    stream_name: Option<StreamName>,
+    /// The type used to decode the entry, if any
+    type_name: Option<String>,
+    /// The variant of the decoded entry, if its type is an enum
+    variant: Option<String>,
+    /// The serialized content of the entry
+    bytes: Vec<u8>,
+    /// The error encountered while decoding the entry, if any
+    error: Option<String>,
+    #[graphql(skip)]
+    json: Option<String>,
+}
+
+impl ApplicationEntry {
+    fn decoded(&self) -> Option<Value> {
+        serde_json::from_str(self.json.as_ref()?).ok()
+    }
+
+    /// Returns whether the entry has the given variant, if any, and all the given field
+    /// values.
+    fn matches(&self, variant: Option<&str>, filters: &[FieldFilter]) -> bool {
+        if variant.is_some_and(|variant| self.variant.as_deref() != Some(variant)) {
+            return false;
+        }
+        if filters.is_empty() {
+            return true;
+        }
+        let Some(value) = self.decoded() else {
+            return false;
+        };
+        let fields = abi::fields(&value);
+        filters.iter().all(|filter| {
+            fields
+                .iter()
+                .any(|(path, value)| *path == filter.path && *value == filter.value.0)
+        })
+    }
+}
+
+#[ComplexObject]
+impl ApplicationEntry {
+    /// The decoded content of the entry, if any
+    async fn value(&self) -> Option<Json<Value>> {
+        self.decoded().map(Json)
+    }
+
+    /// The scalar fields of the decoded entry, with their paths
+    async fn fields(&self) -> Vec<EntryField> {
+        let Some(value) = self.decoded() else {
+            return Vec::new();
+        };
+        abi::fields(&value)
+            .into_iter()
+            .map(|(path, value)| EntryField {
+                path,
+                value: Json(value),
+            })
+            .collect()
+    }
+}
+
+/// A scalar field of a decoded entry, e.g. `Transfer.owner`.
+#[derive(SimpleObject)]
+pub struct EntryField {
+    path: String,
+    value: Json<Value>,
+}
+
+/// A condition on a scalar field of the decoded entries.
+#[derive(InputObject)]
+pub struct FieldFilter {
+    /// The path of the field, e.g. `Transfer.owner`
+    path: String,
+    /// The value that the field must have
+    value: Json<Value>,
+}
+
+/// The number of entries of a variant.
+#[derive(SimpleObject)]
+pub struct VariantCount {
+    variant: String,
+    count: u64,
+}
+
+/// Entries in downward order, with the index to continue from.
+#[derive(SimpleObject)]
+pub struct EntryPage {
+    entries: Vec<ApplicationEntry>,
+    /// The index to pass as `from` to get the next entries, if there may be more
+    next: Option<u64>,
+}
+
+/// The indices of the entries of an application and kind, in increasing order: all of
+/// them, or the ones of a variant or with a field value.
+enum Postings {
+    All,
+    Variant(String),
+    /// The path of a field and its value in JSON
+    Field(String, String),
+}
+
+#[derive(RootView)]
+pub struct Applications<C> {
+    /// The last block height registered for each chain
+    last: MapView<C, ChainId, BlockHeight>,
+    count: MapView<C, (ApplicationId, EntryKind), u64>,
+    /// `ApplicationEntry` `MapView` indexed by application, kind and index
+    entries: MapView<C, (ApplicationId, EntryKind, u64), ApplicationEntry>,
+    variant_count: MapView<C, (ApplicationId, EntryKind, String), u64>,
+    /// The index of each entry among the ones of the same variant
+    variant_entries: MapView<C, (ApplicationId, EntryKind, String, u64), u64>,
+    /// The number of entries by field path and value in JSON
+    field_count: MapView<C, (ApplicationId, EntryKind, String, String), u64>,
+    /// The index of each entry among the ones with the same field value
+    field_entries: MapView<C, (ApplicationId, EntryKind, String, String, u64), u64>,
+}
+
+/// The location of an entry in a block.
+struct EntryOrigin {
+    chain_id: ChainId,
+    height: BlockHeight,
+    block: CryptoHash,
+}
+
+/// Implements helper functions on the `RootView`
+impl<C> Applications<C>
+where
+    C: Context + Send + Sync + 'static + Clone,
+{
+    /// Decodes an entry with the ABI of its application and registers it
+    async fn register_entry(
+        &mut self,
+        abi: &ApplicationAbi,
+        kind: EntryKind,
+        origin: &EntryOrigin,
+        stream_name: Option<StreamName>,
+        bytes: &[u8],
+    ) -> Result<(), IndexerError> {
+        let application_id = abi.application_id;
+        let type_name = abi.type_name(kind);
+        let (value, variant, error) = match type_name.map(|name| (name, abi.decode(name, bytes))) {
+            None => (None, None, None),
+            Some((type_name, Ok(value))) => {
+                let variant = abi.variant(type_name, &value).map(str::to_owned);
+                (Some(value), variant, None)
+            }
+            Some((_, Err(error))) => {
+                warn!(
+                    "failed to decode {:?} of {:?} in {:?} ({}): {}",
+                    kind, application_id, origin.chain_id, origin.height, error
+                );
+                (None, None, Some(error.to_string()))
+            }
+        };
+        let counter = (application_id, kind);
+        let index = self.count.get(&counter).await?.unwrap_or(0);
+        if let Some(variant) = &variant {
+            let variant_counter = (application_id, kind, variant.clone());
+            let position = self.variant_count.get(&variant_counter).await?.unwrap_or(0);
+            self.variant_entries
+                .insert(&(application_id, kind, variant.clone(), position), index)?;
+            self.variant_count.insert(&variant_counter, position + 1)?;
+        }
+        for (path, field) in value.iter().flat_map(abi::fields) {
+            let field_counter = (application_id, kind, path, field.to_string());
+            let position = self.field_count.get(&field_counter).await?.unwrap_or(0);
+            let (_, _, path, field) = &field_counter;
+            self.field_entries.insert(
+                &(application_id, kind, path.clone(), field.clone(), position),
+                index,
+            )?;
+            self.field_count.insert(&field_counter, position + 1)?;
+        }
+        let entry = ApplicationEntry {
+            application_id,
+            kind,
+            index,
+            chain_id: origin.chain_id,
+            height: origin.height,
+            block: origin.block,
+            stream_name,
+            type_name: type_name.map(str::to_owned),
+            variant,
+            bytes: bytes.to_vec(),
+            error,
+            json: value.as_ref().map(Value::to_string),
+        };
+        info!("register {:?} {} of {:?}", kind, index, application_id);
+        self.entries.insert(&(application_id, kind, index), entry)?;
+        Ok(self.count.insert(&counter, index + 1)?)
+    }
+
+    /// Returns the number of indices in a posting list.
+    async fn posting_count(
+        &self,
+        application_id: ApplicationId,
+        kind: EntryKind,
+        postings: &Postings,
+    ) -> Result<u64, IndexerError> {
+        let count = match postings {
+            Postings::All => self.count.get(&(application_id, kind)).await?,
+            Postings::Variant(variant) => {
+                let key = (application_id, kind, variant.clone());
+                self.variant_count.get(&key).await?
+            }
+            Postings::Field(path, value) => {
+                let key = (application_id, kind, path.clone(), value.clone());
+                self.field_count.get(&key).await?
+            }
+        };
+        Ok(count.unwrap_or(0))
+    }
+
+    /// Returns the index at the given position of a posting list.
+    async fn posting(
+        &self,
+        application_id: ApplicationId,
+        kind: EntryKind,
+        postings: &Postings,
+        position: u64,
+    ) -> Result<Option<u64>, IndexerError> {
+        Ok(match postings {
+            Postings::All => Some(position),
+            Postings::Variant(variant) => {
+                let key = (application_id, kind, variant.clone(), position);
+                self.variant_entries.get(&key).await?
+            }
+            Postings::Field(path, value) => {
+                let key = (application_id, kind, path.clone(), value.clone(), position);
+                self.field_entries.get(&key).await?
+            }
+        })
+    }
+}
+
+/// A plugin decoding the operations, messages and events of chosen applications with
+/// their ABIs.
+#[derive(Clone)]
+pub struct ApplicationsPlugin<C> {
+    state: Arc<Mutex<Applications<C>>>,
+    abis: Arc<BTreeMap<ApplicationId, ApplicationAbi>>,
+}
+
+impl<C> ApplicationsPlugin<C> {
+    /// Sets the ABIs of the applications whose entries are decoded.
+    pub fn with_abis(mut self, abis: impl IntoIterator<Item = ApplicationAbi>) -> Self {
+        self.abis = Arc::new(
+            abis.into_iter()
+                .map(|abi| (abi.application_id, abi))
+                .collect(),
+        );
+        self
+    }
+}
+
+static NAME: &str = "applications";
+
+/// Implements `Plugin`
+#[async_trait::async_trait]
+impl<S> Plugin<S> for ApplicationsPlugin<ViewContext<(), S>>
+where
+    S: KeyValueStore + Clone + Send + Sync + 'static,
+    S::Error: From<bcs::Error> + Send + Sync + std::error::Error + 'static,
+{
+    fn name(&self) -> String {
+        NAME.to_string()
+    }
+
+    async fn load(store: S) -> Result<Self, IndexerError>
+    where
+        Self: Sized,
+    {
+        Ok(Self {
+            state: load(store, NAME).await?,
+            abis: Arc::default(),
+        })
+    }
+
+    async fn register(&self, value: &ConfirmedBlock) -> Result<(), IndexerError> {
+        let mut plugin = self.state.lock().await;
+        let origin = EntryOrigin {
+            chain_id: value.chain_id(),
+            height: value.height(),
+            block: value.hash(),
+        };
+        let last = plugin.last.get(&origin.chain_id).await?;
+        if last.is_some_and(|last| last >= origin.height) {
+            return Ok(());
+        }
+        let body = &value.block().body;
+        for operation in &body.operations {
+            let Operation::User {
+                application_id,
+                bytes,
+            } = operation
+            else {
+                continue;
+            };
+            if let Some(abi) = self.abis.get(application_id) {
+                plugin
+                    .register_entry(abi, EntryKind::Operation, &origin, None, bytes)
+                    .await?;
+            }
+        }
+        for message in body.messages.iter().flatten() {
+            let Message::User {
+                application_id,
+                bytes,
+            } = &message.message
+            else {
+                continue;
+            };
+            if let Some(abi) = self.abis.get(application_id) {
+                plugin
+                    .register_entry(abi, EntryKind::Message, &origin, None, bytes)
+                    .await?;
+            }
+        }
+        for event in body.events.iter().flatten() {
+            let GenericApplicationId::User(application_id) = event.stream_id.application_id else {
+                continue;
+            };
+            if let Some(abi) = self.abis.get(&application_id) {
+                let stream_name = Some(event.stream_id.stream_name.clone());
+                plugin
+                    .register_entry(abi, EntryKind::Event, &origin, stream_name, &event.value)
+                    .await?;
+            }
+        }
+        plugin.last.insert(&origin.chain_id, origin.height)?;
+        Ok(plugin.save().await?)
+    }
+
+    fn sdl(&self) -> String {
+        sdl(self.clone())
+    }
+
+    fn route(&self, app: Router) -> Router {
+        route(&self.name(), self.clone(), app)
+    }
+}
+
+/// Implements `ObjectType`
+#[async_graphql::Object(cache_control(no_cache))]
+impl<C> ApplicationsPlugin<C>
+where
+    C: Context + Send + Sync + 'static + Clone,
+{
+    /// Gets the applications whose entries are decoded by the plugin
+    pub async fn applications(&self) -> Vec<ApplicationId> {
+        self.abis.keys().copied().collect()
+    }
+
+    /// Gets an entry of an application
+    pub async fn entry(
+        &self,
+        application_id: ApplicationId,
+        kind: EntryKind,
+        index: u64,
+    ) -> Result<Option<ApplicationEntry>, IndexerError> {
+        let plugin = self.state.lock().await;
+        Ok(plugin.entries.get(&(application_id, kind, index)).await?)
+    }
+
+    /// Gets the entries of an application in downward order from an index or from the last
+    /// one registered. The entries can be restricted to a variant, and to the ones whose
+    /// decoded fields have the given values. The candidates are taken from the most
+    /// selective index; if more than 10000 of them are examined, the query stops early and
+    /// `next` tells where to continue from.
+    pub async fn entries(
+        &self,
+        application_id: ApplicationId,
+        kind: EntryKind,
+        variant: Option<String>,
+        filters: Option<Vec<FieldFilter>>,
+        from: Option<u64>,
+        limit: Option<u32>,
+    ) -> Result<EntryPage, IndexerError> {
+        let filters = filters.unwrap_or_default();
+        let limit = usize::try_from(limit.unwrap_or(20)).unwrap_or(usize::MAX);
+        let conditions =
+            variant
+                .iter()
+                .map(|variant| Postings::Variant(variant.clone()))
+                .chain(filters.iter().map(|filter| {
+                    Postings::Field(filter.path.clone(), filter.value.0.to_string())
+                }));
+        // The lock is only held for each read, so that blocks keep being registered.
+        let mut postings = Postings::All;
+        let mut count = self
+            .state
+            .lock()
+            .await
+            .posting_count(application_id, kind, &postings)
+            .await?;
+        for condition in conditions {
+            let plugin = self.state.lock().await;
+            let condition_count = plugin
+                .posting_count(application_id, kind, &condition)
+                .await?;
+            if condition_count < count {
+                postings = condition;
+                count = condition_count;
+            }
+        }
+        let posting = |position| {
+            let postings = &postings;
+            async move {
+                let plugin = self.state.lock().await;
+                plugin
+                    .posting(application_id, kind, postings, position)
+                    .await
+            }
+        };
+        // The number of indices in the posting list that are at most `from`.
+        let mut end = count;
+        if let Some(from) = from {
+            let mut start = 0;
+            while start < end {
+                let middle = start + (end - start) / 2;
+                match posting(middle).await? {
+                    Some(index) if index <= from => start = middle + 1,
+                    _ => end = middle,
+                }
+            }
+        }
+        let mut entries = Vec::new();
+        let mut scanned = 0;
+        while end > 0 && entries.len() < limit && scanned < MAX_SCANNED_ENTRIES {
+            end -= 1;
+            scanned += 1;
+            let Some(index) = posting(end).await? else {
+                break;
+            };
+            let key = (application_id, kind, index);
+            let Some(entry) = self.state.lock().await.entries.get(&key).await? else {
+                break;
+            };
+            if entry.matches(variant.as_deref(), &filters) {
+                entries.push(entry);
+            }
+        }
+        let next = match end {
+            0 => None,
+            end => posting(end - 1).await?,
+        };
+        Ok(EntryPage { entries, next })
+    }
+
+    /// Gets the variants of the entries of an application, with their number of entries
+    pub async fn variants(
+        &self,
+        application_id: ApplicationId,
+        kind: EntryKind,
+    ) -> Result<Vec<VariantCount>, IndexerError> {
+        let plugin = self.state.lock().await;
+        let mut result = Vec::new();
+        for (id, entry_kind, variant) in plugin.variant_count.indices().await? {
+            if id != application_id || entry_kind != kind {
+                continue;
+            }
+            let key = (id, entry_kind, variant);
+            let count = plugin.variant_count.get(&key).await?.unwrap_or(0);
+            result.push(VariantCount {
+                variant: key.2,
+                count,
+            });
+        }
+        Ok(result)
+    }
+
+    /// Gets the number of entries registered for an application
+    pub async fn count(
+        &self,
+        application_id: ApplicationId,
+        kind: EntryKind,
+    ) -> Result<u64, IndexerError> {
+        let plugin = self.state.lock().await;
+        Ok(plugin
+            .count
+            .get(&(application_id, kind))
+            .await
+            .map(|opt| opt.unwrap_or(0))?)
+    }
+}
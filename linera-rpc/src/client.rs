@@ -4,7 +4,9 @@
 use linera_base::{
     crypto::CryptoHash,
     data_types::{BlobContent, NetworkDescription},
+    http::SignedResponse,
     identifiers::{BlobId, ChainId},
+    randomness::RandomnessShare,
 };
 use linera_chain::{
     data_types::BlockProposal,
@@ -13,7 +15,10 @@ use linera_chain::{
     },
 };
 use linera_core::{
-    data_types::{ChainInfoQuery, ChainInfoResponse},
+    data_types::{
+        ChainInfoQuery, ChainInfoResponse, ChainSnapshotChunk, ChainSnapshotRequest,
+        HttpOracleRequest, RandomnessRequest,
+    },
     node::{CrossChainMessageDelivery, NodeError, NotificationStream, ValidatorNode},
 };
 
@@ -262,4 +267,46 @@ impl ValidatorNode for Client {
             Client::Simple(simple_client) => simple_client.missing_blob_ids(blob_ids).await?,
         })
     }
+
+    async fn handle_http_oracle_request(
+        &self,
+        request: HttpOracleRequest,
+    ) -> Result<SignedResponse, NodeError> {
+        Ok(match self {
+            Client::Grpc(grpc_client) => grpc_client.handle_http_oracle_request(request).await?,
+
+            #[cfg(with_simple_network)]
+            Client::Simple(simple_client) => {
+                simple_client.handle_http_oracle_request(request).await?
+            }
+        })
+    }
+
+    async fn handle_randomness_request(
+        &self,
+        request: RandomnessRequest,
+    ) -> Result<RandomnessShare, NodeError> {
+        Ok(match self {
+            Client::Grpc(grpc_client) => grpc_client.handle_randomness_request(request).await?,
+
+            #[cfg(with_simple_network)]
+            Client::Simple(simple_client) => {
+                simple_client.handle_randomness_request(request).await?
+            }
+        })
+    }
+
+    async fn download_chain_snapshot_chunk(
+        &self,
+        request: ChainSnapshotRequest,
+    ) -> Result<ChainSnapshotChunk, NodeError> {
+        Ok(match self {
+            Client::Grpc(grpc_client) => grpc_client.download_chain_snapshot_chunk(request).await?,
+
+            #[cfg(with_simple_network)]
+            Client::Simple(simple_client) => {
+                simple_client.download_chain_snapshot_chunk(request).await?
+            }
+        })
+    }
 }
@@ -8,7 +8,9 @@ use linera_base::{
     crypto::CryptoHash,
     data_types::{BlobContent, NetworkDescription},
     ensure,
+    http::SignedResponse,
     identifiers::{BlobId, ChainId},
+    randomness::RandomnessShare,
     time::Duration,
 };
 use linera_chain::{
@@ -19,7 +21,10 @@ use linera_chain::{
     },
 };
 use linera_core::{
-    data_types::ChainInfoResponse,
+    data_types::{
+        ChainInfoResponse, ChainSnapshotChunk, ChainSnapshotRequest, HttpOracleRequest,
+        RandomnessRequest,
+    },
     node::{CrossChainMessageDelivery, NodeError, NotificationStream, ValidatorNode},
     worker::Notification,
 };
@@ -164,6 +169,68 @@ impl TryFrom<api::PendingBlobResult> for BlobContent {
     }
 }
 
+impl TryFrom<api::HttpOracleResult> for SignedResponse {
+    type Error = NodeError;
+
+    fn try_from(result: api::HttpOracleResult) -> Result<Self, Self::Error> {
+        let inner = result.inner.ok_or_else(|| NodeError::GrpcError {
+            error: "missing body from response".to_string(),
+        })?;
+        match inner {
+            api::http_oracle_result::Inner::Response(response) => Ok(bincode::deserialize(
+                &response,
+            )
+            .map_err(|err| NodeError::GrpcError {
+                error: format!("failed to unmarshal response: {}", err),
+            })?),
+            api::http_oracle_result::Inner::Error(error) => Err(bincode::deserialize(&error)
+                .map_err(|err| NodeError::GrpcError {
+                    error: format!("failed to unmarshal error message: {}", err),
+                })?),
+        }
+    }
+}
+
+impl TryFrom<api::RandomnessResult> for RandomnessShare {
+    type Error = NodeError;
+
+    fn try_from(result: api::RandomnessResult) -> Result<Self, Self::Error> {
+        let inner = result.inner.ok_or_else(|| NodeError::GrpcError {
+            error: "missing body from response".to_string(),
+        })?;
+        match inner {
+            api::randomness_result::Inner::Share(share) => Ok(bincode::deserialize(&share)
+                .map_err(|err| NodeError::GrpcError {
+                    error: format!("failed to unmarshal response: {}", err),
+                })?),
+            api::randomness_result::Inner::Error(error) => Err(bincode::deserialize(&error)
+                .map_err(|err| NodeError::GrpcError {
+                    error: format!("failed to unmarshal error message: {}", err),
+                })?),
+        }
+    }
+}
+
+impl TryFrom<api::ChainSnapshotResult> for ChainSnapshotChunk {
+    type Error = NodeError;
+
+    fn try_from(result: api::ChainSnapshotResult) -> Result<Self, Self::Error> {
+        let inner = result.inner.ok_or_else(|| NodeError::GrpcError {
+            error: "missing body from response".to_string(),
+        })?;
+        match inner {
+            api::chain_snapshot_result::Inner::Chunk(chunk) => Ok(bincode::deserialize(&chunk)
+                .map_err(|err| NodeError::GrpcError {
+                    error: format!("failed to unmarshal response: {}", err),
+                })?),
+            api::chain_snapshot_result::Inner::Error(error) => Err(bincode::deserialize(&error)
+                .map_err(|err| NodeError::GrpcError {
+                    error: format!("failed to unmarshal error message: {}", err),
+                })?),
+        }
+    }
+}
+
 macro_rules! client_delegate {
     ($self:ident, $handler:ident, $req:ident) => {{
         debug!(
@@ -439,4 +506,28 @@ impl ValidatorNode for GrpcClient {
     async fn missing_blob_ids(&self, blob_ids: Vec<BlobId>) -> Result<Vec<BlobId>, NodeError> {
         Ok(client_delegate!(self, missing_blob_ids, blob_ids)?.try_into()?)
     }
+
+    #[instrument(target = "grpc_client", skip(self), err, fields(address = self.address))]
+    async fn handle_http_oracle_request(
+        &self,
+        request: HttpOracleRequest,
+    ) -> Result<SignedResponse, NodeError> {
+        client_delegate!(self, handle_http_oracle_request, request)?.try_into()
+    }
+
+    #[instrument(target = "grpc_client", skip(self), err, fields(address = self.address))]
+    async fn handle_randomness_request(
+        &self,
+        request: RandomnessRequest,
+    ) -> Result<RandomnessShare, NodeError> {
+        client_delegate!(self, handle_randomness_request, request)?.try_into()
+    }
+
+    #[instrument(target = "grpc_client", skip(self), err, fields(address = self.address))]
+    async fn download_chain_snapshot_chunk(
+        &self,
+        request: ChainSnapshotRequest,
+    ) -> Result<ChainSnapshotChunk, NodeError> {
+        client_delegate!(self, download_chain_snapshot_chunk, request)?.try_into()
+    }
 }
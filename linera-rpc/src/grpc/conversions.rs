@@ -8,7 +8,9 @@ use linera_base::{
     },
     data_types::{BlobContent, BlockHeight, NetworkDescription},
     ensure,
+    http::SignedResponse,
     identifiers::{AccountOwner, BlobId, ChainId},
+    randomness::RandomnessShare,
 };
 use linera_chain::{
     data_types::{BlockProposal, LiteValue, ProposalContent},
@@ -18,7 +20,10 @@ use linera_chain::{
     },
 };
 use linera_core::{
-    data_types::{ChainInfoQuery, ChainInfoResponse, CrossChainRequest},
+    data_types::{
+        ChainInfoQuery, ChainInfoResponse, ChainSnapshotChunk, ChainSnapshotRequest,
+        CrossChainRequest, HttpOracleRequest, RandomnessRequest,
+    },
     node::NodeError,
     worker::Notification,
 };
@@ -604,6 +609,7 @@ impl TryFrom<api::ChainInfoQuery> for ChainInfoQuery {
             request_manager_values: chain_info_query.request_manager_values,
             request_leader_timeout: chain_info_query.request_leader_timeout,
             request_fallback: chain_info_query.request_fallback,
+            request_storage_usage: chain_info_query.request_storage_usage,
         })
     }
 }
@@ -630,6 +636,7 @@ impl TryFrom<ChainInfoQuery> for api::ChainInfoQuery {
             request_manager_values: chain_info_query.request_manager_values,
             request_leader_timeout: chain_info_query.request_leader_timeout,
             request_fallback: chain_info_query.request_fallback,
+            request_storage_usage: chain_info_query.request_storage_usage,
         })
     }
 }
@@ -803,6 +810,152 @@ impl TryFrom<NodeError> for api::PendingBlobResult {
     }
 }
 
+impl TryFrom<HttpOracleRequest> for api::HttpOracleRequest {
+    type Error = GrpcProtoConversionError;
+
+    fn try_from(request: HttpOracleRequest) -> Result<Self, Self::Error> {
+        Ok(Self {
+            chain_id: Some(request.chain_id.into()),
+            height: Some(request.height.into()),
+            request: bincode::serialize(&request.request)?,
+            public_key: Some(request.public_key.into()),
+            signature: Some(request.signature.into()),
+        })
+    }
+}
+
+impl TryFrom<api::HttpOracleRequest> for HttpOracleRequest {
+    type Error = GrpcProtoConversionError;
+
+    fn try_from(request: api::HttpOracleRequest) -> Result<Self, Self::Error> {
+        Ok(Self {
+            chain_id: try_proto_convert(request.chain_id)?,
+            height: request
+                .height
+                .ok_or(GrpcProtoConversionError::MissingField)?
+                .into(),
+            request: bincode::deserialize(&request.request)?,
+            public_key: try_proto_convert(request.public_key)?,
+            signature: try_proto_convert(request.signature)?,
+        })
+    }
+}
+
+impl TryFrom<SignedResponse> for api::HttpOracleResult {
+    type Error = GrpcProtoConversionError;
+
+    fn try_from(response: SignedResponse) -> Result<Self, Self::Error> {
+        let response = bincode::serialize(&response)?;
+        Ok(Self {
+            inner: Some(api::http_oracle_result::Inner::Response(response)),
+        })
+    }
+}
+
+impl TryFrom<NodeError> for api::HttpOracleResult {
+    type Error = GrpcProtoConversionError;
+
+    fn try_from(node_error: NodeError) -> Result<Self, Self::Error> {
+        let error = bincode::serialize(&node_error)?;
+        Ok(api::HttpOracleResult {
+            inner: Some(api::http_oracle_result::Inner::Error(error)),
+        })
+    }
+}
+
+impl TryFrom<RandomnessRequest> for api::RandomnessRequest {
+    type Error = GrpcProtoConversionError;
+
+    fn try_from(request: RandomnessRequest) -> Result<Self, Self::Error> {
+        Ok(Self {
+            chain_id: Some(request.chain_id.into()),
+            height: Some(request.height.into()),
+            round: bincode::serialize(&request.round)?,
+        })
+    }
+}
+
+impl TryFrom<api::RandomnessRequest> for RandomnessRequest {
+    type Error = GrpcProtoConversionError;
+
+    fn try_from(request: api::RandomnessRequest) -> Result<Self, Self::Error> {
+        Ok(Self {
+            chain_id: try_proto_convert(request.chain_id)?,
+            height: request
+                .height
+                .ok_or(GrpcProtoConversionError::MissingField)?
+                .into(),
+            round: bincode::deserialize(&request.round)?,
+        })
+    }
+}
+
+impl TryFrom<RandomnessShare> for api::RandomnessResult {
+    type Error = GrpcProtoConversionError;
+
+    fn try_from(share: RandomnessShare) -> Result<Self, Self::Error> {
+        let share = bincode::serialize(&share)?;
+        Ok(Self {
+            inner: Some(api::randomness_result::Inner::Share(share)),
+        })
+    }
+}
+
+impl TryFrom<NodeError> for api::RandomnessResult {
+    type Error = GrpcProtoConversionError;
+
+    fn try_from(node_error: NodeError) -> Result<Self, Self::Error> {
+        let error = bincode::serialize(&node_error)?;
+        Ok(api::RandomnessResult {
+            inner: Some(api::randomness_result::Inner::Error(error)),
+        })
+    }
+}
+
+impl From<ChainSnapshotRequest> for api::ChainSnapshotRequest {
+    fn from(request: ChainSnapshotRequest) -> Self {
+        Self {
+            chain_id: Some(request.chain_id.into()),
+            block_hash: request.block_hash.map(Into::into),
+            index: request.index,
+        }
+    }
+}
+
+impl TryFrom<api::ChainSnapshotRequest> for ChainSnapshotRequest {
+    type Error = GrpcProtoConversionError;
+
+    fn try_from(request: api::ChainSnapshotRequest) -> Result<Self, Self::Error> {
+        Ok(Self {
+            chain_id: try_proto_convert(request.chain_id)?,
+            block_hash: request.block_hash.map(TryInto::try_into).transpose()?,
+            index: request.index,
+        })
+    }
+}
+
+impl TryFrom<ChainSnapshotChunk> for api::ChainSnapshotResult {
+    type Error = GrpcProtoConversionError;
+
+    fn try_from(chunk: ChainSnapshotChunk) -> Result<Self, Self::Error> {
+        let chunk = bincode::serialize(&chunk)?;
+        Ok(Self {
+            inner: Some(api::chain_snapshot_result::Inner::Chunk(chunk)),
+        })
+    }
+}
+
+impl TryFrom<NodeError> for api::ChainSnapshotResult {
+    type Error = GrpcProtoConversionError;
+
+    fn try_from(node_error: NodeError) -> Result<Self, Self::Error> {
+        let error = bincode::serialize(&node_error)?;
+        Ok(api::ChainSnapshotResult {
+            inner: Some(api::chain_snapshot_result::Inner::Error(error)),
+        })
+    }
+}
+
 impl From<BlockHeight> for api::BlockHeight {
     fn from(block_height: BlockHeight) -> Self {
         Self {
@@ -1001,11 +1154,12 @@ impl TryFrom<api::CertificatesBatchResponse> for Vec<Certificate> {
 
 #[cfg(test)]
 pub mod tests {
-    use std::{borrow::Cow, fmt::Debug};
+    use std::{borrow::Cow, collections::BTreeMap, fmt::Debug};
 
     use linera_base::{
         crypto::{AccountSecretKey, BcsSignable, CryptoHash, Secp256k1SecretKey, ValidatorKeypair},
         data_types::{Amount, Blob, Epoch, Round, Timestamp},
+        http,
     };
     use linera_chain::{
         data_types::{BlockExecutionOutcome, OriginalProposal, ProposedBlock},
@@ -1101,6 +1255,8 @@ pub mod tests {
             requested_sent_certificate_hashes: vec![],
             count_received_log: 0,
             requested_received_log: vec![],
+            requested_storage_usage: BTreeMap::new(),
+            pruned_height: BlockHeight::ZERO,
         });
 
         let chain_info_response_none = ChainInfoResponse {
@@ -1142,6 +1298,7 @@ pub mod tests {
             request_manager_values: false,
             request_leader_timeout: false,
             request_fallback: true,
+            request_storage_usage: true,
         };
         round_trip_check::<_, api::ChainInfoQuery>(chain_info_query_some);
     }
@@ -1168,6 +1325,39 @@ pub mod tests {
         round_trip_check::<_, api::HandlePendingBlobRequest>(pending_blob_request);
     }
 
+    #[test]
+    pub fn test_http_oracle_request() {
+        let key_pair = AccountSecretKey::generate();
+        let http_oracle_request = HttpOracleRequest {
+            chain_id: dummy_chain_id(2),
+            height: BlockHeight(3),
+            request: http::Request::post("https://example.com", b"foo".to_vec()),
+            public_key: key_pair.public(),
+            signature: key_pair.sign(&Foo("test".into())),
+        };
+        round_trip_check::<_, api::HttpOracleRequest>(http_oracle_request);
+    }
+
+    #[test]
+    pub fn test_randomness_request() {
+        let randomness_request = RandomnessRequest {
+            chain_id: dummy_chain_id(2),
+            height: BlockHeight(3),
+            round: Round::MultiLeader(4),
+        };
+        round_trip_check::<_, api::RandomnessRequest>(randomness_request);
+    }
+
+    #[test]
+    pub fn test_chain_snapshot_request() {
+        let chain_snapshot_request = ChainSnapshotRequest {
+            chain_id: dummy_chain_id(2),
+            block_hash: Some(CryptoHash::test_hash("block")),
+            index: 3,
+        };
+        round_trip_check::<_, api::ChainSnapshotRequest>(chain_snapshot_request);
+    }
+
     #[test]
     pub fn test_lite_certificate() {
         let key_pair = ValidatorKeypair::generate();
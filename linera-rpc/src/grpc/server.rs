@@ -33,8 +33,10 @@ use super::{
         notifier_service_client::NotifierServiceClient,
         validator_worker_client::ValidatorWorkerClient,
         validator_worker_server::{ValidatorWorker as ValidatorWorkerRpc, ValidatorWorkerServer},
-        BlockProposal, ChainInfoQuery, ChainInfoResult, CrossChainRequest,
-        HandlePendingBlobRequest, LiteCertificate, PendingBlobRequest, PendingBlobResult,
+        BlockProposal, ChainInfoQuery, ChainInfoResult, ChainSnapshotRequest, ChainSnapshotResult,
+        CrossChainRequest, HandlePendingBlobRequest, HttpOracleRequest, HttpOracleResult,
+        LiteCertificate, PendingBlobRequest, PendingBlobResult, RandomnessRequest,
+        RandomnessResult,
     },
     pool::GrpcConnectionPool,
     GrpcError, GRPC_MAX_MESSAGE_SIZE,
@@ -745,6 +747,107 @@ where
         }
     }
 
+    #[instrument(
+        target = "grpc_server",
+        skip_all,
+        err,
+        fields(
+            nickname = self.state.nickname(),
+            chain_id = ?request.get_ref().chain_id
+        )
+    )]
+    async fn handle_http_oracle_request(
+        &self,
+        request: Request<HttpOracleRequest>,
+    ) -> Result<Response<HttpOracleResult>, Status> {
+        let start = Instant::now();
+        let request: linera_core::data_types::HttpOracleRequest =
+            request.into_inner().try_into()?;
+        trace!(?request, "Handle HTTP oracle request");
+        match self.state.clone().handle_http_oracle_request(request).await {
+            Ok(response) => {
+                Self::log_request_outcome_and_latency(start, true, "handle_http_oracle_request");
+                Ok(Response::new(response.try_into()?))
+            }
+            Err(error) => {
+                Self::log_request_outcome_and_latency(start, false, "handle_http_oracle_request");
+                let nickname = self.state.nickname();
+                error!(nickname, %error, "Failed to handle HTTP oracle request");
+                Ok(Response::new(NodeError::from(error).try_into()?))
+            }
+        }
+    }
+
+    #[instrument(
+        target = "grpc_server",
+        skip_all,
+        err,
+        fields(
+            nickname = self.state.nickname(),
+            chain_id = ?request.get_ref().chain_id
+        )
+    )]
+    async fn handle_randomness_request(
+        &self,
+        request: Request<RandomnessRequest>,
+    ) -> Result<Response<RandomnessResult>, Status> {
+        let start = Instant::now();
+        let request: linera_core::data_types::RandomnessRequest =
+            request.into_inner().try_into()?;
+        trace!(?request, "Handle randomness request");
+        match self.state.clone().handle_randomness_request(request).await {
+            Ok(share) => {
+                Self::log_request_outcome_and_latency(start, true, "handle_randomness_request");
+                Ok(Response::new(share.try_into()?))
+            }
+            Err(error) => {
+                Self::log_request_outcome_and_latency(start, false, "handle_randomness_request");
+                let nickname = self.state.nickname();
+                error!(nickname, %error, "Failed to handle randomness request");
+                Ok(Response::new(NodeError::from(error).try_into()?))
+            }
+        }
+    }
+
+    #[instrument(
+        target = "grpc_server",
+        skip_all,
+        err,
+        fields(
+            nickname = self.state.nickname(),
+            chain_id = ?request.get_ref().chain_id
+        )
+    )]
+    async fn download_chain_snapshot_chunk(
+        &self,
+        request: Request<ChainSnapshotRequest>,
+    ) -> Result<Response<ChainSnapshotResult>, Status> {
+        let start = Instant::now();
+        let request: linera_core::data_types::ChainSnapshotRequest =
+            request.into_inner().try_into()?;
+        trace!(?request, "Handle chain snapshot request");
+        match self.state.clone().handle_chain_snapshot_request(request).await {
+            Ok(chunk) => {
+                Self::log_request_outcome_and_latency(
+                    start,
+                    true,
+                    "download_chain_snapshot_chunk",
+                );
+                Ok(Response::new(chunk.try_into()?))
+            }
+            Err(error) => {
+                Self::log_request_outcome_and_latency(
+                    start,
+                    false,
+                    "download_chain_snapshot_chunk",
+                );
+                let nickname = self.state.nickname();
+                error!(nickname, %error, "Failed to handle chain snapshot request");
+                Ok(Response::new(NodeError::from(error).try_into()?))
+            }
+        }
+    }
+
     #[instrument(
         target = "grpc_server",
         skip_all,
@@ -830,6 +933,24 @@ impl GrpcProxyable for HandlePendingBlobRequest {
     }
 }
 
+impl GrpcProxyable for HttpOracleRequest {
+    fn chain_id(&self) -> Option<ChainId> {
+        self.chain_id.clone()?.try_into().ok()
+    }
+}
+
+impl GrpcProxyable for RandomnessRequest {
+    fn chain_id(&self) -> Option<ChainId> {
+        self.chain_id.clone()?.try_into().ok()
+    }
+}
+
+impl GrpcProxyable for ChainSnapshotRequest {
+    fn chain_id(&self) -> Option<ChainId> {
+        self.chain_id.clone()?.try_into().ok()
+    }
+}
+
 impl GrpcProxyable for CrossChainRequest {
     fn chain_id(&self) -> Option<ChainId> {
         use super::api::cross_chain_request::Inner;
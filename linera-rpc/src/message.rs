@@ -5,14 +5,19 @@
 use linera_base::{
     crypto::CryptoHash,
     data_types::{BlobContent, NetworkDescription},
+    http::SignedResponse,
     identifiers::{BlobId, ChainId},
+    randomness::RandomnessShare,
 };
 use linera_chain::{
     data_types::{BlockProposal, LiteVote},
     types::{ConfirmedBlock, ConfirmedBlockCertificate},
 };
 use linera_core::{
-    data_types::{ChainInfoQuery, ChainInfoResponse, CrossChainRequest},
+    data_types::{
+        ChainInfoQuery, ChainInfoResponse, ChainSnapshotChunk, ChainSnapshotRequest,
+        CrossChainRequest, HttpOracleRequest, RandomnessRequest,
+    },
     node::NodeError,
 };
 use linera_version::VersionInfo;
@@ -43,6 +48,9 @@ pub enum RpcMessage {
     MissingBlobIds(Vec<BlobId>),
     VersionInfoQuery,
     NetworkDescriptionQuery,
+    HttpOracleRequest(Box<HttpOracleRequest>),
+    RandomnessRequest(Box<RandomnessRequest>),
+    ChainSnapshotRequest(Box<ChainSnapshotRequest>),
 
     // Outbound
     Vote(Box<LiteVote>),
@@ -57,6 +65,9 @@ pub enum RpcMessage {
     DownloadCertificatesResponse(Vec<ConfirmedBlockCertificate>),
     BlobLastUsedByResponse(Box<CryptoHash>),
     MissingBlobIdsResponse(Vec<BlobId>),
+    HttpOracleResponse(Box<SignedResponse>),
+    RandomnessResponse(Box<RandomnessShare>),
+    ChainSnapshotResponse(Box<ChainSnapshotChunk>),
 
     // Internal to a validator
     CrossChainRequest(Box<CrossChainRequest>),
@@ -79,6 +90,9 @@ impl RpcMessage {
             CrossChainRequest(request) => request.target_chain_id(),
             DownloadPendingBlob(request) => request.0,
             HandlePendingBlob(request) => request.0,
+            HttpOracleRequest(request) => request.chain_id,
+            RandomnessRequest(request) => request.chain_id,
+            ChainSnapshotRequest(request) => request.chain_id,
             Vote(_)
             | Error(_)
             | ChainInfoResponse(_)
@@ -98,6 +112,9 @@ impl RpcMessage {
             | BlobLastUsedByResponse(_)
             | MissingBlobIds(_)
             | MissingBlobIdsResponse(_)
+            | HttpOracleResponse(_)
+            | RandomnessResponse(_)
+            | ChainSnapshotResponse(_)
             | DownloadCertificatesResponse(_) => {
                 return None;
             }
@@ -136,6 +153,12 @@ impl RpcMessage {
             | DownloadPendingBlob(_)
             | DownloadPendingBlobResponse(_)
             | HandlePendingBlob(_)
+            | HttpOracleRequest(_)
+            | HttpOracleResponse(_)
+            | RandomnessRequest(_)
+            | RandomnessResponse(_)
+            | ChainSnapshotRequest(_)
+            | ChainSnapshotResponse(_)
             | DownloadBlobResponse(_)
             | DownloadConfirmedBlockResponse(_)
             | BlobLastUsedByResponse(_)
@@ -233,6 +256,39 @@ impl TryFrom<RpcMessage> for Vec<BlobId> {
     }
 }
 
+impl TryFrom<RpcMessage> for SignedResponse {
+    type Error = NodeError;
+    fn try_from(message: RpcMessage) -> Result<Self, Self::Error> {
+        match message {
+            RpcMessage::HttpOracleResponse(response) => Ok(*response),
+            RpcMessage::Error(error) => Err(*error),
+            _ => Err(NodeError::UnexpectedMessage),
+        }
+    }
+}
+
+impl TryFrom<RpcMessage> for RandomnessShare {
+    type Error = NodeError;
+    fn try_from(message: RpcMessage) -> Result<Self, Self::Error> {
+        match message {
+            RpcMessage::RandomnessResponse(share) => Ok(*share),
+            RpcMessage::Error(error) => Err(*error),
+            _ => Err(NodeError::UnexpectedMessage),
+        }
+    }
+}
+
+impl TryFrom<RpcMessage> for ChainSnapshotChunk {
+    type Error = NodeError;
+    fn try_from(message: RpcMessage) -> Result<Self, Self::Error> {
+        match message {
+            RpcMessage::ChainSnapshotResponse(chunk) => Ok(*chunk),
+            RpcMessage::Error(error) => Err(*error),
+            _ => Err(NodeError::UnexpectedMessage),
+        }
+    }
+}
+
 impl TryFrom<RpcMessage> for BlobId {
     type Error = NodeError;
     fn try_from(message: RpcMessage) -> Result<Self, Self::Error> {
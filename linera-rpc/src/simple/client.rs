@@ -8,7 +8,9 @@ use futures::{sink::SinkExt, stream::StreamExt};
 use linera_base::{
     crypto::CryptoHash,
     data_types::{BlobContent, NetworkDescription},
+    http::SignedResponse,
     identifiers::{BlobId, ChainId},
+    randomness::RandomnessShare,
     time::{timer, Duration},
 };
 use linera_chain::{
@@ -18,7 +20,10 @@ use linera_chain::{
     },
 };
 use linera_core::{
-    data_types::{ChainInfoQuery, ChainInfoResponse},
+    data_types::{
+        ChainInfoQuery, ChainInfoResponse, ChainSnapshotChunk, ChainSnapshotRequest,
+        HttpOracleRequest, RandomnessRequest,
+    },
     node::{CrossChainMessageDelivery, NodeError, NotificationStream, ValidatorNode},
 };
 use linera_version::VersionInfo;
@@ -230,4 +235,28 @@ impl ValidatorNode for SimpleClient {
     async fn missing_blob_ids(&self, blob_ids: Vec<BlobId>) -> Result<Vec<BlobId>, NodeError> {
         self.query(RpcMessage::MissingBlobIds(blob_ids)).await
     }
+
+    async fn handle_http_oracle_request(
+        &self,
+        request: HttpOracleRequest,
+    ) -> Result<SignedResponse, NodeError> {
+        self.query(RpcMessage::HttpOracleRequest(Box::new(request)))
+            .await
+    }
+
+    async fn handle_randomness_request(
+        &self,
+        request: RandomnessRequest,
+    ) -> Result<RandomnessShare, NodeError> {
+        self.query(RpcMessage::RandomnessRequest(Box::new(request)))
+            .await
+    }
+
+    async fn download_chain_snapshot_chunk(
+        &self,
+        request: ChainSnapshotRequest,
+    ) -> Result<ChainSnapshotChunk, NodeError> {
+        self.query(RpcMessage::ChainSnapshotRequest(Box::new(request)))
+            .await
+    }
 }
@@ -361,6 +361,36 @@ where
                     }
                 }
             }
+            RpcMessage::HttpOracleRequest(request) => {
+                match self.server.state.handle_http_oracle_request(*request).await {
+                    Ok(response) => Ok(Some(RpcMessage::HttpOracleResponse(Box::new(response)))),
+                    Err(error) => {
+                        let nickname = self.server.state.nickname();
+                        error!(nickname, %error, "Failed to handle HTTP oracle request");
+                        Err(error.into())
+                    }
+                }
+            }
+            RpcMessage::RandomnessRequest(request) => {
+                match self.server.state.handle_randomness_request(*request).await {
+                    Ok(share) => Ok(Some(RpcMessage::RandomnessResponse(Box::new(share)))),
+                    Err(error) => {
+                        let nickname = self.server.state.nickname();
+                        error!(nickname, %error, "Failed to handle randomness request");
+                        Err(error.into())
+                    }
+                }
+            }
+            RpcMessage::ChainSnapshotRequest(request) => {
+                match self.server.state.handle_chain_snapshot_request(*request).await {
+                    Ok(chunk) => Ok(Some(RpcMessage::ChainSnapshotResponse(Box::new(chunk)))),
+                    Err(error) => {
+                        let nickname = self.server.state.nickname();
+                        error!(nickname, %error, "Failed to handle chain snapshot request");
+                        Err(error.into())
+                    }
+                }
+            }
 
             RpcMessage::VersionInfoQuery => {
                 Ok(Some(RpcMessage::VersionInfoResponse(Box::default())))
@@ -381,6 +411,9 @@ where
             | RpcMessage::BlobLastUsedByResponse(_)
             | RpcMessage::MissingBlobIds(_)
             | RpcMessage::MissingBlobIdsResponse(_)
+            | RpcMessage::HttpOracleResponse(_)
+            | RpcMessage::RandomnessResponse(_)
+            | RpcMessage::ChainSnapshotResponse(_)
             | RpcMessage::DownloadCertificates(_)
             | RpcMessage::DownloadCertificatesResponse(_)
             | RpcMessage::UploadBlob(_)
@@ -3,10 +3,12 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use linera_base::{
-    crypto::{AccountPublicKey, AccountSignature, TestString},
-    data_types::{BlobContent, ChainDescription, ChainOrigin, OracleResponse, Round},
-    identifiers::{AccountOwner, BlobType, GenericApplicationId},
+    crypto::{AccountPublicKey, AccountSignature, CryptoHash, TestString},
+    data_types::{BlobContent, BlockHeight, ChainDescription, ChainOrigin, OracleResponse, Round},
+    http::Method,
+    identifiers::{AccountOwner, BlobType, ChainId, GenericApplicationId},
     ownership::ChainOwnership,
+    randomness::{RandomnessPublicKeys, RandomnessShare},
     vm::VmRuntime,
 };
 use linera_chain::{
@@ -51,12 +53,28 @@ fn get_registry() -> Result<Registry> {
             &evm_secret_key,
         );
         tracer.trace_value(&mut samples, &evm_signature)?;
+
+        // The randomness beacon's keys and signatures must be valid curve points too.
+        let validator = validator_keypair.public_key;
+        let (randomness_keys, randomness_secrets) =
+            RandomnessPublicKeys::deal([(validator, 1)], 1, &mut rand::rngs::OsRng);
+        let randomness_share = RandomnessShare::new(
+            validator,
+            &randomness_secrets[&validator],
+            ChainId(CryptoHash::new(&TestString::new("chain".to_string()))),
+            BlockHeight(0),
+            Round::Fast,
+        )
+        .expect("signing a randomness share should not fail");
+        tracer.trace_value(&mut samples, &randomness_keys.public_key)?;
+        tracer.trace_value(&mut samples, &randomness_share.signatures[0])?;
     }
     // 2. Trace the main entry point(s) + every enum separately.
     tracer.trace_type::<AccountPublicKey>(&samples)?;
     tracer.trace_type::<AccountSignature>(&samples)?;
     tracer.trace_type::<Round>(&samples)?;
     tracer.trace_type::<OracleResponse>(&samples)?;
+    tracer.trace_type::<Method>(&samples)?;
     tracer.trace_type::<Recipient>(&samples)?;
     tracer.trace_type::<SystemOperation>(&samples)?;
     tracer.trace_type::<AdminOperation>(&samples)?;
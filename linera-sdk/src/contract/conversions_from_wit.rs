@@ -6,6 +6,7 @@
 use linera_base::{
     crypto::CryptoHash,
     data_types::{Amount, BlockHeight, StreamUpdate},
+    http,
     identifiers::{
         AccountOwner, ApplicationId, ChainId, GenericApplicationId, MessageId, ModuleId, StreamId,
         StreamName,
@@ -103,6 +104,26 @@ impl From<wit_contract_api::Amount> for Amount {
     }
 }
 
+impl From<wit_contract_api::HttpResponse> for http::Response {
+    fn from(response: wit_contract_api::HttpResponse) -> http::Response {
+        http::Response {
+            status: response.status,
+            headers: response
+                .headers
+                .into_iter()
+                .map(http::Header::from)
+                .collect(),
+            body: response.body,
+        }
+    }
+}
+
+impl From<wit_contract_api::HttpHeader> for http::Header {
+    fn from(header: wit_contract_api::HttpHeader) -> http::Header {
+        http::Header::new(header.name, header.value)
+    }
+}
+
 impl From<wit_contract_api::CloseChainError> for CloseChainError {
     fn from(guest: wit_contract_api::CloseChainError) -> Self {
         match guest {
@@ -157,6 +178,25 @@ impl From<wit_entrypoints::ChainId> for ChainId {
     }
 }
 
+impl From<wit_entrypoints::ModuleId> for ModuleId {
+    fn from(module_id: wit_entrypoints::ModuleId) -> Self {
+        ModuleId::new(
+            module_id.contract_blob_hash.into(),
+            module_id.service_blob_hash.into(),
+            module_id.vm_runtime.into(),
+        )
+    }
+}
+
+impl From<wit_entrypoints::VmRuntime> for VmRuntime {
+    fn from(vm_runtime: wit_entrypoints::VmRuntime) -> Self {
+        match vm_runtime {
+            wit_entrypoints::VmRuntime::Wasm => VmRuntime::Wasm,
+            wit_entrypoints::VmRuntime::Evm => VmRuntime::Evm,
+        }
+    }
+}
+
 impl From<wit_entrypoints::StreamName> for StreamName {
     fn from(stream_name: wit_entrypoints::StreamName) -> Self {
         StreamName(stream_name.inner0)
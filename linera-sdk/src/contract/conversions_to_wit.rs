@@ -6,8 +6,10 @@
 use linera_base::{
     crypto::CryptoHash,
     data_types::{
-        Amount, ApplicationPermissions, BlockHeight, Resources, SendMessageRequest, TimeDelta,
+        Amount, ApplicationPermissions, BlockHeight, HttpPolicy, Resources, SendMessageRequest,
+        TimeDelta,
     },
+    http,
     identifiers::{Account, AccountOwner, ApplicationId, ChainId, MessageId, ModuleId, StreamName},
     ownership::{ChainOwnership, TimeoutConfig},
     vm::VmRuntime,
@@ -129,6 +131,56 @@ impl From<ApplicationId> for wit_contract_api::ApplicationId {
     }
 }
 
+impl From<http::Request> for wit_contract_api::HttpRequest {
+    fn from(request: http::Request) -> Self {
+        wit_contract_api::HttpRequest {
+            method: request.method.into(),
+            url: request.url,
+            headers: request
+                .headers
+                .into_iter()
+                .map(http::Header::into)
+                .collect(),
+            body: request.body,
+        }
+    }
+}
+
+impl From<http::Method> for wit_contract_api::HttpMethod {
+    fn from(method: http::Method) -> Self {
+        match method {
+            http::Method::Get => wit_contract_api::HttpMethod::Get,
+            http::Method::Post => wit_contract_api::HttpMethod::Post,
+            http::Method::Put => wit_contract_api::HttpMethod::Put,
+            http::Method::Delete => wit_contract_api::HttpMethod::Delete,
+            http::Method::Head => wit_contract_api::HttpMethod::Head,
+            http::Method::Options => wit_contract_api::HttpMethod::Options,
+            http::Method::Connect => wit_contract_api::HttpMethod::Connect,
+            http::Method::Patch => wit_contract_api::HttpMethod::Patch,
+            http::Method::Trace => wit_contract_api::HttpMethod::Trace,
+        }
+    }
+}
+
+impl From<http::Header> for wit_contract_api::HttpHeader {
+    fn from(header: http::Header) -> Self {
+        wit_contract_api::HttpHeader {
+            name: header.name,
+            value: header.value,
+        }
+    }
+}
+
+impl From<http::Reducer> for wit_contract_api::HttpReducer {
+    fn from(reducer: http::Reducer) -> Self {
+        match reducer {
+            http::Reducer::ExactMatch => wit_contract_api::HttpReducer::ExactMatch,
+            http::Reducer::Median(pointers) => wit_contract_api::HttpReducer::Median(pointers),
+            http::Reducer::Majority => wit_contract_api::HttpReducer::Majority,
+        }
+    }
+}
+
 impl From<Resources> for wit_contract_api::Resources {
     fn from(resources: Resources) -> Self {
         wit_contract_api::Resources {
@@ -205,6 +257,7 @@ impl From<ApplicationPermissions> for wit_contract_api::ApplicationPermissions {
             change_application_permissions,
             call_service_as_oracle,
             make_http_requests,
+            http_policies,
         } = permissions;
         Self {
             execute_operations: execute_operations
@@ -219,6 +272,28 @@ impl From<ApplicationPermissions> for wit_contract_api::ApplicationPermissions {
                 .map(|app_ids| app_ids.into_iter().map(Into::into).collect()),
             make_http_requests: make_http_requests
                 .map(|app_ids| app_ids.into_iter().map(Into::into).collect()),
+            http_policies: http_policies.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+impl From<HttpPolicy> for wit_contract_api::HttpPolicy {
+    fn from(policy: HttpPolicy) -> Self {
+        let HttpPolicy {
+            application_id,
+            url_prefixes,
+            methods,
+            maximum_request_bytes,
+            maximum_response_bytes,
+            maximum_requests_per_block,
+        } = policy;
+        Self {
+            application_id: application_id.into(),
+            url_prefixes,
+            methods: methods.into_iter().map(Into::into).collect(),
+            maximum_request_bytes,
+            maximum_response_bytes,
+            maximum_requests_per_block,
         }
     }
 }
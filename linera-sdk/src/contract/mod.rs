@@ -98,6 +98,16 @@ macro_rules! contract {
                 )
             }
 
+            fn migrate(
+                previous_module: $crate::contract::wit::exports::linera::app::contract_entrypoints::ModuleId,
+            ) {
+                use $crate::util::BlockingWait;
+                $crate::contract::run_async_entrypoint::<$contract, _, _>(
+                    unsafe { &mut CONTRACT },
+                    move |contract| contract.migrate(previous_module.into()).blocking_wait(),
+                )
+            }
+
             fn finalize() {
                 use $crate::util::BlockingWait;
 
@@ -316,6 +316,35 @@ where
         serde_json::from_slice(&response).expect("Failed to deserialize service response")
     }
 
+    /// Queries an application service on another chain as an oracle, in the chain's state
+    /// after the block at `height`, and returns the response.
+    ///
+    /// Validators answer from their own copy of the other chain: the chain must have reached
+    /// `height` there, and if it has advanced further, the validators must still keep the
+    /// state history of `height`. The retention of that history is configured by each
+    /// validator, so queries at older heights may be answered by some validators and not by
+    /// others; the block fails unless a quorum can answer. The service runs with the same
+    /// time limit as [`ContractRuntime::query_service`].
+    ///
+    /// Cannot be used in fast blocks: A block using this call should be proposed by a regular
+    /// owner, not a super owner.
+    pub fn query_remote_application<A: ServiceAbi + Send>(
+        &mut self,
+        chain_id: ChainId,
+        height: BlockHeight,
+        application_id: ApplicationId<A>,
+        query: A::Query,
+    ) -> A::QueryResponse {
+        let query = serde_json::to_vec(&query).expect("Failed to serialize service query");
+        let response = contract_wit::query_remote_application(
+            chain_id.into(),
+            height.into(),
+            application_id.forget_abi().into(),
+            &query,
+        );
+        serde_json::from_slice(&response).expect("Failed to deserialize service response")
+    }
+
     /// Opens a new chain, configuring it with the provided `chain_ownership`,
     /// `application_permissions` and initial `balance` (debited from the current chain).
     pub fn open_chain(
@@ -378,10 +407,54 @@ where
         ApplicationId::from(application_id).with_abi::<Abi>()
     }
 
+    /// Upgrades the application `application_id` to the module `module_id`.
+    ///
+    /// The current chain must be the creator chain of `application_id`, and the current
+    /// application its upgrade authority. Other chains follow the upgrade when processing
+    /// their inboxes. The upgrade
+    /// takes effect, and the application's [`Contract::migrate`][crate::Contract::migrate]
+    /// entry point is called, once the current transaction is complete.
+    pub fn upgrade_application(&mut self, application_id: ApplicationId, module_id: ModuleId) {
+        contract_wit::upgrade_application(application_id.into(), module_id.into())
+    }
+
     /// Returns the round in which this block was validated.
     pub fn validation_round(&mut self) -> Option<u32> {
         contract_wit::validation_round()
     }
+
+    /// Checks that the signed `responses` to an HTTP `request` were collected for this
+    /// block from a quorum of validators, and reduces them to a single response.
+    ///
+    /// Unlike [`ContractRuntime::http_request`], this allows using endpoints whose responses
+    /// differ slightly between validators, e.g. prices. The responses are collected by the
+    /// client before proposing the block, and included in the operation.
+    pub fn verify_http_responses(
+        &mut self,
+        request: http::Request,
+        reducer: http::Reducer,
+        responses: &[http::SignedResponse],
+    ) -> http::Response {
+        let responses =
+            bcs::to_bytes(responses).expect("Failed to serialize signed HTTP responses");
+        contract_wit::verify_http_responses(&request.into(), &reducer.into(), &responses).into()
+    }
+
+    /// Returns 32 random bytes, derived from the randomness beacon of the current block.
+    /// Each call returns different bytes.
+    ///
+    /// The block must contain a `ProvideRandomness` system operation before the operation or
+    /// message that uses this, with the shares of a quorum of validators for the block.
+    /// These are collected by the client before proposing the block, so the bytes are
+    /// unpredictable for the application's users, but not for the block proposer.
+    ///
+    /// Cannot be used in fast blocks: A block using this call should be proposed by a regular
+    /// owner, not a super owner.
+    pub fn random_bytes(&mut self) -> [u8; 32] {
+        contract_wit::random_bytes()
+            .try_into()
+            .expect("Random bytes should have a length of 32")
+    }
 }
 
 /// A helper type that uses the builder pattern to configure how a message is sent, and then
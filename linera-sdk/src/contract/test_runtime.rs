@@ -59,11 +59,14 @@ where
     events: BTreeMap<(ChainId, StreamName, u32), Vec<u8>>,
     claim_requests: Vec<ClaimRequest>,
     expected_service_queries: VecDeque<(ApplicationId, String, String)>,
+    expected_remote_queries: VecDeque<(ChainId, BlockHeight, ApplicationId, String, String)>,
     expected_http_requests: VecDeque<(http::Request, http::Response)>,
+    expected_random_bytes: VecDeque<[u8; 32]>,
     expected_read_data_blob_requests: VecDeque<(DataBlobHash, Vec<u8>)>,
     expected_assert_data_blob_exists_requests: VecDeque<(DataBlobHash, Option<()>)>,
     expected_open_chain_calls: VecDeque<(ChainOwnership, ApplicationPermissions, Amount, ChainId)>,
     expected_create_application_calls: VecDeque<ExpectedCreateApplicationCall>,
+    application_upgrades: Vec<(ApplicationId, ModuleId)>,
     key_value_store: KeyValueStore,
 }
 
@@ -106,11 +109,14 @@ where
             events: BTreeMap::new(),
             claim_requests: Vec::new(),
             expected_service_queries: VecDeque::new(),
+            expected_remote_queries: VecDeque::new(),
             expected_http_requests: VecDeque::new(),
+            expected_random_bytes: VecDeque::new(),
             expected_read_data_blob_requests: VecDeque::new(),
             expected_assert_data_blob_exists_requests: VecDeque::new(),
             expected_open_chain_calls: VecDeque::new(),
             expected_create_application_calls: VecDeque::new(),
+            application_upgrades: Vec::new(),
             key_value_store: KeyValueStore::mock().to_mut(),
         }
     }
@@ -740,6 +746,17 @@ where
         application_id.with_abi::<Abi>()
     }
 
+    /// Upgrades the application `application_id` on the current chain to the module
+    /// `module_id`.
+    pub fn upgrade_application(&mut self, application_id: ApplicationId, module_id: ModuleId) {
+        self.application_upgrades.push((application_id, module_id));
+    }
+
+    /// Returns the application upgrades requested during the test so far.
+    pub fn application_upgrades(&self) -> &[(ApplicationId, ModuleId)] {
+        &self.application_upgrades
+    }
+
     /// Configures the handler for cross-application calls made during the test.
     pub fn with_call_application_handler(
         mut self,
@@ -840,11 +857,37 @@ where
             .push_back((application_id.forget_abi(), query, response));
     }
 
+    /// Adds an expected `query_remote_application` call, and the response it should return in
+    /// the test.
+    pub fn add_expected_remote_query<A: ServiceAbi + Send>(
+        &mut self,
+        chain_id: ChainId,
+        height: BlockHeight,
+        application_id: ApplicationId<A>,
+        query: A::Query,
+        response: A::QueryResponse,
+    ) {
+        let query = serde_json::to_string(&query).expect("Failed to serialize query");
+        let response = serde_json::to_string(&response).expect("Failed to serialize response");
+        self.expected_remote_queries.push_back((
+            chain_id,
+            height,
+            application_id.forget_abi(),
+            query,
+            response,
+        ));
+    }
+
     /// Adds an expected `http_request` call, and the response it should return in the test.
     pub fn add_expected_http_request(&mut self, request: http::Request, response: http::Response) {
         self.expected_http_requests.push_back((request, response));
     }
 
+    /// Adds an expected `random_bytes` call, and the bytes it should return in the test.
+    pub fn add_expected_random_bytes(&mut self, bytes: [u8; 32]) {
+        self.expected_random_bytes.push_back(bytes);
+    }
+
     /// Adds an expected `read_data_blob` call, and the response it should return in the test.
     pub fn add_expected_read_data_blob_requests(&mut self, hash: DataBlobHash, response: Vec<u8>) {
         self.expected_read_data_blob_requests
@@ -882,6 +925,26 @@ where
         serde_json::from_str(&response).expect("Failed to deserialize response")
     }
 
+    /// Queries an application service on another chain as an oracle, in the chain's state
+    /// after the block at `height`, and returns the response.
+    pub fn query_remote_application<A: ServiceAbi + Send>(
+        &mut self,
+        chain_id: ChainId,
+        height: BlockHeight,
+        application_id: ApplicationId<A>,
+        query: A::Query,
+    ) -> A::QueryResponse {
+        let maybe_query = self.expected_remote_queries.pop_front();
+        let (expected_chain_id, expected_height, expected_id, expected_query, response) =
+            maybe_query.expect("Unexpected remote query");
+        assert_eq!(chain_id, expected_chain_id);
+        assert_eq!(height, expected_height);
+        assert_eq!(application_id.forget_abi(), expected_id);
+        let query = serde_json::to_string(&query).expect("Failed to serialize query");
+        assert_eq!(query, expected_query);
+        serde_json::from_str(&response).expect("Failed to deserialize response")
+    }
+
     /// Makes an HTTP `request` as an oracle and returns the HTTP response.
     ///
     /// Should only be used with queries where it is very likely that all validators will receive
@@ -926,6 +989,32 @@ where
     pub fn validation_round(&mut self) -> Option<u32> {
         self.round
     }
+
+    /// Reduces the signed `responses` to an HTTP `request`, giving each of them the same
+    /// weight.
+    ///
+    /// Signatures are not checked in tests.
+    pub fn verify_http_responses(
+        &mut self,
+        _request: http::Request,
+        reducer: http::Reducer,
+        responses: &[http::SignedResponse],
+    ) -> http::Response {
+        let responses = responses
+            .iter()
+            .map(|signed_response| (&signed_response.response, 1))
+            .collect::<Vec<_>>();
+        reducer
+            .reduce(&responses)
+            .expect("HTTP responses cannot be reduced")
+    }
+
+    /// Returns 32 random bytes, derived from the randomness beacon of the current block.
+    pub fn random_bytes(&mut self) -> [u8; 32] {
+        self.expected_random_bytes
+            .pop_front()
+            .expect("Unexpected `random_bytes` call")
+    }
 }
 
 /// A type alias for the handler for cross-application calls.
@@ -0,0 +1,579 @@
+// Copyright (c) Zefchain Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Property-based testing of applications on a [`TestValidator`].
+//!
+//! A [`ContractFuzzer`] generates random sequences of [`Step`]s: operations, incoming messages,
+//! received or rejected (and therefore bouncing) message bundles and clock advances. The steps
+//! are executed against a fresh [`TestValidator`] for every test case, and user-defined
+//! invariants are checked after every step.
+//!
+//! Failing cases are shrunk by [`proptest`] into a minimal script, printed as JSON so that it
+//! can be replayed with [`ContractFuzzer::replay`].
+//!
+//! ```ignore
+//! use linera_sdk::fuzz::{proptest::prelude::*, ContractFuzzer, FuzzEnvironment};
+//!
+//! #[test]
+//! fn counter_stays_bounded() {
+//!     ContractFuzzer::<counter::CounterAbi>::new(|validator| async move {
+//!         let publisher = validator.new_chain().await;
+//!         let module_id = publisher.publish_current_module().await;
+//!         let mut chain = validator.new_chain().await;
+//!         let application_id = chain.create_application(module_id, (), 0_u64, vec![]).await;
+//!         FuzzEnvironment::new(validator, application_id, vec![chain])
+//!     })
+//!     .with_operations(|_environment| (0_u64..100).boxed())
+//!     .with_invariant("value stays bounded", |environment| {
+//!         Box::pin(async move {
+//!             let chain = &environment.chains[0];
+//!             let response = chain
+//!                 .graphql_query(environment.application_id, "query { value }")
+//!                 .await
+//!                 .response;
+//!             let value = response["value"].as_u64().ok_or("Missing value")?;
+//!             if value < 100 * 20 {
+//!                 Ok(())
+//!             } else {
+//!                 Err(format!("Unexpected value {value}"))
+//!             }
+//!         })
+//!     })
+//!     .run();
+//! }
+//! ```
+
+use std::{fmt::Debug, future::Future};
+
+use futures::future::LocalBoxFuture;
+use linera_base::{
+    crypto::CryptoHash,
+    data_types::{Amount, BlockHeight, TimeDelta},
+    identifiers::{ApplicationId, ChainId},
+};
+use linera_chain::{
+    data_types::{IncomingBundle, MessageAction, MessageBundle, PostedMessage},
+    ChainError,
+};
+use linera_core::worker::WorkerError;
+use linera_execution::{ExecutionError, MessageKind, WasmExecutionError};
+pub use proptest;
+use proptest::{
+    prelude::*,
+    strategy::{Union, ValueTree},
+    test_runner::{Config, TestRunner},
+};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+use crate::{
+    test::{ActiveChain, TestValidator},
+    ContractAbi,
+};
+
+/// A single step of a generated test case.
+///
+/// Chains are referenced by their index in [`FuzzEnvironment::chains`], modulo the number of
+/// chains.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub enum Step<Operation, Message> {
+    /// Adds a block with the `operation` to a chain.
+    Operation {
+        /// The index of the chain.
+        chain: u8,
+        /// The application's operation.
+        operation: Operation,
+    },
+    /// Delivers the `message` to a chain, as if it had been sent by another instance of the
+    /// application.
+    Message {
+        /// The index of the chain.
+        chain: u8,
+        /// The application's message.
+        message: Message,
+    },
+    /// Adds a block accepting all the message bundles in a chain's inboxes.
+    ReceiveMessages {
+        /// The index of the chain.
+        chain: u8,
+    },
+    /// Adds a block rejecting all the message bundles in a chain's inboxes, except protected
+    /// ones. Tracked messages bounce back to their sender.
+    RejectMessages {
+        /// The index of the chain.
+        chain: u8,
+    },
+    /// Advances the validators' clock.
+    AdvanceClock {
+        /// The number of microseconds to advance the clock by.
+        micros: u64,
+    },
+}
+
+/// The chains and application a [`ContractFuzzer`] runs its test cases against.
+pub struct FuzzEnvironment<Abi> {
+    /// The validator running the chains.
+    pub validator: TestValidator,
+    /// The application under test.
+    pub application_id: ApplicationId<Abi>,
+    /// The chains the steps are executed on.
+    pub chains: Vec<ActiveChain>,
+}
+
+impl<Abi> FuzzEnvironment<Abi> {
+    /// Creates a new [`FuzzEnvironment`] to execute steps on the `chains`.
+    pub fn new(
+        validator: TestValidator,
+        application_id: ApplicationId<Abi>,
+        chains: Vec<ActiveChain>,
+    ) -> Self {
+        assert!(
+            !chains.is_empty(),
+            "At least one chain is needed to run steps"
+        );
+        FuzzEnvironment {
+            validator,
+            application_id,
+            chains,
+        }
+    }
+
+    /// Returns the chain referenced by a [`Step`].
+    pub fn chain(&self, index: u8) -> &ActiveChain {
+        &self.chains[usize::from(index) % self.chains.len()]
+    }
+
+    /// Returns the IDs of the chains, used to check that the setup is deterministic.
+    fn chain_ids(&self) -> Vec<ChainId> {
+        self.chains.iter().map(ActiveChain::id).collect()
+    }
+}
+
+type Setup<Abi> = Box<dyn Fn(TestValidator) -> LocalBoxFuture<'static, FuzzEnvironment<Abi>>>;
+
+type StrategyBuilder<Abi, T> = Box<dyn Fn(&FuzzEnvironment<Abi>) -> BoxedStrategy<T>>;
+
+type Invariant<Abi> =
+    Box<dyn for<'a> Fn(&'a FuzzEnvironment<Abi>) -> LocalBoxFuture<'a, Result<(), String>>>;
+
+/// Generates random sequences of [`Step`]s for an application, and checks invariants after
+/// every step.
+///
+/// The `Message` type parameter is the contract's message type, which only needs to be
+/// specified if messages are generated with [`ContractFuzzer::with_messages`].
+pub struct ContractFuzzer<Abi: ContractAbi, Message = ()> {
+    setup: Setup<Abi>,
+    operations: Option<StrategyBuilder<Abi, Abi::Operation>>,
+    messages: Option<StrategyBuilder<Abi, Message>>,
+    invariants: Vec<(String, Invariant<Abi>)>,
+    seed: u64,
+    cases: u32,
+    max_steps: usize,
+    max_clock_advance: TimeDelta,
+    panics_as_rejections: bool,
+}
+
+impl<Abi, Message> ContractFuzzer<Abi, Message>
+where
+    Abi: ContractAbi + 'static,
+    Message: Debug + Serialize + DeserializeOwned + 'static,
+{
+    /// Creates a new [`ContractFuzzer`] using `setup` to create the [`FuzzEnvironment`] of every
+    /// test case.
+    ///
+    /// The `setup` receives a [`TestValidator`] created with [`TestValidator::with_seed`], and must
+    /// only generate keys through it, so that every test case gets the same chain and
+    /// application IDs.
+    pub fn new<SetupFuture>(setup: impl Fn(TestValidator) -> SetupFuture + 'static) -> Self
+    where
+        SetupFuture: Future<Output = FuzzEnvironment<Abi>> + 'static,
+    {
+        ContractFuzzer {
+            setup: Box::new(move |validator| Box::pin(setup(validator))),
+            operations: None,
+            messages: None,
+            invariants: Vec::new(),
+            seed: 0,
+            cases: 32,
+            max_steps: 20,
+            max_clock_advance: TimeDelta::from_secs(60),
+            panics_as_rejections: false,
+        }
+    }
+
+    /// Generates operations with the strategy returned by `strategy`.
+    ///
+    /// The strategy is created once, from an environment identical to the one of every test
+    /// case, so that it can refer to its chains and accounts.
+    pub fn with_operations(
+        mut self,
+        strategy: impl Fn(&FuzzEnvironment<Abi>) -> BoxedStrategy<Abi::Operation> + 'static,
+    ) -> Self {
+        self.operations = Some(Box::new(strategy));
+        self
+    }
+
+    /// Generates incoming messages with the strategy returned by `strategy`.
+    pub fn with_messages(
+        mut self,
+        strategy: impl Fn(&FuzzEnvironment<Abi>) -> BoxedStrategy<Message> + 'static,
+    ) -> Self {
+        self.messages = Some(Box::new(strategy));
+        self
+    }
+
+    /// Adds an invariant that must hold after every step.
+    pub fn with_invariant(
+        mut self,
+        name: impl Into<String>,
+        invariant: impl for<'a> Fn(&'a FuzzEnvironment<Abi>) -> LocalBoxFuture<'a, Result<(), String>>
+            + 'static,
+    ) -> Self {
+        self.invariants.push((name.into(), Box::new(invariant)));
+        self
+    }
+
+    /// Configures the seed of the keys of the [`TestValidator`].
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    /// Configures the number of test cases to generate.
+    pub fn with_cases(mut self, cases: u32) -> Self {
+        self.cases = cases;
+        self
+    }
+
+    /// Configures the maximum number of steps in a test case.
+    pub fn with_max_steps(mut self, max_steps: usize) -> Self {
+        self.max_steps = max_steps;
+        self
+    }
+
+    /// Configures the maximum duration the clock is advanced by in a single step.
+    pub fn with_max_clock_advance(mut self, max_clock_advance: TimeDelta) -> Self {
+        self.max_clock_advance = max_clock_advance;
+        self
+    }
+
+    /// Treats blocks rejected because the application's Wasm module trapped or panicked as
+    /// expected rejections, for applications that reject operations and messages with
+    /// `panic!` or `assert!`.
+    ///
+    /// By default, traps and panics fail the test case.
+    pub fn with_panics_as_rejections(mut self) -> Self {
+        self.panics_as_rejections = true;
+        self
+    }
+
+    /// Runs the test cases.
+    ///
+    /// Use [`ContractFuzzer::run_async`] from inside an asynchronous runtime, such as in a
+    /// `#[tokio::test]`.
+    ///
+    /// # Panics
+    ///
+    /// If a step or an invariant fails, with the minimal failing script.
+    pub fn run(&self) {
+        block_on(self.run_async());
+    }
+
+    /// Runs the test cases on the current asynchronous runtime.
+    ///
+    /// # Panics
+    ///
+    /// If a step or an invariant fails, with the minimal failing script.
+    pub async fn run_async(&self) {
+        let reference = (self.setup)(TestValidator::with_seed(1, 1, self.seed).await).await;
+        let strategy =
+            proptest::collection::vec(self.step_strategy(&reference), 1..=self.max_steps);
+        let expected_chain_ids = reference.chain_ids();
+        drop(reference);
+
+        let config = Config {
+            cases: self.cases,
+            failure_persistence: None,
+            ..Config::default()
+        };
+        let max_shrink_iters = config.max_shrink_iters;
+        let mut runner = TestRunner::new(config);
+
+        for _ in 0..self.cases {
+            let mut case = strategy
+                .new_tree(&mut runner)
+                .unwrap_or_else(|reason| panic!("Property test aborted: {reason}"));
+            let Err(mut reason) = self
+                .execute(&case.current(), Some(&expected_chain_ids[..]))
+                .await
+            else {
+                continue;
+            };
+            let mut steps = case.current();
+
+            // Shrinks the failing case the same way `TestRunner::run` does.
+            if case.simplify() {
+                for _ in 0..max_shrink_iters {
+                    let candidate = case.current();
+                    let can_shrink = match self
+                        .execute(&candidate, Some(&expected_chain_ids[..]))
+                        .await
+                    {
+                        Err(failure) => {
+                            reason = failure;
+                            steps = candidate;
+                            case.simplify()
+                        }
+                        Ok(()) => case.complicate(),
+                    };
+                    if !can_shrink {
+                        break;
+                    }
+                }
+            }
+
+            let script = serde_json::to_string_pretty(&steps).expect("Failed to serialize steps");
+            panic!(
+                "{reason}\nMinimal failing script, to replay with `ContractFuzzer::replay`:\n\
+                {script}"
+            );
+        }
+    }
+
+    /// Replays a `script` printed by a failed [`ContractFuzzer::run`].
+    ///
+    /// # Panics
+    ///
+    /// If a step or an invariant fails.
+    pub fn replay(&self, script: &str) {
+        block_on(self.replay_async(script));
+    }
+
+    /// Replays a `script` printed by a failed [`ContractFuzzer::run`] on the current
+    /// asynchronous runtime.
+    ///
+    /// # Panics
+    ///
+    /// If a step or an invariant fails.
+    pub async fn replay_async(&self, script: &str) {
+        let steps: Vec<Step<Abi::Operation, Message>> =
+            serde_json::from_str(script).expect("Failed to deserialize script");
+        if let Err(error) = self.execute(&steps, None).await {
+            panic!("{error}");
+        }
+    }
+
+    /// Returns the strategy to generate a single step.
+    fn step_strategy(
+        &self,
+        environment: &FuzzEnvironment<Abi>,
+    ) -> BoxedStrategy<Step<Abi::Operation, Message>> {
+        let max_micros = self.max_clock_advance.as_micros();
+        let mut steps = vec![
+            (
+                2,
+                any::<u8>()
+                    .prop_map(|chain| Step::ReceiveMessages { chain })
+                    .boxed(),
+            ),
+            (
+                1,
+                any::<u8>()
+                    .prop_map(|chain| Step::RejectMessages { chain })
+                    .boxed(),
+            ),
+            (
+                1,
+                (0..=max_micros)
+                    .prop_map(|micros| Step::AdvanceClock { micros })
+                    .boxed(),
+            ),
+        ];
+        if let Some(operations) = &self.operations {
+            let step = (any::<u8>(), operations(environment))
+                .prop_map(|(chain, operation)| Step::Operation { chain, operation });
+            steps.push((4, step.boxed()));
+        }
+        if let Some(messages) = &self.messages {
+            let step = (any::<u8>(), messages(environment))
+                .prop_map(|(chain, message)| Step::Message { chain, message });
+            steps.push((2, step.boxed()));
+        }
+        Union::new_weighted(steps).boxed()
+    }
+
+    /// Executes the `steps` in a new environment, checking the invariants after each of them.
+    async fn execute(
+        &self,
+        steps: &[Step<Abi::Operation, Message>],
+        expected_chain_ids: Option<&[ChainId]>,
+    ) -> Result<(), String> {
+        let environment = (self.setup)(TestValidator::with_seed(1, 1, self.seed).await).await;
+        if let Some(expected_chain_ids) = expected_chain_ids {
+            assert_eq!(
+                environment.chain_ids(),
+                expected_chain_ids,
+                "The setup must be deterministic: only use the keys generated by the validator"
+            );
+        }
+        let mut next_message_height = BlockHeight::ZERO;
+
+        for (index, step) in steps.iter().enumerate() {
+            self.execute_step(&environment, step, &mut next_message_height)
+                .await
+                .map_err(|error| format!("Step {index} ({step:?}) failed: {error}"))?;
+            for (name, invariant) in &self.invariants {
+                invariant(&environment).await.map_err(|error| {
+                    format!("Invariant {name:?} violated after step {index} ({step:?}): {error}")
+                })?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Executes a single `step`.
+    ///
+    /// Blocks rejected by the validator are ignored, like a client would discard them, unless
+    /// the error is unexpected (see [`ContractFuzzer::check_block_error`]).
+    async fn execute_step(
+        &self,
+        environment: &FuzzEnvironment<Abi>,
+        step: &Step<Abi::Operation, Message>,
+        next_message_height: &mut BlockHeight,
+    ) -> Result<(), String> {
+        let now = environment.validator.clock().current_time();
+        let result = match step {
+            Step::Operation { chain, operation } => {
+                let application_id = environment.application_id.forget_abi();
+                let bytes =
+                    Abi::serialize_operation(operation).expect("Failed to serialize operation");
+                environment
+                    .chain(*chain)
+                    .try_add_block(|block| {
+                        block
+                            .with_timestamp(now)
+                            .with_raw_operation(application_id, bytes);
+                    })
+                    .await
+            }
+            Step::Message { chain, message } => {
+                let bytes = bcs::to_bytes(message).expect("Failed to serialize message");
+                let height = *next_message_height;
+                *next_message_height = height.try_add_one().expect("Too many messages");
+                let bundle = IncomingBundle {
+                    origin: ChainId(CryptoHash::test_hash("fuzzed message origin")),
+                    bundle: MessageBundle {
+                        height,
+                        timestamp: now,
+                        certificate_hash: CryptoHash::test_hash(format!("fuzzed message {height}")),
+                        transaction_index: 0,
+                        messages: vec![PostedMessage {
+                            authenticated_signer: None,
+                            grant: Amount::ZERO,
+                            refund_grant_to: None,
+                            kind: MessageKind::Simple,
+                            index: 0,
+                            message: linera_execution::Message::User {
+                                application_id: environment.application_id.forget_abi(),
+                                bytes,
+                            },
+                        }],
+                    },
+                    action: MessageAction::Accept,
+                };
+                environment
+                    .chain(*chain)
+                    .try_add_block(|block| {
+                        block.with_timestamp(now).with_incoming_bundles([bundle]);
+                    })
+                    .await
+            }
+            Step::ReceiveMessages { chain } | Step::RejectMessages { chain } => {
+                let chain = environment.chain(*chain);
+                let mut bundles = chain.pending_message_bundles().await;
+                if bundles.is_empty() {
+                    return Ok(());
+                }
+                if matches!(step, Step::RejectMessages { .. }) {
+                    for bundle in &mut bundles {
+                        if !bundle.bundle.is_protected() {
+                            bundle.action = MessageAction::Reject;
+                        }
+                    }
+                }
+                chain
+                    .try_add_block(|block| {
+                        block.with_timestamp(now).with_incoming_bundles(bundles);
+                    })
+                    .await
+            }
+            Step::AdvanceClock { micros } => {
+                environment
+                    .validator
+                    .clock()
+                    .add(TimeDelta::from_micros(*micros));
+                return Ok(());
+            }
+        };
+
+        match result {
+            Ok(_) => Ok(()),
+            Err(error) => self.check_block_error(error),
+        }
+    }
+
+    /// Checks if the `error` a block was rejected with is expected.
+    ///
+    /// Errors reported by the application, or by the system for invalid operations and messages,
+    /// are expected. Traps and panics of the application's Wasm module are only expected if
+    /// [`ContractFuzzer::with_panics_as_rejections`] was used. Any other error, for instance
+    /// from storage, fails the test case.
+    fn check_block_error(&self, error: WorkerError) -> Result<(), String> {
+        let WorkerError::ChainError(chain_error) = &error else {
+            return Err(format!("Unexpected error when adding a block: {error}"));
+        };
+        let ChainError::ExecutionError(execution_error, _) = &**chain_error else {
+            return match &**chain_error {
+                ChainError::ViewError(_) => {
+                    Err(format!("Unexpected error when adding a block: {error}"))
+                }
+                _ => Ok(()),
+            };
+        };
+        match &**execution_error {
+            ExecutionError::WasmError(
+                WasmExecutionError::LoadContractModule(_)
+                | WasmExecutionError::LoadServiceModule(_)
+                | WasmExecutionError::InstrumentModule
+                | WasmExecutionError::InvalidBytecode(_),
+            )
+            | ExecutionError::ViewError(_) => {
+                Err(format!("Unexpected error when adding a block: {error}"))
+            }
+            ExecutionError::WasmError(_) if !self.panics_as_rejections => Err(format!(
+                "Application trapped or panicked: {error}\n\
+                If the application rejects operations or messages by panicking, use \
+                `ContractFuzzer::with_panics_as_rejections`"
+            )),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Runs a `future` to completion on a new runtime.
+///
+/// # Panics
+///
+/// If called from inside another runtime, which Tokio does not support.
+fn block_on<F: Future>(future: F) -> F::Output {
+    assert!(
+        tokio::runtime::Handle::try_current().is_err(),
+        "`ContractFuzzer::run` and `ContractFuzzer::replay` can't be used inside an asynchronous \
+        runtime, use `ContractFuzzer::run_async` and `ContractFuzzer::replay_async` instead"
+    );
+    tokio::runtime::Builder::new_multi_thread()
+        .enable_all()
+        .build()
+        .expect("Failed to create a Tokio runtime")
+        .block_on(future)
+}
@@ -35,6 +35,8 @@ pub mod contract;
 #[cfg(feature = "ethereum")]
 pub mod ethereum;
 mod extensions;
+#[cfg(with_integration_testing)]
+pub mod fuzz;
 pub mod graphql;
 pub mod linera_base_types;
 mod log;
@@ -56,6 +58,7 @@ use linera_base::{
     crypto::CryptoHash,
     data_types::StreamUpdate,
     doc_scalar,
+    identifiers::ModuleId,
 };
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
 pub use serde_json;
@@ -139,6 +142,13 @@ pub trait Contract: WithContractAbi + ContractAbi + Sized {
     /// subscribes to.
     async fn process_streams(&mut self, _updates: Vec<StreamUpdate>) {}
 
+    /// Migrates the application's state after an upgrade.
+    ///
+    /// This is called once on the chain where the application was upgraded, right after the
+    /// upgrade and before any other entry point of the new module, with the module the
+    /// application used before.
+    async fn migrate(&mut self, _previous_module: ModuleId) {}
+
     /// Finishes the execution of the current transaction.
     ///
     /// This is called once at the end of the transaction, to allow all applications that
@@ -0,0 +1,81 @@
+// Copyright (c) Zefchain Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Descriptions of application ABIs, from which `linera project codegen` generates typed
+//! client bindings.
+//!
+//! A description contains the formats of the contract's `Operation` and `Response` types, as
+//! traced by `serde-reflection`, and the result of introspecting the service's GraphQL schema.
+//! Tests export descriptions with [`ActiveChain::export_abi`], which writes them to the
+//! directory named by the `LINERA_ABI_EXPORT_DIRECTORY` environment variable, if it is set.
+
+use std::{fs, path::PathBuf};
+
+use linera_base::abi::ContractAbi;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use serde_reflection::{Format, Registry, Samples, Tracer, TracerConfig};
+
+#[cfg(doc)]
+use super::ActiveChain;
+
+/// The environment variable naming the directory ABI descriptions are exported to.
+pub const ABI_EXPORT_DIRECTORY_VARIABLE: &str = "LINERA_ABI_EXPORT_DIRECTORY";
+
+/// The description of an application's ABI.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct AbiDescription {
+    /// The name of the application, used to name the generated modules.
+    pub name: String,
+    /// The formats of the containers used by the `Operation` and `Response` types, by name.
+    pub formats: Registry,
+    /// The format of the contract's `Operation` type.
+    pub operation: Format,
+    /// The format of the contract's `Response` type.
+    pub response: Format,
+    /// The result of introspecting the service's GraphQL schema.
+    pub graphql: Value,
+}
+
+impl AbiDescription {
+    /// Traces the contract types of the `Abi`, and describes them together with the
+    /// introspected `graphql` schema of the service.
+    ///
+    /// The formats are those of the BCS serialization of the types, so they don't apply to
+    /// ABIs that customize how operations and responses are serialized.
+    pub fn trace<Abi: ContractAbi>(
+        name: impl Into<String>,
+        graphql: Value,
+    ) -> serde_reflection::Result<Self> {
+        let mut tracer = Tracer::new(TracerConfig::default());
+        let samples = Samples::new();
+        let (operation, _) = tracer.trace_type::<Abi::Operation>(&samples)?;
+        let (response, _) = tracer.trace_type::<Abi::Response>(&samples)?;
+        Ok(AbiDescription {
+            name: name.into(),
+            formats: tracer.registry()?,
+            operation,
+            response,
+            graphql,
+        })
+    }
+
+    /// Writes this description to `<name>.json` in the directory named by
+    /// [`ABI_EXPORT_DIRECTORY_VARIABLE`], if the variable is set.
+    ///
+    /// Returns the path of the written file.
+    pub fn export(&self) -> Option<PathBuf> {
+        let directory = PathBuf::from(std::env::var_os(ABI_EXPORT_DIRECTORY_VARIABLE)?);
+        fs::create_dir_all(&directory).expect("Failed to create the ABI export directory");
+        let path = directory.join(format!("{}.json", self.name));
+        let contents =
+            serde_json::to_string_pretty(self).expect("Failed to serialize the ABI description");
+        fs::write(&path, contents).unwrap_or_else(|error| {
+            panic!(
+                "Failed to write the ABI description {}: {error}",
+                path.display()
+            )
+        });
+        Some(path)
+    }
+}
@@ -7,15 +7,20 @@
 
 use linera_base::{
     abi::ContractAbi,
+    crypto::{AccountSecretKey, InMemorySigner},
     data_types::{Amount, ApplicationPermissions, Blob, Epoch, Round, Timestamp},
     identifiers::{AccountOwner, ApplicationId, ChainId},
     ownership::TimeoutConfig,
 };
 use linera_chain::{
     data_types::{
-        IncomingBundle, LiteValue, LiteVote, MessageAction, ProposedBlock, SignatureAggregator,
+        BlockProposal, IncomingBundle, LiteValue, LiteVote, MessageAction, ProposedBlock,
+        SignatureAggregator,
+    },
+    types::{
+        CertificateValue, ConfirmedBlock, ConfirmedBlockCertificate, GenericCertificate,
+        ValidatedBlock,
     },
-    types::{ConfirmedBlock, ConfirmedBlockCertificate},
 };
 use linera_core::worker::WorkerError;
 use linera_execution::{
@@ -23,7 +28,7 @@ use linera_execution::{
     Operation,
 };
 
-use super::TestValidator;
+use super::{TestValidator, TryProposeBlockError};
 
 /// A helper type to build a block proposal using the builder pattern, and then signing them into
 /// [`ConfirmedBlockCertificate`]s using a [`TestValidator`].
@@ -196,12 +201,95 @@ impl BlockBuilder {
 
     /// Tries to sign the prepared block with the [`TestValidator`]'s keys and return the
     /// resulting [`Certificate`]. Returns an error if block execution fails.
+    ///
+    /// The block is signed in the fast round by all the validators of the network, regardless of
+    /// their [`ValidatorBehavior`](super::ValidatorBehavior). Use [`BlockBuilder::try_propose`]
+    /// to go through the consensus protocol instead.
     pub(crate) async fn try_sign(
         self,
         blobs: &[Blob],
     ) -> Result<ConfirmedBlockCertificate, WorkerError> {
-        let published_blobs = self
-            .block
+        let published_blobs = self.published_blobs(blobs);
+        let (block, _) = self
+            .validator
+            .worker(self.block.chain_id)
+            .stage_block_execution(self.block, None, published_blobs)
+            .await?;
+
+        let value = ConfirmedBlock::new(block);
+        let committee = self.validator.committee().await;
+        let mut builder = SignatureAggregator::new(value.clone(), Round::Fast, &committee);
+        let mut certificate = None;
+        for key_pair in self.validator.key_pairs() {
+            let vote = LiteVote::new(LiteValue::new(&value), Round::Fast, key_pair);
+            certificate = builder
+                .append(vote.public_key, vote.signature)
+                .expect("Failed to sign block");
+            if certificate.is_some() {
+                break;
+            }
+        }
+
+        Ok(certificate.expect("The test validators should form a quorum"))
+    }
+
+    /// Tries to propose the prepared block in `round`, signed by the chain owner's `key_pair`,
+    /// and to collect the votes of the [`TestValidator`]'s validators into a
+    /// [`ConfirmedBlockCertificate`].
+    ///
+    /// In the fast round the votes directly confirm the block. In the other rounds, the block is
+    /// first validated and then confirmed, like a client would do. Validators that fail to handle
+    /// a request simply don't vote, e.g. if they are lagging behind; their error is only returned
+    /// if no quorum is reached. Returns [`TryProposeBlockError::NoQuorum`] if not enough
+    /// validators vote.
+    pub(crate) async fn try_propose(
+        self,
+        round: Round,
+        key_pair: &AccountSecretKey,
+        blobs: &[Blob],
+    ) -> Result<ConfirmedBlockCertificate, TryProposeBlockError> {
+        let published_blobs = self.published_blobs(blobs);
+        let chain_id = self.block.chain_id;
+        let (block, _) = self
+            .validator
+            .worker(chain_id)
+            .stage_block_execution(self.block.clone(), None, published_blobs.clone())
+            .await?;
+
+        let owner = AccountOwner::from(key_pair.public());
+        let signer = InMemorySigner::from_iter([(owner, key_pair.copy())]);
+        let proposal = BlockProposal::new_initial(owner, round, self.block, &signer)
+            .await
+            .expect("Failed to sign block proposal");
+
+        let mut votes = Vec::new();
+        for node in self.validator.nodes() {
+            votes.push(
+                node.handle_block_proposal(proposal.clone(), &published_blobs)
+                    .await,
+            );
+        }
+
+        if round.is_fast() {
+            return self
+                .aggregate(ConfirmedBlock::new(block), round, votes)
+                .await;
+        }
+
+        let validated = self
+            .aggregate(ValidatedBlock::new(block.clone()), round, votes)
+            .await?;
+        let mut votes = Vec::new();
+        for node in self.validator.nodes() {
+            votes.push(node.handle_validated_certificate(validated.clone()).await);
+        }
+        self.aggregate(ConfirmedBlock::new(block), round, votes)
+            .await
+    }
+
+    /// Returns the blobs from `blobs` that the prepared block publishes.
+    fn published_blobs(&self, blobs: &[Blob]) -> Vec<Blob> {
+        self.block
             .published_blob_ids()
             .into_iter()
             .map(|blob_id| {
@@ -211,26 +299,47 @@ impl BlockBuilder {
                     .expect("missing published blob")
                     .clone()
             })
-            .collect();
-        let (block, _) = self
-            .validator
-            .worker()
-            .stage_block_execution(self.block, None, published_blobs)
-            .await?;
+            .collect()
+    }
 
-        let value = ConfirmedBlock::new(block);
-        let vote = LiteVote::new(
-            LiteValue::new(&value),
-            Round::Fast,
-            self.validator.key_pair(),
-        );
+    /// Aggregates the `votes` for `value` into a certificate, if they form a quorum.
+    ///
+    /// Otherwise returns the first error a validator reported, if any.
+    async fn aggregate<T>(
+        &self,
+        value: T,
+        round: Round,
+        votes: Vec<Result<Option<LiteVote>, WorkerError>>,
+    ) -> Result<GenericCertificate<T>, TryProposeBlockError>
+    where
+        T: CertificateValue,
+    {
         let committee = self.validator.committee().await;
-        let mut builder = SignatureAggregator::new(value, Round::Fast, &committee);
-        let certificate = builder
-            .append(vote.public_key, vote.signature)
-            .expect("Failed to sign block")
-            .expect("Committee has more than one test validator");
-
-        Ok(certificate)
+        let lite_value = LiteValue::new(&value);
+        let mut builder = SignatureAggregator::new(value, round, &committee);
+        let mut error = None;
+        for vote in votes {
+            let vote = match vote {
+                Ok(Some(vote)) => vote,
+                Ok(None) => continue,
+                Err(worker_error) => {
+                    error.get_or_insert(worker_error);
+                    continue;
+                }
+            };
+            if vote.value != lite_value || vote.round != round {
+                continue;
+            }
+            if let Some(certificate) = builder
+                .append(vote.public_key, vote.signature)
+                .map_err(WorkerError::from)?
+            {
+                return Ok(certificate);
+            }
+        }
+        if let Some(error) = error {
+            return Err(error.into());
+        }
+        Err(TryProposeBlockError::NoQuorum { round })
     }
 }
@@ -18,22 +18,25 @@ use linera_base::{
     crypto::{AccountPublicKey, AccountSecretKey},
     data_types::{
         Amount, ApplicationDescription, Blob, BlockHeight, Bytecode, ChainDescription,
-        CompressedBytecode, Epoch,
+        CompressedBytecode, Epoch, Round,
     },
     identifiers::{AccountOwner, ApplicationId, ChainId, ModuleId},
     vm::VmRuntime,
 };
-use linera_chain::{types::ConfirmedBlockCertificate, ChainExecutionContext};
+use linera_chain::{
+    data_types::{IncomingBundle, LiteValue, SignatureAggregator},
+    types::{ConfirmedBlockCertificate, Timeout, TimeoutCertificate},
+    ChainExecutionContext,
+};
 use linera_core::{data_types::ChainInfoQuery, worker::WorkerError};
 use linera_execution::{
     system::{SystemOperation, SystemQuery, SystemResponse},
     ExecutionError, Operation, Query, QueryOutcome, QueryResponse,
 };
-use linera_storage::Storage as _;
 use serde::Serialize;
 use tokio::{fs, sync::Mutex};
 
-use super::{BlockBuilder, TestValidator};
+use super::{snapshot, AbiDescription, BlockBuilder, TestValidator};
 use crate::{ContractAbi, ServiceAbi};
 
 /// A reference to a single microchain inside a [`TestValidator`].
@@ -98,7 +101,7 @@ impl ActiveChain {
     pub async fn epoch(&self) -> Epoch {
         *self
             .validator
-            .worker()
+            .worker(self.id())
             .chain_state_view(self.id())
             .await
             .expect("Failed to load chain")
@@ -114,7 +117,7 @@ impl ActiveChain {
 
         let QueryOutcome { response, .. } = self
             .validator
-            .worker()
+            .worker(self.id())
             .query_application(self.id(), query)
             .await
             .expect("Failed to query chain's balance");
@@ -130,7 +133,7 @@ impl ActiveChain {
     pub async fn owner_balance(&self, owner: &AccountOwner) -> Option<Amount> {
         let chain_state = self
             .validator
-            .worker()
+            .worker(self.id())
             .chain_state_view(self.id())
             .await
             .expect("Failed to read chain state");
@@ -151,7 +154,7 @@ impl ActiveChain {
     ) -> HashMap<AccountOwner, Option<Amount>> {
         let chain_state = self
             .validator
-            .worker()
+            .worker(self.id())
             .chain_state_view(self.id())
             .await
             .expect("Failed to read chain state");
@@ -177,7 +180,7 @@ impl ActiveChain {
     pub async fn accounts(&self) -> Vec<AccountOwner> {
         let chain_state = self
             .validator
-            .worker()
+            .worker(self.id())
             .chain_state_view(self.id())
             .await
             .expect("Failed to read chain state");
@@ -269,23 +272,119 @@ impl ActiveChain {
         // TODO(#2066): Remove boxing once call-stack is shallower
         let certificate = Box::pin(block.try_sign(&blobs)).await?;
 
-        let result = self
+        self.validator
+            .handle_certificate(&certificate, &blobs)
+            .await
+            .expect("Rejected certificate");
+
+        *tip = Some(certificate.clone());
+
+        Ok(certificate)
+    }
+
+    /// Proposes a block to the validators of this microchain, and returns the certificate
+    /// once a quorum of them confirmed it.
+    ///
+    /// Unlike [`ActiveChain::add_block`], the block goes through the consensus protocol: it is
+    /// proposed in the chain's current round by the owner with the current key pair, so it
+    /// depends on the chain's ownership and on the behavior of the validators.
+    ///
+    /// The `block_builder` parameter is a closure that should use the [`BlockBuilder`] parameter
+    /// to provide the block's contents.
+    pub async fn propose_block(
+        &self,
+        block_builder: impl FnOnce(&mut BlockBuilder),
+    ) -> ConfirmedBlockCertificate {
+        self.try_propose_block(block_builder)
+            .await
+            .expect("Failed to propose block")
+    }
+
+    /// Tries to propose a block to the validators of this microchain, and returns the
+    /// certificate once a quorum of them confirmed it.
+    ///
+    /// The `block_builder` parameter is a closure that should use the [`BlockBuilder`] parameter
+    /// to provide the block's contents.
+    pub async fn try_propose_block(
+        &self,
+        block_builder: impl FnOnce(&mut BlockBuilder),
+    ) -> Result<ConfirmedBlockCertificate, TryProposeBlockError> {
+        let mut tip = self.tip.lock().await;
+        let mut block = BlockBuilder::new(
+            self.description.id(),
+            self.key_pair.public().into(),
+            self.epoch().await,
+            tip.as_ref(),
+            self.validator.clone(),
+        );
+
+        block_builder(&mut block);
+
+        let round = self.current_round().await;
+        // TODO(#2066): Remove boxing once call-stack is shallower
+        let certificate = Box::pin(block.try_propose(round, &self.key_pair, &[])).await?;
+
+        self.validator.handle_certificate(&certificate, &[]).await?;
+
+        *tip = Some(certificate.clone());
+
+        Ok(certificate)
+    }
+
+    /// Returns the current consensus [`Round`] of this microchain.
+    pub async fn current_round(&self) -> Round {
+        let (response, _) = self
             .validator
-            .worker()
-            .fully_handle_certificate_with_notifications(certificate.clone(), &())
-            .await;
-        if let Err(WorkerError::BlobsNotFound(_)) = &result {
-            self.validator.storage().maybe_write_blobs(&blobs).await?;
-            self.validator
-                .worker()
-                .fully_handle_certificate_with_notifications(certificate.clone(), &())
-                .await
-                .expect("Rejected certificate");
-        } else {
-            result.expect("Rejected certificate");
+            .worker(self.id())
+            .handle_chain_info_query(ChainInfoQuery::new(self.id()))
+            .await
+            .expect("Failed to query chain's current round");
+        response.info.manager.current_round
+    }
+
+    /// Requests the validators to time out the current round of this microchain, and returns
+    /// the resulting [`TimeoutCertificate`].
+    ///
+    /// Validators only vote for a timeout once the round's deadline passed on the
+    /// [`TestClock`](linera_storage::TestClock), so the test should advance the clock
+    /// first. The next round starts on all the validators that handle the certificate.
+    pub async fn request_timeout(&self) -> Result<TimeoutCertificate, TryProposeBlockError> {
+        let chain_id = self.id();
+        let (response, _) = self
+            .validator
+            .worker(chain_id)
+            .handle_chain_info_query(ChainInfoQuery::new(chain_id))
+            .await?;
+        let round = response.info.manager.current_round;
+        let value = Timeout::new(
+            chain_id,
+            response.info.next_block_height,
+            response.info.epoch,
+        );
+        let lite_value = LiteValue::new(&value);
+
+        let committee = self.validator.committee().await;
+        let mut builder = SignatureAggregator::new(value, round, &committee);
+        let mut certificate = None;
+        for node in self.validator.nodes() {
+            let Ok(Some(vote)) = node.request_timeout_vote(chain_id).await else {
+                continue;
+            };
+            if vote.value != lite_value || vote.round != round {
+                continue;
+            }
+            certificate = builder
+                .append(vote.public_key, vote.signature)
+                .map_err(WorkerError::from)?;
+            if certificate.is_some() {
+                break;
+            }
         }
+        let certificate = certificate.ok_or(TryProposeBlockError::NoQuorum { round })?;
 
-        *tip = Some(certificate.clone());
+        for node in self.validator.nodes() {
+            node.handle_timeout_certificate(certificate.clone()).await?;
+        }
 
         Ok(certificate)
     }
@@ -295,14 +394,7 @@ impl ActiveChain {
     /// Adds a block to this microchain that receives all queued messages in the microchains
     /// inboxes.
     pub async fn handle_received_messages(&self) {
-        let chain_id = self.id();
-        let (information, _) = self
-            .validator
-            .worker()
-            .handle_chain_info_query(ChainInfoQuery::new(chain_id).with_pending_message_bundles())
-            .await
-            .expect("Failed to query chain's pending messages");
-        let messages = information.info.requested_pending_message_bundles;
+        let messages = self.pending_message_bundles().await;
         // Empty blocks are not allowed.
         // Return early if there are no messages to process and we'd end up with an empty proposal.
         if messages.is_empty() {
@@ -314,13 +406,26 @@ impl ActiveChain {
         .await;
     }
 
+    /// Returns the message bundles queued in the inboxes of this microchain.
+    pub(crate) async fn pending_message_bundles(&self) -> Vec<IncomingBundle> {
+        let chain_id = self.id();
+        let (information, _) = self
+            .validator
+            .worker(chain_id)
+            .handle_chain_info_query(ChainInfoQuery::new(chain_id).with_pending_message_bundles())
+            .await
+            .expect("Failed to query chain's pending messages");
+        information.info.requested_pending_message_bundles
+    }
+
     /// Processes all new events from streams this chain subscribes to.
     ///
     /// Adds a block to this microchain that processes the new events.
     pub async fn handle_new_events(&self) {
         let chain_id = self.id();
-        let worker = self.validator.worker();
-        let subscription_map = worker
+        let subscription_map = self
+            .validator
+            .worker(chain_id)
             .chain_state_view(chain_id)
             .await
             .expect("Failed to query chain state view")
@@ -334,7 +439,7 @@ impl ActiveChain {
         let futures = subscription_map
             .into_iter()
             .map(|((chain_id, stream_id), subscriptions)| {
-                let worker = worker.clone();
+                let worker = self.validator.worker(chain_id);
                 async move {
                     worker
                         .chain_state_view(chain_id)
@@ -535,6 +640,31 @@ impl ActiveChain {
         instantiation_argument: InstantiationArgument,
         required_application_ids: Vec<ApplicationId>,
     ) -> ApplicationId<Abi>
+    where
+        Abi: ContractAbi,
+        Parameters: Serialize,
+        InstantiationArgument: Serialize,
+    {
+        self.create_upgradeable_application(
+            module_id,
+            parameters,
+            instantiation_argument,
+            required_application_ids,
+            None,
+        )
+        .await
+    }
+
+    /// Creates an application on this microchain like [`ActiveChain::create_application`],
+    /// allowing the `upgrade_authority` to upgrade it to other modules later.
+    pub async fn create_upgradeable_application<Abi, Parameters, InstantiationArgument>(
+        &mut self,
+        module_id: ModuleId<Abi, Parameters, InstantiationArgument>,
+        parameters: Parameters,
+        instantiation_argument: InstantiationArgument,
+        required_application_ids: Vec<ApplicationId>,
+        upgrade_authority: Option<AccountOwner>,
+    ) -> ApplicationId<Abi>
     where
         Abi: ContractAbi,
         Parameters: Serialize,
@@ -550,6 +680,7 @@ impl ActiveChain {
                     parameters: parameters.clone(),
                     instantiation_argument,
                     required_application_ids: required_application_ids.clone(),
+                    upgrade_authority,
                 });
             })
             .await;
@@ -570,11 +701,69 @@ impl ActiveChain {
         ApplicationId::<()>::from(&description).with_abi()
     }
 
+    /// Upgrades the application `application_id` to the module referenced by `module_id`, and
+    /// calls the new contract's `migrate` entry point.
+    ///
+    /// This microchain must be the application's creator chain, and its owner the application's
+    /// upgrade authority. Other microchains follow the upgrade with
+    /// [`ActiveChain::process_application_upgrade`].
+    pub async fn upgrade_application<Abi, Parameters, InstantiationArgument>(
+        &self,
+        application_id: ApplicationId<Abi>,
+        module_id: ModuleId<Abi, Parameters, InstantiationArgument>,
+    ) -> ConfirmedBlockCertificate {
+        self.add_block(|block| {
+            block.with_system_operation(SystemOperation::UpgradeApplication {
+                application_id: application_id.forget_abi(),
+                module_id: module_id.forget_abi(),
+            });
+        })
+        .await
+    }
+
+    /// Applies the next upgrade of the application `application_id` that happened on its
+    /// creator chain to this microchain, and calls the new contract's `migrate` entry point.
+    pub async fn process_application_upgrade<Abi>(
+        &self,
+        application_id: ApplicationId<Abi>,
+    ) -> ConfirmedBlockCertificate {
+        self.add_block(|block| {
+            block.with_system_operation(SystemOperation::ProcessApplicationUpgrade(
+                application_id.forget_abi(),
+            ));
+        })
+        .await
+    }
+
+    /// Returns the module that the application `application_id` currently uses on this
+    /// microchain.
+    pub async fn application_module_id<Abi>(&self, application_id: ApplicationId<Abi>) -> ModuleId {
+        let application_id = application_id.forget_abi();
+        let description = self
+            .validator
+            .worker(self.id())
+            .describe_application(self.id(), application_id)
+            .await
+            .expect("Failed to describe application");
+        let chain = self
+            .validator
+            .worker(self.id())
+            .chain_state_view(self.id())
+            .await
+            .expect("Failed to load chain");
+        chain
+            .execution_state
+            .system
+            .current_module_id(application_id, &description)
+            .await
+            .expect("Failed to read the application's upgrades")
+    }
+
     /// Returns whether this chain has been closed.
     pub async fn is_closed(&self) -> bool {
         let chain = self
             .validator
-            .worker()
+            .worker(self.id())
             .chain_state_view(self.id())
             .await
             .expect("Failed to load chain");
@@ -615,12 +804,13 @@ impl ActiveChain {
             operations,
         } = self
             .validator
-            .worker()
+            .worker(self.id())
             .query_application(
                 self.id(),
                 Query::User {
                     application_id: application_id.forget_abi(),
                     bytes: query_bytes,
+                    at_height: None,
                 },
             )
             .await?;
@@ -687,6 +877,68 @@ impl ActiveChain {
         })
     }
 
+    /// Executes a GraphQL `query` on an `application`'s state on this microchain, and checks
+    /// the JSON response against the snapshot `name`.
+    ///
+    /// The snapshot is stored in `tests/snapshots/<name>.json`, and is only recorded if
+    /// snapshots are being updated. See [`assert_json_snapshot`].
+    ///
+    /// [`assert_json_snapshot`]: super::assert_json_snapshot
+    pub async fn assert_graphql_query_snapshot<Abi>(
+        &self,
+        application_id: ApplicationId<Abi>,
+        name: &str,
+        query: impl Into<async_graphql::Request>,
+    ) where
+        Abi: ServiceAbi<Query = async_graphql::Request, QueryResponse = async_graphql::Response>,
+    {
+        let QueryOutcome { response, .. } = self.graphql_query(application_id, query).await;
+        snapshot::assert_json_snapshot(name, &response);
+    }
+
+    /// Checks the GraphQL schema of an `application`'s service against the snapshot `name`.
+    ///
+    /// The schema is rendered in the same SDL format as `linera-schema-export`, and stored in
+    /// `tests/snapshots/<name>.graphql`. See [`assert_sdl_snapshot`].
+    ///
+    /// [`assert_sdl_snapshot`]: super::assert_sdl_snapshot
+    pub async fn assert_graphql_schema_snapshot<Abi>(
+        &self,
+        application_id: ApplicationId<Abi>,
+        name: &str,
+    ) where
+        Abi: ServiceAbi<Query = async_graphql::Request, QueryResponse = async_graphql::Response>,
+    {
+        let QueryOutcome { response, .. } = self
+            .graphql_query(application_id, snapshot::INTROSPECTION_QUERY)
+            .await;
+        snapshot::assert_sdl_snapshot(name, &snapshot::introspection_to_sdl(&response));
+    }
+
+    /// Describes the ABI of an `application`, from its contract types and the schema of its
+    /// GraphQL service, for `linera project codegen` to generate client bindings from.
+    ///
+    /// The description is written to the directory named by the
+    /// `LINERA_ABI_EXPORT_DIRECTORY` environment variable if it is set, e.g. when running
+    /// `linera project codegen`.
+    pub async fn export_abi<Abi>(
+        &self,
+        application_id: ApplicationId<Abi>,
+        name: &str,
+    ) -> AbiDescription
+    where
+        Abi: ContractAbi
+            + ServiceAbi<Query = async_graphql::Request, QueryResponse = async_graphql::Response>,
+    {
+        let QueryOutcome { response, .. } = self
+            .graphql_query(application_id, snapshot::INTROSPECTION_QUERY)
+            .await;
+        let description = AbiDescription::trace::<Abi>(name, response)
+            .unwrap_or_else(|error| panic!("Failed to trace the types of {name:?}: {error}"));
+        description.export();
+        description
+    }
+
     /// Executes a GraphQL `mutation` on an `application` and proposes a block with the resulting
     /// scheduled operations.
     ///
@@ -798,6 +1050,21 @@ impl TryGraphQLQueryError {
     }
 }
 
+/// Failure to propose a block or to time out a round through the consensus protocol.
+#[derive(Debug, thiserror::Error)]
+pub enum TryProposeBlockError {
+    /// A validator rejected the request.
+    #[error("Validator failed to handle the request")]
+    Worker(#[from] WorkerError),
+
+    /// Not enough validators voted to form a certificate.
+    #[error("Not enough validators voted in round {round}")]
+    NoQuorum {
+        /// The round in which the votes were requested.
+        round: Round,
+    },
+}
+
 /// Failure to perform a GraphQL mutation on an application on a chain.
 #[derive(Debug, thiserror::Error)]
 pub enum TryGraphQLMutationError {
@@ -10,12 +10,18 @@
 
 #![cfg(any(with_testing, with_wasm_runtime))]
 
+#[cfg(with_integration_testing)]
+mod abi;
 #[cfg(with_integration_testing)]
 mod block;
 #[cfg(with_integration_testing)]
 mod chain;
 mod mock_stubs;
 #[cfg(with_integration_testing)]
+mod node;
+#[cfg(with_integration_testing)]
+mod snapshot;
+#[cfg(with_integration_testing)]
 mod validator;
 
 #[cfg(with_integration_testing)]
@@ -31,8 +37,16 @@ pub use {
 pub use self::mock_stubs::*;
 #[cfg(with_integration_testing)]
 pub use self::{
+    abi::{AbiDescription, ABI_EXPORT_DIRECTORY_VARIABLE},
     block::BlockBuilder,
-    chain::{ActiveChain, TryGraphQLMutationError, TryGraphQLQueryError, TryQueryError},
+    chain::{
+        ActiveChain, TryGraphQLMutationError, TryGraphQLQueryError, TryProposeBlockError,
+        TryQueryError,
+    },
+    node::ValidatorBehavior,
+    snapshot::{
+        assert_json_snapshot, assert_sdl_snapshot, SnapshotDifference, UPDATE_SNAPSHOTS_VARIABLE,
+    },
     validator::TestValidator,
 };
 use crate::{Contract, ContractRuntime, Service, ServiceRuntime};
@@ -0,0 +1,323 @@
+// Copyright (c) Zefchain Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! A single validator of the network simulated by a [`TestValidator`].
+//!
+//! Each validator has its own storage, shared by several shards. Every shard is a
+//! [`WorkerState`] responsible for a subset of the microchains, and cross-chain requests are
+//! routed between the shards like in a real validator.
+
+use std::{
+    collections::{hash_map::DefaultHasher, VecDeque},
+    hash::{Hash, Hasher},
+    num::NonZeroUsize,
+    sync::Mutex as SyncMutex,
+};
+
+use futures::{lock::Mutex, FutureExt as _};
+use linera_base::{
+    crypto::{ValidatorPublicKey, ValidatorSecretKey},
+    data_types::Blob,
+    identifiers::ChainId,
+};
+use linera_chain::{
+    data_types::{BlockProposal, LiteVote},
+    types::{ConfirmedBlockCertificate, TimeoutCertificate, ValidatedBlockCertificate},
+};
+use linera_core::{
+    data_types::{ChainInfoQuery, ChainInfoResponse, CrossChainRequest},
+    worker::{NetworkActions, WorkerError, WorkerState},
+};
+use linera_execution::WasmRuntime;
+use linera_storage::{DbStorage, Storage as _, TestClock};
+use linera_views::{
+    memory::MemoryStore, random::generate_test_namespace, store::TestKeyValueStore,
+};
+
+#[cfg(doc)]
+use super::TestValidator;
+
+/// How a validator of a [`TestValidator`] network behaves.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum ValidatorBehavior {
+    /// The validator follows the protocol.
+    #[default]
+    Honest,
+    /// The validator handles proposals and certificates, but never returns its votes.
+    WithholdVotes,
+    /// The validator holds back the cross-chain requests between its shards, until they are
+    /// delivered with [`TestValidator::deliver_delayed_cross_chain_requests`] or the behavior
+    /// changes.
+    DelayCrossChainRequests,
+    /// The validator is unreachable. It misses all the requests, and catches up with the
+    /// certificates it missed once its behavior changes.
+    Offline,
+}
+
+/// A validator of a [`TestValidator`] network.
+pub(crate) struct TestNode {
+    secret_key: ValidatorSecretKey,
+    storage: DbStorage<MemoryStore, TestClock>,
+    shards: Vec<WorkerState<DbStorage<MemoryStore, TestClock>>>,
+    behavior: SyncMutex<ValidatorBehavior>,
+    missed_certificates: Mutex<Vec<(ConfirmedBlockCertificate, Vec<Blob>)>>,
+    delayed_requests: Mutex<Vec<CrossChainRequest>>,
+}
+
+impl TestNode {
+    /// Creates a new validator signing with `secret_key`, with `shard_count` shards, using the
+    /// shared `clock`.
+    pub(crate) fn new(
+        name: &str,
+        secret_key: ValidatorSecretKey,
+        shard_count: usize,
+        clock: TestClock,
+    ) -> Self {
+        assert!(shard_count > 0, "A validator needs at least one shard");
+        let wasm_runtime = Some(WasmRuntime::default());
+        let storage = async {
+            let config = MemoryStore::new_test_config().await?;
+            DbStorage::<MemoryStore, _>::new_for_testing(
+                config,
+                &generate_test_namespace(),
+                wasm_runtime,
+                clock,
+            )
+            .await
+        }
+        .now_or_never()
+        .expect("execution of DbStorage::new should not await anything")
+        .expect("Failed to create the validator's storage");
+        let shards = (0..shard_count)
+            .map(|index| {
+                WorkerState::new(
+                    format!("{name} shard {index}"),
+                    Some(secret_key.copy()),
+                    storage.clone(),
+                    NonZeroUsize::new(40).expect("Chain worker limit should not be zero"),
+                )
+            })
+            .collect();
+
+        TestNode {
+            secret_key,
+            storage,
+            shards,
+            behavior: SyncMutex::default(),
+            missed_certificates: Mutex::default(),
+            delayed_requests: Mutex::default(),
+        }
+    }
+
+    /// Returns the key this validator signs its votes with.
+    pub(crate) fn secret_key(&self) -> &ValidatorSecretKey {
+        &self.secret_key
+    }
+
+    /// Returns the public key of this validator.
+    pub(crate) fn public_key(&self) -> ValidatorPublicKey {
+        self.secret_key.public()
+    }
+
+    /// Returns this validator's storage.
+    pub(crate) fn storage(&self) -> &DbStorage<MemoryStore, TestClock> {
+        &self.storage
+    }
+
+    /// Returns the shard responsible for the chain `chain_id`.
+    pub(crate) fn worker(
+        &self,
+        chain_id: ChainId,
+    ) -> WorkerState<DbStorage<MemoryStore, TestClock>> {
+        let mut hasher = DefaultHasher::new();
+        // Use the validator public key to randomise shard assignment.
+        self.public_key().hash(&mut hasher);
+        chain_id.hash(&mut hasher);
+        let shard_id = (hasher.finish() as usize) % self.shards.len();
+        self.shards[shard_id].clone()
+    }
+
+    /// Returns how this validator currently behaves.
+    pub(crate) fn behavior(&self) -> ValidatorBehavior {
+        *self
+            .behavior
+            .lock()
+            .expect("Behavior lock should not be poisoned")
+    }
+
+    /// Changes how this validator behaves, and delivers what it was holding back or
+    /// missed because of its previous behavior.
+    pub(crate) async fn set_behavior(&self, behavior: ValidatorBehavior) {
+        *self
+            .behavior
+            .lock()
+            .expect("Behavior lock should not be poisoned") = behavior;
+        if behavior != ValidatorBehavior::Offline {
+            let missed_certificates = std::mem::take(&mut *self.missed_certificates.lock().await);
+            for (certificate, blobs) in missed_certificates {
+                Box::pin(self.handle_confirmed_certificate(certificate, &blobs))
+                    .await
+                    .expect("Rejected missed certificate");
+            }
+        }
+        if behavior != ValidatorBehavior::DelayCrossChainRequests {
+            self.deliver_delayed_cross_chain_requests().await;
+        }
+    }
+
+    /// Handles a block proposal, returning this validator's vote if it gives one.
+    ///
+    /// The `blobs` published by the block are sent to the validator if it is missing them.
+    pub(crate) async fn handle_block_proposal(
+        &self,
+        proposal: BlockProposal,
+        blobs: &[Blob],
+    ) -> Result<Option<LiteVote>, WorkerError> {
+        if self.behavior() == ValidatorBehavior::Offline {
+            return Ok(None);
+        }
+        let chain_id = proposal.content.block.chain_id;
+        let worker = self.worker(chain_id);
+        let result = worker.handle_block_proposal(proposal.clone()).await;
+        let (response, _actions) = match result {
+            Err(WorkerError::BlobsNotFound(_)) => {
+                for blob in blobs {
+                    worker.handle_pending_blob(chain_id, blob.clone()).await?;
+                }
+                worker.handle_block_proposal(proposal).await?
+            }
+            result => result?,
+        };
+        Ok(self.vote(response.info.manager.pending))
+    }
+
+    /// Handles a validated block certificate, returning this validator's vote to confirm the
+    /// block if it gives one.
+    pub(crate) async fn handle_validated_certificate(
+        &self,
+        certificate: ValidatedBlockCertificate,
+    ) -> Result<Option<LiteVote>, WorkerError> {
+        if self.behavior() == ValidatorBehavior::Offline {
+            return Ok(None);
+        }
+        let worker = self.worker(certificate.inner().chain_id());
+        let (response, actions) = worker.handle_validated_certificate(certificate).await?;
+        self.handle_network_actions(actions).await?;
+        Ok(self.vote(response.info.manager.pending))
+    }
+
+    /// Asks this validator to vote for a timeout of the current round of the chain
+    /// `chain_id`. It only votes if the round has timed out according to the clock.
+    pub(crate) async fn request_timeout_vote(
+        &self,
+        chain_id: ChainId,
+    ) -> Result<Option<LiteVote>, WorkerError> {
+        if self.behavior() == ValidatorBehavior::Offline {
+            return Ok(None);
+        }
+        let query = ChainInfoQuery::new(chain_id).with_timeout();
+        let (response, _actions) = self.worker(chain_id).handle_chain_info_query(query).await?;
+        Ok(self.vote(response.info.manager.timeout_vote))
+    }
+
+    /// Handles a timeout certificate, starting the next round.
+    pub(crate) async fn handle_timeout_certificate(
+        &self,
+        certificate: TimeoutCertificate,
+    ) -> Result<(), WorkerError> {
+        if self.behavior() == ValidatorBehavior::Offline {
+            return Ok(());
+        }
+        let worker = self.worker(certificate.inner().chain_id());
+        let (_response, actions) = worker.handle_timeout_certificate(certificate).await?;
+        self.handle_network_actions(actions).await
+    }
+
+    /// Handles a confirmed block certificate, writing the `blobs` to storage if they are
+    /// needed, and delivers the resulting cross-chain requests.
+    pub(crate) async fn handle_confirmed_certificate(
+        &self,
+        certificate: ConfirmedBlockCertificate,
+        blobs: &[Blob],
+    ) -> Result<(), WorkerError> {
+        if self.behavior() == ValidatorBehavior::Offline {
+            self.missed_certificates
+                .lock()
+                .await
+                .push((certificate, blobs.to_vec()));
+            return Ok(());
+        }
+        let worker = self.worker(certificate.inner().chain_id());
+        let result = worker
+            .handle_confirmed_certificate(certificate.clone(), None)
+            .await;
+        let (_response, actions) = match result {
+            Err(WorkerError::BlobsNotFound(_)) => {
+                self.storage.maybe_write_blobs(blobs).await?;
+                worker
+                    .handle_confirmed_certificate(certificate, None)
+                    .await?
+            }
+            result => result?,
+        };
+        self.handle_network_actions(actions).await
+    }
+
+    /// Queries the chain `chain_id` on its shard.
+    pub(crate) async fn handle_chain_info_query(
+        &self,
+        query: ChainInfoQuery,
+    ) -> Result<ChainInfoResponse, WorkerError> {
+        let (response, _actions) = self
+            .worker(query.chain_id)
+            .handle_chain_info_query(query)
+            .await?;
+        Ok(response)
+    }
+
+    /// Delivers the cross-chain requests this validator held back.
+    pub(crate) async fn deliver_delayed_cross_chain_requests(&self) {
+        let requests = std::mem::take(&mut *self.delayed_requests.lock().await);
+        self.deliver_cross_chain_requests(requests)
+            .await
+            .expect("Failed to handle delayed cross-chain requests");
+    }
+
+    /// Delivers the cross-chain requests in `actions`, unless they should be delayed.
+    async fn handle_network_actions(&self, actions: NetworkActions) -> Result<(), WorkerError> {
+        if self.behavior() == ValidatorBehavior::DelayCrossChainRequests {
+            self.delayed_requests
+                .lock()
+                .await
+                .extend(actions.cross_chain_requests);
+            return Ok(());
+        }
+        self.deliver_cross_chain_requests(actions.cross_chain_requests)
+            .await
+    }
+
+    /// Routes the cross-chain `requests` to the shards of their target chains, until no new
+    /// requests result from them.
+    async fn deliver_cross_chain_requests(
+        &self,
+        requests: Vec<CrossChainRequest>,
+    ) -> Result<(), WorkerError> {
+        let mut requests = VecDeque::from(requests);
+        while let Some(request) = requests.pop_front() {
+            let actions = self
+                .worker(request.target_chain_id())
+                .handle_cross_chain_request(request)
+                .await?;
+            requests.extend(actions.cross_chain_requests);
+        }
+        Ok(())
+    }
+
+    /// Returns the `vote` unless this validator withholds its votes.
+    fn vote(&self, vote: Option<LiteVote>) -> Option<LiteVote> {
+        match self.behavior() {
+            ValidatorBehavior::Honest | ValidatorBehavior::DelayCrossChainRequests => vote,
+            ValidatorBehavior::WithholdVotes | ValidatorBehavior::Offline => None,
+        }
+    }
+}
@@ -0,0 +1,614 @@
+// Copyright (c) Zefchain Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Golden-file snapshots of GraphQL responses and schemas.
+//!
+//! Snapshots are stored in the `tests/snapshots` directory of the crate being tested. A snapshot
+//! is compared with the actual value, and the test fails with a list of [`SnapshotDifference`]s
+//! if they don't match, or if the snapshot is missing. If the `LINERA_UPDATE_SNAPSHOTS`
+//! environment variable is set, e.g. by running `linera project test --update-snapshots`, the
+//! snapshots are recorded instead.
+
+use std::{
+    collections::BTreeMap,
+    fmt::{self, Display, Formatter},
+    fs,
+    path::PathBuf,
+};
+
+use serde_json::Value;
+
+/// The environment variable that makes snapshot assertions record new snapshots instead of
+/// comparing with the existing ones, or failing if they are missing.
+pub const UPDATE_SNAPSHOTS_VARIABLE: &str = "LINERA_UPDATE_SNAPSHOTS";
+
+/// The query to introspect the schema of a GraphQL service.
+pub(crate) const INTROSPECTION_QUERY: &str = r#"
+query IntrospectionQuery {
+  __schema {
+    queryType { name }
+    mutationType { name }
+    subscriptionType { name }
+    types { ...FullType }
+  }
+}
+
+fragment FullType on __Type {
+  kind
+  name
+  fields(includeDeprecated: true) {
+    name
+    args { ...InputValue }
+    type { ...TypeRef }
+    isDeprecated
+    deprecationReason
+  }
+  inputFields { ...InputValue }
+  interfaces { ...TypeRef }
+  enumValues(includeDeprecated: true) {
+    name
+    isDeprecated
+    deprecationReason
+  }
+  possibleTypes { ...TypeRef }
+}
+
+fragment InputValue on __InputValue {
+  name
+  type { ...TypeRef }
+  defaultValue
+}
+
+fragment TypeRef on __Type {
+  kind
+  name
+  ofType {
+    kind
+    name
+    ofType {
+      kind
+      name
+      ofType {
+        kind
+        name
+        ofType {
+          kind
+          name
+        }
+      }
+    }
+  }
+}
+"#;
+
+/// The scalars every GraphQL schema has, which are left out of the rendered schemas.
+const BUILT_IN_SCALARS: [&str; 5] = ["Boolean", "Float", "ID", "Int", "String"];
+
+/// A difference between a snapshot and the actual value.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum SnapshotDifference {
+    /// Something is in the actual value but not in the snapshot.
+    Added {
+        /// Where the difference is.
+        path: String,
+        /// The new value.
+        actual: String,
+    },
+    /// Something is in the snapshot but not in the actual value.
+    Removed {
+        /// Where the difference is.
+        path: String,
+        /// The missing value.
+        expected: String,
+    },
+    /// Something has a different value in the snapshot and in the actual value.
+    Changed {
+        /// Where the difference is.
+        path: String,
+        /// The value in the snapshot.
+        expected: String,
+        /// The actual value.
+        actual: String,
+    },
+}
+
+impl Display for SnapshotDifference {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            SnapshotDifference::Added { path, actual } => write!(formatter, "{path}: + {actual}"),
+            SnapshotDifference::Removed { path, expected } => {
+                write!(formatter, "{path}: - {expected}")
+            }
+            SnapshotDifference::Changed {
+                path,
+                expected,
+                actual,
+            } => write!(formatter, "{path}: {expected} -> {actual}"),
+        }
+    }
+}
+
+/// Checks the JSON `actual` value against the snapshot `name`.
+///
+/// # Panics
+///
+/// If the snapshot is missing or doesn't match, listing the differences.
+pub fn assert_json_snapshot(name: &str, actual: &Value) {
+    let rendered = serde_json::to_string_pretty(actual).expect("Failed to serialize JSON");
+    assert_snapshot(name, "json", &rendered, |expected| {
+        let expected = serde_json::from_str(expected)
+            .unwrap_or_else(|error| panic!("Snapshot {name:?} is not valid JSON: {error}"));
+        json_differences(&expected, actual)
+    });
+}
+
+/// Checks the GraphQL schema definition `actual` against the snapshot `name`.
+///
+/// # Panics
+///
+/// If the snapshot is missing or doesn't match, listing the differences.
+pub fn assert_sdl_snapshot(name: &str, actual: &str) {
+    assert_snapshot(name, "graphql", actual, |expected| {
+        sdl_differences(expected, actual)
+    });
+}
+
+/// Checks the `rendered` value against the snapshot `name` with the `extension`, using
+/// `differences` to compare them, or records it if [`UPDATE_SNAPSHOTS_VARIABLE`] is set.
+fn assert_snapshot(
+    name: &str,
+    extension: &str,
+    rendered: &str,
+    differences: impl FnOnce(&str) -> Vec<SnapshotDifference>,
+) {
+    let directory = PathBuf::from("tests").join("snapshots");
+    let path = directory.join(format!("{name}.{extension}"));
+    let update = std::env::var_os(UPDATE_SNAPSHOTS_VARIABLE).is_some();
+
+    if !update {
+        assert!(
+            path.exists(),
+            "Snapshot {name:?} is missing from {}.\n\
+            Run `linera project test --update-snapshots` to record it.",
+            path.display()
+        );
+        let expected = fs::read_to_string(&path)
+            .unwrap_or_else(|error| panic!("Failed to read snapshot {}: {error}", path.display()));
+        let differences = differences(&expected);
+        if !differences.is_empty() {
+            let differences = differences
+                .iter()
+                .map(|difference| format!("  {difference}"))
+                .collect::<Vec<_>>()
+                .join("\n");
+            panic!(
+                "Snapshot {name:?} does not match {}:\n{differences}\n\
+                Run `linera project test --update-snapshots` to accept the changes.",
+                path.display()
+            );
+        }
+        return;
+    }
+
+    fs::create_dir_all(&directory).expect("Failed to create the snapshot directory");
+    fs::write(&path, rendered)
+        .unwrap_or_else(|error| panic!("Failed to write snapshot {}: {error}", path.display()));
+}
+
+/// Lists the differences between two JSON values, with JSON pointers as paths.
+fn json_differences(expected: &Value, actual: &Value) -> Vec<SnapshotDifference> {
+    let mut differences = Vec::new();
+    collect_json_differences(String::new(), expected, actual, &mut differences);
+    differences
+}
+
+fn collect_json_differences(
+    path: String,
+    expected: &Value,
+    actual: &Value,
+    differences: &mut Vec<SnapshotDifference>,
+) {
+    match (expected, actual) {
+        (Value::Object(expected), Value::Object(actual)) => {
+            for (key, expected_value) in expected {
+                let path = format!("{path}/{}", escape_json_pointer(key));
+                match actual.get(key) {
+                    Some(actual_value) => {
+                        collect_json_differences(path, expected_value, actual_value, differences)
+                    }
+                    None => differences.push(SnapshotDifference::Removed {
+                        path,
+                        expected: expected_value.to_string(),
+                    }),
+                }
+            }
+            for (key, actual_value) in actual {
+                if !expected.contains_key(key) {
+                    differences.push(SnapshotDifference::Added {
+                        path: format!("{path}/{}", escape_json_pointer(key)),
+                        actual: actual_value.to_string(),
+                    });
+                }
+            }
+        }
+        (Value::Array(expected), Value::Array(actual)) => {
+            for (index, expected_value) in expected.iter().enumerate() {
+                let path = format!("{path}/{index}");
+                match actual.get(index) {
+                    Some(actual_value) => {
+                        collect_json_differences(path, expected_value, actual_value, differences)
+                    }
+                    None => differences.push(SnapshotDifference::Removed {
+                        path,
+                        expected: expected_value.to_string(),
+                    }),
+                }
+            }
+            for (index, actual_value) in actual.iter().enumerate().skip(expected.len()) {
+                differences.push(SnapshotDifference::Added {
+                    path: format!("{path}/{index}"),
+                    actual: actual_value.to_string(),
+                });
+            }
+        }
+        (expected, actual) if expected != actual => {
+            differences.push(SnapshotDifference::Changed {
+                path: if path.is_empty() {
+                    "/".to_owned()
+                } else {
+                    path
+                },
+                expected: expected.to_string(),
+                actual: actual.to_string(),
+            });
+        }
+        _ => {}
+    }
+}
+
+/// Escapes a `key` to be used as a reference token in a JSON pointer, as specified by RFC 6901.
+fn escape_json_pointer(key: &str) -> String {
+    key.replace('~', "~0").replace('/', "~1")
+}
+
+/// Lists the differences between two GraphQL schema definitions, per type definition and per
+/// field.
+///
+/// The paths are the names of the types, e.g. `type Query`, and the differences are the added
+/// or removed lines of their definitions.
+fn sdl_differences(expected: &str, actual: &str) -> Vec<SnapshotDifference> {
+    let expected = sdl_definitions(expected);
+    let actual = sdl_definitions(actual);
+    let mut differences = Vec::new();
+
+    for (header, expected_lines) in &expected {
+        let Some(actual_lines) = actual.get(header) else {
+            differences.push(SnapshotDifference::Removed {
+                path: header.clone(),
+                expected: "definition".to_owned(),
+            });
+            continue;
+        };
+        for line in expected_lines {
+            if !actual_lines.contains(line) {
+                differences.push(SnapshotDifference::Removed {
+                    path: header.clone(),
+                    expected: line.clone(),
+                });
+            }
+        }
+        for line in actual_lines {
+            if !expected_lines.contains(line) {
+                differences.push(SnapshotDifference::Added {
+                    path: header.clone(),
+                    actual: line.clone(),
+                });
+            }
+        }
+    }
+    for header in actual.keys() {
+        if !expected.contains_key(header) {
+            differences.push(SnapshotDifference::Added {
+                path: header.clone(),
+                actual: "definition".to_owned(),
+            });
+        }
+    }
+
+    differences
+}
+
+/// Splits a schema definition into its type definitions, indexed by their kind and name, with
+/// the lines defining their members.
+fn sdl_definitions(sdl: &str) -> BTreeMap<String, Vec<String>> {
+    let mut definitions = BTreeMap::new();
+    for block in sdl.split("\n\n") {
+        let mut lines = block.lines().map(str::trim).filter(|line| !line.is_empty());
+        let Some(first_line) = lines.next() else {
+            continue;
+        };
+        let (header, rest) = match first_line.split_once(['{', '=']) {
+            Some((header, rest)) => (header.trim(), Some(rest.trim())),
+            None => (first_line, None),
+        };
+        let header = header
+            .split(" implements ")
+            .next()
+            .unwrap_or(header)
+            .to_owned();
+        let mut members = Vec::new();
+        if first_line.contains(" implements ") {
+            members.push(first_line.to_owned());
+        }
+        members.extend(rest.filter(|rest| !rest.is_empty()).map(str::to_owned));
+        members.extend(lines.filter(|line| *line != "}").map(str::to_owned));
+        definitions.insert(header, members);
+    }
+    definitions
+}
+
+/// Renders the result of the [`INTROSPECTION_QUERY`] as a GraphQL schema definition.
+///
+/// Types are sorted by name, and built-in scalars and introspection types are left out.
+pub(crate) fn introspection_to_sdl(introspection: &Value) -> String {
+    let schema = &introspection["__schema"];
+    let mut definitions = Vec::new();
+
+    let roots = [
+        ("query", &schema["queryType"]),
+        ("mutation", &schema["mutationType"]),
+        ("subscription", &schema["subscriptionType"]),
+    ]
+    .into_iter()
+    .filter_map(|(operation, root)| Some(format!("  {operation}: {}", root["name"].as_str()?)))
+    .collect::<Vec<_>>();
+    definitions.push(format!("schema {{\n{}\n}}", roots.join("\n")));
+
+    let mut types = schema["types"]
+        .as_array()
+        .map(Vec::as_slice)
+        .unwrap_or_default()
+        .iter()
+        .filter(|ty| {
+            let name = ty["name"].as_str().unwrap_or_default();
+            !name.starts_with("__") && !BUILT_IN_SCALARS.contains(&name)
+        })
+        .collect::<Vec<_>>();
+    types.sort_by_key(|ty| ty["name"].as_str().unwrap_or_default());
+
+    for ty in types {
+        let name = ty["name"].as_str().unwrap_or_default();
+        let definition = match ty["kind"].as_str().unwrap_or_default() {
+            "SCALAR" => format!("scalar {name}"),
+            "OBJECT" | "INTERFACE" => {
+                let keyword = if ty["kind"] == "OBJECT" {
+                    "type"
+                } else {
+                    "interface"
+                };
+                let interfaces = type_names(&ty["interfaces"]);
+                let implements = if interfaces.is_empty() {
+                    String::new()
+                } else {
+                    format!(" implements {}", interfaces.join(" & "))
+                };
+                let fields = members(&ty["fields"], |field| {
+                    let arguments = members(&field["args"], render_input_value);
+                    let arguments = if arguments.is_empty() {
+                        String::new()
+                    } else {
+                        format!("({})", arguments.join(", "))
+                    };
+                    format!(
+                        "{}{arguments}: {}{}",
+                        field["name"].as_str().unwrap_or_default(),
+                        render_type(&field["type"]),
+                        render_deprecation(field),
+                    )
+                });
+                format!("{keyword} {name}{implements} {{\n{}\n}}", indent(&fields))
+            }
+            "INPUT_OBJECT" => {
+                let fields = members(&ty["inputFields"], render_input_value);
+                format!("input {name} {{\n{}\n}}", indent(&fields))
+            }
+            "ENUM" => {
+                let values = members(&ty["enumValues"], |value| {
+                    format!(
+                        "{}{}",
+                        value["name"].as_str().unwrap_or_default(),
+                        render_deprecation(value)
+                    )
+                });
+                format!("enum {name} {{\n{}\n}}", indent(&values))
+            }
+            "UNION" => format!(
+                "union {name} = {}",
+                type_names(&ty["possibleTypes"]).join(" | ")
+            ),
+            kind => format!("# Unsupported {kind} type {name}"),
+        };
+        definitions.push(definition);
+    }
+
+    let mut sdl = definitions.join("\n\n");
+    sdl.push('\n');
+    sdl
+}
+
+/// Renders each element of the JSON array `values` with `render`.
+fn members(values: &Value, render: impl Fn(&Value) -> String) -> Vec<String> {
+    values
+        .as_array()
+        .map(Vec::as_slice)
+        .unwrap_or_default()
+        .iter()
+        .map(render)
+        .collect()
+}
+
+/// Returns the names of the types in the JSON array `types`.
+fn type_names(types: &Value) -> Vec<String> {
+    members(types, |ty| {
+        ty["name"].as_str().unwrap_or_default().to_owned()
+    })
+}
+
+/// Indents the `lines` of a type definition.
+fn indent(lines: &[String]) -> String {
+    lines
+        .iter()
+        .map(|line| format!("  {line}"))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Renders an argument or an input field.
+fn render_input_value(value: &Value) -> String {
+    let default = match value["defaultValue"].as_str() {
+        Some(default) => format!(" = {default}"),
+        None => String::new(),
+    };
+    format!(
+        "{}: {}{default}",
+        value["name"].as_str().unwrap_or_default(),
+        render_type(&value["type"])
+    )
+}
+
+/// Renders a type reference, with its list and non-null wrappers.
+fn render_type(ty: &Value) -> String {
+    match ty["kind"].as_str().unwrap_or_default() {
+        "NON_NULL" => format!("{}!", render_type(&ty["ofType"])),
+        "LIST" => format!("[{}]", render_type(&ty["ofType"])),
+        _ => ty["name"].as_str().unwrap_or_default().to_owned(),
+    }
+}
+
+/// Renders the deprecation directive of a field or enum value, if it is deprecated.
+fn render_deprecation(member: &Value) -> String {
+    if member["isDeprecated"] != true {
+        return String::new();
+    }
+    match member["deprecationReason"].as_str() {
+        Some(reason) => format!(" @deprecated(reason: {})", Value::from(reason)),
+        None => " @deprecated".to_owned(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn json_differences_have_paths() {
+        let expected = json!({ "value": 1, "list": [1, 2], "removed": true });
+        let actual = json!({ "value": 2, "list": [1], "added": null });
+
+        assert_eq!(
+            json_differences(&expected, &actual),
+            vec![
+                SnapshotDifference::Removed {
+                    path: "/list/1".to_owned(),
+                    expected: "2".to_owned(),
+                },
+                SnapshotDifference::Removed {
+                    path: "/removed".to_owned(),
+                    expected: "true".to_owned(),
+                },
+                SnapshotDifference::Changed {
+                    path: "/value".to_owned(),
+                    expected: "1".to_owned(),
+                    actual: "2".to_owned(),
+                },
+                SnapshotDifference::Added {
+                    path: "/added".to_owned(),
+                    actual: "null".to_owned(),
+                },
+            ]
+        );
+        assert!(json_differences(&expected, &expected).is_empty());
+    }
+
+    #[test]
+    fn json_difference_paths_are_escaped() {
+        let expected = json!({ "a/b": { "c~d": 1 } });
+        let actual = json!({ "a/b": { "c~d": 2 } });
+
+        assert_eq!(
+            json_differences(&expected, &actual),
+            vec![SnapshotDifference::Changed {
+                path: "/a~1b/c~0d".to_owned(),
+                expected: "1".to_owned(),
+                actual: "2".to_owned(),
+            }]
+        );
+    }
+
+    #[test]
+    fn sdl_differences_are_per_field() {
+        let introspection = json!({
+            "__schema": {
+                "queryType": { "name": "Query" },
+                "mutationType": null,
+                "subscriptionType": null,
+                "types": [
+                    {
+                        "kind": "OBJECT",
+                        "name": "Query",
+                        "fields": [
+                            {
+                                "name": "value",
+                                "args": [],
+                                "type": {
+                                    "kind": "NON_NULL",
+                                    "name": null,
+                                    "ofType": { "kind": "SCALAR", "name": "Int", "ofType": null }
+                                },
+                                "isDeprecated": false,
+                                "deprecationReason": null
+                            }
+                        ],
+                        "interfaces": []
+                    },
+                    { "kind": "SCALAR", "name": "Int" },
+                    { "kind": "SCALAR", "name": "Amount" }
+                ]
+            }
+        });
+        let expected = introspection_to_sdl(&introspection);
+        assert_eq!(
+            expected,
+            "schema {\n  query: Query\n}\n\n\
+            scalar Amount\n\n\
+            type Query {\n  value: Int!\n}\n"
+        );
+
+        let actual = expected
+            .replace("value: Int!", "value: Int\n  owner: String!")
+            .replace("scalar Amount\n\n", "");
+        assert_eq!(
+            sdl_differences(&expected, &actual),
+            vec![
+                SnapshotDifference::Removed {
+                    path: "scalar Amount".to_owned(),
+                    expected: "definition".to_owned(),
+                },
+                SnapshotDifference::Removed {
+                    path: "type Query".to_owned(),
+                    expected: "value: Int!".to_owned(),
+                },
+                SnapshotDifference::Added {
+                    path: "type Query".to_owned(),
+                    actual: "value: Int".to_owned(),
+                },
+                SnapshotDifference::Added {
+                    path: "type Query".to_owned(),
+                    actual: "owner: String!".to_owned(),
+                },
+            ]
+        );
+    }
+}
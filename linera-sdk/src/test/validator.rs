@@ -5,16 +5,19 @@
 //!
 //! The [`TestValidator`] is a minimal validator with a single shard. Micro-chains can be added to
 //! it, and blocks can be added to each microchain individually.
+//!
+//! It can also simulate a network of several validators with several shards each, where some
+//! validators misbehave according to their [`ValidatorBehavior`].
 
-use std::{num::NonZeroUsize, sync::Arc};
+use std::sync::{Arc, Mutex as SyncMutex};
 
 use dashmap::DashMap;
 use futures::{
+    future,
     lock::{MappedMutexGuard, Mutex, MutexGuard},
-    FutureExt as _,
 };
 use linera_base::{
-    crypto::{AccountSecretKey, CryptoHash, ValidatorKeypair, ValidatorSecretKey},
+    crypto::{AccountSecretKey, CryptoHash, CryptoRng, ValidatorSecretKey},
     data_types::{
         Amount, ApplicationPermissions, Blob, BlobContent, ChainDescription, ChainOrigin, Epoch,
         InitialChainConfig, NetworkDescription, Timestamp,
@@ -22,17 +25,18 @@ use linera_base::{
     identifiers::{AccountOwner, ApplicationId, ChainId, ModuleId},
     ownership::ChainOwnership,
 };
-use linera_core::worker::WorkerState;
+use linera_chain::types::ConfirmedBlockCertificate;
+use linera_core::worker::{WorkerError, WorkerState};
 use linera_execution::{
     committee::Committee,
     system::{AdminOperation, OpenChainConfig, SystemOperation},
-    ResourceControlPolicy, WasmRuntime,
+    ResourceControlPolicy,
 };
 use linera_storage::{DbStorage, Storage, TestClock};
 use linera_views::memory::MemoryStore;
 use serde::Serialize;
 
-use super::ActiveChain;
+use super::{node::TestNode, ActiveChain, ValidatorBehavior};
 use crate::ContractAbi;
 
 /// A minimal validator implementation suited for tests.
@@ -48,12 +52,28 @@ use crate::ContractAbi;
 /// );
 /// # });
 /// ```
+///
+/// A network of several validators, each with several shards, is created with
+/// [`TestValidator::with_validators`]:
+///
+/// ```rust
+/// # use linera_sdk::test::*;
+/// # use linera_base::data_types::Amount;
+/// # tokio_test::block_on(async {
+/// let validator = TestValidator::with_validators(4, 2).await;
+/// validator
+///     .set_validator_behavior(3, ValidatorBehavior::Offline)
+///     .await;
+/// assert_eq!(
+///     validator.new_chain().await.chain_balance().await,
+///     Amount::from_tokens(10)
+/// );
+/// # });
+/// ```
 pub struct TestValidator {
-    validator_secret: ValidatorSecretKey,
-    account_secret: AccountSecretKey,
+    nodes: Arc<Vec<TestNode>>,
     committee: Arc<Mutex<(Epoch, Committee)>>,
-    storage: DbStorage<MemoryStore, TestClock>,
-    worker: WorkerState<DbStorage<MemoryStore, TestClock>>,
+    rng: Arc<SyncMutex<Box<dyn CryptoRng>>>,
     clock: TestClock,
     admin_chain_id: ChainId,
     chains: Arc<DashMap<ChainId, ActiveChain>>,
@@ -63,11 +83,9 @@ impl Clone for TestValidator {
     fn clone(&self) -> Self {
         TestValidator {
             admin_chain_id: self.admin_chain_id,
-            validator_secret: self.validator_secret.copy(),
-            account_secret: self.account_secret.copy(),
+            nodes: self.nodes.clone(),
             committee: self.committee.clone(),
-            storage: self.storage.clone(),
-            worker: self.worker.clone(),
+            rng: self.rng.clone(),
             clock: self.clock.clone(),
             chains: self.chains.clone(),
         }
@@ -77,27 +95,61 @@ impl Clone for TestValidator {
 impl TestValidator {
     /// Creates a new [`TestValidator`].
     pub async fn new() -> Self {
-        let validator_keypair = ValidatorKeypair::generate();
-        let account_secret = AccountSecretKey::generate();
+        Self::with_validators(1, 1).await
+    }
+
+    /// Creates a new [`TestValidator`] simulating a network of `validator_count` validators,
+    /// each with `shards_per_validator` shards.
+    ///
+    /// All validators start out [`ValidatorBehavior::Honest`], and form the committee of the
+    /// network with equal votes.
+    pub async fn with_validators(validator_count: usize, shards_per_validator: usize) -> Self {
+        Self::with_rng(validator_count, shards_per_validator, None.into()).await
+    }
+
+    /// Creates a new [`TestValidator`] like [`TestValidator::with_validators`], but generating
+    /// all the keys from `seed`.
+    ///
+    /// Creating the same chains and applications in the same order then always results in the
+    /// same IDs, which allows reproducing a test.
+    pub async fn with_seed(validator_count: usize, shards_per_validator: usize, seed: u64) -> Self {
+        Self::with_rng(validator_count, shards_per_validator, Some(seed).into()).await
+    }
+
+    /// Creates a new [`TestValidator`] generating its keys with `rng`.
+    async fn with_rng(
+        validator_count: usize,
+        shards_per_validator: usize,
+        mut rng: Box<dyn CryptoRng>,
+    ) -> Self {
+        assert!(
+            validator_count > 0,
+            "The network needs at least one validator"
+        );
+        let clock = TestClock::new();
+        let nodes = (0..validator_count)
+            .map(|index| {
+                TestNode::new(
+                    &format!("Validator {index}"),
+                    ValidatorSecretKey::generate_from(&mut rng),
+                    shards_per_validator,
+                    clock.clone(),
+                )
+            })
+            .collect::<Vec<_>>();
         let epoch = Epoch::ZERO;
-        let committee = Committee::make_simple(vec![(
-            validator_keypair.public_key,
-            account_secret.public(),
-        )]);
-        let wasm_runtime = Some(WasmRuntime::default());
-        let storage = DbStorage::<MemoryStore, _>::make_test_storage(wasm_runtime)
-            .now_or_never()
-            .expect("execution of DbStorage::new should not await anything");
-        let clock = storage.clock().clone();
-        let worker = WorkerState::new(
-            "Single validator node".to_string(),
-            Some(validator_keypair.secret_key.copy()),
-            storage.clone(),
-            NonZeroUsize::new(40).expect("Chain worker limit should not be zero"),
+        let committee = Committee::make_simple(
+            nodes
+                .iter()
+                .map(|node| {
+                    let account_secret = AccountSecretKey::generate_from(&mut rng);
+                    (node.public_key(), account_secret.public())
+                })
+                .collect(),
         );
 
         // Create an admin chain.
-        let key_pair = AccountSecretKey::generate();
+        let key_pair = AccountSecretKey::generate_from(&mut rng);
 
         let new_chain_config = InitialChainConfig {
             ownership: ChainOwnership::single(key_pair.public().into()),
@@ -122,22 +174,21 @@ impl TestValidator {
             genesis_timestamp: description.timestamp(),
             admin_chain_id,
         };
-        storage
-            .write_network_description(&network_description)
-            .await
-            .unwrap();
-        worker
-            .storage_client()
-            .create_chain(description.clone())
-            .await
-            .expect("Failed to create root admin chain");
+        for node in &nodes {
+            node.storage()
+                .write_network_description(&network_description)
+                .await
+                .unwrap();
+            node.storage()
+                .create_chain(description.clone())
+                .await
+                .expect("Failed to create root admin chain");
+        }
 
         let validator = TestValidator {
-            validator_secret: validator_keypair.secret_key,
-            account_secret,
+            nodes: Arc::new(nodes),
             committee: Arc::new(Mutex::new((epoch, committee))),
-            storage,
-            worker,
+            rng: Arc::new(SyncMutex::new(rng)),
             clock,
             admin_chain_id,
             chains: Arc::default(),
@@ -195,14 +246,63 @@ impl TestValidator {
         (validator, application_id, creator)
     }
 
-    /// Returns this validator's storage.
-    pub(crate) fn storage(&self) -> &DbStorage<MemoryStore, TestClock> {
-        &self.storage
+    /// Returns the [`WorkerState`] of the shard responsible for the chain `chain_id`, in the
+    /// validator used to read the state of the network.
+    pub(crate) fn worker(
+        &self,
+        chain_id: ChainId,
+    ) -> WorkerState<DbStorage<MemoryStore, TestClock>> {
+        self.reference_node().worker(chain_id)
+    }
+
+    /// Returns the validators of the network.
+    pub(crate) fn nodes(&self) -> &[TestNode] {
+        &self.nodes
+    }
+
+    /// Returns the validator used to read the state of the network, which is the first one
+    /// that is not offline.
+    fn reference_node(&self) -> &TestNode {
+        self.nodes
+            .iter()
+            .find(|node| node.behavior() != ValidatorBehavior::Offline)
+            .unwrap_or(&self.nodes[0])
+    }
+
+    /// Returns the number of validators in the network.
+    pub fn validator_count(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// Changes how the validator at `index` behaves.
+    ///
+    /// A validator that stops being [`ValidatorBehavior::Offline`] catches up with the
+    /// certificates it missed, and a validator that stops delaying its cross-chain requests
+    /// delivers them.
+    pub async fn set_validator_behavior(&self, index: usize, behavior: ValidatorBehavior) {
+        self.nodes[index].set_behavior(behavior).await;
+    }
+
+    /// Delivers the cross-chain requests held back by validators with the
+    /// [`ValidatorBehavior::DelayCrossChainRequests`] behavior.
+    pub async fn deliver_delayed_cross_chain_requests(&self) {
+        for node in self.nodes.iter() {
+            node.deliver_delayed_cross_chain_requests().await;
+        }
     }
 
-    /// Returns the locked [`WorkerState`] of this validator.
-    pub(crate) fn worker(&self) -> WorkerState<DbStorage<MemoryStore, TestClock>> {
-        self.worker.clone()
+    /// Sends a confirmed block `certificate` to all validators, with the `blobs` they may need
+    /// to process it.
+    pub(crate) async fn handle_certificate(
+        &self,
+        certificate: &ConfirmedBlockCertificate,
+        blobs: &[Blob],
+    ) -> Result<(), WorkerError> {
+        for node in self.nodes.iter() {
+            node.handle_confirmed_certificate(certificate.clone(), blobs)
+                .await?;
+        }
+        Ok(())
     }
 
     /// Returns the [`TestClock`] of this validator.
@@ -210,9 +310,14 @@ impl TestValidator {
         &self.clock
     }
 
-    /// Returns the keys this test validator uses for signing certificates.
+    /// Returns the keys the first validator of the network uses for signing certificates.
     pub fn key_pair(&self) -> &ValidatorSecretKey {
-        &self.validator_secret
+        self.nodes[0].secret_key()
+    }
+
+    /// Returns the keys of all the validators of the network.
+    pub(crate) fn key_pairs(&self) -> impl Iterator<Item = &ValidatorSecretKey> {
+        self.nodes.iter().map(TestNode::secret_key)
     }
 
     /// Returns the ID of the admin chain.
@@ -220,9 +325,10 @@ impl TestValidator {
         self.admin_chain_id
     }
 
-    /// Returns the latest committee that this test validator is part of.
+    /// Returns the latest committee of the test network.
     ///
-    /// The committee contains only this validator.
+    /// Unless created with [`TestValidator::with_validators`], the committee contains only this
+    /// validator.
     pub async fn committee(&self) -> MappedMutexGuard<'_, (Epoch, Committee), Committee> {
         MutexGuard::map(self.committee.lock().await, |(_epoch, committee)| committee)
     }
@@ -251,10 +357,13 @@ impl TestValidator {
             bcs::to_bytes(&committee).unwrap(),
         ));
         let blob_hash = committee_blob.id().hash;
-        self.storage
-            .write_blob(&committee_blob)
-            .await
-            .expect("Should write committee blob");
+        future::try_join_all(
+            self.nodes
+                .iter()
+                .map(|node| node.storage().write_blob(&committee_blob)),
+        )
+        .await
+        .expect("Should write committee blob");
 
         admin_chain
             .add_block(|block| {
@@ -295,10 +404,17 @@ impl TestValidator {
     /// Creates a new microchain and returns the [`ActiveChain`] that can be used to add blocks to
     /// it.
     pub async fn new_chain(&self) -> ActiveChain {
-        let key_pair = AccountSecretKey::generate();
+        let key_pair = self.generate_key_pair();
         self.new_chain_with_keypair(key_pair).await
     }
 
+    /// Generates a new key pair, which is deterministic if this validator was created
+    /// [`TestValidator::with_seed`].
+    pub fn generate_key_pair(&self) -> AccountSecretKey {
+        let mut rng = self.rng.lock().expect("RNG lock should not be poisoned");
+        AccountSecretKey::generate_from(&mut *rng)
+    }
+
     /// Adds an existing [`ActiveChain`].
     pub fn add_chain(&self, chain: ActiveChain) {
         self.chains.insert(chain.id(), chain);
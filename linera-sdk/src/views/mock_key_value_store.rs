@@ -5,6 +5,7 @@
 
 use std::{
     collections::BTreeMap,
+    ops::Bound,
     sync::{
         atomic::{AtomicU32, Ordering},
         Mutex,
@@ -176,6 +177,53 @@ impl MockKeyValueStore {
         self.find_key_values_promises.take(promise)
     }
 
+    /// Finds at most `limit` keys in the storage that start with `key_prefix` and whose
+    /// remainder lies in `range`, returning a promise resolved by [`find_keys_wait`].
+    pub(crate) fn find_keys_in_range_new(
+        &self,
+        key_prefix: &[u8],
+        range: (Bound<Vec<u8>>, Bound<Vec<u8>>),
+        reverse: bool,
+        limit: Option<u32>,
+    ) -> u32 {
+        self.find_keys_promises.register(
+            self.store
+                .find_keys_by_prefix_in_range(
+                    key_prefix,
+                    range,
+                    reverse,
+                    limit.map(|limit| limit as usize),
+                )
+                .now_or_never()
+                .expect("Memory store should never wait for anything")
+                .expect("Memory store should never fail"),
+        )
+    }
+
+    /// Finds at most `limit` key-value pairs in the storage in which the key starts with
+    /// `key_prefix` and its remainder lies in `range`, returning a promise resolved by
+    /// [`find_key_values_wait`].
+    pub(crate) fn find_key_values_in_range_new(
+        &self,
+        key_prefix: &[u8],
+        range: (Bound<Vec<u8>>, Bound<Vec<u8>>),
+        reverse: bool,
+        limit: Option<u32>,
+    ) -> u32 {
+        self.find_key_values_promises.register(
+            self.store
+                .find_key_values_by_prefix_in_range(
+                    key_prefix,
+                    range,
+                    reverse,
+                    limit.map(|limit| limit as usize),
+                )
+                .now_or_never()
+                .expect("Memory store should never wait for anything")
+                .expect("Memory store should never fail"),
+        )
+    }
+
     /// Writes a `batch` of operations to storage.
     pub(crate) fn write_batch(&self, batch: Batch) {
         self.store
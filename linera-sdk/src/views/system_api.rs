@@ -3,6 +3,7 @@
 
 //! Functions and types to interface with the system API available to application views.
 
+use std::ops::Bound;
 #[cfg(with_testing)]
 use std::sync::Arc;
 
@@ -185,6 +186,42 @@ impl ReadableKeyValueStore for KeyValueStore {
         yield_once().await;
         Ok(self.wit_api.find_key_values_wait(promise))
     }
+
+    async fn find_keys_by_prefix_in_range(
+        &self,
+        key_prefix: &[u8],
+        range: (Bound<Vec<u8>>, Bound<Vec<u8>>),
+        reverse: bool,
+        limit: Option<usize>,
+    ) -> Result<Vec<Vec<u8>>, KeyValueStoreError> {
+        ensure!(
+            key_prefix.len() <= Self::MAX_KEY_SIZE,
+            KeyValueStoreError::KeyTooLong
+        );
+        let promise = self
+            .wit_api
+            .find_keys_in_range_new(key_prefix, &range, reverse, limit);
+        yield_once().await;
+        Ok(self.wit_api.find_keys_wait(promise))
+    }
+
+    async fn find_key_values_by_prefix_in_range(
+        &self,
+        key_prefix: &[u8],
+        range: (Bound<Vec<u8>>, Bound<Vec<u8>>),
+        reverse: bool,
+        limit: Option<usize>,
+    ) -> Result<Vec<(Vec<u8>, Vec<u8>)>, KeyValueStoreError> {
+        ensure!(
+            key_prefix.len() <= Self::MAX_KEY_SIZE,
+            KeyValueStoreError::KeyTooLong
+        );
+        let promise = self
+            .wit_api
+            .find_key_values_in_range_new(key_prefix, &range, reverse, limit);
+        yield_once().await;
+        Ok(self.wit_api.find_key_values_wait(promise))
+    }
 }
 
 impl WritableKeyValueStore for KeyValueStore {
@@ -337,6 +374,56 @@ impl WitInterface {
         }
     }
 
+    /// Creates a promise for finding at most `limit` keys having a specified prefix and
+    /// whose remainder lies in `range`, resolved by `find_keys_wait`
+    fn find_keys_in_range_new(
+        &self,
+        key_prefix: &[u8],
+        range: &(Bound<Vec<u8>>, Bound<Vec<u8>>),
+        reverse: bool,
+        limit: Option<usize>,
+    ) -> u32 {
+        let (lower, upper) = (bound_to_wit(&range.0), bound_to_wit(&range.1));
+        let limit = limit_to_wit(limit);
+        match self {
+            WitInterface::Contract => {
+                contract_wit::find_keys_in_range_new(key_prefix, lower, upper, reverse, limit)
+            }
+            WitInterface::Service => {
+                service_wit::find_keys_in_range_new(key_prefix, lower, upper, reverse, limit)
+            }
+            #[cfg(with_testing)]
+            WitInterface::Mock { store, .. } => {
+                store.find_keys_in_range_new(key_prefix, range.clone(), reverse, limit)
+            }
+        }
+    }
+
+    /// Creates a promise for finding at most `limit` key/values having a specified prefix
+    /// and whose key remainder lies in `range`, resolved by `find_key_values_wait`
+    fn find_key_values_in_range_new(
+        &self,
+        key_prefix: &[u8],
+        range: &(Bound<Vec<u8>>, Bound<Vec<u8>>),
+        reverse: bool,
+        limit: Option<usize>,
+    ) -> u32 {
+        let (lower, upper) = (bound_to_wit(&range.0), bound_to_wit(&range.1));
+        let limit = limit_to_wit(limit);
+        match self {
+            WitInterface::Contract => {
+                contract_wit::find_key_values_in_range_new(key_prefix, lower, upper, reverse, limit)
+            }
+            WitInterface::Service => {
+                service_wit::find_key_values_in_range_new(key_prefix, lower, upper, reverse, limit)
+            }
+            #[cfg(with_testing)]
+            WitInterface::Mock { store, .. } => {
+                store.find_key_values_in_range_new(key_prefix, range.clone(), reverse, limit)
+            }
+        }
+    }
+
     /// Calls the `write_batch` WIT function.
     fn write_batch(&self, batch: Batch) {
         match self {
@@ -367,6 +454,21 @@ impl WitInterface {
     }
 }
 
+/// Converts a range bound into the key and inclusion flag expected by the WIT functions.
+fn bound_to_wit(bound: &Bound<Vec<u8>>) -> Option<(&[u8], bool)> {
+    match bound {
+        Bound::Included(key) => Some((key, true)),
+        Bound::Excluded(key) => Some((key, false)),
+        Bound::Unbounded => None,
+    }
+}
+
+/// Converts a limit into the one expected by the WIT functions. Limits that do not fit are
+/// saturated, which returns the same entries since no range holds that many.
+fn limit_to_wit(limit: Option<usize>) -> Option<u32> {
+    limit.map(|limit| u32::try_from(limit).unwrap_or(u32::MAX))
+}
+
 /// Implementation of [`linera_views::context::Context`] to be used for data storage
 /// by Linera applications.
 pub type ViewStorageContext = linera_views::context::ViewContext<(), KeyValueStore>;
@@ -407,6 +509,28 @@ mod tests {
         let value = mock_store.read_value(b"bar").await?;
         assert_eq!(value, Some(42_u128));
 
+        // Find keys in a range, in both directions
+        let keys = mock_store
+            .find_keys_by_prefix_in_range(
+                b"",
+                (Bound::Excluded(b"bar".to_vec()), Bound::Unbounded),
+                false,
+                None,
+            )
+            .await?;
+        assert_eq!(keys, vec![b"foo".to_vec()]);
+
+        let key_values = mock_store
+            .find_key_values_by_prefix_in_range(
+                b"",
+                (Bound::Unbounded, Bound::Unbounded),
+                true,
+                Some(1),
+            )
+            .await?;
+        assert_eq!(key_values.len(), 1);
+        assert_eq!(key_values[0].0, b"foo".to_vec());
+
         Ok(())
     }
 }
@@ -112,6 +112,14 @@ pub struct Blocks;
 )]
 pub struct Block;
 
+#[derive(GraphQLQuery)]
+#[graphql(
+    schema_path = "gql/service_schema.graphql",
+    query_path = "gql/service_requests.graphql",
+    response_derives = "Debug, Serialize, Clone, PartialEq"
+)]
+pub struct ConfirmedLog;
+
 #[derive(GraphQLQuery)]
 #[graphql(
     schema_path = "gql/service_schema.graphql",
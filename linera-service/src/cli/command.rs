@@ -6,7 +6,7 @@ use std::{borrow::Cow, num::NonZeroU16, path::PathBuf};
 use chrono::{DateTime, Utc};
 use linera_base::{
     crypto::{AccountPublicKey, CryptoHash, ValidatorPublicKey},
-    data_types::Amount,
+    data_types::{Amount, TimeDelta},
     identifiers::{Account, AccountOwner, ApplicationId, ChainId, ModuleId, StreamId},
     time::Duration,
     vm::VmRuntime,
@@ -280,6 +280,9 @@ pub enum ClientCommand {
     },
 
     /// Add or modify a validator (admin only)
+    ///
+    /// If the committee has a randomness beacon, the votes can't change: the key shares
+    /// would have to be dealt again for the new committee.
     SetValidator {
         /// The public key of the validator.
         #[arg(long)]
@@ -303,6 +306,9 @@ pub enum ClientCommand {
     },
 
     /// Remove a validator (admin only)
+    ///
+    /// If the committee has a randomness beacon, the votes can't change: the key shares
+    /// would have to be dealt again for the new committee.
     RemoveValidator {
         /// The public key of the validator.
         #[arg(long)]
@@ -358,6 +364,10 @@ pub enum ClientCommand {
         #[arg(long)]
         byte_stored: Option<Amount>,
 
+        /// Set the refund per byte freed from storage.
+        #[arg(long)]
+        byte_stored_refund: Option<Amount>,
+
         /// Set the base price of sending an operation from a block..
         #[arg(long)]
         operation: Option<Amount>,
@@ -552,6 +562,11 @@ pub enum ClientCommand {
         #[arg(long)]
         byte_stored_price: Option<Amount>,
 
+        /// Set the refund per byte freed from storage.
+        /// (This will overwrite value from `--policy-config`)
+        #[arg(long)]
+        byte_stored_refund: Option<Amount>,
+
         /// Set the base price of sending an operation from a block..
         /// (This will overwrite value from `--policy-config`)
         #[arg(long)]
@@ -675,6 +690,17 @@ pub enum ClientCommand {
         /// The port on which to run the server
         #[arg(long)]
         port: NonZeroU16,
+
+        /// Serve the chains of several tenants, as described in the given JSON file,
+        /// instead of the chains of the wallet. Each tenant has its own wallet, keystore
+        /// and API token.
+        #[arg(long)]
+        tenants: Option<PathBuf>,
+
+        /// Keep the prior values of the application states for this number of block heights,
+        /// so that applications can be queried at past heights.
+        #[arg(long)]
+        keep_state_history_heights: Option<u64>,
     },
 
     /// Run a GraphQL service that exposes a faucet where users can claim tokens.
@@ -779,6 +805,26 @@ pub enum ClientCommand {
         /// The list of required dependencies of application, if any.
         #[arg(long, num_args(0..))]
         required_application_ids: Option<Vec<ApplicationId>>,
+
+        /// The owner allowed to upgrade the application to a different module. By default,
+        /// the application cannot be upgraded.
+        #[arg(long)]
+        upgrade_authority: Option<AccountOwner>,
+    },
+
+    /// Upgrade an application to a different module, and run its migration. This must be done
+    /// on the application's creator chain, whose default owner must be the application's upgrade
+    /// authority. Other chains follow the upgrade when processing their inboxes.
+    UpgradeApplication {
+        /// The application to upgrade.
+        application_id: ApplicationId,
+
+        /// The module ID to upgrade the application to.
+        module_id: ModuleId,
+
+        /// The creator chain of the application. The default chain of the wallet is used
+        /// otherwise.
+        chain_id: Option<ChainId>,
     },
 
     /// Create an application, and publish the required module.
@@ -855,6 +901,57 @@ pub enum ClientCommand {
     #[command(subcommand)]
     Net(NetCommand),
 
+    /// Develop a Linera project against a local network with a single validator.
+    ///
+    /// The project is built and deployed as a new application every time its sources
+    /// change. A node service with a GraphiQL IDE is kept running on the default chain.
+    Dev {
+        /// The path of the root of the Linera project.
+        /// Defaults to current working directory if unspecified.
+        path: Option<PathBuf>,
+
+        /// The name of the Linera project, used to locate the generated bytecode files.
+        /// Defaults to the package name in Cargo.toml, with dashes replaced by underscores.
+        #[arg(long)]
+        name: Option<String>,
+
+        /// The shared parameters of the application, as a JSON string.
+        #[arg(long)]
+        json_parameters: Option<String>,
+
+        /// The instantiation argument of the application, as a JSON string.
+        #[arg(long)]
+        json_argument: Option<String>,
+
+        /// Path to a JSON file with a list of GraphQL requests, replayed against every new
+        /// application. Each request is either a GraphQL document, or an object with `query`
+        /// and `variables` fields.
+        #[arg(long)]
+        script: Option<PathBuf>,
+
+        /// The port of the node service.
+        #[arg(long, default_value = "8080")]
+        port: NonZeroU16,
+
+        /// How often the project's sources are checked for changes, in milliseconds.
+        #[arg(long, default_value = "500", value_parser = util::parse_millis)]
+        poll_interval: Duration,
+
+        /// Configure the resource control policy (notably fees) according to pre-defined
+        /// settings.
+        #[arg(long, default_value = "no-fees")]
+        policy_config: ResourceControlPolicyConfig,
+
+        /// The configuration for cross-chain messages.
+        #[clap(flatten)]
+        cross_chain_config: CrossChainConfig,
+
+        /// Force this wallet to generate keys using a PRNG and a given seed. USE FOR
+        /// TESTING ONLY.
+        #[arg(long)]
+        testing_prng_seed: Option<u64>,
+    },
+
     /// Operation on the storage.
     #[command(subcommand)]
     Storage(DatabaseToolCommand),
@@ -909,6 +1006,7 @@ impl ClientCommand {
             | ClientCommand::PublishDataBlob { .. }
             | ClientCommand::ReadDataBlob { .. }
             | ClientCommand::CreateApplication { .. }
+            | ClientCommand::UpgradeApplication { .. }
             | ClientCommand::PublishAndCreate { .. }
             | ClientCommand::Keygen
             | ClientCommand::Assign { .. }
@@ -919,6 +1017,7 @@ impl ClientCommand {
             #[cfg(feature = "benchmark")]
             ClientCommand::MultiBenchmark { .. } => "multi-benchmark".into(),
             ClientCommand::Net { .. } => "net".into(),
+            ClientCommand::Dev { .. } => "dev".into(),
             ClientCommand::Project { .. } => "project".into(),
             ClientCommand::Watch { .. } => "watch".into(),
             ClientCommand::Storage { .. } => "storage".into(),
@@ -954,6 +1053,24 @@ pub enum DatabaseToolCommand {
     /// List the blob IDs in the database
     ListBlobIds,
 
+    /// Delete the blobs that are no longer referenced by any chain or certificate in the
+    /// database
+    Gc {
+        /// Only report the blobs that would be deleted, without changing the database.
+        #[arg(long)]
+        dry_run: bool,
+
+        /// How long a blob must have been unreferenced before it is deleted, in seconds. It is
+        /// measured from the first run that found the blob unreferenced.
+        #[arg(long = "grace-period-secs", default_value = "86400", value_parser = util::parse_secs_delta)]
+        grace_period: TimeDelta,
+
+        /// The grace period of data blobs, e.g. the ones of abandoned block proposals, in
+        /// seconds. Defaults to the grace period of the other blobs.
+        #[arg(long = "data-grace-period-secs", value_parser = util::parse_secs_delta)]
+        data_grace_period: Option<TimeDelta>,
+    },
+
     /// List the chain IDs in the database
     ListChainIds,
 }
@@ -1120,6 +1237,10 @@ pub enum WalletCommand {
         /// Synchronize the new chain and download all its blocks from the validators.
         #[arg(long)]
         sync: bool,
+        /// Synchronize the new chain from a snapshot of its state certified by the validators,
+        /// instead of downloading and executing all its blocks. Implies `--sync`.
+        #[arg(long)]
+        from_snapshot: bool,
     },
 
     /// Forgets the specified chain's keys. The chain will still be followed by the
@@ -1145,7 +1266,29 @@ pub enum ProjectCommand {
     /// Test a Linera project.
     ///
     /// Equivalent to running `cargo test` with the appropriate test runner.
-    Test { path: Option<PathBuf> },
+    Test {
+        path: Option<PathBuf>,
+
+        /// Record the GraphQL snapshots checked by the tests, instead of failing if they are
+        /// missing or don't match.
+        #[arg(long)]
+        update_snapshots: bool,
+    },
+
+    /// Generate typed Rust and TypeScript client bindings for a Linera project.
+    ///
+    /// The project's tests are run to export the ABIs of the applications that call
+    /// `ActiveChain::export_abi`. The TypeScript bindings use the `@linera/client` package.
+    Codegen {
+        /// The path of the root of the Linera project.
+        /// Defaults to current working directory if unspecified.
+        path: Option<PathBuf>,
+
+        /// The directory to write the bindings to. Defaults to the `bindings` directory of
+        /// the project.
+        #[arg(long)]
+        output: Option<PathBuf>,
+    },
 
     /// Build and publish a Linera project.
     PublishAndCreate {
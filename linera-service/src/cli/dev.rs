@@ -0,0 +1,242 @@
+// Copyright (c) Zefchain Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! The `linera dev` command: a local network that redeploys a project's application whenever
+//! its sources change.
+
+use std::{
+    collections::BTreeMap,
+    num::NonZeroU16,
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::SystemTime,
+};
+
+use anyhow::{bail, Context as _, Result};
+use colored::Colorize as _;
+use linera_base::{
+    abi::ContractAbi,
+    data_types::Amount,
+    identifiers::ChainId,
+    listen_for_shutdown_signals,
+    time::{timer, Duration},
+    vm::VmRuntime,
+};
+use linera_client::client_options::ResourceControlPolicyConfig;
+use linera_rpc::config::CrossChainConfig;
+use serde_json::{json, Value};
+use tokio_util::sync::CancellationToken;
+use tracing::{error, info};
+
+use super::net_up_utils::StorageConfigProvider;
+use crate::{
+    cli_wrappers::{
+        local_net::{LocalNetConfig, PathProvider, ProcessInbox, StorageConfigBuilder},
+        LineraNet, LineraNetConfig, Network, NetworkConfig, NodeService,
+    },
+    project::Project,
+};
+
+/// The paths of a project, relative to its root, whose changes trigger a new deployment.
+const WATCHED_PATHS: [&str; 3] = ["src", "Cargo.toml", "build.rs"];
+
+/// The amount of tokens on the chain the application is deployed on.
+const INITIAL_AMOUNT: u128 = 1_000_000;
+
+/// The ABI the project's application is deployed with, since its types aren't known here.
+struct UntypedAbi;
+
+impl ContractAbi for UntypedAbi {
+    type Operation = Vec<u8>;
+    type Response = Vec<u8>;
+}
+
+/// What is deployed each time the project's sources change.
+pub struct DevDeployment {
+    /// The name of the project, used to locate its bytecode files.
+    pub name: Option<String>,
+    /// The shared parameters of the application.
+    pub parameters: Value,
+    /// The instantiation argument of the application.
+    pub argument: Value,
+    /// The GraphQL requests replayed against every new application.
+    pub script: Vec<Value>,
+}
+
+impl DevDeployment {
+    /// Reads a script of GraphQL requests from a JSON file holding a list of requests.
+    ///
+    /// Each request is either a string with a GraphQL document, or an object with `query`
+    /// and optional `variables` fields.
+    pub fn read_script(path: &Path) -> Result<Vec<Value>> {
+        let contents = fs_err::read_to_string(path)?;
+        let requests: Vec<Value> = serde_json::from_str(&contents)
+            .with_context(|| format!("Invalid script {}", path.display()))?;
+        requests
+            .into_iter()
+            .map(|request| match request {
+                Value::String(query) => Ok(json!({ "query": query })),
+                Value::Object(ref fields) if fields.contains_key("query") => Ok(request),
+                _ => bail!("Invalid request in script {}: {request}", path.display()),
+            })
+            .collect()
+    }
+}
+
+#[expect(clippy::too_many_arguments)]
+pub async fn handle_dev(
+    project_root: PathBuf,
+    deployment: DevDeployment,
+    port: NonZeroU16,
+    poll_interval: Duration,
+    policy_config: ResourceControlPolicyConfig,
+    cross_chain_config: CrossChainConfig,
+    testing_prng_seed: Option<u64>,
+    storage: &Option<String>,
+) -> Result<()> {
+    let project = Arc::new(Project::from_existing_project(project_root.clone())?);
+
+    let shutdown_notifier = CancellationToken::new();
+    tokio::spawn(listen_for_shutdown_signals(shutdown_notifier.clone()));
+
+    let storage = StorageConfigProvider::new(storage).await?;
+    let config = LocalNetConfig {
+        network: NetworkConfig {
+            external: Network::Grpc,
+            internal: Network::Grpc,
+        },
+        database: storage.database()?,
+        testing_prng_seed,
+        namespace: storage.namespace(),
+        num_other_initial_chains: 0,
+        initial_amount: Amount::from_tokens(INITIAL_AMOUNT),
+        num_initial_validators: 1,
+        num_shards: 1,
+        policy_config,
+        cross_chain_config,
+        storage_config_builder: StorageConfigBuilder::ExistingConfig {
+            storage_config: storage.storage_config(),
+        },
+        path_provider: PathProvider::create_temporary_directory()?,
+        num_block_exporters: 0,
+    };
+    let (mut net, client) = config.instantiate().await?;
+    let chain_id = client
+        .default_chain()
+        .context("The local network has no default chain")?;
+    let node_service = client
+        .run_node_service(port.get(), ProcessInbox::Automatic)
+        .await?;
+    eprintln!(
+        "\nGraphiQL IDE of the node service: {}",
+        format!("http://localhost:{port}/").bold()
+    );
+
+    let mut sources = source_modification_times(&project_root)?;
+    deploy_and_report(&project, &deployment, &node_service, chain_id).await;
+    eprintln!("\nWatching the project's sources. Press ^C to terminate the local network.");
+    loop {
+        tokio::select! {
+            () = shutdown_notifier.cancelled() => break,
+            () = timer::sleep(poll_interval) => {}
+        }
+        let new_sources = source_modification_times(&project_root)?;
+        if new_sources != sources {
+            sources = new_sources;
+            info!("The project's sources changed; deploying a new application");
+            deploy_and_report(&project, &deployment, &node_service, chain_id).await;
+        }
+    }
+
+    eprintln!();
+    eprintln!("Terminating the node service");
+    node_service.terminate().await?;
+    eprintln!("Terminating the local network");
+    net.terminate().await?;
+    eprintln!("Done.");
+    Ok(())
+}
+
+/// Deploys the project as a new application and prints its endpoint, or reports why it failed.
+///
+/// Failures don't stop `linera dev`, so that they can be fixed in the sources.
+async fn deploy_and_report(
+    project: &Arc<Project>,
+    deployment: &DevDeployment,
+    node_service: &NodeService,
+    chain_id: ChainId,
+) {
+    match deploy(project, deployment, node_service, chain_id).await {
+        Ok(endpoint) => eprintln!("\nApplication deployed: {}", endpoint.bold()),
+        Err(error) => error!("Failed to deploy the project: {error:#}"),
+    }
+}
+
+/// Builds the project, creates a new application from its bytecode on `chain_id`, and replays
+/// the script against it.
+///
+/// Returns the GraphQL endpoint of the new application.
+async fn deploy(
+    project: &Arc<Project>,
+    deployment: &DevDeployment,
+    node_service: &NodeService,
+    chain_id: ChainId,
+) -> Result<String> {
+    // Building waits for `cargo`, so it mustn't block the node service's runtime.
+    let (contract, service) = {
+        let project = project.clone();
+        let name = deployment.name.clone();
+        tokio::task::spawn_blocking(move || project.build(name)).await??
+    };
+    let module_id = node_service
+        .publish_module::<UntypedAbi, Value, Value>(&chain_id, contract, service, VmRuntime::Wasm)
+        .await?;
+    let application_id = node_service
+        .create_application(
+            &chain_id,
+            &module_id,
+            &deployment.parameters,
+            &deployment.argument,
+            &[],
+        )
+        .await?;
+    let application = node_service
+        .make_application(&chain_id, &application_id)
+        .await?;
+    for (index, request) in deployment.script.iter().enumerate() {
+        application
+            .run_json_query(request)
+            .await
+            .with_context(|| format!("Request {index} of the script failed"))?;
+    }
+    Ok(format!(
+        "http://localhost:{}/chains/{chain_id}/applications/{}",
+        node_service.port(),
+        application_id.forget_abi()
+    ))
+}
+
+/// Returns the modification times of the watched files of the project in `root`.
+fn source_modification_times(root: &Path) -> Result<BTreeMap<PathBuf, SystemTime>> {
+    let mut times = BTreeMap::new();
+    let mut pending = WATCHED_PATHS
+        .iter()
+        .map(|path| root.join(path))
+        .filter(|path| path.exists())
+        .collect::<Vec<_>>();
+    while let Some(path) = pending.pop() {
+        let metadata = fs_err::metadata(&path)?;
+        if metadata.is_dir() {
+            for entry in fs_err::read_dir(&path)? {
+                pending.push(entry?.path());
+            }
+        } else {
+            times.insert(path, metadata.modified()?);
+        }
+    }
+    Ok(times)
+}
+
+#[cfg(test)]
+#[path = "../unit_tests/dev.rs"]
+mod tests;
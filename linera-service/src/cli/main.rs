@@ -35,26 +35,28 @@ use linera_client::{
     wallet::{UserChain, Wallet},
 };
 use linera_core::{
-    data_types::ClientOutcome, node::ValidatorNodeProvider, worker::Reason, JoinSetExt as _,
+    client::ChainClientError, data_types::ClientOutcome, node::ValidatorNodeProvider,
+    worker::Reason, JoinSetExt as _,
 };
 use linera_execution::{
     committee::{Committee, ValidatorState},
-    WasmRuntime, WithWasmDefault as _,
+    ExecutionRuntimeConfig, WasmRuntime, WithWasmDefault as _,
 };
 use linera_faucet_server::FaucetService;
 use linera_persistent::{self as persistent, Persist, PersistExt as _};
 use linera_service::{
     cli::{
         command::{ClientCommand, DatabaseToolCommand, NetCommand, ProjectCommand, WalletCommand},
-        net_up_utils,
+        dev, net_up_utils,
     },
     cli_wrappers::{self},
     node_service::NodeService,
     project::{self, Project},
     storage::{Runnable, RunnableWithStore, StorageConfigNamespace},
+    tenants::{Tenant, TenantService, TenantsConfig},
     util, wallet,
 };
-use linera_storage::{DbStorage, Storage};
+use linera_storage::{BlobCollectionPolicy, DbStorage, Storage};
 use linera_views::{
     lru_caching::StorageCacheConfig,
     store::{CommonStoreConfig, KeyValueStore},
@@ -575,6 +577,7 @@ impl Runnable for Job {
                                     blob_byte_read,
                                     blob_byte_published,
                                     byte_stored,
+                                    byte_stored_refund,
                                     operation,
                                     operation_byte,
                                     message,
@@ -618,6 +621,8 @@ impl Runnable for Job {
                                             .unwrap_or(existing_policy.blob_byte_published),
                                         byte_stored: byte_stored
                                             .unwrap_or(existing_policy.byte_stored),
+                                        byte_stored_refund: byte_stored_refund
+                                            .unwrap_or(existing_policy.byte_stored_refund),
                                         operation: operation.unwrap_or(existing_policy.operation),
                                         operation_byte: operation_byte
                                             .unwrap_or(existing_policy.operation_byte),
@@ -673,7 +678,17 @@ impl Runnable for Job {
                                 }
                                 _ => unreachable!(),
                             }
+                            let randomness_keys = committee.randomness_keys().cloned();
                             committee = Committee::new(validators, policy);
+                            // The randomness key shares remain valid as long as the votes
+                            // are unchanged. Otherwise they must be redealt for the new
+                            // committee: without them, every block asking for randomness
+                            // would fail.
+                            if let Some(randomness_keys) = randomness_keys {
+                                committee = committee
+                                    .with_randomness_keys(randomness_keys)
+                                    .map_err(ChainClientError::InvalidCommittee)?;
+                            }
                             chain_client
                                 .stage_new_committee(committee)
                                 .await
@@ -814,7 +829,15 @@ impl Runnable for Job {
                 info!("Notification stream ended.");
             }
 
-            Service { config, port } => {
+            Service {
+                config,
+                port,
+                tenants: None,
+                keep_state_history_heights,
+            } => {
+                let storage = storage.with_execution_runtime_config(ExecutionRuntimeConfig {
+                    state_history_retention: keep_state_history_heights,
+                });
                 let context =
                     ClientContext::new(storage, options.inner.clone(), wallet, signer.into_value());
 
@@ -826,6 +849,41 @@ impl Runnable for Job {
                 service.run(child_token).await?;
             }
 
+            Service {
+                config,
+                port,
+                tenants: Some(tenants_path),
+                keep_state_history_heights,
+            } => {
+                let storage = storage.with_execution_runtime_config(ExecutionRuntimeConfig {
+                    state_history_retention: keep_state_history_heights,
+                });
+                let mut tenants = Vec::new();
+                for tenant in TenantsConfig::read(&tenants_path)?.tenants {
+                    let wallet = persistent::File::<Wallet>::read(&tenant.wallet)?;
+                    let signer = persistent::File::<InMemorySigner>::read(&tenant.keystore)?;
+                    let context = ClientContext::new(
+                        storage.clone(),
+                        options.inner.clone(),
+                        wallet,
+                        signer.into_value(),
+                    );
+                    let default_chain = context.wallet().default_chain();
+                    let config = tenant.listener_config(&config);
+                    let service = NodeService::new(config, port, default_chain, context).await;
+                    info!(
+                        "Loaded tenant {} with wallet {}",
+                        tenant.name,
+                        tenant.wallet.display()
+                    );
+                    tenants.push(Tenant::new(tenant.name, &tenant.token, service));
+                }
+                let cancellation_token = CancellationToken::new();
+                let child_token = cancellation_token.child_token();
+                tokio::spawn(listen_for_shutdown_signals(cancellation_token));
+                Box::pin(TenantService::new(port, tenants).run(child_token)).await?;
+            }
+
             Faucet {
                 chain_id,
                 port,
@@ -950,6 +1008,7 @@ impl Runnable for Job {
                 json_argument,
                 json_argument_path,
                 required_application_ids,
+                upgrade_authority,
             } => {
                 let mut context =
                     ClientContext::new(storage, options.inner.clone(), wallet, signer.into_value());
@@ -973,11 +1032,12 @@ impl Runnable for Job {
                         let required_application_ids = required_application_ids.clone();
                         async move {
                             chain_client
-                                .create_application_untyped(
+                                .create_upgradeable_application_untyped(
                                     module_id,
                                     parameters,
                                     argument,
                                     required_application_ids.unwrap_or_default(),
+                                    upgrade_authority,
                                 )
                                 .await
                         }
@@ -992,6 +1052,42 @@ impl Runnable for Job {
                 println!("{}", application_id);
             }
 
+            UpgradeApplication {
+                application_id,
+                module_id,
+                chain_id,
+            } => {
+                let mut context =
+                    ClientContext::new(storage, options.inner.clone(), wallet, signer.into_value());
+
+                let start_time = Instant::now();
+                let chain_id = chain_id.unwrap_or_else(|| context.default_chain());
+                info!(
+                    "Upgrading application {} on chain {} to module {}",
+                    application_id, chain_id, module_id
+                );
+                let chain_client = context.make_chain_client(chain_id);
+                context.process_inbox(&chain_client).await?;
+
+                let certificate = context
+                    .apply_client_command(&chain_client, move |chain_client| {
+                        let chain_client = chain_client.clone();
+                        async move {
+                            chain_client
+                                .upgrade_application(application_id, module_id)
+                                .await
+                        }
+                    })
+                    .await
+                    .context("Failed to upgrade application")?;
+                info!("{}", "Application upgraded successfully!".green().bold());
+                info!(
+                    "Application upgraded in {} ms",
+                    start_time.elapsed().as_millis()
+                );
+                debug!("{:?}", certificate);
+            }
+
             PublishAndCreate {
                 contract,
                 service,
@@ -1180,13 +1276,17 @@ impl Runnable for Job {
 
             Wallet(WalletCommand::FollowChain {
                 chain_id,
-                sync: true,
-            }) => {
+                sync,
+                from_snapshot,
+            }) if sync || from_snapshot => {
                 let mut context =
                     ClientContext::new(storage, options.inner.clone(), wallet, signer.into_value());
                 let chain_client = context.make_chain_client(chain_id);
                 info!("Synchronizing chain information");
                 let time_start = Instant::now();
+                if from_snapshot {
+                    chain_client.synchronize_from_snapshot().await?;
+                }
                 chain_client.synchronize_from_validators().await?;
                 context.update_wallet_from_client(&chain_client).await?;
                 let time_total = time_start.elapsed();
@@ -1203,6 +1303,7 @@ impl Runnable for Job {
             CreateGenesisConfig { .. }
             | Keygen
             | Net(_)
+            | Dev { .. }
             | Storage { .. }
             | Wallet(_)
             | ExtractScriptFromMarkdown { .. }
@@ -1512,6 +1613,42 @@ impl RunnableWithStore for DatabaseToolJob<'_> {
                     println!("{}", id);
                 }
             }
+            DatabaseToolCommand::Gc {
+                dry_run,
+                grace_period,
+                data_grace_period,
+            } => {
+                let chain_ids = DbStorage::<S, _>::list_chain_ids(&config, &namespace).await?;
+                let storage = DbStorage::<S, _>::connect(&config, &namespace, None).await?;
+                let policy = BlobCollectionPolicy {
+                    grace_period: *grace_period,
+                    data_grace_period: data_grace_period.unwrap_or(*grace_period),
+                };
+                let report = storage.collect_blobs(&chain_ids, &policy, *dry_run).await?;
+                info!("Blobs collected in {} ms", start_time.elapsed().as_millis());
+                info!(
+                    "{} of {} blobs are kept, {} unreferenced blobs are within their grace period",
+                    report.num_kept,
+                    report.num_blobs,
+                    report.pending.len()
+                );
+                if *dry_run {
+                    info!(
+                        "The following {} blobs ({} bytes) would be deleted:",
+                        report.deleted.len(),
+                        report.deleted_bytes
+                    );
+                } else {
+                    info!(
+                        "The following {} blobs ({} bytes) were deleted:",
+                        report.deleted.len(),
+                        report.deleted_bytes
+                    );
+                }
+                for id in report.deleted {
+                    println!("{}", id);
+                }
+            }
             DatabaseToolCommand::ListChainIds => {
                 let chain_ids = DbStorage::<S, _>::list_chain_ids(&config, &namespace).await?;
                 info!(
@@ -1609,6 +1746,7 @@ async fn run(options: &ClientOptions) -> Result<i32, Error> {
             byte_read_price,
             byte_written_price,
             byte_stored_price,
+            byte_stored_refund,
             blob_read_price,
             blob_published_price,
             blob_byte_read_price,
@@ -1653,6 +1791,8 @@ async fn run(options: &ClientOptions) -> Result<i32, Error> {
                 blob_byte_published: blob_byte_published_price
                     .unwrap_or(existing_policy.blob_byte_published),
                 byte_stored: byte_stored_price.unwrap_or(existing_policy.byte_stored),
+                byte_stored_refund: byte_stored_refund
+                    .unwrap_or(existing_policy.byte_stored_refund),
                 operation: operation_price.unwrap_or(existing_policy.operation),
                 operation_byte: operation_byte_price.unwrap_or(existing_policy.operation_byte),
                 message: message_price.unwrap_or(existing_policy.message),
@@ -1753,17 +1893,33 @@ async fn run(options: &ClientOptions) -> Result<i32, Error> {
                 );
                 Ok(0)
             }
-            ProjectCommand::Test { path } => {
+            ProjectCommand::Test {
+                path,
+                update_snapshots,
+            } => {
                 let start_time = Instant::now();
                 let path = path.clone().unwrap_or_else(|| env::current_dir().unwrap());
                 let project = Project::from_existing_project(path)?;
-                project.test().await?;
+                project.test(*update_snapshots).await?;
                 info!(
                     "Test project created in {} ms",
                     start_time.elapsed().as_millis()
                 );
                 Ok(0)
             }
+            ProjectCommand::Codegen { path, output } => {
+                let start_time = Instant::now();
+                let path = path.clone().unwrap_or_else(|| env::current_dir().unwrap());
+                let project = Project::from_existing_project(path)?;
+                for path in project.codegen(output.clone()).await? {
+                    println!("{}", path.display());
+                }
+                info!(
+                    "Client bindings generated in {} ms",
+                    start_time.elapsed().as_millis()
+                );
+                Ok(0)
+            }
             ProjectCommand::PublishAndCreate { .. } => {
                 let start_time = Instant::now();
                 options.run_with_storage(Job(options.clone())).await??;
@@ -1879,6 +2035,51 @@ async fn run(options: &ClientOptions) -> Result<i32, Error> {
             }
         },
 
+        ClientCommand::Dev {
+            path,
+            name,
+            json_parameters,
+            json_argument,
+            script,
+            port,
+            poll_interval,
+            policy_config,
+            cross_chain_config,
+            testing_prng_seed,
+        } => {
+            let path = path.clone().unwrap_or_else(|| env::current_dir().unwrap());
+            let parse_json = |json: &Option<String>| -> anyhow::Result<Value> {
+                Ok(json
+                    .as_deref()
+                    .map(serde_json::from_str)
+                    .transpose()?
+                    .unwrap_or(Value::Null))
+            };
+            let deployment = dev::DevDeployment {
+                name: name.clone(),
+                parameters: parse_json(json_parameters)?,
+                argument: parse_json(json_argument)?,
+                script: match script {
+                    Some(script) => dev::DevDeployment::read_script(script)?,
+                    None => Vec::new(),
+                },
+            };
+            dev::handle_dev(
+                path,
+                deployment,
+                *port,
+                *poll_interval,
+                *policy_config,
+                cross_chain_config.clone(),
+                *testing_prng_seed,
+                // Not using the default value for storage
+                &options.storage_config,
+            )
+            .boxed()
+            .await?;
+            Ok(0)
+        }
+
         ClientCommand::Storage(command) => {
             Ok(options.run_with_store(DatabaseToolJob(command)).await?)
         }
@@ -1951,7 +2152,11 @@ async fn run(options: &ClientOptions) -> Result<i32, Error> {
                 Ok(0)
             }
 
-            WalletCommand::FollowChain { chain_id, sync } => {
+            WalletCommand::FollowChain {
+                chain_id,
+                sync,
+                from_snapshot,
+            } => {
                 let start_time = Instant::now();
                 options
                     .wallet()
@@ -1960,7 +2165,7 @@ async fn run(options: &ClientOptions) -> Result<i32, Error> {
                         wallet.extend([UserChain::make_other(*chain_id, Timestamp::now())])
                     })
                     .await?;
-                if *sync {
+                if *sync || *from_snapshot {
                     options.run_with_storage(Job(options.clone())).await??;
                 }
                 info!(
@@ -6,4 +6,5 @@
 #![deny(clippy::large_futures)]
 
 pub mod command;
+pub mod dev;
 pub mod net_up_utils;
@@ -28,7 +28,7 @@ use crate::{
     storage::{StorageConfig, StorageConfigNamespace},
 };
 
-struct StorageConfigProvider {
+pub(crate) struct StorageConfigProvider {
     /// The `StorageConfig` and the namespace
     pub storage: StorageConfigNamespace,
     #[cfg(feature = "storage-service")]
@@ -99,6 +99,18 @@ impl StorageConfigProvider {
             StorageConfig::ScyllaDb { .. } => Ok(Database::ScyllaDb),
             #[cfg(all(feature = "rocksdb", feature = "scylladb"))]
             StorageConfig::DualRocksDbScyllaDb { .. } => Ok(Database::DualRocksDbScyllaDb),
+            #[cfg(all(feature = "rocksdb", feature = "scylladb"))]
+            StorageConfig::TieredRocksDbScyllaDb { .. } => {
+                anyhow::bail!("Not possible to work with tiered storage")
+            }
+            #[cfg(all(feature = "rocksdb", feature = "dynamodb"))]
+            StorageConfig::TieredRocksDbDynamoDb { .. } => {
+                anyhow::bail!("Not possible to work with tiered storage")
+            }
+            #[cfg(feature = "encryption")]
+            StorageConfig::Encrypted { .. } => {
+                anyhow::bail!("Not possible to work with encrypted storage")
+            }
         }
     }
 }
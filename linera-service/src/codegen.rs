@@ -0,0 +1,1566 @@
+// Copyright (c) Zefchain Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Generation of typed client bindings from application ABI descriptions.
+//!
+//! The descriptions are exported by an application's tests with `ActiveChain::export_abi`
+//! from the Linera SDK. Each one contains the `serde-reflection` formats of the contract's
+//! `Operation` and `Response` types, and the introspected GraphQL schema of the service.
+//!
+//! The Rust bindings contain the contract types, to serialize operations with BCS, and
+//! request builders for the service's queries and mutations. The TypeScript bindings contain
+//! the JSON representation of the contract types, and a client class sending the service's
+//! queries and mutations through an `Application` of `@linera/client`.
+
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt::Write as _,
+    path::Path,
+};
+
+use anyhow::{bail, Context as _, Result};
+use convert_case::{Case, Casing};
+use serde::Deserialize;
+use serde_json::Value;
+use serde_reflection::{ContainerFormat, Format, Named, Registry, VariantFormat};
+
+/// The types that are serialized as strings in JSON, both as ABI types and as GraphQL
+/// scalars.
+const STRING_TYPES: [&str; 6] = [
+    "AccountOwner",
+    "Amount",
+    "ApplicationId",
+    "ChainId",
+    "CryptoHash",
+    "ModuleId",
+];
+
+/// The scalars every GraphQL schema has.
+const BUILT_IN_SCALARS: [&str; 5] = ["Boolean", "Float", "ID", "Int", "String"];
+
+/// The Rust keywords, which are escaped when used as identifiers.
+const RUST_KEYWORDS: [&str; 51] = [
+    "Self", "abstract", "as", "async", "await", "become", "box", "break", "const", "continue",
+    "crate", "do", "dyn", "else", "enum", "extern", "false", "final", "fn", "for", "gen", "if",
+    "impl", "in", "let", "loop", "macro", "match", "mod", "move", "mut", "override", "priv", "pub",
+    "ref", "return", "self", "static", "struct", "super", "trait", "true", "try", "type", "typeof",
+    "unsafe", "unsized", "use", "virtual", "where", "while",
+];
+
+/// The reserved words of TypeScript, which can't be used as parameter names.
+const TYPESCRIPT_RESERVED_WORDS: [&str; 44] = [
+    "await",
+    "break",
+    "case",
+    "catch",
+    "class",
+    "const",
+    "continue",
+    "debugger",
+    "default",
+    "delete",
+    "do",
+    "else",
+    "enum",
+    "export",
+    "extends",
+    "false",
+    "finally",
+    "for",
+    "function",
+    "if",
+    "implements",
+    "import",
+    "in",
+    "instanceof",
+    "interface",
+    "let",
+    "new",
+    "null",
+    "package",
+    "private",
+    "protected",
+    "public",
+    "return",
+    "static",
+    "super",
+    "switch",
+    "this",
+    "throw",
+    "true",
+    "try",
+    "typeof",
+    "var",
+    "void",
+    "while",
+];
+
+/// The description of an application's ABI, as exported by the Linera SDK.
+#[derive(Clone, Debug, Deserialize)]
+pub struct AbiDescription {
+    /// The name of the application, used to name the generated modules.
+    pub name: String,
+    /// The formats of the containers used by the `Operation` and `Response` types, by name.
+    pub formats: Registry,
+    /// The format of the contract's `Operation` type.
+    pub operation: Format,
+    /// The format of the contract's `Response` type.
+    pub response: Format,
+    /// The result of introspecting the service's GraphQL schema.
+    pub graphql: Value,
+}
+
+impl AbiDescription {
+    /// Reads an ABI description from a JSON file.
+    pub fn read(path: &Path) -> Result<Self> {
+        let contents = fs_err::read_to_string(path)?;
+        serde_json::from_str(&contents)
+            .with_context(|| format!("Invalid ABI description in {}", path.display()))
+    }
+}
+
+/// Generates the Rust client module for the application described by `description`.
+///
+/// The module depends on the `bcs`, `serde` and `serde_json` crates.
+pub fn generate_rust(description: &AbiDescription) -> Result<String> {
+    let schema = Schema::from_introspection(&description.graphql)?;
+    let mut code = header(&description.name, "//");
+
+    writeln!(code)?;
+    writeln!(
+        code,
+        "/// The types of the application's contract, serialized with BCS."
+    )?;
+    writeln!(code, "pub mod abi {{")?;
+    for (root, format) in [
+        ("Operation", &description.operation),
+        ("Response", &description.response),
+    ] {
+        if *format != Format::TypeName(root.to_owned()) {
+            writeln!(
+                code,
+                "    /// The `{root}` type of the application's contract."
+            )?;
+            writeln!(
+                code,
+                "    pub type {root} = {};",
+                rust_format(format, None)?
+            )?;
+            writeln!(code)?;
+        }
+    }
+    for (name, container) in &description.formats {
+        write_rust_container(&mut code, name, container)?;
+        writeln!(code)?;
+    }
+    writeln!(
+        code,
+        "    /// Serializes an `operation`, to be added to a block for the application."
+    )?;
+    writeln!(
+        code,
+        "    pub fn serialize_operation(operation: &Operation) -> Result<Vec<u8>, bcs::Error> {{"
+    )?;
+    writeln!(code, "        bcs::to_bytes(operation)")?;
+    writeln!(code, "    }}")?;
+    writeln!(code)?;
+    writeln!(
+        code,
+        "    /// Deserializes the `bytes` of the contract's response to an operation."
+    )?;
+    writeln!(
+        code,
+        "    pub fn deserialize_response(bytes: &[u8]) -> Result<Response, bcs::Error> {{"
+    )?;
+    writeln!(code, "        bcs::from_bytes(bytes)")?;
+    writeln!(code, "    }}")?;
+    writeln!(code, "}}")?;
+
+    writeln!(code)?;
+    writeln!(
+        code,
+        "/// Requests to the application's GraphQL service, with their typed results."
+    )?;
+    writeln!(code, "pub mod graphql {{")?;
+    code.push_str(RUST_GRAPHQL_PRELUDE);
+    for graphql_type in schema.types.values() {
+        schema.write_rust_type(&mut code, graphql_type)?;
+    }
+    for (operation, root) in schema.roots() {
+        for field in &root.fields {
+            schema.write_rust_request(&mut code, operation, field)?;
+        }
+    }
+    writeln!(code, "}}")?;
+    Ok(code)
+}
+
+/// Generates the TypeScript client module for the application described by `description`,
+/// for use with the `@linera/client` package.
+pub fn generate_typescript(description: &AbiDescription) -> Result<String> {
+    let schema = Schema::from_introspection(&description.graphql)?;
+    let mut code = header(&description.name, "//");
+
+    writeln!(code)?;
+    writeln!(
+        code,
+        "import type {{ Application }} from \"@linera/client\";"
+    )?;
+    writeln!(code)?;
+    writeln!(
+        code,
+        "/** The types of the application's contract, as serialized in JSON. */"
+    )?;
+    writeln!(code, "export namespace Abi {{")?;
+    for (root, format) in [
+        ("Operation", &description.operation),
+        ("Response", &description.response),
+    ] {
+        if *format != Format::TypeName(root.to_owned()) {
+            writeln!(
+                code,
+                "  export type {root} = {};",
+                typescript_format(format)?
+            )?;
+        }
+    }
+    for (name, container) in &description.formats {
+        write_typescript_container(&mut code, name, container)?;
+    }
+    writeln!(code, "}}")?;
+
+    for graphql_type in schema.types.values() {
+        schema.write_typescript_type(&mut code, graphql_type)?;
+    }
+
+    let class_name = format!("{}Client", description.name.to_case(Case::Pascal));
+    writeln!(code)?;
+    writeln!(
+        code,
+        "/** An error returned by the application's GraphQL service. */"
+    )?;
+    writeln!(code, "export class GraphQlError extends Error {{")?;
+    writeln!(
+        code,
+        "  constructor(readonly errors: {{ message: string }}[]) {{"
+    )?;
+    writeln!(
+        code,
+        "    super(errors.map((error) => error.message).join(\"\\n\"));"
+    )?;
+    writeln!(code, "  }}")?;
+    writeln!(code, "}}")?;
+    writeln!(code)?;
+    writeln!(
+        code,
+        "/** A client for the `{}` application's service. Mutations execute the operations \
+        they schedule. */",
+        description.name
+    )?;
+    writeln!(code, "export class {class_name} {{")?;
+    writeln!(
+        code,
+        "  constructor(private readonly application: Application) {{}}"
+    )?;
+    for (operation, root) in schema.roots() {
+        for field in &root.fields {
+            schema.write_typescript_request(&mut code, operation, field)?;
+        }
+    }
+    code.push_str(TYPESCRIPT_REQUEST_METHOD);
+    writeln!(code, "}}")?;
+    Ok(code)
+}
+
+/// The comment at the top of the generated modules.
+fn header(name: &str, comment: &str) -> String {
+    format!(
+        "{comment} Client bindings for the `{name}` application, generated by \
+        `linera project codegen`.\n\
+        {comment} Do not edit: run `linera project codegen` again instead.\n"
+    )
+}
+
+/// The definitions shared by the GraphQL requests of every Rust module.
+const RUST_GRAPHQL_PRELUDE: &str = r#"    use std::{fmt, marker::PhantomData};
+
+    use serde::de::DeserializeOwned;
+    use serde_json::{json, Value};
+
+    /// A request to the application's GraphQL service, whose result has the type `T`.
+    #[derive(Clone, Debug)]
+    pub struct Request<T> {
+        query: &'static str,
+        variables: Value,
+        field: &'static str,
+        result: PhantomData<T>,
+    }
+
+    impl<T: DeserializeOwned> Request<T> {
+        /// Returns the JSON body to send to the application's GraphQL endpoint.
+        pub fn body(&self) -> Value {
+            json!({ "query": self.query, "variables": self.variables })
+        }
+
+        /// Extracts the result from the JSON `response` of the application's GraphQL endpoint.
+        pub fn parse_response(&self, mut response: Value) -> Result<T, Error> {
+            if let Some(errors) = response.get("errors").and_then(Value::as_array) {
+                if !errors.is_empty() {
+                    return Err(Error::Service(errors.clone()));
+                }
+            }
+            let result = response
+                .pointer_mut(&format!("/data/{}", self.field))
+                .map(Value::take)
+                .unwrap_or(Value::Null);
+            serde_json::from_value(result).map_err(Error::Deserialization)
+        }
+    }
+
+    /// An error in the response of the application's GraphQL service.
+    #[derive(Debug)]
+    pub enum Error {
+        /// The service returned errors.
+        Service(Vec<Value>),
+        /// The result doesn't have the expected type.
+        Deserialization(serde_json::Error),
+    }
+
+    impl fmt::Display for Error {
+        fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+            match self {
+                Error::Service(errors) => write!(formatter, "service errors: {errors:?}"),
+                Error::Deserialization(error) => write!(formatter, "invalid result: {error}"),
+            }
+        }
+    }
+
+    impl std::error::Error for Error {}
+"#;
+
+/// The method of the TypeScript client that sends requests through the application.
+const TYPESCRIPT_REQUEST_METHOD: &str = r#"
+  private async request<T>(
+    query: string,
+    variables: Record<string, unknown>,
+    field: string,
+  ): Promise<T> {
+    const response = JSON.parse(
+      await this.application.query(JSON.stringify({ query, variables })),
+    );
+    if (response.errors?.length) {
+      throw new GraphQlError(response.errors);
+    }
+    return response.data[field] as T;
+  }
+"#;
+
+/// Writes the Rust definition of the ABI container `name`.
+fn write_rust_container(code: &mut String, name: &str, container: &ContainerFormat) -> Result<()> {
+    writeln!(
+        code,
+        "    #[derive(Clone, Debug, PartialEq, serde::Serialize, serde::Deserialize)]"
+    )?;
+    match container {
+        ContainerFormat::UnitStruct => writeln!(code, "    pub struct {name};")?,
+        ContainerFormat::NewTypeStruct(format) => writeln!(
+            code,
+            "    pub struct {name}(pub {});",
+            rust_format(format, Some(name))?
+        )?,
+        ContainerFormat::TupleStruct(formats) => writeln!(
+            code,
+            "    pub struct {name}({});",
+            formats
+                .iter()
+                .map(|format| Ok(format!("pub {}", rust_format(format, Some(name))?)))
+                .collect::<Result<Vec<_>>>()?
+                .join(", ")
+        )?,
+        ContainerFormat::Struct(fields) => {
+            writeln!(code, "    pub struct {name} {{")?;
+            write_rust_fields(code, fields, name, "        ", "pub ")?;
+            writeln!(code, "    }}")?;
+        }
+        ContainerFormat::Enum(variants) => {
+            writeln!(code, "    pub enum {name} {{")?;
+            for Named {
+                name: variant,
+                value,
+            } in variants.values()
+            {
+                match value {
+                    VariantFormat::Unit => writeln!(code, "        {variant},")?,
+                    VariantFormat::NewType(format) => writeln!(
+                        code,
+                        "        {variant}({}),",
+                        rust_format(format, Some(name))?
+                    )?,
+                    VariantFormat::Tuple(formats) => writeln!(
+                        code,
+                        "        {variant}({}),",
+                        formats
+                            .iter()
+                            .map(|format| rust_format(format, Some(name)))
+                            .collect::<Result<Vec<_>>>()?
+                            .join(", ")
+                    )?,
+                    VariantFormat::Struct(fields) => {
+                        writeln!(code, "        {variant} {{")?;
+                        write_rust_fields(code, fields, name, "            ", "")?;
+                        writeln!(code, "        }},")?;
+                    }
+                    VariantFormat::Variable(_) => bail!("Incomplete format for {name}::{variant}"),
+                }
+            }
+            writeln!(code, "    }}")?;
+        }
+    }
+    Ok(())
+}
+
+/// Writes the Rust fields of a struct or struct variant in the container `container`.
+fn write_rust_fields(
+    code: &mut String,
+    fields: &[Named<Format>],
+    container: &str,
+    indentation: &str,
+    visibility: &str,
+) -> Result<()> {
+    for Named { name, value } in fields {
+        let identifier = rust_identifier(name);
+        if identifier != *name {
+            writeln!(code, "{indentation}#[serde(rename = \"{name}\")]")?;
+        }
+        writeln!(
+            code,
+            "{indentation}{visibility}{identifier}: {},",
+            rust_format(value, Some(container))?
+        )?;
+    }
+    Ok(())
+}
+
+/// Returns the Rust type for an ABI `format`, boxing direct references to the `container`
+/// it is part of.
+fn rust_format(format: &Format, container: Option<&str>) -> Result<String> {
+    let rust_type = match format {
+        Format::TypeName(name) if Some(name.as_str()) == container => format!("Box<{name}>"),
+        Format::TypeName(name) => name.clone(),
+        Format::Unit => "()".to_owned(),
+        Format::Bool => "bool".to_owned(),
+        Format::I8 => "i8".to_owned(),
+        Format::I16 => "i16".to_owned(),
+        Format::I32 => "i32".to_owned(),
+        Format::I64 => "i64".to_owned(),
+        Format::I128 => "i128".to_owned(),
+        Format::U8 => "u8".to_owned(),
+        Format::U16 => "u16".to_owned(),
+        Format::U32 => "u32".to_owned(),
+        Format::U64 => "u64".to_owned(),
+        Format::U128 => "u128".to_owned(),
+        Format::F32 => "f32".to_owned(),
+        Format::F64 => "f64".to_owned(),
+        Format::Char => "char".to_owned(),
+        Format::Str => "String".to_owned(),
+        Format::Bytes => "Vec<u8>".to_owned(),
+        Format::Option(format) => format!("Option<{}>", rust_format(format, container)?),
+        Format::Seq(format) => format!("Vec<{}>", rust_format(format, None)?),
+        Format::Map { key, value } => format!(
+            "std::collections::BTreeMap<{}, {}>",
+            rust_format(key, None)?,
+            rust_format(value, None)?
+        ),
+        Format::Tuple(formats) if formats.len() == 1 => {
+            format!("({},)", rust_format(&formats[0], container)?)
+        }
+        Format::Tuple(formats) => format!(
+            "({})",
+            formats
+                .iter()
+                .map(|format| rust_format(format, container))
+                .collect::<Result<Vec<_>>>()?
+                .join(", ")
+        ),
+        Format::TupleArray { content, size } => {
+            format!("[{}; {size}]", rust_format(content, container)?)
+        }
+        Format::Variable(_) => bail!("Incomplete format in ABI description"),
+    };
+    Ok(rust_type)
+}
+
+/// Returns a Rust identifier for `name`, escaping keywords.
+fn rust_identifier(name: &str) -> String {
+    match name {
+        "self" | "Self" | "super" | "crate" => format!("{name}_"),
+        _ if RUST_KEYWORDS.contains(&name) => format!("r#{name}"),
+        _ => name.to_owned(),
+    }
+}
+
+/// Writes the TypeScript definition of the ABI container `name`.
+fn write_typescript_container(
+    code: &mut String,
+    name: &str,
+    container: &ContainerFormat,
+) -> Result<()> {
+    if STRING_TYPES.contains(&name) {
+        writeln!(code, "  export type {name} = string;")?;
+        return Ok(());
+    }
+    match container {
+        ContainerFormat::UnitStruct => writeln!(code, "  export type {name} = null;")?,
+        ContainerFormat::NewTypeStruct(format) => writeln!(
+            code,
+            "  export type {name} = {};",
+            typescript_format(format)?
+        )?,
+        ContainerFormat::TupleStruct(formats) => writeln!(
+            code,
+            "  export type {name} = {};",
+            typescript_tuple(formats)?
+        )?,
+        ContainerFormat::Struct(fields) => writeln!(
+            code,
+            "  export interface {name} {};",
+            typescript_fields(fields)?
+        )?,
+        ContainerFormat::Enum(variants) => {
+            writeln!(code, "  export type {name} =")?;
+            for Named {
+                name: variant,
+                value,
+            } in variants.values()
+            {
+                let variant_type = match value {
+                    VariantFormat::Unit => format!("\"{variant}\""),
+                    VariantFormat::NewType(format) => {
+                        format!("{{ {variant}: {} }}", typescript_format(format)?)
+                    }
+                    VariantFormat::Tuple(formats) => {
+                        format!("{{ {variant}: {} }}", typescript_tuple(formats)?)
+                    }
+                    VariantFormat::Struct(fields) => {
+                        format!("{{ {variant}: {} }}", typescript_fields(fields)?)
+                    }
+                    VariantFormat::Variable(_) => bail!("Incomplete format for {name}::{variant}"),
+                };
+                writeln!(code, "    | {variant_type}")?;
+            }
+            writeln!(code, "  ;")?;
+        }
+    }
+    Ok(())
+}
+
+/// Returns the TypeScript object type with the `fields`.
+fn typescript_fields(fields: &[Named<Format>]) -> Result<String> {
+    let fields = fields
+        .iter()
+        .map(|Named { name, value }| {
+            Ok(format!(
+                "{}: {}",
+                typescript_property(name),
+                typescript_format(value)?
+            ))
+        })
+        .collect::<Result<Vec<_>>>()?;
+    Ok(format!("{{ {} }}", fields.join("; ")))
+}
+
+/// Returns the TypeScript tuple type with the `formats`.
+fn typescript_tuple(formats: &[Format]) -> Result<String> {
+    let formats = formats
+        .iter()
+        .map(typescript_format)
+        .collect::<Result<Vec<_>>>()?;
+    Ok(format!("[{}]", formats.join(", ")))
+}
+
+/// Returns the TypeScript type for the JSON representation of an ABI `format`.
+fn typescript_format(format: &Format) -> Result<String> {
+    let typescript_type = match format {
+        Format::TypeName(name) if STRING_TYPES.contains(&name.as_str()) => "string".to_owned(),
+        Format::TypeName(name) => name.clone(),
+        Format::Unit => "null".to_owned(),
+        Format::Bool => "boolean".to_owned(),
+        Format::I8
+        | Format::I16
+        | Format::I32
+        | Format::I64
+        | Format::I128
+        | Format::U8
+        | Format::U16
+        | Format::U32
+        | Format::U64
+        | Format::U128
+        | Format::F32
+        | Format::F64 => "number".to_owned(),
+        Format::Char | Format::Str => "string".to_owned(),
+        Format::Bytes => "number[]".to_owned(),
+        Format::Option(format) => format!("{} | null", typescript_format(format)?),
+        Format::Seq(format)
+        | Format::TupleArray {
+            content: format, ..
+        } => format!("{}[]", parenthesize(typescript_format(format)?)),
+        Format::Map { value, .. } => format!("Record<string, {}>", typescript_format(value)?),
+        Format::Tuple(formats) => typescript_tuple(formats)?,
+        Format::Variable(_) => bail!("Incomplete format in ABI description"),
+    };
+    Ok(typescript_type)
+}
+
+/// Returns a TypeScript property name for `name`, quoting it if needed.
+fn typescript_property(name: &str) -> String {
+    let is_identifier = name.chars().enumerate().all(|(index, c)| {
+        c == '_' || c == '$' || c.is_ascii_alphabetic() || (index > 0 && c.is_ascii_digit())
+    });
+    if is_identifier && !name.is_empty() {
+        name.to_owned()
+    } else {
+        format!("{name:?}")
+    }
+}
+
+/// Returns a TypeScript parameter name for `name`, avoiding reserved words.
+fn typescript_parameter(name: &str) -> String {
+    if TYPESCRIPT_RESERVED_WORDS.contains(&name) {
+        format!("{name}_")
+    } else {
+        name.to_owned()
+    }
+}
+
+/// Wraps a TypeScript union type in parentheses, so that it can be used as an array element.
+fn parenthesize(typescript_type: String) -> String {
+    if typescript_type.contains(" | ") {
+        format!("({typescript_type})")
+    } else {
+        typescript_type
+    }
+}
+
+/// A reference to a GraphQL type.
+#[derive(Clone, Debug)]
+enum TypeRef {
+    Named(String),
+    List(Box<TypeRef>),
+    NonNull(Box<TypeRef>),
+}
+
+impl TypeRef {
+    /// Returns the name of the type, without list and non-null wrappers.
+    fn name(&self) -> &str {
+        match self {
+            TypeRef::Named(name) => name,
+            TypeRef::List(inner) | TypeRef::NonNull(inner) => inner.name(),
+        }
+    }
+
+    /// Returns the type as written in GraphQL documents.
+    fn to_graphql(&self) -> String {
+        match self {
+            TypeRef::Named(name) => name.clone(),
+            TypeRef::List(inner) => format!("[{}]", inner.to_graphql()),
+            TypeRef::NonNull(inner) => format!("{}!", inner.to_graphql()),
+        }
+    }
+
+    /// Returns the type without a non-null wrapper.
+    fn nullable(&self) -> &TypeRef {
+        match self {
+            TypeRef::NonNull(inner) => inner,
+            other => other,
+        }
+    }
+}
+
+/// A type reference in an introspection result.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct IntrospectedTypeRef {
+    kind: String,
+    name: Option<String>,
+    of_type: Option<Box<IntrospectedTypeRef>>,
+}
+
+impl TryFrom<IntrospectedTypeRef> for TypeRef {
+    type Error = anyhow::Error;
+
+    fn try_from(type_ref: IntrospectedTypeRef) -> Result<Self> {
+        let inner = |of_type: Option<Box<IntrospectedTypeRef>>| -> Result<Box<TypeRef>> {
+            Ok(Box::new(
+                (*of_type.context("Wrapping type without an inner type")?).try_into()?,
+            ))
+        };
+        match type_ref.kind.as_str() {
+            "LIST" => Ok(TypeRef::List(inner(type_ref.of_type)?)),
+            "NON_NULL" => Ok(TypeRef::NonNull(inner(type_ref.of_type)?)),
+            _ => Ok(TypeRef::Named(
+                type_ref.name.context("Named type without a name")?,
+            )),
+        }
+    }
+}
+
+/// An argument or input field in an introspection result.
+#[derive(Debug, Deserialize)]
+struct InputValue {
+    name: String,
+    #[serde(rename = "type")]
+    type_ref: IntrospectedTypeRef,
+}
+
+/// A field in an introspection result.
+#[derive(Debug, Deserialize)]
+struct IntrospectedField {
+    name: String,
+    #[serde(default)]
+    args: Vec<InputValue>,
+    #[serde(rename = "type")]
+    type_ref: IntrospectedTypeRef,
+}
+
+/// An enum value in an introspection result.
+#[derive(Debug, Deserialize)]
+struct EnumValue {
+    name: String,
+}
+
+/// A type in an introspection result.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct IntrospectedType {
+    kind: String,
+    name: String,
+    fields: Option<Vec<IntrospectedField>>,
+    input_fields: Option<Vec<InputValue>>,
+    enum_values: Option<Vec<EnumValue>>,
+}
+
+/// The root types in an introspection result.
+#[derive(Debug, Deserialize)]
+struct RootType {
+    name: String,
+}
+
+/// The schema in an introspection result.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct IntrospectedSchema {
+    query_type: Option<RootType>,
+    mutation_type: Option<RootType>,
+    types: Vec<IntrospectedType>,
+}
+
+/// The kind of a named GraphQL type.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum Kind {
+    Scalar,
+    Object,
+    Abstract,
+    Enum,
+    InputObject,
+}
+
+/// An argument, input field or output field of a GraphQL type.
+#[derive(Clone, Debug)]
+struct Field {
+    name: String,
+    arguments: Vec<(String, TypeRef)>,
+    type_ref: TypeRef,
+}
+
+/// A named GraphQL type.
+#[derive(Clone, Debug)]
+struct GraphQlType {
+    kind: Kind,
+    name: String,
+    fields: Vec<Field>,
+    enum_values: Vec<String>,
+}
+
+/// The parts of a GraphQL schema that bindings are generated for.
+#[derive(Debug)]
+struct Schema {
+    query_type: Option<String>,
+    mutation_type: Option<String>,
+    types: BTreeMap<String, GraphQlType>,
+}
+
+impl Schema {
+    /// Reads the schema from the result of an introspection query.
+    fn from_introspection(introspection: &Value) -> Result<Self> {
+        let schema = IntrospectedSchema::deserialize(&introspection["__schema"])
+            .context("Invalid GraphQL introspection result")?;
+        let mut types = BTreeMap::new();
+        for introspected in schema.types {
+            if introspected.name.starts_with("__")
+                || BUILT_IN_SCALARS.contains(&introspected.name.as_str())
+            {
+                continue;
+            }
+            let kind = match introspected.kind.as_str() {
+                "SCALAR" => Kind::Scalar,
+                "OBJECT" => Kind::Object,
+                "INTERFACE" | "UNION" => Kind::Abstract,
+                "ENUM" => Kind::Enum,
+                "INPUT_OBJECT" => Kind::InputObject,
+                kind => bail!("Unknown kind {kind} of GraphQL type {}", introspected.name),
+            };
+            let mut fields = Vec::new();
+            for field in introspected.fields.into_iter().flatten() {
+                fields.push(Field {
+                    name: field.name,
+                    arguments: field
+                        .args
+                        .into_iter()
+                        .map(|argument| Ok((argument.name, argument.type_ref.try_into()?)))
+                        .collect::<Result<_>>()?,
+                    type_ref: field.type_ref.try_into()?,
+                });
+            }
+            for input_field in introspected.input_fields.into_iter().flatten() {
+                fields.push(Field {
+                    name: input_field.name,
+                    arguments: Vec::new(),
+                    type_ref: input_field.type_ref.try_into()?,
+                });
+            }
+            let enum_values = introspected
+                .enum_values
+                .into_iter()
+                .flatten()
+                .map(|value| value.name)
+                .collect();
+            types.insert(
+                introspected.name.clone(),
+                GraphQlType {
+                    kind,
+                    name: introspected.name,
+                    fields,
+                    enum_values,
+                },
+            );
+        }
+        Ok(Schema {
+            query_type: schema.query_type.map(|root| root.name),
+            mutation_type: schema.mutation_type.map(|root| root.name),
+            types,
+        })
+    }
+
+    /// Returns the root types of the queries and mutations, with the GraphQL operation
+    /// keyword for each.
+    fn roots(&self) -> impl Iterator<Item = (&'static str, &GraphQlType)> {
+        [
+            ("query", self.query_type.as_ref()),
+            ("mutation", self.mutation_type.as_ref()),
+        ]
+        .into_iter()
+        .filter_map(|(operation, name)| Some((operation, self.types.get(name?)?)))
+    }
+
+    /// Returns whether `name` is the name of a root type.
+    fn is_root(&self, name: &str) -> bool {
+        self.query_type.as_deref() == Some(name) || self.mutation_type.as_deref() == Some(name)
+    }
+
+    /// Returns the kind of the named type `name`, if it isn't a built-in scalar.
+    fn kind(&self, name: &str) -> Option<Kind> {
+        self.types.get(name).map(|graphql_type| graphql_type.kind)
+    }
+
+    /// Returns the selection set for a field of the type `type_ref`, selecting every field
+    /// without arguments, except those that would recurse into a type in `path`.
+    fn selection(&self, type_ref: &TypeRef, path: &mut Vec<String>) -> String {
+        let name = type_ref.name();
+        match self.types.get(name) {
+            Some(graphql_type) if graphql_type.kind == Kind::Object => {
+                path.push(name.to_owned());
+                let mut selections = Vec::new();
+                for field in &graphql_type.fields {
+                    let field_type = field.type_ref.name();
+                    if !field.arguments.is_empty() || path.iter().any(|seen| seen == field_type) {
+                        continue;
+                    }
+                    selections.push(format!(
+                        "{}{}",
+                        field.name,
+                        self.selection(&field.type_ref, path)
+                    ));
+                }
+                path.pop();
+                if selections.is_empty() {
+                    selections.push("__typename".to_owned());
+                }
+                format!(" {{ {} }}", selections.join(" "))
+            }
+            Some(graphql_type) if graphql_type.kind == Kind::Abstract => {
+                " { __typename }".to_owned()
+            }
+            _ => String::new(),
+        }
+    }
+
+    /// Returns the GraphQL document requesting the `field` of the root type of the
+    /// `operation`.
+    fn document(&self, operation: &str, field: &Field) -> String {
+        let variables = field
+            .arguments
+            .iter()
+            .map(|(name, type_ref)| format!("${name}: {}", type_ref.to_graphql()))
+            .collect::<Vec<_>>();
+        let arguments = field
+            .arguments
+            .iter()
+            .map(|(name, _)| format!("{name}: ${name}"))
+            .collect::<Vec<_>>();
+        let mut document = operation.to_owned();
+        if !variables.is_empty() {
+            write!(document, "({})", variables.join(", ")).expect("Writing to a string");
+        }
+        write!(document, " {{ {}", field.name).expect("Writing to a string");
+        if !arguments.is_empty() {
+            write!(document, "({})", arguments.join(", ")).expect("Writing to a string");
+        }
+        document.push_str(&self.selection(&field.type_ref, &mut Vec::new()));
+        document.push_str(" }");
+        document
+    }
+
+    /// Writes the Rust definition of a GraphQL type.
+    fn write_rust_type(&self, code: &mut String, graphql_type: &GraphQlType) -> Result<()> {
+        let name = &graphql_type.name;
+        match graphql_type.kind {
+            Kind::Scalar => {
+                let alias = if STRING_TYPES.contains(&name.as_str()) {
+                    "String"
+                } else {
+                    "Value"
+                };
+                writeln!(code)?;
+                writeln!(code, "    /// The `{name}` scalar.")?;
+                writeln!(code, "    pub type {name} = {alias};")?;
+            }
+            Kind::Enum => {
+                writeln!(code)?;
+                writeln!(
+                    code,
+                    "    #[derive(Clone, Copy, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]"
+                )?;
+                writeln!(code, "    pub enum {name} {{")?;
+                for value in &graphql_type.enum_values {
+                    writeln!(code, "        #[serde(rename = \"{value}\")]")?;
+                    writeln!(code, "        {},", value.to_case(Case::Pascal))?;
+                }
+                writeln!(code, "    }}")?;
+            }
+            Kind::InputObject => {
+                writeln!(code)?;
+                writeln!(
+                    code,
+                    "    #[derive(Clone, Debug, PartialEq, serde::Serialize)]"
+                )?;
+                writeln!(code, "    pub struct {name} {{")?;
+                for field in &graphql_type.fields {
+                    let mut rust_type = self.rust_field_type(&field.type_ref);
+                    self.write_rust_field_attributes(code, &field.name)?;
+                    if !matches!(field.type_ref, TypeRef::NonNull(_)) {
+                        writeln!(
+                            code,
+                            "        #[serde(skip_serializing_if = \"Option::is_none\")]"
+                        )?;
+                        rust_type = format!("Option<{rust_type}>");
+                    }
+                    writeln!(
+                        code,
+                        "        pub {}: {rust_type},",
+                        rust_identifier(&field.name.to_case(Case::Snake))
+                    )?;
+                }
+                writeln!(code, "    }}")?;
+            }
+            Kind::Object if !self.is_root(name) => {
+                writeln!(code)?;
+                writeln!(
+                    code,
+                    "    #[derive(Clone, Debug, Default, PartialEq, serde::Deserialize)]"
+                )?;
+                writeln!(code, "    #[serde(default)]")?;
+                writeln!(code, "    pub struct {name} {{")?;
+                for field in graphql_type
+                    .fields
+                    .iter()
+                    .filter(|field| field.arguments.is_empty())
+                {
+                    self.write_rust_field_attributes(code, &field.name)?;
+                    writeln!(
+                        code,
+                        "        pub {}: Option<{}>,",
+                        rust_identifier(&field.name.to_case(Case::Snake)),
+                        self.rust_field_type(&field.type_ref)
+                    )?;
+                }
+                writeln!(code, "    }}")?;
+            }
+            Kind::Object | Kind::Abstract => {}
+        }
+        Ok(())
+    }
+
+    /// Writes the `serde` attribute renaming a Rust field to its GraphQL `name`, if needed.
+    fn write_rust_field_attributes(&self, code: &mut String, name: &str) -> Result<()> {
+        if name.to_case(Case::Snake) != name {
+            writeln!(code, "        #[serde(rename = \"{name}\")]")?;
+        }
+        Ok(())
+    }
+
+    /// Writes the Rust function building the request for the `field` of the root type of the
+    /// `operation`.
+    fn write_rust_request(&self, code: &mut String, operation: &str, field: &Field) -> Result<()> {
+        let function = rust_identifier(&format!("{operation}_{}", field.name.to_case(Case::Snake)));
+        let parameters = field
+            .arguments
+            .iter()
+            .map(|(name, type_ref)| {
+                format!(
+                    "{}: {}",
+                    rust_identifier(&name.to_case(Case::Snake)),
+                    self.rust_type(type_ref)
+                )
+            })
+            .collect::<Vec<_>>();
+        let variables = field
+            .arguments
+            .iter()
+            .map(|(name, _)| format!("{name:?}: {}", rust_identifier(&name.to_case(Case::Snake))))
+            .collect::<Vec<_>>();
+        writeln!(code)?;
+        match operation {
+            "mutation" => writeln!(
+                code,
+                "    /// Runs the `{}` mutation, scheduling operations to execute.",
+                field.name
+            )?,
+            _ => writeln!(code, "    /// Queries the `{}` field.", field.name)?,
+        }
+        writeln!(
+            code,
+            "    pub fn {function}({}) -> Request<{}> {{",
+            parameters.join(", "),
+            self.rust_type(&field.type_ref)
+        )?;
+        writeln!(code, "        Request {{")?;
+        writeln!(
+            code,
+            "            query: {:?},",
+            self.document(operation, field)
+        )?;
+        if variables.is_empty() {
+            writeln!(code, "            variables: json!({{}}),")?;
+        } else {
+            writeln!(
+                code,
+                "            variables: json!({{ {} }}),",
+                variables.join(", ")
+            )?;
+        }
+        writeln!(code, "            field: {:?},", field.name)?;
+        writeln!(code, "            result: PhantomData,")?;
+        writeln!(code, "        }}")?;
+        writeln!(code, "    }}")?;
+        Ok(())
+    }
+
+    /// Returns the Rust type for a value of the GraphQL type `type_ref`.
+    fn rust_type(&self, type_ref: &TypeRef) -> String {
+        match type_ref {
+            TypeRef::NonNull(inner) => self.rust_non_null_type(inner),
+            other => format!("Option<{}>", self.rust_non_null_type(other)),
+        }
+    }
+
+    /// Returns the Rust type for a non-null value of the GraphQL type `type_ref`.
+    fn rust_non_null_type(&self, type_ref: &TypeRef) -> String {
+        match type_ref {
+            TypeRef::NonNull(inner) => self.rust_non_null_type(inner),
+            TypeRef::List(inner) => format!("Vec<{}>", self.rust_type(inner)),
+            TypeRef::Named(name) => match name.as_str() {
+                "Int" => "i32".to_owned(),
+                "Float" => "f64".to_owned(),
+                "String" | "ID" => "String".to_owned(),
+                "Boolean" => "bool".to_owned(),
+                _ if self.kind(name) == Some(Kind::Abstract) => "Value".to_owned(),
+                _ => name.clone(),
+            },
+        }
+    }
+
+    /// Returns the Rust type for a non-null value of the type `type_ref` in a field of an
+    /// object or input object, boxing the objects and input objects it contains directly, since
+    /// they may contain the field's type in turn.
+    fn rust_field_type(&self, type_ref: &TypeRef) -> String {
+        let rust_type = self.rust_non_null_type(type_ref);
+        match type_ref.nullable() {
+            TypeRef::Named(name)
+                if matches!(self.kind(name), Some(Kind::Object | Kind::InputObject)) =>
+            {
+                format!("Box<{rust_type}>")
+            }
+            _ => rust_type,
+        }
+    }
+
+    /// Writes the TypeScript definition of a GraphQL type.
+    fn write_typescript_type(&self, code: &mut String, graphql_type: &GraphQlType) -> Result<()> {
+        let name = &graphql_type.name;
+        match graphql_type.kind {
+            Kind::Scalar => {
+                let alias = if STRING_TYPES.contains(&name.as_str()) {
+                    "string"
+                } else {
+                    "unknown"
+                };
+                writeln!(code)?;
+                writeln!(code, "/** The `{name}` scalar. */")?;
+                writeln!(code, "export type {name} = {alias};")?;
+            }
+            Kind::Enum => {
+                let values = graphql_type
+                    .enum_values
+                    .iter()
+                    .map(|value| format!("{value:?}"))
+                    .collect::<Vec<_>>();
+                writeln!(code)?;
+                writeln!(code, "export type {name} = {};", values.join(" | "))?;
+            }
+            Kind::InputObject => {
+                writeln!(code)?;
+                writeln!(code, "export interface {name} {{")?;
+                for field in &graphql_type.fields {
+                    let optional = if matches!(field.type_ref, TypeRef::NonNull(_)) {
+                        ""
+                    } else {
+                        "?"
+                    };
+                    writeln!(
+                        code,
+                        "  {}{optional}: {};",
+                        typescript_property(&field.name),
+                        self.typescript_type(&field.type_ref)
+                    )?;
+                }
+                writeln!(code, "}}")?;
+            }
+            Kind::Object if !self.is_root(name) => {
+                writeln!(code)?;
+                writeln!(code, "export interface {name} {{")?;
+                for field in graphql_type
+                    .fields
+                    .iter()
+                    .filter(|field| field.arguments.is_empty())
+                {
+                    writeln!(
+                        code,
+                        "  {}?: {};",
+                        typescript_property(&field.name),
+                        self.typescript_type(&field.type_ref)
+                    )?;
+                }
+                writeln!(code, "}}")?;
+            }
+            Kind::Object | Kind::Abstract => {}
+        }
+        Ok(())
+    }
+
+    /// Writes the TypeScript method sending the request for the `field` of the root type of
+    /// the `operation`.
+    fn write_typescript_request(
+        &self,
+        code: &mut String,
+        operation: &str,
+        field: &Field,
+    ) -> Result<()> {
+        let method = format!("{operation}_{}", field.name).to_case(Case::Camel);
+        let parameters = field
+            .arguments
+            .iter()
+            .map(|(name, type_ref)| {
+                format!(
+                    "{}: {}",
+                    typescript_parameter(name),
+                    self.typescript_type(type_ref)
+                )
+            })
+            .collect::<Vec<_>>();
+        let variables = field
+            .arguments
+            .iter()
+            .map(|(name, _)| {
+                format!(
+                    "{}: {}",
+                    typescript_property(name),
+                    typescript_parameter(name)
+                )
+            })
+            .collect::<Vec<_>>();
+        writeln!(code)?;
+        match operation {
+            "mutation" => writeln!(
+                code,
+                "  /** Runs the `{}` mutation, executing the operations it schedules. */",
+                field.name
+            )?,
+            _ => writeln!(code, "  /** Queries the `{}` field. */", field.name)?,
+        }
+        writeln!(
+            code,
+            "  {method}({}): Promise<{}> {{",
+            parameters.join(", "),
+            self.typescript_type(&field.type_ref)
+        )?;
+        writeln!(code, "    return this.request(")?;
+        writeln!(code, "      {:?},", self.document(operation, field))?;
+        writeln!(code, "      {{ {} }},", variables.join(", "))?;
+        writeln!(code, "      {:?},", field.name)?;
+        writeln!(code, "    );")?;
+        writeln!(code, "  }}")?;
+        Ok(())
+    }
+
+    /// Returns the TypeScript type for a value of the GraphQL type `type_ref`.
+    fn typescript_type(&self, type_ref: &TypeRef) -> String {
+        match type_ref {
+            TypeRef::NonNull(inner) => self.typescript_non_null_type(inner),
+            other => format!("{} | null", self.typescript_non_null_type(other)),
+        }
+    }
+
+    /// Returns the TypeScript type for a non-null value of the GraphQL type `type_ref`.
+    fn typescript_non_null_type(&self, type_ref: &TypeRef) -> String {
+        match type_ref {
+            TypeRef::NonNull(inner) => self.typescript_non_null_type(inner),
+            TypeRef::List(inner) => format!("{}[]", parenthesize(self.typescript_type(inner))),
+            TypeRef::Named(name) => match name.as_str() {
+                "Int" | "Float" => "number".to_owned(),
+                "String" | "ID" => "string".to_owned(),
+                "Boolean" => "boolean".to_owned(),
+                _ if self.kind(name) == Some(Kind::Abstract) => "unknown".to_owned(),
+                _ => name.clone(),
+            },
+        }
+    }
+}
+
+/// Returns the names of the types in the `formats` of the ABI description that are not
+/// defined in it.
+pub fn undefined_types(description: &AbiDescription) -> BTreeSet<String> {
+    fn collect(format: &Format, names: &mut BTreeSet<String>) {
+        match format {
+            Format::TypeName(name) => {
+                names.insert(name.clone());
+            }
+            Format::Option(format) | Format::Seq(format) => collect(format, names),
+            Format::TupleArray { content, .. } => collect(content, names),
+            Format::Map { key, value } => {
+                collect(key, names);
+                collect(value, names);
+            }
+            Format::Tuple(formats) => formats.iter().for_each(|format| collect(format, names)),
+            _ => {}
+        }
+    }
+
+    let mut names = BTreeSet::new();
+    collect(&description.operation, &mut names);
+    collect(&description.response, &mut names);
+    for container in description.formats.values() {
+        match container {
+            ContainerFormat::UnitStruct => {}
+            ContainerFormat::NewTypeStruct(format) => collect(format, &mut names),
+            ContainerFormat::TupleStruct(formats) => formats
+                .iter()
+                .for_each(|format| collect(format, &mut names)),
+            ContainerFormat::Struct(fields) => fields
+                .iter()
+                .for_each(|field| collect(&field.value, &mut names)),
+            ContainerFormat::Enum(variants) => {
+                for variant in variants.values() {
+                    match &variant.value {
+                        VariantFormat::NewType(format) => collect(format, &mut names),
+                        VariantFormat::Tuple(formats) => formats
+                            .iter()
+                            .for_each(|format| collect(format, &mut names)),
+                        VariantFormat::Struct(fields) => fields
+                            .iter()
+                            .for_each(|field| collect(&field.value, &mut names)),
+                        VariantFormat::Unit | VariantFormat::Variable(_) => {}
+                    }
+                }
+            }
+        }
+    }
+    names.retain(|name| !description.formats.contains_key(name));
+    names
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    /// Returns the description of an application with a `u64` counter, using every kind of
+    /// container and GraphQL type.
+    fn counter_description() -> AbiDescription {
+        let mut formats = Registry::new();
+        formats.insert(
+            "Settings".to_owned(),
+            ContainerFormat::Struct(vec![
+                Named {
+                    name: "label".to_owned(),
+                    value: Format::Option(Box::new(Format::Str)),
+                },
+                Named {
+                    name: "limits".to_owned(),
+                    value: Format::Map {
+                        key: Box::new(Format::Str),
+                        value: Box::new(Format::U64),
+                    },
+                },
+                Named {
+                    name: "type".to_owned(),
+                    value: Format::TypeName("Tag".to_owned()),
+                },
+                Named {
+                    name: "parent".to_owned(),
+                    value: Format::Option(Box::new(Format::TypeName("Settings".to_owned()))),
+                },
+            ]),
+        );
+        formats.insert(
+            "Tag".to_owned(),
+            ContainerFormat::NewTypeStruct(Box::new(Format::Bytes)),
+        );
+        formats.insert(
+            "Pair".to_owned(),
+            ContainerFormat::TupleStruct(vec![
+                Format::U8,
+                Format::TupleArray {
+                    content: Box::new(Format::Bool),
+                    size: 2,
+                },
+            ]),
+        );
+        formats.insert("Marker".to_owned(), ContainerFormat::UnitStruct);
+        formats.insert(
+            "Operation".to_owned(),
+            ContainerFormat::Enum(BTreeMap::from([
+                (
+                    0,
+                    Named {
+                        name: "Increment".to_owned(),
+                        value: VariantFormat::Struct(vec![Named {
+                            name: "value".to_owned(),
+                            value: Format::U64,
+                        }]),
+                    },
+                ),
+                (
+                    1,
+                    Named {
+                        name: "Reset".to_owned(),
+                        value: VariantFormat::Unit,
+                    },
+                ),
+                (
+                    2,
+                    Named {
+                        name: "Configure".to_owned(),
+                        value: VariantFormat::NewType(Box::new(Format::TypeName(
+                            "Settings".to_owned(),
+                        ))),
+                    },
+                ),
+                (
+                    3,
+                    Named {
+                        name: "Mark".to_owned(),
+                        value: VariantFormat::Tuple(vec![
+                            Format::TypeName("Pair".to_owned()),
+                            Format::TypeName("Marker".to_owned()),
+                        ]),
+                    },
+                ),
+            ])),
+        );
+        let named = |name: &str| json!({ "kind": "SCALAR", "name": name, "ofType": null });
+        let other = |kind: &str, name: &str| json!({ "kind": kind, "name": name, "ofType": null });
+        let non_null =
+            |of_type: Value| json!({ "kind": "NON_NULL", "name": null, "ofType": of_type });
+        let list = |of_type: Value| json!({ "kind": "LIST", "name": null, "ofType": of_type });
+        AbiDescription {
+            name: "my-counter".to_owned(),
+            formats,
+            operation: Format::TypeName("Operation".to_owned()),
+            response: Format::U64,
+            graphql: json!({
+                "__schema": {
+                    "queryType": { "name": "QueryRoot" },
+                    "mutationType": { "name": "MutationRoot" },
+                    "types": [
+                        {
+                            "kind": "OBJECT",
+                            "name": "QueryRoot",
+                            "fields": [
+                                {
+                                    "name": "value",
+                                    "args": [],
+                                    "type": non_null(named("Int")),
+                                },
+                                {
+                                    "name": "history",
+                                    "args": [{ "name": "filter", "type": other("INPUT_OBJECT", "Filter") }],
+                                    "type": non_null(list(non_null(other("OBJECT", "Entry")))),
+                                },
+                            ],
+                        },
+                        {
+                            "kind": "OBJECT",
+                            "name": "Entry",
+                            "fields": [
+                                { "name": "blockHeight", "args": [], "type": named("Int") },
+                                { "name": "kind", "args": [], "type": non_null(other("ENUM", "Kind")) },
+                                { "name": "previous", "args": [], "type": other("OBJECT", "Entry") },
+                            ],
+                        },
+                        {
+                            "kind": "ENUM",
+                            "name": "Kind",
+                            "enumValues": [{ "name": "INCREMENT" }, { "name": "RESET" }],
+                        },
+                        {
+                            "kind": "INPUT_OBJECT",
+                            "name": "Filter",
+                            "inputFields": [
+                                { "name": "kind", "type": other("ENUM", "Kind") },
+                                { "name": "minHeight", "type": non_null(named("Int")) },
+                            ],
+                        },
+                        {
+                            "kind": "OBJECT",
+                            "name": "MutationRoot",
+                            "fields": [{
+                                "name": "increment",
+                                "args": [{ "name": "value", "type": non_null(named("Int")) }],
+                                "type": non_null(named("Amount")),
+                            }],
+                        },
+                        { "kind": "SCALAR", "name": "Amount" },
+                        { "kind": "SCALAR", "name": "Int" },
+                    ],
+                }
+            }),
+        }
+    }
+
+    #[test]
+    fn rust_bindings() {
+        let code = generate_rust(&counter_description()).unwrap();
+        assert!(code.contains("    pub type Response = u64;"));
+        assert!(!code.contains("pub type Operation"));
+        assert!(code.contains("    pub enum Operation {\n        Increment {\n            value: u64,\n        },\n        Reset,\n        Configure(Settings),\n        Mark(Pair, Marker),\n    }"));
+        assert!(code.contains("        pub parent: Option<Box<Settings>>,"));
+        assert!(code.contains("        pub previous: Option<Box<Entry>>,"));
+        assert!(code.contains("    pub type Amount = String;"));
+        assert!(code.contains("    pub fn query_value() -> Request<i32> {"));
+        assert!(code.contains("            query: \"query { value }\","));
+        assert!(code.contains("    pub fn mutation_increment(value: i32) -> Request<Amount> {"));
+        assert!(code.contains(
+            "            query: \"mutation($value: Int!) { increment(value: $value) }\","
+        ));
+        assert!(code.contains("            variables: json!({ \"value\": value }),"));
+    }
+
+    /// The Rust bindings generated for [`counter_description`], compiled with the tests.
+    #[allow(dead_code)]
+    mod counter_bindings {
+        include!("../tests/fixtures/counter_bindings.rs.expected");
+    }
+
+    #[test]
+    fn compiled_rust_bindings_are_up_to_date() {
+        assert_eq!(
+            generate_rust(&counter_description()).unwrap(),
+            include_str!("../tests/fixtures/counter_bindings.rs.expected"),
+            "tests/fixtures/counter_bindings.rs.expected must contain the generated bindings"
+        );
+    }
+
+    #[test]
+    fn compiled_rust_bindings() {
+        use counter_bindings::{abi, graphql};
+
+        let operation = abi::Operation::Increment { value: 3 };
+        let bytes = abi::serialize_operation(&operation).unwrap();
+        assert_eq!(bytes, bcs::to_bytes(&(0_u8, 3_u64)).unwrap());
+        let bytes = bcs::to_bytes(&5_u64).unwrap();
+        assert_eq!(abi::deserialize_response(&bytes).unwrap(), 5);
+
+        let request = graphql::mutation_increment(3);
+        assert_eq!(
+            request.body(),
+            json!({
+                "query": "mutation($value: Int!) { increment(value: $value) }",
+                "variables": { "value": 3 },
+            })
+        );
+        let request = graphql::query_history(Some(graphql::Filter {
+            kind: None,
+            min_height: 1,
+        }));
+        assert_eq!(
+            request.body()["variables"],
+            json!({ "filter": { "minHeight": 1 } })
+        );
+        let response = json!({ "data": { "history": [{ "blockHeight": 2, "kind": "RESET" }] } });
+        assert_eq!(
+            request.parse_response(response).unwrap(),
+            vec![graphql::Entry {
+                block_height: Some(2),
+                kind: Some(graphql::Kind::Reset),
+                previous: None,
+            }]
+        );
+        let response = json!({ "errors": [{ "message": "Invalid filter" }] });
+        assert!(matches!(
+            request.parse_response(response),
+            Err(graphql::Error::Service(_))
+        ));
+    }
+
+    #[test]
+    fn typescript_bindings() {
+        let code = generate_typescript(&counter_description()).unwrap();
+        assert!(code.contains("  export type Response = number;"));
+        assert!(code.contains("    | { Increment: { value: number } }\n    | \"Reset\"\n"));
+        assert!(code.contains("export type Amount = string;"));
+        assert!(code.contains("export class MyCounterClient {"));
+        assert!(code.contains("  queryValue(): Promise<number> {"));
+        assert!(code.contains("  mutationIncrement(value: number): Promise<Amount> {"));
+    }
+
+    #[test]
+    fn undefined_types_are_listed() {
+        let mut description = counter_description();
+        description.response = Format::Seq(Box::new(Format::TypeName("Missing".to_owned())));
+        assert_eq!(
+            undefined_types(&description),
+            BTreeSet::from(["Missing".to_owned()])
+        );
+    }
+}
@@ -8,10 +8,12 @@
 
 pub mod cli;
 pub mod cli_wrappers;
+pub mod codegen;
 pub mod node_service;
 pub mod project;
 #[cfg(with_metrics)]
 pub mod prometheus_server;
 pub mod storage;
+pub mod tenants;
 pub mod util;
 pub mod wallet;
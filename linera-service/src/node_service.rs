@@ -1,34 +1,53 @@
 // Copyright (c) Zefchain Labs, Inc.
 // SPDX-License-Identifier: Apache-2.0
 
-use std::{borrow::Cow, future::IntoFuture, iter, net::SocketAddr, num::NonZeroU16, sync::Arc};
+use std::{
+    borrow::Cow, convert::Infallible, future::IntoFuture, iter, net::SocketAddr, num::NonZeroU16,
+    sync::Arc,
+};
 
 use async_graphql::{
-    futures_util::Stream, resolver_utils::ContainerType, Error, MergedObject, OutputType,
-    ScalarType, Schema, SimpleObject, Subscription,
+    extensions::{Extension, ExtensionContext, ExtensionFactory, NextParseQuery},
+    futures_util::Stream,
+    parser::types::{ExecutableDocument, Selection, SelectionSet},
+    resolver_utils::ContainerType,
+    Error, InputType, MergedObject, OutputType, Positioned, ScalarType, Schema, ServerError,
+    ServerResult, SimpleObject, Subscription, Value, Variables,
 };
 use async_graphql_axum::{GraphQLRequest, GraphQLResponse, GraphQLSubscription};
-use axum::{extract::Path, http::StatusCode, response, response::IntoResponse, Extension, Router};
-use futures::{lock::Mutex, Future, FutureExt as _};
+use axum::{
+    extract::{Path, Query as UrlQuery},
+    http::StatusCode,
+    response,
+    response::IntoResponse,
+    Extension, Router,
+};
+use futures::{lock::Mutex, stream, Future, FutureExt as _, StreamExt as _};
 use linera_base::{
     crypto::{CryptoError, CryptoHash},
     data_types::{
-        Amount, ApplicationDescription, ApplicationPermissions, Bytecode, Epoch, TimeDelta,
+        Amount, ApplicationDescription, ApplicationPermissions, BlockHeight, Bytecode, Epoch,
+        HttpPolicy, TimeDelta,
+    },
+    identifiers::{
+        AccountOwner, ApplicationId, ChainId, GenericApplicationId, IndexAndEvent, ModuleId,
+        StreamId,
     },
-    identifiers::{AccountOwner, ApplicationId, ChainId, IndexAndEvent, ModuleId, StreamId},
     ownership::{ChainOwnership, TimeoutConfig},
     vm::VmRuntime,
     BcsHexParseError,
 };
 use linera_chain::{
-    types::{ConfirmedBlock, GenericCertificate},
+    data_types::MessageAction,
+    types::{CertificateValue as _, ConfirmedBlock, GenericCertificate},
     ChainStateView,
 };
 use linera_client::chain_listener::{ChainListener, ChainListenerConfig, ClientContext};
 use linera_core::{
     client::{ChainClient, ChainClientError},
     data_types::ClientOutcome,
-    worker::Notification,
+    node::NotificationStream,
+    worker::{Notification, Reason},
 };
 use linera_execution::{
     committee::Committee,
@@ -79,6 +98,18 @@ enum NodeServiceError {
     JsonError(#[from] serde_json::Error),
     #[error("malformed chain ID: {0}")]
     InvalidChainId(CryptoError),
+    #[error("queries at a past block height cannot create operations")]
+    OperationsAtHeight,
+    #[error("chain {0} is not in the wallet")]
+    UnknownChain(ChainId),
+}
+
+/// The URL parameters of the queries to an application.
+#[derive(Debug, Default, Deserialize)]
+struct ApplicationQueryParams {
+    /// The block height after which the application state should be queried, instead of
+    /// the current state.
+    at_height: Option<u64>,
 }
 
 impl IntoResponse for NodeServiceError {
@@ -95,6 +126,10 @@ impl IntoResponse for NodeServiceError {
                 StatusCode::BAD_REQUEST,
                 vec!["invalid chain ID".to_string()],
             ),
+            NodeServiceError::OperationsAtHeight => {
+                (StatusCode::BAD_REQUEST, vec![self.to_string()])
+            }
+            NodeServiceError::UnknownChain(_) => (StatusCode::FORBIDDEN, vec![self.to_string()]),
         };
         let tuple = (tuple.0, json!({"error": tuple.1}).to_string());
         tuple.into_response()
@@ -114,6 +149,175 @@ where
         let client = self.context.lock().await.make_chain_client(chain_id);
         Ok(client.subscribe().await?)
     }
+
+    /// Subscribes to the new confirmed blocks of the specified chain.
+    async fn blocks(&self, chain_id: ChainId) -> Result<impl Stream<Item = ConfirmedBlock>, Error> {
+        let client = self.context.lock().await.make_chain_client(chain_id);
+        let notifications = client.subscribe().await?;
+        Ok(confirmed_blocks(client, notifications))
+    }
+
+    /// Subscribes to the events of a stream of the specified chain, starting with the
+    /// existing ones from the given index.
+    async fn events(
+        &self,
+        chain_id: ChainId,
+        stream_id: StreamId,
+        start_index: Option<u32>,
+    ) -> Result<impl Stream<Item = IndexAndEvent>, Error> {
+        let client = self.context.lock().await.make_chain_client(chain_id);
+        // Subscribe before reading the past events so that none is missed in between.
+        let notifications = client.subscribe().await?;
+        let past_events = client
+            .events_from_index(stream_id.clone(), start_index.unwrap_or(0))
+            .await?;
+        let next_index = past_events
+            .last()
+            .map_or(start_index.unwrap_or(0), |event| {
+                event.index.saturating_add(1)
+            });
+        let new_events = stream::unfold(
+            (client, notifications, next_index),
+            move |(client, mut notifications, mut next_index)| {
+                let stream_id = stream_id.clone();
+                async move {
+                    loop {
+                        let notification = notifications.next().await?;
+                        if !matches!(notification.reason, Reason::NewBlock { .. }) {
+                            continue;
+                        }
+                        match client
+                            .events_from_index(stream_id.clone(), next_index)
+                            .await
+                        {
+                            Ok(events) => {
+                                if let Some(event) = events.last() {
+                                    next_index = event.index.saturating_add(1);
+                                }
+                                return Some((events, (client, notifications, next_index)));
+                            }
+                            Err(error) => {
+                                error!("Failed to read the events of {chain_id}: {error}")
+                            }
+                        }
+                    }
+                }
+            },
+        )
+        .flat_map(stream::iter);
+        Ok(stream::iter(past_events).chain(new_events))
+    }
+
+    /// Subscribes to the new blocks of the specified chain in which an application was
+    /// directly involved, i.e. that executed its operations or messages, or in which it
+    /// sent messages or emitted events.
+    ///
+    /// Changes made to the application's state through calls from other applications leave
+    /// no trace in the block and are not reported.
+    async fn application_activity(
+        &self,
+        chain_id: ChainId,
+        application_id: ApplicationId,
+    ) -> Result<impl Stream<Item = ApplicationActivity>, Error> {
+        let client = self.context.lock().await.make_chain_client(chain_id);
+        let notifications = client.subscribe().await?;
+        Ok(
+            confirmed_blocks(client, notifications).filter_map(move |block| {
+                let activity = ApplicationActivity::new(&block, application_id);
+                async move { activity }
+            }),
+        )
+    }
+}
+
+/// Reads the confirmed block announced by each `NewBlock` notification.
+fn confirmed_blocks<Env: linera_core::Environment>(
+    client: ChainClient<Env>,
+    notifications: NotificationStream,
+) -> impl Stream<Item = ConfirmedBlock> {
+    notifications.filter_map(move |notification| {
+        let client = client.clone();
+        async move {
+            let Reason::NewBlock { hash, .. } = notification.reason else {
+                return None;
+            };
+            match client.read_confirmed_block(hash).await {
+                Ok(block) => Some(block),
+                Err(error) => {
+                    error!("Failed to read the confirmed block {hash}: {error}");
+                    None
+                }
+            }
+        }
+    })
+}
+
+/// The activity of an application in a confirmed block, as recorded in the block.
+#[derive(SimpleObject, Clone, Debug)]
+pub struct ApplicationActivity {
+    pub chain_id: ChainId,
+    pub application_id: ApplicationId,
+    pub height: BlockHeight,
+    pub hash: CryptoHash,
+    /// The number of operations of the application executed in the block.
+    pub operations: u32,
+    /// The number of incoming messages of the application executed in the block.
+    pub messages: u32,
+    /// The number of messages sent by the application in the block.
+    pub sent_messages: u32,
+    /// The number of events emitted in the streams of the application.
+    pub events: u32,
+}
+
+impl ApplicationActivity {
+    /// Returns the activity of the application in the block, if any.
+    fn new(block: &ConfirmedBlock, application_id: ApplicationId) -> Option<Self> {
+        let id = GenericApplicationId::User(application_id);
+        let body = &block.block().body;
+        let count = |n: usize| u32::try_from(n).unwrap_or(u32::MAX);
+        let operations = count(
+            body.operations
+                .iter()
+                .filter(|operation| operation.application_id() == id)
+                .count(),
+        );
+        let messages = count(
+            body.incoming_bundles
+                .iter()
+                .filter(|bundle| bundle.action == MessageAction::Accept)
+                .flat_map(|bundle| &bundle.bundle.messages)
+                .filter(|posted| posted.message.application_id() == id)
+                .count(),
+        );
+        let sent_messages = count(
+            body.messages
+                .iter()
+                .flatten()
+                .filter(|outgoing| outgoing.message.application_id() == id)
+                .count(),
+        );
+        let events = count(
+            body.events
+                .iter()
+                .flatten()
+                .filter(|event| event.stream_id.application_id == id)
+                .count(),
+        );
+        if operations == 0 && messages == 0 && sent_messages == 0 && events == 0 {
+            return None;
+        }
+        let header = &block.block().header;
+        Some(Self {
+            chain_id: header.chain_id,
+            application_id,
+            height: header.height,
+            hash: block.hash(),
+            operations,
+            messages,
+            sent_messages,
+            events,
+        })
+    }
 }
 
 impl<C> MutationRoot<C>
@@ -448,6 +652,7 @@ where
         change_application_permissions: Vec<ApplicationId>,
         call_service_as_oracle: Option<Vec<ApplicationId>>,
         make_http_requests: Option<Vec<ApplicationId>>,
+        #[graphql(default)] http_policies: Vec<HttpPolicy>,
     ) -> Result<CryptoHash, Error> {
         let operation = SystemOperation::ChangeApplicationPermissions(ApplicationPermissions {
             execute_operations,
@@ -456,6 +661,7 @@ where
             change_application_permissions,
             call_service_as_oracle,
             make_http_requests,
+            http_policies,
         });
         self.execute_system_operation(operation, chain_id).await
     }
@@ -765,6 +971,93 @@ impl ApplicationOverview {
     }
 }
 
+/// A GraphQL extension rejecting the requests with a `chainId` argument that is not in the
+/// wallet.
+struct WalletChainsOnly<C> {
+    context: Arc<Mutex<C>>,
+}
+
+impl<C> ExtensionFactory for WalletChainsOnly<C>
+where
+    C: ClientContext + 'static,
+{
+    fn create(&self) -> Arc<dyn Extension> {
+        Arc::new(WalletChainsOnly {
+            context: Arc::clone(&self.context),
+        })
+    }
+}
+
+#[async_trait::async_trait]
+impl<C> Extension for WalletChainsOnly<C>
+where
+    C: ClientContext + 'static,
+{
+    async fn parse_query(
+        &self,
+        ctx: &ExtensionContext<'_>,
+        query: &str,
+        variables: &Variables,
+        next: NextParseQuery<'_>,
+    ) -> ServerResult<ExecutableDocument> {
+        let document = next.run(ctx, query, variables).await?;
+        let mut arguments = Vec::new();
+        for (_, operation) in document.operations.iter() {
+            chain_id_arguments(
+                &operation.node.selection_set.node,
+                variables,
+                &mut arguments,
+            );
+        }
+        for fragment in document.fragments.values() {
+            chain_id_arguments(&fragment.node.selection_set.node, variables, &mut arguments);
+        }
+        let context = self.context.lock().await;
+        for argument in arguments {
+            let chain_id = <ChainId as InputType>::parse(Some(argument.node))
+                .map_err(|error| error.into_server_error(argument.pos))?;
+            if context.wallet().get(chain_id).is_none() {
+                let error = NodeServiceError::UnknownChain(chain_id);
+                return Err(ServerError::new(error.to_string(), Some(argument.pos)));
+            }
+        }
+        Ok(document)
+    }
+}
+
+/// Collects the values of the `chainId` arguments in a selection set.
+fn chain_id_arguments(
+    selection_set: &SelectionSet,
+    variables: &Variables,
+    arguments: &mut Vec<Positioned<Value>>,
+) {
+    for selection in &selection_set.items {
+        match &selection.node {
+            Selection::Field(field) => {
+                for (name, value) in &field.node.arguments {
+                    if name.node == "chainId" {
+                        let const_value = value
+                            .node
+                            .clone()
+                            .into_const_with(|name| {
+                                Ok::<_, Infallible>(
+                                    variables.get(&name).cloned().unwrap_or_default(),
+                                )
+                            })
+                            .unwrap_or_else(|never| match never {});
+                        arguments.push(Positioned::new(const_value, value.pos));
+                    }
+                }
+                chain_id_arguments(&field.node.selection_set.node, variables, arguments);
+            }
+            Selection::InlineFragment(fragment) => {
+                chain_id_arguments(&fragment.node.selection_set.node, variables, arguments);
+            }
+            Selection::FragmentSpread(_) => {}
+        }
+    }
+}
+
 /// The `NodeService` is a server that exposes a web-server to the client.
 /// The node service is primarily used to explore the state of a chain in GraphQL.
 pub struct NodeService<C>
@@ -775,6 +1068,8 @@ where
     port: NonZeroU16,
     default_chain: Option<ChainId>,
     context: Arc<Mutex<C>>,
+    /// Whether the requests are restricted to the chains of the wallet.
+    wallet_chains_only: bool,
 }
 
 impl<C> Clone for NodeService<C>
@@ -787,6 +1082,7 @@ where
             port: self.port,
             default_chain: self.default_chain,
             context: Arc::clone(&self.context),
+            wallet_chains_only: self.wallet_chains_only,
         }
     }
 }
@@ -807,11 +1103,19 @@ where
             port,
             default_chain,
             context: Arc::new(Mutex::new(context)),
+            wallet_chains_only: false,
         }
     }
 
+    /// Rejects the requests about chains that are not in the wallet, e.g. to serve several
+    /// tenants from the same storage.
+    pub fn with_wallet_chains_only(mut self) -> Self {
+        self.wallet_chains_only = true;
+        self
+    }
+
     pub fn schema(&self) -> Schema<QueryRoot<C>, MutationRoot<C>, SubscriptionRoot<C>> {
-        Schema::build(
+        let builder = Schema::build(
             QueryRoot {
                 context: Arc::clone(&self.context),
                 port: self.port,
@@ -823,19 +1127,33 @@ where
             SubscriptionRoot {
                 context: Arc::clone(&self.context),
             },
-        )
-        .finish()
+        );
+        if self.wallet_chains_only {
+            builder
+                .extension(WalletChainsOnly {
+                    context: Arc::clone(&self.context),
+                })
+                .finish()
+        } else {
+            builder.finish()
+        }
     }
 
-    /// Runs the node service.
-    #[instrument(name = "node_service", level = "info", skip_all, fields(port = ?self.port))]
-    pub async fn run(self, cancellation_token: CancellationToken) -> Result<(), anyhow::Error> {
-        let port = self.port.get();
+    /// Fails if the requests about the given chain are rejected.
+    async fn check_chain(&self, chain_id: ChainId) -> Result<(), NodeServiceError> {
+        if self.wallet_chains_only && self.context.lock().await.wallet().get(chain_id).is_none() {
+            return Err(NodeServiceError::UnknownChain(chain_id));
+        }
+        Ok(())
+    }
+
+    /// Returns the routes of the node service.
+    pub fn router(&self) -> Router {
         let index_handler = axum::routing::get(util::graphiql).post(Self::index_handler);
         let application_handler =
             axum::routing::get(util::graphiql).post(Self::application_handler);
 
-        let app = Router::new()
+        Router::new()
             .route("/", index_handler)
             .route(
                 "/chains/{chain_id}/applications/{application_id}",
@@ -844,16 +1162,27 @@ where
             .route("/ready", axum::routing::get(|| async { "ready!" }))
             .route_service("/ws", GraphQLSubscription::new(self.schema()))
             .layer(Extension(self.clone()))
-            // TODO(#551): Provide application authentication.
-            .layer(CorsLayer::permissive());
-
-        info!("GraphiQL IDE: http://localhost:{}", port);
+    }
 
+    /// Runs the chain listener of the node service, without serving any requests.
+    pub async fn listen(self, cancellation_token: CancellationToken) -> Result<(), anyhow::Error> {
         let storage = self.context.lock().await.storage().clone();
+        ChainListener::new(self.config, self.context, storage, cancellation_token)
+            .run()
+            .await?;
+        Ok(())
+    }
+
+    /// Runs the node service.
+    #[instrument(name = "node_service", level = "info", skip_all, fields(port = ?self.port))]
+    pub async fn run(self, cancellation_token: CancellationToken) -> Result<(), anyhow::Error> {
+        let port = self.port.get();
+        // TODO(#551): Provide application authentication.
+        let app = self.router().layer(CorsLayer::permissive());
 
-        let chain_listener =
-            ChainListener::new(self.config, self.context, storage, cancellation_token).run();
-        let mut chain_listener = Box::pin(chain_listener).fuse();
+        info!("GraphiQL IDE: http://localhost:{}", port);
+
+        let mut chain_listener = Box::pin(self.listen(cancellation_token)).fuse();
         let tcp_listener =
             tokio::net::TcpListener::bind(SocketAddr::from(([0, 0, 0, 0], port))).await?;
         let server = axum::serve(tcp_listener, app).into_future();
@@ -865,22 +1194,28 @@ where
         Ok(())
     }
 
-    /// Handles service queries for user applications (including mutations).
+    /// Handles service queries for user applications (including mutations). If `at_height` is
+    /// provided, the application state after the block at that height is queried instead,
+    /// and mutations are rejected.
     async fn handle_service_request(
         &self,
         application_id: ApplicationId,
         request: Vec<u8>,
         chain_id: ChainId,
+        at_height: Option<BlockHeight>,
     ) -> Result<Vec<u8>, NodeServiceError> {
         let QueryOutcome {
             response,
             operations,
         } = self
-            .query_user_application(application_id, request, chain_id)
+            .query_user_application(application_id, request, chain_id, at_height)
             .await?;
         if operations.is_empty() {
             return Ok(response);
         }
+        if at_height.is_some() {
+            return Err(NodeServiceError::OperationsAtHeight);
+        }
 
         trace!("Query requested a new block with operations: {operations:?}");
         let client = self.context.lock().await.make_chain_client(chain_id);
@@ -907,10 +1242,12 @@ where
         application_id: ApplicationId,
         bytes: Vec<u8>,
         chain_id: ChainId,
+        at_height: Option<BlockHeight>,
     ) -> Result<QueryOutcome<Vec<u8>>, NodeServiceError> {
         let query = Query::User {
             application_id,
             bytes,
+            at_height,
         };
         let client = self.context.lock().await.make_chain_client(chain_id);
         let QueryOutcome {
@@ -940,14 +1277,17 @@ where
 
     /// Executes a GraphQL query against an application.
     /// Pattern matches on the `OperationType` of the query and routes the query
-    /// accordingly.
+    /// accordingly. The `at_height` URL parameter queries the application state after the
+    /// block at that height.
     async fn application_handler(
         Path((chain_id, application_id)): Path<(String, String)>,
+        UrlQuery(params): UrlQuery<ApplicationQueryParams>,
         service: Extension<Self>,
         request: String,
     ) -> Result<Vec<u8>, NodeServiceError> {
         let chain_id: ChainId = chain_id.parse().map_err(NodeServiceError::InvalidChainId)?;
         let application_id: ApplicationId = application_id.parse()?;
+        service.0.check_chain(chain_id).await?;
 
         debug!(
             "Processing request for application {application_id} on chain {chain_id}:\n{:?}",
@@ -955,9 +1295,18 @@ where
         );
         let response = service
             .0
-            .handle_service_request(application_id, request.into_bytes(), chain_id)
+            .handle_service_request(
+                application_id,
+                request.into_bytes(),
+                chain_id,
+                params.at_height.map(BlockHeight),
+            )
             .await?;
 
         Ok(response)
     }
 }
+
+#[cfg(test)]
+#[path = "unit_tests/node_service.rs"]
+mod tests;
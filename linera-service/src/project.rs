@@ -14,6 +14,15 @@ use current_platform::CURRENT_PLATFORM;
 use fs_err::File;
 use tracing::debug;
 
+use crate::codegen;
+
+/// The environment variable that makes the Linera SDK record GraphQL snapshots again instead of
+/// comparing them.
+const UPDATE_SNAPSHOTS_VARIABLE: &str = "LINERA_UPDATE_SNAPSHOTS";
+
+/// The environment variable naming the directory the Linera SDK exports ABI descriptions to.
+const ABI_EXPORT_DIRECTORY_VARIABLE: &str = "LINERA_ABI_EXPORT_DIRECTORY";
+
 pub struct Project {
     root: PathBuf,
 }
@@ -80,15 +89,69 @@ impl Project {
     }
 
     /// Runs the unit and integration tests of an application.
-    pub async fn test(&self) -> Result<()> {
+    ///
+    /// If `update_snapshots` is set, the GraphQL snapshots checked by the tests are recorded
+    /// instead of being compared, including the missing ones.
+    pub async fn test(&self, update_snapshots: bool) -> Result<()> {
+        let mut command = Command::new("cargo");
+        command
+            .arg("test")
+            .args(["--target", CURRENT_PLATFORM])
+            .current_dir(&self.root);
+        if update_snapshots {
+            command.env(UPDATE_SNAPSHOTS_VARIABLE, "1");
+        }
+        let tests = command.spawn()?.wait()?;
+        ensure!(tests.success(), "tests failed");
+        Ok(())
+    }
+
+    /// Generates typed Rust and TypeScript client bindings for the applications whose ABIs
+    /// are exported by the project's tests.
+    ///
+    /// The bindings are written to `output`, or to the `bindings` directory of the project.
+    /// Returns the paths of the generated files.
+    pub async fn codegen(&self, output: Option<PathBuf>) -> Result<Vec<PathBuf>> {
+        let export_directory = self.workspace_root()?.join("target").join("linera-abi");
+        if export_directory.exists() {
+            fs_err::remove_dir_all(&export_directory)?;
+        }
         let tests = Command::new("cargo")
             .arg("test")
             .args(["--target", CURRENT_PLATFORM])
+            .env(ABI_EXPORT_DIRECTORY_VARIABLE, &export_directory)
             .current_dir(&self.root)
             .spawn()?
             .wait()?;
         ensure!(tests.success(), "tests failed");
-        Ok(())
+        ensure!(
+            export_directory.exists(),
+            "no ABI was exported: call `ActiveChain::export_abi` in the project's tests"
+        );
+
+        let output = output.unwrap_or_else(|| self.root.join("bindings"));
+        fs_err::create_dir_all(&output)?;
+        let mut paths = Vec::new();
+        for entry in fs_err::read_dir(&export_directory)? {
+            let description = codegen::AbiDescription::read(&entry?.path())?;
+            let undefined_types = codegen::undefined_types(&description);
+            ensure!(
+                undefined_types.is_empty(),
+                "the ABI of {} uses types without a traced format: {undefined_types:?}",
+                description.name
+            );
+            let rust_path = output.join(format!("{}.rs", description.name.to_case(Case::Snake)));
+            Self::write_string_to_file(&rust_path, &codegen::generate_rust(&description)?)?;
+            let typescript_path =
+                output.join(format!("{}.ts", description.name.to_case(Case::Kebab)));
+            Self::write_string_to_file(
+                &typescript_path,
+                &codegen::generate_typescript(&description)?,
+            )?;
+            paths.extend([rust_path, typescript_path]);
+        }
+        paths.sort();
+        Ok(paths)
     }
 
     /// Finds the workspace for a given crate. If the workspace
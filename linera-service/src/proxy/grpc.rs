@@ -29,9 +29,11 @@ use linera_rpc::{
             validator_node_server::{ValidatorNode, ValidatorNodeServer},
             validator_worker_client::ValidatorWorkerClient,
             BlobContent, BlobId, BlobIds, BlockProposal, Certificate, CertificatesBatchRequest,
-            CertificatesBatchResponse, ChainInfoQuery, ChainInfoResult, CryptoHash,
-            HandlePendingBlobRequest, LiteCertificate, NetworkDescription, Notification,
-            PendingBlobRequest, PendingBlobResult, SubscriptionRequest, VersionInfo,
+            CertificatesBatchResponse, ChainInfoQuery, ChainInfoResult, ChainSnapshotRequest,
+            ChainSnapshotResult, CryptoHash, HandlePendingBlobRequest, HttpOracleRequest,
+            HttpOracleResult, LiteCertificate, NetworkDescription, Notification,
+            PendingBlobRequest, PendingBlobResult, RandomnessRequest, RandomnessResult,
+            SubscriptionRequest, VersionInfo,
         },
         pool::GrpcConnectionPool,
         GrpcProtoConversionError, GrpcProxyable, GRPC_CHUNKED_MESSAGE_FILL_LIMIT,
@@ -556,6 +558,81 @@ where
         }
     }
 
+    #[instrument(skip_all, err(Display))]
+    async fn handle_http_oracle_request(
+        &self,
+        request: Request<HttpOracleRequest>,
+    ) -> Result<Response<HttpOracleResult>, Status> {
+        let (mut client, inner) = self.worker_client(request).await?;
+        #[cfg_attr(not(with_metrics), expect(clippy::needless_match))]
+        match client.handle_http_oracle_request(inner).await {
+            Ok(result) => {
+                #[cfg(with_metrics)]
+                metrics::PROXY_REQUEST_SUCCESS
+                    .with_label_values(&["handle_http_oracle_request"])
+                    .inc();
+                Ok(result)
+            }
+            Err(status) => {
+                #[cfg(with_metrics)]
+                metrics::PROXY_REQUEST_ERROR
+                    .with_label_values(&["handle_http_oracle_request"])
+                    .inc();
+                Err(status)
+            }
+        }
+    }
+
+    #[instrument(skip_all, err(Display))]
+    async fn handle_randomness_request(
+        &self,
+        request: Request<RandomnessRequest>,
+    ) -> Result<Response<RandomnessResult>, Status> {
+        let (mut client, inner) = self.worker_client(request).await?;
+        #[cfg_attr(not(with_metrics), expect(clippy::needless_match))]
+        match client.handle_randomness_request(inner).await {
+            Ok(result) => {
+                #[cfg(with_metrics)]
+                metrics::PROXY_REQUEST_SUCCESS
+                    .with_label_values(&["handle_randomness_request"])
+                    .inc();
+                Ok(result)
+            }
+            Err(status) => {
+                #[cfg(with_metrics)]
+                metrics::PROXY_REQUEST_ERROR
+                    .with_label_values(&["handle_randomness_request"])
+                    .inc();
+                Err(status)
+            }
+        }
+    }
+
+    #[instrument(skip_all, err(Display))]
+    async fn download_chain_snapshot_chunk(
+        &self,
+        request: Request<ChainSnapshotRequest>,
+    ) -> Result<Response<ChainSnapshotResult>, Status> {
+        let (mut client, inner) = self.worker_client(request).await?;
+        #[cfg_attr(not(with_metrics), expect(clippy::needless_match))]
+        match client.download_chain_snapshot_chunk(inner).await {
+            Ok(result) => {
+                #[cfg(with_metrics)]
+                metrics::PROXY_REQUEST_SUCCESS
+                    .with_label_values(&["download_chain_snapshot_chunk"])
+                    .inc();
+                Ok(result)
+            }
+            Err(status) => {
+                #[cfg(with_metrics)]
+                metrics::PROXY_REQUEST_ERROR
+                    .with_label_values(&["download_chain_snapshot_chunk"])
+                    .inc();
+                Err(status)
+            }
+        }
+    }
+
     #[instrument(skip_all, err(Display))]
     async fn download_certificate(
         &self,
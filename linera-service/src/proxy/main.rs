@@ -372,6 +372,12 @@ where
             | DownloadPendingBlob(_)
             | DownloadPendingBlobResponse(_)
             | HandlePendingBlob(_)
+            | HttpOracleRequest(_)
+            | HttpOracleResponse(_)
+            | RandomnessRequest(_)
+            | RandomnessResponse(_)
+            | ChainSnapshotRequest(_)
+            | ChainSnapshotResponse(_)
             | BlobLastUsedByResponse(_)
             | MissingBlobIdsResponse(_)
             | DownloadConfirmedBlockResponse(_)
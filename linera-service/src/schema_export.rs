@@ -4,7 +4,9 @@
 use linera_base::{
     crypto::CryptoHash,
     data_types::{BlobContent, NetworkDescription, Timestamp},
+    http::SignedResponse,
     identifiers::{AccountOwner, BlobId, ChainId},
+    randomness::RandomnessShare,
 };
 use linera_chain::{
     data_types::BlockProposal,
@@ -20,7 +22,10 @@ use linera_client::{
 };
 use linera_core::{
     client::ChainClient,
-    data_types::{ChainInfoQuery, ChainInfoResponse},
+    data_types::{
+        ChainInfoQuery, ChainInfoResponse, ChainSnapshotChunk, ChainSnapshotRequest,
+        HttpOracleRequest, RandomnessRequest,
+    },
     node::{
         CrossChainMessageDelivery, NodeError, NotificationStream, ValidatorNode,
         ValidatorNodeProvider,
@@ -136,6 +141,27 @@ impl ValidatorNode for DummyValidatorNode {
     async fn missing_blob_ids(&self, _: Vec<BlobId>) -> Result<Vec<BlobId>, NodeError> {
         Err(NodeError::UnexpectedMessage)
     }
+
+    async fn handle_http_oracle_request(
+        &self,
+        _: HttpOracleRequest,
+    ) -> Result<SignedResponse, NodeError> {
+        Err(NodeError::UnexpectedMessage)
+    }
+
+    async fn handle_randomness_request(
+        &self,
+        _: RandomnessRequest,
+    ) -> Result<RandomnessShare, NodeError> {
+        Err(NodeError::UnexpectedMessage)
+    }
+
+    async fn download_chain_snapshot_chunk(
+        &self,
+        _: ChainSnapshotRequest,
+    ) -> Result<ChainSnapshotChunk, NodeError> {
+        Err(NodeError::UnexpectedMessage)
+    }
 }
 
 struct DummyValidatorNodeProvider;
@@ -6,8 +6,10 @@
 
 use std::{
     borrow::Cow,
+    io,
     num::{NonZeroU16, NonZeroUsize},
     path::{Path, PathBuf},
+    sync::Arc,
     time::Duration,
 };
 
@@ -16,13 +18,21 @@ use async_trait::async_trait;
 use futures::{stream::FuturesUnordered, FutureExt as _, StreamExt, TryFutureExt as _};
 use linera_base::{
     crypto::{CryptoRng, Ed25519SecretKey},
+    data_types::Blob,
     listen_for_shutdown_signals,
+    randomness::RandomnessPublicKeys,
 };
-use linera_client::config::{
-    CommitteeConfig, GenesisConfig, ValidatorConfig, ValidatorServerConfig,
+use linera_chain::types::ConfirmedBlockCertificate;
+use linera_client::{
+    config::{CommitteeConfig, GenesisConfig, ValidatorConfig, ValidatorServerConfig},
+    util::parse_secs,
 };
-use linera_core::{worker::WorkerState, JoinSetExt as _};
-use linera_execution::{WasmRuntime, WithWasmDefault};
+use linera_core::{
+    chain_worker::{HistoryArchive, RetentionPolicy},
+    worker::WorkerState,
+    JoinSetExt as _,
+};
+use linera_execution::{ResourceControlPolicy, WasmRuntime, WithWasmDefault};
 use linera_persistent::{self as persistent, Persist};
 use linera_rpc::{
     config::{
@@ -52,6 +62,8 @@ struct ServerContext {
     shard: Option<usize>,
     grace_period: Duration,
     max_loaded_chains: NonZeroUsize,
+    retention_policy: RetentionPolicy,
+    history_archive: Option<Arc<dyn HistoryArchive>>,
 }
 
 impl ServerContext {
@@ -78,7 +90,9 @@ impl ServerContext {
         )
         .with_allow_inactive_chains(false)
         .with_allow_messages_from_deprecated_epochs(false)
-        .with_grace_period(self.grace_period);
+        .with_grace_period(self.grace_period)
+        .with_retention_policy(self.retention_policy, self.history_archive.clone())
+        .with_randomness_key(self.server_config.randomness_secret.clone());
         (state, shard_id, shard.clone())
     }
 
@@ -235,6 +249,37 @@ impl Runnable for ServerContext {
     }
 }
 
+/// Archives the pruned blocks of chains as files in a directory.
+///
+/// Each certificate is written to `<chain ID>/<height>.bcs`, and each blob to
+/// `blobs/<blob ID>`.
+struct HistoryDirectory {
+    path: PathBuf,
+}
+
+#[async_trait]
+impl HistoryArchive for HistoryDirectory {
+    async fn archive(
+        &self,
+        certificates: &[ConfirmedBlockCertificate],
+        blobs: &[Blob],
+    ) -> Result<(), io::Error> {
+        let blobs_path = self.path.join("blobs");
+        fs_err::tokio::create_dir_all(&blobs_path).await?;
+        for blob in blobs {
+            fs_err::tokio::write(blobs_path.join(blob.id().to_string()), blob.bytes()).await?;
+        }
+        for certificate in certificates {
+            let header = &certificate.block().header;
+            let chain_path = self.path.join(header.chain_id.to_string());
+            fs_err::tokio::create_dir_all(&chain_path).await?;
+            let bytes = bcs::to_bytes(certificate).map_err(io::Error::other)?;
+            fs_err::tokio::write(chain_path.join(format!("{}.bcs", header.height)), bytes).await?;
+        }
+        Ok(())
+    }
+}
+
 #[derive(clap::Parser)]
 #[command(
     name = "linera-server",
@@ -322,6 +367,7 @@ fn make_server_config<R: CryptoRng>(
             validator,
             validator_secret: validator_keypair.secret_key,
             internal_network,
+            randomness_secret: None,
         },
     )?)
 }
@@ -360,6 +406,19 @@ enum ServerCommand {
         #[arg(long = "grace-period-ms", default_value = "500", value_parser = util::parse_millis)]
         grace_period: Duration,
 
+        /// Prunes the blocks of each chain that are not among its last this many blocks. If
+        /// `--keep-blocks-secs` is also set, blocks are only pruned once outside of both limits.
+        #[arg(long)]
+        keep_block_heights: Option<u64>,
+
+        /// Prunes the blocks of each chain that are older than this many seconds.
+        #[arg(long = "keep-blocks-secs", value_parser = parse_secs)]
+        keep_blocks_duration: Option<Duration>,
+
+        /// The directory where pruned blocks and blobs are archived before being deleted.
+        #[arg(long)]
+        history_archive_dir: Option<PathBuf>,
+
         /// The WebAssembly runtime to use.
         #[arg(long)]
         wasm_runtime: Option<WasmRuntime>,
@@ -539,6 +598,9 @@ async fn run(options: ServerOptions) {
             genesis_config_path,
             shard,
             grace_period,
+            keep_block_heights,
+            keep_blocks_duration,
+            history_archive_dir,
             wasm_runtime,
             max_loaded_chains,
             max_concurrent_queries,
@@ -554,6 +616,16 @@ async fn run(options: ServerOptions) {
                 util::read_json(&genesis_config_path).expect("Failed to read initial chain config");
             let server_config: ValidatorServerConfig =
                 util::read_json(&server_config_path).expect("Failed to read server config");
+            // With write-back, the chain states written by a shard are only visible to the
+            // processes sharing its local store, but every shard reads the states of the
+            // chains it queries.
+            let num_shards = server_config.internal_network.shards.len();
+            assert!(
+                shard.is_none()
+                    || num_shards <= 1
+                    || !storage_config.storage_config.is_write_back(),
+                "The write-back policy requires running all the shards in the same process"
+            );
 
             let job = ServerContext {
                 server_config,
@@ -562,6 +634,12 @@ async fn run(options: ServerOptions) {
                 shard,
                 grace_period,
                 max_loaded_chains,
+                retention_policy: RetentionPolicy {
+                    keep_heights: keep_block_heights,
+                    keep_duration: keep_blocks_duration,
+                },
+                history_archive: history_archive_dir
+                    .map(|path| Arc::new(HistoryDirectory { path }) as Arc<dyn HistoryArchive>),
             };
             let wasm_runtime = wasm_runtime.with_wasm_default();
             let storage_cache_config = StorageCacheConfig {
@@ -592,7 +670,7 @@ async fn run(options: ServerOptions) {
             committee,
             testing_prng_seed,
         } => {
-            let mut config_validators = Vec::new();
+            let mut servers = Vec::new();
             let mut rng = Box::<dyn CryptoRng>::from(testing_prng_seed);
             for options_path in validators {
                 let options_string = fs_err::tokio::read_to_string(options_path)
@@ -601,8 +679,31 @@ async fn run(options: ServerOptions) {
                 let options: ValidatorOptions =
                     toml::from_str(&options_string).expect("Invalid options file format");
                 let path = options.server_config_path.clone();
-                let mut server = make_server_config(&path, &mut rng, options)
+                let server = make_server_config(&path, &mut rng, options)
                     .expect("Unable to open server config file");
+                servers.push((path, server));
+            }
+            // Deal the shares of the randomness key for the committee of these validators.
+            // This process is a trusted dealer: it knows the master secret key, and with it
+            // every random seed of the committee, so it must run on a trusted machine and
+            // the server configurations must be handed over to their validators privately.
+            let committee_config = CommitteeConfig {
+                validators: servers
+                    .iter()
+                    .map(|(_, server)| server.validator.clone())
+                    .collect(),
+                randomness_keys: None,
+            };
+            let initial_committee = committee_config
+                .clone()
+                .into_committee(ResourceControlPolicy::default());
+            let (randomness_keys, mut randomness_secrets) = RandomnessPublicKeys::deal(
+                initial_committee.keys_and_weights(),
+                initial_committee.quorum_threshold(),
+                &mut rng,
+            );
+            for (path, mut server) in servers {
+                server.randomness_secret = randomness_secrets.remove(&server.validator.public_key);
                 Persist::persist(&mut server)
                     .await
                     .expect("Unable to write server config file");
@@ -611,13 +712,13 @@ async fn run(options: ServerOptions) {
                     "{},{}",
                     server.validator.public_key, server.validator.account_key
                 );
-                config_validators.push(Persist::into_value(server).validator);
             }
             if let Some(committee) = committee {
                 let mut config = persistent::File::new(
                     &committee,
                     CommitteeConfig {
-                        validators: config_validators,
+                        randomness_keys: Some(randomness_keys),
+                        ..committee_config
                     },
                 )
                 .expect("Unable to open committee configuration");
@@ -15,6 +15,8 @@ use linera_storage_service::{
 };
 #[cfg(feature = "dynamodb")]
 use linera_views::dynamo_db::{DynamoDbStore, DynamoDbStoreConfig};
+#[cfg(feature = "encryption")]
+use linera_views::encrypted::{EncryptedStore, EncryptedStoreConfig, EncryptionConfig};
 use linera_views::{
     memory::{MemoryStore, MemoryStoreConfig},
     store::{CommonStoreConfig, KeyValueStore},
@@ -23,9 +25,15 @@ use serde::{Deserialize, Serialize};
 use tracing::error;
 #[allow(unused_imports)]
 use {anyhow::bail, linera_views::store::AdminKeyValueStore as _};
-#[cfg(all(feature = "rocksdb", feature = "scylladb"))]
+#[cfg(all(feature = "rocksdb", any(feature = "scylladb", feature = "dynamodb")))]
 use {
     linera_storage::ChainStatesFirstAssignment,
+    linera_views::backends::tiered::{
+        TieredStore, TieredStoreConfig, WritePolicy, DEFAULT_MAX_LOCAL_SIZE,
+    },
+};
+#[cfg(all(feature = "rocksdb", feature = "scylladb"))]
+use {
     linera_views::backends::dual::{DualStore, DualStoreConfig},
     std::path::Path,
 };
@@ -78,6 +86,24 @@ pub enum StoreConfig {
         config: DualStoreConfig<RocksDbStoreConfig, ScyllaDbStoreConfig>,
         namespace: String,
     },
+    /// The chain states cached in RocksDB over ScyllaDB
+    #[cfg(all(feature = "rocksdb", feature = "scylladb"))]
+    TieredRocksDbScyllaDb {
+        config: TieredStoreConfig<RocksDbStoreConfig, ScyllaDbStoreConfig>,
+        namespace: String,
+    },
+    /// The chain states cached in RocksDB over DynamoDB
+    #[cfg(all(feature = "rocksdb", feature = "dynamodb"))]
+    TieredRocksDbDynamoDb {
+        config: TieredStoreConfig<RocksDbStoreConfig, DynamoDbStoreConfig>,
+        namespace: String,
+    },
+    /// A key value store encrypted at rest
+    #[cfg(feature = "encryption")]
+    Encrypted {
+        encryption_config: EncryptionConfig,
+        inner: Box<StoreConfig>,
+    },
 }
 
 /// The description of a storage implementation.
@@ -121,6 +147,38 @@ pub enum StorageConfig {
         /// The URI for accessing the database.
         uri: String,
     },
+    /// The description of the chain states cached in RocksDB over ScyllaDB.
+    #[cfg(all(feature = "rocksdb", feature = "scylladb"))]
+    TieredRocksDbScyllaDb {
+        /// The path of the local cache.
+        path_with_guard: PathWithGuard,
+        /// Whether to use `block_in_place` or `spawn_blocking`.
+        spawn_mode: RocksDbSpawnMode,
+        /// How the writes reach ScyllaDB. Write-back requires a single process.
+        write_policy: WritePolicy,
+        /// The URI for accessing the database.
+        uri: String,
+    },
+    /// The description of the chain states cached in RocksDB over DynamoDB.
+    #[cfg(all(feature = "rocksdb", feature = "dynamodb"))]
+    TieredRocksDbDynamoDb {
+        /// The path of the local cache.
+        path_with_guard: PathWithGuard,
+        /// Whether to use `block_in_place` or `spawn_blocking`.
+        spawn_mode: RocksDbSpawnMode,
+        /// How the writes reach DynamoDB. Write-back requires a single process.
+        write_policy: WritePolicy,
+        /// Whether to use the DynamoDB Local system
+        use_dynamodb_local: bool,
+    },
+    /// The description of a storage encrypted at rest.
+    #[cfg(feature = "encryption")]
+    Encrypted {
+        /// The JSON file containing the encryption keys.
+        key_file: std::path::PathBuf,
+        /// The description of the underlying storage.
+        inner: Box<StorageConfig>,
+    },
 }
 
 impl StorageConfig {
@@ -128,17 +186,46 @@ impl StorageConfig {
         match self {
             #[cfg(all(feature = "rocksdb", feature = "scylladb"))]
             StorageConfig::DualRocksDbScyllaDb {
-                path_with_guard,
-                spawn_mode: _,
-                uri: _,
+                path_with_guard, ..
+            }
+            | StorageConfig::TieredRocksDbScyllaDb {
+                path_with_guard, ..
             } => {
                 let shard_str = format!("shard_{}", _shard);
                 path_with_guard.path_buf.push(shard_str);
                 std::fs::create_dir_all(&path_with_guard.path_buf)
             }
+            #[cfg(all(feature = "rocksdb", feature = "dynamodb"))]
+            StorageConfig::TieredRocksDbDynamoDb {
+                path_with_guard, ..
+            } => {
+                let shard_str = format!("shard_{}", _shard);
+                path_with_guard.path_buf.push(shard_str);
+                std::fs::create_dir_all(&path_with_guard.path_buf)
+            }
+            #[cfg(feature = "encryption")]
+            StorageConfig::Encrypted { inner, .. } => inner.maybe_append_shard_path(_shard),
             _ => Ok(()),
         }
     }
+
+    /// Returns whether the writes only reach the remote store of a tiered storage when they
+    /// are written back. Such a storage must not be shared by several processes.
+    pub fn is_write_back(&self) -> bool {
+        match self {
+            #[cfg(all(feature = "rocksdb", feature = "scylladb"))]
+            StorageConfig::TieredRocksDbScyllaDb { write_policy, .. } => {
+                *write_policy == WritePolicy::WriteBack
+            }
+            #[cfg(all(feature = "rocksdb", feature = "dynamodb"))]
+            StorageConfig::TieredRocksDbDynamoDb { write_policy, .. } => {
+                *write_policy == WritePolicy::WriteBack
+            }
+            #[cfg(feature = "encryption")]
+            StorageConfig::Encrypted { inner, .. } => inner.is_write_back(),
+            _ => false,
+        }
+    }
 }
 
 /// The description of a storage implementation.
@@ -163,6 +250,12 @@ const DYNAMO_DB: &str = "dynamodb:";
 const SCYLLA_DB: &str = "scylladb:";
 #[cfg(all(feature = "rocksdb", feature = "scylladb"))]
 const DUAL_ROCKS_DB_SCYLLA_DB: &str = "dualrocksdbscylladb:";
+#[cfg(all(feature = "rocksdb", feature = "scylladb"))]
+const TIERED_ROCKS_DB_SCYLLA_DB: &str = "tieredrocksdbscylladb:";
+#[cfg(all(feature = "rocksdb", feature = "dynamodb"))]
+const TIERED_ROCKS_DB_DYNAMO_DB: &str = "tieredrocksdbdynamodb:";
+#[cfg(feature = "encryption")]
+const ENCRYPTED: &str = "encrypted:";
 
 impl FromStr for StorageConfigNamespace {
     type Err = anyhow::Error;
@@ -362,6 +455,103 @@ example service:tcp:127.0.0.1:7878:table_do_my_test"
                 namespace,
             });
         }
+        #[cfg(all(feature = "rocksdb", feature = "scylladb"))]
+        if let Some(s) = input.strip_prefix(TIERED_ROCKS_DB_SCYLLA_DB) {
+            let parts = s.split(':').collect::<Vec<_>>();
+            if parts.len() != 6 && parts.len() != 7 {
+                bail!(
+                    "For TieredRocksDbScyllaDb, the formatting has to be tieredrocksdbscylladb:directory:mode:write_policy:tcp:hostname:port:namespace"
+                );
+            }
+            let path_with_guard = PathWithGuard::new(PathBuf::from(parts[0]));
+            let spawn_mode = match parts[1] {
+                "spawn_blocking" => Ok(RocksDbSpawnMode::SpawnBlocking),
+                "block_in_place" => Ok(RocksDbSpawnMode::BlockInPlace),
+                "runtime" => Ok(RocksDbSpawnMode::get_spawn_mode_from_runtime()),
+                _ => Err(anyhow!("Failed to parse {} as a spawn_mode", parts[1])),
+            }?;
+            let write_policy = WritePolicy::from_str(parts[2]).map_err(|error| anyhow!(error))?;
+            let protocol = parts[3];
+            if protocol != "tcp" {
+                bail!("The only allowed protocol is tcp");
+            }
+            let address = parts[4];
+            let port_str = parts[5];
+            let port = NonZeroU16::from_str(port_str)
+                .map_err(|_| anyhow!("Failed to find parse port {port_str} for {s}"))?;
+            let uri = format!("{}:{}", &address, port);
+            let storage_config = StorageConfig::TieredRocksDbScyllaDb {
+                path_with_guard,
+                spawn_mode,
+                write_policy,
+                uri,
+            };
+            let namespace = if parts.len() == 6 {
+                DEFAULT_NAMESPACE.to_string()
+            } else {
+                parts[6].to_string()
+            };
+            return Ok(StorageConfigNamespace {
+                storage_config,
+                namespace,
+            });
+        }
+        #[cfg(all(feature = "rocksdb", feature = "dynamodb"))]
+        if let Some(s) = input.strip_prefix(TIERED_ROCKS_DB_DYNAMO_DB) {
+            let parts = s.split(':').collect::<Vec<_>>();
+            if (parts.len() != 4 && parts.len() != 5) || parts[3].is_empty() {
+                bail!(
+                    "For TieredRocksDbDynamoDb, the formatting has to be tieredrocksdbdynamodb:directory:mode:write_policy:TABLE:[env|dynamodb_local]"
+                );
+            }
+            let path_with_guard = PathWithGuard::new(PathBuf::from(parts[0]));
+            let spawn_mode = match parts[1] {
+                "spawn_blocking" => Ok(RocksDbSpawnMode::SpawnBlocking),
+                "block_in_place" => Ok(RocksDbSpawnMode::BlockInPlace),
+                "runtime" => Ok(RocksDbSpawnMode::get_spawn_mode_from_runtime()),
+                _ => Err(anyhow!("Failed to parse {} as a spawn_mode", parts[1])),
+            }?;
+            let write_policy = WritePolicy::from_str(parts[2]).map_err(|error| anyhow!(error))?;
+            let namespace = parts[3].to_string();
+            let use_dynamodb_local = match parts.get(4) {
+                None | Some(&"env") => false,
+                Some(&"dynamodb_local") => true,
+                Some(unknown) => {
+                    bail!(
+                        "Invalid DynamoDB endpoint {unknown:?}. \
+                        Expected {TIERED_ROCKS_DB_DYNAMO_DB}directory:mode:write_policy:TABLE:[env|dynamodb_local]"
+                    );
+                }
+            };
+            let storage_config = StorageConfig::TieredRocksDbDynamoDb {
+                path_with_guard,
+                spawn_mode,
+                write_policy,
+                use_dynamodb_local,
+            };
+            return Ok(StorageConfigNamespace {
+                storage_config,
+                namespace,
+            });
+        }
+        #[cfg(feature = "encryption")]
+        if let Some(s) = input.strip_prefix(ENCRYPTED) {
+            let Some((key_file, inner)) = s.split_once(':') else {
+                bail!("For encrypted storage, the formatting has to be encrypted:key_file:storage");
+            };
+            let inner = StorageConfigNamespace::from_str(inner)?;
+            if let StorageConfig::Encrypted { .. } = inner.storage_config {
+                bail!("The storage is already encrypted");
+            }
+            let storage_config = StorageConfig::Encrypted {
+                key_file: key_file.into(),
+                inner: Box::new(inner.storage_config),
+            };
+            return Ok(StorageConfigNamespace {
+                storage_config,
+                namespace: inner.namespace,
+            });
+        }
         error!("available storage: memory");
         #[cfg(feature = "storage-service")]
         error!("Also available is linera-storage-service");
@@ -373,6 +563,12 @@ example service:tcp:127.0.0.1:7878:table_do_my_test"
         error!("Also available is ScyllaDB");
         #[cfg(all(feature = "rocksdb", feature = "scylladb"))]
         error!("Also available is DualRocksDbScyllaDb");
+        #[cfg(all(feature = "rocksdb", feature = "scylladb"))]
+        error!("Also available is TieredRocksDbScyllaDb");
+        #[cfg(all(feature = "rocksdb", feature = "dynamodb"))]
+        error!("Also available is TieredRocksDbDynamoDb");
+        #[cfg(feature = "encryption")]
+        error!("Any of them can be encrypted at rest");
         Err(anyhow!("The input has not matched: {input}"))
     }
 }
@@ -439,6 +635,61 @@ impl StorageConfigNamespace {
                 };
                 Ok(StoreConfig::DualRocksDbScyllaDb { config, namespace })
             }
+            #[cfg(all(feature = "rocksdb", feature = "scylladb"))]
+            StorageConfig::TieredRocksDbScyllaDb {
+                path_with_guard,
+                spawn_mode,
+                write_policy,
+                uri,
+            } => {
+                let local_config = RocksDbStoreConfig::new(
+                    *spawn_mode,
+                    path_with_guard.clone(),
+                    common_config.clone(),
+                );
+                let remote_config = ScyllaDbStoreConfig::new(uri.to_string(), common_config);
+                let config = TieredStoreConfig {
+                    local_config,
+                    remote_config,
+                    max_local_size: DEFAULT_MAX_LOCAL_SIZE,
+                    write_policy: *write_policy,
+                };
+                Ok(StoreConfig::TieredRocksDbScyllaDb { config, namespace })
+            }
+            #[cfg(all(feature = "rocksdb", feature = "dynamodb"))]
+            StorageConfig::TieredRocksDbDynamoDb {
+                path_with_guard,
+                spawn_mode,
+                write_policy,
+                use_dynamodb_local,
+            } => {
+                let local_config = RocksDbStoreConfig::new(
+                    *spawn_mode,
+                    path_with_guard.clone(),
+                    common_config.clone(),
+                );
+                let remote_config = DynamoDbStoreConfig::new(*use_dynamodb_local, common_config);
+                let config = TieredStoreConfig {
+                    local_config,
+                    remote_config,
+                    max_local_size: DEFAULT_MAX_LOCAL_SIZE,
+                    write_policy: *write_policy,
+                };
+                Ok(StoreConfig::TieredRocksDbDynamoDb { config, namespace })
+            }
+            #[cfg(feature = "encryption")]
+            StorageConfig::Encrypted { key_file, inner } => {
+                let encryption_config = serde_json::from_str(&fs_err::read_to_string(key_file)?)?;
+                let inner = StorageConfigNamespace {
+                    storage_config: (**inner).clone(),
+                    namespace,
+                };
+                let inner = Box::new(Box::pin(inner.add_common_config(common_config)).await?);
+                Ok(StoreConfig::Encrypted {
+                    encryption_config,
+                    inner,
+                })
+            }
         }
     }
 }
@@ -483,6 +734,53 @@ impl fmt::Display for StorageConfigNamespace {
                     namespace
                 )
             }
+            #[cfg(all(feature = "rocksdb", feature = "scylladb"))]
+            StorageConfig::TieredRocksDbScyllaDb {
+                path_with_guard,
+                spawn_mode,
+                write_policy,
+                uri,
+            } => {
+                write!(
+                    f,
+                    "tieredrocksdbscylladb:{}:{}:{}:tcp:{}:{}",
+                    path_with_guard.path_buf.display(),
+                    spawn_mode,
+                    write_policy,
+                    uri,
+                    namespace
+                )
+            }
+            #[cfg(all(feature = "rocksdb", feature = "dynamodb"))]
+            StorageConfig::TieredRocksDbDynamoDb {
+                path_with_guard,
+                spawn_mode,
+                write_policy,
+                use_dynamodb_local,
+            } => {
+                let endpoint = if *use_dynamodb_local {
+                    "dynamodb_local"
+                } else {
+                    "env"
+                };
+                write!(
+                    f,
+                    "tieredrocksdbdynamodb:{}:{}:{}:{}:{}",
+                    path_with_guard.path_buf.display(),
+                    spawn_mode,
+                    write_policy,
+                    namespace,
+                    endpoint
+                )
+            }
+            #[cfg(feature = "encryption")]
+            StorageConfig::Encrypted { key_file, inner } => {
+                let inner = StorageConfigNamespace {
+                    storage_config: (**inner).clone(),
+                    namespace: namespace.clone(),
+                };
+                write!(f, "encrypted:{}:{}", key_file.display(), inner)
+            }
         }
     }
 }
@@ -571,6 +869,101 @@ impl StoreConfig {
                 .await?;
                 Ok(job.run(storage).await)
             }
+            #[cfg(all(feature = "rocksdb", feature = "scylladb"))]
+            StoreConfig::TieredRocksDbScyllaDb { config, namespace } => {
+                let storage = DbStorage::<
+                    TieredStore<RocksDbStore, ScyllaDbStore, ChainStatesFirstAssignment>,
+                    _,
+                >::connect(&config, &namespace, wasm_runtime)
+                .await?;
+                Ok(job.run(storage).await)
+            }
+            #[cfg(all(feature = "rocksdb", feature = "dynamodb"))]
+            StoreConfig::TieredRocksDbDynamoDb { config, namespace } => {
+                let storage = DbStorage::<
+                    TieredStore<RocksDbStore, DynamoDbStore, ChainStatesFirstAssignment>,
+                    _,
+                >::connect(&config, &namespace, wasm_runtime)
+                .await?;
+                Ok(job.run(storage).await)
+            }
+            #[cfg(feature = "encryption")]
+            StoreConfig::Encrypted {
+                encryption_config,
+                inner,
+            } => match *inner {
+                #[cfg(feature = "storage-service")]
+                StoreConfig::Service {
+                    config: inner_config,
+                    namespace,
+                } => {
+                    let config = EncryptedStoreConfig {
+                        inner_config,
+                        encryption_config,
+                    };
+                    let storage = DbStorage::<EncryptedStore<ServiceStoreClient>, _>::connect(
+                        &config,
+                        &namespace,
+                        wasm_runtime,
+                    )
+                    .await?;
+                    Ok(job.run(storage).await)
+                }
+                #[cfg(feature = "rocksdb")]
+                StoreConfig::RocksDb {
+                    config: inner_config,
+                    namespace,
+                } => {
+                    let config = EncryptedStoreConfig {
+                        inner_config,
+                        encryption_config,
+                    };
+                    let storage = DbStorage::<EncryptedStore<RocksDbStore>, _>::connect(
+                        &config,
+                        &namespace,
+                        wasm_runtime,
+                    )
+                    .await?;
+                    Ok(job.run(storage).await)
+                }
+                #[cfg(feature = "dynamodb")]
+                StoreConfig::DynamoDb {
+                    config: inner_config,
+                    namespace,
+                } => {
+                    let config = EncryptedStoreConfig {
+                        inner_config,
+                        encryption_config,
+                    };
+                    let storage = DbStorage::<EncryptedStore<DynamoDbStore>, _>::connect(
+                        &config,
+                        &namespace,
+                        wasm_runtime,
+                    )
+                    .await?;
+                    Ok(job.run(storage).await)
+                }
+                #[cfg(feature = "scylladb")]
+                StoreConfig::ScyllaDb {
+                    config: inner_config,
+                    namespace,
+                } => {
+                    let config = EncryptedStoreConfig {
+                        inner_config,
+                        encryption_config,
+                    };
+                    let storage = DbStorage::<EncryptedStore<ScyllaDbStore>, _>::connect(
+                        &config,
+                        &namespace,
+                        wasm_runtime,
+                    )
+                    .await?;
+                    Ok(job.run(storage).await)
+                }
+                _ => Err(anyhow!(
+                    "Encryption at rest is not supported for this store"
+                )),
+            },
         }
     }
 
@@ -605,6 +998,79 @@ impl StoreConfig {
                     config, namespace,
                 )
                 .await?),
+            #[cfg(all(feature = "rocksdb", feature = "scylladb"))]
+            StoreConfig::TieredRocksDbScyllaDb { config, namespace } => Ok(job
+                .run::<TieredStore<RocksDbStore, ScyllaDbStore, ChainStatesFirstAssignment>>(
+                    config, namespace,
+                )
+                .await?),
+            #[cfg(all(feature = "rocksdb", feature = "dynamodb"))]
+            StoreConfig::TieredRocksDbDynamoDb { config, namespace } => Ok(job
+                .run::<TieredStore<RocksDbStore, DynamoDbStore, ChainStatesFirstAssignment>>(
+                    config, namespace,
+                )
+                .await?),
+            #[cfg(feature = "encryption")]
+            StoreConfig::Encrypted {
+                encryption_config,
+                inner,
+            } => match *inner {
+                #[cfg(feature = "storage-service")]
+                StoreConfig::Service {
+                    config: inner_config,
+                    namespace,
+                } => {
+                    let config = EncryptedStoreConfig {
+                        inner_config,
+                        encryption_config,
+                    };
+                    Ok(job
+                        .run::<EncryptedStore<ServiceStoreClient>>(config, namespace)
+                        .await?)
+                }
+                #[cfg(feature = "rocksdb")]
+                StoreConfig::RocksDb {
+                    config: inner_config,
+                    namespace,
+                } => {
+                    let config = EncryptedStoreConfig {
+                        inner_config,
+                        encryption_config,
+                    };
+                    Ok(job
+                        .run::<EncryptedStore<RocksDbStore>>(config, namespace)
+                        .await?)
+                }
+                #[cfg(feature = "dynamodb")]
+                StoreConfig::DynamoDb {
+                    config: inner_config,
+                    namespace,
+                } => {
+                    let config = EncryptedStoreConfig {
+                        inner_config,
+                        encryption_config,
+                    };
+                    Ok(job
+                        .run::<EncryptedStore<DynamoDbStore>>(config, namespace)
+                        .await?)
+                }
+                #[cfg(feature = "scylladb")]
+                StoreConfig::ScyllaDb {
+                    config: inner_config,
+                    namespace,
+                } => {
+                    let config = EncryptedStoreConfig {
+                        inner_config,
+                        encryption_config,
+                    };
+                    Ok(job
+                        .run::<EncryptedStore<ScyllaDbStore>>(config, namespace)
+                        .await?)
+                }
+                _ => Err(anyhow!(
+                    "Encryption at rest is not supported for this store"
+                )),
+            },
         }
     }
 
@@ -786,3 +1252,108 @@ fn test_scylla_db_storage_config_from_str() {
     assert!(StorageConfigNamespace::from_str("scylladb:tcp:address1:tcp:/address2").is_err());
     assert!(StorageConfigNamespace::from_str("scylladb:wrong").is_err());
 }
+
+#[cfg(all(feature = "encryption", feature = "rocksdb"))]
+#[test]
+fn test_encrypted_storage_config_from_str() {
+    let storage_config = StorageConfigNamespace::from_str(
+        "encrypted:keys.json:rocksdb:foo.db:block_in_place:linera",
+    )
+    .unwrap();
+    assert_eq!(
+        storage_config,
+        StorageConfigNamespace {
+            storage_config: StorageConfig::Encrypted {
+                key_file: "keys.json".into(),
+                inner: Box::new(StorageConfig::RocksDb {
+                    path: "foo.db".into(),
+                    spawn_mode: RocksDbSpawnMode::BlockInPlace,
+                }),
+            },
+            namespace: "linera".into()
+        }
+    );
+    assert_eq!(
+        storage_config.to_string(),
+        "encrypted:keys.json:rocksdb:foo.db:block_in_place:linera"
+    );
+    assert!(StorageConfigNamespace::from_str("encrypted:keys.json").is_err());
+    assert!(StorageConfigNamespace::from_str(
+        "encrypted:keys.json:encrypted:keys.json:rocksdb:foo.db"
+    )
+    .is_err());
+}
+
+#[cfg(all(feature = "rocksdb", feature = "scylladb"))]
+#[test]
+fn test_tiered_rocks_db_scylla_db_storage_config_from_str() {
+    let storage_config = StorageConfigNamespace::from_str(
+        "tieredrocksdbscylladb:foo.db:block_in_place:write_back:tcp:db_hostname:230:linera",
+    )
+    .unwrap();
+    assert_eq!(
+        storage_config,
+        StorageConfigNamespace {
+            storage_config: StorageConfig::TieredRocksDbScyllaDb {
+                path_with_guard: PathWithGuard::new("foo.db".into()),
+                spawn_mode: RocksDbSpawnMode::BlockInPlace,
+                write_policy: WritePolicy::WriteBack,
+                uri: "db_hostname:230".to_string(),
+            },
+            namespace: "linera".into()
+        }
+    );
+    assert_eq!(
+        storage_config.to_string(),
+        "tieredrocksdbscylladb:foo.db:block_in_place:write_back:tcp:db_hostname:230:linera"
+    );
+    assert!(storage_config.storage_config.is_write_back());
+    assert!(StorageConfigNamespace::from_str("tieredrocksdbscylladb:foo.db").is_err());
+    assert!(StorageConfigNamespace::from_str(
+        "tieredrocksdbscylladb:foo.db:block_in_place:tcp:db_hostname:230:linera"
+    )
+    .is_err());
+}
+
+#[cfg(all(feature = "rocksdb", feature = "dynamodb"))]
+#[test]
+fn test_tiered_rocks_db_dynamo_db_storage_config_from_str() {
+    let storage_config = StorageConfigNamespace::from_str(
+        "tieredrocksdbdynamodb:foo.db:spawn_blocking:write_through:table",
+    )
+    .unwrap();
+    assert_eq!(
+        storage_config,
+        StorageConfigNamespace {
+            storage_config: StorageConfig::TieredRocksDbDynamoDb {
+                path_with_guard: PathWithGuard::new("foo.db".into()),
+                spawn_mode: RocksDbSpawnMode::SpawnBlocking,
+                write_policy: WritePolicy::WriteThrough,
+                use_dynamodb_local: false,
+            },
+            namespace: "table".into()
+        }
+    );
+    assert_eq!(
+        storage_config.to_string(),
+        "tieredrocksdbdynamodb:foo.db:spawn_blocking:write_through:table:env"
+    );
+    assert!(!storage_config.storage_config.is_write_back());
+    let storage_config = StorageConfigNamespace::from_str(
+        "tieredrocksdbdynamodb:foo.db:spawn_blocking:write_back:table:dynamodb_local",
+    )
+    .unwrap();
+    assert_eq!(
+        storage_config.to_string(),
+        "tieredrocksdbdynamodb:foo.db:spawn_blocking:write_back:table:dynamodb_local"
+    );
+    assert!(StorageConfigNamespace::from_str("tieredrocksdbdynamodb:foo.db").is_err());
+    assert!(StorageConfigNamespace::from_str(
+        "tieredrocksdbdynamodb:foo.db:spawn_blocking:write_back:"
+    )
+    .is_err());
+    assert!(StorageConfigNamespace::from_str(
+        "tieredrocksdbdynamodb:foo.db:spawn_blocking:write_back:table:wrong"
+    )
+    .is_err());
+}
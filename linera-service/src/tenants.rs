@@ -0,0 +1,223 @@
+// Copyright (c) Zefchain Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! A node service serving the chains of several tenants from a single process, e.g. for
+//! custodial operators.
+//!
+//! Each tenant has its own wallet, keystore and chain listener, and its GraphQL endpoints
+//! are served under `/tenants/<name>`, behind the tenant's API token. The token is sent in
+//! the `Authorization: Bearer` header, or, since browsers cannot set headers on WebSocket
+//! connections, in the `token` query parameter of `/ws`. A tenant can only access the
+//! chains of its own wallet.
+
+use std::{
+    collections::BTreeSet,
+    future::IntoFuture,
+    net::SocketAddr,
+    num::NonZeroU16,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use axum::{
+    extract::{Query, Request},
+    http::{header::AUTHORIZATION, StatusCode},
+    middleware::{self, Next},
+    response::IntoResponse,
+    Router,
+};
+use futures::{future::try_join_all, FutureExt as _};
+use linera_client::chain_listener::{ChainListenerConfig, ClientContext};
+use serde::{Deserialize, Serialize};
+use subtle::ConstantTimeEq as _;
+use tokio_util::sync::CancellationToken;
+use tower_http::cors::CorsLayer;
+use tracing::{info, instrument};
+
+use crate::node_service::NodeService;
+
+/// The configuration of the tenants, as read from a JSON file.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct TenantsConfig {
+    pub tenants: Vec<TenantConfig>,
+}
+
+/// The configuration of a tenant.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct TenantConfig {
+    /// The name of the tenant, used in the path of its endpoints.
+    pub name: String,
+    /// The API token expected in the `Authorization: Bearer` header of the requests, or
+    /// in the `token` query parameter of the WebSocket requests.
+    pub token: String,
+    /// The path of the tenant's wallet.
+    pub wallet: PathBuf,
+    /// The path of the tenant's keystore.
+    pub keystore: PathBuf,
+    /// The maximal number of chains to listen to for this tenant, if different from the
+    /// service's. The chains of the tenant's wallet are always listened to.
+    #[serde(default)]
+    pub max_chains: Option<usize>,
+}
+
+impl TenantsConfig {
+    /// Reads the configuration of the tenants from a JSON file.
+    pub fn read(path: &Path) -> Result<Self, anyhow::Error> {
+        let config: Self = serde_json::from_str(&fs_err::read_to_string(path)?)?;
+        let mut names = BTreeSet::new();
+        for tenant in &config.tenants {
+            anyhow::ensure!(
+                !tenant.name.is_empty()
+                    && tenant
+                        .name
+                        .chars()
+                        .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_'),
+                "invalid tenant name: {:?}",
+                tenant.name
+            );
+            anyhow::ensure!(
+                names.insert(&tenant.name),
+                "duplicate tenant name: {}",
+                tenant.name
+            );
+            anyhow::ensure!(
+                !tenant.token.is_empty(),
+                "missing API token for tenant {}",
+                tenant.name
+            );
+        }
+        Ok(config)
+    }
+}
+
+impl TenantConfig {
+    /// Returns the chain listener configuration of the tenant, given the service's.
+    pub fn listener_config(&self, config: &ChainListenerConfig) -> ChainListenerConfig {
+        ChainListenerConfig {
+            max_chains: self.max_chains.or(config.max_chains),
+            ..config.clone()
+        }
+    }
+}
+
+/// A tenant of the [`TenantService`].
+pub struct Tenant<C>
+where
+    C: ClientContext + 'static,
+{
+    name: String,
+    token: Arc<str>,
+    service: NodeService<C>,
+}
+
+/// The query parameters of the WebSocket requests.
+#[derive(Deserialize)]
+struct WebSocketParams {
+    token: Option<String>,
+}
+
+impl<C> Tenant<C>
+where
+    C: ClientContext + 'static,
+{
+    /// Creates a tenant serving the chains of a node service, given its name and API token.
+    /// The node service only serves the chains of its wallet.
+    pub fn new(name: String, token: &str, service: NodeService<C>) -> Self {
+        Self {
+            name,
+            token: token.into(),
+            service: service.with_wallet_chains_only(),
+        }
+    }
+
+    /// Returns the routes of the tenant, rejecting requests without its API token.
+    fn router(&self) -> Router {
+        let token = self.token.clone();
+        self.service
+            .router()
+            .layer(middleware::from_fn(move |request: Request, next: Next| {
+                let token = token.clone();
+                async move {
+                    if is_authorized(&request, &token) {
+                        next.run(request).await
+                    } else {
+                        StatusCode::UNAUTHORIZED.into_response()
+                    }
+                }
+            }))
+    }
+}
+
+/// Returns whether a request carries the given API token.
+fn is_authorized(request: &Request, token: &str) -> bool {
+    let header = request
+        .headers()
+        .get(AUTHORIZATION)
+        .and_then(|value| value.as_bytes().strip_prefix(b"Bearer "));
+    let parameter = (request.uri().path() == "/ws")
+        .then(|| Query::<WebSocketParams>::try_from_uri(request.uri()).ok())
+        .flatten()
+        .and_then(|Query(params)| params.token);
+    let candidate = header.or(parameter.as_ref().map(String::as_bytes));
+    candidate.is_some_and(|candidate| bool::from(candidate.ct_eq(token.as_bytes())))
+}
+
+/// A server exposing the node services of several tenants on the same port.
+pub struct TenantService<C>
+where
+    C: ClientContext + 'static,
+{
+    port: NonZeroU16,
+    tenants: Vec<Tenant<C>>,
+}
+
+impl<C> TenantService<C>
+where
+    C: ClientContext,
+{
+    /// Creates a new instance of the service given its tenants and a port.
+    pub fn new(port: NonZeroU16, tenants: Vec<Tenant<C>>) -> Self {
+        Self { port, tenants }
+    }
+
+    /// Returns the routes of all tenants.
+    pub fn router(&self) -> Router {
+        let mut app = Router::new().route("/ready", axum::routing::get(|| async { "ready!" }));
+        for tenant in &self.tenants {
+            app = app.nest(&format!("/tenants/{}", tenant.name), tenant.router());
+        }
+        app
+    }
+
+    /// Runs the service and the chain listeners of all tenants.
+    #[instrument(name = "tenant_service", level = "info", skip_all, fields(port = ?self.port))]
+    pub async fn run(self, cancellation_token: CancellationToken) -> Result<(), anyhow::Error> {
+        let port = self.port.get();
+        let app = self.router().layer(CorsLayer::permissive());
+
+        info!(
+            "Serving {} tenants on http://localhost:{}/tenants/<name>",
+            self.tenants.len(),
+            port
+        );
+
+        let chain_listeners = try_join_all(
+            self.tenants
+                .into_iter()
+                .map(|tenant| Box::pin(tenant.service.listen(cancellation_token.clone()))),
+        );
+        let tcp_listener =
+            tokio::net::TcpListener::bind(SocketAddr::from(([0, 0, 0, 0], port))).await?;
+        let server = axum::serve(tcp_listener, app).into_future();
+        futures::select! {
+            result = chain_listeners.fuse() => { result?; },
+            result = Box::pin(server).fuse() => result?,
+        };
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+#[path = "unit_tests/tenants.rs"]
+mod tests;
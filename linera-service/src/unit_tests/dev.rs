@@ -0,0 +1,158 @@
+// Copyright (c) Zefchain Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+#![allow(clippy::large_futures)]
+
+use std::path::Path;
+
+use anyhow::Result;
+use serde_json::json;
+#[cfg(any(
+    feature = "storage-service",
+    feature = "scylladb",
+    feature = "dynamodb"
+))]
+use {
+    crate::{
+        cli_wrappers::{
+            local_net::{get_node_port, Database, LocalNetConfig, ProcessInbox},
+            ClientWrapper, LineraNet as _, LineraNetConfig as _, Network,
+        },
+        project::Project,
+    },
+    linera_base::identifiers::ApplicationId,
+    std::sync::Arc,
+    test_case::test_case,
+};
+
+use super::{source_modification_times, DevDeployment};
+
+/// Writes `contents` to the file at `path`, creating its parent directories.
+fn write(path: &Path, contents: &str) -> Result<()> {
+    fs_err::create_dir_all(path.parent().unwrap())?;
+    fs_err::write(path, contents)?;
+    Ok(())
+}
+
+#[test]
+fn scripts_accept_documents_and_requests() -> Result<()> {
+    let directory = tempfile::tempdir()?;
+    let path = directory.path().join("script.json");
+    write(
+        &path,
+        r#"[
+            "query { value }",
+            { "query": "mutation($value: Int!) { increment(value: $value) }", "variables": { "value": 1 } }
+        ]"#,
+    )?;
+    assert_eq!(
+        DevDeployment::read_script(&path)?,
+        vec![
+            json!({ "query": "query { value }" }),
+            json!({
+                "query": "mutation($value: Int!) { increment(value: $value) }",
+                "variables": { "value": 1 },
+            }),
+        ]
+    );
+
+    write(&path, r#"[{ "variables": {} }]"#)?;
+    assert!(DevDeployment::read_script(&path).is_err());
+    write(&path, r#"{ "query": "query { value }" }"#)?;
+    assert!(DevDeployment::read_script(&path).is_err());
+    Ok(())
+}
+
+#[test]
+fn only_watched_sources_are_listed() -> Result<()> {
+    let directory = tempfile::tempdir()?;
+    let root = directory.path();
+    for path in [
+        "Cargo.toml",
+        "src/lib.rs",
+        "src/nested/state.rs",
+        "README.md",
+    ] {
+        write(&root.join(path), "")?;
+    }
+    write(&root.join("target/debug/build.log"), "")?;
+
+    let times = source_modification_times(root)?;
+    assert_eq!(
+        times.keys().cloned().collect::<Vec<_>>(),
+        vec![
+            root.join("Cargo.toml"),
+            root.join("src/lib.rs"),
+            root.join("src/nested/state.rs"),
+        ]
+    );
+
+    write(&root.join("src/contract.rs"), "")?;
+    let new_times = source_modification_times(root)?;
+    assert_ne!(new_times, times);
+    assert!(new_times.contains_key(&root.join("src/contract.rs")));
+    Ok(())
+}
+
+#[cfg_attr(feature = "storage-service", test_case(Database::Service, Network::Grpc ; "storage_service_grpc"))]
+#[cfg_attr(feature = "scylladb", test_case(Database::ScyllaDb, Network::Grpc ; "scylladb_grpc"))]
+#[cfg_attr(feature = "dynamodb", test_case(Database::DynamoDb, Network::Grpc ; "aws_grpc"))]
+#[cfg(any(
+    feature = "storage-service",
+    feature = "scylladb",
+    feature = "dynamodb"
+))]
+#[test_log::test(tokio::test)]
+async fn deployment_builds_the_project_and_replays_the_script(
+    database: Database,
+    network: Network,
+) -> Result<()> {
+    let config = LocalNetConfig {
+        num_initial_validators: 1,
+        num_shards: 1,
+        ..LocalNetConfig::new_test(database, network)
+    };
+    let (mut net, client) = config.instantiate().await?;
+    let chain_id = client.default_chain().expect("Client has no default chain");
+    let port = get_node_port().await;
+    let mut node_service = client
+        .run_node_service(port, ProcessInbox::Automatic)
+        .await?;
+
+    let project = Arc::new(Project::from_existing_project(
+        ClientWrapper::example_path("counter")?,
+    )?);
+    let deployment = DevDeployment {
+        name: None,
+        parameters: json!(null),
+        argument: json!(1),
+        script: vec![json!({ "query": "mutation { increment(value: 2) }" })],
+    };
+    let endpoint = super::deploy(&project, &deployment, &node_service, chain_id).await?;
+    let application_id = endpoint
+        .rsplit('/')
+        .next()
+        .expect("The endpoint ends with the application ID")
+        .parse::<ApplicationId>()?
+        .with_abi::<counter::CounterAbi>();
+    let application = node_service
+        .make_application(&chain_id, &application_id)
+        .await?;
+    assert_eq!(application.query_json::<u64>("value").await?, 3);
+
+    // A failing script request fails the deployment, without stopping the node service.
+    let deployment = DevDeployment {
+        script: vec![json!({ "query": "mutation { missing }" })],
+        ..deployment
+    };
+    assert!(
+        super::deploy(&project, &deployment, &node_service, chain_id)
+            .await
+            .is_err()
+    );
+
+    node_service.ensure_is_running()?;
+    net.ensure_is_running().await?;
+    net.terminate().await?;
+    Ok(())
+}
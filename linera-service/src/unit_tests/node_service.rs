@@ -0,0 +1,281 @@
+// Copyright (c) Zefchain Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+#![allow(clippy::large_futures)]
+
+use std::{num::NonZeroU16, time::Duration};
+
+use async_graphql::{Request, Variables};
+use futures::{FutureExt as _, Stream, StreamExt as _};
+use linera_base::{
+    crypto::{CryptoHash, InMemorySigner},
+    data_types::{Amount, Event, Timestamp},
+    identifiers::{
+        AccountOwner, ApplicationId, ChainId, GenericApplicationId, StreamId, StreamName,
+    },
+};
+use linera_chain::{
+    data_types::BlockExecutionOutcome,
+    test::{make_first_block, BlockTestExt as _},
+    types::{CertificateValue as _, ConfirmedBlock},
+};
+use linera_client::{
+    chain_listener::{self, ChainListenerConfig},
+    wallet::Wallet,
+};
+use linera_core::{
+    client::ChainClient,
+    environment,
+    test_utils::{MemoryStorageBuilder, StorageBuilder as _, TestBuilder},
+};
+use linera_execution::{
+    committee::Committee, system::EPOCH_STREAM_NAME, Message, MessageKind, Operation,
+    OutgoingMessage, ResourceControlPolicy,
+};
+use serde_json::json;
+
+use super::{ApplicationActivity, NodeService};
+
+struct ClientContext {
+    client: ChainClient<environment::Test>,
+}
+
+impl chain_listener::ClientContext for ClientContext {
+    type Environment = environment::Test;
+
+    fn wallet(&self) -> &Wallet {
+        unimplemented!()
+    }
+
+    fn storage(&self) -> &environment::TestStorage {
+        self.client.storage_client()
+    }
+
+    fn client(&self) -> &linera_core::client::Client<environment::Test> {
+        unimplemented!()
+    }
+
+    fn make_chain_client(&self, chain_id: ChainId) -> ChainClient<environment::Test> {
+        assert_eq!(chain_id, self.client.chain_id());
+        self.client.clone()
+    }
+
+    async fn update_wallet_for_new_chain(
+        &mut self,
+        _: ChainId,
+        _: Option<AccountOwner>,
+        _: Timestamp,
+    ) -> Result<(), linera_client::Error> {
+        Ok(())
+    }
+
+    async fn update_wallet(
+        &mut self,
+        _: &ChainClient<environment::Test>,
+    ) -> Result<(), linera_client::Error> {
+        Ok(())
+    }
+}
+
+async fn make_service(client: ChainClient<environment::Test>) -> NodeService<ClientContext> {
+    let chain_id = client.chain_id();
+    NodeService::new(
+        ChainListenerConfig::default(),
+        NonZeroU16::new(8080).unwrap(),
+        Some(chain_id),
+        ClientContext { client },
+    )
+    .await
+}
+
+/// Returns the data of the next response of a subscription.
+async fn next_data(
+    responses: &mut (impl Stream<Item = async_graphql::Response> + Unpin),
+) -> serde_json::Value {
+    let response = tokio::time::timeout(Duration::from_secs(10), responses.next())
+        .await
+        .expect("no response from the subscription")
+        .expect("the subscription ended");
+    assert!(response.errors.is_empty(), "{:?}", response.errors);
+    response.data.into_json().unwrap()
+}
+
+#[tokio::test]
+async fn test_blocks_subscription() {
+    let storage_builder = MemoryStorageBuilder::default();
+    let signer = InMemorySigner::new(None);
+    let mut builder = TestBuilder::new(storage_builder, 4, 1, signer)
+        .await
+        .unwrap();
+    let client = builder
+        .add_root_chain(1, Amount::from_tokens(3))
+        .await
+        .unwrap();
+    let chain_id = client.chain_id();
+    let service = make_service(client.clone()).await;
+
+    let mut blocks = service.schema().execute_stream(format!(
+        "subscription {{ blocks(chainId: \"{chain_id}\") {{ hash block {{ header {{ height }} }} }} }}"
+    ));
+    // The first poll sets up the subscription.
+    assert!(blocks.next().now_or_never().is_none());
+
+    for height in 0..2 {
+        let certificate = client
+            .burn(AccountOwner::CHAIN, Amount::ONE)
+            .await
+            .unwrap()
+            .unwrap();
+        let data = next_data(&mut blocks).await;
+        assert_eq!(
+            data["blocks"]["hash"],
+            serde_json::to_value(certificate.hash()).unwrap()
+        );
+        assert_eq!(data["blocks"]["block"]["header"]["height"], json!(height));
+    }
+}
+
+#[tokio::test]
+async fn test_events_subscription() {
+    let storage_builder = MemoryStorageBuilder::default();
+    let signer = InMemorySigner::new(None);
+    let mut builder = TestBuilder::new(storage_builder, 4, 1, signer)
+        .await
+        .unwrap();
+    let admin = builder
+        .add_root_chain(0, Amount::from_tokens(3))
+        .await
+        .unwrap();
+    let chain_id = admin.chain_id();
+    let validators = builder.initial_committee.validators().clone();
+    let service = make_service(admin.clone()).await;
+
+    let query = "subscription Events($chainId: ChainId!, $streamId: StreamIdInput!) { \
+                 events(chainId: $chainId, streamId: $streamId, startIndex: 1) { index } }";
+    let variables = Variables::from_json(json!({
+        "chainId": chain_id,
+        "streamId": {
+            "applicationId": GenericApplicationId::System,
+            "streamName": StreamName(EPOCH_STREAM_NAME.to_vec()),
+        },
+    }));
+    let mut events = service
+        .schema()
+        .execute_stream(Request::new(query).variables(variables.clone()));
+    assert!(events.next().now_or_never().is_none());
+
+    // Creating a new committee emits the event of epoch 1.
+    let committee = Committee::new(validators, ResourceControlPolicy::only_fuel());
+    admin.stage_new_committee(committee).await.unwrap();
+    let data = next_data(&mut events).await;
+    assert_eq!(data["events"]["index"], json!(1));
+
+    // A new subscription starts with the existing events.
+    let mut events = service
+        .schema()
+        .execute_stream(Request::new(query).variables(variables));
+    let data = next_data(&mut events).await;
+    assert_eq!(data["events"]["index"], json!(1));
+}
+
+#[tokio::test]
+async fn test_application_activity_subscription() {
+    let storage_builder = MemoryStorageBuilder::default();
+    let signer = InMemorySigner::new(None);
+    let mut builder = TestBuilder::new(storage_builder, 4, 1, signer)
+        .await
+        .unwrap();
+    let client = builder
+        .add_root_chain(1, Amount::from_tokens(3))
+        .await
+        .unwrap();
+    let chain_id = client.chain_id();
+    let service = make_service(client.clone()).await;
+    let application_id = ApplicationId::new(CryptoHash::test_hash("application"));
+
+    let mut activity = service.schema().execute_stream(format!(
+        "subscription {{ applicationActivity(chainId: \"{chain_id}\", \
+         applicationId: \"{application_id}\") {{ height }} }}"
+    ));
+    assert!(activity.next().now_or_never().is_none());
+
+    // Blocks that don't involve the application are not reported.
+    client
+        .burn(AccountOwner::CHAIN, Amount::ONE)
+        .await
+        .unwrap()
+        .unwrap();
+    let next = tokio::time::timeout(Duration::from_millis(500), activity.next()).await;
+    assert!(next.is_err(), "unexpected activity: {next:?}");
+}
+
+#[test]
+fn test_application_activity() {
+    let chain_id = ChainId(CryptoHash::test_hash("chain"));
+    let application_id = ApplicationId::new(CryptoHash::test_hash("application"));
+    let other_id = ApplicationId::new(CryptoHash::test_hash("other"));
+    let operation = |application_id| Operation::User {
+        application_id,
+        bytes: vec![],
+    };
+    let message = |application_id| OutgoingMessage {
+        destination: chain_id,
+        authenticated_signer: None,
+        grant: Amount::ZERO,
+        refund_grant_to: None,
+        kind: MessageKind::Simple,
+        message: Message::User {
+            application_id,
+            bytes: vec![],
+        },
+    };
+    let event = |application_id, index| Event {
+        stream_id: StreamId {
+            application_id: GenericApplicationId::User(application_id),
+            stream_name: StreamName(b"updates".to_vec()),
+        },
+        index,
+        value: vec![],
+    };
+
+    // The other application calls ours, which sends a message and emits two events.
+    let block = make_first_block(chain_id)
+        .with_operation(operation(other_id))
+        .with_operation(operation(other_id));
+    let outcome = BlockExecutionOutcome {
+        messages: vec![vec![message(application_id)], vec![]],
+        events: vec![
+            vec![event(application_id, 0), event(application_id, 1)],
+            vec![],
+        ],
+        ..BlockExecutionOutcome::default()
+    };
+    let block = ConfirmedBlock::new(outcome.with(block));
+    let activity = ApplicationActivity::new(&block, application_id).unwrap();
+    assert_eq!(activity.height, block.height());
+    assert_eq!(activity.hash, block.hash());
+    assert_eq!(
+        (
+            activity.operations,
+            activity.messages,
+            activity.sent_messages,
+            activity.events
+        ),
+        (0, 0, 1, 2)
+    );
+    let activity = ApplicationActivity::new(&block, other_id).unwrap();
+    assert_eq!(
+        (
+            activity.operations,
+            activity.messages,
+            activity.sent_messages,
+            activity.events
+        ),
+        (2, 0, 0, 0)
+    );
+
+    // A block that doesn't involve the application is not reported.
+    let block = make_first_block(chain_id).with_operation(operation(other_id));
+    let block = ConfirmedBlock::new(BlockExecutionOutcome::default().with(block));
+    assert!(ApplicationActivity::new(&block, application_id).is_none());
+}
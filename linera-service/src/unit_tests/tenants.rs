@@ -0,0 +1,245 @@
+// Copyright (c) Zefchain Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+#![allow(clippy::large_futures)]
+
+use std::{net::SocketAddr, num::NonZeroU16};
+
+use linera_base::{
+    crypto::{AccountPublicKey, CryptoHash, InMemorySigner},
+    data_types::{Amount, Timestamp},
+    identifiers::{AccountOwner, ApplicationId, ChainId},
+};
+use linera_client::{
+    chain_listener::{self, ChainListenerConfig},
+    config::{CommitteeConfig, GenesisConfig},
+    wallet::{UserChain, Wallet},
+};
+use linera_core::{
+    client::ChainClient,
+    environment,
+    test_utils::{MemoryStorageBuilder, StorageBuilder as _, TestBuilder},
+};
+use linera_execution::ResourceControlPolicy;
+use serde_json::{json, Value};
+
+use super::{Tenant, TenantService};
+use crate::node_service::NodeService;
+
+/// A client context with a wallet containing the chain of its client.
+struct ClientContext {
+    wallet: Wallet,
+    client: ChainClient<environment::Test>,
+}
+
+impl ClientContext {
+    fn new(client: ChainClient<environment::Test>) -> Self {
+        let genesis_config = GenesisConfig::new(
+            CommitteeConfig::default(),
+            Timestamp::from(0),
+            ResourceControlPolicy::default(),
+            "test network".to_string(),
+            AccountPublicKey::test_key(0),
+            Amount::ZERO,
+        );
+        let mut wallet = Wallet::new(genesis_config);
+        wallet.insert(UserChain::make_other(client.chain_id(), Timestamp::from(0)));
+        Self { wallet, client }
+    }
+}
+
+impl chain_listener::ClientContext for ClientContext {
+    type Environment = environment::Test;
+
+    fn wallet(&self) -> &Wallet {
+        &self.wallet
+    }
+
+    fn storage(&self) -> &environment::TestStorage {
+        self.client.storage_client()
+    }
+
+    fn client(&self) -> &linera_core::client::Client<environment::Test> {
+        unimplemented!()
+    }
+
+    fn make_chain_client(&self, chain_id: ChainId) -> ChainClient<environment::Test> {
+        assert_eq!(chain_id, self.client.chain_id(), "chain of another tenant");
+        self.client.clone()
+    }
+
+    async fn update_wallet_for_new_chain(
+        &mut self,
+        _: ChainId,
+        _: Option<AccountOwner>,
+        _: Timestamp,
+    ) -> Result<(), linera_client::Error> {
+        Ok(())
+    }
+
+    async fn update_wallet(
+        &mut self,
+        _: &ChainClient<environment::Test>,
+    ) -> Result<(), linera_client::Error> {
+        Ok(())
+    }
+}
+
+/// Serves two tenants, `alice` and `bob`, with a chain each, and returns the address of the
+/// server and the chains.
+async fn serve_tenants() -> (SocketAddr, ChainId, ChainId) {
+    let storage_builder = MemoryStorageBuilder::default();
+    let signer = InMemorySigner::new(None);
+    let mut builder = TestBuilder::new(storage_builder, 4, 1, signer)
+        .await
+        .unwrap();
+    let port = NonZeroU16::new(8080).unwrap();
+    let mut tenants = Vec::new();
+    let mut chain_ids = Vec::new();
+    for (index, name) in ["alice", "bob"].into_iter().enumerate() {
+        let client = builder
+            .add_root_chain(index as u32 + 1, Amount::ONE)
+            .await
+            .unwrap();
+        chain_ids.push(client.chain_id());
+        let context = ClientContext::new(client);
+        let config = ChainListenerConfig::default();
+        let service = NodeService::new(config, port, None, context).await;
+        tenants.push(Tenant::new(
+            name.to_string(),
+            &format!("{name}-token"),
+            service,
+        ));
+    }
+    let app = TenantService::new(port, tenants).router();
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let address = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, app).await });
+    (address, chain_ids[0], chain_ids[1])
+}
+
+/// Sends a GraphQL query to a tenant and returns the status and the response.
+async fn query(
+    address: SocketAddr,
+    tenant: &str,
+    token: Option<&str>,
+    query: String,
+) -> (u16, Option<Value>) {
+    let mut request = reqwest::Client::new()
+        .post(format!("http://{address}/tenants/{tenant}/"))
+        .json(&json!({ "query": query }));
+    if let Some(token) = token {
+        request = request.bearer_auth(token);
+    }
+    let response = request.send().await.unwrap();
+    let status = response.status().as_u16();
+    (status, response.json().await.ok())
+}
+
+#[tokio::test]
+async fn test_tenant_authentication() {
+    let (address, alice_chain, _) = serve_tenants().await;
+    let chain_query = format!("{{ chain(chainId: \"{alice_chain}\") {{ chainId }} }}");
+
+    let (status, _) = query(address, "alice", None, chain_query.clone()).await;
+    assert_eq!(status, 401);
+    let (status, _) = query(address, "alice", Some("alice-tok"), chain_query.clone()).await;
+    assert_eq!(status, 401);
+    let (status, _) = query(address, "alice", Some("bob-token"), chain_query.clone()).await;
+    assert_eq!(status, 401);
+    let (status, response) = query(address, "alice", Some("alice-token"), chain_query).await;
+    assert_eq!(status, 200);
+    let response = response.unwrap();
+    assert_eq!(
+        response["data"]["chain"]["chainId"],
+        json!(alice_chain),
+        "{response}"
+    );
+
+    // The token is only accepted as a query parameter on the WebSocket endpoint, which
+    // then requires a WebSocket upgrade.
+    let client = reqwest::Client::new();
+    let ws_status = |token: &str| {
+        let request = client.get(format!("http://{address}/tenants/alice/ws?token={token}"));
+        async move { request.send().await.unwrap().status().as_u16() }
+    };
+    assert_eq!(ws_status("bob-token").await, 401);
+    assert_ne!(ws_status("alice-token").await, 401);
+    let response = client
+        .post(format!("http://{address}/tenants/alice/?token=alice-token"))
+        .json(&json!({ "query": "{ version { crateVersion } }" }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status().as_u16(), 401);
+}
+
+#[tokio::test]
+async fn test_tenant_isolation() {
+    let (address, alice_chain, bob_chain) = serve_tenants().await;
+
+    // Alice cannot read Bob's chain, directly or through variables and fragments.
+    let queries = [
+        format!("{{ chain(chainId: \"{bob_chain}\") {{ chainId }} }}"),
+        format!("{{ applications(chainId: \"{bob_chain}\") {{ id }} }}"),
+        format!(
+            "query {{ ...Chain }} fragment Chain on QueryRoot {{ \
+             chain(chainId: \"{bob_chain}\") {{ chainId }} }}"
+        ),
+        format!("mutation {{ processInbox(chainId: \"{bob_chain}\") }}"),
+    ];
+    for chain_query in queries {
+        let (status, response) = query(address, "alice", Some("alice-token"), chain_query).await;
+        assert_eq!(status, 200);
+        let response = response.unwrap();
+        assert!(response["data"].is_null(), "{response}");
+        let message = response["errors"][0]["message"].as_str().unwrap();
+        assert!(message.contains("is not in the wallet"), "{message}");
+    }
+    let response = reqwest::Client::new()
+        .post(format!("http://{address}/tenants/alice/"))
+        .bearer_auth("alice-token")
+        .json(&json!({
+            "query": "query Chain($chainId: ChainId!) { chain(chainId: $chainId) { chainId } }",
+            "variables": { "chainId": bob_chain },
+        }))
+        .send()
+        .await
+        .unwrap();
+    let response: Value = response.json().await.unwrap();
+    assert!(response["data"].is_null(), "{response}");
+
+    // The application endpoints of Bob's chain are forbidden too.
+    let application_id = ApplicationId::new(CryptoHash::test_hash("application"));
+    let response = reqwest::Client::new()
+        .post(format!(
+            "http://{address}/tenants/alice/chains/{bob_chain}/applications/{application_id}"
+        ))
+        .bearer_auth("alice-token")
+        .body("{ value }")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status().as_u16(), 403);
+
+    // Bob can read his own chain, but not Alice's.
+    let (_, response) = query(
+        address,
+        "bob",
+        Some("bob-token"),
+        format!("{{ chain(chainId: \"{bob_chain}\") {{ chainId }} }}"),
+    )
+    .await;
+    assert_eq!(
+        response.unwrap()["data"]["chain"]["chainId"],
+        json!(bob_chain)
+    );
+    let (_, response) = query(
+        address,
+        "bob",
+        Some("bob-token"),
+        format!("{{ chain(chainId: \"{alice_chain}\") {{ chainId }} }}"),
+    )
+    .await;
+    assert!(response.unwrap()["data"].is_null());
+}
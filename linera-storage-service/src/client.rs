@@ -3,6 +3,7 @@
 
 use std::{
     mem,
+    ops::Bound,
     sync::{
         atomic::{AtomicBool, Ordering},
         Arc,
@@ -32,14 +33,15 @@ use crate::common::storage_service_test_endpoint;
 use crate::{
     common::{KeyPrefix, ServiceStoreError, ServiceStoreInternalConfig, MAX_PAYLOAD_SIZE},
     key_value_store::{
-        statement::Operation, store_processor_client::StoreProcessorClient, KeyValue,
-        KeyValueAppend, ReplyContainsKey, ReplyContainsKeys, ReplyExistsNamespace,
+        key_bound, statement::Operation, store_processor_client::StoreProcessorClient, KeyBound,
+        KeyValue, KeyValueAppend, ReplyContainsKey, ReplyContainsKeys, ReplyExistsNamespace,
         ReplyFindKeyValuesByPrefix, ReplyFindKeysByPrefix, ReplyListAll, ReplyListRootKeys,
         ReplyReadMultiValues, ReplyReadValue, ReplySpecificChunk, RequestContainsKey,
         RequestContainsKeys, RequestCreateNamespace, RequestDeleteNamespace,
-        RequestExistsNamespace, RequestFindKeyValuesByPrefix, RequestFindKeysByPrefix,
-        RequestListRootKeys, RequestReadMultiValues, RequestReadValue, RequestSpecificChunk,
-        RequestWriteBatchExtended, Statement,
+        RequestExistsNamespace, RequestFindKeyValuesByPrefix, RequestFindKeyValuesByPrefixInRange,
+        RequestFindKeysByPrefix, RequestFindKeysByPrefixInRange, RequestListRootKeys,
+        RequestReadMultiValues, RequestReadValue, RequestSpecificChunk, RequestWriteBatchExtended,
+        Statement,
     },
 };
 
@@ -241,6 +243,99 @@ impl ReadableKeyValueStore for ServiceStoreClientInternal {
             self.read_entries(message_index, num_chunks).await
         }
     }
+
+    async fn find_keys_by_prefix_in_range(
+        &self,
+        key_prefix: &[u8],
+        range: (Bound<Vec<u8>>, Bound<Vec<u8>>),
+        reverse: bool,
+        limit: Option<usize>,
+    ) -> Result<Vec<Vec<u8>>, ServiceStoreError> {
+        ensure!(
+            key_prefix.len() <= MAX_KEY_SIZE,
+            ServiceStoreError::KeyTooLong
+        );
+        let mut full_key_prefix = self.start_key.clone();
+        full_key_prefix.extend(key_prefix);
+        let query = RequestFindKeysByPrefixInRange {
+            key_prefix: full_key_prefix,
+            lower: to_key_bound(range.0),
+            upper: to_key_bound(range.1),
+            reverse,
+            limit: limit.map(|limit| limit as u64),
+        };
+        let request = tonic::Request::new(query);
+        let channel = self.channel.clone();
+        let mut client = StoreProcessorClient::new(channel);
+        let _guard = self.acquire().await;
+        let response = client.process_find_keys_by_prefix_in_range(request).await?;
+        let response = response.into_inner();
+        let ReplyFindKeysByPrefix {
+            keys,
+            message_index,
+            num_chunks,
+        } = response;
+        if num_chunks == 0 {
+            Ok(keys)
+        } else {
+            self.read_entries(message_index, num_chunks).await
+        }
+    }
+
+    async fn find_key_values_by_prefix_in_range(
+        &self,
+        key_prefix: &[u8],
+        range: (Bound<Vec<u8>>, Bound<Vec<u8>>),
+        reverse: bool,
+        limit: Option<usize>,
+    ) -> Result<Vec<(Vec<u8>, Vec<u8>)>, ServiceStoreError> {
+        ensure!(
+            key_prefix.len() <= MAX_KEY_SIZE,
+            ServiceStoreError::KeyTooLong
+        );
+        let mut full_key_prefix = self.start_key.clone();
+        full_key_prefix.extend(key_prefix);
+        let query = RequestFindKeyValuesByPrefixInRange {
+            key_prefix: full_key_prefix,
+            lower: to_key_bound(range.0),
+            upper: to_key_bound(range.1),
+            reverse,
+            limit: limit.map(|limit| limit as u64),
+        };
+        let request = tonic::Request::new(query);
+        let channel = self.channel.clone();
+        let mut client = StoreProcessorClient::new(channel);
+        let _guard = self.acquire().await;
+        let response = client
+            .process_find_key_values_by_prefix_in_range(request)
+            .await?;
+        let response = response.into_inner();
+        let ReplyFindKeyValuesByPrefix {
+            key_values,
+            message_index,
+            num_chunks,
+        } = response;
+        if num_chunks == 0 {
+            let key_values = key_values
+                .into_iter()
+                .map(|x| (x.key, x.value))
+                .collect::<Vec<_>>();
+            Ok(key_values)
+        } else {
+            self.read_entries(message_index, num_chunks).await
+        }
+    }
+}
+
+/// Converts a bound of a range of keys into its protobuf message, which is absent for
+/// an unbounded range.
+fn to_key_bound(bound: Bound<Vec<u8>>) -> Option<KeyBound> {
+    let bound = match bound {
+        Bound::Included(key) => key_bound::Bound::Included(key),
+        Bound::Excluded(key) => key_bound::Bound::Excluded(key),
+        Bound::Unbounded => return None,
+    };
+    Some(KeyBound { bound: Some(bound) })
 }
 
 impl WritableKeyValueStore for ServiceStoreClientInternal {
@@ -1,7 +1,7 @@
 // Copyright (c) Zefchain Labs, Inc.
 // SPDX-License-Identifier: Apache-2.0
 
-use std::{collections::BTreeMap, sync::Arc};
+use std::{collections::BTreeMap, ops::Bound, sync::Arc};
 
 use async_lock::RwLock;
 use linera_storage_service::common::{KeyPrefix, MAX_PAYLOAD_SIZE};
@@ -21,20 +21,35 @@ use tracing::{info, instrument};
 use tracing_subscriber::fmt::format::FmtSpan;
 
 use crate::key_value_store::{
+    key_bound,
     statement::Operation,
     store_processor_server::{StoreProcessor, StoreProcessorServer},
-    KeyValue, OptValue, ReplyContainsKey, ReplyContainsKeys, ReplyExistsNamespace,
+    KeyBound, KeyValue, OptValue, ReplyContainsKey, ReplyContainsKeys, ReplyExistsNamespace,
     ReplyFindKeyValuesByPrefix, ReplyFindKeysByPrefix, ReplyListAll, ReplyListRootKeys,
     ReplyReadMultiValues, ReplyReadValue, ReplySpecificChunk, RequestContainsKey,
     RequestContainsKeys, RequestCreateNamespace, RequestDeleteNamespace, RequestExistsNamespace,
-    RequestFindKeyValuesByPrefix, RequestFindKeysByPrefix, RequestListRootKeys,
-    RequestReadMultiValues, RequestReadValue, RequestSpecificChunk, RequestWriteBatchExtended,
+    RequestFindKeyValuesByPrefix, RequestFindKeyValuesByPrefixInRange, RequestFindKeysByPrefix,
+    RequestFindKeysByPrefixInRange, RequestListRootKeys, RequestReadMultiValues, RequestReadValue,
+    RequestSpecificChunk, RequestWriteBatchExtended,
 };
 
 pub mod key_value_store {
     tonic::include_proto!("key_value_store.v1");
 }
 
+/// Converts the protobuf message of a bound of a range of keys, which is absent for an
+/// unbounded range.
+fn from_key_bound(bound: Option<KeyBound>) -> Result<Bound<Vec<u8>>, Status> {
+    let Some(KeyBound { bound }) = bound else {
+        return Ok(Bound::Unbounded);
+    };
+    match bound {
+        Some(key_bound::Bound::Included(key)) => Ok(Bound::Included(key)),
+        Some(key_bound::Bound::Excluded(key)) => Ok(Bound::Excluded(key)),
+        None => Err(Status::invalid_argument("missing key bound")),
+    }
+}
+
 enum ServiceStoreServerInternal {
     Memory(MemoryStore),
     /// The RocksDB key value store
@@ -162,6 +177,66 @@ impl ServiceStoreServer {
         }
     }
 
+    pub async fn find_keys_by_prefix_in_range(
+        &self,
+        key_prefix: &[u8],
+        range: (Bound<Vec<u8>>, Bound<Vec<u8>>),
+        reverse: bool,
+        limit: Option<usize>,
+    ) -> Result<Vec<Vec<u8>>, Status> {
+        match &self.store {
+            ServiceStoreServerInternal::Memory(store) => store
+                .find_keys_by_prefix_in_range(key_prefix, range, reverse, limit)
+                .await
+                .map_err(|e| {
+                    Status::unknown(format!(
+                        "Memory error {:?} at find_keys_by_prefix_in_range",
+                        e
+                    ))
+                }),
+            #[cfg(with_rocksdb)]
+            ServiceStoreServerInternal::RocksDb(store) => store
+                .find_keys_by_prefix_in_range(key_prefix, range, reverse, limit)
+                .await
+                .map_err(|e| {
+                    Status::unknown(format!(
+                        "RocksDB error {:?} at find_keys_by_prefix_in_range",
+                        e
+                    ))
+                }),
+        }
+    }
+
+    pub async fn find_key_values_by_prefix_in_range(
+        &self,
+        key_prefix: &[u8],
+        range: (Bound<Vec<u8>>, Bound<Vec<u8>>),
+        reverse: bool,
+        limit: Option<usize>,
+    ) -> Result<Vec<(Vec<u8>, Vec<u8>)>, Status> {
+        match &self.store {
+            ServiceStoreServerInternal::Memory(store) => store
+                .find_key_values_by_prefix_in_range(key_prefix, range, reverse, limit)
+                .await
+                .map_err(|e| {
+                    Status::unknown(format!(
+                        "Memory error {:?} at find_key_values_by_prefix_in_range",
+                        e
+                    ))
+                }),
+            #[cfg(with_rocksdb)]
+            ServiceStoreServerInternal::RocksDb(store) => store
+                .find_key_values_by_prefix_in_range(key_prefix, range, reverse, limit)
+                .await
+                .map_err(|e| {
+                    Status::unknown(format!(
+                        "RocksDB error {:?} at find_key_values_by_prefix_in_range",
+                        e
+                    ))
+                }),
+        }
+    }
+
     pub async fn write_batch(&self, batch: Batch) -> Result<(), Status> {
         match &self.store {
             ServiceStoreServerInternal::Memory(store) => store
@@ -418,6 +493,88 @@ impl StoreProcessor for ServiceStoreServer {
         Ok(Response::new(response))
     }
 
+    #[instrument(target = "store_server", skip_all, err, fields(key_prefix_len = ?request.get_ref().key_prefix.len()))]
+    async fn process_find_keys_by_prefix_in_range(
+        &self,
+        request: Request<RequestFindKeysByPrefixInRange>,
+    ) -> Result<Response<ReplyFindKeysByPrefix>, Status> {
+        let request = request.into_inner();
+        let RequestFindKeysByPrefixInRange {
+            key_prefix,
+            lower,
+            upper,
+            reverse,
+            limit,
+        } = request;
+        let range = (from_key_bound(lower)?, from_key_bound(upper)?);
+        let limit = limit.map(|limit| limit as usize);
+        let keys = self
+            .find_keys_by_prefix_in_range(&key_prefix, range, reverse, limit)
+            .await?;
+        let size = keys.iter().map(|x| x.len()).sum::<usize>();
+        let response = if size < MAX_PAYLOAD_SIZE {
+            ReplyFindKeysByPrefix {
+                keys,
+                message_index: 0,
+                num_chunks: 0,
+            }
+        } else {
+            let (message_index, num_chunks) = self.insert_pending_read(keys).await;
+            ReplyFindKeysByPrefix {
+                keys: Vec::default(),
+                message_index,
+                num_chunks,
+            }
+        };
+        Ok(Response::new(response))
+    }
+
+    #[instrument(target = "store_server", skip_all, err, fields(key_prefix_len = ?request.get_ref().key_prefix.len()))]
+    async fn process_find_key_values_by_prefix_in_range(
+        &self,
+        request: Request<RequestFindKeyValuesByPrefixInRange>,
+    ) -> Result<Response<ReplyFindKeyValuesByPrefix>, Status> {
+        let request = request.into_inner();
+        let RequestFindKeyValuesByPrefixInRange {
+            key_prefix,
+            lower,
+            upper,
+            reverse,
+            limit,
+        } = request;
+        let range = (from_key_bound(lower)?, from_key_bound(upper)?);
+        let limit = limit.map(|limit| limit as usize);
+        let key_values = self
+            .find_key_values_by_prefix_in_range(&key_prefix, range, reverse, limit)
+            .await?;
+        let size = key_values
+            .iter()
+            .map(|x| x.0.len() + x.1.len())
+            .sum::<usize>();
+        let response = if size < MAX_PAYLOAD_SIZE {
+            let key_values = key_values
+                .into_iter()
+                .map(|x| KeyValue {
+                    key: x.0,
+                    value: x.1,
+                })
+                .collect::<Vec<_>>();
+            ReplyFindKeyValuesByPrefix {
+                key_values,
+                message_index: 0,
+                num_chunks: 0,
+            }
+        } else {
+            let (message_index, num_chunks) = self.insert_pending_read(key_values).await;
+            ReplyFindKeyValuesByPrefix {
+                key_values: Vec::default(),
+                message_index,
+                num_chunks,
+            }
+        };
+        Ok(Response::new(response))
+    }
+
     #[instrument(target = "store_server", skip_all, err, fields(n_statements = ?request.get_ref().statements.len()))]
     async fn process_write_batch_extended(
         &self,
@@ -1,7 +1,11 @@
 // Copyright (c) Zefchain Labs, Inc.
 // SPDX-License-Identifier: Apache-2.0
 
-use std::{fmt::Debug, sync::Arc};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt::Debug,
+    sync::Arc,
+};
 
 use async_trait::async_trait;
 use dashmap::DashMap;
@@ -10,7 +14,7 @@ use linera_base::prometheus_util::MeasureLatency as _;
 use linera_base::{
     crypto::CryptoHash,
     data_types::{Blob, Epoch, NetworkDescription, TimeDelta, Timestamp},
-    identifiers::{ApplicationId, BlobId, ChainId, EventId, IndexAndEvent, StreamId},
+    identifiers::{BlobId, BlobType, ChainId, EventId, IndexAndEvent, ModuleId, StreamId},
 };
 use linera_chain::{
     types::{CertificateValue, ConfirmedBlock, ConfirmedBlockCertificate, LiteCertificate},
@@ -23,7 +27,7 @@ use linera_views::{
     backends::dual::{DualStoreRootKeyAssignment, StoreInUse},
     batch::Batch,
     context::ViewContext,
-    store::{AdminKeyValueStore, KeyIterable as _, KeyValueStore},
+    store::{AdminKeyValueStore, KeyIterable as _, KeyValueIterable as _, KeyValueStore},
     views::View,
     ViewError,
 };
@@ -32,7 +36,7 @@ use serde::{Deserialize, Serialize};
 use {
     futures::channel::oneshot::{self, Receiver},
     linera_views::{random::generate_test_namespace, store::TestKeyValueStore},
-    std::{cmp::Reverse, collections::BTreeMap},
+    std::cmp::Reverse,
 };
 
 use crate::{ChainRuntimeContext, Clock, Storage};
@@ -132,6 +136,37 @@ pub mod metrics {
         )
     });
 
+    /// The metric counting the unreferenced blobs deleted by blob collection.
+    #[doc(hidden)]
+    pub static COLLECTED_BLOB_COUNTER: LazyLock<IntCounterVec> = LazyLock::new(|| {
+        register_int_counter_vec(
+            "collected_blob",
+            "The metric counting the unreferenced blobs deleted by blob collection",
+            &[],
+        )
+    });
+
+    /// The metric counting the bytes of the unreferenced blobs deleted by blob collection.
+    #[doc(hidden)]
+    pub static COLLECTED_BLOB_BYTES: LazyLock<IntCounterVec> = LazyLock::new(|| {
+        register_int_counter_vec(
+            "collected_blob_bytes",
+            "The metric counting the bytes of the unreferenced blobs deleted by blob collection",
+            &[],
+        )
+    });
+
+    /// The latency of a blob collection.
+    #[doc(hidden)]
+    pub static COLLECT_BLOBS_LATENCY: LazyLock<HistogramVec> = LazyLock::new(|| {
+        register_histogram_vec(
+            "collect_blobs_latency",
+            "The latency of a blob collection",
+            &[],
+            exponential_bucket_latencies(600_000.0),
+        )
+    });
+
     /// The metric counting how often a certificate is read from storage.
     #[doc(hidden)]
     pub static READ_CERTIFICATE_COUNTER: LazyLock<IntCounterVec> = LazyLock::new(|| {
@@ -162,6 +197,16 @@ pub mod metrics {
         )
     });
 
+    /// The metric counting how often a certificate is deleted from storage.
+    #[doc(hidden)]
+    pub static DELETE_CERTIFICATE_COUNTER: LazyLock<IntCounterVec> = LazyLock::new(|| {
+        register_int_counter_vec(
+            "delete_certificate",
+            "The metric counting how often a certificate is deleted from storage",
+            &[],
+        )
+    });
+
     /// The latency to load a chain state.
     #[doc(hidden)]
     pub static LOAD_CHAIN_LATENCY: LazyLock<HistogramVec> = LazyLock::new(|| {
@@ -292,14 +337,51 @@ impl BatchExt for Batch {
     }
 }
 
+/// How long unreferenced blobs are kept before [`DbStorage::collect_blobs`] deletes them.
+#[derive(Clone, Copy, Debug)]
+pub struct BlobCollectionPolicy {
+    /// How long a blob must have been unreferenced before it is deleted.
+    pub grace_period: TimeDelta,
+    /// The grace period of data blobs, e.g. the ones of abandoned block proposals.
+    pub data_grace_period: TimeDelta,
+}
+
+impl BlobCollectionPolicy {
+    fn grace_period(&self, blob_type: BlobType) -> TimeDelta {
+        match blob_type {
+            BlobType::Data => self.data_grace_period,
+            _ => self.grace_period,
+        }
+    }
+}
+
+/// The outcome of [`DbStorage::collect_blobs`].
+#[derive(Clone, Debug, Default)]
+pub struct BlobCollectionReport {
+    /// The number of blobs in storage.
+    pub num_blobs: usize,
+    /// The number of blobs that are referenced, or that are always kept.
+    pub num_kept: usize,
+    /// The unreferenced blobs that are still within their grace period, with the time since
+    /// when they are known to be unreferenced.
+    pub pending: Vec<(BlobId, Timestamp)>,
+    /// The blobs that were deleted, or would be deleted in a dry run.
+    pub deleted: Vec<BlobId>,
+    /// The total size of the deleted blobs, in bytes.
+    pub deleted_bytes: u64,
+}
+
+/// The number of confirmed blocks read at once when looking for referenced blobs.
+const BLOCKS_PER_READ: usize = 100;
+
 /// Main implementation of the [`Storage`] trait.
 #[derive(Clone)]
 pub struct DbStorage<Store, Clock = WallClock> {
     store: Arc<Store>,
     clock: Clock,
     wasm_runtime: Option<WasmRuntime>,
-    user_contracts: Arc<DashMap<ApplicationId, UserContractCode>>,
-    user_services: Arc<DashMap<ApplicationId, UserServiceCode>>,
+    user_contracts: Arc<DashMap<ModuleId, UserContractCode>>,
+    user_services: Arc<DashMap<ModuleId, UserServiceCode>>,
     execution_runtime_config: ExecutionRuntimeConfig,
 }
 
@@ -313,11 +395,16 @@ enum BaseKey {
     Event(EventId),
     BlockExporterState(u32),
     NetworkDescription,
+    BlobCollectionCandidate(BlobId),
+    StagedChainState(ChainId),
+    ChainSnapshot(ChainId),
 }
 
 const INDEX_CHAIN_ID: u8 = 0;
+const INDEX_CERTIFICATE: u8 = 1;
 const INDEX_BLOB_ID: u8 = 3;
 const INDEX_EVENT_ID: u8 = 5;
+const INDEX_BLOB_COLLECTION_CANDIDATE: u8 = 8;
 const CHAIN_ID_LENGTH: usize = std::mem::size_of::<ChainId>();
 const BLOB_ID_LENGTH: usize = std::mem::size_of::<BlobId>();
 
@@ -325,14 +412,27 @@ const BLOB_ID_LENGTH: usize = std::mem::size_of::<BlobId>();
 mod tests {
     use linera_base::{
         crypto::CryptoHash,
+        data_types::{Blob, CompressedBytecode, Round, TimeDelta},
         identifiers::{
             ApplicationId, BlobId, BlobType, ChainId, EventId, GenericApplicationId, StreamId,
             StreamName,
         },
     };
-
-    use crate::db_storage::{
-        BaseKey, BLOB_ID_LENGTH, CHAIN_ID_LENGTH, INDEX_BLOB_ID, INDEX_CHAIN_ID, INDEX_EVENT_ID,
+    use linera_chain::{
+        data_types::BlockExecutionOutcome,
+        test::{make_first_block, BlockTestExt as _},
+        types::{ConfirmedBlock, ConfirmedBlockCertificate},
+    };
+    use linera_execution::{system::SystemOperation, Operation};
+    use linera_views::{memory::MemoryStore, views::RootView as _};
+
+    use crate::{
+        db_storage::{
+            BaseKey, BlobCollectionPolicy, BLOB_ID_LENGTH, CHAIN_ID_LENGTH,
+            INDEX_BLOB_COLLECTION_CANDIDATE, INDEX_BLOB_ID, INDEX_CERTIFICATE, INDEX_CHAIN_ID,
+            INDEX_EVENT_ID,
+        },
+        Clock as _, DbStorage, Storage as _, TestClock,
     };
 
     // Several functionalities of the storage rely on the way that the serialization
@@ -352,6 +452,33 @@ mod tests {
         assert_eq!(key.len(), 1 + BLOB_ID_LENGTH);
     }
 
+    // The listing of the certificates in `collect_blobs` depends on the serialization
+    // of `BaseKey::Certificate`.
+    #[test]
+    fn test_basekey_certificate_serialization() {
+        let hash = CryptoHash::test_hash("certificate");
+        let base_key = BaseKey::Certificate(hash);
+        let key = bcs::to_bytes(&base_key).expect("a key");
+        assert_eq!(key[0], INDEX_CERTIFICATE);
+        assert_eq!(
+            bcs::from_bytes::<CryptoHash>(&key[1..]).expect("a hash"),
+            hash
+        );
+    }
+
+    // The listing of the blob collection candidates in `collect_blobs` depends on the
+    // serialization of `BaseKey::BlobCollectionCandidate`.
+    #[test]
+    fn test_basekey_blob_collection_candidate_serialization() {
+        let hash = CryptoHash::default();
+        let blob_type = BlobType::default();
+        let blob_id = BlobId::new(hash, blob_type);
+        let base_key = BaseKey::BlobCollectionCandidate(blob_id);
+        let key = bcs::to_bytes(&base_key).expect("a key");
+        assert_eq!(key[0], INDEX_BLOB_COLLECTION_CANDIDATE);
+        assert_eq!(key.len(), 1 + BLOB_ID_LENGTH);
+    }
+
     // The listing of the chains in `list_chain_ids` depends on the serialization
     // of `BaseKey::ChainState`.
     #[test]
@@ -392,6 +519,117 @@ mod tests {
         let key = bcs::to_bytes(&base_key).unwrap();
         assert!(key.starts_with(&prefix));
     }
+
+    // Blobs are only deleted once they have been unreferenced for their grace period, by
+    // chain states and certificates alike, and never in a dry run.
+    #[tokio::test]
+    async fn test_collect_blobs() -> anyhow::Result<()> {
+        let storage = DbStorage::<MemoryStore, TestClock>::make_test_storage(None).await;
+        let clock = storage.clock().clone();
+        let policy = BlobCollectionPolicy {
+            grace_period: TimeDelta::from_secs(100),
+            data_grace_period: TimeDelta::from_secs(10),
+        };
+        let chain_id = ChainId(CryptoHash::test_hash("chain"));
+        let used = Blob::new_data(b"used".to_vec());
+        let published = Blob::new_data(b"published".to_vec());
+        let unreferenced = Blob::new_data(b"unreferenced".to_vec());
+        let committee = Blob::new_committee(b"committee".to_vec());
+        let bytecode = Blob::new_contract_bytecode(CompressedBytecode {
+            compressed_bytes: b"bytecode".to_vec(),
+        });
+        storage
+            .write_blobs(&[
+                used.clone(),
+                published.clone(),
+                unreferenced.clone(),
+                committee.clone(),
+                bytecode.clone(),
+            ])
+            .await?;
+
+        let mut chain = storage.load_chain(chain_id).await?;
+        chain.execution_state.system.used_blobs.insert(&used.id())?;
+        chain.save().await?;
+        let block =
+            BlockExecutionOutcome::default().with(make_first_block(chain_id).with_operation(
+                Operation::system(SystemOperation::PublishDataBlob {
+                    blob_hash: published.id().hash,
+                }),
+            ));
+        let certificate =
+            ConfirmedBlockCertificate::new(ConfirmedBlock::new(block), Round::Fast, Vec::new());
+        storage
+            .write_blobs_and_certificate(&[], &certificate)
+            .await?;
+
+        // The first run only records when the unreferenced blobs were first found.
+        let start = clock.current_time();
+        let report = storage.collect_blobs(&[chain_id], &policy, false).await?;
+        assert_eq!(report.num_blobs, 5);
+        assert_eq!(report.num_kept, 3);
+        let mut pending = report.pending;
+        pending.sort();
+        let mut expected = vec![(unreferenced.id(), start), (bytecode.id(), start)];
+        expected.sort();
+        assert_eq!(pending, expected);
+        assert!(report.deleted.is_empty());
+
+        // A dry run lists the data blob that is past its grace period, but keeps it.
+        clock.add(TimeDelta::from_secs(10));
+        let report = storage.collect_blobs(&[chain_id], &policy, true).await?;
+        assert_eq!(report.deleted, vec![unreferenced.id()]);
+        assert_eq!(report.deleted_bytes, unreferenced.bytes().len() as u64);
+        assert_eq!(report.pending, vec![(bytecode.id(), start)]);
+        assert!(storage.contains_blob(unreferenced.id()).await?);
+
+        // A blob that is referenced again is no longer a candidate.
+        let mut chain = storage.load_chain(chain_id).await?;
+        chain
+            .execution_state
+            .system
+            .used_blobs
+            .insert(&bytecode.id())?;
+        chain.save().await?;
+        let report = storage.collect_blobs(&[chain_id], &policy, false).await?;
+        assert_eq!(report.deleted, vec![unreferenced.id()]);
+        assert!(report.pending.is_empty());
+        assert_eq!(report.num_kept, 4);
+        assert!(!storage.contains_blob(unreferenced.id()).await?);
+
+        // Once it is unreferenced again, its grace period starts over.
+        let mut chain = storage.load_chain(chain_id).await?;
+        chain
+            .execution_state
+            .system
+            .used_blobs
+            .remove(&bytecode.id())?;
+        chain.save().await?;
+        storage.delete_certificates(&[certificate.hash()]).await?;
+        clock.add(TimeDelta::from_secs(100));
+        let restart = clock.current_time();
+        let report = storage.collect_blobs(&[chain_id], &policy, false).await?;
+        let mut pending = report.pending;
+        pending.sort();
+        let mut expected = vec![(published.id(), restart), (bytecode.id(), restart)];
+        expected.sort();
+        assert_eq!(pending, expected);
+        assert!(report.deleted.is_empty());
+
+        clock.add(TimeDelta::from_secs(10));
+        let report = storage.collect_blobs(&[chain_id], &policy, false).await?;
+        assert_eq!(report.deleted, vec![published.id()]);
+        assert_eq!(report.pending, vec![(bytecode.id(), restart)]);
+
+        clock.add(TimeDelta::from_secs(90));
+        let report = storage.collect_blobs(&[chain_id], &policy, false).await?;
+        assert_eq!(report.deleted, vec![bytecode.id()]);
+        assert!(report.pending.is_empty());
+        assert_eq!(report.num_blobs, 2);
+        assert!(storage.contains_blob(used.id()).await?);
+        assert!(storage.contains_blob(committee.id()).await?);
+        Ok(())
+    }
 }
 
 /// An implementation of [`DualStoreRootKeyAssignment`] that stores the
@@ -405,7 +643,9 @@ impl DualStoreRootKeyAssignment for ChainStatesFirstAssignment {
             return Ok(StoreInUse::Second);
         }
         let store = match bcs::from_bytes(root_key)? {
-            BaseKey::ChainState(_) => StoreInUse::First,
+            BaseKey::ChainState(_) | BaseKey::StagedChainState(_) | BaseKey::ChainSnapshot(_) => {
+                StoreInUse::First
+            }
             _ => StoreInUse::Second,
         };
         Ok(store)
@@ -546,17 +786,21 @@ where
     ) -> Result<ChainStateView<Self::Context>, ViewError> {
         #[cfg(with_metrics)]
         let _metric = metrics::LOAD_CHAIN_LATENCY.measure_latency();
-        let runtime_context = ChainRuntimeContext {
-            storage: self.clone(),
-            chain_id,
-            execution_runtime_config: self.execution_runtime_config,
-            user_contracts: self.user_contracts.clone(),
-            user_services: self.user_services.clone(),
-        };
-        let root_key = bcs::to_bytes(&BaseKey::ChainState(chain_id))?;
-        let store = self.store.clone_with_root_key(&root_key)?;
-        let context = ViewContext::create_root_context(store, runtime_context).await?;
-        ChainStateView::load(context).await
+        self.load_chain_with_base_key(chain_id, BaseKey::ChainState(chain_id))
+            .await
+    }
+
+    async fn load_staged_chain(
+        &self,
+        chain_id: ChainId,
+    ) -> Result<ChainStateView<Self::Context>, ViewError> {
+        self.load_chain_with_base_key(chain_id, BaseKey::StagedChainState(chain_id))
+            .await
+    }
+
+    fn chain_snapshot_store(&self, chain_id: ChainId) -> Result<Store, ViewError> {
+        let root_key = bcs::to_bytes(&BaseKey::ChainSnapshot(chain_id))?;
+        Ok(self.store.clone_with_root_key(&root_key)?)
     }
 
     async fn contains_blob(&self, blob_id: BlobId) -> Result<bool, ViewError> {
@@ -809,6 +1053,21 @@ where
         self.write_batch(batch).await
     }
 
+    async fn delete_certificates(&self, hashes: &[CryptoHash]) -> Result<(), ViewError> {
+        if hashes.is_empty() {
+            return Ok(());
+        }
+        let mut batch = Batch::new();
+        for key in Self::get_keys_for_certificates(hashes)? {
+            batch.delete_key(key);
+        }
+        #[cfg(with_metrics)]
+        metrics::DELETE_CERTIFICATE_COUNTER
+            .with_label_values(&[])
+            .inc_by(hashes.len() as u64);
+        self.write_batch(batch).await
+    }
+
     async fn contains_certificate(&self, hash: CryptoHash) -> Result<bool, ViewError> {
         let keys = Self::get_keys_for_certificates(&[hash])?;
         let results = self.store.contains_keys(keys).await?;
@@ -941,6 +1200,11 @@ where
         self.wasm_runtime
     }
 
+    fn with_execution_runtime_config(mut self, config: ExecutionRuntimeConfig) -> Self {
+        self.execution_runtime_config = config;
+        self
+    }
+
     async fn block_exporter_context(
         &self,
         block_exporter_id: u32,
@@ -1004,6 +1268,189 @@ where
     }
 }
 
+impl<Store, C> DbStorage<Store, C>
+where
+    Store: KeyValueStore + Clone + Send + Sync + 'static,
+    C: Clock + Clone + Send + Sync + 'static,
+    Store::Error: Send + Sync,
+{
+    async fn load_chain_with_base_key(
+        &self,
+        chain_id: ChainId,
+        base_key: BaseKey,
+    ) -> Result<ChainStateView<<Self as Storage>::Context>, ViewError> {
+        let runtime_context = ChainRuntimeContext {
+            storage: self.clone(),
+            chain_id,
+            execution_runtime_config: self.execution_runtime_config,
+            user_contracts: self.user_contracts.clone(),
+            user_services: self.user_services.clone(),
+        };
+        let root_key = bcs::to_bytes(&base_key)?;
+        let store = self.store.clone_with_root_key(&root_key)?;
+        let context = ViewContext::create_root_context(store, runtime_context).await?;
+        ChainStateView::load(context).await
+    }
+
+    /// Deletes the blobs that are referenced neither by the state of one of the `chain_ids` nor
+    /// by a certificate in storage, once they have been unreferenced for their grace period.
+    ///
+    /// The `chain_ids` must include every chain in storage. A blob is deleted by the first run
+    /// after its grace period, measured from the run that first found it unreferenced, so
+    /// that the blobs of blocks in progress are kept. Chain descriptions and committees are
+    /// never deleted. In a dry run, nothing is written, and the report lists the blobs that
+    /// would be deleted.
+    pub async fn collect_blobs(
+        &self,
+        chain_ids: &[ChainId],
+        policy: &BlobCollectionPolicy,
+        dry_run: bool,
+    ) -> Result<BlobCollectionReport, ViewError> {
+        #[cfg(with_metrics)]
+        let _metric = metrics::COLLECT_BLOBS_LATENCY.measure_latency();
+        let now = self.clock.current_time();
+        let referenced = self.referenced_blob_ids(chain_ids).await?;
+        let blob_ids = find_blob_ids(&*self.store).await?;
+        let mut candidates = BTreeMap::new();
+        let key_values = self
+            .store
+            .find_key_values_by_prefix(&[INDEX_BLOB_COLLECTION_CANDIDATE])
+            .await?;
+        for key_value in key_values.iterator() {
+            let (key, value) = key_value?;
+            let blob_id = bcs::from_bytes::<BlobId>(key)?;
+            candidates.insert(blob_id, bcs::from_bytes::<Timestamp>(value)?);
+        }
+
+        let mut report = BlobCollectionReport {
+            num_blobs: blob_ids.len(),
+            ..BlobCollectionReport::default()
+        };
+        let mut batch = Batch::new();
+        for blob_id in blob_ids {
+            let candidate_key = bcs::to_bytes(&BaseKey::BlobCollectionCandidate(blob_id))?;
+            let since = candidates.remove(&blob_id);
+            if referenced.contains(&blob_id)
+                || matches!(
+                    blob_id.blob_type,
+                    BlobType::ChainDescription | BlobType::Committee
+                )
+            {
+                report.num_kept += 1;
+                if since.is_some() {
+                    batch.delete_key(candidate_key);
+                }
+                continue;
+            }
+            match since {
+                Some(since) if now.delta_since(since) >= policy.grace_period(blob_id.blob_type) => {
+                    batch.delete_key(candidate_key);
+                    batch.delete_key(bcs::to_bytes(&BaseKey::Blob(blob_id))?);
+                    batch.delete_key(bcs::to_bytes(&BaseKey::BlobState(blob_id))?);
+                    report.deleted.push(blob_id);
+                }
+                Some(since) => report.pending.push((blob_id, since)),
+                None => {
+                    batch.put_key_value(candidate_key, &now)?;
+                    report.pending.push((blob_id, now));
+                }
+            }
+        }
+        // The remaining candidates are blobs that were deleted in the meantime.
+        for blob_id in candidates.into_keys() {
+            batch.delete_key(bcs::to_bytes(&BaseKey::BlobCollectionCandidate(blob_id))?);
+        }
+
+        let blob_keys = report
+            .deleted
+            .iter()
+            .map(|blob_id| bcs::to_bytes(&BaseKey::Blob(*blob_id)))
+            .collect::<Result<_, _>>()?;
+        report.deleted_bytes = self
+            .store
+            .read_multi_values_bytes(blob_keys)
+            .await?
+            .into_iter()
+            .flatten()
+            .map(|bytes| bytes.len() as u64)
+            .sum();
+        if !dry_run {
+            self.write_batch(batch).await?;
+            #[cfg(with_metrics)]
+            {
+                metrics::COLLECTED_BLOB_COUNTER
+                    .with_label_values(&[])
+                    .inc_by(report.deleted.len() as u64);
+                metrics::COLLECTED_BLOB_BYTES
+                    .with_label_values(&[])
+                    .inc_by(report.deleted_bytes);
+            }
+        }
+        Ok(report)
+    }
+
+    /// Returns the blobs used by the states of the given chains, including pending blocks, and
+    /// the blobs required by the confirmed blocks in storage.
+    async fn referenced_blob_ids(
+        &self,
+        chain_ids: &[ChainId],
+    ) -> Result<BTreeSet<BlobId>, ViewError> {
+        let mut referenced = BTreeSet::new();
+        for chain_id in chain_ids {
+            let chain = self.load_chain(*chain_id).await?;
+            referenced.extend(chain.execution_state.system.used_blobs.indices().await?);
+            referenced.extend(chain.manager.proposed_blobs.indices().await?);
+            referenced.extend(chain.manager.locking_blobs.indices().await?);
+            referenced.extend(
+                chain
+                    .pending_validated_blobs
+                    .pending_blobs
+                    .indices()
+                    .await?,
+            );
+            for (_, pending_blobs) in chain.pending_proposed_blobs.try_load_all_entries().await? {
+                referenced.extend(pending_blobs.pending_blobs.indices().await?);
+            }
+        }
+        let mut hashes = Vec::new();
+        let keys = self.store.find_keys_by_prefix(&[INDEX_CERTIFICATE]).await?;
+        for key in keys.iterator() {
+            hashes.push(bcs::from_bytes::<CryptoHash>(key?)?);
+        }
+        for hashes in hashes.chunks(BLOCKS_PER_READ) {
+            let block_keys = hashes
+                .iter()
+                .map(|hash| bcs::to_bytes(&BaseKey::ConfirmedBlock(*hash)))
+                .collect::<Result<_, _>>()?;
+            for bytes in self
+                .store
+                .read_multi_values_bytes(block_keys)
+                .await?
+                .into_iter()
+                .flatten()
+            {
+                let block = bcs::from_bytes::<ConfirmedBlock>(&bytes)?;
+                referenced.extend(block.block().required_blob_ids());
+            }
+        }
+        Ok(referenced)
+    }
+}
+
+/// Returns the IDs of the blobs in the store.
+async fn find_blob_ids<Store: KeyValueStore>(store: &Store) -> Result<Vec<BlobId>, ViewError> {
+    let prefix = &[INDEX_BLOB_ID];
+    let keys = store.find_keys_by_prefix(prefix).await?;
+    let mut blob_ids = Vec::new();
+    for key in keys.iterator() {
+        let key = key?;
+        let key_red = &key[..BLOB_ID_LENGTH];
+        let blob_id = bcs::from_bytes(key_red)?;
+        blob_ids.push(blob_id);
+    }
+    Ok(blob_ids)
+}
+
 impl<Store> DbStorage<Store, WallClock>
 where
     Store: KeyValueStore + Clone + Send + Sync + 'static,
@@ -1033,16 +1480,7 @@ where
         namespace: &str,
     ) -> Result<Vec<BlobId>, ViewError> {
         let store = Store::maybe_create_and_connect(config, namespace).await?;
-        let prefix = &[INDEX_BLOB_ID];
-        let keys = store.find_keys_by_prefix(prefix).await?;
-        let mut blob_ids = Vec::new();
-        for key in keys.iterator() {
-            let key = key?;
-            let key_red = &key[..BLOB_ID_LENGTH];
-            let blob_id = bcs::from_bytes(key_red)?;
-            blob_ids.push(blob_id);
-        }
-        Ok(blob_ids)
+        find_blob_ids(&store).await
     }
 }
 
@@ -14,10 +14,12 @@ use dashmap::{mapref::entry::Entry, DashMap};
 use linera_base::{
     crypto::CryptoHash,
     data_types::{
-        ApplicationDescription, Blob, ChainDescription, CompressedBytecode, Epoch,
+        ApplicationDescription, Blob, BlockHeight, ChainDescription, CompressedBytecode, Epoch,
         NetworkDescription, TimeDelta, Timestamp,
     },
-    identifiers::{ApplicationId, BlobId, ChainId, EventId, IndexAndEvent, StreamId},
+    ensure,
+    identifiers::{ApplicationId, BlobId, ChainId, EventId, IndexAndEvent, ModuleId, StreamId},
+    time::Instant,
     vm::VmRuntime,
 };
 use linera_chain::{
@@ -30,8 +32,8 @@ use linera_execution::{
     EvmRuntime,
 };
 use linera_execution::{
-    BlobState, ExecutionError, ExecutionRuntimeConfig, ExecutionRuntimeContext, UserContractCode,
-    UserServiceCode, WasmRuntime,
+    BlobState, ExecutionError, ExecutionRuntimeConfig, ExecutionRuntimeContext, QueryContext,
+    UserContractCode, UserServiceCode, WasmRuntime,
 };
 #[cfg(with_wasm_runtime)]
 use linera_execution::{WasmContractModule, WasmServiceModule};
@@ -41,7 +43,9 @@ use linera_views::{context::Context, views::RootView, ViewError};
 pub use crate::db_storage::metrics;
 #[cfg(with_testing)]
 pub use crate::db_storage::TestClock;
-pub use crate::db_storage::{ChainStatesFirstAssignment, DbStorage, WallClock};
+pub use crate::db_storage::{
+    BlobCollectionPolicy, BlobCollectionReport, ChainStatesFirstAssignment, DbStorage, WallClock,
+};
 
 /// The default namespace to be used when none is specified
 pub const DEFAULT_NAMESPACE: &str = "table_linera";
@@ -71,6 +75,20 @@ pub trait Storage: Sized {
     /// storage. This can lead to invalid states and data corruption.
     async fn load_chain(&self, id: ChainId) -> Result<ChainStateView<Self::Context>, ViewError>;
 
+    /// Loads the view of a chain state staged under a separate root key, e.g. while a chain
+    /// snapshot is verified. [`Storage::load_chain`] does not see it until it is copied over.
+    async fn load_staged_chain(
+        &self,
+        id: ChainId,
+    ) -> Result<ChainStateView<Self::Context>, ViewError>;
+
+    /// Returns the store under which the latest snapshot of a chain's state is written for
+    /// other nodes to download. It is separate from the chain's state.
+    fn chain_snapshot_store(
+        &self,
+        id: ChainId,
+    ) -> Result<<Self::Context as Context>::Store, ViewError>;
+
     /// Tests the existence of a blob with the given blob ID.
     async fn contains_blob(&self, blob_id: BlobId) -> Result<bool, ViewError>;
 
@@ -144,6 +162,9 @@ pub trait Storage: Sized {
     /// Writes several blobs.
     async fn write_blobs(&self, blobs: &[Blob]) -> Result<(), ViewError>;
 
+    /// Deletes the certificates with the given hashes, together with their confirmed blocks.
+    async fn delete_certificates(&self, hashes: &[CryptoHash]) -> Result<(), ViewError>;
+
     /// Tests existence of the certificate with the given hash.
     async fn contains_certificate(&self, hash: CryptoHash) -> Result<bool, ViewError>;
 
@@ -214,6 +235,10 @@ pub trait Storage: Sized {
     /// Selects the WebAssembly runtime to use for applications (if any).
     fn wasm_runtime(&self) -> Option<WasmRuntime>;
 
+    /// Returns this storage with the given configuration of the execution runtime of the
+    /// chains it loads.
+    fn with_execution_runtime_config(self, config: ExecutionRuntimeConfig) -> Self;
+
     /// Creates a [`UserContractCode`] instance using the bytecode in storage referenced
     /// by the `application_description`.
     async fn load_contract(
@@ -343,8 +368,8 @@ pub struct ChainRuntimeContext<S> {
     storage: S,
     chain_id: ChainId,
     execution_runtime_config: ExecutionRuntimeConfig,
-    user_contracts: Arc<DashMap<ApplicationId, UserContractCode>>,
-    user_services: Arc<DashMap<ApplicationId, UserServiceCode>>,
+    user_contracts: Arc<DashMap<ModuleId, UserContractCode>>,
+    user_services: Arc<DashMap<ModuleId, UserServiceCode>>,
 }
 
 #[cfg_attr(not(web), async_trait)]
@@ -361,11 +386,11 @@ where
         self.execution_runtime_config
     }
 
-    fn user_contracts(&self) -> &Arc<DashMap<ApplicationId, UserContractCode>> {
+    fn user_contracts(&self) -> &Arc<DashMap<ModuleId, UserContractCode>> {
         &self.user_contracts
     }
 
-    fn user_services(&self) -> &Arc<DashMap<ApplicationId, UserServiceCode>> {
+    fn user_services(&self) -> &Arc<DashMap<ModuleId, UserServiceCode>> {
         &self.user_services
     }
 
@@ -373,7 +398,7 @@ where
         &self,
         description: &ApplicationDescription,
     ) -> Result<UserContractCode, ExecutionError> {
-        match self.user_contracts.entry(description.into()) {
+        match self.user_contracts.entry(description.module_id) {
             Entry::Occupied(entry) => Ok(entry.get().clone()),
             Entry::Vacant(entry) => {
                 let contract = self.storage.load_contract(description).await?;
@@ -387,7 +412,7 @@ where
         &self,
         description: &ApplicationDescription,
     ) -> Result<UserServiceCode, ExecutionError> {
-        match self.user_services.entry(description.into()) {
+        match self.user_services.entry(description.module_id) {
             Entry::Occupied(entry) => Ok(entry.get().clone()),
             Entry::Vacant(entry) => {
                 let service = self.storage.load_service(description).await?;
@@ -417,6 +442,50 @@ where
         self.storage.contains_event(event_id).await
     }
 
+    async fn query_remote_application(
+        &self,
+        chain_id: ChainId,
+        height: BlockHeight,
+        application_id: ApplicationId,
+        query: Vec<u8>,
+        local_time: Timestamp,
+        deadline: Option<Instant>,
+    ) -> Result<Vec<u8>, ExecutionError> {
+        // This reads the state last saved by the chain's worker, possibly on another shard.
+        let mut chain = self.storage.load_chain(chain_id).await?;
+        let next_block_height = height.try_add_one()?;
+        let tip_height = chain.tip_state.get().next_block_height;
+        let state_history = chain.state_history_window();
+        // If the chain has advanced past `height`, the application state at that height is
+        // read from the state history, if this node still keeps it.
+        let at_height = if tip_height == next_block_height {
+            None
+        } else {
+            ensure!(
+                state_history.is_some_and(|window| window.contains(height.0)),
+                ExecutionError::RemoteChainStateUnavailable { chain_id, height }
+            );
+            Some(height)
+        };
+        let context = QueryContext {
+            chain_id,
+            next_block_height,
+            local_time,
+        };
+        let outcome = chain
+            .execution_state
+            .query_user_application(
+                application_id,
+                context,
+                query,
+                at_height,
+                state_history,
+                deadline,
+            )
+            .await?;
+        Ok(outcome.response)
+    }
+
     #[cfg(with_testing)]
     async fn add_blobs(
         &self,
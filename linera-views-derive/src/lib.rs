@@ -12,6 +12,112 @@ use syn::{parse_macro_input, parse_quote, ItemStruct, Type};
 #[deluxe(attributes(view))]
 struct StructAttrs {
     context: Option<syn::Type>,
+    /// The version of the layout of the fields, stored with the view.
+    version: Option<u32>,
+    /// The function migrating views stored with a previous layout version. The migrated
+    /// views are saved when loaded, and the fields of the previous layouts deleted.
+    migrate: Option<syn::Path>,
+    /// The file, relative to the crate, holding the snapshot of the layout of the fields.
+    layout: Option<String>,
+}
+
+#[derive(Debug, deluxe::ParseAttributes)]
+#[deluxe(attributes(view))]
+struct FieldAttrs {
+    /// The index under which the field is stored, instead of its position.
+    index: Option<u32>,
+}
+
+/// Returns the indices under which the fields of the view are stored.
+fn get_field_indices(input: &ItemStruct) -> Vec<u32> {
+    let mut indices = Vec::new();
+    for (position, field) in input.fields.iter().enumerate() {
+        let attrs: FieldAttrs = deluxe::parse_attributes(field).unwrap();
+        let index = attrs
+            .index
+            .unwrap_or_else(|| u32::try_from(position).expect("too many fields"));
+        assert!(
+            i32::try_from(index).is_ok(),
+            "the index of a field should fit in an `i32`"
+        );
+        assert!(
+            !indices.contains(&index),
+            "several fields have the index {index}"
+        );
+        indices.push(index);
+    }
+    indices
+}
+
+/// Describes the layout of the fields of a view: its version, then the index, name and
+/// type of each field, by increasing index.
+fn get_layout_snapshot(input: &ItemStruct, indices: &[u32], version: u32) -> String {
+    let mut fields = indices.iter().zip(&input.fields).collect::<Vec<_>>();
+    fields.sort_by_key(|(index, _)| **index);
+    let mut snapshot = format!("version {version}\n");
+    for (index, field) in fields {
+        let name = field.ident.as_ref().unwrap();
+        let ty = &field.ty;
+        snapshot.push_str(&format!("{index} {name}: {}\n", quote! { #ty }));
+    }
+    snapshot
+}
+
+/// Compares the layout snapshot of a view with the stored one. The layout of a view may
+/// only change together with its version.
+fn check_layout_snapshot(
+    path: &str,
+    stored: Option<&str>,
+    expected: &str,
+    version: u32,
+) -> Result<(), String> {
+    let Some(stored) = stored else {
+        return Err(format!(
+            "missing layout snapshot {path}. Its content should be:\n{expected}"
+        ));
+    };
+    if stored == expected {
+        return Ok(());
+    }
+    let stored_version = stored
+        .lines()
+        .next()
+        .and_then(|line| line.strip_prefix("version "))
+        .and_then(|version| version.trim().parse::<u32>().ok());
+    if stored_version == Some(version) {
+        Err(format!(
+            "the layout of the view changed but not its version {version}. Increase the \
+            version, migrate the previous layouts if needed, and update {path} to:\n{expected}"
+        ))
+    } else {
+        Err(format!(
+            "the layout snapshot {path} is outdated. Its content should be:\n{expected}"
+        ))
+    }
+}
+
+/// Generates a compile-time check of the layout of the view against its snapshot file.
+fn generate_layout_check(input: &ItemStruct, indices: &[u32], attrs: &StructAttrs) -> TokenStream2 {
+    let Some(path) = &attrs.layout else {
+        return quote! {};
+    };
+    let version = attrs.version.unwrap_or(0);
+    let expected = get_layout_snapshot(input, indices, version);
+    let manifest_dir = std::env::var("CARGO_MANIFEST_DIR").unwrap_or_default();
+    let full_path = std::path::Path::new(&manifest_dir).join(path);
+    let stored = std::fs::read_to_string(&full_path).ok();
+    // Including the snapshot lets the compiler track changes of the file.
+    let include = match (&stored, full_path.to_str()) {
+        (Some(_), Some(full_path)) => quote! { const _: &[u8] = include_bytes!(#full_path); },
+        _ => quote! {},
+    };
+    match check_layout_snapshot(path, stored.as_deref(), &expected, version) {
+        Ok(()) => include,
+        Err(message) => quote! {
+            #include
+            compile_error!(#message);
+        },
+    }
 }
 
 struct Constraints<'a> {
@@ -55,6 +161,12 @@ fn generate_view_code(input: ItemStruct, root: bool) -> TokenStream2 {
     } = Constraints::get(&input);
 
     let attrs: StructAttrs = deluxe::parse_attributes(&input).unwrap();
+    assert!(
+        attrs.migrate.is_none() || attrs.version.is_some(),
+        "a `migrate` function requires a layout `version`"
+    );
+    let indices = get_field_indices(&input);
+    let layout_check = generate_layout_check(&input, &indices, &attrs);
     let context = attrs.context.unwrap_or_else(|| {
         let ident = &input
             .generics
@@ -77,10 +189,13 @@ fn generate_view_code(input: ItemStruct, root: bool) -> TokenStream2 {
     let mut num_init_keys_quotes = Vec::new();
     let mut pre_load_keys_quotes = Vec::new();
     let mut post_load_keys_quotes = Vec::new();
-    for (idx, e) in input.fields.iter().enumerate() {
+    let mut load_keys_quotes = Vec::new();
+    let mut index_quotes = Vec::new();
+    for (idx, (e, index)) in input.fields.iter().zip(&indices).enumerate() {
         let name = e.ident.clone().unwrap();
         let test_flush_ident = format_ident!("deleted{}", idx);
-        let idx_lit = syn::LitInt::new(&idx.to_string(), Span::call_site());
+        let idx_lit = syn::LitInt::new(&index.to_string(), Span::call_site());
+        index_quotes.push(quote! { #idx_lit });
         let g = get_extended_entry(e.ty.clone());
         name_quotes.push(quote! { #name });
         rollback_quotes.push(quote! { self.#name.rollback(); });
@@ -105,6 +220,13 @@ fn generate_view_code(input: ItemStruct, root: bool) -> TokenStream2 {
             let #name = #g :: post_load(context.clone_with_base_key(base_key), &values[pos..pos_next])?;
             pos = pos_next;
         });
+        load_keys_quotes.push(quote! {
+            let index = #idx_lit;
+            let pos_next = pos + #g :: NUM_INIT_KEYS;
+            let base_key = context.base_key().derive_tag_key(linera_views::views::MIN_VIEW_TAG, &index)?;
+            let #name = linera_views::views::post_load_or_migrate(context.clone_with_base_key(base_key), &values[pos..pos_next]).await?;
+            pos = pos_next;
+        });
     }
 
     let first_name_quote = name_quotes
@@ -127,7 +249,145 @@ fn generate_view_code(input: ItemStruct, root: bool) -> TokenStream2 {
         quote! {}
     };
 
+    if let Some(version) = attrs.version {
+        let migrate = attrs.migrate.map(|migrate| {
+            quote! {
+                #migrate(&mut view, &context, version).await?;
+            }
+        });
+        return quote! {
+            #layout_check
+
+            impl #impl_generics linera_views::views::View for #struct_name #type_generics
+            where
+                #context: linera_views::context::Context,
+                #(#input_constraints,)*
+                #(#field_types: linera_views::views::View<Context = #context>,)*
+            {
+                const NUM_INIT_KEYS: usize = 1 #(+ <#field_types as linera_views::views::View>::NUM_INIT_KEYS)*;
+
+                type Context = #context;
+
+                fn context(&self) -> &#context {
+                    use linera_views::views::View;
+                    self.#first_name_quote.context()
+                }
+
+                fn pre_load(context: &#context) -> Result<Vec<Vec<u8>>, linera_views::ViewError> {
+                    use linera_views::context::Context as _;
+                    let mut keys = vec![context.base_key().derive_tag_key(
+                        linera_views::views::MIN_VIEW_TAG,
+                        &linera_views::views::LAYOUT_VERSION_INDEX,
+                    )?];
+                    #(#pre_load_keys_quotes)*
+                    Ok(keys)
+                }
+
+                fn post_load(context: #context, values: &[Option<Vec<u8>>]) -> Result<Self, linera_views::ViewError> {
+                    use linera_views::context::Context as _;
+                    // Views stored before their layout was versioned have version 0. Views
+                    // never stored have no version either, but none of their keys.
+                    let version = linera_views::common::from_bytes_option::<u32>(&values[0])?
+                        .unwrap_or(0);
+                    let is_new = values.iter().all(Option::is_none);
+                    if version != #version && !is_new {
+                        return Err(linera_views::ViewError::UnsupportedLayoutVersion {
+                            stored: version,
+                            expected: #version,
+                        });
+                    }
+                    let mut pos = 1;
+                    #(#post_load_keys_quotes)*
+                    Ok(Self {#(#name_quotes),*})
+                }
+
+                async fn load(context: #context) -> Result<Self, linera_views::ViewError> {
+                    use linera_views::{
+                        context::Context as _,
+                        store::{ReadableKeyValueStore as _, WritableKeyValueStore as _},
+                    };
+                    #load_metrics
+                    let keys = Self::pre_load(&context)?;
+                    let values = context.store().read_multi_values_bytes(keys).await?;
+                    // Views stored before their layout was versioned have version 0.
+                    let version = linera_views::common::from_bytes_option::<u32>(&values[0])?
+                        .unwrap_or(0);
+                    if version > #version {
+                        return Err(linera_views::ViewError::UnsupportedLayoutVersion {
+                            stored: version,
+                            expected: #version,
+                        });
+                    }
+                    // The fields are migrated as well if they were stored with a previous
+                    // layout version.
+                    let mut pos = 1;
+                    #(#load_keys_quotes)*
+                    let mut view = Self {#(#name_quotes),*};
+                    if version == #version {
+                        return Ok(view);
+                    }
+                    let stored_indices = linera_views::views::stored_field_indices(&context).await?;
+                    if stored_indices.is_empty() {
+                        // The view was never stored, so there is nothing to migrate.
+                        return Ok(view);
+                    }
+                    #migrate
+                    // Save the migrated view right away, together with its version, and
+                    // delete the fields of the previous layouts.
+                    let mut batch = linera_views::batch::Batch::new();
+                    for index in stored_indices {
+                        if ![#(#index_quotes),*].contains(&index) {
+                            let key_prefix = context
+                                .base_key()
+                                .derive_tag_key(linera_views::views::MIN_VIEW_TAG, &index)?;
+                            batch.delete_key_prefix(key_prefix);
+                        }
+                    }
+                    let version_key = context.base_key().derive_tag_key(
+                        linera_views::views::MIN_VIEW_TAG,
+                        &linera_views::views::LAYOUT_VERSION_INDEX,
+                    )?;
+                    batch.put_key_value(version_key, &#version)?;
+                    linera_views::views::View::flush(&mut view, &mut batch)?;
+                    context.store().write_batch(batch).await?;
+                    Ok(view)
+                }
+
+                fn rollback(&mut self) {
+                    #(#rollback_quotes)*
+                }
+
+                async fn has_pending_changes(&self) -> bool {
+                    #(#has_pending_changes_quotes)*
+                    false
+                }
+
+                fn flush(&mut self, batch: &mut linera_views::batch::Batch) -> Result<bool, linera_views::ViewError> {
+                    use linera_views::views::View;
+                    let num_operations = batch.operations.len();
+                    #(#flush_quotes)*
+                    let deleted = #(#test_flush_quotes)&&*;
+                    if batch.operations.len() > num_operations {
+                        let key = linera_views::views::layout_version_key(self.#first_name_quote.context())?;
+                        if deleted {
+                            batch.delete_key(key);
+                        } else {
+                            batch.put_key_value(key, &#version)?;
+                        }
+                    }
+                    Ok(deleted)
+                }
+
+                fn clear(&mut self) {
+                    #(#clear_quotes)*
+                }
+            }
+        };
+    }
+
     quote! {
+        #layout_check
+
         impl #impl_generics linera_views::views::View for #struct_name #type_generics
         where
             #context: linera_views::context::Context,
@@ -165,7 +425,11 @@ fn generate_view_code(input: ItemStruct, root: bool) -> TokenStream2 {
                 } else {
                     let keys = Self::pre_load(&context)?;
                     let values = context.store().read_multi_values_bytes(keys).await?;
-                    Self::post_load(context, &values)
+                    // The fields are migrated if they were stored with a previous layout
+                    // version.
+                    let mut pos = 0;
+                    #(#load_keys_quotes)*
+                    Ok(Self {#(#name_quotes),*})
                 }
             }
 
@@ -500,6 +764,49 @@ pub mod tests {
         }
     }
 
+    #[test]
+    fn test_layout_snapshot() {
+        let input: ItemStruct = parse_quote! {
+            #[view(version = 2)]
+            struct TestView<C> {
+                #[view(index = 3)]
+                register: RegisterView<C, u64>,
+                collection: CollectionView<C, usize, RegisterView<C, usize>>,
+            }
+        };
+        let indices = get_field_indices(&input);
+        assert_eq!(indices, vec![3, 1]);
+        let snapshot = get_layout_snapshot(&input, &indices, 2);
+        assert_eq!(
+            snapshot,
+            "version 2\n\
+            1 collection: CollectionView < C , usize , RegisterView < C , usize > >\n\
+            3 register: RegisterView < C , u64 >\n"
+        );
+        assert!(check_layout_snapshot("layout", Some(&snapshot), &snapshot, 2).is_ok());
+        let stored = "version 2\n3 register: RegisterView < C , u32 >\n";
+        let error = check_layout_snapshot("layout", Some(stored), &snapshot, 2).unwrap_err();
+        assert!(error.contains("changed but not its version"));
+        let stored = "version 1\n3 register: RegisterView < C , u32 >\n";
+        let error = check_layout_snapshot("layout", Some(stored), &snapshot, 2).unwrap_err();
+        assert!(error.contains("is outdated"));
+        let error = check_layout_snapshot("layout", None, &snapshot, 2).unwrap_err();
+        assert!(error.contains("missing layout snapshot"));
+    }
+
+    #[test]
+    #[should_panic(expected = "several fields have the index 1")]
+    fn test_duplicate_field_indices() {
+        let input: ItemStruct = parse_quote! {
+            struct TestView<C> {
+                #[view(index = 1)]
+                register: RegisterView<C, u64>,
+                collection: CollectionView<C, usize, RegisterView<C, usize>>,
+            }
+        };
+        get_field_indices(&input);
+    }
+
     #[derive(Clone)]
     pub struct SpecificContextInfo {
         name: String,
@@ -11,5 +11,6 @@ fn main() {
         with_rocksdb: { all(not(target_arch = "wasm32"), feature = "rocksdb") },
         with_scylladb: { all(not(target_arch = "wasm32"), feature = "scylladb") },
         with_graphql: { not(web) },
+        with_encryption: { all(not(target_arch = "wasm32"), feature = "encryption") },
     };
 }
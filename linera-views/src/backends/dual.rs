@@ -3,6 +3,8 @@
 
 //! Implements [`crate::store::KeyValueStore`] by combining two existing stores.
 
+use std::ops::Bound;
+
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
@@ -189,6 +191,48 @@ where
         };
         Ok(result)
     }
+
+    async fn find_keys_by_prefix_in_range(
+        &self,
+        key_prefix: &[u8],
+        range: (Bound<Vec<u8>>, Bound<Vec<u8>>),
+        reverse: bool,
+        limit: Option<usize>,
+    ) -> Result<Vec<Vec<u8>>, Self::Error> {
+        match self.store_in_use {
+            StoreInUse::First => self
+                .first_store
+                .find_keys_by_prefix_in_range(key_prefix, range, reverse, limit)
+                .await
+                .map_err(DualStoreError::First),
+            StoreInUse::Second => self
+                .second_store
+                .find_keys_by_prefix_in_range(key_prefix, range, reverse, limit)
+                .await
+                .map_err(DualStoreError::Second),
+        }
+    }
+
+    async fn find_key_values_by_prefix_in_range(
+        &self,
+        key_prefix: &[u8],
+        range: (Bound<Vec<u8>>, Bound<Vec<u8>>),
+        reverse: bool,
+        limit: Option<usize>,
+    ) -> Result<Vec<(Vec<u8>, Vec<u8>)>, Self::Error> {
+        match self.store_in_use {
+            StoreInUse::First => self
+                .first_store
+                .find_key_values_by_prefix_in_range(key_prefix, range, reverse, limit)
+                .await
+                .map_err(DualStoreError::First),
+            StoreInUse::Second => self
+                .second_store
+                .find_key_values_by_prefix_in_range(key_prefix, range, reverse, limit)
+                .await
+                .map_err(DualStoreError::Second),
+        }
+    }
 }
 
 impl<S1, S2, A> WritableKeyValueStore for DualStore<S1, S2, A>
@@ -6,6 +6,7 @@
 use std::{
     collections::HashMap,
     env,
+    ops::Bound,
     sync::{
         atomic::{AtomicBool, Ordering},
         Arc,
@@ -44,7 +45,10 @@ use crate::metering::MeteredStore;
 use crate::store::TestKeyValueStore;
 use crate::{
     batch::SimpleUnorderedBatch,
-    common::get_uleb128_size,
+    common::{
+        get_interval_in_range, get_uleb128_size, is_above_lower_bound, is_below_upper_bound,
+        is_empty_range,
+    },
     journaling::{DirectWritableKeyValueStore, JournalConsistencyError, JournalingKeyValueStore},
     lru_caching::{LruCachingConfig, LruCachingStore},
     store::{
@@ -690,6 +694,82 @@ impl DynamoDbStoreInternal {
             responses,
         })
     }
+
+    /// Reads the items of the keys of `key_prefix` whose remainder lies in `range`, in
+    /// the requested order, stopping after `limit` items.
+    async fn get_items_in_range(
+        &self,
+        attribute: &str,
+        key_prefix: &[u8],
+        range: &(Bound<Vec<u8>>, Bound<Vec<u8>>),
+        reverse: bool,
+        limit: Option<usize>,
+    ) -> Result<Vec<HashMap<String, AttributeValue>>, DynamoDbStoreInternalError> {
+        check_key_size(key_prefix)?;
+        let mut items = Vec::new();
+        if is_empty_range(range) || limit == Some(0) {
+            return Ok(items);
+        }
+        let (lower, upper) = get_interval_in_range(key_prefix, range);
+        let lower_key = match &lower {
+            Bound::Included(key) | Bound::Excluded(key) => key.clone(),
+            Bound::Unbounded => key_prefix.to_vec(),
+        };
+        // A key condition has a single comparison on the sort key, so the excluded
+        // endpoints of the range are skipped below.
+        let mut query = self
+            .client
+            .query()
+            .table_name(&self.namespace)
+            .projection_expression(attribute)
+            .expression_attribute_values(
+                ":partition",
+                AttributeValue::B(Blob::new(self.start_key.clone())),
+            )
+            .expression_attribute_values(":lower", AttributeValue::B(Blob::new(lower_key)))
+            .scan_index_forward(!reverse)
+            .set_limit(
+                limit.map(|limit| i32::try_from(limit.saturating_add(2)).unwrap_or(i32::MAX)),
+            );
+        query = match &upper {
+            Bound::Included(upper_key) | Bound::Excluded(upper_key) => query
+                .key_condition_expression(format!(
+                    "{PARTITION_ATTRIBUTE} = :partition and {KEY_ATTRIBUTE} between :lower and :upper"
+                ))
+                .expression_attribute_values(
+                    ":upper",
+                    AttributeValue::B(Blob::new(upper_key.clone())),
+                ),
+            Bound::Unbounded => query.key_condition_expression(format!(
+                "{PARTITION_ATTRIBUTE} = :partition and {KEY_ATTRIBUTE} >= :lower"
+            )),
+        };
+        let mut start_key_map = None;
+        loop {
+            let response = {
+                let _guard = self.acquire().await;
+                query
+                    .clone()
+                    .set_exclusive_start_key(start_key_map)
+                    .send()
+                    .boxed()
+                    .await?
+            };
+            for item in response.items.unwrap_or_default() {
+                let key = extract_key(0, &item)?;
+                if is_above_lower_bound(key, &lower) && is_below_upper_bound(key, &upper) {
+                    items.push(item);
+                    if limit == Some(items.len()) {
+                        return Ok(items);
+                    }
+                }
+            }
+            match response.last_evaluated_key {
+                None => return Ok(items),
+                Some(value) => start_key_map = Some(value),
+            }
+        }
+    }
 }
 
 struct QueryResponses {
@@ -942,6 +1022,38 @@ impl ReadableKeyValueStore for DynamoDbStoreInternal {
             .await?;
         Ok(DynamoDbKeyValues { result_queries })
     }
+
+    async fn find_keys_by_prefix_in_range(
+        &self,
+        key_prefix: &[u8],
+        range: (Bound<Vec<u8>>, Bound<Vec<u8>>),
+        reverse: bool,
+        limit: Option<usize>,
+    ) -> Result<Vec<Vec<u8>>, DynamoDbStoreInternalError> {
+        let items = self
+            .get_items_in_range(KEY_ATTRIBUTE, key_prefix, &range, reverse, limit)
+            .await?;
+        items
+            .iter()
+            .map(|item| Ok(extract_key(key_prefix.len(), item)?.to_vec()))
+            .collect()
+    }
+
+    async fn find_key_values_by_prefix_in_range(
+        &self,
+        key_prefix: &[u8],
+        range: (Bound<Vec<u8>>, Bound<Vec<u8>>),
+        reverse: bool,
+        limit: Option<usize>,
+    ) -> Result<Vec<(Vec<u8>, Vec<u8>)>, DynamoDbStoreInternalError> {
+        let items = self
+            .get_items_in_range(KEY_VALUE_ATTRIBUTE, key_prefix, &range, reverse, limit)
+            .await?;
+        items
+            .into_iter()
+            .map(|mut item| extract_key_value_owned(key_prefix.len(), &mut item))
+            .collect()
+    }
 }
 
 impl DirectWritableKeyValueStore for DynamoDbStoreInternal {
@@ -0,0 +1,889 @@
+// Copyright (c) Zefchain Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Adds encryption at rest to a given store.
+
+use std::{collections::BTreeMap, fmt, ops::Bound, str::FromStr, sync::Arc};
+
+use chacha20poly1305::{
+    aead::{Aead, AeadCore, KeyInit, OsRng, Payload},
+    XChaCha20Poly1305, XNonce,
+};
+use serde::{Deserialize, Serialize};
+use sha3::{
+    digest::{ExtendableOutput, XofReader},
+    Digest, Sha3_256, Shake256,
+};
+use thiserror::Error;
+
+use crate::{
+    batch::{Batch, WriteOperation},
+    common::{is_above_lower_bound, is_below_upper_bound, order_and_limit},
+    store::{
+        AdminKeyValueStore, KeyIterable, KeyValueIterable, KeyValueStoreError,
+        ReadableKeyValueStore, WithError, WritableKeyValueStore,
+    },
+};
+#[cfg(with_testing)]
+use crate::{
+    memory::{create_test_memory_store, MemoryStore},
+    store::TestKeyValueStore,
+};
+
+/// The size of the nonce stored with every encrypted value.
+const NONCE_SIZE: usize = 24;
+
+/// The size of the authentication tag stored with every encrypted value.
+const TAG_SIZE: usize = 16;
+
+/// The number of bytes added to a value by the encryption: the key identifier, the nonce
+/// and the authentication tag.
+pub const ENCRYPTION_OVERHEAD: usize = 1 + NONCE_SIZE + TAG_SIZE;
+
+/// The domain separator of the pseudo-random function used to encrypt the keys.
+const KEY_ENCRYPTION_DOMAIN: &[u8] = b"linera-views key encryption";
+
+/// The composed error type built from the inner error type.
+#[derive(Error, Debug)]
+pub enum EncryptedStoreError<E> {
+    /// inner store error
+    #[error(transparent)]
+    InnerStoreError(#[from] E),
+
+    /// The active key is not one of the configured keys
+    #[error("the active key {0} is not one of the configured keys")]
+    MissingActiveKey(u8),
+
+    /// The value was encrypted with a key that is not configured
+    #[error("the value was encrypted with key {0}, which is not configured")]
+    UnknownKeyId(u8),
+
+    /// The stored value is too short to be an encrypted value
+    #[error("the stored value is too short to be an encrypted value")]
+    TooShortValue,
+
+    /// The value could not be encrypted
+    #[error("the value could not be encrypted")]
+    EncryptionFailed,
+
+    /// The value could not be decrypted or authenticated
+    #[error("the value could not be decrypted or authenticated")]
+    DecryptionFailed,
+}
+
+impl<E: KeyValueStoreError> From<bcs::Error> for EncryptedStoreError<E> {
+    fn from(error: bcs::Error) -> Self {
+        let error = E::from(error);
+        EncryptedStoreError::InnerStoreError(error)
+    }
+}
+
+impl<E: KeyValueStoreError + 'static> KeyValueStoreError for EncryptedStoreError<E> {
+    const BACKEND: &'static str = "encrypted";
+}
+
+/// A 256-bit secret key. It is written in hexadecimal in human-readable formats.
+#[derive(Clone, PartialEq, Eq)]
+pub struct EncryptionKey([u8; 32]);
+
+impl EncryptionKey {
+    /// Creates a key from its bytes.
+    pub fn new(bytes: [u8; 32]) -> Self {
+        EncryptionKey(bytes)
+    }
+
+    /// Generates a new random key.
+    pub fn generate() -> Self {
+        EncryptionKey(XChaCha20Poly1305::generate_key(&mut OsRng).into())
+    }
+}
+
+impl fmt::Debug for EncryptionKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // Keys must never end up in logs.
+        write!(f, "EncryptionKey(..)")
+    }
+}
+
+impl FromStr for EncryptionKey {
+    type Err = hex::FromHexError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut bytes = [0u8; 32];
+        hex::decode_to_slice(s, &mut bytes)?;
+        Ok(EncryptionKey(bytes))
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename = "EncryptionKey")]
+struct EncryptionKeyString(String);
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename = "EncryptionKey")]
+struct EncryptionKeyBytes([u8; 32]);
+
+impl Serialize for EncryptionKey {
+    fn serialize<S: serde::ser::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if serializer.is_human_readable() {
+            EncryptionKeyString(hex::encode(self.0)).serialize(serializer)
+        } else {
+            EncryptionKeyBytes(self.0).serialize(serializer)
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for EncryptionKey {
+    fn deserialize<D: serde::de::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        if deserializer.is_human_readable() {
+            let EncryptionKeyString(s) = EncryptionKeyString::deserialize(deserializer)?;
+            s.parse().map_err(serde::de::Error::custom)
+        } else {
+            Ok(EncryptionKey(
+                EncryptionKeyBytes::deserialize(deserializer)?.0,
+            ))
+        }
+    }
+}
+
+/// The keys used by an `EncryptedStore`.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EncryptionConfig {
+    /// The keys that can decrypt the values, by identifier.
+    pub value_keys: BTreeMap<u8, EncryptionKey>,
+    /// The identifier of the key used to encrypt the values being written.
+    pub active_key_id: u8,
+    /// The key used to encrypt the keys, if they are not to be stored in clear.
+    /// Unlike the value keys, it cannot be rotated without rewriting the whole store.
+    pub key_encryption_key: Option<EncryptionKey>,
+}
+
+impl EncryptionConfig {
+    /// Creates a configuration with a single new random value key and, if
+    /// `encrypt_keys` is set, a new random key encryption key.
+    pub fn generate(encrypt_keys: bool) -> Self {
+        let active_key_id = 0;
+        let value_keys = BTreeMap::from([(active_key_id, EncryptionKey::generate())]);
+        let key_encryption_key = encrypt_keys.then(EncryptionKey::generate);
+        EncryptionConfig {
+            value_keys,
+            active_key_id,
+            key_encryption_key,
+        }
+    }
+
+    /// Adds a new random value key and makes it the active one. The previous keys are kept
+    /// so that the existing values can still be read.
+    ///
+    /// Panics if all the 256 key identifiers are in use.
+    pub fn rotate(&mut self) {
+        let active_key_id = (1..=u8::MAX)
+            .map(|offset| self.active_key_id.wrapping_add(offset))
+            .find(|key_id| !self.value_keys.contains_key(key_id))
+            .expect("all the key identifiers are in use");
+        self.value_keys
+            .insert(active_key_id, EncryptionKey::generate());
+        self.active_key_id = active_key_id;
+    }
+}
+
+/// The deterministic encryption of the keys.
+///
+/// Every byte is mapped by a permutation of the byte values, shuffled by a pseudo-random
+/// function of the key encryption key and of the bytes of the key before it. The encryption
+/// of a prefix is therefore a prefix of the encryption, and the prefix searches are
+/// preserved. The lexicographic order is not preserved, so the results of the searches have
+/// to be sorted again after decryption.
+///
+/// This leaks the length of every key and, for any two keys, the length of their common
+/// prefix, hence which keys are equal. The bytes after a common prefix are unrelated
+/// otherwise: unlike masking them, permuting them does not reveal how they differ.
+#[derive(Clone)]
+struct KeyCipher {
+    /// The state of the pseudo-random function before absorbing any byte of the key.
+    initial_state: Sha3_256,
+}
+
+impl KeyCipher {
+    fn new(key: &EncryptionKey) -> Self {
+        let mut initial_state = Sha3_256::new();
+        initial_state.update(KEY_ENCRYPTION_DOMAIN);
+        initial_state.update(key.0);
+        KeyCipher { initial_state }
+    }
+
+    /// Returns the permutation of the byte values used after the plaintext absorbed so far
+    /// in `state`.
+    fn permutation(state: &Sha3_256) -> [u8; 256] {
+        let mut xof = Shake256::default();
+        sha3::digest::Update::update(&mut xof, &state.clone().finalize());
+        let mut reader = xof.finalize_xof();
+        let mut permutation = std::array::from_fn(|i| i as u8);
+        // A Fisher-Yates shuffle, drawing every swap uniformly by rejection sampling.
+        for i in (1..permutation.len()).rev() {
+            let num_choices = i + 1;
+            let num_accepted = 256 - 256 % num_choices;
+            let j = loop {
+                let mut byte = [0];
+                reader.read(&mut byte);
+                if usize::from(byte[0]) < num_accepted {
+                    break usize::from(byte[0]) % num_choices;
+                }
+            };
+            permutation.swap(i, j);
+        }
+        permutation
+    }
+
+    /// Encrypts `bytes` following the plaintext absorbed so far in `state`.
+    fn encrypt_with(state: &mut Sha3_256, bytes: &[u8]) -> Vec<u8> {
+        bytes
+            .iter()
+            .map(|byte| {
+                let encrypted_byte = Self::permutation(state)[usize::from(*byte)];
+                state.update([*byte]);
+                encrypted_byte
+            })
+            .collect()
+    }
+
+    /// Decrypts `bytes` following the plaintext absorbed so far in `state`.
+    fn decrypt_with(state: &mut Sha3_256, bytes: &[u8]) -> Vec<u8> {
+        bytes
+            .iter()
+            .map(|encrypted_byte| {
+                let byte = Self::permutation(state)
+                    .iter()
+                    .position(|value| value == encrypted_byte)
+                    .expect("a permutation contains every byte") as u8;
+                state.update([byte]);
+                byte
+            })
+            .collect()
+    }
+
+    /// Encrypts `key` and returns the state after absorbing it.
+    fn encrypt(&self, key: &[u8]) -> (Vec<u8>, Sha3_256) {
+        let mut state = self.initial_state.clone();
+        let encrypted_key = Self::encrypt_with(&mut state, key);
+        (encrypted_key, state)
+    }
+}
+
+/// The ciphers derived from an `EncryptionConfig`.
+struct Ciphers {
+    value_ciphers: BTreeMap<u8, XChaCha20Poly1305>,
+    active_key_id: u8,
+    key_cipher: Option<KeyCipher>,
+}
+
+/// A key-value store with its values, and optionally its keys, encrypted at rest.
+///
+/// Values are encrypted with XChaCha20-Poly1305 under the active key, and authenticated
+/// together with their root key and key, so that they cannot be moved to another key or
+/// to another root key without failing to decrypt. Each stored value starts with the identifier of the key that
+/// encrypted it, so that keys can be rotated: new values use the active key while the
+/// existing ones remain readable as long as their key is configured.
+///
+/// If a key encryption key is configured, keys are encrypted deterministically in a way
+/// that preserves prefixes. The underlying store then still sees the length of every key
+/// and which keys share a prefix, and how long it is. The root keys are not encrypted.
+#[derive(Clone)]
+pub struct EncryptedStore<K> {
+    /// The underlying store of the transformed store.
+    store: K,
+    /// The root key of the underlying store, authenticated with every value.
+    root_key: Vec<u8>,
+    /// The ciphers in use.
+    ciphers: Arc<Ciphers>,
+}
+
+impl<K> WithError for EncryptedStore<K>
+where
+    K: WithError,
+    K::Error: 'static,
+{
+    type Error = EncryptedStoreError<K::Error>;
+}
+
+impl<K> ReadableKeyValueStore for EncryptedStore<K>
+where
+    K: ReadableKeyValueStore + Send + Sync,
+    K::Error: 'static,
+{
+    // The key encryption does not change the size of the keys.
+    const MAX_KEY_SIZE: usize = K::MAX_KEY_SIZE;
+    type Keys = Vec<Vec<u8>>;
+    type KeyValues = Vec<(Vec<u8>, Vec<u8>)>;
+
+    fn max_stream_queries(&self) -> usize {
+        self.store.max_stream_queries()
+    }
+
+    async fn read_value_bytes(&self, key: &[u8]) -> Result<Option<Vec<u8>>, Self::Error> {
+        let encrypted_key = self.encrypt_key(key);
+        let value = self.store.read_value_bytes(&encrypted_key).await?;
+        value
+            .map(|value| self.decrypt_value(key, &value))
+            .transpose()
+    }
+
+    async fn contains_key(&self, key: &[u8]) -> Result<bool, Self::Error> {
+        let encrypted_key = self.encrypt_key(key);
+        Ok(self.store.contains_key(&encrypted_key).await?)
+    }
+
+    async fn contains_keys(&self, keys: Vec<Vec<u8>>) -> Result<Vec<bool>, Self::Error> {
+        let encrypted_keys = keys.iter().map(|key| self.encrypt_key(key)).collect();
+        Ok(self.store.contains_keys(encrypted_keys).await?)
+    }
+
+    async fn read_multi_values_bytes(
+        &self,
+        keys: Vec<Vec<u8>>,
+    ) -> Result<Vec<Option<Vec<u8>>>, Self::Error> {
+        let encrypted_keys = keys.iter().map(|key| self.encrypt_key(key)).collect();
+        let values = self.store.read_multi_values_bytes(encrypted_keys).await?;
+        keys.iter()
+            .zip(values)
+            .map(|(key, value)| {
+                value
+                    .map(|value| self.decrypt_value(key, &value))
+                    .transpose()
+            })
+            .collect()
+    }
+
+    async fn find_keys_by_prefix(&self, key_prefix: &[u8]) -> Result<Self::Keys, Self::Error> {
+        let Some(key_cipher) = &self.ciphers.key_cipher else {
+            let keys = self.store.find_keys_by_prefix(key_prefix).await?;
+            return keys.iterator().map(|key| Ok(key?.to_vec())).collect();
+        };
+        let (encrypted_prefix, state) = key_cipher.encrypt(key_prefix);
+        let mut keys = Vec::new();
+        for encrypted_key in self
+            .store
+            .find_keys_by_prefix(&encrypted_prefix)
+            .await?
+            .iterator()
+        {
+            let encrypted_key = encrypted_key?;
+            keys.push(KeyCipher::decrypt_with(&mut state.clone(), encrypted_key));
+        }
+        keys.sort();
+        Ok(keys)
+    }
+
+    async fn find_key_values_by_prefix(
+        &self,
+        key_prefix: &[u8],
+    ) -> Result<Self::KeyValues, Self::Error> {
+        let encrypted_prefix = self.encrypt_key(key_prefix);
+        let encrypted_key_values = self
+            .store
+            .find_key_values_by_prefix(&encrypted_prefix)
+            .await?;
+        self.decrypt_key_values(key_prefix, encrypted_key_values)
+    }
+
+    async fn find_keys_by_prefix_in_range(
+        &self,
+        key_prefix: &[u8],
+        range: (Bound<Vec<u8>>, Bound<Vec<u8>>),
+        reverse: bool,
+        limit: Option<usize>,
+    ) -> Result<Vec<Vec<u8>>, Self::Error> {
+        if self.ciphers.key_cipher.is_none() {
+            return Ok(self
+                .store
+                .find_keys_by_prefix_in_range(key_prefix, range, reverse, limit)
+                .await?);
+        }
+        // The encrypted keys are not ordered, so the range cannot be passed down.
+        let keys = self.find_keys_by_prefix(key_prefix).await?;
+        let keys = keys
+            .into_iter()
+            .skip_while(|key| !is_above_lower_bound(key, &range.0))
+            .take_while(|key| is_below_upper_bound(key, &range.1))
+            .collect();
+        Ok(order_and_limit(keys, reverse, limit))
+    }
+
+    async fn find_key_values_by_prefix_in_range(
+        &self,
+        key_prefix: &[u8],
+        range: (Bound<Vec<u8>>, Bound<Vec<u8>>),
+        reverse: bool,
+        limit: Option<usize>,
+    ) -> Result<Vec<(Vec<u8>, Vec<u8>)>, Self::Error> {
+        if self.ciphers.key_cipher.is_none() {
+            let encrypted_key_values = self
+                .store
+                .find_key_values_by_prefix_in_range(key_prefix, range, reverse, limit)
+                .await?;
+            return encrypted_key_values
+                .into_iter()
+                .map(|(key, value)| {
+                    let mut full_key = key_prefix.to_vec();
+                    full_key.extend(&key);
+                    let value = self.decrypt_value(&full_key, &value)?;
+                    Ok((key, value))
+                })
+                .collect();
+        }
+        // The encrypted keys are not ordered, so the range cannot be passed down.
+        let key_values = self.find_key_values_by_prefix(key_prefix).await?;
+        let key_values = key_values
+            .into_iter()
+            .skip_while(|(key, _)| !is_above_lower_bound(key, &range.0))
+            .take_while(|(key, _)| is_below_upper_bound(key, &range.1))
+            .collect();
+        Ok(order_and_limit(key_values, reverse, limit))
+    }
+}
+
+impl<K> WritableKeyValueStore for EncryptedStore<K>
+where
+    K: WritableKeyValueStore + Send + Sync,
+    K::Error: 'static,
+{
+    const MAX_VALUE_SIZE: usize = K::MAX_VALUE_SIZE.saturating_sub(ENCRYPTION_OVERHEAD);
+
+    async fn write_batch(&self, batch: Batch) -> Result<(), Self::Error> {
+        let mut batch_new = Batch::new();
+        for operation in batch.operations {
+            match operation {
+                WriteOperation::Delete { key } => {
+                    batch_new.delete_key(self.encrypt_key(&key));
+                }
+                WriteOperation::Put { key, value } => {
+                    let encrypted_value = self.encrypt_value(&key, &value)?;
+                    batch_new.put_key_value_bytes(self.encrypt_key(&key), encrypted_value);
+                }
+                WriteOperation::DeletePrefix { key_prefix } => {
+                    batch_new.delete_key_prefix(self.encrypt_key(&key_prefix));
+                }
+            }
+        }
+        Ok(self.store.write_batch(batch_new).await?)
+    }
+
+    async fn clear_journal(&self) -> Result<(), Self::Error> {
+        Ok(self.store.clear_journal().await?)
+    }
+}
+
+/// The configuration type for the `EncryptedStore`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EncryptedStoreConfig<C> {
+    /// The inner configuration of the `EncryptedStore`.
+    pub inner_config: C,
+    /// The keys being used.
+    pub encryption_config: EncryptionConfig,
+}
+
+impl<K> AdminKeyValueStore for EncryptedStore<K>
+where
+    K: AdminKeyValueStore + Send + Sync,
+    K::Error: 'static,
+{
+    type Config = EncryptedStoreConfig<K::Config>;
+
+    fn get_name() -> String {
+        format!("encrypted {}", K::get_name())
+    }
+
+    async fn connect(config: &Self::Config, namespace: &str) -> Result<Self, Self::Error> {
+        let store = K::connect(&config.inner_config, namespace).await?;
+        EncryptedStore::new(store, &config.encryption_config)
+    }
+
+    fn clone_with_root_key(&self, root_key: &[u8]) -> Result<Self, Self::Error> {
+        let store = self.store.clone_with_root_key(root_key)?;
+        let root_key = root_key.to_vec();
+        let ciphers = self.ciphers.clone();
+        Ok(Self {
+            store,
+            root_key,
+            ciphers,
+        })
+    }
+
+    async fn list_all(config: &Self::Config) -> Result<Vec<String>, Self::Error> {
+        Ok(K::list_all(&config.inner_config).await?)
+    }
+
+    async fn list_root_keys(
+        config: &Self::Config,
+        namespace: &str,
+    ) -> Result<Vec<Vec<u8>>, Self::Error> {
+        Ok(K::list_root_keys(&config.inner_config, namespace).await?)
+    }
+
+    async fn delete_all(config: &Self::Config) -> Result<(), Self::Error> {
+        Ok(K::delete_all(&config.inner_config).await?)
+    }
+
+    async fn exists(config: &Self::Config, namespace: &str) -> Result<bool, Self::Error> {
+        Ok(K::exists(&config.inner_config, namespace).await?)
+    }
+
+    async fn create(config: &Self::Config, namespace: &str) -> Result<(), Self::Error> {
+        Ok(K::create(&config.inner_config, namespace).await?)
+    }
+
+    async fn delete(config: &Self::Config, namespace: &str) -> Result<(), Self::Error> {
+        Ok(K::delete(&config.inner_config, namespace).await?)
+    }
+}
+
+#[cfg(with_testing)]
+impl<K> TestKeyValueStore for EncryptedStore<K>
+where
+    K: TestKeyValueStore + Send + Sync,
+    K::Error: 'static,
+{
+    async fn new_test_config() -> Result<EncryptedStoreConfig<K::Config>, Self::Error> {
+        let inner_config = K::new_test_config().await?;
+        let encryption_config = EncryptionConfig::generate(true);
+        Ok(EncryptedStoreConfig {
+            inner_config,
+            encryption_config,
+        })
+    }
+}
+
+impl<K> EncryptedStore<K>
+where
+    K: WithError,
+{
+    /// Creates a new store that encrypts the data written to the given one.
+    pub fn new(
+        store: K,
+        encryption_config: &EncryptionConfig,
+    ) -> Result<Self, EncryptedStoreError<K::Error>> {
+        let active_key_id = encryption_config.active_key_id;
+        if !encryption_config.value_keys.contains_key(&active_key_id) {
+            return Err(EncryptedStoreError::MissingActiveKey(active_key_id));
+        }
+        let value_ciphers = encryption_config
+            .value_keys
+            .iter()
+            .map(|(key_id, key)| (*key_id, XChaCha20Poly1305::new(&key.0.into())))
+            .collect();
+        let key_cipher = encryption_config
+            .key_encryption_key
+            .as_ref()
+            .map(KeyCipher::new);
+        let ciphers = Arc::new(Ciphers {
+            value_ciphers,
+            active_key_id,
+            key_cipher,
+        });
+        Ok(EncryptedStore {
+            store,
+            root_key: Vec::new(),
+            ciphers,
+        })
+    }
+
+    /// Returns the data authenticated with the value of `key`: the root key, prefixed by
+    /// its length, followed by the key.
+    fn associated_data(&self, key: &[u8]) -> Vec<u8> {
+        let root_key_len = self.root_key.len() as u64;
+        let mut associated_data = Vec::with_capacity(8 + self.root_key.len() + key.len());
+        associated_data.extend(root_key_len.to_le_bytes());
+        associated_data.extend(&self.root_key);
+        associated_data.extend(key);
+        associated_data
+    }
+
+    fn encrypt_key(&self, key: &[u8]) -> Vec<u8> {
+        match &self.ciphers.key_cipher {
+            None => key.to_vec(),
+            Some(key_cipher) => key_cipher.encrypt(key).0,
+        }
+    }
+
+    fn encrypt_value(
+        &self,
+        key: &[u8],
+        value: &[u8],
+    ) -> Result<Vec<u8>, EncryptedStoreError<K::Error>> {
+        let key_id = self.ciphers.active_key_id;
+        let cipher = &self.ciphers.value_ciphers[&key_id];
+        let nonce = XChaCha20Poly1305::generate_nonce(&mut OsRng);
+        let associated_data = self.associated_data(key);
+        let payload = Payload {
+            msg: value,
+            aad: &associated_data,
+        };
+        let ciphertext = cipher
+            .encrypt(&nonce, payload)
+            .map_err(|_| EncryptedStoreError::EncryptionFailed)?;
+        let mut encrypted_value = Vec::with_capacity(1 + NONCE_SIZE + ciphertext.len());
+        encrypted_value.push(key_id);
+        encrypted_value.extend(nonce);
+        encrypted_value.extend(ciphertext);
+        Ok(encrypted_value)
+    }
+
+    fn decrypt_value(
+        &self,
+        key: &[u8],
+        encrypted_value: &[u8],
+    ) -> Result<Vec<u8>, EncryptedStoreError<K::Error>> {
+        if encrypted_value.len() < ENCRYPTION_OVERHEAD {
+            return Err(EncryptedStoreError::TooShortValue);
+        }
+        let key_id = encrypted_value[0];
+        let cipher = self
+            .ciphers
+            .value_ciphers
+            .get(&key_id)
+            .ok_or(EncryptedStoreError::UnknownKeyId(key_id))?;
+        let nonce = XNonce::from_slice(&encrypted_value[1..1 + NONCE_SIZE]);
+        let associated_data = self.associated_data(key);
+        let payload = Payload {
+            msg: &encrypted_value[1 + NONCE_SIZE..],
+            aad: &associated_data,
+        };
+        cipher
+            .decrypt(nonce, payload)
+            .map_err(|_| EncryptedStoreError::DecryptionFailed)
+    }
+
+    /// Returns the state of the key encryption after absorbing `key_prefix`, if the keys
+    /// are encrypted.
+    fn key_prefix_state(&self, key_prefix: &[u8]) -> Option<Sha3_256> {
+        self.ciphers
+            .key_cipher
+            .as_ref()
+            .map(|key_cipher| key_cipher.encrypt(key_prefix).1)
+    }
+
+    /// Decrypts the key-value pairs found under `key_prefix` in the underlying store, and
+    /// sorts them by key if the keys are encrypted.
+    fn decrypt_key_values<I>(
+        &self,
+        key_prefix: &[u8],
+        encrypted_key_values: I,
+    ) -> Result<Vec<(Vec<u8>, Vec<u8>)>, EncryptedStoreError<K::Error>>
+    where
+        I: KeyValueIterable<K::Error>,
+    {
+        let state = self.key_prefix_state(key_prefix);
+        let mut key_values = Vec::new();
+        for entry in encrypted_key_values.into_iterator_owned() {
+            let (key, value) = entry?;
+            let key = match &state {
+                None => key,
+                Some(state) => KeyCipher::decrypt_with(&mut state.clone(), &key),
+            };
+            let mut full_key = key_prefix.to_vec();
+            full_key.extend(&key);
+            let value = self.decrypt_value(&full_key, &value)?;
+            key_values.push((key, value));
+        }
+        if state.is_some() {
+            key_values.sort_by(|(key1, _), (key2, _)| key1.cmp(key2));
+        }
+        Ok(key_values)
+    }
+}
+
+impl<K> EncryptedStore<K>
+where
+    K: ReadableKeyValueStore + WritableKeyValueStore + Send + Sync,
+    K::Error: 'static,
+{
+    /// Re-encrypts with the active key the values under `key_prefix` that were encrypted
+    /// with another key, and returns their number. Once this is done for the whole store,
+    /// the previous keys can be removed from the configuration.
+    pub async fn reencrypt_values_by_prefix(
+        &self,
+        key_prefix: &[u8],
+    ) -> Result<usize, EncryptedStoreError<K::Error>> {
+        let encrypted_prefix = self.encrypt_key(key_prefix);
+        let state = self.key_prefix_state(key_prefix);
+        let encrypted_key_values = self
+            .store
+            .find_key_values_by_prefix(&encrypted_prefix)
+            .await?;
+        let mut batch = Batch::new();
+        for entry in encrypted_key_values.into_iterator_owned() {
+            let (encrypted_key, encrypted_value) = entry?;
+            if encrypted_value.first() == Some(&self.ciphers.active_key_id) {
+                continue;
+            }
+            let mut full_key = key_prefix.to_vec();
+            match &state {
+                None => full_key.extend(&encrypted_key),
+                Some(state) => {
+                    full_key.extend(KeyCipher::decrypt_with(&mut state.clone(), &encrypted_key))
+                }
+            }
+            let value = self.decrypt_value(&full_key, &encrypted_value)?;
+            let mut full_encrypted_key = encrypted_prefix.clone();
+            full_encrypted_key.extend(encrypted_key);
+            batch.put_key_value_bytes(full_encrypted_key, self.encrypt_value(&full_key, &value)?);
+        }
+        let count = batch.operations.len();
+        if count > 0 {
+            self.store.write_batch(batch).await?;
+        }
+        Ok(count)
+    }
+}
+
+/// Provides an `EncryptedStore<MemoryStore>` with encrypted keys that can be used for tests.
+#[cfg(with_testing)]
+pub fn create_encrypted_memory_store() -> EncryptedStore<MemoryStore> {
+    let encryption_config = EncryptionConfig::generate(true);
+    EncryptedStore::new(create_test_memory_store(), &encryption_config).unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeSet;
+
+    use linera_views::{
+        batch::Batch,
+        encrypted::{EncryptedStore, EncryptedStoreError, EncryptionConfig},
+        memory::create_test_memory_store,
+        store::{AdminKeyValueStore as _, ReadableKeyValueStore, WritableKeyValueStore},
+    };
+
+    #[tokio::test]
+    async fn test_encrypted_store_hides_keys_and_values() {
+        let store = create_test_memory_store();
+        let encryption_config = EncryptionConfig::generate(true);
+        let encrypted_store = EncryptedStore::new(store.clone(), &encryption_config).unwrap();
+        let key = b"some key".to_vec();
+        let value = b"some secret value".to_vec();
+        let mut batch = Batch::new();
+        batch.put_key_value_bytes(key.clone(), value.clone());
+        encrypted_store.write_batch(batch).await.unwrap();
+        let key_values = store.find_key_values_by_prefix(&[]).await.unwrap();
+        assert_eq!(key_values.len(), 1);
+        let (stored_key, stored_value) = &key_values[0];
+        assert_eq!(stored_key.len(), key.len());
+        assert_ne!(stored_key, &key);
+        assert!(!stored_value
+            .windows(value.len())
+            .any(|window| window == value.as_slice()));
+        let value_read = encrypted_store.read_value_bytes(&key).await.unwrap();
+        assert_eq!(value_read, Some(value));
+    }
+
+    #[tokio::test]
+    async fn test_encrypted_store_prefix_order() {
+        let encryption_config = EncryptionConfig::generate(true);
+        let store = EncryptedStore::new(create_test_memory_store(), &encryption_config).unwrap();
+        let mut batch = Batch::new();
+        for key in [[1, 3], [1, 1], [2, 0], [1, 2], [1, 0]] {
+            batch.put_key_value_bytes(key.to_vec(), key.to_vec());
+        }
+        store.write_batch(batch).await.unwrap();
+        let keys = store.find_keys_by_prefix(&[1]).await.unwrap();
+        assert_eq!(keys, vec![vec![0], vec![1], vec![2], vec![3]]);
+        let key_values = store.find_key_values_by_prefix(&[1]).await.unwrap();
+        for (key, value) in key_values {
+            let mut full_key = vec![1];
+            full_key.extend(key);
+            assert_eq!(value, full_key);
+        }
+        let mut batch = Batch::new();
+        batch.delete_key_prefix(vec![1]);
+        store.write_batch(batch).await.unwrap();
+        let keys = store.find_keys_by_prefix(&[]).await.unwrap();
+        assert_eq!(keys, vec![vec![2, 0]]);
+    }
+
+    #[tokio::test]
+    async fn test_encrypted_store_key_rotation() {
+        let store = create_test_memory_store();
+        let mut encryption_config = EncryptionConfig::generate(false);
+        let old_store = EncryptedStore::new(store.clone(), &encryption_config).unwrap();
+        let mut batch = Batch::new();
+        batch.put_key_value_bytes(vec![0, 1], vec![1]);
+        batch.put_key_value_bytes(vec![0, 2], vec![2]);
+        old_store.write_batch(batch).await.unwrap();
+        // After the rotation, the old values remain readable and new values use the new key.
+        let old_key_id = encryption_config.active_key_id;
+        encryption_config.rotate();
+        let new_store = EncryptedStore::new(store.clone(), &encryption_config).unwrap();
+        let mut batch = Batch::new();
+        batch.put_key_value_bytes(vec![0, 3], vec![3]);
+        new_store.write_batch(batch).await.unwrap();
+        let values = new_store
+            .read_multi_values_bytes(vec![vec![0, 1], vec![0, 2], vec![0, 3]])
+            .await
+            .unwrap();
+        assert_eq!(values, vec![Some(vec![1]), Some(vec![2]), Some(vec![3])]);
+        // Once re-encrypted, the old key is no longer needed.
+        assert_eq!(new_store.reencrypt_values_by_prefix(&[0]).await.unwrap(), 2);
+        assert_eq!(new_store.reencrypt_values_by_prefix(&[0]).await.unwrap(), 0);
+        encryption_config.value_keys.remove(&old_key_id);
+        let new_store = EncryptedStore::new(store.clone(), &encryption_config).unwrap();
+        let key_values = new_store.find_key_values_by_prefix(&[0]).await.unwrap();
+        assert_eq!(
+            key_values,
+            vec![(vec![1], vec![1]), (vec![2], vec![2]), (vec![3], vec![3])]
+        );
+        assert!(matches!(
+            old_store.read_value_bytes(&[0, 1]).await,
+            Err(EncryptedStoreError::UnknownKeyId(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_encrypted_store_binds_values_to_root_key() {
+        let store = create_test_memory_store();
+        let encryption_config = EncryptionConfig::generate(true);
+        let encrypted_store = EncryptedStore::new(store.clone(), &encryption_config).unwrap();
+        let store1 = store.clone_with_root_key(&[1]).unwrap();
+        let store2 = store.clone_with_root_key(&[2]).unwrap();
+        let encrypted_store1 = encrypted_store.clone_with_root_key(&[1]).unwrap();
+        let encrypted_store2 = encrypted_store.clone_with_root_key(&[2]).unwrap();
+        let key = b"some key".to_vec();
+        let mut batch = Batch::new();
+        batch.put_key_value_bytes(key.clone(), b"some value".to_vec());
+        encrypted_store1.write_batch(batch).await.unwrap();
+        // The encrypted key and value are moved as they are to the other root key.
+        let key_values = store1.find_key_values_by_prefix(&[]).await.unwrap();
+        let mut batch = Batch::new();
+        for (stored_key, stored_value) in key_values {
+            batch.put_key_value_bytes(stored_key, stored_value);
+        }
+        store2.write_batch(batch).await.unwrap();
+        assert!(encrypted_store2.contains_key(&key).await.unwrap());
+        assert!(matches!(
+            encrypted_store2.read_value_bytes(&key).await,
+            Err(EncryptedStoreError::DecryptionFailed)
+        ));
+        let value = encrypted_store1.read_value_bytes(&key).await.unwrap();
+        assert_eq!(value, Some(b"some value".to_vec()));
+    }
+
+    #[tokio::test]
+    async fn test_encrypted_store_permutes_key_bytes() {
+        let encryption_config = EncryptionConfig::generate(true);
+        let mut encrypted_bytes = Vec::new();
+        for byte in 0..=u8::MAX {
+            let store = create_test_memory_store();
+            let encrypted_store = EncryptedStore::new(store.clone(), &encryption_config).unwrap();
+            let mut batch = Batch::new();
+            batch.put_key_value_bytes(vec![7, byte], vec![]);
+            encrypted_store.write_batch(batch).await.unwrap();
+            let keys = store.find_keys_by_prefix(&[]).await.unwrap();
+            encrypted_bytes.push(keys[0][1]);
+        }
+        // The bytes following a common prefix are permuted...
+        let distinct_bytes = encrypted_bytes.iter().collect::<BTreeSet<_>>();
+        assert_eq!(distinct_bytes.len(), 256);
+        // ... and, unlike with a mask, their differences do not reveal those of the keys.
+        assert!((0..=u8::MAX)
+            .any(|byte| encrypted_bytes[usize::from(byte)] ^ encrypted_bytes[0] != byte));
+    }
+}
@@ -3,7 +3,7 @@
 
 //! Implements [`crate::store::KeyValueStore`] for the IndexedDB Web database.
 
-use std::rc::Rc;
+use std::{ops::Bound, rc::Rc};
 
 use futures::future;
 use indexed_db_futures::{js_sys, prelude::*, web_sys};
@@ -12,7 +12,7 @@ use thiserror::Error;
 
 use crate::{
     batch::{Batch, WriteOperation},
-    common::get_upper_bound_option,
+    common::{get_interval_in_range, get_upper_bound_option, is_empty_range},
     lru_caching::DEFAULT_STORAGE_CACHE_CONFIG,
     store::{
         AdminKeyValueStore, CommonStoreConfig, KeyValueStoreError, ReadableKeyValueStore,
@@ -78,6 +78,58 @@ impl IndexedDbStore {
         full_key
     }
 
+    /// Iterates with a cursor over the keys of `key_prefix` whose remainder lies in
+    /// `range`, in the requested order, reading the values only if `with_values` is set.
+    async fn find_key_values_or_keys_in_range(
+        &self,
+        key_prefix: &[u8],
+        range: (Bound<Vec<u8>>, Bound<Vec<u8>>),
+        reverse: bool,
+        limit: Option<usize>,
+        with_values: bool,
+    ) -> Result<Vec<(Vec<u8>, Vec<u8>)>, IndexedDbStoreError> {
+        let mut key_values = vec![];
+        if is_empty_range(&range) || limit == Some(0) {
+            return Ok(key_values);
+        }
+        let key_prefix = self.full_key(key_prefix);
+        let range = interval_to_range(get_interval_in_range(&key_prefix, &range))?;
+        let direction = if reverse {
+            web_sys::IdbCursorDirection::Prev
+        } else {
+            web_sys::IdbCursorDirection::Next
+        };
+        let transaction = self.database.transaction_on_one(&self.object_store_name)?;
+        let object_store = transaction.object_store(&self.object_store_name)?;
+        let Some(cursor) = object_store
+            .open_cursor_with_range_and_direction_owned(range, direction)?
+            .await?
+        else {
+            return Ok(key_values);
+        };
+
+        loop {
+            let Some(key) = cursor.primary_key() else {
+                break;
+            };
+            let key = js_sys::Uint8Array::new(&key);
+            let value = if with_values {
+                js_sys::Uint8Array::new(&cursor.value()).to_vec()
+            } else {
+                Vec::new()
+            };
+            key_values.push((
+                key.subarray(key_prefix.len() as u32, key.length()).to_vec(),
+                value,
+            ));
+            if limit == Some(key_values.len()) || !cursor.continue_cursor()?.await? {
+                break;
+            }
+        }
+
+        Ok(key_values)
+    }
+
     async fn connect_internal(
         config: &IndexedDbStoreConfig,
         namespace: &str,
@@ -122,6 +174,32 @@ fn prefix_to_range(prefix: &[u8]) -> Result<web_sys::IdbKeyRange, wasm_bindgen::
     }
 }
 
+/// Converts an interval of full keys, bounded below, into a key range.
+fn interval_to_range(
+    (lower, upper): (Bound<Vec<u8>>, Bound<Vec<u8>>),
+) -> Result<web_sys::IdbKeyRange, wasm_bindgen::JsValue> {
+    let (lower, lower_open) = match lower {
+        Bound::Included(lower) => (lower, false),
+        Bound::Excluded(lower) => (lower, true),
+        Bound::Unbounded => (Vec::new(), false),
+    };
+    let lower = js_sys::Uint8Array::from(&lower[..]);
+    let (upper, upper_open) = match upper {
+        Bound::Included(upper) => (upper, false),
+        Bound::Excluded(upper) => (upper, true),
+        Bound::Unbounded => {
+            return web_sys::IdbKeyRange::lower_bound_with_open(&lower.into(), lower_open);
+        }
+    };
+    let upper = js_sys::Uint8Array::from(&upper[..]);
+    web_sys::IdbKeyRange::bound_with_lower_open_and_upper_open(
+        &lower.into(),
+        &upper.into(),
+        lower_open,
+        upper_open,
+    )
+}
+
 impl WithError for IndexedDbStore {
     type Error = IndexedDbStoreError;
 }
@@ -215,6 +293,32 @@ impl ReadableKeyValueStore for IndexedDbStore {
 
         Ok(key_values)
     }
+
+    async fn find_keys_by_prefix_in_range(
+        &self,
+        key_prefix: &[u8],
+        range: (Bound<Vec<u8>>, Bound<Vec<u8>>),
+        reverse: bool,
+        limit: Option<usize>,
+    ) -> Result<Vec<Vec<u8>>, IndexedDbStoreError> {
+        Ok(self
+            .find_key_values_or_keys_in_range(key_prefix, range, reverse, limit, false)
+            .await?
+            .into_iter()
+            .map(|(key, _)| key)
+            .collect())
+    }
+
+    async fn find_key_values_by_prefix_in_range(
+        &self,
+        key_prefix: &[u8],
+        range: (Bound<Vec<u8>>, Bound<Vec<u8>>),
+        reverse: bool,
+        limit: Option<usize>,
+    ) -> Result<Vec<(Vec<u8>, Vec<u8>)>, IndexedDbStoreError> {
+        self.find_key_values_or_keys_in_range(key_prefix, range, reverse, limit, true)
+            .await
+    }
 }
 
 impl WritableKeyValueStore for IndexedDbStore {
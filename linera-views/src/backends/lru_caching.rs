@@ -5,6 +5,7 @@
 
 use std::{
     collections::{btree_map, hash_map::RandomState, BTreeMap},
+    ops::Bound,
     sync::{Arc, Mutex},
 };
 
@@ -420,6 +421,30 @@ where
     ) -> Result<Self::KeyValues, Self::Error> {
         self.store.find_key_values_by_prefix(key_prefix).await
     }
+
+    async fn find_keys_by_prefix_in_range(
+        &self,
+        key_prefix: &[u8],
+        range: (Bound<Vec<u8>>, Bound<Vec<u8>>),
+        reverse: bool,
+        limit: Option<usize>,
+    ) -> Result<Vec<Vec<u8>>, Self::Error> {
+        self.store
+            .find_keys_by_prefix_in_range(key_prefix, range, reverse, limit)
+            .await
+    }
+
+    async fn find_key_values_by_prefix_in_range(
+        &self,
+        key_prefix: &[u8],
+        range: (Bound<Vec<u8>>, Bound<Vec<u8>>),
+        reverse: bool,
+        limit: Option<usize>,
+    ) -> Result<Vec<(Vec<u8>, Vec<u8>)>, Self::Error> {
+        self.store
+            .find_key_values_by_prefix_in_range(key_prefix, range, reverse, limit)
+            .await
+    }
 }
 
 impl<K> WritableKeyValueStore for LruCachingStore<K>
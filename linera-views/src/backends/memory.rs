@@ -5,6 +5,7 @@
 
 use std::{
     collections::BTreeMap,
+    ops::Bound,
     sync::{Arc, LazyLock, Mutex, RwLock},
 };
 
@@ -17,7 +18,7 @@ use crate::random::generate_test_namespace;
 use crate::store::TestKeyValueStore;
 use crate::{
     batch::{Batch, WriteOperation},
-    common::get_interval,
+    common::{get_interval, get_interval_in_range, is_empty_range},
     store::{
         AdminKeyValueStore, CommonStoreInternalConfig, KeyValueStoreError, ReadableKeyValueStore,
         WithError, WritableKeyValueStore,
@@ -226,6 +227,58 @@ impl ReadableKeyValueStore for MemoryStore {
         }
         Ok(key_values)
     }
+
+    async fn find_keys_by_prefix_in_range(
+        &self,
+        key_prefix: &[u8],
+        range: (Bound<Vec<u8>>, Bound<Vec<u8>>),
+        reverse: bool,
+        limit: Option<usize>,
+    ) -> Result<Vec<Vec<u8>>, MemoryStoreError> {
+        if is_empty_range(&range) {
+            return Ok(Vec::new());
+        }
+        let map = self
+            .map
+            .read()
+            .expect("MemoryStore lock should not be poisoned");
+        let len = key_prefix.len();
+        let limit = limit.unwrap_or(usize::MAX);
+        let entries = map
+            .range(get_interval_in_range(key_prefix, &range))
+            .map(|(key, _value)| key[len..].to_vec());
+        if reverse {
+            Ok(entries.rev().take(limit).collect())
+        } else {
+            Ok(entries.take(limit).collect())
+        }
+    }
+
+    async fn find_key_values_by_prefix_in_range(
+        &self,
+        key_prefix: &[u8],
+        range: (Bound<Vec<u8>>, Bound<Vec<u8>>),
+        reverse: bool,
+        limit: Option<usize>,
+    ) -> Result<Vec<(Vec<u8>, Vec<u8>)>, MemoryStoreError> {
+        if is_empty_range(&range) {
+            return Ok(Vec::new());
+        }
+        let map = self
+            .map
+            .read()
+            .expect("MemoryStore lock should not be poisoned");
+        let len = key_prefix.len();
+        let limit = limit.unwrap_or(usize::MAX);
+        let entries = map
+            .range(get_interval_in_range(key_prefix, &range))
+            .map(|(key, value)| (key[len..].to_vec(), value.to_vec()));
+        if reverse {
+            Ok(entries.rev().take(limit).collect())
+        } else {
+            Ok(entries.take(limit).collect())
+        }
+    }
 }
 
 impl WritableKeyValueStore for MemoryStore {
@@ -5,6 +5,7 @@
 
 use std::{
     collections::{btree_map::Entry, BTreeMap},
+    ops::Bound,
     sync::{Arc, LazyLock, Mutex},
 };
 
@@ -33,6 +34,8 @@ pub struct KeyValueStoreMetrics {
     read_multi_values_bytes_latency: HistogramVec,
     find_keys_by_prefix_latency: HistogramVec,
     find_key_values_by_prefix_latency: HistogramVec,
+    find_keys_by_prefix_in_range_latency: HistogramVec,
+    find_key_values_by_prefix_in_range_latency: HistogramVec,
     write_batch_latency: HistogramVec,
     clear_journal_latency: HistogramVec,
     connect_latency: HistogramVec,
@@ -118,6 +121,16 @@ impl KeyValueStoreMetrics {
         let entry2 = format!("{} find key values by prefix latency", title_name);
         let find_key_values_by_prefix_latency = register_histogram_vec(&entry1, &entry2, &[], None);
 
+        let entry1 = format!("{}_find_keys_by_prefix_in_range_latency", var_name);
+        let entry2 = format!("{} find keys by prefix in range latency", title_name);
+        let find_keys_by_prefix_in_range_latency =
+            register_histogram_vec(&entry1, &entry2, &[], None);
+
+        let entry1 = format!("{}_find_key_values_by_prefix_in_range_latency", var_name);
+        let entry2 = format!("{} find key values by prefix in range latency", title_name);
+        let find_key_values_by_prefix_in_range_latency =
+            register_histogram_vec(&entry1, &entry2, &[], None);
+
         let entry1 = format!("{}_write_batch_latency", var_name);
         let entry2 = format!("{} write batch latency", title_name);
         let write_batch_latency = register_histogram_vec(&entry1, &entry2, &[], None);
@@ -236,6 +249,8 @@ impl KeyValueStoreMetrics {
             read_multi_values_bytes_latency,
             find_keys_by_prefix_latency,
             find_key_values_by_prefix_latency,
+            find_keys_by_prefix_in_range_latency,
+            find_key_values_by_prefix_in_range_latency,
             write_batch_latency,
             clear_journal_latency,
             connect_latency,
@@ -413,6 +428,38 @@ where
             .observe(key_values_size as f64);
         Ok(result)
     }
+
+    async fn find_keys_by_prefix_in_range(
+        &self,
+        key_prefix: &[u8],
+        range: (Bound<Vec<u8>>, Bound<Vec<u8>>),
+        reverse: bool,
+        limit: Option<usize>,
+    ) -> Result<Vec<Vec<u8>>, Self::Error> {
+        let _latency = self
+            .counter
+            .find_keys_by_prefix_in_range_latency
+            .measure_latency();
+        self.store
+            .find_keys_by_prefix_in_range(key_prefix, range, reverse, limit)
+            .await
+    }
+
+    async fn find_key_values_by_prefix_in_range(
+        &self,
+        key_prefix: &[u8],
+        range: (Bound<Vec<u8>>, Bound<Vec<u8>>),
+        reverse: bool,
+        limit: Option<usize>,
+    ) -> Result<Vec<(Vec<u8>, Vec<u8>)>, Self::Error> {
+        let _latency = self
+            .counter
+            .find_key_values_by_prefix_in_range_latency
+            .measure_latency();
+        self.store
+            .find_key_values_by_prefix_in_range(key_prefix, range, reverse, limit)
+            .await
+    }
 }
 
 impl<K> WritableKeyValueStore for MeteredStore<K>
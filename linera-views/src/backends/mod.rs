@@ -12,8 +12,13 @@ pub mod memory;
 
 pub mod lru_caching;
 
+#[cfg(with_encryption)]
+pub mod encrypted;
+
 pub mod dual;
 
+pub mod tiered;
+
 #[cfg(with_scylladb)]
 pub mod scylla_db;
 
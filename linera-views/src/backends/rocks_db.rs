@@ -6,6 +6,7 @@
 use std::{
     ffi::OsString,
     fmt::Display,
+    ops::Bound::{self, Excluded, Included},
     path::PathBuf,
     sync::{
         atomic::{AtomicBool, Ordering},
@@ -26,7 +27,7 @@ use crate::metering::MeteredStore;
 use crate::store::TestKeyValueStore;
 use crate::{
     batch::{Batch, WriteOperation},
-    common::get_upper_bound_option,
+    common::{get_upper_bound_option, is_above_lower_bound, is_below_upper_bound},
     lru_caching::{LruCachingConfig, LruCachingStore},
     store::{
         AdminKeyValueStore, CommonStoreInternalConfig, KeyValueStoreError, ReadableKeyValueStore,
@@ -122,6 +123,15 @@ struct RocksDbStoreExecutor {
     start_key: Vec<u8>,
 }
 
+/// The arguments of a range query: the key prefix, the range of the remainders of the
+/// keys, whether to iterate in reverse, and the maximum number of entries.
+type RangeQuery = (
+    Vec<u8>,
+    (Bound<Vec<u8>>, Bound<Vec<u8>>),
+    bool,
+    Option<usize>,
+);
+
 impl RocksDbStoreExecutor {
     pub fn contains_keys_internal(
         &self,
@@ -216,6 +226,94 @@ impl RocksDbStoreExecutor {
         Ok(key_values)
     }
 
+    /// Visits the entries of `key_prefix` whose remainder lies in `range`, in lexicographic
+    /// order or in reverse, calling `f` on the remainder of the key and the value until
+    /// `limit` entries have been visited.
+    fn for_each_in_range(
+        &self,
+        key_prefix: &[u8],
+        range: &(Bound<Vec<u8>>, Bound<Vec<u8>>),
+        reverse: bool,
+        limit: Option<usize>,
+        mut f: impl FnMut(&[u8], &[u8]),
+    ) -> Result<(), RocksDbStoreInternalError> {
+        check_key_size(key_prefix)?;
+        let mut prefix = self.start_key.clone();
+        prefix.extend(key_prefix);
+        let len = prefix.len();
+        let mut remaining = limit.unwrap_or(usize::MAX);
+        let mut iter = self.db.raw_iterator();
+        if reverse {
+            match &range.1 {
+                Included(upper) | Excluded(upper) => {
+                    let mut start = prefix.clone();
+                    start.extend(upper);
+                    iter.seek_for_prev(start);
+                }
+                Unbounded => match get_upper_bound_option(&prefix) {
+                    Some(start) => iter.seek_for_prev(start),
+                    None => iter.seek_to_last(),
+                },
+            }
+        } else {
+            let mut start = prefix.clone();
+            if let Included(lower) | Excluded(lower) = &range.0 {
+                start.extend(lower);
+            }
+            iter.seek(start);
+        }
+        while remaining > 0 {
+            let (Some(key), Some(value)) = (iter.key(), iter.value()) else {
+                break;
+            };
+            if !key.starts_with(&prefix) {
+                // Iterating backwards, we may start from the first key after the prefix.
+                if !reverse || key < prefix.as_slice() {
+                    break;
+                }
+            } else {
+                let suffix = &key[len..];
+                let is_above = is_above_lower_bound(suffix, &range.0);
+                let is_below = is_below_upper_bound(suffix, &range.1);
+                if is_above && is_below {
+                    f(suffix, value);
+                    remaining -= 1;
+                } else if (reverse && !is_above) || (!reverse && !is_below) {
+                    break;
+                }
+            }
+            if reverse {
+                iter.prev();
+            } else {
+                iter.next();
+            }
+        }
+        iter.status()?;
+        Ok(())
+    }
+
+    fn find_keys_by_prefix_in_range_internal(
+        &self,
+        (key_prefix, range, reverse, limit): RangeQuery,
+    ) -> Result<Vec<Vec<u8>>, RocksDbStoreInternalError> {
+        let mut keys = Vec::new();
+        self.for_each_in_range(&key_prefix, &range, reverse, limit, |key, _value| {
+            keys.push(key.to_vec())
+        })?;
+        Ok(keys)
+    }
+
+    fn find_key_values_by_prefix_in_range_internal(
+        &self,
+        (key_prefix, range, reverse, limit): RangeQuery,
+    ) -> Result<Vec<(Vec<u8>, Vec<u8>)>, RocksDbStoreInternalError> {
+        let mut key_values = Vec::new();
+        self.for_each_in_range(&key_prefix, &range, reverse, limit, |key, value| {
+            key_values.push((key.to_vec(), value.to_vec()))
+        })?;
+        Ok(key_values)
+    }
+
     fn write_batch_internal(
         &self,
         batch: Batch,
@@ -451,6 +549,40 @@ impl ReadableKeyValueStore for RocksDbStoreInternal {
             )
             .await
     }
+
+    async fn find_keys_by_prefix_in_range(
+        &self,
+        key_prefix: &[u8],
+        range: (Bound<Vec<u8>>, Bound<Vec<u8>>),
+        reverse: bool,
+        limit: Option<usize>,
+    ) -> Result<Vec<Vec<u8>>, RocksDbStoreInternalError> {
+        let executor = self.executor.clone();
+        let key_prefix = key_prefix.to_vec();
+        self.spawn_mode
+            .spawn(
+                move |x| executor.find_keys_by_prefix_in_range_internal(x),
+                (key_prefix, range, reverse, limit),
+            )
+            .await
+    }
+
+    async fn find_key_values_by_prefix_in_range(
+        &self,
+        key_prefix: &[u8],
+        range: (Bound<Vec<u8>>, Bound<Vec<u8>>),
+        reverse: bool,
+        limit: Option<usize>,
+    ) -> Result<Vec<(Vec<u8>, Vec<u8>)>, RocksDbStoreInternalError> {
+        let executor = self.executor.clone();
+        let key_prefix = key_prefix.to_vec();
+        self.spawn_mode
+            .spawn(
+                move |x| executor.find_key_values_by_prefix_in_range_internal(x),
+                (key_prefix, range, reverse, limit),
+            )
+            .await
+    }
 }
 
 impl WritableKeyValueStore for RocksDbStoreInternal {
@@ -9,7 +9,7 @@
 
 use std::{
     collections::{hash_map::Entry, BTreeSet, HashMap},
-    ops::Deref,
+    ops::{Bound, Deref},
     sync::Arc,
 };
 
@@ -37,7 +37,7 @@ use crate::metering::MeteredStore;
 use crate::store::TestKeyValueStore;
 use crate::{
     batch::UnorderedBatch,
-    common::{get_uleb128_size, get_upper_bound_option},
+    common::{get_interval_in_range, get_uleb128_size, get_upper_bound_option, is_empty_range},
     journaling::{DirectWritableKeyValueStore, JournalConsistencyError, JournalingKeyValueStore},
     lru_caching::{LruCachingConfig, LruCachingStore},
     store::{
@@ -442,6 +442,117 @@ impl ScyllaDbClient {
         }
         Ok(key_values)
     }
+
+    /// Builds the query selecting `columns` for the keys of `key_prefix` whose remainder
+    /// lies in `range`, ordered as requested, together with its inputs.
+    fn get_range_query(
+        &self,
+        columns: &str,
+        root_key: &[u8],
+        key_prefix: &[u8],
+        range: &(Bound<Vec<u8>>, Bound<Vec<u8>>),
+        reverse: bool,
+        limit: Option<usize>,
+    ) -> (String, Vec<Vec<u8>>) {
+        let mut inputs = vec![root_key.to_vec()];
+        let mut conditions = Vec::new();
+        let (lower, upper) = get_interval_in_range(key_prefix, range);
+        match lower {
+            Bound::Included(lower) => {
+                conditions.push("k >= ?");
+                inputs.push(lower);
+            }
+            Bound::Excluded(lower) => {
+                conditions.push("k > ?");
+                inputs.push(lower);
+            }
+            Bound::Unbounded => {}
+        }
+        match upper {
+            Bound::Included(upper) => {
+                conditions.push("k <= ?");
+                inputs.push(upper);
+            }
+            Bound::Excluded(upper) => {
+                conditions.push("k < ?");
+                inputs.push(upper);
+            }
+            Bound::Unbounded => {}
+        }
+        let mut query = format!(
+            "SELECT {} FROM kv.{} WHERE root_key = ?",
+            columns, self.namespace
+        );
+        for condition in conditions {
+            query.push_str(" AND ");
+            query.push_str(condition);
+        }
+        query.push_str(if reverse {
+            " ORDER BY k DESC"
+        } else {
+            " ORDER BY k ASC"
+        });
+        if let Some(limit) = limit {
+            query.push_str(&format!(" LIMIT {}", limit));
+        }
+        (query, inputs)
+    }
+
+    async fn find_keys_by_prefix_in_range_internal(
+        &self,
+        root_key: &[u8],
+        key_prefix: Vec<u8>,
+        range: (Bound<Vec<u8>>, Bound<Vec<u8>>),
+        reverse: bool,
+        limit: Option<usize>,
+    ) -> Result<Vec<Vec<u8>>, ScyllaDbStoreInternalError> {
+        Self::check_key_size(&key_prefix)?;
+        if is_empty_range(&range) || limit == Some(0) {
+            return Ok(Vec::new());
+        }
+        let len = key_prefix.len();
+        let (query, inputs) =
+            self.get_range_query("k", root_key, &key_prefix, &range, reverse, limit);
+        let mut rows = self
+            .session
+            .query_iter(&*query, &inputs)
+            .await?
+            .rows_stream::<(Vec<u8>,)>()?;
+        let mut keys = Vec::new();
+        while let Some(row) = rows.next().await {
+            let (key,) = row?;
+            keys.push(key[len..].to_vec());
+        }
+        Ok(keys)
+    }
+
+    async fn find_key_values_by_prefix_in_range_internal(
+        &self,
+        root_key: &[u8],
+        key_prefix: Vec<u8>,
+        range: (Bound<Vec<u8>>, Bound<Vec<u8>>),
+        reverse: bool,
+        limit: Option<usize>,
+    ) -> Result<Vec<(Vec<u8>, Vec<u8>)>, ScyllaDbStoreInternalError> {
+        Self::check_key_size(&key_prefix)?;
+        if is_empty_range(&range) || limit == Some(0) {
+            return Ok(Vec::new());
+        }
+        let len = key_prefix.len();
+        let (query, inputs) =
+            self.get_range_query("k,v", root_key, &key_prefix, &range, reverse, limit);
+        let mut rows = self
+            .session
+            .query_iter(&*query, &inputs)
+            .await?
+            .rows_stream::<(Vec<u8>, Vec<u8>)>()?;
+        let mut key_values = Vec::new();
+        while let Some(row) = rows.next().await {
+            let (key, value) = row?;
+            key_values.push((key[len..].to_vec(), value));
+        }
+        Ok(key_values)
+    }
 }
 
 /// The client itself and the keeping of the count of active connections.
@@ -612,6 +723,46 @@ impl ReadableKeyValueStore for ScyllaDbStoreInternal {
             .find_key_values_by_prefix_internal(&self.root_key, key_prefix.to_vec())
             .await
     }
+
+    async fn find_keys_by_prefix_in_range(
+        &self,
+        key_prefix: &[u8],
+        range: (Bound<Vec<u8>>, Bound<Vec<u8>>),
+        reverse: bool,
+        limit: Option<usize>,
+    ) -> Result<Vec<Vec<u8>>, ScyllaDbStoreInternalError> {
+        let store = self.store.deref();
+        let _guard = self.acquire().await;
+        store
+            .find_keys_by_prefix_in_range_internal(
+                &self.root_key,
+                key_prefix.to_vec(),
+                range,
+                reverse,
+                limit,
+            )
+            .await
+    }
+
+    async fn find_key_values_by_prefix_in_range(
+        &self,
+        key_prefix: &[u8],
+        range: (Bound<Vec<u8>>, Bound<Vec<u8>>),
+        reverse: bool,
+        limit: Option<usize>,
+    ) -> Result<Vec<(Vec<u8>, Vec<u8>)>, ScyllaDbStoreInternalError> {
+        let store = self.store.deref();
+        let _guard = self.acquire().await;
+        store
+            .find_key_values_by_prefix_in_range_internal(
+                &self.root_key,
+                key_prefix.to_vec(),
+                range,
+                reverse,
+                limit,
+            )
+            .await
+    }
 }
 
 impl DirectWritableKeyValueStore for ScyllaDbStoreInternal {
@@ -0,0 +1,1036 @@
+// Copyright (c) Zefchain Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Implements [`crate::store::KeyValueStore`] by keeping the recently used root keys of a
+//! remote store in a local store.
+//!
+//! The remote store (e.g. ScyllaDB or DynamoDB) holds all the data. The root keys that are
+//! tiered according to a [`DualStoreRootKeyAssignment`] are copied to the local store
+//! (e.g. RocksDB) when they are accessed, and served from there. When the root keys
+//! resident in the local store take more than a given size, the least recently used ones
+//! are evicted. The copies between the stores are made in batches of bounded size.
+//!
+//! With [`WritePolicy::WriteThrough`], batches are written to the remote store and then
+//! to the local one. With [`WritePolicy::WriteBack`], batches are only written to the
+//! local store and the root key is marked as dirty in the local store. A dirty root key is
+//! written back to the remote store as a single batch when it is evicted, flushed, or
+//! loaded again after a restart.
+//!
+//! Every tiered root key must be written by a single process at a time: the copy in the local
+//! store is trusted without checking the remote store, and the remote store may only hold
+//! part of a write-back until it completes. Until then, the root key remains marked as
+//! dirty, so that the write-back is resumed after a restart. When a write to the remote
+//! store fails, the batch may still be applied when the journal is cleared, so the local
+//! copy is dropped and the root key is loaded again on the next access.
+//!
+//! A root key may also be read by processes other than the one writing it, e.g. when a
+//! chain queries the applications of another chain. With [`WritePolicy::WriteThrough`],
+//! they read it from the remote store, or from a local copy as recent as their last access.
+//! With [`WritePolicy::WriteBack`], the remote store misses the writes that were not
+//! written back yet, so the storage must then be used by a single process, e.g. a
+//! validator running all its shards in the same process.
+
+use std::{
+    collections::{BTreeSet, HashMap},
+    fmt,
+    ops::Bound,
+    str::FromStr,
+    sync::{Arc, Mutex},
+};
+
+use async_lock::{RwLock, RwLockReadGuardArc, RwLockWriteGuardArc};
+use linked_hash_map::LinkedHashMap;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    backends::dual::{
+        DualStoreError, DualStoreKeyValues, DualStoreKeys, DualStoreRootKeyAssignment, StoreInUse,
+    },
+    batch::{Batch, WriteOperation},
+    store::{
+        AdminKeyValueStore, KeyValueStore, ReadableKeyValueStore, WithError, WritableKeyValueStore,
+    },
+};
+#[cfg(with_testing)]
+use crate::{
+    memory::{MemoryStore, TEST_MEMORY_MAX_STREAM_QUERIES},
+    random::generate_test_namespace,
+    store::TestKeyValueStore,
+};
+
+/// The root key of the local store holding the state of the tiers.
+const METADATA_ROOT_KEY: &[u8] = b"tiered_store_metadata";
+
+/// The prefix of the keys marking the dirty root keys in the metadata.
+const DIRTY_TAG: u8 = 0;
+
+/// The default maximum size in bytes of the root keys resident in the local store.
+pub const DEFAULT_MAX_LOCAL_SIZE: u64 = 10 << 30;
+
+/// The maximum number of entries copied in a batch between the stores.
+const COPY_BATCH_SIZE: usize = 1000;
+
+/// How the writes reach the remote store.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum WritePolicy {
+    /// Every batch is written to the remote store, then to the local one.
+    #[default]
+    WriteThrough,
+    /// Batches are written to the local store only, and written back to the remote
+    /// store when the root key is evicted or flushed. The storage must then be used by a
+    /// single process, since the others do not see the writes until then.
+    WriteBack,
+}
+
+impl fmt::Display for WritePolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WritePolicy::WriteThrough => write!(f, "write_through"),
+            WritePolicy::WriteBack => write!(f, "write_back"),
+        }
+    }
+}
+
+impl FromStr for WritePolicy {
+    type Err = String;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        match input {
+            "write_through" => Ok(WritePolicy::WriteThrough),
+            "write_back" => Ok(WritePolicy::WriteBack),
+            _ => Err(format!("Failed to parse {input} as a write policy")),
+        }
+    }
+}
+
+/// The configuration of a `TieredStore`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TieredStoreConfig<C1, C2> {
+    /// The config of the local store.
+    pub local_config: C1,
+    /// The config of the remote store.
+    pub remote_config: C2,
+    /// The maximum size in bytes of the root keys resident in the local store.
+    pub max_local_size: u64,
+    /// How the writes reach the remote store. [`WritePolicy::WriteBack`] requires the
+    /// storage to be used by a single process.
+    pub write_policy: WritePolicy,
+}
+
+/// The state of a tiered root key.
+#[derive(Default)]
+struct RootKeyState {
+    /// Whether the local store holds the data of the root key.
+    is_resident: bool,
+    /// Whether the local store holds writes that are not in the remote store.
+    is_dirty: bool,
+}
+
+/// A root key resident in the local store.
+struct ResidentRootKey {
+    state: Arc<RwLock<RootKeyState>>,
+    /// The size of the data of the root key when it was copied, plus the size of the
+    /// values written since. This is an upper bound, as overwritten and deleted values are
+    /// not subtracted.
+    size: u64,
+}
+
+/// The index of the tiered root keys.
+#[derive(Default)]
+struct TierIndex {
+    /// The state of every tiered root key accessed so far.
+    states: HashMap<Vec<u8>, Arc<RwLock<RootKeyState>>>,
+    /// The resident root keys, from the least to the most recently used.
+    resident: LinkedHashMap<Vec<u8>, ResidentRootKey>,
+    /// The sum of the sizes of the resident root keys.
+    resident_size: u64,
+}
+
+/// The tiers shared by the stores cloned from the same connection.
+struct Tiers {
+    max_local_size: u64,
+    write_policy: WritePolicy,
+    index: Mutex<TierIndex>,
+}
+
+impl Tiers {
+    fn new(max_local_size: u64, write_policy: WritePolicy) -> Self {
+        Tiers {
+            max_local_size,
+            write_policy,
+            index: Mutex::new(TierIndex::default()),
+        }
+    }
+
+    /// Returns the state of `root_key`, marking it as the most recently used.
+    fn state(&self, root_key: &[u8]) -> Arc<RwLock<RootKeyState>> {
+        let mut index = self.index.lock().unwrap();
+        if let Some(resident) = index.resident.remove(root_key) {
+            index.resident.insert(root_key.to_vec(), resident);
+        }
+        index.states.entry(root_key.to_vec()).or_default().clone()
+    }
+
+    fn mark_resident(&self, root_key: &[u8], state: &mut RootKeyState, size: u64) {
+        state.is_resident = true;
+        let mut index = self.index.lock().unwrap();
+        let shared_state = index.states[root_key].clone();
+        let resident = ResidentRootKey {
+            state: shared_state,
+            size,
+        };
+        if let Some(previous) = index.resident.insert(root_key.to_vec(), resident) {
+            index.resident_size -= previous.size;
+        }
+        index.resident_size += size;
+    }
+
+    /// Accounts for `size` more bytes written to the resident `root_key`.
+    fn add_size(&self, root_key: &[u8], size: u64) {
+        let mut index = self.index.lock().unwrap();
+        if let Some(resident) = index.resident.get_mut(root_key) {
+            resident.size += size;
+            index.resident_size += size;
+        }
+    }
+
+    fn mark_evicted(&self, root_key: &[u8], state: &mut RootKeyState) {
+        state.is_resident = false;
+        let mut index = self.index.lock().unwrap();
+        if let Some(resident) = index.resident.remove(root_key) {
+            index.resident_size -= resident.size;
+        }
+    }
+
+    /// Returns the least recently used root keys to evict to fit in the maximum size.
+    fn eviction_candidates(&self) -> Vec<(Vec<u8>, Arc<RwLock<RootKeyState>>)> {
+        let index = self.index.lock().unwrap();
+        let mut excess_size = index.resident_size.saturating_sub(self.max_local_size);
+        let mut candidates = Vec::new();
+        for (root_key, resident) in index.resident.iter() {
+            if excess_size == 0 {
+                break;
+            }
+            excess_size = excess_size.saturating_sub(resident.size);
+            candidates.push((root_key.clone(), resident.state.clone()));
+        }
+        candidates
+    }
+
+    fn resident_root_keys(&self) -> Vec<(Vec<u8>, Arc<RwLock<RootKeyState>>)> {
+        let index = self.index.lock().unwrap();
+        index
+            .resident
+            .iter()
+            .map(|(root_key, resident)| (root_key.clone(), resident.state.clone()))
+            .collect()
+    }
+}
+
+/// A store keeping the recently used root keys of a remote store in a local store.
+///
+/// Every tiered root key must be written by a single process at a time, e.g. the chain
+/// worker of the shard owning the chain. Otherwise, the processes read stale copies and
+/// overwrite the writes of each other. With [`WritePolicy::WriteBack`], the other processes
+/// do not even see the writes that were not written back, so all the root keys must be
+/// used by a single process.
+#[derive(Clone)]
+pub struct TieredStore<S1, S2, A> {
+    /// The local store.
+    local_store: S1,
+    /// The remote store.
+    remote_store: S2,
+    /// The local store holding the state of the tiers.
+    metadata_store: S1,
+    /// The current root key.
+    root_key: Vec<u8>,
+    /// Whether the current root key is tiered. Otherwise, only the remote store is used.
+    is_tiered: bool,
+    /// The tiered root keys.
+    tiers: Arc<Tiers>,
+    /// Marker for the static root key assignment.
+    _marker: std::marker::PhantomData<A>,
+}
+
+impl<S1, S2, A> WithError for TieredStore<S1, S2, A>
+where
+    S1: WithError,
+    S2: WithError,
+{
+    type Error = DualStoreError<S1::Error, S2::Error>;
+}
+
+impl<S1, S2, A> ReadableKeyValueStore for TieredStore<S1, S2, A>
+where
+    S1: KeyValueStore + Send + Sync,
+    S2: KeyValueStore + Send + Sync,
+    A: Send + Sync,
+{
+    const MAX_KEY_SIZE: usize = if S1::MAX_KEY_SIZE < S2::MAX_KEY_SIZE {
+        S1::MAX_KEY_SIZE
+    } else {
+        S2::MAX_KEY_SIZE
+    };
+
+    type Keys = DualStoreKeys<S1::Keys, S2::Keys>;
+    type KeyValues = DualStoreKeyValues<S1::KeyValues, S2::KeyValues>;
+
+    fn max_stream_queries(&self) -> usize {
+        if self.is_tiered {
+            self.local_store.max_stream_queries()
+        } else {
+            self.remote_store.max_stream_queries()
+        }
+    }
+
+    async fn read_value_bytes(&self, key: &[u8]) -> Result<Option<Vec<u8>>, Self::Error> {
+        if !self.is_tiered {
+            return self
+                .remote_store
+                .read_value_bytes(key)
+                .await
+                .map_err(DualStoreError::Second);
+        }
+        let _state = self.resident_state().await?;
+        self.local_store
+            .read_value_bytes(key)
+            .await
+            .map_err(DualStoreError::First)
+    }
+
+    async fn contains_key(&self, key: &[u8]) -> Result<bool, Self::Error> {
+        if !self.is_tiered {
+            return self
+                .remote_store
+                .contains_key(key)
+                .await
+                .map_err(DualStoreError::Second);
+        }
+        let _state = self.resident_state().await?;
+        self.local_store
+            .contains_key(key)
+            .await
+            .map_err(DualStoreError::First)
+    }
+
+    async fn contains_keys(&self, keys: Vec<Vec<u8>>) -> Result<Vec<bool>, Self::Error> {
+        if !self.is_tiered {
+            return self
+                .remote_store
+                .contains_keys(keys)
+                .await
+                .map_err(DualStoreError::Second);
+        }
+        let _state = self.resident_state().await?;
+        self.local_store
+            .contains_keys(keys)
+            .await
+            .map_err(DualStoreError::First)
+    }
+
+    async fn read_multi_values_bytes(
+        &self,
+        keys: Vec<Vec<u8>>,
+    ) -> Result<Vec<Option<Vec<u8>>>, Self::Error> {
+        if !self.is_tiered {
+            return self
+                .remote_store
+                .read_multi_values_bytes(keys)
+                .await
+                .map_err(DualStoreError::Second);
+        }
+        let _state = self.resident_state().await?;
+        self.local_store
+            .read_multi_values_bytes(keys)
+            .await
+            .map_err(DualStoreError::First)
+    }
+
+    async fn find_keys_by_prefix(&self, key_prefix: &[u8]) -> Result<Self::Keys, Self::Error> {
+        if !self.is_tiered {
+            return Ok(DualStoreKeys::Second(
+                self.remote_store
+                    .find_keys_by_prefix(key_prefix)
+                    .await
+                    .map_err(DualStoreError::Second)?,
+            ));
+        }
+        let _state = self.resident_state().await?;
+        Ok(DualStoreKeys::First(
+            self.local_store
+                .find_keys_by_prefix(key_prefix)
+                .await
+                .map_err(DualStoreError::First)?,
+        ))
+    }
+
+    async fn find_key_values_by_prefix(
+        &self,
+        key_prefix: &[u8],
+    ) -> Result<Self::KeyValues, Self::Error> {
+        if !self.is_tiered {
+            return Ok(DualStoreKeyValues::Second(
+                self.remote_store
+                    .find_key_values_by_prefix(key_prefix)
+                    .await
+                    .map_err(DualStoreError::Second)?,
+            ));
+        }
+        let _state = self.resident_state().await?;
+        Ok(DualStoreKeyValues::First(
+            self.local_store
+                .find_key_values_by_prefix(key_prefix)
+                .await
+                .map_err(DualStoreError::First)?,
+        ))
+    }
+
+    async fn find_keys_by_prefix_in_range(
+        &self,
+        key_prefix: &[u8],
+        range: (Bound<Vec<u8>>, Bound<Vec<u8>>),
+        reverse: bool,
+        limit: Option<usize>,
+    ) -> Result<Vec<Vec<u8>>, Self::Error> {
+        if !self.is_tiered {
+            return self
+                .remote_store
+                .find_keys_by_prefix_in_range(key_prefix, range, reverse, limit)
+                .await
+                .map_err(DualStoreError::Second);
+        }
+        let _state = self.resident_state().await?;
+        self.local_store
+            .find_keys_by_prefix_in_range(key_prefix, range, reverse, limit)
+            .await
+            .map_err(DualStoreError::First)
+    }
+
+    async fn find_key_values_by_prefix_in_range(
+        &self,
+        key_prefix: &[u8],
+        range: (Bound<Vec<u8>>, Bound<Vec<u8>>),
+        reverse: bool,
+        limit: Option<usize>,
+    ) -> Result<Vec<(Vec<u8>, Vec<u8>)>, Self::Error> {
+        if !self.is_tiered {
+            return self
+                .remote_store
+                .find_key_values_by_prefix_in_range(key_prefix, range, reverse, limit)
+                .await
+                .map_err(DualStoreError::Second);
+        }
+        let _state = self.resident_state().await?;
+        self.local_store
+            .find_key_values_by_prefix_in_range(key_prefix, range, reverse, limit)
+            .await
+            .map_err(DualStoreError::First)
+    }
+}
+
+impl<S1, S2, A> WritableKeyValueStore for TieredStore<S1, S2, A>
+where
+    S1: KeyValueStore + Send + Sync,
+    S2: KeyValueStore + Send + Sync,
+    A: Send + Sync,
+{
+    const MAX_VALUE_SIZE: usize = if S1::MAX_VALUE_SIZE < S2::MAX_VALUE_SIZE {
+        S1::MAX_VALUE_SIZE
+    } else {
+        S2::MAX_VALUE_SIZE
+    };
+
+    async fn write_batch(&self, batch: Batch) -> Result<(), Self::Error> {
+        if !self.is_tiered {
+            return self
+                .remote_store
+                .write_batch(batch)
+                .await
+                .map_err(DualStoreError::Second);
+        }
+        let state = self.tiers.state(&self.root_key);
+        let mut state = state.write_arc().await;
+        if !state.is_resident {
+            self.load(&mut state).await?;
+        }
+        let written_size = written_size(&batch);
+        match self.tiers.write_policy {
+            WritePolicy::WriteThrough => {
+                if let Err(error) = self.remote_store.write_batch(batch.clone()).await {
+                    self.tiers.mark_evicted(&self.root_key, &mut state);
+                    return Err(DualStoreError::Second(error));
+                }
+                if let Err(error) = self.local_store.write_batch(batch).await {
+                    self.tiers.mark_evicted(&self.root_key, &mut state);
+                    return Err(DualStoreError::First(error));
+                }
+            }
+            WritePolicy::WriteBack => {
+                if !state.is_dirty {
+                    let mut metadata_batch = Batch::new();
+                    metadata_batch.put_key_value_bytes(dirty_key(&self.root_key), Vec::new());
+                    self.metadata_store
+                        .write_batch(metadata_batch)
+                        .await
+                        .map_err(DualStoreError::First)?;
+                    state.is_dirty = true;
+                }
+                self.local_store
+                    .write_batch(batch)
+                    .await
+                    .map_err(DualStoreError::First)?;
+            }
+        }
+        self.tiers.add_size(&self.root_key, written_size);
+        drop(state);
+        self.evict_least_recently_used().await
+    }
+
+    async fn clear_journal(&self) -> Result<(), Self::Error> {
+        self.remote_store
+            .clear_journal()
+            .await
+            .map_err(DualStoreError::Second)?;
+        if self.is_tiered {
+            self.local_store
+                .clear_journal()
+                .await
+                .map_err(DualStoreError::First)?;
+        }
+        Ok(())
+    }
+}
+
+impl<S1, S2, A> AdminKeyValueStore for TieredStore<S1, S2, A>
+where
+    S1: KeyValueStore + Send + Sync,
+    S2: KeyValueStore + Send + Sync,
+    A: DualStoreRootKeyAssignment + Send + Sync,
+{
+    type Config = TieredStoreConfig<S1::Config, S2::Config>;
+
+    fn get_name() -> String {
+        format!("tiered {} over {}", S1::get_name(), S2::get_name())
+    }
+
+    async fn connect(config: &Self::Config, namespace: &str) -> Result<Self, Self::Error> {
+        let local_store = S1::connect(&config.local_config, namespace)
+            .await
+            .map_err(DualStoreError::First)?;
+        let remote_store = S2::connect(&config.remote_config, namespace)
+            .await
+            .map_err(DualStoreError::Second)?;
+        Self::new(
+            local_store,
+            remote_store,
+            config.max_local_size,
+            config.write_policy,
+        )
+    }
+
+    fn clone_with_root_key(&self, root_key: &[u8]) -> Result<Self, Self::Error> {
+        let local_store = self
+            .local_store
+            .clone_with_root_key(root_key)
+            .map_err(DualStoreError::First)?;
+        let remote_store = self
+            .remote_store
+            .clone_with_root_key(root_key)
+            .map_err(DualStoreError::Second)?;
+        let is_tiered = matches!(A::assigned_store(root_key)?, StoreInUse::First);
+        Ok(Self {
+            local_store,
+            remote_store,
+            metadata_store: self.metadata_store.clone(),
+            root_key: root_key.to_vec(),
+            is_tiered,
+            tiers: self.tiers.clone(),
+            _marker: std::marker::PhantomData,
+        })
+    }
+
+    async fn list_all(config: &Self::Config) -> Result<Vec<String>, Self::Error> {
+        // The local store only holds copies, so it may not know about all the namespaces.
+        S2::list_all(&config.remote_config)
+            .await
+            .map_err(DualStoreError::Second)
+    }
+
+    async fn list_root_keys(
+        config: &Self::Config,
+        namespace: &str,
+    ) -> Result<Vec<Vec<u8>>, Self::Error> {
+        // Root keys that were never written back only exist in the local store.
+        let mut root_keys = S2::list_root_keys(&config.remote_config, namespace)
+            .await
+            .map_err(DualStoreError::Second)?
+            .into_iter()
+            .collect::<BTreeSet<_>>();
+        root_keys.extend(
+            S1::list_root_keys(&config.local_config, namespace)
+                .await
+                .map_err(DualStoreError::First)?,
+        );
+        root_keys.remove(METADATA_ROOT_KEY);
+        Ok(root_keys.into_iter().collect())
+    }
+
+    async fn exists(config: &Self::Config, namespace: &str) -> Result<bool, Self::Error> {
+        Ok(S1::exists(&config.local_config, namespace)
+            .await
+            .map_err(DualStoreError::First)?
+            && S2::exists(&config.remote_config, namespace)
+                .await
+                .map_err(DualStoreError::Second)?)
+    }
+
+    async fn create(config: &Self::Config, namespace: &str) -> Result<(), Self::Error> {
+        let exists1 = S1::exists(&config.local_config, namespace)
+            .await
+            .map_err(DualStoreError::First)?;
+        let exists2 = S2::exists(&config.remote_config, namespace)
+            .await
+            .map_err(DualStoreError::Second)?;
+        if exists1 && exists2 {
+            return Err(DualStoreError::StoreAlreadyExists);
+        }
+        if !exists1 {
+            S1::create(&config.local_config, namespace)
+                .await
+                .map_err(DualStoreError::First)?;
+        }
+        if !exists2 {
+            S2::create(&config.remote_config, namespace)
+                .await
+                .map_err(DualStoreError::Second)?;
+        }
+        Ok(())
+    }
+
+    async fn delete(config: &Self::Config, namespace: &str) -> Result<(), Self::Error> {
+        S1::delete(&config.local_config, namespace)
+            .await
+            .map_err(DualStoreError::First)?;
+        S2::delete(&config.remote_config, namespace)
+            .await
+            .map_err(DualStoreError::Second)?;
+        Ok(())
+    }
+}
+
+#[cfg(with_testing)]
+impl<S1, S2, A> TestKeyValueStore for TieredStore<S1, S2, A>
+where
+    S1: TestKeyValueStore + Send + Sync,
+    S2: TestKeyValueStore + Send + Sync,
+    A: DualStoreRootKeyAssignment + Send + Sync,
+{
+    async fn new_test_config() -> Result<Self::Config, Self::Error> {
+        let local_config = S1::new_test_config().await.map_err(DualStoreError::First)?;
+        let remote_config = S2::new_test_config()
+            .await
+            .map_err(DualStoreError::Second)?;
+        Ok(TieredStoreConfig {
+            local_config,
+            remote_config,
+            max_local_size: TEST_MAX_LOCAL_SIZE,
+            write_policy: WritePolicy::WriteBack,
+        })
+    }
+}
+
+fn dirty_key(root_key: &[u8]) -> Vec<u8> {
+    let mut key = vec![DIRTY_TAG];
+    key.extend(root_key);
+    key
+}
+
+/// Returns the size of the keys and values put by `batch`.
+fn written_size(batch: &Batch) -> u64 {
+    batch
+        .operations
+        .iter()
+        .map(|operation| match operation {
+            WriteOperation::Put { key, value } => (key.len() + value.len()) as u64,
+            WriteOperation::Delete { .. } | WriteOperation::DeletePrefix { .. } => 0,
+        })
+        .sum()
+}
+
+/// Replaces all the data of `target` by the one of `source`, in batches of at most
+/// `COPY_BATCH_SIZE` entries, and returns the size of the data.
+async fn copy_data<S, T, E>(
+    source: &S,
+    target: &T,
+    source_error: impl Fn(S::Error) -> E,
+    target_error: impl Fn(T::Error) -> E,
+) -> Result<u64, E>
+where
+    S: ReadableKeyValueStore + Sync,
+    T: WritableKeyValueStore,
+{
+    let mut size = 0;
+    let mut batch = Batch::new();
+    batch.delete_key_prefix(Vec::new());
+    let mut lower_bound = Bound::Unbounded;
+    loop {
+        let key_values = source
+            .find_key_values_by_prefix_in_range(
+                &[],
+                (lower_bound, Bound::Unbounded),
+                false,
+                Some(COPY_BATCH_SIZE),
+            )
+            .await
+            .map_err(&source_error)?;
+        let is_last = key_values.len() < COPY_BATCH_SIZE;
+        lower_bound = match key_values.last() {
+            Some((key, _)) => Bound::Excluded(key.clone()),
+            None => Bound::Unbounded,
+        };
+        for (key, value) in key_values {
+            size += (key.len() + value.len()) as u64;
+            batch.put_key_value_bytes(key, value);
+        }
+        target
+            .write_batch(std::mem::take(&mut batch))
+            .await
+            .map_err(&target_error)?;
+        if is_last {
+            return Ok(size);
+        }
+    }
+}
+
+impl<S1, S2, A> TieredStore<S1, S2, A>
+where
+    S1: KeyValueStore + Send + Sync,
+    S2: KeyValueStore + Send + Sync,
+    A: DualStoreRootKeyAssignment + Send + Sync,
+{
+    /// Creates a tiered store from two stores connected to the same namespace.
+    pub fn new(
+        local_store: S1,
+        remote_store: S2,
+        max_local_size: u64,
+        write_policy: WritePolicy,
+    ) -> Result<Self, DualStoreError<S1::Error, S2::Error>> {
+        let metadata_store = local_store
+            .clone_with_root_key(METADATA_ROOT_KEY)
+            .map_err(DualStoreError::First)?;
+        let is_tiered = matches!(A::assigned_store(&[])?, StoreInUse::First);
+        let tiers = Arc::new(Tiers::new(max_local_size, write_policy));
+        Ok(Self {
+            local_store,
+            remote_store,
+            metadata_store,
+            root_key: Vec::new(),
+            is_tiered,
+            tiers,
+            _marker: std::marker::PhantomData,
+        })
+    }
+}
+
+impl<S1, S2, A> TieredStore<S1, S2, A>
+where
+    S1: KeyValueStore + Send + Sync,
+    S2: KeyValueStore + Send + Sync,
+    A: Send + Sync,
+{
+    /// Writes back to the remote store all the resident root keys that are dirty.
+    pub async fn flush(&self) -> Result<(), DualStoreError<S1::Error, S2::Error>> {
+        for (root_key, state) in self.tiers.resident_root_keys() {
+            let mut state = state.write_arc().await;
+            if state.is_resident && state.is_dirty {
+                let local_store = self
+                    .local_store
+                    .clone_with_root_key(&root_key)
+                    .map_err(DualStoreError::First)?;
+                let remote_store = self
+                    .remote_store
+                    .clone_with_root_key(&root_key)
+                    .map_err(DualStoreError::Second)?;
+                self.write_back(&root_key, &local_store, &remote_store, &mut state)
+                    .await?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the state of the current root key, once it is resident in the local store.
+    async fn resident_state(
+        &self,
+    ) -> Result<RwLockReadGuardArc<RootKeyState>, DualStoreError<S1::Error, S2::Error>> {
+        let state = self.tiers.state(&self.root_key);
+        let read_state = state.read_arc().await;
+        if read_state.is_resident {
+            return Ok(read_state);
+        }
+        drop(read_state);
+        let mut write_state = state.write_arc().await;
+        if !write_state.is_resident {
+            self.load(&mut write_state).await?;
+        }
+        let read_state = RwLockWriteGuardArc::downgrade(write_state);
+        self.evict_least_recently_used().await?;
+        Ok(read_state)
+    }
+
+    /// Makes the current root key resident in the local store.
+    async fn load(
+        &self,
+        state: &mut RootKeyState,
+    ) -> Result<(), DualStoreError<S1::Error, S2::Error>> {
+        let is_dirty = self
+            .metadata_store
+            .contains_key(&dirty_key(&self.root_key))
+            .await
+            .map_err(DualStoreError::First)?;
+        let size = if is_dirty {
+            // The writes of a previous run were not all written back: the local store is
+            // authoritative.
+            state.is_dirty = true;
+            self.write_back(&self.root_key, &self.local_store, &self.remote_store, state)
+                .await?
+        } else {
+            self.remote_store
+                .clear_journal()
+                .await
+                .map_err(DualStoreError::Second)?;
+            // Any leftover of a previous residency is removed first. If the copy is
+            // interrupted, the root key is not resident and is loaded again.
+            copy_data(
+                &self.remote_store,
+                &self.local_store,
+                DualStoreError::Second,
+                DualStoreError::First,
+            )
+            .await?
+        };
+        self.tiers.mark_resident(&self.root_key, state, size);
+        Ok(())
+    }
+
+    /// Replaces the data of `root_key` in the remote store by the one of the local store,
+    /// and returns its size. The root key remains dirty until the copy is complete.
+    async fn write_back(
+        &self,
+        root_key: &[u8],
+        local_store: &S1,
+        remote_store: &S2,
+        state: &mut RootKeyState,
+    ) -> Result<u64, DualStoreError<S1::Error, S2::Error>> {
+        let size = copy_data(
+            local_store,
+            remote_store,
+            DualStoreError::First,
+            DualStoreError::Second,
+        )
+        .await?;
+        let mut metadata_batch = Batch::new();
+        metadata_batch.delete_key(dirty_key(root_key));
+        self.metadata_store
+            .write_batch(metadata_batch)
+            .await
+            .map_err(DualStoreError::First)?;
+        state.is_dirty = false;
+        Ok(size)
+    }
+
+    /// Evicts the least recently used root keys until the resident ones fit in the maximum
+    /// size. The root keys that are in use are skipped.
+    async fn evict_least_recently_used(&self) -> Result<(), DualStoreError<S1::Error, S2::Error>> {
+        for (root_key, state) in self.tiers.eviction_candidates() {
+            let Some(mut state) = state.try_write_arc() else {
+                continue;
+            };
+            if !state.is_resident {
+                continue;
+            }
+            let local_store = self
+                .local_store
+                .clone_with_root_key(&root_key)
+                .map_err(DualStoreError::First)?;
+            if state.is_dirty {
+                let remote_store = self
+                    .remote_store
+                    .clone_with_root_key(&root_key)
+                    .map_err(DualStoreError::Second)?;
+                self.write_back(&root_key, &local_store, &remote_store, &mut state)
+                    .await?;
+            }
+            self.tiers.mark_evicted(&root_key, &mut state);
+            let mut batch = Batch::new();
+            batch.delete_key_prefix(Vec::new());
+            local_store
+                .write_batch(batch)
+                .await
+                .map_err(DualStoreError::First)?;
+        }
+        Ok(())
+    }
+}
+
+/// The root key assignment keeping every root key in the local store.
+#[derive(Clone, Copy)]
+pub struct AllRootKeysTiered;
+
+impl DualStoreRootKeyAssignment for AllRootKeysTiered {
+    fn assigned_store(_root_key: &[u8]) -> Result<StoreInUse, bcs::Error> {
+        Ok(StoreInUse::First)
+    }
+}
+
+/// The maximum size of the local store in tests, small enough to evict root keys often.
+#[cfg(with_testing)]
+const TEST_MAX_LOCAL_SIZE: u64 = 10_000;
+
+/// Provides a `TieredStore` over two memory stores that can be used for tests.
+#[cfg(with_testing)]
+pub fn create_tiered_memory_store(
+    write_policy: WritePolicy,
+) -> TieredStore<MemoryStore, MemoryStore, AllRootKeysTiered> {
+    // The stores of the root keys are cloned and dropped by the tiered store, so the
+    // memory stores must not delete their data on drop.
+    let local_store =
+        MemoryStore::new(TEST_MEMORY_MAX_STREAM_QUERIES, &generate_test_namespace()).unwrap();
+    let remote_store =
+        MemoryStore::new(TEST_MEMORY_MAX_STREAM_QUERIES, &generate_test_namespace()).unwrap();
+    TieredStore::new(local_store, remote_store, TEST_MAX_LOCAL_SIZE, write_policy).unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use linera_views::{
+        backends::tiered::{AllRootKeysTiered, TieredStore, WritePolicy},
+        batch::Batch,
+        memory::{MemoryStore, TEST_MEMORY_MAX_STREAM_QUERIES},
+        random::generate_test_namespace,
+        store::{AdminKeyValueStore as _, ReadableKeyValueStore as _, WritableKeyValueStore as _},
+    };
+
+    /// The size of two of the root keys written by `write_root_keys`.
+    const MAX_LOCAL_SIZE: u64 = 6;
+
+    fn new_memory_store() -> MemoryStore {
+        MemoryStore::new(TEST_MEMORY_MAX_STREAM_QUERIES, &generate_test_namespace()).unwrap()
+    }
+
+    async fn write_root_keys<S: linera_views::store::KeyValueStore>(store: &S, count: u8) {
+        for index in 0..count {
+            let store = store.clone_with_root_key(&[index]).unwrap();
+            let mut batch = Batch::new();
+            batch.put_key_value_bytes(vec![1, index], vec![index]);
+            store.write_batch(batch).await.unwrap();
+        }
+    }
+
+    #[tokio::test]
+    async fn test_tiered_store_write_through() {
+        let local_store = new_memory_store();
+        let remote_store = new_memory_store();
+        let store = TieredStore::<_, _, AllRootKeysTiered>::new(
+            local_store.clone(),
+            remote_store.clone(),
+            MAX_LOCAL_SIZE,
+            WritePolicy::WriteThrough,
+        )
+        .unwrap();
+        write_root_keys(&store, 3).await;
+        // Every batch reached the remote store, and the first root key was evicted.
+        for index in 0..3 {
+            let remote_store = remote_store.clone_with_root_key(&[index]).unwrap();
+            let value = remote_store.read_value_bytes(&[1, index]).await.unwrap();
+            assert_eq!(value, Some(vec![index]));
+        }
+        let evicted_store = local_store.clone_with_root_key(&[0]).unwrap();
+        assert!(evicted_store
+            .find_keys_by_prefix(&[])
+            .await
+            .unwrap()
+            .is_empty());
+        // The evicted root key is loaded again on demand.
+        let store = store.clone_with_root_key(&[0]).unwrap();
+        let value = store.read_value_bytes(&[1, 0]).await.unwrap();
+        assert_eq!(value, Some(vec![0]));
+        let value = evicted_store.read_value_bytes(&[1, 0]).await.unwrap();
+        assert_eq!(value, Some(vec![0]));
+    }
+
+    #[tokio::test]
+    async fn test_tiered_store_write_back() {
+        let local_store = new_memory_store();
+        let remote_store = new_memory_store();
+        let store = TieredStore::<_, _, AllRootKeysTiered>::new(
+            local_store.clone(),
+            remote_store.clone(),
+            MAX_LOCAL_SIZE,
+            WritePolicy::WriteBack,
+        )
+        .unwrap();
+        write_root_keys(&store, 3).await;
+        // Only the evicted root key was written back.
+        for index in 0..3 {
+            let remote_store = remote_store.clone_with_root_key(&[index]).unwrap();
+            let value = remote_store.read_value_bytes(&[1, index]).await.unwrap();
+            assert_eq!(value.is_some(), index == 0);
+        }
+        // After a restart, the dirty root keys are written back when they are loaded.
+        let store = TieredStore::<_, _, AllRootKeysTiered>::new(
+            local_store,
+            remote_store.clone(),
+            MAX_LOCAL_SIZE,
+            WritePolicy::WriteBack,
+        )
+        .unwrap();
+        let tiered_store = store.clone_with_root_key(&[2]).unwrap();
+        let value = tiered_store.read_value_bytes(&[1, 2]).await.unwrap();
+        assert_eq!(value, Some(vec![2]));
+        let remote_store_2 = remote_store.clone_with_root_key(&[2]).unwrap();
+        let value = remote_store_2.read_value_bytes(&[1, 2]).await.unwrap();
+        assert_eq!(value, Some(vec![2]));
+        // Flushing writes back the remaining dirty root keys.
+        let tiered_store = store.clone_with_root_key(&[1]).unwrap();
+        let mut batch = Batch::new();
+        batch.put_key_value_bytes(vec![1, 1], vec![42]);
+        tiered_store.write_batch(batch).await.unwrap();
+        store.flush().await.unwrap();
+        let remote_store_1 = remote_store.clone_with_root_key(&[1]).unwrap();
+        let value = remote_store_1.read_value_bytes(&[1, 1]).await.unwrap();
+        assert_eq!(value, Some(vec![42]));
+    }
+
+    #[tokio::test]
+    async fn test_tiered_store_copies_in_batches() {
+        let local_store = new_memory_store();
+        let remote_store = new_memory_store();
+        let store = TieredStore::<_, _, AllRootKeysTiered>::new(
+            local_store.clone(),
+            remote_store.clone(),
+            10_000,
+            WritePolicy::WriteBack,
+        )
+        .unwrap();
+        // The root key takes more than the maximum size, so it is evicted right after it
+        // is written, and written back in several batches.
+        let large_store = store.clone_with_root_key(&[0]).unwrap();
+        let keys = (0..2500u32)
+            .map(|index| index.to_be_bytes().to_vec())
+            .collect::<Vec<_>>();
+        let mut batch = Batch::new();
+        for key in &keys {
+            batch.put_key_value_bytes(key.clone(), vec![1]);
+        }
+        large_store.write_batch(batch).await.unwrap();
+        let remote_large_store = remote_store.clone_with_root_key(&[0]).unwrap();
+        let remote_keys = remote_large_store.find_keys_by_prefix(&[]).await.unwrap();
+        assert_eq!(remote_keys, keys);
+        let local_large_store = local_store.clone_with_root_key(&[0]).unwrap();
+        let local_keys = local_large_store.find_keys_by_prefix(&[]).await.unwrap();
+        assert!(local_keys.is_empty());
+        // It is also loaded again in several batches.
+        let values = large_store.read_multi_values_bytes(keys).await.unwrap();
+        assert!(values.iter().all(|value| value == &Some(vec![1])));
+    }
+}
@@ -3,6 +3,8 @@
 
 //! Adds support for large values to a given store by splitting them between several keys.
 
+use std::ops::Bound;
+
 use linera_base::ensure;
 use thiserror::Error;
 
@@ -231,6 +233,78 @@ where
         }
         Ok(key_values)
     }
+
+    async fn find_keys_by_prefix_in_range(
+        &self,
+        key_prefix: &[u8],
+        range: (Bound<Vec<u8>>, Bound<Vec<u8>>),
+        reverse: bool,
+        limit: Option<usize>,
+    ) -> Result<Vec<Vec<u8>>, Self::Error> {
+        // Appending the first segment index preserves the order of the keys, so the first
+        // segments of the keys in `range` are exactly those in the extended range.
+        let mut range = (
+            Self::get_first_segment_bound(range.0)?,
+            Self::get_first_segment_bound(range.1)?,
+        );
+        let mut keys = Vec::new();
+        // The other segments count against the limit of the underlying store, so we read
+        // pages until we have `limit` keys or the range is exhausted.
+        loop {
+            let remaining = limit.map(|limit| limit - keys.len());
+            let big_keys = self
+                .store
+                .find_keys_by_prefix_in_range(key_prefix, range.clone(), reverse, remaining)
+                .await?;
+            let is_exhausted = remaining.is_none_or(|remaining| big_keys.len() < remaining);
+            let last_key = big_keys.last().cloned();
+            for mut big_key in big_keys {
+                if Self::read_index_from_key(&big_key)? == 0 {
+                    big_key.truncate(big_key.len() - 4);
+                    keys.push(big_key);
+                }
+            }
+            let Some(last_key) = last_key else {
+                break;
+            };
+            if is_exhausted || limit == Some(keys.len()) {
+                break;
+            }
+            if reverse {
+                range.1 = Bound::Excluded(last_key);
+            } else {
+                range.0 = Bound::Excluded(last_key);
+            }
+        }
+        Ok(keys)
+    }
+
+    async fn find_key_values_by_prefix_in_range(
+        &self,
+        key_prefix: &[u8],
+        range: (Bound<Vec<u8>>, Bound<Vec<u8>>),
+        reverse: bool,
+        limit: Option<usize>,
+    ) -> Result<Vec<(Vec<u8>, Vec<u8>)>, Self::Error> {
+        // The segments of the last values may lie outside of the extended range, so we read
+        // the values by their keys.
+        let keys = self
+            .find_keys_by_prefix_in_range(key_prefix, range, reverse, limit)
+            .await?;
+        let full_keys = keys
+            .iter()
+            .map(|key| {
+                let mut full_key = key_prefix.to_vec();
+                full_key.extend(key);
+                full_key
+            })
+            .collect();
+        let values = self.read_multi_values_bytes(full_keys).await?;
+        keys.into_iter()
+            .zip(values)
+            .map(|(key, value)| Ok((key, value.ok_or(ValueSplittingError::MissingSegment)?)))
+            .collect()
+    }
 }
 
 impl<K> WritableKeyValueStore for ValueSplittingStore<K>
@@ -355,6 +429,16 @@ where
         Ok(big_key_segment)
     }
 
+    fn get_first_segment_bound(
+        bound: Bound<Vec<u8>>,
+    ) -> Result<Bound<Vec<u8>>, ValueSplittingError<K::Error>> {
+        Ok(match bound {
+            Bound::Included(key) => Bound::Included(Self::get_segment_key(&key, 0)?),
+            Bound::Excluded(key) => Bound::Excluded(Self::get_segment_key(&key, 0)?),
+            Bound::Unbounded => Bound::Unbounded,
+        })
+    }
+
     fn get_initial_count_first_chunk(
         count: u32,
         first_chunk: &[u8],
@@ -4,10 +4,11 @@
 //! This provides some common code for the linera-views.
 
 use std::{
-    collections::BTreeSet,
+    collections::{BTreeMap, BTreeSet},
     ops::{
         Bound,
         Bound::{Excluded, Included, Unbounded},
+        RangeBounds,
     },
 };
 
@@ -101,6 +102,234 @@ pub(crate) fn get_interval(key_prefix: Vec<u8>) -> (Bound<Vec<u8>>, Bound<Vec<u8
     (Included(key_prefix), upper_bound)
 }
 
+/// Converts a range of keys into its owned bounds.
+pub(crate) fn get_range_bounds<R: RangeBounds<Vec<u8>>>(
+    range: R,
+) -> (Bound<Vec<u8>>, Bound<Vec<u8>>) {
+    (range.start_bound().cloned(), range.end_bound().cloned())
+}
+
+/// Converts a range of indices into the bounds of their serializations.
+pub(crate) fn serialize_range_bounds<Q, R, F>(
+    range: R,
+    serialize: F,
+) -> Result<(Bound<Vec<u8>>, Bound<Vec<u8>>), ViewError>
+where
+    Q: ?Sized,
+    R: RangeBounds<Q>,
+    F: Fn(&Q) -> Result<Vec<u8>, ViewError>,
+{
+    let serialize_bound = |bound: Bound<&Q>| -> Result<Bound<Vec<u8>>, ViewError> {
+        Ok(match bound {
+            Included(index) => Included(serialize(index)?),
+            Excluded(index) => Excluded(serialize(index)?),
+            Unbounded => Unbounded,
+        })
+    };
+    Ok((
+        serialize_bound(range.start_bound())?,
+        serialize_bound(range.end_bound())?,
+    ))
+}
+
+/// Tests whether `key` is above the `lower` bound of a range.
+pub(crate) fn is_above_lower_bound(key: &[u8], lower: &Bound<Vec<u8>>) -> bool {
+    match lower {
+        Included(lower) => key >= lower.as_slice(),
+        Excluded(lower) => key > lower.as_slice(),
+        Unbounded => true,
+    }
+}
+
+/// Tests whether `key` is below the `upper` bound of a range.
+pub(crate) fn is_below_upper_bound(key: &[u8], upper: &Bound<Vec<u8>>) -> bool {
+    match upper {
+        Included(upper) => key <= upper.as_slice(),
+        Excluded(upper) => key < upper.as_slice(),
+        Unbounded => true,
+    }
+}
+
+/// Tests whether the bounds of a range exclude every key. This covers the ranges for
+/// which `BTreeMap::range` panics.
+pub(crate) fn is_empty_range(range: &(Bound<Vec<u8>>, Bound<Vec<u8>>)) -> bool {
+    match range {
+        (Included(lower), Included(upper)) => lower > upper,
+        (Included(lower) | Excluded(lower), Included(upper) | Excluded(upper)) => lower >= upper,
+        _ => false,
+    }
+}
+
+/// Puts the `entries` of a range, found in lexicographic order, in reverse order if
+/// `reverse` is set, and keeps at most `limit` of them.
+pub(crate) fn order_and_limit<T>(
+    mut entries: Vec<T>,
+    reverse: bool,
+    limit: Option<usize>,
+) -> Vec<T> {
+    if reverse {
+        entries.reverse();
+    }
+    if let Some(limit) = limit {
+        entries.truncate(limit);
+    }
+    entries
+}
+
+/// Computes an interval so that a vector belongs to it if and only if it is of the
+/// form `key_prefix + suffix` with `suffix` in `range`.
+pub(crate) fn get_interval_in_range(
+    key_prefix: &[u8],
+    range: &(Bound<Vec<u8>>, Bound<Vec<u8>>),
+) -> (Bound<Vec<u8>>, Bound<Vec<u8>>) {
+    let extend = |suffix: &Vec<u8>| {
+        let mut key = key_prefix.to_vec();
+        key.extend(suffix);
+        key
+    };
+    let lower = match &range.0 {
+        Included(lower) => Included(extend(lower)),
+        Excluded(lower) => Excluded(extend(lower)),
+        Unbounded => Included(key_prefix.to_vec()),
+    };
+    let upper = match &range.1 {
+        Included(upper) => Included(extend(upper)),
+        Excluded(upper) => Excluded(extend(upper)),
+        Unbounded => get_upper_bound(key_prefix),
+    };
+    (lower, upper)
+}
+
+/// An entry of a container in a range, either read from storage or pending.
+pub(crate) enum RangeEntry<'a, T, U> {
+    /// The entry as read from storage.
+    Stored(T),
+    /// The entry as set by a pending update.
+    Pending(&'a U),
+}
+
+/// The number of stored entries read in the first page of an iteration over a range
+/// whose length is not known in advance. The following pages are twice as large.
+pub(crate) const INITIAL_RANGE_PAGE_SIZE: usize = 16;
+
+/// Tests whether `key` has a prefix in the set of `prefixes`, none of which is a prefix
+/// of another.
+fn has_prefix_in(prefixes: &BTreeSet<Vec<u8>>, key: &[u8]) -> bool {
+    prefixes
+        .range::<[u8], _>((Unbounded, Included(key)))
+        .next_back()
+        .is_some_and(|prefix| key.starts_with(prefix))
+}
+
+/// Iterates over a range of a container by reading its stored entries by pages, in
+/// lexicographic order or in reverse, and merging each page with the pending updates.
+pub(crate) struct RangePager {
+    /// The part of the range not visited yet.
+    remaining: (Bound<Vec<u8>>, Bound<Vec<u8>>),
+    /// Whether to visit the range in reverse lexicographic order.
+    reverse: bool,
+    /// The number of stored entries to read in the next page, or `None` to read them all.
+    page_size: Option<usize>,
+    /// Whether the whole range has been visited.
+    is_done: bool,
+}
+
+impl RangePager {
+    /// Creates a pager over `range`, reading `page_size` stored entries in the first page,
+    /// or all of them if `None`.
+    pub(crate) fn new(
+        range: (Bound<Vec<u8>>, Bound<Vec<u8>>),
+        reverse: bool,
+        page_size: Option<usize>,
+    ) -> Self {
+        let is_done = is_empty_range(&range) || page_size == Some(0);
+        Self {
+            remaining: range,
+            reverse,
+            page_size,
+            is_done,
+        }
+    }
+
+    /// Returns the range and the maximum number of stored entries of the next page to read
+    /// from storage, or `None` if the whole range has been visited.
+    #[expect(clippy::type_complexity)]
+    pub(crate) fn next_page(&self) -> Option<((Bound<Vec<u8>>, Bound<Vec<u8>>), Option<usize>)> {
+        (!self.is_done).then(|| (self.remaining.clone(), self.page_size))
+    }
+
+    /// Merges a `stored` page, as read from storage for the range of `next_page`, with the
+    /// pending `updates` in the part of the range that the page covers. The stored entries
+    /// under one of the `deleted_prefixes` are skipped, and the pending updates take
+    /// precedence over the stored entries. The entries are returned in the order of the
+    /// iteration.
+    pub(crate) fn merge_page<'a, T, U>(
+        &mut self,
+        stored: Vec<(Vec<u8>, T)>,
+        deleted_prefixes: &BTreeSet<Vec<u8>>,
+        updates: &'a BTreeMap<Vec<u8>, Update<U>>,
+    ) -> Vec<(Vec<u8>, RangeEntry<'a, T, U>)> {
+        let is_last_page = stored.is_empty()
+            || self
+                .page_size
+                .is_none_or(|page_size| stored.len() < page_size);
+        // A full page only covers the range up to its last key, the stored entries beyond
+        // it being read with the next page.
+        let covered = if is_last_page {
+            self.is_done = true;
+            self.remaining.clone()
+        } else {
+            let last_key = stored.last().expect("the page is not empty").0.clone();
+            let covered = if self.reverse {
+                let covered = (Included(last_key.clone()), self.remaining.1.clone());
+                self.remaining.1 = Excluded(last_key);
+                covered
+            } else {
+                let covered = (self.remaining.0.clone(), Included(last_key.clone()));
+                self.remaining.0 = Excluded(last_key);
+                covered
+            };
+            self.is_done = is_empty_range(&self.remaining);
+            self.page_size = self.page_size.map(|page_size| page_size.saturating_mul(2));
+            covered
+        };
+        let mut updates = updates.range(covered).collect::<Vec<_>>();
+        if self.reverse {
+            updates.reverse();
+        }
+        let mut updates = updates.into_iter().peekable();
+        let reverse = self.reverse;
+        let comes_first = |key1: &[u8], key2: &[u8]| {
+            if reverse {
+                key1 >= key2
+            } else {
+                key1 <= key2
+            }
+        };
+        let mut entries = Vec::new();
+        for (key, value) in stored {
+            let mut is_updated = false;
+            while let Some((update_key, update)) =
+                updates.next_if(|(update_key, _)| comes_first(update_key, &key))
+            {
+                is_updated = *update_key == key;
+                if let Update::Set(value) = update {
+                    entries.push((update_key.clone(), RangeEntry::Pending(value)));
+                }
+            }
+            if !is_updated && !has_prefix_in(deleted_prefixes, &key) {
+                entries.push((key, RangeEntry::Stored(value)));
+            }
+        }
+        for (update_key, update) in updates {
+            if let Update::Set(value) = update {
+                entries.push((update_key.clone(), RangeEntry::Pending(value)));
+            }
+        }
+        entries
+    }
+}
+
 /// Deserializes an optional vector of `u8`
 pub fn from_bytes_option<V: DeserializeOwned>(
     key_opt: &Option<Vec<u8>>,
@@ -262,6 +491,56 @@ fn suffix_closed_set_test3_find_key_prefix_len() {
     assert!(!suffix_closed_set.find_key(&[5]));
 }
 
+#[test]
+fn range_pager_test() {
+    let mut deleted_prefixes = BTreeSet::new();
+    deleted_prefixes.insert(vec![2]);
+    let mut updates = BTreeMap::new();
+    updates.insert(vec![0], Update::Set(10));
+    updates.insert(vec![1], Update::Removed);
+    updates.insert(vec![2, 1], Update::Set(21));
+    updates.insert(vec![3], Update::Set(30));
+    let stored = vec![(vec![1], 1), (vec![2, 0], 20), (vec![3], 3), (vec![4], 4)];
+    let expected = vec![(vec![0], 10), (vec![2, 1], 21), (vec![3], 30), (vec![4], 4)];
+    for page_size in [None, Some(1), Some(2), Some(10)] {
+        for reverse in [false, true] {
+            let mut pager = RangePager::new((Unbounded, Unbounded), reverse, page_size);
+            let mut entries = Vec::new();
+            while let Some((range, limit)) = pager.next_page() {
+                let page = stored
+                    .iter()
+                    .filter(|(key, _)| {
+                        is_above_lower_bound(key, &range.0) && is_below_upper_bound(key, &range.1)
+                    })
+                    .cloned()
+                    .collect::<Vec<_>>();
+                let page = order_and_limit(page, reverse, limit);
+                for (key, entry) in pager.merge_page(page, &deleted_prefixes, &updates) {
+                    let value = match entry {
+                        RangeEntry::Stored(value) => value,
+                        RangeEntry::Pending(value) => *value,
+                    };
+                    entries.push((key, value));
+                }
+            }
+            let mut expected = expected.clone();
+            if reverse {
+                expected.reverse();
+            }
+            assert_eq!(entries, expected);
+        }
+    }
+}
+
+#[test]
+fn empty_range_test() {
+    assert!(!is_empty_range(&(Unbounded, Excluded(vec![0]))));
+    assert!(!is_empty_range(&(Included(vec![1]), Included(vec![1]))));
+    assert!(is_empty_range(&(Included(vec![1]), Excluded(vec![1]))));
+    assert!(is_empty_range(&(Included(vec![2]), Included(vec![1]))));
+    assert!(!is_empty_range(&(Excluded(vec![1]), Included(vec![1, 0]))));
+}
+
 #[test]
 fn insert_key_prefix_test1() {
     let mut set = BTreeSet::<Vec<u8>>::new();
@@ -283,21 +562,31 @@ pub trait CustomSerialize: Sized {
     fn from_custom_bytes(short_key: &[u8]) -> Result<Self, ViewError>;
 }
 
-impl CustomSerialize for u128 {
-    fn to_custom_bytes(&self) -> Result<Vec<u8>, ViewError> {
-        let mut bytes = bcs::to_bytes(&self)?;
-        bytes.reverse();
-        Ok(bytes)
-    }
+/// Implements [`CustomSerialize`] for unsigned integers as their big-endian bytes,
+/// which preserves the order.
+macro_rules! impl_custom_serialize_for_unsigned {
+    ($($integer:ty),+) => {
+        $(
+            impl CustomSerialize for $integer {
+                fn to_custom_bytes(&self) -> Result<Vec<u8>, ViewError> {
+                    let mut bytes = bcs::to_bytes(&self)?;
+                    bytes.reverse();
+                    Ok(bytes)
+                }
 
-    fn from_custom_bytes(bytes: &[u8]) -> Result<Self, ViewError> {
-        let mut bytes = bytes.to_vec();
-        bytes.reverse();
-        let value = bcs::from_bytes(&bytes)?;
-        Ok(value)
-    }
+                fn from_custom_bytes(bytes: &[u8]) -> Result<Self, ViewError> {
+                    let mut bytes = bytes.to_vec();
+                    bytes.reverse();
+                    let value = bcs::from_bytes(&bytes)?;
+                    Ok(value)
+                }
+            }
+        )+
+    };
 }
 
+impl_custom_serialize_for_unsigned!(u8, u16, u32, u64, u128);
+
 /// This computes the offset of the BCS serialization of a vector.
 /// The formula that should be satisfied is
 /// `serialized_size(vec![v_1, ...., v_n]) = get_uleb128_size(n)`
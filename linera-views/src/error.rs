@@ -62,6 +62,19 @@ pub enum ViewError {
     /// The value is too large for the client
     #[error("the value is too large for the client")]
     TooLargeValue,
+
+    /// The layout version of a view in storage is not the one of its type.
+    #[error("the view has layout version {stored} in storage, but version {expected} is expected")]
+    UnsupportedLayoutVersion {
+        /// The layout version in storage.
+        stored: u32,
+        /// The layout version of the type of the view.
+        expected: u32,
+    },
+
+    /// The view cannot be read as it was at a version outside its history window.
+    #[error("the view cannot be read at version {0}")]
+    UnavailableVersion(u64),
 }
 
 impl ViewError {
@@ -49,11 +49,26 @@ The following views implement the `View` trait:
 * `QueueView` implements a queue, which is a list of entries that can be expanded and reduced.
 * `MapView` implements a map with keys and values.
 * `SetView` implements a set with keys.
+* `IndexedMapView` implements a map whose values can also be looked up by secondary indexes.
 * `CollectionView` implements a map whose values are views themselves.
 * `ReentrantCollectionView` implements a map for which different keys can be accessed independently.
 * `ViewContainer<C>` implements a `KeyValueStore` and is used internally.
 
 The `LogView` can be seen as an analog of `VecDeque` while `MapView` is an analog of `BTreeMap`.
+
+## Versioned layouts.
+
+The views derived with `#[derive(View)]` and the like store each field under its index.
+The attribute `#[view(index = ...)]` pins the index of a field, so that fields can be
+added or removed without moving the others. The attribute `#[view(version = ...)]`
+stores a layout version with the view, and `#[view(migrate = ...)]` names an async
+function that upgrades the views stored with a previous version when they are loaded.
+The upgraded views are saved right away, without the fields of the previous layouts.
+This also applies to the views held by other views or collections, when they are loaded
+with `load` or in a batch. Only `post_load` alone cannot migrate a view, and reports an
+`UnsupportedLayoutVersion` error instead.
+Finally, `#[view(layout = "...")]` checks at compile time that the layout of the fields
+matches a snapshot file, unless the version changes.
 */
 
 #![deny(missing_docs)]
@@ -100,6 +115,8 @@ pub mod test_utils;
 
 #[cfg(with_dynamodb)]
 pub use backends::dynamo_db;
+#[cfg(with_encryption)]
+pub use backends::encrypted;
 #[cfg(with_indexeddb)]
 pub use backends::indexed_db;
 #[cfg(with_metrics)]
@@ -110,8 +127,8 @@ pub use backends::rocks_db;
 pub use backends::scylla_db;
 pub use backends::{journaling, lru_caching, memory, value_splitting};
 pub use views::{
-    bucket_queue_view, collection_view, hashable_wrapper, key_value_store_view, log_view, map_view,
-    queue_view, reentrant_collection_view, register_view, set_view,
+    bucket_queue_view, collection_view, hashable_wrapper, indexed_map_view, key_value_store_view,
+    log_view, map_view, queue_view, reentrant_collection_view, register_view, set_view,
 };
 /// Re-exports used by the derive macros of this library.
 #[doc(hidden)]
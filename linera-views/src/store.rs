@@ -3,7 +3,7 @@
 
 //! This provides the trait definitions for the stores.
 
-use std::{fmt::Debug, future::Future};
+use std::{fmt::Debug, future::Future, ops::Bound};
 
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
 
@@ -11,7 +11,7 @@ use serde::{de::DeserializeOwned, Deserialize, Serialize};
 use crate::random::generate_test_namespace;
 use crate::{
     batch::Batch,
-    common::from_bytes_option,
+    common::{from_bytes_option, is_above_lower_bound, is_below_upper_bound, order_and_limit},
     lru_caching::{StorageCacheConfig, DEFAULT_STORAGE_CACHE_CONFIG},
     ViewError,
 };
@@ -128,6 +128,70 @@ pub trait ReadableKeyValueStore: WithError {
     // https://github.com/rust-lang/impl-trait-utils/issues/17, but once that bug is fixed
     // we can revert them to `async fn` syntax, which is neater.
 
+    /// Finds the `key` matching the prefix whose remainder, once the prefix is removed, lies
+    /// in `range`. The keys are returned without the prefix, in lexicographic order, or in
+    /// reverse lexicographic order if `reverse` is set. At most `limit` keys are returned,
+    /// starting from the beginning of that order.
+    ///
+    /// The default implementation filters the result of `find_keys_by_prefix`. Stores
+    /// iterating over ordered keys should seek to the relevant end of the range and stop
+    /// after `limit` keys instead.
+    fn find_keys_by_prefix_in_range(
+        &self,
+        key_prefix: &[u8],
+        range: (Bound<Vec<u8>>, Bound<Vec<u8>>),
+        reverse: bool,
+        limit: Option<usize>,
+    ) -> impl Future<Output = Result<Vec<Vec<u8>>, Self::Error>>
+    where
+        Self: Sync,
+    {
+        async move {
+            let mut keys = Vec::new();
+            for key in self.find_keys_by_prefix(key_prefix).await?.iterator() {
+                let key = key?;
+                if !is_below_upper_bound(key, &range.1) {
+                    break;
+                }
+                if is_above_lower_bound(key, &range.0) {
+                    keys.push(key.to_vec());
+                }
+            }
+            Ok(order_and_limit(keys, reverse, limit))
+        }
+    }
+
+    /// Finds the `(key,value)` pairs matching the prefix whose key, once the prefix is
+    /// removed, lies in `range`. The pairs are returned without the prefix, ordered and
+    /// limited as in `find_keys_by_prefix_in_range`.
+    ///
+    /// The default implementation filters the result of `find_key_values_by_prefix`.
+    fn find_key_values_by_prefix_in_range(
+        &self,
+        key_prefix: &[u8],
+        range: (Bound<Vec<u8>>, Bound<Vec<u8>>),
+        reverse: bool,
+        limit: Option<usize>,
+    ) -> impl Future<Output = Result<Vec<(Vec<u8>, Vec<u8>)>, Self::Error>>
+    where
+        Self: Sync,
+    {
+        async move {
+            let mut key_values = Vec::new();
+            let entries = self.find_key_values_by_prefix(key_prefix).await?;
+            for entry in entries.into_iterator_owned() {
+                let (key, value) = entry?;
+                if !is_below_upper_bound(&key, &range.1) {
+                    break;
+                }
+                if is_above_lower_bound(&key, &range.0) {
+                    key_values.push((key, value));
+                }
+            }
+            Ok(order_and_limit(key_values, reverse, limit))
+        }
+    }
+
     /// Reads a single `key` and deserializes the result if present.
     fn read_value<V: DeserializeOwned>(
         &self,
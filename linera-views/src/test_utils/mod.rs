@@ -7,7 +7,13 @@ pub mod test_views;
 #[cfg(not(target_arch = "wasm32"))]
 pub mod performance;
 
-use std::collections::{BTreeMap, BTreeSet, HashSet};
+use std::{
+    collections::{BTreeMap, BTreeSet, HashSet},
+    ops::{
+        Bound::{self, Excluded, Included, Unbounded},
+        RangeBounds as _,
+    },
+};
 
 use rand::{seq::SliceRandom, Rng};
 
@@ -16,6 +22,7 @@ use crate::{
         Batch, WriteOperation,
         WriteOperation::{Delete, Put},
     },
+    common::order_and_limit,
     random::{generate_test_namespace, make_deterministic_rng, make_nondeterministic_rng},
     store::{
         KeyIterable, KeyValueIterable, KeyValueStore, RestrictedKeyValueStore, TestKeyValueStore,
@@ -150,7 +157,12 @@ pub fn span_random_reordering_put_delete<R: Rng>(
 /// * `read_multi_values_bytes`
 /// * `find_keys_by_prefix` / `find_key_values_by_prefix`
 /// * The ordering of keys returned by `find_keys_by_prefix` and `find_key_values_by_prefix`
-pub async fn run_reads<S: RestrictedKeyValueStore>(store: S, key_values: Vec<(Vec<u8>, Vec<u8>)>) {
+/// * `find_keys_by_prefix_in_range` / `find_key_values_by_prefix_in_range`, in both orders
+///   and with limits
+pub async fn run_reads<S: RestrictedKeyValueStore + Sync>(
+    store: S,
+    key_values: Vec<(Vec<u8>, Vec<u8>)>,
+) {
     // We need a nontrivial key_prefix because dynamo requires a non-trivial prefix
     let mut batch = Batch::new();
     let mut keys = Vec::new();
@@ -193,6 +205,41 @@ pub async fn run_reads<S: RestrictedKeyValueStore>(store: S, key_values: Vec<(Ve
             }
         }
         assert_eq!(set_key_value1, set_key_value2);
+        // Check find_keys_by_prefix_in_range / find_key_values_by_prefix_in_range
+        for range in get_test_ranges(&keys_request) {
+            let expected_key_values = key_values_by_prefix
+                .iterator()
+                .map(Result::unwrap)
+                .filter(|(key, _)| range.contains(&key.to_vec()))
+                .map(|(key, value)| (key.to_vec(), value.to_vec()))
+                .collect::<Vec<_>>();
+            let expected_keys = expected_key_values
+                .iter()
+                .map(|(key, _)| key.clone())
+                .collect::<Vec<_>>();
+            for reverse in [false, true] {
+                for limit in [None, Some(0), Some(1), Some(3)] {
+                    let expected_key_values =
+                        order_and_limit(expected_key_values.clone(), reverse, limit);
+                    let expected_keys = order_and_limit(expected_keys.clone(), reverse, limit);
+                    let keys_in_range = store
+                        .find_keys_by_prefix_in_range(key_prefix, range.clone(), reverse, limit)
+                        .await
+                        .unwrap();
+                    assert_eq!(keys_in_range, expected_keys);
+                    let key_values_in_range = store
+                        .find_key_values_by_prefix_in_range(
+                            key_prefix,
+                            range.clone(),
+                            reverse,
+                            limit,
+                        )
+                        .await
+                        .unwrap();
+                    assert_eq!(key_values_in_range, expected_key_values);
+                }
+            }
+        }
     }
     // Now checking the read_multi_values_bytes
     let mut rng = make_deterministic_rng();
@@ -234,6 +281,23 @@ pub async fn run_reads<S: RestrictedKeyValueStore>(store: S, key_values: Vec<(Ve
     }
 }
 
+/// Returns ranges bounded by the first, middle and last of the `keys`.
+fn get_test_ranges(keys: &[&[u8]]) -> Vec<(Bound<Vec<u8>>, Bound<Vec<u8>>)> {
+    let mut ranges = vec![(Unbounded, Unbounded)];
+    let (Some(first), Some(last)) = (keys.first(), keys.last()) else {
+        return ranges;
+    };
+    let middle = keys[keys.len() / 2];
+    ranges.push((Included(middle.to_vec()), Unbounded));
+    ranges.push((Excluded(middle.to_vec()), Unbounded));
+    ranges.push((Unbounded, Included(middle.to_vec())));
+    ranges.push((Unbounded, Excluded(middle.to_vec())));
+    ranges.push((Included(first.to_vec()), Included(last.to_vec())));
+    ranges.push((Excluded(first.to_vec()), Excluded(last.to_vec())));
+    ranges.push((Included(middle.to_vec()), Excluded(middle.to_vec())));
+    ranges
+}
+
 fn get_random_key_values1(num_entries: usize, len_value: usize) -> Vec<(Vec<u8>, Vec<u8>)> {
     let key_prefix = vec![0];
     let mut rng = make_deterministic_rng();
@@ -13,7 +13,7 @@ use crate::{
     batch::Batch,
     common::from_bytes_option,
     context::Context,
-    store::ReadableKeyValueStore as _,
+    store::{ReadableKeyValueStore as _, WritableKeyValueStore as _},
     views::{ClonableView, HashableView, Hasher, View, ViewError, MIN_VIEW_TAG},
 };
 
@@ -75,7 +75,22 @@ where
     async fn load(context: Self::Context) -> Result<Self, ViewError> {
         let keys = Self::pre_load(&context)?;
         let values = context.store().read_multi_values_bytes(keys).await?;
-        Self::post_load(context, &values)
+        match Self::post_load(context.clone(), &values) {
+            Err(ViewError::UnsupportedLayoutVersion { stored, expected }) if stored < expected => {}
+            result => return result,
+        }
+        // The inner view is migrated and saved, so its stored hash is deleted as well.
+        let base_key = context.base_key().base_tag(KeyTag::Inner as u8);
+        let inner = W::load(context.clone_with_base_key(base_key)).await?;
+        let mut batch = Batch::new();
+        batch.delete_key(context.base_key().base_tag(KeyTag::Hash as u8));
+        context.store().write_batch(batch).await?;
+        Ok(Self {
+            _phantom: PhantomData,
+            stored_hash: None,
+            hash: Mutex::new(None),
+            inner,
+        })
     }
 
     fn rollback(&mut self) {
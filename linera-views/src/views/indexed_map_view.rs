@@ -0,0 +1,880 @@
+// Copyright (c) Zefchain Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use std::{
+    borrow::Borrow,
+    marker::PhantomData,
+    ops::{Bound, RangeBounds},
+};
+
+use serde::{de::DeserializeOwned, Serialize};
+
+use crate::{
+    batch::Batch,
+    common::{
+        get_interval, get_upper_bound, get_upper_bound_option, CustomSerialize, HasherOutput,
+    },
+    context::{BaseKey, Context},
+    hashable_wrapper::WrappedHashableContainerView,
+    map_view::MapView,
+    set_view::ByteSetView,
+    store::ReadableKeyValueStore as _,
+    views::{ClonableView, HashableView, Hasher, View, ViewError, MIN_VIEW_TAG},
+};
+
+/// Key tags to create the sub-keys of an `IndexedMapView` on top of the base key.
+#[repr(u8)]
+enum KeyTag {
+    /// Prefix for the primary map.
+    Map = MIN_VIEW_TAG,
+    /// Prefix for the entries of the secondary indexes.
+    Index,
+}
+
+/// A secondary index of the values stored in an [`IndexedMapView`].
+///
+/// The index keys of a value are computed by [`SecondaryIndex::keys`]. An entry
+/// `(ID, key, index)` is stored for each of them, where `index` is the primary index of
+/// the value.
+pub trait SecondaryIndex<V>: Send + Sync + 'static {
+    /// The identifier of the index. It is part of the storage layout, so it has to be
+    /// distinct among the indexes of a value type and must not change once data is stored.
+    const ID: u8;
+
+    /// The type of the keys of the index.
+    type Key: Serialize + DeserializeOwned + Send;
+
+    /// Extracts the index keys of a value.
+    fn keys(value: &V) -> Vec<Self::Key>;
+
+    /// Serializes a key as stored in the index entries. The serializations of distinct
+    /// keys must not be prefixes of one another. The default is BCS, which does not
+    /// preserve the order of the keys, so that only [`OrderedSecondaryIndex`] types can
+    /// be queried by range.
+    fn serialize_key(key: &Self::Key) -> Result<Vec<u8>, ViewError> {
+        Ok(bcs::to_bytes(key)?)
+    }
+}
+
+/// A secondary index whose entries are ordered like its keys, so that they can be
+/// queried by range. The keys are stored in their [`CustomSerialize`] serialization,
+/// which has to preserve their order. It is listed among the [`IndexedValue::Indexes`]
+/// as [`Ordered<Self>`](Ordered).
+pub trait OrderedSecondaryIndex<V>: Send + Sync + 'static {
+    /// The identifier of the index, as for [`SecondaryIndex::ID`].
+    const ID: u8;
+
+    /// The type of the keys of the index.
+    type Key: Serialize + DeserializeOwned + CustomSerialize + Send;
+
+    /// Extracts the index keys of a value.
+    fn keys(value: &V) -> Vec<Self::Key>;
+}
+
+/// The [`SecondaryIndex`] maintaining the entries of an [`OrderedSecondaryIndex`].
+pub struct Ordered<X>(PhantomData<X>);
+
+impl<V, X: OrderedSecondaryIndex<V>> SecondaryIndex<V> for Ordered<X> {
+    const ID: u8 = X::ID;
+
+    type Key = X::Key;
+
+    fn keys(value: &V) -> Vec<Self::Key> {
+        X::keys(value)
+    }
+
+    fn serialize_key(key: &Self::Key) -> Result<Vec<u8>, ViewError> {
+        serialize_ordered_key(key)
+    }
+}
+
+/// Serializes a key of an [`OrderedSecondaryIndex`]. The zero bytes of the custom
+/// serialization are escaped as `[0, 255]` and the result is terminated by `[0, 0]`,
+/// which makes the serializations prefix-free while preserving their order.
+fn serialize_ordered_key<K: CustomSerialize>(key: &K) -> Result<Vec<u8>, ViewError> {
+    let mut bytes = Vec::new();
+    for byte in key.to_custom_bytes()? {
+        bytes.push(byte);
+        if byte == 0 {
+            bytes.push(255);
+        }
+    }
+    bytes.extend([0, 0]);
+    Ok(bytes)
+}
+
+/// Deserializes an entry of an [`OrderedSecondaryIndex`] without its identifier into
+/// the key and the primary index.
+fn deserialize_ordered_entry<K: CustomSerialize, I: DeserializeOwned>(
+    entry: &[u8],
+) -> Result<(K, I), ViewError> {
+    let mut key_bytes = Vec::new();
+    let mut position = 0;
+    loop {
+        match entry.get(position..position + 2) {
+            Some([0, 0]) => break,
+            Some([0, 255]) => {
+                key_bytes.push(0);
+                position += 2;
+            }
+            Some([0, _]) | None => return Err(ViewError::InconsistentEntries),
+            Some(_) => {
+                key_bytes.push(entry[position]);
+                position += 1;
+            }
+        }
+    }
+    let key = K::from_custom_bytes(&key_bytes)?;
+    let index = BaseKey::deserialize_value(&entry[position + 2..])?;
+    Ok((key, index))
+}
+
+/// A list of secondary indexes. This is implemented for the tuples of up to four
+/// [`SecondaryIndex`] types.
+pub trait SecondaryIndexes<V> {
+    /// Returns the serialized `(ID, key)` prefixes of the index entries of a value.
+    fn entry_prefixes(value: &V) -> Result<Vec<Vec<u8>>, ViewError>;
+}
+
+/// A type of values that can be stored in an [`IndexedMapView`].
+pub trait IndexedValue: Sized {
+    /// The secondary indexes maintained for the values, e.g. `(ByOwner, ByStatus)`.
+    type Indexes: SecondaryIndexes<Self>;
+}
+
+/// Serializes the prefix shared by the index entries of `X` with the given key.
+fn get_entry_prefix<V, X: SecondaryIndex<V>>(key: &X::Key) -> Result<Vec<u8>, ViewError> {
+    let mut prefix = vec![X::ID];
+    prefix.extend(X::serialize_key(key)?);
+    Ok(prefix)
+}
+
+macro_rules! impl_secondary_indexes {
+    ($($index:ident),+) => {
+        impl<V, $($index: SecondaryIndex<V>),+> SecondaryIndexes<V> for ($($index,)+) {
+            fn entry_prefixes(value: &V) -> Result<Vec<Vec<u8>>, ViewError> {
+                let mut prefixes = Vec::new();
+                $(
+                    for key in <$index as SecondaryIndex<V>>::keys(value) {
+                        prefixes.push(get_entry_prefix::<V, $index>(&key)?);
+                    }
+                )+
+                Ok(prefixes)
+            }
+        }
+    };
+}
+
+impl_secondary_indexes!(A);
+impl_secondary_indexes!(A, B);
+impl_secondary_indexes!(A, B, C);
+impl_secondary_indexes!(A, B, C, D);
+
+/// Converts a range of keys of the index `X` into the range of the serialized index
+/// entries. Since the serializations of the keys are prefix-free and preserve their
+/// order, the entries of the keys in the range are exactly the ones in the returned
+/// range. Returns `None` if the range is empty.
+fn get_entry_range<V, X, R>(range: R) -> Result<Option<(Bound<Vec<u8>>, Bound<Vec<u8>>)>, ViewError>
+where
+    X: SecondaryIndex<V>,
+    X::Key: CustomSerialize,
+    R: RangeBounds<X::Key>,
+{
+    let lower = match range.start_bound() {
+        Bound::Included(key) => Bound::Included(get_entry_prefix::<V, X>(key)?),
+        Bound::Excluded(key) => {
+            let prefix = get_entry_prefix::<V, X>(key)?;
+            match get_upper_bound_option(&prefix) {
+                Some(upper_bound) => Bound::Included(upper_bound),
+                None => return Ok(None),
+            }
+        }
+        Bound::Unbounded => Bound::Included(vec![X::ID]),
+    };
+    let upper = match range.end_bound() {
+        Bound::Included(key) => get_upper_bound(&get_entry_prefix::<V, X>(key)?),
+        Bound::Excluded(key) => Bound::Excluded(get_entry_prefix::<V, X>(key)?),
+        Bound::Unbounded => get_upper_bound(&[X::ID]),
+    };
+    Ok(Some((lower, upper)))
+}
+
+/// A [`MapView`] that maintains secondary indexes of its values. The indexes are
+/// declared by implementing [`IndexedValue`] for the type of the values. The entries of
+/// the indexes are updated together with the map and flushed into the same batch, so
+/// that both are always consistent in storage.
+///
+/// As for `MapView`, the order of the indices and of the index keys is determined by
+/// their serialization. The entries of an [`OrderedSecondaryIndex`] follow the order of
+/// their keys and can be queried by range.
+/// ```rust
+/// # tokio_test::block_on(async {
+/// # use linera_views::context::MemoryContext;
+/// # use linera_views::views::View;
+/// use linera_views::indexed_map_view::{IndexedMapView, IndexedValue, SecondaryIndex};
+/// use serde::{Deserialize, Serialize};
+///
+/// #[derive(Clone, Serialize, Deserialize)]
+/// struct Task {
+///     owner: u8,
+///     tags: Vec<String>,
+/// }
+///
+/// struct ByOwner;
+///
+/// impl SecondaryIndex<Task> for ByOwner {
+///     const ID: u8 = 0;
+///     type Key = u8;
+///
+///     fn keys(task: &Task) -> Vec<u8> {
+///         vec![task.owner]
+///     }
+/// }
+///
+/// struct ByTag;
+///
+/// impl SecondaryIndex<Task> for ByTag {
+///     const ID: u8 = 1;
+///     type Key = String;
+///
+///     fn keys(task: &Task) -> Vec<String> {
+///         task.tags.clone()
+///     }
+/// }
+///
+/// impl IndexedValue for Task {
+///     type Indexes = (ByOwner, ByTag);
+/// }
+///
+/// # let context = MemoryContext::new_for_testing(());
+/// let mut map: IndexedMapView<_, u32, Task> = IndexedMapView::load(context).await.unwrap();
+/// let tags = vec![String::from("urgent")];
+/// map.insert(&(1 as u32), Task { owner: 3, tags }).await.unwrap();
+/// let tags = vec![String::from("urgent"), String::from("bug")];
+/// map.insert(&(2 as u32), Task { owner: 5, tags }).await.unwrap();
+/// assert_eq!(map.indices_by::<ByOwner>(&3).await.unwrap(), vec![1]);
+/// let urgent = String::from("urgent");
+/// assert_eq!(map.indices_by::<ByTag>(&urgent).await.unwrap(), vec![1, 2]);
+/// # })
+/// ```
+#[derive(Debug)]
+pub struct IndexedMapView<C, I, V> {
+    context: C,
+    map: MapView<C, I, V>,
+    index: ByteSetView<C>,
+}
+
+impl<C, I, V> IndexedMapView<C, I, V>
+where
+    C: Context,
+{
+    fn map_context(context: &C) -> C {
+        context.clone_with_base_key(context.base_key().base_tag(KeyTag::Map as u8))
+    }
+
+    fn index_context(context: &C) -> C {
+        context.clone_with_base_key(context.base_key().base_tag(KeyTag::Index as u8))
+    }
+}
+
+impl<C, I, V> View for IndexedMapView<C, I, V>
+where
+    C: Context,
+    I: Send + Sync,
+    V: Send + Sync + Serialize,
+{
+    const NUM_INIT_KEYS: usize =
+        MapView::<C, I, V>::NUM_INIT_KEYS + ByteSetView::<C>::NUM_INIT_KEYS;
+
+    type Context = C;
+
+    fn context(&self) -> &C {
+        &self.context
+    }
+
+    fn pre_load(context: &C) -> Result<Vec<Vec<u8>>, ViewError> {
+        let mut keys = MapView::<C, I, V>::pre_load(&Self::map_context(context))?;
+        keys.extend(ByteSetView::<C>::pre_load(&Self::index_context(context))?);
+        Ok(keys)
+    }
+
+    fn post_load(context: C, values: &[Option<Vec<u8>>]) -> Result<Self, ViewError> {
+        let (map_values, index_values) = values.split_at(MapView::<C, I, V>::NUM_INIT_KEYS);
+        let map = MapView::post_load(Self::map_context(&context), map_values)?;
+        let index = ByteSetView::post_load(Self::index_context(&context), index_values)?;
+        Ok(IndexedMapView {
+            context,
+            map,
+            index,
+        })
+    }
+
+    async fn load(context: C) -> Result<Self, ViewError> {
+        if Self::NUM_INIT_KEYS == 0 {
+            Self::post_load(context, &[])
+        } else {
+            let keys = Self::pre_load(&context)?;
+            let values = context.store().read_multi_values_bytes(keys).await?;
+            Self::post_load(context, &values)
+        }
+    }
+
+    fn rollback(&mut self) {
+        self.map.rollback();
+        self.index.rollback();
+    }
+
+    async fn has_pending_changes(&self) -> bool {
+        self.map.has_pending_changes().await || self.index.has_pending_changes().await
+    }
+
+    fn flush(&mut self, batch: &mut Batch) -> Result<bool, ViewError> {
+        let delete_map = self.map.flush(batch)?;
+        let delete_index = self.index.flush(batch)?;
+        Ok(delete_map && delete_index)
+    }
+
+    fn clear(&mut self) {
+        self.map.clear();
+        self.index.clear();
+    }
+}
+
+impl<C, I, V> ClonableView for IndexedMapView<C, I, V>
+where
+    Self: View,
+    MapView<C, I, V>: ClonableView,
+    ByteSetView<C>: ClonableView,
+{
+    fn clone_unchecked(&mut self) -> Result<Self, ViewError> {
+        Ok(IndexedMapView {
+            context: self.context.clone(),
+            map: self.map.clone_unchecked()?,
+            index: self.index.clone_unchecked()?,
+        })
+    }
+}
+
+impl<C, I, V> IndexedMapView<C, I, V>
+where
+    C: Context,
+    I: Serialize,
+    V: IndexedValue + Clone + DeserializeOwned + 'static,
+{
+    /// Inserts or resets a value at an index. The index entries of the previous value,
+    /// if any, are replaced by the ones of the new value.
+    /// ```rust
+    /// # tokio_test::block_on(async {
+    /// # use linera_views::context::MemoryContext;
+    /// # use linera_views::indexed_map_view::{IndexedMapView, IndexedValue, SecondaryIndex};
+    /// # use linera_views::views::View;
+    /// # use serde::{Deserialize, Serialize};
+    /// # #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+    /// # struct Task {
+    /// #     owner: u8,
+    /// #     priority: u8,
+    /// # }
+    /// # struct ByOwner;
+    /// # impl SecondaryIndex<Task> for ByOwner {
+    /// #     const ID: u8 = 0;
+    /// #     type Key = u8;
+    /// #     fn keys(task: &Task) -> Vec<u8> {
+    /// #         vec![task.owner]
+    /// #     }
+    /// # }
+    /// # impl IndexedValue for Task {
+    /// #     type Indexes = (ByOwner,);
+    /// # }
+    /// # let context = MemoryContext::new_for_testing(());
+    /// let mut map: IndexedMapView<_, u32, Task> = IndexedMapView::load(context).await.unwrap();
+    /// let task = Task {
+    ///     owner: 3,
+    ///     priority: 1,
+    /// };
+    /// map.insert(&(37 as u32), task).await.unwrap();
+    /// let task = Task {
+    ///     owner: 4,
+    ///     priority: 1,
+    /// };
+    /// map.insert(&(37 as u32), task).await.unwrap();
+    /// assert!(map.indices_by::<ByOwner>(&3).await.unwrap().is_empty());
+    /// assert_eq!(map.indices_by::<ByOwner>(&4).await.unwrap(), vec![37]);
+    /// # })
+    /// ```
+    pub async fn insert<Q>(&mut self, index: &Q, value: V) -> Result<(), ViewError>
+    where
+        I: Borrow<Q>,
+        Q: Serialize + ?Sized,
+    {
+        let short_key = BaseKey::derive_short_key(index)?;
+        self.remove_index_entries(index, &short_key).await?;
+        for prefix in <V::Indexes as SecondaryIndexes<V>>::entry_prefixes(&value)? {
+            self.index.insert([prefix, short_key.clone()].concat());
+        }
+        self.map.insert(index, value)
+    }
+
+    /// Removes a value and its index entries. If absent then the operation does nothing.
+    /// ```rust
+    /// # tokio_test::block_on(async {
+    /// # use linera_views::context::MemoryContext;
+    /// # use linera_views::indexed_map_view::{IndexedMapView, IndexedValue, SecondaryIndex};
+    /// # use linera_views::views::View;
+    /// # use serde::{Deserialize, Serialize};
+    /// # #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+    /// # struct Task {
+    /// #     owner: u8,
+    /// #     priority: u8,
+    /// # }
+    /// # struct ByOwner;
+    /// # impl SecondaryIndex<Task> for ByOwner {
+    /// #     const ID: u8 = 0;
+    /// #     type Key = u8;
+    /// #     fn keys(task: &Task) -> Vec<u8> {
+    /// #         vec![task.owner]
+    /// #     }
+    /// # }
+    /// # impl IndexedValue for Task {
+    /// #     type Indexes = (ByOwner,);
+    /// # }
+    /// # let context = MemoryContext::new_for_testing(());
+    /// let mut map: IndexedMapView<_, u32, Task> = IndexedMapView::load(context).await.unwrap();
+    /// let task = Task {
+    ///     owner: 3,
+    ///     priority: 1,
+    /// };
+    /// map.insert(&(37 as u32), task).await.unwrap();
+    /// map.remove(&(37 as u32)).await.unwrap();
+    /// assert_eq!(map.get(&(37 as u32)).await.unwrap(), None);
+    /// assert!(map.indices_by::<ByOwner>(&3).await.unwrap().is_empty());
+    /// # })
+    /// ```
+    pub async fn remove<Q>(&mut self, index: &Q) -> Result<(), ViewError>
+    where
+        I: Borrow<Q>,
+        Q: Serialize + ?Sized,
+    {
+        let short_key = BaseKey::derive_short_key(index)?;
+        self.remove_index_entries(index, &short_key).await?;
+        self.map.remove(index)
+    }
+
+    /// Removes the index entries of the value currently stored at an index, if any.
+    async fn remove_index_entries<Q>(
+        &mut self,
+        index: &Q,
+        short_key: &[u8],
+    ) -> Result<(), ViewError>
+    where
+        I: Borrow<Q>,
+        Q: Serialize + ?Sized,
+    {
+        if let Some(value) = self.map.get(index).await? {
+            for prefix in <V::Indexes as SecondaryIndexes<V>>::entry_prefixes(&value)? {
+                self.index.remove([prefix, short_key.to_vec()].concat());
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<C, I, V> IndexedMapView<C, I, V>
+where
+    C: Context,
+    I: Serialize,
+    V: Clone + DeserializeOwned + 'static,
+{
+    /// Obtains the extra data.
+    pub fn extra(&self) -> &C::Extra {
+        self.map.extra()
+    }
+
+    /// Reads the value at the given position, if any.
+    /// ```rust
+    /// # tokio_test::block_on(async {
+    /// # use linera_views::context::MemoryContext;
+    /// # use linera_views::indexed_map_view::{IndexedMapView, IndexedValue, SecondaryIndex};
+    /// # use linera_views::views::View;
+    /// # use serde::{Deserialize, Serialize};
+    /// # #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+    /// # struct Task {
+    /// #     owner: u8,
+    /// #     priority: u8,
+    /// # }
+    /// # struct ByOwner;
+    /// # impl SecondaryIndex<Task> for ByOwner {
+    /// #     const ID: u8 = 0;
+    /// #     type Key = u8;
+    /// #     fn keys(task: &Task) -> Vec<u8> {
+    /// #         vec![task.owner]
+    /// #     }
+    /// # }
+    /// # impl IndexedValue for Task {
+    /// #     type Indexes = (ByOwner,);
+    /// # }
+    /// # let context = MemoryContext::new_for_testing(());
+    /// let mut map: IndexedMapView<_, u32, Task> = IndexedMapView::load(context).await.unwrap();
+    /// let task = Task {
+    ///     owner: 3,
+    ///     priority: 1,
+    /// };
+    /// map.insert(&(37 as u32), task.clone()).await.unwrap();
+    /// assert_eq!(map.get(&(37 as u32)).await.unwrap(), Some(task));
+    /// assert_eq!(map.get(&(34 as u32)).await.unwrap(), None);
+    /// # })
+    /// ```
+    pub async fn get<Q>(&self, index: &Q) -> Result<Option<V>, ViewError>
+    where
+        I: Borrow<Q>,
+        Q: Serialize + ?Sized,
+    {
+        self.map.get(index).await
+    }
+
+    /// Returns `true` if the map contains a value for the specified key.
+    pub async fn contains_key<Q>(&self, index: &Q) -> Result<bool, ViewError>
+    where
+        I: Borrow<Q>,
+        Q: Serialize + ?Sized,
+    {
+        self.map.contains_key(index).await
+    }
+}
+
+impl<C, I, V> IndexedMapView<C, I, V>
+where
+    C: Context,
+    I: Send + DeserializeOwned,
+    V: Clone + Sync + Serialize + DeserializeOwned + 'static,
+{
+    /// Returns the list of indices in the map. The order is determined by serialization.
+    pub async fn indices(&self) -> Result<Vec<I>, ViewError> {
+        self.map.indices().await
+    }
+
+    /// Obtains the number of entries in the map.
+    pub async fn count(&self) -> Result<usize, ViewError> {
+        self.map.count().await
+    }
+}
+
+impl<C, I, V> IndexedMapView<C, I, V>
+where
+    C: Context,
+    I: Send + Serialize + DeserializeOwned,
+    V: Clone + DeserializeOwned + 'static,
+{
+    /// Returns the indices of the values having the given key in the index `X`. The order
+    /// is determined by serialization.
+    /// ```rust
+    /// # tokio_test::block_on(async {
+    /// # use linera_views::context::MemoryContext;
+    /// # use linera_views::indexed_map_view::{IndexedMapView, IndexedValue, SecondaryIndex};
+    /// # use linera_views::views::View;
+    /// # use serde::{Deserialize, Serialize};
+    /// # #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+    /// # struct Task {
+    /// #     owner: u8,
+    /// #     priority: u8,
+    /// # }
+    /// # struct ByOwner;
+    /// # impl SecondaryIndex<Task> for ByOwner {
+    /// #     const ID: u8 = 0;
+    /// #     type Key = u8;
+    /// #     fn keys(task: &Task) -> Vec<u8> {
+    /// #         vec![task.owner]
+    /// #     }
+    /// # }
+    /// # impl IndexedValue for Task {
+    /// #     type Indexes = (ByOwner,);
+    /// # }
+    /// # let context = MemoryContext::new_for_testing(());
+    /// let mut map: IndexedMapView<_, u32, Task> = IndexedMapView::load(context).await.unwrap();
+    /// let task = Task {
+    ///     owner: 3,
+    ///     priority: 1,
+    /// };
+    /// map.insert(&(37 as u32), task.clone()).await.unwrap();
+    /// map.insert(&(34 as u32), task).await.unwrap();
+    /// assert_eq!(map.indices_by::<ByOwner>(&3).await.unwrap(), vec![34, 37]);
+    /// # })
+    /// ```
+    pub async fn indices_by<X>(&self, key: &X::Key) -> Result<Vec<I>, ViewError>
+    where
+        X: SecondaryIndex<V>,
+    {
+        let prefix = get_entry_prefix::<V, X>(key)?;
+        let len = prefix.len();
+        let mut indices = Vec::new();
+        for entry in self.index.keys_in_range(get_interval(prefix)).await? {
+            indices.push(BaseKey::deserialize_value(&entry[len..])?);
+        }
+        Ok(indices)
+    }
+
+    /// Returns the `(index, value)` pairs of the values having the given key in the index
+    /// `X`. The order is determined by serialization.
+    /// ```rust
+    /// # tokio_test::block_on(async {
+    /// # use linera_views::context::MemoryContext;
+    /// # use linera_views::indexed_map_view::{IndexedMapView, IndexedValue, SecondaryIndex};
+    /// # use linera_views::views::View;
+    /// # use serde::{Deserialize, Serialize};
+    /// # #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+    /// # struct Task {
+    /// #     owner: u8,
+    /// #     priority: u8,
+    /// # }
+    /// # struct ByOwner;
+    /// # impl SecondaryIndex<Task> for ByOwner {
+    /// #     const ID: u8 = 0;
+    /// #     type Key = u8;
+    /// #     fn keys(task: &Task) -> Vec<u8> {
+    /// #         vec![task.owner]
+    /// #     }
+    /// # }
+    /// # impl IndexedValue for Task {
+    /// #     type Indexes = (ByOwner,);
+    /// # }
+    /// # let context = MemoryContext::new_for_testing(());
+    /// let mut map: IndexedMapView<_, u32, Task> = IndexedMapView::load(context).await.unwrap();
+    /// let task = Task {
+    ///     owner: 3,
+    ///     priority: 1,
+    /// };
+    /// map.insert(&(37 as u32), task.clone()).await.unwrap();
+    /// assert_eq!(
+    ///     map.index_values_by::<ByOwner>(&3).await.unwrap(),
+    ///     vec![(37, task)]
+    /// );
+    /// # })
+    /// ```
+    pub async fn index_values_by<X>(&self, key: &X::Key) -> Result<Vec<(I, V)>, ViewError>
+    where
+        X: SecondaryIndex<V>,
+    {
+        let mut index_values = Vec::new();
+        for index in self.indices_by::<X>(key).await? {
+            let value = self
+                .map
+                .get(&index)
+                .await?
+                .ok_or(ViewError::MissingEntries)?;
+            index_values.push((index, value));
+        }
+        Ok(index_values)
+    }
+
+    /// Applies a function f on each `(key, index)` entry of the index `X` whose key is
+    /// in the range. Entries are visited in the order of the keys, then of the serialized
+    /// indices, or in the reverse order if `reverse` is set. If the function returns false, then the
+    /// loop ends prematurely.
+    /// ```rust
+    /// # tokio_test::block_on(async {
+    /// # use linera_views::context::MemoryContext;
+    /// # use linera_views::indexed_map_view::{
+    /// #     IndexedMapView, IndexedValue, Ordered, OrderedSecondaryIndex,
+    /// # };
+    /// # use linera_views::views::View;
+    /// # use serde::{Deserialize, Serialize};
+    /// # #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+    /// # struct Task {
+    /// #     owner: u16,
+    /// #     priority: u8,
+    /// # }
+    /// # struct ByOwner;
+    /// # impl OrderedSecondaryIndex<Task> for ByOwner {
+    /// #     const ID: u8 = 0;
+    /// #     type Key = u16;
+    /// #     fn keys(task: &Task) -> Vec<u16> {
+    /// #         vec![task.owner]
+    /// #     }
+    /// # }
+    /// # impl IndexedValue for Task {
+    /// #     type Indexes = (Ordered<ByOwner>,);
+    /// # }
+    /// # let context = MemoryContext::new_for_testing(());
+    /// let mut map: IndexedMapView<_, u32, Task> = IndexedMapView::load(context).await.unwrap();
+    /// for (index, owner) in [(1 as u32, 3), (2, 5), (3, 300)] {
+    ///     let task = Task { owner, priority: 1 };
+    ///     map.insert(&index, task).await.unwrap();
+    /// }
+    /// let mut entries = Vec::new();
+    /// map.for_each_index_entry_in_range_while::<ByOwner, _, _>(
+    ///     |owner, index| {
+    ///         entries.push((owner, index));
+    ///         Ok(true)
+    ///     },
+    ///     4..,
+    ///     true,
+    /// )
+    /// .await
+    /// .unwrap();
+    /// assert_eq!(entries, vec![(300, 3), (5, 2)]);
+    /// # })
+    /// ```
+    pub async fn for_each_index_entry_in_range_while<X, F, R>(
+        &self,
+        mut f: F,
+        range: R,
+        reverse: bool,
+    ) -> Result<(), ViewError>
+    where
+        X: OrderedSecondaryIndex<V>,
+        F: FnMut(X::Key, I) -> Result<bool, ViewError> + Send,
+        R: RangeBounds<X::Key>,
+    {
+        let Some(range) = get_entry_range::<V, Ordered<X>, R>(range)? else {
+            return Ok(());
+        };
+        self.index
+            .for_each_key_in_range_while(
+                |entry| {
+                    let (key, index) = deserialize_ordered_entry(&entry[1..])?;
+                    f(key, index)
+                },
+                range,
+                reverse,
+            )
+            .await
+    }
+
+    /// Returns at most `limit` `(key, index)` entries of the index `X` whose key is in the
+    /// range, in the order of the keys, then of the serialized indices, or in the reverse
+    /// order if `reverse` is set.
+    /// ```rust
+    /// # tokio_test::block_on(async {
+    /// # use linera_views::context::MemoryContext;
+    /// # use linera_views::indexed_map_view::{
+    /// #     IndexedMapView, IndexedValue, Ordered, OrderedSecondaryIndex,
+    /// # };
+    /// # use linera_views::views::View;
+    /// # use serde::{Deserialize, Serialize};
+    /// # #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+    /// # struct Task {
+    /// #     owner: u16,
+    /// #     priority: u8,
+    /// # }
+    /// # struct ByOwner;
+    /// # impl OrderedSecondaryIndex<Task> for ByOwner {
+    /// #     const ID: u8 = 0;
+    /// #     type Key = u16;
+    /// #     fn keys(task: &Task) -> Vec<u16> {
+    /// #         vec![task.owner]
+    /// #     }
+    /// # }
+    /// # impl IndexedValue for Task {
+    /// #     type Indexes = (Ordered<ByOwner>,);
+    /// # }
+    /// # let context = MemoryContext::new_for_testing(());
+    /// let mut map: IndexedMapView<_, u32, Task> = IndexedMapView::load(context).await.unwrap();
+    /// for (index, owner) in [(1 as u32, 3), (2, 5), (3, 300)] {
+    ///     let task = Task { owner, priority: 1 };
+    ///     map.insert(&index, task).await.unwrap();
+    /// }
+    /// let page = map
+    ///     .index_entries_page::<ByOwner, _>(.., 2, false)
+    ///     .await
+    ///     .unwrap();
+    /// assert_eq!(page, vec![(3, 1), (5, 2)]);
+    /// # })
+    /// ```
+    pub async fn index_entries_page<X, R>(
+        &self,
+        range: R,
+        limit: usize,
+        reverse: bool,
+    ) -> Result<Vec<(X::Key, I)>, ViewError>
+    where
+        X: OrderedSecondaryIndex<V>,
+        R: RangeBounds<X::Key>,
+    {
+        let mut entries = Vec::new();
+        if limit == 0 {
+            return Ok(entries);
+        }
+        self.for_each_index_entry_in_range_while::<X, _, _>(
+            |key, index| {
+                entries.push((key, index));
+                Ok(entries.len() < limit)
+            },
+            range,
+            reverse,
+        )
+        .await?;
+        Ok(entries)
+    }
+
+    /// Returns the `(key, index)` entries of the index `X` whose key is in the range, in
+    /// the order of the keys, then of the serialized indices.
+    /// ```rust
+    /// # tokio_test::block_on(async {
+    /// # use linera_views::context::MemoryContext;
+    /// # use linera_views::indexed_map_view::{
+    /// #     IndexedMapView, IndexedValue, Ordered, OrderedSecondaryIndex,
+    /// # };
+    /// # use linera_views::views::View;
+    /// # use serde::{Deserialize, Serialize};
+    /// # #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+    /// # struct Task {
+    /// #     owner: u16,
+    /// #     priority: u8,
+    /// # }
+    /// # struct ByOwner;
+    /// # impl OrderedSecondaryIndex<Task> for ByOwner {
+    /// #     const ID: u8 = 0;
+    /// #     type Key = u16;
+    /// #     fn keys(task: &Task) -> Vec<u16> {
+    /// #         vec![task.owner]
+    /// #     }
+    /// # }
+    /// # impl IndexedValue for Task {
+    /// #     type Indexes = (Ordered<ByOwner>,);
+    /// # }
+    /// # let context = MemoryContext::new_for_testing(());
+    /// let mut map: IndexedMapView<_, u32, Task> = IndexedMapView::load(context).await.unwrap();
+    /// for (index, owner) in [(1 as u32, 3), (2, 5), (3, 300)] {
+    ///     let task = Task { owner, priority: 1 };
+    ///     map.insert(&index, task).await.unwrap();
+    /// }
+    /// assert_eq!(
+    ///     map.index_entries_in_range::<ByOwner, _>(3..=5)
+    ///         .await
+    ///         .unwrap(),
+    ///     vec![(3, 1), (5, 2)]
+    /// );
+    /// # })
+    /// ```
+    pub async fn index_entries_in_range<X, R>(
+        &self,
+        range: R,
+    ) -> Result<Vec<(X::Key, I)>, ViewError>
+    where
+        X: OrderedSecondaryIndex<V>,
+        R: RangeBounds<X::Key>,
+    {
+        self.index_entries_page::<X, R>(range, usize::MAX, false)
+            .await
+    }
+}
+
+impl<C, I, V> HashableView for IndexedMapView<C, I, V>
+where
+    Self: View + Sync,
+    MapView<C, I, V>: HashableView,
+{
+    type Hasher = <MapView<C, I, V> as HashableView>::Hasher;
+
+    // The secondary indexes are derived from the values, so hashing the map is enough.
+    async fn hash_mut(&mut self) -> Result<<Self::Hasher as Hasher>::Output, ViewError> {
+        self.map.hash_mut().await
+    }
+
+    async fn hash(&self) -> Result<<Self::Hasher as Hasher>::Output, ViewError> {
+        self.map.hash().await
+    }
+}
+
+/// Type wrapping `IndexedMapView` while memoizing the hash.
+pub type HashedIndexedMapView<C, I, V> =
+    WrappedHashableContainerView<C, IndexedMapView<C, I, V>, HasherOutput>;
@@ -13,7 +13,13 @@
 //!
 //! Key tags to create the sub-keys of a `KeyValueStoreView` on top of the base key.
 
-use std::{collections::BTreeMap, fmt::Debug, mem, ops::Bound::Included, sync::Mutex};
+use std::{
+    collections::BTreeMap,
+    fmt::Debug,
+    mem,
+    ops::{Bound, Bound::Included},
+    sync::Mutex,
+};
 
 #[cfg(with_metrics)]
 use linera_base::prometheus_util::MeasureLatency as _;
@@ -23,8 +29,9 @@ use serde::{Deserialize, Serialize};
 use crate::{
     batch::{Batch, WriteOperation},
     common::{
-        from_bytes_option, from_bytes_option_or_default, get_interval, get_upper_bound,
-        DeletionSet, HasherOutput, SuffixClosedSetIterator, Update,
+        from_bytes_option, from_bytes_option_or_default, get_interval, get_interval_in_range,
+        get_upper_bound, is_above_lower_bound, is_below_upper_bound, order_and_limit, DeletionSet,
+        HasherOutput, RangeEntry, RangePager, SuffixClosedSetIterator, Update,
     },
     context::Context,
     map_view::ByteMapView,
@@ -144,6 +151,8 @@ enum KeyTag {
     Sizes,
     /// Prefix for the hash.
     Hash,
+    /// Prefix for the prior values of the keys written by versioned writes.
+    History,
 }
 
 /// A pair containing the key and value size.
@@ -181,6 +190,56 @@ impl SizeData {
     }
 }
 
+/// The configuration of the writes of a [`KeyValueStoreView`] that record the prior values
+/// of the keys, so that the view can be read as it was at past versions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct VersioningConfig {
+    /// The version of the writes, e.g. the height of the block being executed. Versions
+    /// must not decrease from one write to the next.
+    pub version: u64,
+    /// The number of versions for which the prior values are kept.
+    pub retention: u64,
+}
+
+/// The versions at which a [`KeyValueStoreView`] written with versioned writes can be
+/// read. Reads at other versions are rejected, since the prior values needed to answer
+/// them may be missing or discarded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HistoryWindow {
+    /// The version of the first writes that recorded the prior values. All the writes
+    /// since then must be versioned writes.
+    pub start: u64,
+    /// The version of the next writes. The view is readable at the last version even
+    /// without any recorded history.
+    pub next_version: u64,
+    /// The retention of the versioned writes, see [`VersioningConfig::retention`].
+    pub retention: u64,
+}
+
+impl HistoryWindow {
+    /// Returns whether the view can be read as it was after the writes at `version`, i.e.
+    /// if `version` is the last version, or it is within the last `retention` versions and
+    /// the writes after it recorded the prior values.
+    pub fn contains(&self, version: u64) -> bool {
+        let Some(next) = version.checked_add(1) else {
+            return false;
+        };
+        next == self.next_version
+            || (next < self.next_version
+                && next >= self.start
+                && version.saturating_add(self.retention) >= self.next_version)
+    }
+}
+
+/// The value of a key before it was first written at some version.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct HistoryEntry {
+    /// The version at which the key was written.
+    version: u64,
+    /// The value of the key before that version, if any.
+    value: Option<Vec<u8>>,
+}
+
 /// A view that represents the functions of `KeyValueStore`.
 ///
 /// Comment on the data set:
@@ -206,12 +265,15 @@ pub struct KeyValueStoreView<C> {
     stored_total_size: SizeData,
     total_size: SizeData,
     sizes: ByteMapView<C, u32>,
+    history: ByteMapView<C, Vec<HistoryEntry>>,
     stored_hash: Option<HasherOutput>,
     hash: Mutex<Option<HasherOutput>>,
 }
 
 impl<C: Context> View for KeyValueStoreView<C> {
-    const NUM_INIT_KEYS: usize = 2 + ByteMapView::<C, u32>::NUM_INIT_KEYS;
+    const NUM_INIT_KEYS: usize = 2
+        + ByteMapView::<C, u32>::NUM_INIT_KEYS
+        + ByteMapView::<C, Vec<HistoryEntry>>::NUM_INIT_KEYS;
 
     type Context = C;
 
@@ -226,6 +288,11 @@ impl<C: Context> View for KeyValueStoreView<C> {
         let base_key = context.base_key().base_tag(KeyTag::Sizes as u8);
         let context_sizes = context.clone_with_base_key(base_key);
         v.extend(ByteMapView::<C, u32>::pre_load(&context_sizes)?);
+        let base_key = context.base_key().base_tag(KeyTag::History as u8);
+        let context_history = context.clone_with_base_key(base_key);
+        v.extend(ByteMapView::<C, Vec<HistoryEntry>>::pre_load(
+            &context_history,
+        )?);
         Ok(v)
     }
 
@@ -235,9 +302,20 @@ impl<C: Context> View for KeyValueStoreView<C> {
             from_bytes_option_or_default(values.get(1).ok_or(ViewError::PostLoadValuesError)?)?;
         let base_key = context.base_key().base_tag(KeyTag::Sizes as u8);
         let context_sizes = context.clone_with_base_key(base_key);
+        let num_sizes_keys = ByteMapView::<C, u32>::NUM_INIT_KEYS;
         let sizes = ByteMapView::post_load(
             context_sizes,
-            values.get(2..).ok_or(ViewError::PostLoadValuesError)?,
+            values
+                .get(2..2 + num_sizes_keys)
+                .ok_or(ViewError::PostLoadValuesError)?,
+        )?;
+        let base_key = context.base_key().base_tag(KeyTag::History as u8);
+        let context_history = context.clone_with_base_key(base_key);
+        let history = ByteMapView::post_load(
+            context_history,
+            values
+                .get(2 + num_sizes_keys..)
+                .ok_or(ViewError::PostLoadValuesError)?,
         )?;
         Ok(Self {
             context,
@@ -246,6 +324,7 @@ impl<C: Context> View for KeyValueStoreView<C> {
             stored_total_size: total_size,
             total_size,
             sizes,
+            history,
             stored_hash: hash,
             hash: Mutex::new(hash),
         })
@@ -262,6 +341,7 @@ impl<C: Context> View for KeyValueStoreView<C> {
         self.updates.clear();
         self.total_size = self.stored_total_size;
         self.sizes.rollback();
+        self.history.rollback();
         *self.hash.get_mut().unwrap() = self.stored_hash;
     }
 
@@ -278,6 +358,9 @@ impl<C: Context> View for KeyValueStoreView<C> {
         if self.sizes.has_pending_changes().await {
             return true;
         }
+        if self.history.has_pending_changes().await {
+            return true;
+        }
         let hash = self.hash.lock().unwrap();
         self.stored_hash != *hash
     }
@@ -319,6 +402,7 @@ impl<C: Context> View for KeyValueStoreView<C> {
             }
         }
         self.sizes.flush(batch)?;
+        self.history.flush(batch)?;
         let hash = *self.hash.get_mut().unwrap();
         if self.stored_hash != hash {
             let key = self.context.base_key().base_tag(KeyTag::Hash as u8);
@@ -342,6 +426,7 @@ impl<C: Context> View for KeyValueStoreView<C> {
         self.updates.clear();
         self.total_size = SizeData::default();
         self.sizes.clear();
+        self.history.clear();
         *self.hash.get_mut().unwrap() = None;
     }
 }
@@ -355,6 +440,7 @@ impl<C: Context> ClonableView for KeyValueStoreView<C> {
             stored_total_size: self.stored_total_size,
             total_size: self.total_size,
             sizes: self.sizes.clone_unchecked()?,
+            history: self.history.clone_unchecked()?,
             stored_hash: self.stored_hash,
             hash: Mutex::new(*self.hash.get_mut().unwrap()),
         })
@@ -921,6 +1007,95 @@ impl<C: Context> KeyValueStoreView<C> {
         Ok(())
     }
 
+    /// Applies the given batch like [`Self::write_batch`], recording the prior values of
+    /// the keys written for the first time at `config.version`. The prior values of a key
+    /// that are older than `config.retention` versions are discarded when the key is
+    /// written again, so the view is only read at past versions within a [`HistoryWindow`].
+    /// ```rust
+    /// # tokio_test::block_on(async {
+    /// # use linera_views::context::MemoryContext;
+    /// # use linera_views::key_value_store_view::{
+    /// #     HistoryWindow, KeyValueStoreView, VersioningConfig,
+    /// # };
+    /// # use linera_views::batch::Batch;
+    /// # use linera_views::views::View;
+    /// # let context = MemoryContext::new_for_testing(());
+    /// let mut view = KeyValueStoreView::load(context).await.unwrap();
+    /// let mut batch = Batch::new();
+    /// batch.put_key_value_bytes(vec![0, 1], vec![34]);
+    /// let config = VersioningConfig {
+    ///     version: 1,
+    ///     retention: 10,
+    /// };
+    /// view.write_batch_versioned(batch, config).await.unwrap();
+    /// let mut batch = Batch::new();
+    /// batch.put_key_value_bytes(vec![0, 1], vec![42]);
+    /// let config = VersioningConfig {
+    ///     version: 2,
+    ///     retention: 10,
+    /// };
+    /// view.write_batch_versioned(batch, config).await.unwrap();
+    /// let window = HistoryWindow {
+    ///     start: 1,
+    ///     next_version: 3,
+    ///     retention: 10,
+    /// };
+    /// assert_eq!(view.get_at_version(&[0, 1], 0, window).await.unwrap(), None);
+    /// assert_eq!(
+    ///     view.get_at_version(&[0, 1], 1, window).await.unwrap(),
+    ///     Some(vec![34])
+    /// );
+    /// assert_eq!(
+    ///     view.get_at_version(&[0, 1], 2, window).await.unwrap(),
+    ///     Some(vec![42])
+    /// );
+    /// assert!(view.get_at_version(&[0, 1], 3, window).await.is_err());
+    /// # })
+    /// ```
+    pub async fn write_batch_versioned(
+        &mut self,
+        batch: Batch,
+        config: VersioningConfig,
+    ) -> Result<(), ViewError> {
+        for operation in &batch.operations {
+            match operation {
+                WriteOperation::Delete { key } | WriteOperation::Put { key, .. } => {
+                    self.record_prior_value(key.clone(), config).await?;
+                }
+                WriteOperation::DeletePrefix { key_prefix } => {
+                    for suffix in self.find_keys_by_prefix(key_prefix).await? {
+                        let key = [key_prefix.as_slice(), &suffix].concat();
+                        self.record_prior_value(key, config).await?;
+                    }
+                }
+            }
+        }
+        self.write_batch(batch).await
+    }
+
+    /// Records the current value of an index as its prior value at `config.version`,
+    /// unless the index was already written at that version.
+    async fn record_prior_value(
+        &mut self,
+        index: Vec<u8>,
+        config: VersioningConfig,
+    ) -> Result<(), ViewError> {
+        let value = self.get(&index).await?;
+        let entries = self.history.get_mut_or_default(&index).await?;
+        if entries
+            .last()
+            .is_some_and(|entry| entry.version >= config.version)
+        {
+            return Ok(());
+        }
+        entries.retain(|entry| entry.version.saturating_add(config.retention) > config.version);
+        entries.push(HistoryEntry {
+            version: config.version,
+            value,
+        });
+        Ok(())
+    }
+
     /// Sets or inserts a value.
     /// ```rust
     /// # tokio_test::block_on(async {
@@ -1135,6 +1310,361 @@ impl<C: Context> KeyValueStoreView<C> {
         Ok(key_values)
     }
 
+    /// Returns the keys matching the given prefix whose remainder lies in `range`. The
+    /// keys are returned without the prefix, in lexicographic order, or in reverse
+    /// lexicographic order if `reverse` is set, and at most `limit` of them are returned.
+    /// ```rust
+    /// # tokio_test::block_on(async {
+    /// # use std::ops::Bound;
+    /// # use linera_views::context::MemoryContext;
+    /// # use linera_views::key_value_store_view::KeyValueStoreView;
+    /// # use linera_views::views::View;
+    /// # let context = MemoryContext::new_for_testing(());
+    /// let mut view = KeyValueStoreView::load(context).await.unwrap();
+    /// view.insert(vec![0, 1], vec![34]).await.unwrap();
+    /// view.insert(vec![0, 2], vec![42]).await.unwrap();
+    /// view.insert(vec![0, 3], vec![17]).await.unwrap();
+    /// let range = (Bound::Excluded(vec![1]), Bound::Unbounded);
+    /// let keys = view
+    ///     .find_keys_by_prefix_in_range(&[0], range, true, Some(1))
+    ///     .await
+    ///     .unwrap();
+    /// assert_eq!(keys, vec![vec![3]]);
+    /// # })
+    /// ```
+    pub async fn find_keys_by_prefix_in_range(
+        &self,
+        key_prefix: &[u8],
+        range: (Bound<Vec<u8>>, Bound<Vec<u8>>),
+        reverse: bool,
+        limit: Option<usize>,
+    ) -> Result<Vec<Vec<u8>>, ViewError> {
+        let key_values = self
+            .find_entries_in_range(key_prefix, range, reverse, limit, false)
+            .await?;
+        Ok(key_values.into_iter().map(|(key, _)| key).collect())
+    }
+
+    /// Returns the key-value pairs, for keys matching the given prefix whose remainder
+    /// lies in `range`. The pairs are returned without the prefix, ordered and limited as
+    /// in `find_keys_by_prefix_in_range`.
+    pub async fn find_key_values_by_prefix_in_range(
+        &self,
+        key_prefix: &[u8],
+        range: (Bound<Vec<u8>>, Bound<Vec<u8>>),
+        reverse: bool,
+        limit: Option<usize>,
+    ) -> Result<Vec<(Vec<u8>, Vec<u8>)>, ViewError> {
+        self.find_entries_in_range(key_prefix, range, reverse, limit, true)
+            .await
+    }
+
+    /// Reads the stored entries in the range by pages and merges them with the pending
+    /// updates. The values are only read if `with_values` is set.
+    async fn find_entries_in_range(
+        &self,
+        key_prefix: &[u8],
+        range: (Bound<Vec<u8>>, Bound<Vec<u8>>),
+        reverse: bool,
+        limit: Option<usize>,
+        with_values: bool,
+    ) -> Result<Vec<(Vec<u8>, Vec<u8>)>, ViewError> {
+        ensure!(
+            key_prefix.len() <= self.max_key_size(),
+            ViewError::KeyTooLong
+        );
+        let len = key_prefix.len();
+        // The pages are read with the full indices, as the pending updates are.
+        let index_prefix = self.context.base_key().base_tag(KeyTag::Index as u8);
+        let range = get_interval_in_range(key_prefix, &range);
+        let mut pager = RangePager::new(range, reverse, limit);
+        let mut key_values = Vec::new();
+        while let Some((range, page_size)) = pager.next_page() {
+            let stored = if self.deletion_set.delete_storage_first {
+                Vec::new()
+            } else if with_values {
+                self.context
+                    .store()
+                    .find_key_values_by_prefix_in_range(&index_prefix, range, reverse, page_size)
+                    .await?
+            } else {
+                self.context
+                    .store()
+                    .find_keys_by_prefix_in_range(&index_prefix, range, reverse, page_size)
+                    .await?
+                    .into_iter()
+                    .map(|key| (key, Vec::new()))
+                    .collect()
+            };
+            let deleted_prefixes = &self.deletion_set.deleted_prefixes;
+            for (key, entry) in pager.merge_page(stored, deleted_prefixes, &self.updates) {
+                let value = match entry {
+                    RangeEntry::Stored(value) => value,
+                    RangeEntry::Pending(value) if with_values => value.clone(),
+                    RangeEntry::Pending(_) => Vec::new(),
+                };
+                key_values.push((key[len..].to_vec(), value));
+                if limit == Some(key_values.len()) {
+                    return Ok(key_values);
+                }
+            }
+        }
+        Ok(key_values)
+    }
+
+    /// Obtains the value at the given index as it was after the writes at the given
+    /// version, if any. Versions outside of the history `window` are rejected.
+    /// ```rust
+    /// # tokio_test::block_on(async {
+    /// # use linera_views::context::MemoryContext;
+    /// # use linera_views::key_value_store_view::{
+    /// #     HistoryWindow, KeyValueStoreView, VersioningConfig,
+    /// # };
+    /// # use linera_views::batch::Batch;
+    /// # use linera_views::views::View;
+    /// # let context = MemoryContext::new_for_testing(());
+    /// let mut view = KeyValueStoreView::load(context).await.unwrap();
+    /// view.insert(vec![0, 1], vec![34]).await.unwrap();
+    /// let mut batch = Batch::new();
+    /// batch.delete_key(vec![0, 1]);
+    /// let config = VersioningConfig {
+    ///     version: 5,
+    ///     retention: 10,
+    /// };
+    /// view.write_batch_versioned(batch, config).await.unwrap();
+    /// let window = HistoryWindow {
+    ///     start: 5,
+    ///     next_version: 6,
+    ///     retention: 10,
+    /// };
+    /// assert_eq!(
+    ///     view.get_at_version(&[0, 1], 4, window).await.unwrap(),
+    ///     Some(vec![34])
+    /// );
+    /// assert_eq!(view.get_at_version(&[0, 1], 5, window).await.unwrap(), None);
+    /// // The writes before version 5 did not record the prior values.
+    /// assert!(view.get_at_version(&[0, 1], 3, window).await.is_err());
+    /// # })
+    /// ```
+    pub async fn get_at_version(
+        &self,
+        index: &[u8],
+        version: u64,
+        window: HistoryWindow,
+    ) -> Result<Option<Vec<u8>>, ViewError> {
+        let entries = self.history.get(index).await?;
+        match find_prior_value(entries, version, window)? {
+            Some(value) => Ok(value),
+            None => self.get(index).await,
+        }
+    }
+
+    /// Tests whether the view contained a specific index after the writes at the given
+    /// version.
+    pub async fn contains_key_at_version(
+        &self,
+        index: &[u8],
+        version: u64,
+        window: HistoryWindow,
+    ) -> Result<bool, ViewError> {
+        Ok(self.get_at_version(index, version, window).await?.is_some())
+    }
+
+    /// Tests whether the view contained a range of indices after the writes at the given
+    /// version.
+    pub async fn contains_keys_at_version(
+        &self,
+        indices: Vec<Vec<u8>>,
+        version: u64,
+        window: HistoryWindow,
+    ) -> Result<Vec<bool>, ViewError> {
+        let values = self.multi_get_at_version(indices, version, window).await?;
+        Ok(values.iter().map(Option::is_some).collect())
+    }
+
+    /// Obtains the values of a range of indices as they were after the writes at the given
+    /// version.
+    /// ```rust
+    /// # tokio_test::block_on(async {
+    /// # use linera_views::context::MemoryContext;
+    /// # use linera_views::key_value_store_view::{
+    /// #     HistoryWindow, KeyValueStoreView, VersioningConfig,
+    /// # };
+    /// # use linera_views::batch::Batch;
+    /// # use linera_views::views::View;
+    /// # let context = MemoryContext::new_for_testing(());
+    /// let mut view = KeyValueStoreView::load(context).await.unwrap();
+    /// let mut batch = Batch::new();
+    /// batch.put_key_value_bytes(vec![0, 1], vec![34]);
+    /// let config = VersioningConfig {
+    ///     version: 5,
+    ///     retention: 10,
+    /// };
+    /// view.write_batch_versioned(batch, config).await.unwrap();
+    /// let window = HistoryWindow {
+    ///     start: 5,
+    ///     next_version: 6,
+    ///     retention: 10,
+    /// };
+    /// let indices = vec![vec![0, 1], vec![0, 2]];
+    /// assert_eq!(
+    ///     view.multi_get_at_version(indices.clone(), 4, window)
+    ///         .await
+    ///         .unwrap(),
+    ///     vec![None, None]
+    /// );
+    /// assert_eq!(
+    ///     view.multi_get_at_version(indices, 5, window).await.unwrap(),
+    ///     vec![Some(vec![34]), None]
+    /// );
+    /// # })
+    /// ```
+    pub async fn multi_get_at_version(
+        &self,
+        indices: Vec<Vec<u8>>,
+        version: u64,
+        window: HistoryWindow,
+    ) -> Result<Vec<Option<Vec<u8>>>, ViewError> {
+        ensure!(
+            window.contains(version),
+            ViewError::UnavailableVersion(version)
+        );
+        let histories = self.history.multi_get(indices.clone()).await?;
+        let mut values = self.multi_get(indices).await?;
+        for (value, entries) in values.iter_mut().zip(histories) {
+            if let Some(prior_value) = find_prior_value(entries, version, window)? {
+                *value = prior_value;
+            }
+        }
+        Ok(values)
+    }
+
+    /// Returns the keys matching the given prefix after the writes at the given version.
+    /// The prefix is not included in the returned keys.
+    pub async fn find_keys_by_prefix_at_version(
+        &self,
+        key_prefix: &[u8],
+        version: u64,
+        window: HistoryWindow,
+    ) -> Result<Vec<Vec<u8>>, ViewError> {
+        let key_values = self
+            .find_key_values_by_prefix_at_version(key_prefix, version, window)
+            .await?;
+        Ok(key_values.into_iter().map(|(key, _)| key).collect())
+    }
+
+    /// Returns the key-value pairs, for keys matching the given prefix, after the writes at
+    /// the given version. The prefix is not included in the returned keys.
+    /// ```rust
+    /// # tokio_test::block_on(async {
+    /// # use linera_views::context::MemoryContext;
+    /// # use linera_views::key_value_store_view::{
+    /// #     HistoryWindow, KeyValueStoreView, VersioningConfig,
+    /// # };
+    /// # use linera_views::batch::Batch;
+    /// # use linera_views::views::View;
+    /// # let context = MemoryContext::new_for_testing(());
+    /// let mut view = KeyValueStoreView::load(context).await.unwrap();
+    /// view.insert(vec![0, 1], vec![34]).await.unwrap();
+    /// let mut batch = Batch::new();
+    /// batch.delete_key_prefix(vec![0]);
+    /// batch.put_key_value_bytes(vec![0, 2], vec![42]);
+    /// let config = VersioningConfig {
+    ///     version: 5,
+    ///     retention: 10,
+    /// };
+    /// view.write_batch_versioned(batch, config).await.unwrap();
+    /// let window = HistoryWindow {
+    ///     start: 5,
+    ///     next_version: 6,
+    ///     retention: 10,
+    /// };
+    /// let key_values = view
+    ///     .find_key_values_by_prefix_at_version(&[0], 4, window)
+    ///     .await
+    ///     .unwrap();
+    /// assert_eq!(key_values, vec![(vec![1], vec![34])]);
+    /// let key_values = view
+    ///     .find_key_values_by_prefix_at_version(&[0], 5, window)
+    ///     .await
+    ///     .unwrap();
+    /// assert_eq!(key_values, vec![(vec![2], vec![42])]);
+    /// # })
+    /// ```
+    pub async fn find_key_values_by_prefix_at_version(
+        &self,
+        key_prefix: &[u8],
+        version: u64,
+        window: HistoryWindow,
+    ) -> Result<Vec<(Vec<u8>, Vec<u8>)>, ViewError> {
+        ensure!(
+            window.contains(version),
+            ViewError::UnavailableVersion(version)
+        );
+        let mut key_values = self
+            .find_key_values_by_prefix(key_prefix)
+            .await?
+            .into_iter()
+            .collect::<BTreeMap<_, _>>();
+        let histories = self
+            .history
+            .key_values_by_prefix(key_prefix.to_vec())
+            .await?;
+        for (index, entries) in histories {
+            if let Some(prior_value) = find_prior_value(Some(entries), version, window)? {
+                let key = index[key_prefix.len()..].to_vec();
+                match prior_value {
+                    Some(value) => key_values.insert(key, value),
+                    None => key_values.remove(&key),
+                };
+            }
+        }
+        Ok(key_values.into_iter().collect())
+    }
+
+    /// Returns the key-value pairs, for keys matching the given prefix whose remainder
+    /// lies in `range`, after the writes at the given version. The pairs are returned
+    /// without the prefix, ordered and limited as in `find_keys_by_prefix_in_range`.
+    pub async fn find_key_values_by_prefix_in_range_at_version(
+        &self,
+        key_prefix: &[u8],
+        range: (Bound<Vec<u8>>, Bound<Vec<u8>>),
+        reverse: bool,
+        limit: Option<usize>,
+        version: u64,
+        window: HistoryWindow,
+    ) -> Result<Vec<(Vec<u8>, Vec<u8>)>, ViewError> {
+        let key_values = self
+            .find_key_values_by_prefix_at_version(key_prefix, version, window)
+            .await?
+            .into_iter()
+            .filter(|(key, _)| {
+                is_above_lower_bound(key, &range.0) && is_below_upper_bound(key, &range.1)
+            })
+            .collect();
+        Ok(order_and_limit(key_values, reverse, limit))
+    }
+
+    /// Returns the keys matching the given prefix whose remainder lies in `range`, after
+    /// the writes at the given version, ordered and limited as in
+    /// `find_keys_by_prefix_in_range`.
+    pub async fn find_keys_by_prefix_in_range_at_version(
+        &self,
+        key_prefix: &[u8],
+        range: (Bound<Vec<u8>>, Bound<Vec<u8>>),
+        reverse: bool,
+        limit: Option<usize>,
+        version: u64,
+        window: HistoryWindow,
+    ) -> Result<Vec<Vec<u8>>, ViewError> {
+        let key_values = self
+            .find_key_values_by_prefix_in_range_at_version(
+                key_prefix, range, reverse, limit, version, window,
+            )
+            .await?;
+        Ok(key_values.into_iter().map(|(key, _)| key).collect())
+    }
+
     async fn compute_hash(&self) -> Result<<sha3::Sha3_256 as Hasher>::Output, ViewError> {
         #[cfg(with_metrics)]
         let _hash_latency = metrics::KEY_VALUE_STORE_VIEW_HASH_LATENCY.measure_latency();
@@ -1152,6 +1682,27 @@ impl<C: Context> KeyValueStoreView<C> {
     }
 }
 
+/// Returns the prior value recorded at the first version after `version`, if any. That is
+/// the value after the writes at `version`, if the index was written since. Versions
+/// outside of the history `window` are rejected, since the prior values of the writes
+/// after them may be missing or discarded.
+fn find_prior_value(
+    entries: Option<Vec<HistoryEntry>>,
+    version: u64,
+    window: HistoryWindow,
+) -> Result<Option<Option<Vec<u8>>>, ViewError> {
+    ensure!(
+        window.contains(version),
+        ViewError::UnavailableVersion(version)
+    );
+    Ok(entries.and_then(|entries| {
+        entries
+            .into_iter()
+            .find(|entry| entry.version > version)
+            .map(|entry| entry.value)
+    }))
+}
+
 impl<C: Context> HashableView for KeyValueStoreView<C> {
     type Hasher = sha3::Sha3_256;
 
@@ -1260,6 +1811,32 @@ impl<C: Context> ReadableKeyValueStore for ViewContainer<C> {
         let view = self.view.read().await;
         Ok(view.find_key_values_by_prefix(key_prefix).await?)
     }
+
+    async fn find_keys_by_prefix_in_range(
+        &self,
+        key_prefix: &[u8],
+        range: (Bound<Vec<u8>>, Bound<Vec<u8>>),
+        reverse: bool,
+        limit: Option<usize>,
+    ) -> Result<Vec<Vec<u8>>, ViewContainerError> {
+        let view = self.view.read().await;
+        Ok(view
+            .find_keys_by_prefix_in_range(key_prefix, range, reverse, limit)
+            .await?)
+    }
+
+    async fn find_key_values_by_prefix_in_range(
+        &self,
+        key_prefix: &[u8],
+        range: (Bound<Vec<u8>>, Bound<Vec<u8>>),
+        reverse: bool,
+        limit: Option<usize>,
+    ) -> Result<Vec<(Vec<u8>, Vec<u8>)>, ViewContainerError> {
+        let view = self.view.read().await;
+        Ok(view
+            .find_key_values_by_prefix_in_range(key_prefix, range, reverse, limit)
+            .await?)
+    }
 }
 
 #[cfg(with_testing)]
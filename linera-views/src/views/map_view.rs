@@ -42,6 +42,7 @@ use std::{
     collections::{btree_map::Entry, BTreeMap},
     marker::PhantomData,
     mem,
+    ops::{Bound, RangeBounds},
 };
 
 use serde::{de::DeserializeOwned, Serialize};
@@ -49,8 +50,9 @@ use serde::{de::DeserializeOwned, Serialize};
 use crate::{
     batch::Batch,
     common::{
-        from_bytes_option, get_interval, CustomSerialize, DeletionSet, HasherOutput,
-        SuffixClosedSetIterator, Update,
+        from_bytes_option, get_interval, get_range_bounds, serialize_range_bounds, CustomSerialize,
+        DeletionSet, HasherOutput, RangeEntry, RangePager, SuffixClosedSetIterator, Update,
+        INITIAL_RANGE_PAGE_SIZE,
     },
     context::{BaseKey, Context},
     hashable_wrapper::WrappedHashableContainerView,
@@ -859,6 +861,349 @@ where
     }
 }
 
+impl<C, V> ByteMapView<C, V>
+where
+    C: Context,
+    V: Clone + Serialize + DeserializeOwned + 'static,
+{
+    /// Applies the function f on each key of the map in the range, in lexicographic
+    /// order, or in reverse lexicographic order if `reverse` is set. If the function
+    /// returns false, then the loop ends prematurely.
+    /// ```rust
+    /// # tokio_test::block_on(async {
+    /// # use linera_views::context::MemoryContext;
+    /// # use linera_views::map_view::ByteMapView;
+    /// # use linera_views::views::View;
+    /// # let context = MemoryContext::new_for_testing(());
+    /// let mut map = ByteMapView::load(context).await.unwrap();
+    /// map.insert(vec![0, 1], String::from("Hello"));
+    /// map.insert(vec![1, 2], String::from("Bonjour"));
+    /// map.insert(vec![2, 3], String::from("Hallo"));
+    /// let mut keys = Vec::new();
+    /// map.for_each_key_in_range_while(
+    ///     |key| {
+    ///         keys.push(key.to_vec());
+    ///         Ok(true)
+    ///     },
+    ///     vec![1]..,
+    ///     true,
+    /// )
+    /// .await
+    /// .unwrap();
+    /// assert_eq!(keys, vec![vec![2, 3], vec![1, 2]]);
+    /// # })
+    /// ```
+    pub async fn for_each_key_in_range_while<F, R>(
+        &self,
+        f: F,
+        range: R,
+        reverse: bool,
+    ) -> Result<(), ViewError>
+    where
+        F: FnMut(&[u8]) -> Result<bool, ViewError> + Send,
+        R: RangeBounds<Vec<u8>>,
+    {
+        self.for_each_key_in_pages_while(
+            f,
+            get_range_bounds(range),
+            reverse,
+            Some(INITIAL_RANGE_PAGE_SIZE),
+        )
+        .await
+    }
+
+    /// Applies a function f on each key of the map in the range, reading the stored keys
+    /// by pages of `page_size` keys or more, or all at once if `None`. If f returns false,
+    /// the loop ends prematurely.
+    async fn for_each_key_in_pages_while<F>(
+        &self,
+        mut f: F,
+        range: (Bound<Vec<u8>>, Bound<Vec<u8>>),
+        reverse: bool,
+        page_size: Option<usize>,
+    ) -> Result<(), ViewError>
+    where
+        F: FnMut(&[u8]) -> Result<bool, ViewError> + Send,
+    {
+        let mut pager = RangePager::new(range, reverse, page_size);
+        while let Some((range, limit)) = pager.next_page() {
+            let stored = if self.deletion_set.delete_storage_first {
+                Vec::new()
+            } else {
+                self.context
+                    .store()
+                    .find_keys_by_prefix_in_range(
+                        &self.context.base_key().bytes,
+                        range,
+                        reverse,
+                        limit,
+                    )
+                    .await?
+                    .into_iter()
+                    .map(|key| (key, ()))
+                    .collect()
+            };
+            let deleted_prefixes = &self.deletion_set.deleted_prefixes;
+            for (key, _) in pager.merge_page(stored, deleted_prefixes, &self.updates) {
+                if !f(&key)? {
+                    return Ok(());
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Applies a function f on each key/value pair of the map in the range. The value is
+    /// either the value itself or its serialization, so that stored values are only
+    /// deserialized when needed. Keys are visited in lexicographic order, or in reverse
+    /// lexicographic order if `reverse` is set, and the stored entries are read by pages of
+    /// `page_size` entries or more, or all at once if `None`. If f returns false, the loop
+    /// ends prematurely.
+    async fn for_each_key_value_or_bytes_in_pages_while<'a, F>(
+        &'a self,
+        mut f: F,
+        range: (Bound<Vec<u8>>, Bound<Vec<u8>>),
+        reverse: bool,
+        page_size: Option<usize>,
+    ) -> Result<(), ViewError>
+    where
+        F: FnMut(&[u8], ValueOrBytes<'a, V>) -> Result<bool, ViewError> + Send,
+    {
+        let mut pager = RangePager::new(range, reverse, page_size);
+        while let Some((range, limit)) = pager.next_page() {
+            let stored = if self.deletion_set.delete_storage_first {
+                Vec::new()
+            } else {
+                self.context
+                    .store()
+                    .find_key_values_by_prefix_in_range(
+                        &self.context.base_key().bytes,
+                        range,
+                        reverse,
+                        limit,
+                    )
+                    .await?
+            };
+            let deleted_prefixes = &self.deletion_set.deleted_prefixes;
+            for (key, entry) in pager.merge_page(stored, deleted_prefixes, &self.updates) {
+                let value = match entry {
+                    RangeEntry::Stored(bytes) => ValueOrBytes::Bytes(bytes),
+                    RangeEntry::Pending(value) => ValueOrBytes::Value(value),
+                };
+                if !f(&key, value)? {
+                    return Ok(());
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns at most `limit` key/value pairs of the map in the range, or all of them if
+    /// `None`, in the requested order.
+    async fn key_values_in_pages(
+        &self,
+        range: (Bound<Vec<u8>>, Bound<Vec<u8>>),
+        limit: Option<usize>,
+        reverse: bool,
+    ) -> Result<Vec<(Vec<u8>, V)>, ViewError> {
+        let mut key_values = Vec::new();
+        self.for_each_key_value_or_bytes_in_pages_while(
+            |key, value| {
+                key_values.push((key.to_vec(), value.to_value()?.into_owned()));
+                Ok(limit.is_none_or(|limit| key_values.len() < limit))
+            },
+            range,
+            reverse,
+            limit,
+        )
+        .await?;
+        Ok(key_values)
+    }
+
+    /// Applies a function f on each key/value pair of the map in the range, in
+    /// lexicographic order, or in reverse lexicographic order if `reverse` is set. If the
+    /// function returns false, then the loop ends prematurely.
+    /// ```rust
+    /// # tokio_test::block_on(async {
+    /// # use linera_views::context::MemoryContext;
+    /// # use linera_views::map_view::ByteMapView;
+    /// # use linera_views::views::View;
+    /// # let context = MemoryContext::new_for_testing(());
+    /// let mut map = ByteMapView::load(context).await.unwrap();
+    /// map.insert(vec![0, 1], String::from("Hello"));
+    /// map.insert(vec![1, 2], String::from("Bonjour"));
+    /// map.insert(vec![2, 3], String::from("Hallo"));
+    /// let mut values = Vec::new();
+    /// map.for_each_key_value_in_range_while(
+    ///     |_key, value| {
+    ///         values.push(value.into_owned());
+    ///         Ok(false)
+    ///     },
+    ///     vec![1]..,
+    ///     false,
+    /// )
+    /// .await
+    /// .unwrap();
+    /// assert_eq!(values, vec![String::from("Bonjour")]);
+    /// # })
+    /// ```
+    pub async fn for_each_key_value_in_range_while<'a, F, R>(
+        &'a self,
+        mut f: F,
+        range: R,
+        reverse: bool,
+    ) -> Result<(), ViewError>
+    where
+        F: FnMut(&[u8], Cow<'a, V>) -> Result<bool, ViewError> + Send,
+        R: RangeBounds<Vec<u8>>,
+    {
+        self.for_each_key_value_or_bytes_in_pages_while(
+            |key, value| {
+                let value = value.to_value()?;
+                f(key, value)
+            },
+            get_range_bounds(range),
+            reverse,
+            Some(INITIAL_RANGE_PAGE_SIZE),
+        )
+        .await
+    }
+
+    /// Returns the keys of the map in the range, in lexicographic order.
+    /// ```rust
+    /// # tokio_test::block_on(async {
+    /// # use linera_views::context::MemoryContext;
+    /// # use linera_views::map_view::ByteMapView;
+    /// # use linera_views::views::View;
+    /// # let context = MemoryContext::new_for_testing(());
+    /// let mut map = ByteMapView::load(context).await.unwrap();
+    /// map.insert(vec![0, 1], String::from("Hello"));
+    /// map.insert(vec![1, 2], String::from("Bonjour"));
+    /// map.insert(vec![2, 3], String::from("Hallo"));
+    /// assert_eq!(
+    ///     map.keys_in_range(vec![0, 2]..vec![2]).await.unwrap(),
+    ///     vec![vec![1, 2]]
+    /// );
+    /// # })
+    /// ```
+    pub async fn keys_in_range<R>(&self, range: R) -> Result<Vec<Vec<u8>>, ViewError>
+    where
+        R: RangeBounds<Vec<u8>>,
+    {
+        let mut keys = Vec::new();
+        self.for_each_key_in_pages_while(
+            |key| {
+                keys.push(key.to_vec());
+                Ok(true)
+            },
+            get_range_bounds(range),
+            false,
+            None,
+        )
+        .await?;
+        Ok(keys)
+    }
+
+    /// Returns at most `limit` key/value pairs of the map in the range, in lexicographic
+    /// order, or in reverse lexicographic order if `reverse` is set. The next page starts
+    /// right after the last returned key, which can be excluded from the next range.
+    /// ```rust
+    /// # tokio_test::block_on(async {
+    /// # use std::ops::Bound;
+    /// # use linera_views::context::MemoryContext;
+    /// # use linera_views::map_view::ByteMapView;
+    /// # use linera_views::views::View;
+    /// # let context = MemoryContext::new_for_testing(());
+    /// let mut map = ByteMapView::load(context).await.unwrap();
+    /// map.insert(vec![0, 1], String::from("Hello"));
+    /// map.insert(vec![1, 2], String::from("Bonjour"));
+    /// map.insert(vec![2, 3], String::from("Hallo"));
+    /// let page = map.key_values_page(.., 2, false).await.unwrap();
+    /// assert_eq!(page.len(), 2);
+    /// let cursor = page.last().unwrap().0.clone();
+    /// let range = (Bound::Excluded(cursor), Bound::Unbounded);
+    /// let page = map.key_values_page(range, 2, false).await.unwrap();
+    /// assert_eq!(page, vec![(vec![2, 3], String::from("Hallo"))]);
+    /// # })
+    /// ```
+    pub async fn key_values_page<R>(
+        &self,
+        range: R,
+        limit: usize,
+        reverse: bool,
+    ) -> Result<Vec<(Vec<u8>, V)>, ViewError>
+    where
+        R: RangeBounds<Vec<u8>>,
+    {
+        self.key_values_in_pages(get_range_bounds(range), Some(limit), reverse)
+            .await
+    }
+
+    /// Returns the key/value pairs of the map in the range, in lexicographic order.
+    /// ```rust
+    /// # tokio_test::block_on(async {
+    /// # use linera_views::context::MemoryContext;
+    /// # use linera_views::map_view::ByteMapView;
+    /// # use linera_views::views::View;
+    /// # let context = MemoryContext::new_for_testing(());
+    /// let mut map = ByteMapView::load(context).await.unwrap();
+    /// map.insert(vec![0, 1], String::from("Hello"));
+    /// map.insert(vec![1, 2], String::from("Bonjour"));
+    /// assert_eq!(
+    ///     map.key_values_in_range(vec![1]..).await.unwrap(),
+    ///     vec![(vec![1, 2], String::from("Bonjour"))]
+    /// );
+    /// # })
+    /// ```
+    pub async fn key_values_in_range<R>(&self, range: R) -> Result<Vec<(Vec<u8>, V)>, ViewError>
+    where
+        R: RangeBounds<Vec<u8>>,
+    {
+        self.key_values_in_pages(get_range_bounds(range), None, false)
+            .await
+    }
+
+    /// Returns the key/value pair of the map with the smallest key, if any.
+    /// ```rust
+    /// # tokio_test::block_on(async {
+    /// # use linera_views::context::MemoryContext;
+    /// # use linera_views::map_view::ByteMapView;
+    /// # use linera_views::views::View;
+    /// # let context = MemoryContext::new_for_testing(());
+    /// let mut map = ByteMapView::load(context).await.unwrap();
+    /// map.insert(vec![0, 1], String::from("Hello"));
+    /// map.insert(vec![1, 2], String::from("Bonjour"));
+    /// assert_eq!(
+    ///     map.first_key_value().await.unwrap(),
+    ///     Some((vec![0, 1], String::from("Hello")))
+    /// );
+    /// # })
+    /// ```
+    pub async fn first_key_value(&self) -> Result<Option<(Vec<u8>, V)>, ViewError> {
+        Ok(self.key_values_page(.., 1, false).await?.pop())
+    }
+
+    /// Returns the key/value pair of the map with the largest key, if any.
+    /// ```rust
+    /// # tokio_test::block_on(async {
+    /// # use linera_views::context::MemoryContext;
+    /// # use linera_views::map_view::ByteMapView;
+    /// # use linera_views::views::View;
+    /// # let context = MemoryContext::new_for_testing(());
+    /// let mut map = ByteMapView::load(context).await.unwrap();
+    /// map.insert(vec![0, 1], String::from("Hello"));
+    /// map.insert(vec![1, 2], String::from("Bonjour"));
+    /// assert_eq!(
+    ///     map.last_key_value().await.unwrap(),
+    ///     Some((vec![1, 2], String::from("Bonjour")))
+    /// );
+    /// # })
+    /// ```
+    pub async fn last_key_value(&self) -> Result<Option<(Vec<u8>, V)>, ViewError> {
+        Ok(self.key_values_page(.., 1, true).await?.pop())
+    }
+}
+
 impl<C, V> ByteMapView<C, V>
 where
     C: Context,
@@ -1872,6 +2217,192 @@ where
     }
 }
 
+impl<C, I, V> CustomMapView<C, I, V>
+where
+    C: Context,
+    I: Send + CustomSerialize,
+    V: Clone + Sync + Send + Serialize + DeserializeOwned + 'static,
+{
+    /// Applies a function f on each index in the range. Indices are visited in the order
+    /// determined by the custom serialization, or in the reverse order if `reverse` is set. If the
+    /// function returns false, then the loop ends prematurely.
+    /// ```rust
+    /// # tokio_test::block_on(async {
+    /// # use linera_views::context::MemoryContext;
+    /// # use linera_views::map_view::CustomMapView;
+    /// # use linera_views::views::View;
+    /// # let context = MemoryContext::new_for_testing(());
+    /// let mut map: CustomMapView<_, u128, String> = CustomMapView::load(context).await.unwrap();
+    /// map.insert(&(1 as u128), String::from("Hello"));
+    /// map.insert(&(256 as u128), String::from("Bonjour"));
+    /// map.insert(&(65536 as u128), String::from("Hallo"));
+    /// let mut indices = Vec::new();
+    /// map.for_each_index_in_range_while(
+    ///     |index| {
+    ///         indices.push(index);
+    ///         Ok(true)
+    ///     },
+    ///     (2 as u128)..,
+    ///     true,
+    /// )
+    /// .await
+    /// .unwrap();
+    /// assert_eq!(indices, vec![65536, 256]);
+    /// # })
+    /// ```
+    pub async fn for_each_index_in_range_while<F, Q, R>(
+        &self,
+        mut f: F,
+        range: R,
+        reverse: bool,
+    ) -> Result<(), ViewError>
+    where
+        F: FnMut(I) -> Result<bool, ViewError> + Send,
+        I: Borrow<Q>,
+        Q: CustomSerialize,
+        R: RangeBounds<Q>,
+    {
+        let range = serialize_range_bounds(range, |index| Ok(index.to_custom_bytes()?))?;
+        self.map
+            .for_each_key_in_range_while(
+                |key| {
+                    let index = I::from_custom_bytes(key)?;
+                    f(index)
+                },
+                range,
+                reverse,
+            )
+            .await
+    }
+
+    /// Applies a function f on each index/value pair in the range. Indices are visited in
+    /// the order determined by the custom serialization, or in the reverse order if `reverse` is
+    /// set. If the function returns false, then the loop ends prematurely.
+    pub async fn for_each_index_value_in_range_while<'a, F, Q, R>(
+        &'a self,
+        mut f: F,
+        range: R,
+        reverse: bool,
+    ) -> Result<(), ViewError>
+    where
+        F: FnMut(I, Cow<'a, V>) -> Result<bool, ViewError> + Send,
+        I: Borrow<Q>,
+        Q: CustomSerialize,
+        R: RangeBounds<Q>,
+    {
+        let range = serialize_range_bounds(range, |index| Ok(index.to_custom_bytes()?))?;
+        self.map
+            .for_each_key_value_in_range_while(
+                |key, value| {
+                    let index = I::from_custom_bytes(key)?;
+                    f(index, value)
+                },
+                range,
+                reverse,
+            )
+            .await
+    }
+
+    /// Returns the indices in the range, in the order determined by the custom serialization.
+    pub async fn indices_in_range<Q, R>(&self, range: R) -> Result<Vec<I>, ViewError>
+    where
+        I: Borrow<Q>,
+        Q: CustomSerialize,
+        R: RangeBounds<Q>,
+    {
+        let range = serialize_range_bounds(range, |index| Ok(index.to_custom_bytes()?))?;
+        self.map
+            .keys_in_range(range)
+            .await?
+            .iter()
+            .map(|key| I::from_custom_bytes(key))
+            .collect()
+    }
+
+    /// Returns at most `limit` index/value pairs in the range, in the order determined by
+    /// the custom serialization, or in the reverse order if `reverse` is set. The next page starts
+    /// right after the last returned index, which can be excluded from the next range.
+    pub async fn index_values_page<Q, R>(
+        &self,
+        range: R,
+        limit: usize,
+        reverse: bool,
+    ) -> Result<Vec<(I, V)>, ViewError>
+    where
+        I: Borrow<Q>,
+        Q: CustomSerialize,
+        R: RangeBounds<Q>,
+    {
+        let range = serialize_range_bounds(range, |index| Ok(index.to_custom_bytes()?))?;
+        let key_values = self.map.key_values_page(range, limit, reverse).await?;
+        Self::to_index_values(key_values)
+    }
+
+    /// Returns the index/value pairs in the range, in the order determined by
+    /// the custom serialization.
+    pub async fn index_values_in_range<Q, R>(&self, range: R) -> Result<Vec<(I, V)>, ViewError>
+    where
+        I: Borrow<Q>,
+        Q: CustomSerialize,
+        R: RangeBounds<Q>,
+    {
+        let range = serialize_range_bounds(range, |index| Ok(index.to_custom_bytes()?))?;
+        let key_values = self.map.key_values_in_range(range).await?;
+        Self::to_index_values(key_values)
+    }
+
+    /// Returns the first index/value pair in the order determined by the custom serialization, if any.
+    /// ```rust
+    /// # tokio_test::block_on(async {
+    /// # use linera_views::context::MemoryContext;
+    /// # use linera_views::map_view::CustomMapView;
+    /// # use linera_views::views::View;
+    /// # let context = MemoryContext::new_for_testing(());
+    /// let mut map: CustomMapView<_, u128, String> = CustomMapView::load(context).await.unwrap();
+    /// map.insert(&(1 as u128), String::from("Hello"));
+    /// map.insert(&(256 as u128), String::from("Bonjour"));
+    /// map.insert(&(65536 as u128), String::from("Hallo"));
+    /// assert_eq!(
+    ///     map.first_index_value().await.unwrap(),
+    ///     Some((1, String::from("Hello")))
+    /// );
+    /// # })
+    /// ```
+    pub async fn first_index_value(&self) -> Result<Option<(I, V)>, ViewError> {
+        let key_values = self.map.key_values_page(.., 1, false).await?;
+        Ok(Self::to_index_values(key_values)?.pop())
+    }
+
+    /// Returns the last index/value pair in the order determined by the custom serialization, if any.
+    /// ```rust
+    /// # tokio_test::block_on(async {
+    /// # use linera_views::context::MemoryContext;
+    /// # use linera_views::map_view::CustomMapView;
+    /// # use linera_views::views::View;
+    /// # let context = MemoryContext::new_for_testing(());
+    /// let mut map: CustomMapView<_, u128, String> = CustomMapView::load(context).await.unwrap();
+    /// map.insert(&(1 as u128), String::from("Hello"));
+    /// map.insert(&(256 as u128), String::from("Bonjour"));
+    /// map.insert(&(65536 as u128), String::from("Hallo"));
+    /// assert_eq!(
+    ///     map.last_index_value().await.unwrap(),
+    ///     Some((65536, String::from("Hallo")))
+    /// );
+    /// # })
+    /// ```
+    pub async fn last_index_value(&self) -> Result<Option<(I, V)>, ViewError> {
+        let key_values = self.map.key_values_page(.., 1, true).await?;
+        Ok(Self::to_index_values(key_values)?.pop())
+    }
+
+    fn to_index_values(key_values: Vec<(Vec<u8>, V)>) -> Result<Vec<(I, V)>, ViewError> {
+        key_values
+            .into_iter()
+            .map(|(key, value)| Ok((I::from_custom_bytes(&key)?, value)))
+            .collect()
+    }
+}
+
 impl<C, I, V> CustomMapView<C, I, V>
 where
     C: Context,
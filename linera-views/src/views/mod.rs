@@ -1,7 +1,7 @@
 // Copyright (c) Zefchain Labs, Inc.
 // SPDX-License-Identifier: Apache-2.0
 
-use std::{fmt::Debug, io::Write};
+use std::{fmt::Debug, io::Write, ops::Bound};
 
 use linera_base::crypto::CryptoHash;
 pub use linera_views_derive::{
@@ -9,7 +9,13 @@ pub use linera_views_derive::{
 };
 use serde::Serialize;
 
-use crate::{batch::Batch, common::HasherOutput, ViewError};
+use crate::{
+    batch::Batch,
+    common::{get_upper_bound, HasherOutput},
+    context::Context,
+    store::ReadableKeyValueStore as _,
+    ViewError,
+};
 
 #[cfg(test)]
 #[path = "unit_tests/views.rs"]
@@ -33,6 +39,9 @@ pub mod map_view;
 /// The `SetView` implements a set with ordered entries.
 pub mod set_view;
 
+/// The `IndexedMapView` implements a map with ordered keys and secondary indexes of the values.
+pub mod indexed_map_view;
+
 /// The `CollectionView` implements a map structure whose keys are ordered and the values are views.
 pub mod collection_view;
 
@@ -48,6 +57,75 @@ pub mod hashable_wrapper;
 /// The minimum value for the view tags. Values in `0..MIN_VIEW_TAG` are used for other purposes.
 pub const MIN_VIEW_TAG: u8 = 1;
 
+/// The index reserved for the layout version among the fields of the views derived with
+/// `#[view(version = ...)]`. The indices of the fields are non-negative.
+pub const LAYOUT_VERSION_INDEX: i32 = -1;
+
+/// Returns the context of the field with the given index in a derived view with the given
+/// context. Migrations of versioned layouts use it to load the fields of previous layouts.
+pub fn field_context<C: Context>(context: &C, index: i32) -> Result<C, ViewError> {
+    let base_key = context.base_key().derive_tag_key(MIN_VIEW_TAG, &index)?;
+    Ok(context.clone_with_base_key(base_key))
+}
+
+/// Returns the key of the layout version of a derived view, given the context of one of
+/// its fields.
+#[doc(hidden)]
+pub fn layout_version_key<C: Context>(field_context: &C) -> Result<Vec<u8>, ViewError> {
+    let bytes = &field_context.base_key().bytes;
+    let index_size = bcs::serialized_size(&LAYOUT_VERSION_INDEX)?;
+    let mut key = bytes[..bytes.len() - index_size].to_vec();
+    bcs::serialize_into(&mut key, &LAYOUT_VERSION_INDEX)?;
+    Ok(key)
+}
+
+/// Returns the indices of the fields stored by a derived view with the given context, not
+/// counting its layout version. Migrations use it to delete the fields of previous layouts.
+#[doc(hidden)]
+pub async fn stored_field_indices<C: Context>(context: &C) -> Result<Vec<i32>, ViewError> {
+    let prefix = context.base_key().base_tag(MIN_VIEW_TAG);
+    let index_size = bcs::serialized_size(&LAYOUT_VERSION_INDEX)?;
+    let mut indices = Vec::new();
+    let mut lower_bound = Bound::Unbounded;
+    // Seek the first key of each field in turn, rather than reading all their keys.
+    loop {
+        let keys = context
+            .store()
+            .find_keys_by_prefix_in_range(&prefix, (lower_bound, Bound::Unbounded), false, Some(1))
+            .await?;
+        let Some(key) = keys.into_iter().next() else {
+            break;
+        };
+        let index_bytes = key
+            .get(..index_size)
+            .ok_or(ViewError::InconsistentEntries)?;
+        let index = bcs::from_bytes::<i32>(index_bytes)?;
+        if index != LAYOUT_VERSION_INDEX {
+            indices.push(index);
+        }
+        match get_upper_bound(index_bytes) {
+            Bound::Excluded(next_index_bytes) => lower_bound = Bound::Included(next_index_bytes),
+            _ => break,
+        }
+    }
+    Ok(indices)
+}
+
+/// Builds a view from the values read with its `pre_load` keys, like `post_load`, unless it
+/// was stored with a previous layout version. Only `load` migrates such a view, and it is
+/// loaded again that way. The views that hold other views use it in their `load`.
+#[doc(hidden)]
+pub async fn post_load_or_migrate<W: View>(
+    context: W::Context,
+    values: &[Option<Vec<u8>>],
+) -> Result<W, ViewError> {
+    match W::post_load(context.clone(), values) {
+        Err(ViewError::UnsupportedLayoutVersion { stored, expected }) if stored < expected => {}
+        result => return result,
+    }
+    W::load(context).await
+}
+
 /// A view gives exclusive access to read and write the data stored at an underlying
 /// address in storage.
 #[cfg_attr(not(web), trait_variant::make(Send))]
@@ -21,7 +21,9 @@ use crate::{
     context::{BaseKey, Context},
     hashable_wrapper::WrappedHashableContainerView,
     store::{KeyIterable, ReadableKeyValueStore as _},
-    views::{ClonableView, HashableView, Hasher, View, ViewError, MIN_VIEW_TAG},
+    views::{
+        post_load_or_migrate, ClonableView, HashableView, Hasher, View, ViewError, MIN_VIEW_TAG,
+    },
 };
 
 #[cfg(with_metrics)]
@@ -549,7 +551,7 @@ impl<W: View + Sync> ReentrantByteCollectionView<W::Context, W> {
                 .base_key()
                 .base_tag_index(KeyTag::Subview as u8, &short_key);
             let context = self.context.clone_with_base_key(key);
-            let view = W::post_load(context, loaded_values)?;
+            let view = post_load_or_migrate(context, loaded_values).await?;
             let wrapped_view = Arc::new(RwLock::new(view));
             self.updates
                 .insert(short_key.to_vec(), Update::Set(wrapped_view));
@@ -643,11 +645,15 @@ impl<W: View + Sync> ReentrantByteCollectionView<W::Context, W> {
                 .store()
                 .read_multi_values_bytes(keys_to_load)
                 .await?;
-            let mut cached_entries = self.cached_entries.lock().unwrap();
+            let mut views = Vec::with_capacity(entries_to_load.len());
             for (loaded_values, (position, short_key, context)) in
                 values.chunks_exact(W::NUM_INIT_KEYS).zip(entries_to_load)
             {
-                let view = W::post_load(context, loaded_values)?;
+                let view = post_load_or_migrate(context, loaded_values).await?;
+                views.push((position, short_key, view));
+            }
+            let mut cached_entries = self.cached_entries.lock().unwrap();
+            for (position, short_key, view) in views {
                 let wrapped_view = Arc::new(RwLock::new(view));
                 cached_entries.insert(short_key.clone(), wrapped_view.clone());
                 results[position] = Some((short_key, wrapped_view));
@@ -707,21 +713,23 @@ impl<W: View + Sync> ReentrantByteCollectionView<W::Context, W> {
                 }
             }
             let values = self.context.store().read_multi_values_bytes(keys).await?;
+            let mut views = Vec::with_capacity(short_keys_to_load.len());
+            for (loaded_values, short_key) in values
+                .chunks_exact(W::NUM_INIT_KEYS)
+                .zip(short_keys_to_load)
             {
-                let mut cached_entries = self.cached_entries.lock().unwrap();
-                for (loaded_values, short_key) in values
-                    .chunks_exact(W::NUM_INIT_KEYS)
-                    .zip(short_keys_to_load)
-                {
-                    let key = self
-                        .context
-                        .base_key()
-                        .base_tag_index(KeyTag::Subview as u8, &short_key);
-                    let context = self.context.clone_with_base_key(key);
-                    let view = W::post_load(context, loaded_values)?;
-                    let wrapped_view = Arc::new(RwLock::new(view));
-                    cached_entries.insert(short_key.to_vec(), wrapped_view);
-                }
+                let key = self
+                    .context
+                    .base_key()
+                    .base_tag_index(KeyTag::Subview as u8, &short_key);
+                let context = self.context.clone_with_base_key(key);
+                let view = post_load_or_migrate(context, loaded_values).await?;
+                views.push((short_key, view));
+            }
+            let mut cached_entries = self.cached_entries.lock().unwrap();
+            for (short_key, view) in views {
+                let wrapped_view = Arc::new(RwLock::new(view));
+                cached_entries.insert(short_key, wrapped_view);
             }
         }
         let cached_entries = self.cached_entries.lock().unwrap();
@@ -796,7 +804,7 @@ impl<W: View + Sync> ReentrantByteCollectionView<W::Context, W> {
                     .base_key()
                     .base_tag_index(KeyTag::Subview as u8, &short_key);
                 let context = self.context.clone_with_base_key(key);
-                let view = W::post_load(context, loaded_values)?;
+                let view = post_load_or_migrate(context, loaded_values).await?;
                 let wrapped_view = Arc::new(RwLock::new(view));
                 self.updates
                     .insert(short_key.to_vec(), Update::Set(wrapped_view));
@@ -1,7 +1,13 @@
 // Copyright (c) Zefchain Labs, Inc.
 // SPDX-License-Identifier: Apache-2.0
 
-use std::{borrow::Borrow, collections::BTreeMap, marker::PhantomData, mem};
+use std::{
+    borrow::Borrow,
+    collections::{BTreeMap, BTreeSet},
+    marker::PhantomData,
+    mem,
+    ops::{Bound, RangeBounds},
+};
 
 #[cfg(with_metrics)]
 use linera_base::prometheus_util::MeasureLatency as _;
@@ -9,7 +15,10 @@ use serde::{de::DeserializeOwned, Serialize};
 
 use crate::{
     batch::Batch,
-    common::{CustomSerialize, HasherOutput, Update},
+    common::{
+        get_range_bounds, serialize_range_bounds, CustomSerialize, HasherOutput, RangePager,
+        Update, INITIAL_RANGE_PAGE_SIZE,
+    },
     context::{BaseKey, Context},
     hashable_wrapper::WrappedHashableContainerView,
     store::{KeyIterable, ReadableKeyValueStore as _},
@@ -341,6 +350,204 @@ impl<C: Context> ByteSetView<C> {
     }
 }
 
+impl<C: Context> ByteSetView<C> {
+    /// Applies a function f on each key in the range. Keys are visited in lexicographic
+    /// order, or in reverse lexicographic order if `reverse` is set. If the function
+    /// returns false, then the loop ends prematurely.
+    /// ```rust
+    /// # tokio_test::block_on(async {
+    /// # use linera_views::{context::MemoryContext, set_view::ByteSetView};
+    /// # use linera_views::views::View;
+    /// # let context = MemoryContext::new_for_testing(());
+    /// let mut set = ByteSetView::load(context).await.unwrap();
+    /// set.insert(vec![0, 1]);
+    /// set.insert(vec![0, 2]);
+    /// set.insert(vec![3]);
+    /// let mut keys = Vec::new();
+    /// set.for_each_key_in_range_while(
+    ///     |key| {
+    ///         keys.push(key.to_vec());
+    ///         Ok(true)
+    ///     },
+    ///     vec![0, 2]..,
+    ///     true,
+    /// )
+    /// .await
+    /// .unwrap();
+    /// assert_eq!(keys, vec![vec![3], vec![0, 2]]);
+    /// # })
+    /// ```
+    pub async fn for_each_key_in_range_while<F, R>(
+        &self,
+        f: F,
+        range: R,
+        reverse: bool,
+    ) -> Result<(), ViewError>
+    where
+        F: FnMut(&[u8]) -> Result<bool, ViewError> + Send,
+        R: RangeBounds<Vec<u8>>,
+    {
+        self.for_each_key_in_pages_while(
+            f,
+            get_range_bounds(range),
+            reverse,
+            Some(INITIAL_RANGE_PAGE_SIZE),
+        )
+        .await
+    }
+
+    /// Applies a function f on each key in the range, reading the stored keys by pages of
+    /// `page_size` keys or more, or all at once if `None`. If f returns false, the loop
+    /// ends prematurely.
+    async fn for_each_key_in_pages_while<F>(
+        &self,
+        mut f: F,
+        range: (Bound<Vec<u8>>, Bound<Vec<u8>>),
+        reverse: bool,
+        page_size: Option<usize>,
+    ) -> Result<(), ViewError>
+    where
+        F: FnMut(&[u8]) -> Result<bool, ViewError> + Send,
+    {
+        let mut pager = RangePager::new(range, reverse, page_size);
+        while let Some((range, limit)) = pager.next_page() {
+            let stored = if self.delete_storage_first {
+                Vec::new()
+            } else {
+                self.context
+                    .store()
+                    .find_keys_by_prefix_in_range(
+                        &self.context.base_key().bytes,
+                        range,
+                        reverse,
+                        limit,
+                    )
+                    .await?
+                    .into_iter()
+                    .map(|key| (key, ()))
+                    .collect()
+            };
+            for (key, _) in pager.merge_page(stored, &BTreeSet::new(), &self.updates) {
+                if !f(&key)? {
+                    return Ok(());
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns at most `limit` keys in the range, or all of them if `None`, in the
+    /// requested order.
+    async fn keys_in_pages(
+        &self,
+        range: (Bound<Vec<u8>>, Bound<Vec<u8>>),
+        limit: Option<usize>,
+        reverse: bool,
+    ) -> Result<Vec<Vec<u8>>, ViewError> {
+        let mut keys = Vec::new();
+        self.for_each_key_in_pages_while(
+            |key| {
+                keys.push(key.to_vec());
+                Ok(limit.is_none_or(|limit| keys.len() < limit))
+            },
+            range,
+            reverse,
+            limit,
+        )
+        .await?;
+        Ok(keys)
+    }
+
+    /// Returns at most `limit` keys in the range, in lexicographic order, or in reverse
+    /// lexicographic order if `reverse` is set. The next page starts right after the last
+    /// returned key, which can be excluded from the next range.
+    /// ```rust
+    /// # tokio_test::block_on(async {
+    /// # use std::ops::Bound;
+    /// # use linera_views::{context::MemoryContext, set_view::ByteSetView};
+    /// # use linera_views::views::View;
+    /// # let context = MemoryContext::new_for_testing(());
+    /// let mut set = ByteSetView::load(context).await.unwrap();
+    /// set.insert(vec![0, 1]);
+    /// set.insert(vec![0, 2]);
+    /// set.insert(vec![3]);
+    /// let page = set.keys_page(.., 2, true).await.unwrap();
+    /// assert_eq!(page, vec![vec![3], vec![0, 2]]);
+    /// let range = (Bound::Unbounded, Bound::Excluded(vec![0, 2]));
+    /// let page = set.keys_page(range, 2, true).await.unwrap();
+    /// assert_eq!(page, vec![vec![0, 1]]);
+    /// # })
+    /// ```
+    pub async fn keys_page<R>(
+        &self,
+        range: R,
+        limit: usize,
+        reverse: bool,
+    ) -> Result<Vec<Vec<u8>>, ViewError>
+    where
+        R: RangeBounds<Vec<u8>>,
+    {
+        self.keys_in_pages(get_range_bounds(range), Some(limit), reverse)
+            .await
+    }
+
+    /// Returns the keys in the range, in lexicographic order.
+    /// ```rust
+    /// # tokio_test::block_on(async {
+    /// # use linera_views::{context::MemoryContext, set_view::ByteSetView};
+    /// # use linera_views::views::View;
+    /// # let context = MemoryContext::new_for_testing(());
+    /// let mut set = ByteSetView::load(context).await.unwrap();
+    /// set.insert(vec![0, 1]);
+    /// set.insert(vec![0, 2]);
+    /// set.insert(vec![3]);
+    /// assert_eq!(
+    ///     set.keys_in_range(vec![0, 2]..=vec![3]).await.unwrap(),
+    ///     vec![vec![0, 2], vec![3]]
+    /// );
+    /// # })
+    /// ```
+    pub async fn keys_in_range<R>(&self, range: R) -> Result<Vec<Vec<u8>>, ViewError>
+    where
+        R: RangeBounds<Vec<u8>>,
+    {
+        self.keys_in_pages(get_range_bounds(range), None, false)
+            .await
+    }
+
+    /// Returns the smallest key of the set, if any.
+    /// ```rust
+    /// # tokio_test::block_on(async {
+    /// # use linera_views::{context::MemoryContext, set_view::ByteSetView};
+    /// # use linera_views::views::View;
+    /// # let context = MemoryContext::new_for_testing(());
+    /// let mut set = ByteSetView::load(context).await.unwrap();
+    /// set.insert(vec![0, 1]);
+    /// set.insert(vec![3]);
+    /// assert_eq!(set.first_key().await.unwrap(), Some(vec![0, 1]));
+    /// # })
+    /// ```
+    pub async fn first_key(&self) -> Result<Option<Vec<u8>>, ViewError> {
+        Ok(self.keys_page(.., 1, false).await?.pop())
+    }
+
+    /// Returns the largest key of the set, if any.
+    /// ```rust
+    /// # tokio_test::block_on(async {
+    /// # use linera_views::{context::MemoryContext, set_view::ByteSetView};
+    /// # use linera_views::views::View;
+    /// # let context = MemoryContext::new_for_testing(());
+    /// let mut set = ByteSetView::load(context).await.unwrap();
+    /// set.insert(vec![0, 1]);
+    /// set.insert(vec![3]);
+    /// assert_eq!(set.last_key().await.unwrap(), Some(vec![3]));
+    /// # })
+    /// ```
+    pub async fn last_key(&self) -> Result<Option<Vec<u8>>, ViewError> {
+        Ok(self.keys_page(.., 1, true).await?.pop())
+    }
+}
+
 impl<C: Context> HashableView for ByteSetView<C> {
     type Hasher = sha3::Sha3_256;
 
@@ -888,6 +1095,123 @@ where
     }
 }
 
+impl<C, I> CustomSetView<C, I>
+where
+    C: Context,
+    I: Sync + Clone + Send + CustomSerialize,
+{
+    /// Applies a function f on each index in the range. Indices are visited in the order
+    /// determined by the custom serialization, or in the reverse order if `reverse` is set. If the
+    /// function returns false, then the loop ends prematurely.
+    /// ```rust
+    /// # tokio_test::block_on(async {
+    /// # use linera_views::context::MemoryContext;
+    /// # use linera_views::set_view::CustomSetView;
+    /// # use linera_views::views::View;
+    /// # let context = MemoryContext::new_for_testing(());
+    /// let mut set = CustomSetView::<_, u128>::load(context).await.unwrap();
+    /// set.insert(&(1 as u128));
+    /// set.insert(&(2 as u128));
+    /// set.insert(&(3 as u128));
+    /// let mut indices = Vec::new();
+    /// set.for_each_index_in_range_while(
+    ///     |index| {
+    ///         indices.push(index);
+    ///         Ok(true)
+    ///     },
+    ///     (2 as u128)..,
+    ///     true,
+    /// )
+    /// .await
+    /// .unwrap();
+    /// assert_eq!(indices, vec![3, 2]);
+    /// # })
+    /// ```
+    pub async fn for_each_index_in_range_while<F, Q, R>(
+        &self,
+        mut f: F,
+        range: R,
+        reverse: bool,
+    ) -> Result<(), ViewError>
+    where
+        F: FnMut(I) -> Result<bool, ViewError> + Send,
+        I: Borrow<Q>,
+        Q: CustomSerialize,
+        R: RangeBounds<Q>,
+    {
+        let range = serialize_range_bounds(range, |index| Ok(index.to_custom_bytes()?))?;
+        self.set
+            .for_each_key_in_range_while(
+                |key| {
+                    let index = I::from_custom_bytes(key)?;
+                    f(index)
+                },
+                range,
+                reverse,
+            )
+            .await
+    }
+
+    /// Returns at most `limit` indices in the range, in the order determined by
+    /// the custom serialization, or in the reverse order if `reverse` is set. The next page starts
+    /// right after the last returned index, which can be excluded from the next range.
+    pub async fn indices_page<Q, R>(
+        &self,
+        range: R,
+        limit: usize,
+        reverse: bool,
+    ) -> Result<Vec<I>, ViewError>
+    where
+        I: Borrow<Q>,
+        Q: CustomSerialize,
+        R: RangeBounds<Q>,
+    {
+        let range = serialize_range_bounds(range, |index| Ok(index.to_custom_bytes()?))?;
+        let keys = self.set.keys_page(range, limit, reverse).await?;
+        keys.iter().map(|key| I::from_custom_bytes(key)).collect()
+    }
+
+    /// Returns the indices in the range, in the order determined by the custom serialization.
+    /// ```rust
+    /// # tokio_test::block_on(async {
+    /// # use linera_views::context::MemoryContext;
+    /// # use linera_views::set_view::CustomSetView;
+    /// # use linera_views::views::View;
+    /// # let context = MemoryContext::new_for_testing(());
+    /// let mut set = CustomSetView::<_, u128>::load(context).await.unwrap();
+    /// set.insert(&(1 as u128));
+    /// set.insert(&(2 as u128));
+    /// set.insert(&(3 as u128));
+    /// assert_eq!(
+    ///     set.indices_in_range((1 as u128)..(3 as u128)).await.unwrap(),
+    ///     vec![1, 2]
+    /// );
+    /// # })
+    /// ```
+    pub async fn indices_in_range<Q, R>(&self, range: R) -> Result<Vec<I>, ViewError>
+    where
+        I: Borrow<Q>,
+        Q: CustomSerialize,
+        R: RangeBounds<Q>,
+    {
+        let range = serialize_range_bounds(range, |index| Ok(index.to_custom_bytes()?))?;
+        let keys = self.set.keys_in_range(range).await?;
+        keys.iter().map(|key| I::from_custom_bytes(key)).collect()
+    }
+
+    /// Returns the first index in the order determined by the custom serialization, if any.
+    pub async fn first_index(&self) -> Result<Option<I>, ViewError> {
+        let key = self.set.first_key().await?;
+        key.map(|key| I::from_custom_bytes(&key)).transpose()
+    }
+
+    /// Returns the last index in the order determined by the custom serialization, if any.
+    pub async fn last_index(&self) -> Result<Option<I>, ViewError> {
+        let key = self.set.last_key().await?;
+        key.map(|key| I::from_custom_bytes(&key)).transpose()
+    }
+}
+
 impl<C: Context, I> HashableView for CustomSetView<C, I>
 where
     Self: View + Sync,
@@ -1,14 +1,21 @@
 // Copyright (c) Zefchain Labs, Inc.
 // SPDX-License-Identifier: Apache-2.0
 
-use std::collections::{BTreeMap, BTreeSet};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    ops::{Bound, RangeBounds},
+};
 
 use anyhow::Result;
 use linera_views::{
+    batch::Batch,
     bucket_queue_view::HashedBucketQueueView,
     collection_view::HashedCollectionView,
     context::{Context, MemoryContext},
-    key_value_store_view::{KeyValueStoreView, SizeData},
+    indexed_map_view::{
+        HashedIndexedMapView, IndexedValue, Ordered, OrderedSecondaryIndex, SecondaryIndex,
+    },
+    key_value_store_view::{HistoryWindow, KeyValueStoreView, SizeData, VersioningConfig},
     map_view::HashedByteMapView,
     queue_view::HashedQueueView,
     random::make_deterministic_rng,
@@ -17,6 +24,7 @@ use linera_views::{
     views::{CryptoHashRootView, CryptoHashView, RootView, View},
 };
 use rand::{distributions::Uniform, Rng, RngCore};
+use serde::{Deserialize, Serialize};
 
 #[derive(CryptoHashRootView)]
 struct CollectionStateView<C> {
@@ -248,6 +256,133 @@ async fn key_value_store_view_mutability() -> Result<()> {
     Ok(())
 }
 
+#[tokio::test]
+async fn key_value_store_view_versioned_reads() -> Result<()> {
+    let context = MemoryContext::new_for_testing(());
+    let mut rng = make_deterministic_rng();
+    let retention = 5;
+    let mut state_map = BTreeMap::new();
+    let mut snapshots = vec![state_map.clone()];
+    let mut all_keys = BTreeSet::new();
+    for version in 1..40 {
+        let mut view = KeyValueStateView::load(context.clone()).await?;
+        let mut batch = Batch::new();
+        for _ in 0..rng.gen_range(0..10) {
+            let len = rng.gen_range(1..4);
+            let key = (&mut rng)
+                .sample_iter(Uniform::from(0..4))
+                .take(len)
+                .collect::<Vec<_>>();
+            all_keys.insert(key.clone());
+            match rng.gen_range(0..4) {
+                0 => {
+                    batch.delete_key(key.clone());
+                    state_map.remove(&key);
+                }
+                1 => {
+                    let key_prefix = key[..1].to_vec();
+                    batch.delete_key_prefix(key_prefix.clone());
+                    remove_by_prefix(&mut state_map, key_prefix);
+                }
+                _ => {
+                    let value = vec![rng.gen::<u8>()];
+                    batch.put_key_value_bytes(key.clone(), value.clone());
+                    state_map.insert(key, value);
+                }
+            }
+        }
+        let hash = view.crypto_hash().await?;
+        let config = VersioningConfig { version, retention };
+        view.store.write_batch_versioned(batch, config).await?;
+        view.save().await?;
+        snapshots.push(state_map.clone());
+
+        // The prior values are not part of the hash.
+        let mut plain_view = KeyValueStateView::load(context.clone()).await?;
+        plain_view.store.clear();
+        for (key, value) in &state_map {
+            plain_view.store.insert(key.clone(), value.clone()).await?;
+        }
+        assert_eq!(view.crypto_hash().await?, plain_view.crypto_hash().await?);
+        if state_map == snapshots[version as usize - 1] {
+            assert_eq!(view.crypto_hash().await?, hash);
+        }
+
+        let view = KeyValueStateView::load(context.clone()).await?;
+        let all_keys_vec = all_keys.iter().cloned().collect::<Vec<_>>();
+        let window = HistoryWindow {
+            start: 1,
+            next_version: version + 1,
+            retention,
+        };
+        // The state after the writes at a version older than the retention, or at a future
+        // version, is rejected.
+        assert!(!window.contains(version + 1));
+        if version >= retention {
+            let past_version = version - retention;
+            assert!(!window.contains(past_version));
+            assert!(view
+                .store
+                .get_at_version(&[0], past_version, window)
+                .await
+                .is_err());
+            assert!(view
+                .store
+                .find_key_values_by_prefix_at_version(&[], past_version, window)
+                .await
+                .is_err());
+        }
+        // Before the first versioned writes, only the last version is readable.
+        let late_window = HistoryWindow {
+            start: version,
+            ..window
+        };
+        if version > 1 {
+            assert!(view
+                .store
+                .multi_get_at_version(all_keys_vec.clone(), version - 2, late_window)
+                .await
+                .is_err());
+        }
+        for past_version in (version + 1).saturating_sub(retention)..=version {
+            let snapshot = &snapshots[past_version as usize];
+            let values = view
+                .store
+                .multi_get_at_version(all_keys_vec.clone(), past_version, window)
+                .await?;
+            let contains = view
+                .store
+                .contains_keys_at_version(all_keys_vec.clone(), past_version, window)
+                .await?;
+            for (i, key) in all_keys_vec.iter().enumerate() {
+                let value = view.store.get_at_version(key, past_version, window).await?;
+                assert_eq!(value.as_ref(), snapshot.get(key));
+                assert_eq!(values[i].as_ref(), snapshot.get(key));
+                assert_eq!(contains[i], snapshot.contains_key(key));
+            }
+            for key_prefix in [vec![], vec![0], vec![1, 2]] {
+                let key_values = view
+                    .store
+                    .find_key_values_by_prefix_at_version(&key_prefix, past_version, window)
+                    .await?;
+                let expected = snapshot
+                    .iter()
+                    .filter(|(key, _)| key.starts_with(&key_prefix))
+                    .map(|(key, value)| (key[key_prefix.len()..].to_vec(), value.clone()))
+                    .collect::<Vec<_>>();
+                assert_eq!(key_values, expected);
+                let keys = view
+                    .store
+                    .find_keys_by_prefix_at_version(&key_prefix, past_version, window)
+                    .await?;
+                let expected_keys = expected.into_iter().map(|(key, _)| key).collect::<Vec<_>>();
+                assert_eq!(keys, expected_keys);
+            }
+        }
+    }
+    Ok(())
+}
+
 #[derive(CryptoHashRootView)]
 pub struct ByteMapStateView<C> {
     pub map: HashedByteMapView<C, u8>,
@@ -369,6 +504,70 @@ async fn run_map_view_mutability<R: RngCore + Clone>(rng: &mut R) -> Result<()>
                 let part_key_values = view.map.key_values_by_prefix(vec![u]).await?;
                 assert_eq!(part_state_vec, part_key_values);
             }
+            for _ in 0..3 {
+                let mut bounds = (0..2)
+                    .map(|_| {
+                        let len = rng.gen_range(1..6);
+                        (0..len).map(|_| rng.gen_range(0..4)).collect::<Vec<u8>>()
+                    })
+                    .collect::<Vec<_>>();
+                bounds.sort();
+                let range = (
+                    Bound::Included(bounds[0].clone()),
+                    Bound::Excluded(bounds[1].clone()),
+                );
+                let part_state_vec = new_state_map
+                    .range(range.clone())
+                    .map(|(key, value)| (key.clone(), *value))
+                    .collect::<Vec<_>>();
+                let part_key_values = view.map.key_values_in_range(range.clone()).await?;
+                assert_eq!(part_state_vec, part_key_values);
+                let part_keys = view.map.keys_in_range(range.clone()).await?;
+                assert_eq!(
+                    part_state_vec
+                        .iter()
+                        .map(|(key, _)| key.clone())
+                        .collect::<Vec<_>>(),
+                    part_keys
+                );
+                let reversed_page = part_state_vec.iter().rev().take(3).cloned();
+                let page = view.map.key_values_page(range.clone(), 3, true).await?;
+                assert_eq!(reversed_page.collect::<Vec<_>>(), page);
+                let mut pages = Vec::new();
+                let mut page_range = range;
+                loop {
+                    let page = view
+                        .map
+                        .key_values_page(page_range.clone(), 2, false)
+                        .await?;
+                    let Some((last_key, _)) = page.last() else {
+                        break;
+                    };
+                    page_range.0 = Bound::Excluded(last_key.clone());
+                    pages.extend(page);
+                }
+                assert_eq!(part_state_vec, pages);
+            }
+            let mut reversed_keys = Vec::new();
+            view.map
+                .for_each_key_in_range_while(
+                    |key| {
+                        reversed_keys.push(key.to_vec());
+                        Ok(true)
+                    },
+                    ..,
+                    true,
+                )
+                .await?;
+            assert!(new_state_map.keys().rev().eq(reversed_keys.iter()));
+            let first_key_value = new_state_map
+                .first_key_value()
+                .map(|(key, value)| (key.clone(), *value));
+            assert_eq!(first_key_value, view.map.first_key_value().await?);
+            let last_key_value = new_state_map
+                .last_key_value()
+                .map(|(key, value)| (key.clone(), *value));
+            assert_eq!(last_key_value, view.map.last_key_value().await?);
             let keys_vec = all_keys.iter().cloned().collect::<Vec<_>>();
             let values = view.map.multi_get(keys_vec.clone()).await?;
             for i in 0..keys_vec.len() {
@@ -402,6 +601,131 @@ async fn map_view_mutability() -> Result<()> {
     Ok(())
 }
 
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+struct Record {
+    group: u8,
+    tags: BTreeSet<u16>,
+}
+
+struct ByGroup;
+
+impl SecondaryIndex<Record> for ByGroup {
+    const ID: u8 = 0;
+    type Key = u8;
+
+    fn keys(record: &Record) -> Vec<u8> {
+        vec![record.group]
+    }
+}
+
+struct ByTag;
+
+impl OrderedSecondaryIndex<Record> for ByTag {
+    const ID: u8 = 1;
+    type Key = u16;
+
+    fn keys(record: &Record) -> Vec<u16> {
+        record.tags.iter().cloned().collect()
+    }
+}
+
+impl IndexedValue for Record {
+    type Indexes = (ByGroup, Ordered<ByTag>);
+}
+
+#[derive(CryptoHashRootView)]
+pub struct IndexedMapStateView<C> {
+    pub map: HashedIndexedMapView<C, u8, Record>,
+}
+
+fn index_entries<K: Ord, X: SecondaryIndex<Record, Key = K>>(
+    state: &BTreeMap<u8, Record>,
+) -> BTreeSet<(K, u8)> {
+    let mut entries = BTreeSet::new();
+    for (index, record) in state {
+        for key in X::keys(record) {
+            entries.insert((key, *index));
+        }
+    }
+    entries
+}
+
+#[tokio::test]
+async fn indexed_map_view_mutability() -> Result<()> {
+    let context = MemoryContext::new_for_testing(());
+    let mut rng = make_deterministic_rng();
+    let mut state = BTreeMap::new();
+    for _ in 0..50 {
+        let mut view = IndexedMapStateView::load(context.clone()).await?;
+        let mut new_state = state.clone();
+        let save = rng.gen::<bool>();
+        let count_oper = rng.gen_range(0..20);
+        for _ in 0..count_oper {
+            let choice = rng.gen_range(0..5);
+            let index = rng.gen_range(0..20);
+            if choice <= 1 {
+                let n_tags = rng.gen_range(0..4);
+                let record = Record {
+                    group: rng.gen_range(0..5),
+                    // Multiples of 128 have zero bytes and are not ordered as in BCS.
+                    tags: (0..n_tags).map(|_| rng.gen_range(0..8) * 128).collect(),
+                };
+                view.map.insert(&index, record.clone()).await?;
+                new_state.insert(index, record);
+            }
+            if choice == 2 {
+                view.map.remove(&index).await?;
+                new_state.remove(&index);
+            }
+            if choice == 3 && rng.gen_range(0..4) == 0 {
+                view.clear();
+                new_state.clear();
+            }
+            if choice == 4 && rng.gen_range(0..4) == 0 {
+                view.rollback();
+                assert!(!view.has_pending_changes().await);
+                new_state.clone_from(&state);
+            }
+            let indices = new_state.keys().cloned().collect::<Vec<_>>();
+            assert_eq!(view.map.indices().await?, indices);
+            let group_entries = index_entries::<_, ByGroup>(&new_state);
+            let tag_entries = index_entries::<_, Ordered<ByTag>>(&new_state);
+            for group in 0..5 {
+                let indices = group_entries
+                    .iter()
+                    .filter(|(key, _)| *key == group)
+                    .map(|(_, index)| *index)
+                    .collect::<Vec<_>>();
+                assert_eq!(view.map.indices_by::<ByGroup>(&group).await?, indices);
+            }
+            let lower = rng.gen_range(0..8) * 128;
+            let upper = rng.gen_range(lower..1024);
+            let range = (Bound::Excluded(lower), Bound::Included(upper));
+            let entries = tag_entries
+                .iter()
+                .filter(|(key, _)| range.contains(key))
+                .cloned()
+                .collect::<Vec<_>>();
+            let view_entries = view.map.index_entries_in_range::<ByTag, _>(range).await?;
+            assert_eq!(view_entries, entries);
+            let reversed_page = entries.iter().rev().take(3).cloned().collect::<Vec<_>>();
+            let page = view
+                .map
+                .index_entries_page::<ByTag, _>(range, 3, true)
+                .await?;
+            assert_eq!(page, reversed_page);
+            let entries = view.map.index_entries_in_range::<ByTag, _>(..).await?;
+            assert_eq!(entries, tag_entries.iter().cloned().collect::<Vec<_>>());
+        }
+        if save {
+            state.clone_from(&new_state);
+            view.save().await?;
+            assert!(!view.has_pending_changes().await);
+        }
+    }
+    Ok(())
+}
+
 #[derive(CryptoHashRootView)]
 pub struct BucketQueueStateView<C> {
     pub queue: HashedBucketQueueView<C, u8, 5>,
@@ -2,6 +2,7 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use linera_views::{
+    backends::tiered::{create_tiered_memory_store, WritePolicy},
     batch::Batch,
     context::{Context as _, MemoryContext},
     key_value_store_view::ViewContainer,
@@ -64,6 +65,25 @@ async fn test_reads_memory() {
     }
 }
 
+#[cfg(with_encryption)]
+#[tokio::test]
+async fn test_reads_encrypted_memory() {
+    for scenario in get_random_test_scenarios() {
+        let store = linera_views::encrypted::create_encrypted_memory_store();
+        run_reads(store, scenario).await;
+    }
+}
+
+#[tokio::test]
+async fn test_reads_tiered_memory() {
+    for write_policy in [WritePolicy::WriteThrough, WritePolicy::WriteBack] {
+        for scenario in get_random_test_scenarios() {
+            let store = create_tiered_memory_store(write_policy);
+            run_reads(store, scenario).await;
+        }
+    }
+}
+
 #[cfg(with_rocksdb)]
 #[tokio::test]
 async fn test_reads_rocks_db() {
@@ -156,6 +176,24 @@ async fn test_memory_writes_from_blank() {
     run_writes_from_blank(&store).await;
 }
 
+#[tokio::test]
+async fn test_tiered_memory_writes_from_blank() {
+    for write_policy in [WritePolicy::WriteThrough, WritePolicy::WriteBack] {
+        let store = create_tiered_memory_store(write_policy);
+        run_writes_from_blank(&store).await;
+    }
+}
+
+#[cfg(with_encryption)]
+#[tokio::test]
+async fn test_encrypted_memory_writes_from_blank() {
+    use linera_views::encrypted::EncryptedStore;
+    let store = EncryptedStore::<MemoryStore>::new_test_store()
+        .await
+        .unwrap();
+    run_writes_from_blank(&store).await;
+}
+
 #[tokio::test]
 async fn test_key_value_store_view_memory_writes_from_blank() {
     let context = MemoryContext::new_for_testing(());
@@ -305,6 +343,16 @@ async fn test_memory_writes_from_state() {
     run_writes_from_state(&store).await;
 }
 
+#[cfg(with_encryption)]
+#[tokio::test]
+async fn test_encrypted_memory_writes_from_state() {
+    use linera_views::encrypted::EncryptedStore;
+    let store = EncryptedStore::<MemoryStore>::new_test_store()
+        .await
+        .unwrap();
+    run_writes_from_state(&store).await;
+}
+
 #[cfg(with_rocksdb)]
 #[tokio::test]
 async fn test_rocks_db_writes_from_state() {
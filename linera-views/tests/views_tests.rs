@@ -26,15 +26,15 @@ use linera_views::{
     memory::MemoryStore,
     queue_view::HashedQueueView,
     random::make_deterministic_rng,
-    reentrant_collection_view::HashedReentrantCollectionView,
-    register_view::HashedRegisterView,
+    reentrant_collection_view::{HashedReentrantCollectionView, ReentrantCollectionView},
+    register_view::{HashedRegisterView, RegisterView},
     set_view::HashedSetView,
-    store::{TestKeyValueStore as _, WritableKeyValueStore as _},
+    store::{ReadableKeyValueStore as _, TestKeyValueStore as _, WritableKeyValueStore as _},
     test_utils::{
         get_random_byte_vector, get_random_key_value_operations, get_random_key_values,
         span_random_reordering_put_delete,
     },
-    views::{CryptoHashRootView, HashableView, Hasher, RootView, View},
+    views::{field_context, CryptoHashRootView, HashableView, Hasher, RootView, View},
     ViewError,
 };
 use rand::{Rng, RngCore};
@@ -644,6 +644,165 @@ async fn test_byte_map_view() -> Result<()> {
     Ok(())
 }
 
+#[derive(RootView)]
+pub struct LayoutV0View<C> {
+    pub count: RegisterView<C, u32>,
+    pub name: RegisterView<C, String>,
+}
+
+#[derive(RootView)]
+#[view(version = 1, migrate = migrate_layout)]
+pub struct LayoutV1View<C> {
+    #[view(index = 1)]
+    pub name: RegisterView<C, String>,
+    #[view(index = 2)]
+    pub count: RegisterView<C, u64>,
+}
+
+#[derive(RootView)]
+#[view(version = 0)]
+pub struct LayoutV0VersionedView<C> {
+    pub count: RegisterView<C, u32>,
+    pub name: RegisterView<C, String>,
+}
+
+async fn migrate_layout<C: Context>(
+    view: &mut LayoutV1View<C>,
+    context: &C,
+    from_version: u32,
+) -> Result<(), ViewError> {
+    if from_version < 1 {
+        let count = RegisterView::<C, u32>::load(field_context(context, 0)?).await?;
+        view.count.set(u64::from(*count.get()));
+    }
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_view_layout_migration() -> Result<()> {
+    let context = MemoryContext::new_for_testing(());
+    let old_count_prefix = field_context(&context, 0)?.base_key().bytes.clone();
+    {
+        let mut view = LayoutV0View::load(context.clone()).await?;
+        view.count.set(5);
+        view.name.set("name".to_string());
+        view.save().await?;
+    }
+    // Without migrating, with `post_load` alone, the unversioned data has version 0.
+    let keys = LayoutV1View::<MemoryContext<()>>::pre_load(&context)?;
+    let values = context.store().read_multi_values_bytes(keys).await?;
+    assert!(matches!(
+        LayoutV1View::post_load(context.clone(), &values),
+        Err(ViewError::UnsupportedLayoutVersion {
+            stored: 0,
+            expected: 1
+        })
+    ));
+    {
+        let mut view = LayoutV1View::load(context.clone()).await?;
+        assert_eq!(*view.count.get(), 5);
+        assert_eq!(view.name.get(), "name");
+        // The migration is saved when loading, and the data of the previous layout is gone.
+        assert!(!view.has_pending_changes().await);
+        let old_count = RegisterView::<_, u32>::load(field_context(&context, 0)?).await?;
+        assert_eq!(*old_count.get(), 0);
+        assert!(context
+            .store()
+            .find_keys_by_prefix(&old_count_prefix)
+            .await?
+            .is_empty());
+        view.count.set(u64::from(u32::MAX) + 1);
+        view.save().await?;
+    }
+    {
+        let view = LayoutV1View::load(context.clone()).await?;
+        assert_eq!(*view.count.get(), u64::from(u32::MAX) + 1);
+        assert_eq!(view.name.get(), "name");
+        assert!(!view.has_pending_changes().await);
+    }
+    // A view expecting the previous layout fails.
+    let result = LayoutV0VersionedView::load(context.clone()).await;
+    assert!(matches!(
+        result,
+        Err(ViewError::UnsupportedLayoutVersion {
+            stored: 1,
+            expected: 0
+        })
+    ));
+    {
+        let mut view = LayoutV1View::load(context.clone()).await?;
+        view.clear();
+        view.save().await?;
+    }
+    let view = LayoutV0VersionedView::load(context.clone()).await?;
+    assert_eq!(*view.count.get(), 0);
+    assert_eq!(view.name.get(), "");
+    Ok(())
+}
+
+#[derive(RootView)]
+pub struct NestedLayoutV0View<C> {
+    pub child: LayoutV0View<C>,
+    pub entries: ReentrantCollectionView<C, u8, LayoutV0View<C>>,
+}
+
+#[derive(RootView)]
+pub struct NestedLayoutV1View<C> {
+    pub child: LayoutV1View<C>,
+    pub entries: ReentrantCollectionView<C, u8, LayoutV1View<C>>,
+}
+
+#[tokio::test]
+async fn test_nested_view_layout_migration() -> Result<()> {
+    let context = MemoryContext::new_for_testing(());
+    {
+        let mut view = NestedLayoutV0View::load(context.clone()).await?;
+        view.child.count.set(5);
+        view.child.name.set("child".to_string());
+        for index in 1..=2u8 {
+            let mut entry = view.entries.try_load_entry_mut(&index).await?;
+            entry.count.set(u32::from(index));
+            entry.name.set(format!("entry {index}"));
+        }
+        view.save().await?;
+    }
+    // Building the view from its values alone does not migrate its fields.
+    let keys = NestedLayoutV1View::<MemoryContext<()>>::pre_load(&context)?;
+    let values = context.store().read_multi_values_bytes(keys).await?;
+    assert!(matches!(
+        NestedLayoutV1View::post_load(context.clone(), &values),
+        Err(ViewError::UnsupportedLayoutVersion {
+            stored: 0,
+            expected: 1
+        })
+    ));
+    {
+        let view = NestedLayoutV1View::load(context.clone()).await?;
+        assert_eq!(*view.child.count.get(), 5);
+        assert_eq!(view.child.name.get(), "child");
+        assert!(!view.has_pending_changes().await);
+        // The entries are read together, and migrated one by one.
+        let entries = view.entries.try_load_entries(&[1u8, 2]).await?;
+        for (index, entry) in (1..).zip(entries) {
+            let entry = entry.expect("the entry was stored");
+            assert_eq!(*entry.count.get(), index);
+            assert_eq!(*entry.name.get(), format!("entry {index}"));
+        }
+    }
+    // The migrations were saved, without the fields of the previous layout.
+    let view = NestedLayoutV1View::load(context.clone()).await?;
+    assert_eq!(*view.child.count.get(), 5);
+    let entries = view.entries.try_load_all_entries().await?;
+    assert_eq!(entries.len(), 2);
+    for (index, entry) in entries {
+        assert_eq!(*entry.count.get(), u64::from(index));
+        assert_eq!(*entry.name.get(), format!("entry {index}"));
+    }
+    let old_count = RegisterView::<_, u32>::load(field_context(view.child.context(), 0)?).await?;
+    assert_eq!(*old_count.get(), 0);
+    Ok(())
+}
+
 #[cfg(test)]
 async fn test_views_in_lru_memory_param(config: &TestConfig) -> Result<()> {
     tracing::warn!("Testing config {:?} with lru memory", config);
@@ -427,6 +427,7 @@ impl Application {
             .query_application(linera_execution::Query::User {
                 application_id: self.id,
                 bytes: query.as_bytes().to_vec(),
+                at_height: None,
             })
             .await?
         else {